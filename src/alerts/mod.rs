@@ -0,0 +1,427 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::{info, warn, error};
+
+use crate::error::codes;
+use crate::events::EngineEvent;
+
+pub mod webhook;
+
+pub use webhook::{WebhookEndpoint, WebhookKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "INFO"),
+            Severity::Warning => write!(f, "WARNING"),
+            Severity::Critical => write!(f, "CRITICAL"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    /// Alerts with the same key share a rate limit
+    pub key: String,
+    pub severity: Severity,
+    pub title: String,
+    pub message: String,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertConfig {
+    pub endpoints: Vec<WebhookEndpoint>,
+    /// Minimum interval between alerts with the same key
+    pub rate_limit: Duration,
+    /// Number of order rejects within `reject_spike_window` that raises an alert
+    pub reject_spike_threshold: usize,
+    pub reject_spike_window: Duration,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            rate_limit: Duration::from_secs(60),
+            reject_spike_threshold: 10,
+            reject_spike_window: Duration::from_secs(10),
+        }
+    }
+}
+
+impl AlertConfig {
+    /// Build endpoints from `ALERT_WEBHOOK_URL`, `ALERT_SLACK_WEBHOOK_URL` and
+    /// `ALERT_TELEGRAM_BOT_TOKEN`/`ALERT_TELEGRAM_CHAT_ID`.
+    ///
+    /// Returns `None` when no endpoint is configured.
+    pub fn from_env() -> Option<Self> {
+        let mut endpoints = Vec::new();
+
+        if let Ok(url) = std::env::var("ALERT_WEBHOOK_URL") {
+            endpoints.push(WebhookEndpoint {
+                url,
+                kind: WebhookKind::Generic,
+                min_severity: Severity::Info,
+            });
+        }
+
+        if let Ok(url) = std::env::var("ALERT_SLACK_WEBHOOK_URL") {
+            endpoints.push(WebhookEndpoint {
+                url,
+                kind: WebhookKind::Slack,
+                min_severity: Severity::Warning,
+            });
+        }
+
+        if let (Ok(token), Ok(chat_id)) = (
+            std::env::var("ALERT_TELEGRAM_BOT_TOKEN"),
+            std::env::var("ALERT_TELEGRAM_CHAT_ID"),
+        ) {
+            endpoints.push(WebhookEndpoint {
+                url: format!("https://api.telegram.org/bot{}/sendMessage", token),
+                kind: WebhookKind::Telegram { chat_id },
+                min_severity: Severity::Critical,
+            });
+        }
+
+        if endpoints.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            endpoints,
+            ..Default::default()
+        })
+    }
+}
+
+/// Turns engine events into rate-limited webhook notifications
+pub struct AlertManager {
+    config: AlertConfig,
+    client: reqwest::Client,
+    last_sent: HashMap<String, Instant>,
+    rejects: VecDeque<Instant>,
+}
+
+impl AlertManager {
+    pub fn new(config: AlertConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            last_sent: HashMap::new(),
+            rejects: VecDeque::new(),
+        }
+    }
+
+    /// Map an engine event to an alert, if it warrants one
+    pub fn classify(&mut self, event: &EngineEvent) -> Option<Alert> {
+        let (key, severity, title, message) = match event {
+            EngineEvent::VenueConnected { .. } | EngineEvent::OrderQueued { .. } => return None,
+            EngineEvent::ScheduledJob { ok: true, .. } => return None,
+            EngineEvent::Transfer { kind, .. } if kind == "funding" => return None,
+            EngineEvent::ScheduledJob { job, detail, .. } => (
+                format!("scheduled_job:{}", job),
+                Severity::Warning,
+                format!("Scheduled job {} failed", job),
+                detail.clone(),
+            ),
+            EngineEvent::OrderRerouted { symbol, from, to } => (
+                format!("reroute:{}", from),
+                Severity::Warning,
+                format!("Orders rerouted from {}", from),
+                format!("{} orders sent to {} while {} is down", symbol, to, from),
+            ),
+            EngineEvent::QueuedOrderExpired { venue, symbol } => (
+                format!("queue_expired:{}", venue),
+                Severity::Warning,
+                format!("Queued orders for {} expired", venue),
+                format!("{} order rejected after {} did not reconnect", symbol, venue),
+            ),
+            EngineEvent::PriceDeviation { venue, symbol, detail } => (
+                format!("price_deviation:{}:{}", venue, symbol),
+                Severity::Critical,
+                format!("Order on {} held: {} price deviates", venue, symbol),
+                detail.clone(),
+            ),
+            EngineEvent::MarginMismatch { venue, symbol, detail } => (
+                format!("margin:{}:{}", venue, symbol),
+                Severity::Critical,
+                format!("{} margin settings not applied on {}", symbol, venue),
+                detail.clone(),
+            ),
+            EngineEvent::LiquidationRisk { venue, symbol, distance_pct, reducing, detail } => (
+                format!("liquidation:{}:{}", venue, symbol),
+                if *reducing { Severity::Critical } else { Severity::Warning },
+                if *reducing {
+                    format!("Reducing {} on {}: {:.1}% from liquidation", symbol, venue, distance_pct)
+                } else {
+                    format!("{} on {} is {:.1}% from liquidation", symbol, venue, distance_pct)
+                },
+                detail.clone(),
+            ),
+            EngineEvent::VenueOutage { venue, action, detail } => (
+                format!("outage:{}:{}", venue, action),
+                if action == "flattening" { Severity::Warning } else { Severity::Critical },
+                match action.as_str() {
+                    "hedging" => format!("Hedging positions stranded on {}", venue),
+                    "flattening" => format!("Flattening positions on reconnected {}", venue),
+                    _ => format!("Positions stranded on {}", venue),
+                },
+                detail.clone(),
+            ),
+            EngineEvent::ContractExpiry { symbol, stage, detail } => (
+                format!("expiry:{}:{}", symbol, stage),
+                if stage == "rolling" { Severity::Warning } else { Severity::Info },
+                match stage.as_str() {
+                    "close_only" => format!("{} nearing expiry, closing only", symbol),
+                    "rolling" => format!("Rolling {} positions before expiry", symbol),
+                    _ => format!("{} expired and delisted", symbol),
+                },
+                detail.clone(),
+            ),
+            EngineEvent::DataQuality { venue, symbol, issue, detail } => (
+                format!("data_quality:{}:{}", venue, issue),
+                Severity::Warning,
+                format!("{} market data: {}", venue, issue),
+                format!("{} {}", symbol, detail),
+            ),
+            EngineEvent::QuoteStorm { venue, active, detail } => (
+                format!("quote_storm:{}", venue),
+                if *active { Severity::Warning } else { Severity::Info },
+                if *active {
+                    format!("Quote storm on {}, quotes conflated", venue)
+                } else {
+                    format!("Quote storm on {} over", venue)
+                },
+                detail.clone(),
+            ),
+            EngineEvent::VenueDisconnected { venue, reason } => (
+                format!("venue_disconnected:{}", venue),
+                Severity::Critical,
+                format!("{} disconnected", venue),
+                reason.clone(),
+            ),
+            EngineEvent::KillSwitchEngaged { reason } => (
+                "kill_switch".to_string(),
+                Severity::Critical,
+                "Kill switch engaged".to_string(),
+                reason.clone(),
+            ),
+            EngineEvent::TradingModeChanged { from, to, reason } => (
+                "trading_mode".to_string(),
+                match to.as_str() {
+                    "halted" => Severity::Critical,
+                    "reduce_only" => Severity::Warning,
+                    _ => Severity::Info,
+                },
+                format!("Trading mode {}", to),
+                format!("Changed from {}: {}", from, reason),
+            ),
+            EngineEvent::TradeBreak { venue, symbol, kind, detail } => (
+                format!("trade_break:{}:{}", venue, symbol),
+                Severity::Critical,
+                format!("{} {} fills on {}", symbol, kind, venue),
+                detail.clone(),
+            ),
+            EngineEvent::Transfer { venue, asset, kind, detail, .. } => (
+                format!("transfer:{}:{}", venue, asset),
+                if kind == "withdrawal" { Severity::Warning } else { Severity::Info },
+                format!("{} {} on {}", asset, kind, venue),
+                detail.clone(),
+            ),
+            EngineEvent::KillSwitchReleased => (
+                "kill_switch_released".to_string(),
+                Severity::Info,
+                "Kill switch released".to_string(),
+                "Trading re-enabled".to_string(),
+            ),
+            EngineEvent::RiskBreach { scope, detail } => (
+                format!("risk_breach:{}", scope),
+                Severity::Critical,
+                format!("Risk breach ({})", scope),
+                detail.clone(),
+            ),
+            EngineEvent::RoleChanged { node, role } => (
+                format!("role:{}", node),
+                Severity::Warning,
+                format!("{} is now {}", node, role),
+                format!("Engine instance {} changed role to {}", node, role),
+            ),
+            // Bad credentials fail every order to the venue until fixed
+            EngineEvent::OrderRejected { venue, symbol, reason, code: Some(codes::AUTHENTICATION_FAILED) } => (
+                format!("order_auth:{}", venue),
+                Severity::Critical,
+                format!("{} rejected the engine's credentials", venue),
+                format!("{} order rejected: {}", symbol, reason),
+            ),
+            EngineEvent::OrderRejected { venue, .. } => {
+                let now = Instant::now();
+                self.rejects.push_back(now);
+                while let Some(&oldest) = self.rejects.front() {
+                    if now.duration_since(oldest) > self.config.reject_spike_window {
+                        self.rejects.pop_front();
+                    } else {
+                        break;
+                    }
+                }
+
+                if self.rejects.len() < self.config.reject_spike_threshold {
+                    return None;
+                }
+
+                (
+                    "order_reject_spike".to_string(),
+                    Severity::Warning,
+                    "Order reject spike".to_string(),
+                    format!(
+                        "{} rejects in the last {:?}, latest from {}",
+                        self.rejects.len(),
+                        self.config.reject_spike_window,
+                        venue
+                    ),
+                )
+            }
+        };
+
+        Some(Alert {
+            key,
+            severity,
+            title,
+            message,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        })
+    }
+
+    /// Apply the per-key rate limit, recording the alert as sent if allowed
+    fn allow(&mut self, alert: &Alert) -> bool {
+        let now = Instant::now();
+        if let Some(last) = self.last_sent.get(&alert.key) {
+            if now.duration_since(*last) < self.config.rate_limit {
+                return false;
+            }
+        }
+        self.last_sent.insert(alert.key.clone(), now);
+        true
+    }
+
+    pub async fn dispatch(&self, alert: &Alert) {
+        for endpoint in self.config.endpoints.iter().filter(|e| e.accepts(alert)) {
+            let result = self.client
+                .post(&endpoint.url)
+                .json(&endpoint.payload(alert))
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+
+            if let Err(e) = result {
+                error!(endpoint = ?endpoint.kind, error = %e, "Failed to deliver alert");
+            }
+        }
+    }
+
+    pub async fn handle(&mut self, event: &EngineEvent) {
+        let Some(alert) = self.classify(event) else {
+            return;
+        };
+
+        if !self.allow(&alert) {
+            return;
+        }
+
+        info!(key = %alert.key, severity = %alert.severity, "Sending alert");
+        self.dispatch(&alert).await;
+    }
+
+    /// Consume events until the bus is closed
+    pub async fn run(mut self, mut events: broadcast::Receiver<EngineEvent>) {
+        loop {
+            match events.recv().await {
+                Ok(event) => self.handle(&event).await,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(skipped = skipped, "Alert manager lagged behind engine events");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_per_key() {
+        let mut manager = AlertManager::new(AlertConfig::default());
+        let event = EngineEvent::KillSwitchEngaged { reason: "loss".to_string() };
+
+        let alert = manager.classify(&event).unwrap();
+        assert_eq!(alert.severity, Severity::Critical);
+        assert!(manager.allow(&alert));
+        assert!(!manager.allow(&alert));
+
+        // A different key is not affected
+        let other = manager.classify(&EngineEvent::VenueDisconnected {
+            venue: "BINANCE_FUTURES".to_string(),
+            reason: "stream ended".to_string(),
+        }).unwrap();
+        assert!(manager.allow(&other));
+    }
+
+    #[test]
+    fn test_reject_spike_detection() {
+        let mut manager = AlertManager::new(AlertConfig {
+            reject_spike_threshold: 3,
+            ..Default::default()
+        });
+        let event = EngineEvent::OrderRejected {
+            venue: "MOCK".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            reason: "insufficient margin".to_string(),
+            code: None,
+        };
+
+        assert!(manager.classify(&event).is_none());
+        assert!(manager.classify(&event).is_none());
+
+        let alert = manager.classify(&event).unwrap();
+        assert_eq!(alert.key, "order_reject_spike");
+        assert_eq!(alert.severity, Severity::Warning);
+
+        // Credential failures alert on the first reject
+        let mut manager = AlertManager::new(AlertConfig::default());
+        let auth = EngineEvent::OrderRejected {
+            venue: "MOCK".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            reason: "invalid API key".to_string(),
+            code: Some(codes::AUTHENTICATION_FAILED),
+        };
+        let alert = manager.classify(&auth).unwrap();
+        assert_eq!((alert.key.as_str(), alert.severity), ("order_auth:MOCK", Severity::Critical));
+    }
+
+    #[test]
+    fn test_endpoint_severity_filter() {
+        let endpoint = WebhookEndpoint {
+            url: "http://localhost".to_string(),
+            kind: WebhookKind::Generic,
+            min_severity: Severity::Critical,
+        };
+
+        let mut manager = AlertManager::new(AlertConfig::default());
+        let released = manager.classify(&EngineEvent::KillSwitchReleased).unwrap();
+        assert!(!endpoint.accepts(&released));
+    }
+}
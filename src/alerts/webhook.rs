@@ -0,0 +1,80 @@
+use serde_json::{json, Value};
+
+use super::{Alert, Severity};
+
+/// Payload format expected by the receiving service
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebhookKind {
+    /// The alert serialized as JSON
+    Generic,
+    /// Slack incoming webhook
+    Slack,
+    /// Telegram Bot API `sendMessage`; the URL must include the bot token
+    Telegram { chat_id: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    pub kind: WebhookKind,
+    /// Alerts below this severity are not sent to this endpoint
+    pub min_severity: Severity,
+}
+
+impl WebhookEndpoint {
+    pub fn accepts(&self, alert: &Alert) -> bool {
+        alert.severity >= self.min_severity
+    }
+
+    pub fn payload(&self, alert: &Alert) -> Value {
+        let text = format!("[{}] {}: {}", alert.severity, alert.title, alert.message);
+
+        match &self.kind {
+            WebhookKind::Generic => json!(alert),
+            WebhookKind::Slack => json!({ "text": text }),
+            WebhookKind::Telegram { chat_id } => json!({
+                "chat_id": chat_id,
+                "text": text,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert() -> Alert {
+        Alert {
+            key: "kill_switch".to_string(),
+            severity: Severity::Critical,
+            title: "Kill switch engaged".to_string(),
+            message: "loss limit".to_string(),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_payload_formats() {
+        let slack = WebhookEndpoint {
+            url: "https://hooks.slack.com/services/x".to_string(),
+            kind: WebhookKind::Slack,
+            min_severity: Severity::Warning,
+        };
+        assert_eq!(slack.payload(&alert())["text"], "[CRITICAL] Kill switch engaged: loss limit");
+
+        let telegram = WebhookEndpoint {
+            url: "https://api.telegram.org/botTOKEN/sendMessage".to_string(),
+            kind: WebhookKind::Telegram { chat_id: "42".to_string() },
+            min_severity: Severity::Warning,
+        };
+        assert_eq!(telegram.payload(&alert())["chat_id"], "42");
+
+        let generic = WebhookEndpoint {
+            url: "http://localhost/alerts".to_string(),
+            kind: WebhookKind::Generic,
+            min_severity: Severity::Info,
+        };
+        assert_eq!(generic.payload(&alert())["severity"], "critical");
+    }
+}
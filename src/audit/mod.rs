@@ -0,0 +1,425 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::{error, warn};
+
+use crate::command::auth::AdminRole;
+use crate::error::HftError;
+use crate::types::{Fill, Order};
+
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const SYSLOG_SOCKET: &str = "/dev/log";
+
+/// `local0.info`
+const SYSLOG_PRIORITY: u8 = 134;
+
+/// How long records that failed to write wait before the next attempt
+const RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Compliance record of order flow, kept separate from debug logging
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEvent {
+    OrderRequest { strategy: Option<String>, order: Order },
+    RiskDecision { order: Order, accepted: bool, reason: Option<String> },
+    Ack { order_id: String, order: Order },
+    Reject { order: Order, reason: String },
+    Amend { order_id: String, venue: String, price: f64, quantity: f64 },
+    Cancel { venue: String, order_id: Option<String>, reason: String },
+    Fill(Fill),
+    /// A control action on the admin API, or a request refused for its
+    /// token; `principal` is the token's name when it was valid
+    ControlAction { principal: Option<String>, role: Option<AdminRole>, action: String, allowed: bool },
+    /// A control action under the two-man rule, held for a second
+    /// principal's approval
+    ApprovalRequested { principal: String, action: String, expires_in_secs: u64 },
+    /// A held control action approved by a second principal, who ran it
+    ApprovalGranted { requester: String, approver: String, action: String },
+}
+
+#[derive(Serialize)]
+struct AuditBody<'a> {
+    seq: u64,
+    /// Milliseconds since the Unix epoch, taken when the event was recorded
+    timestamp: u64,
+    event: &'a AuditEvent,
+}
+
+struct Entry {
+    timestamp: u64,
+    event: AuditEvent,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditConfig {
+    pub path: PathBuf,
+    /// Also forward each record to the local syslog daemon
+    pub syslog: bool,
+}
+
+impl AuditConfig {
+    /// Read `HFT_AUDIT_LOG` and `HFT_AUDIT_SYSLOG`; returns `None` when no
+    /// path is configured
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            path: PathBuf::from(std::env::var("HFT_AUDIT_LOG").ok()?),
+            syslog: std::env::var("HFT_AUDIT_SYSLOG").is_ok_and(|v| v == "1" || v == "true"),
+        })
+    }
+}
+
+fn checksum(prev: &str, body: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev.as_bytes());
+    hasher.update(body.as_bytes());
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Split a line into its checksum and JSON body
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    line.split_once(' ').filter(|(sum, _)| sum.len() == GENESIS.len())
+}
+
+fn body_seq(body: &str) -> Option<u64> {
+    serde_json::from_str::<serde_json::Value>(body).ok()?.get("seq")?.as_u64()
+}
+
+/// Handle for recording audit events.
+///
+/// Records are written by a dedicated thread to an append-only file, one
+/// per line as `<sha256> <json>`. Each checksum covers the previous
+/// checksum and the record, so editing or removing a line breaks the chain
+/// from that point on; see [`verify`]. Recording never blocks and never
+/// drops events: records that fail to write are held and retried, in
+/// order, ahead of later ones.
+#[derive(Clone)]
+pub struct AuditLog {
+    tx: mpsc::Sender<Entry>,
+}
+
+impl AuditLog {
+    /// Open or continue the log at `config.path`
+    pub fn open(config: &AuditConfig) -> Result<Self, HftError> {
+        if let Some(parent) = config.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let (mut seq, mut prev) = (0, GENESIS.to_string());
+        if config.path.exists() {
+            let last = BufReader::new(File::open(&config.path)?)
+                .lines()
+                .map_while(Result::ok)
+                .filter(|l| !l.is_empty())
+                .last();
+            if let Some(line) = last {
+                let (sum, body) = parse_line(&line)
+                    .ok_or_else(|| HftError::Config(format!("Corrupt audit log {}", config.path.display())))?;
+                seq = body_seq(body)
+                    .ok_or_else(|| HftError::Config(format!("Corrupt audit log {}", config.path.display())))?;
+                prev = sum.to_string();
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&config.path)?;
+        let syslog = config.syslog.then(|| {
+            UnixDatagram::unbound()
+                .and_then(|socket| socket.connect(SYSLOG_SOCKET).map(|_| socket))
+                .map_err(|e| warn!(error = %e, "Syslog unavailable, audit records go to file only"))
+                .ok()
+        }).flatten();
+
+        let (tx, rx) = mpsc::channel();
+        let writer = AuditWriter { file, syslog, seq, prev };
+        std::thread::Builder::new()
+            .name("audit-writer".to_string())
+            .spawn(move || writer.run(rx))?;
+
+        Ok(Self { tx })
+    }
+
+    pub fn record(&self, event: AuditEvent) {
+        let entry = Entry {
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+            event,
+        };
+        if self.tx.send(entry).is_err() {
+            error!("Audit writer stopped, record lost");
+        }
+    }
+}
+
+/// The file records are appended to
+trait AuditFile: Write {
+    /// Flush written records to disk
+    fn sync(&mut self) -> std::io::Result<()>;
+    fn len(&self) -> std::io::Result<u64>;
+    /// Cut the file back to `len` bytes
+    fn truncate(&mut self, len: u64) -> std::io::Result<()>;
+}
+
+impl AuditFile for File {
+    fn sync(&mut self) -> std::io::Result<()> {
+        self.sync_data()
+    }
+
+    fn len(&self) -> std::io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    fn truncate(&mut self, len: u64) -> std::io::Result<()> {
+        self.set_len(len)
+    }
+}
+
+struct AuditWriter<F = File> {
+    file: F,
+    syslog: Option<UnixDatagram>,
+    seq: u64,
+    prev: String,
+}
+
+impl<F: AuditFile> AuditWriter<F> {
+    fn run(mut self, rx: mpsc::Receiver<Entry>) {
+        // Records not yet written, oldest first
+        let mut pending = Vec::new();
+        loop {
+            let next = if pending.is_empty() {
+                rx.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected)
+            } else {
+                rx.recv_timeout(RETRY_INTERVAL)
+            };
+            let closed = match next {
+                Ok(entry) => {
+                    pending.push(entry);
+                    false
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => false,
+                Err(mpsc::RecvTimeoutError::Disconnected) => true,
+            };
+            pending.extend(rx.try_iter());
+
+            if !pending.is_empty() {
+                match self.write(&pending) {
+                    Ok(()) => pending.clear(),
+                    Err(e) if closed => error!(error = %e, records = pending.len(), "Failed to write audit records on shutdown, records lost"),
+                    Err(e) => error!(error = %e, records = pending.len(), "Failed to write audit records, retrying"),
+                }
+            }
+            if closed {
+                break;
+            }
+        }
+    }
+
+    /// Append `batch` to the chain. The sequence number and checksum only
+    /// move on once the records are on disk, so a failed batch can be
+    /// written again as it was.
+    fn write(&mut self, batch: &[Entry]) -> std::io::Result<()> {
+        let (mut seq, mut prev) = (self.seq, self.prev.clone());
+        let mut out = String::new();
+        let mut bodies = Vec::with_capacity(batch.len());
+        for entry in batch {
+            seq += 1;
+            let body = serde_json::to_string(&AuditBody {
+                seq,
+                timestamp: entry.timestamp,
+                event: &entry.event,
+            })?;
+            let sum = checksum(&prev, &body);
+
+            out.push_str(&sum);
+            out.push(' ');
+            out.push_str(&body);
+            out.push('\n');
+            prev = sum;
+            bodies.push(body);
+        }
+
+        let len = self.file.len()?;
+        if let Err(e) = self.file.write_all(out.as_bytes()).and_then(|_| self.file.sync()) {
+            // A partly written record would break the chain under the retry
+            if let Err(e) = self.file.truncate(len) {
+                error!(error = %e, "Failed to remove a partly written audit record");
+            }
+            return Err(e);
+        }
+        self.seq = seq;
+        self.prev = prev;
+
+        if let Some(socket) = &self.syslog {
+            for body in &bodies {
+                let _ = socket.send(format!("<{}>hft-engine-audit: {}", SYSLOG_PRIORITY, body).as_bytes());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check the checksum chain of an audit log, returning the number of records
+pub fn verify(path: &Path) -> Result<u64, HftError> {
+    let mut prev = GENESIS.to_string();
+    let mut count = 0;
+
+    for (i, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        let corrupt = || HftError::Config(format!("Audit log {} fails verification at line {}", path.display(), i + 1));
+
+        let (sum, body) = parse_line(&line).ok_or_else(corrupt)?;
+        if checksum(&prev, body) != sum || body_seq(body) != Some(count + 1) {
+            return Err(corrupt());
+        }
+        prev = sum.to_string();
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// The fills recorded in the log at `path`, with their sequence numbers
+pub fn read_fills(path: &Path) -> Result<Vec<(u64, Fill)>, HftError> {
+    let mut fills = Vec::new();
+    for (i, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if line.is_empty() {
+            continue;
+        }
+        let corrupt = || HftError::Config(format!("Corrupt audit log {} at line {}", path.display(), i + 1));
+        let (_, body) = parse_line(&line).ok_or_else(corrupt)?;
+        let mut body: serde_json::Value = serde_json::from_str(body).map_err(|_| corrupt())?;
+        if body["event"]["type"] != "fill" {
+            continue;
+        }
+        let seq = body["seq"].as_u64().ok_or_else(corrupt)?;
+        let fill = serde_json::from_value(body["event"].take()).map_err(|_| corrupt())?;
+        fills.push((seq, fill));
+    }
+    Ok(fills)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderSide;
+
+    fn fill(quantity: f64) -> AuditEvent {
+        AuditEvent::Fill(Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "MOCK".into(),
+            strategy: "mm".to_string(),
+            side: OrderSide::Buy,
+            quantity,
+            price: 50000.0,
+            timestamp: 1,
+            commission: None,
+        })
+    }
+
+    async fn wait_for_lines(path: &Path, n: usize) {
+        for _ in 0..100 {
+            if std::fs::read_to_string(path).map(|s| s.lines().count()).unwrap_or(0) >= n {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("Audit records were not written");
+    }
+
+    #[tokio::test]
+    async fn test_chain_survives_reopen_and_detects_tampering() {
+        let dir = std::env::temp_dir().join(format!("hft_audit_{}", std::process::id()));
+        let config = AuditConfig { path: dir.join("audit.log"), syslog: false };
+
+        let log = AuditLog::open(&config).unwrap();
+        log.record(fill(1.0));
+        log.record(AuditEvent::Cancel { venue: "MOCK".to_string(), order_id: None, reason: "test".to_string() });
+        wait_for_lines(&config.path, 2).await;
+
+        // A restarted engine continues the same chain
+        let log = AuditLog::open(&config).unwrap();
+        log.record(fill(2.0));
+        wait_for_lines(&config.path, 3).await;
+        assert_eq!(verify(&config.path).unwrap(), 3);
+        let fills: Vec<_> = read_fills(&config.path).unwrap().into_iter().map(|(seq, f)| (seq, f.quantity)).collect();
+        assert_eq!(fills, vec![(1, 1.0), (3, 2.0)]);
+
+        let contents = std::fs::read_to_string(&config.path).unwrap();
+        std::fs::write(&config.path, contents.replacen("\"quantity\":2.0", "\"quantity\":20.0", 1)).unwrap();
+        assert!(verify(&config.path).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Fails its first `failures` writes after writing half the buffer
+    struct FlakyFile {
+        file: File,
+        failures: usize,
+    }
+
+    impl Write for FlakyFile {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                self.file.write_all(&buf[..buf.len() / 2])?;
+                return Err(std::io::Error::other("disk full"));
+            }
+            self.file.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.file.flush()
+        }
+    }
+
+    impl AuditFile for FlakyFile {
+        fn sync(&mut self) -> std::io::Result<()> {
+            self.file.sync()
+        }
+
+        fn len(&self) -> std::io::Result<u64> {
+            AuditFile::len(&self.file)
+        }
+
+        fn truncate(&mut self, len: u64) -> std::io::Result<()> {
+            self.file.truncate(len)
+        }
+    }
+
+    #[test]
+    fn test_failed_writes_are_retried_without_breaking_the_chain() {
+        let dir = std::env::temp_dir().join(format!("hft_audit_flaky_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.log");
+        let file = OpenOptions::new().create(true).append(true).open(&path).unwrap();
+        let mut writer = AuditWriter { file: FlakyFile { file, failures: 1 }, syslog: None, seq: 0, prev: GENESIS.to_string() };
+        let entry = |quantity| Entry { timestamp: 1, event: fill(quantity) };
+
+        // The failed batch leaves nothing behind and the chain where it was
+        assert!(writer.write(&[entry(1.0), entry(2.0)]).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        assert_eq!((writer.seq, writer.prev.as_str()), (0, GENESIS));
+
+        // Records held by the writer go out ahead of later ones
+        writer.file.failures = 1;
+        let (tx, rx) = mpsc::channel();
+        tx.send(entry(1.0)).unwrap();
+        tx.send(entry(2.0)).unwrap();
+        let handle = std::thread::spawn(move || writer.run(rx));
+        std::thread::sleep(RETRY_INTERVAL / 2);
+        tx.send(entry(3.0)).unwrap();
+        drop(tx);
+        handle.join().unwrap();
+
+        assert_eq!(verify(&path).unwrap(), 3);
+        let fills: Vec<_> = read_fills(&path).unwrap().into_iter().map(|(seq, f)| (seq, f.quantity)).collect();
+        assert_eq!(fills, vec![(1, 1.0), (2, 2.0), (3, 3.0)]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,351 @@
+//! How the backtester turns orders into fills.
+//!
+//! A [`FillModel`] decides how long orders take to reach the simulated
+//! venue, whether and at what price they fill, how much of them fills, and
+//! what each fill costs in fees. [`TopOfBook`] fills against the quote
+//! alone; [`QueuePosition`] also makes passive orders wait behind the size
+//! displayed at their price.
+
+use std::collections::HashMap;
+use rand::Rng;
+
+use crate::rng::{component_rng, SimRng};
+use crate::types::{Order, OrderSide, OrderType, Quote};
+
+pub use crate::fees::{Fees, Liquidity};
+
+/// A simulated fill of some or all of an order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Execution {
+    pub price: f64,
+    pub quantity: f64,
+    pub liquidity: Liquidity,
+}
+
+/// Simulates a venue's matching for the backtester.
+///
+/// Orders are identified by their backtest order ID. A limit order that
+/// does not fill completely on arrival rests until it does or the strategy
+/// replaces it; a market order's unfilled quantity is dropped.
+pub trait FillModel: Send {
+    /// Delay between the strategy sending `order` and the venue acting on
+    /// it, in quote timestamp units (milliseconds for recordings)
+    fn latency(&mut self, _order: &Order) -> u64 {
+        0
+    }
+
+    /// `order` reaches the venue while `quote` is its book's top; returns
+    /// the immediate fill, if any
+    fn on_arrival(&mut self, id: &str, order: &Order, quote: &Quote) -> Option<Execution>;
+
+    /// A new quote on the book of a resting order with `remaining`
+    /// quantity unfilled
+    fn on_quote(&mut self, id: &str, order: &Order, remaining: f64, quote: &Quote) -> Option<Execution>;
+
+    /// A resting order filled completely or was replaced
+    fn on_done(&mut self, _id: &str) {}
+
+    /// Reseed the model's random draws before a run, so runs with the same
+    /// seed fill alike
+    fn seed(&mut self, _seed: u64) {}
+
+    /// Fee charged for `execution` of `order`, in the quote currency
+    fn fee(&self, _order: &Order, _execution: &Execution) -> f64 {
+        0.0
+    }
+}
+
+/// Whether `quote` trades through a limit order's price
+fn crosses(order: &Order, quote: &Quote) -> bool {
+    match order.side {
+        OrderSide::Buy => quote.ask <= order.price,
+        OrderSide::Sell => quote.bid >= order.price,
+    }
+}
+
+/// The whole order filled at the touch, taking liquidity
+fn take(order: &Order, quote: &Quote) -> Execution {
+    let price = match order.side {
+        OrderSide::Buy => quote.ask,
+        OrderSide::Sell => quote.bid,
+    };
+    Execution { price, quantity: order.quantity, liquidity: Liquidity::Taker }
+}
+
+fn marketable(order: &Order, quote: &Quote) -> bool {
+    order.order_type == OrderType::Market || crosses(order, quote)
+}
+
+/// A fixed delay plus up to `jitter` more, drawn uniformly
+#[derive(Debug, Clone)]
+struct Latency {
+    base: u64,
+    jitter: u64,
+    rng: SimRng,
+}
+
+impl Default for Latency {
+    fn default() -> Self {
+        Self { base: 0, jitter: 0, rng: component_rng(0, "fill_model") }
+    }
+}
+
+impl Latency {
+    fn sample(&mut self) -> u64 {
+        match self.jitter {
+            0 => self.base,
+            jitter => self.base + self.rng.random_range(0..=jitter),
+        }
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = component_rng(seed, "fill_model");
+    }
+}
+
+/// Naive fills against the top of book: marketable orders fill completely
+/// at the quote, resting limit orders fill completely at their price once
+/// a quote trades through it. Queue position and displayed size are
+/// ignored, so results are optimistic for passive strategies.
+#[derive(Debug, Clone, Default)]
+pub struct TopOfBook {
+    latency: Latency,
+    fees: Fees,
+}
+
+impl TopOfBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_latency(mut self, latency: u64) -> Self {
+        self.latency.base = latency;
+        self
+    }
+
+    /// Delay each order by up to `jitter` more than the fixed latency
+    pub fn with_jitter(mut self, jitter: u64) -> Self {
+        self.latency.jitter = jitter;
+        self
+    }
+
+    pub fn with_fees(mut self, fees: Fees) -> Self {
+        self.fees = fees;
+        self
+    }
+}
+
+impl FillModel for TopOfBook {
+    fn latency(&mut self, _order: &Order) -> u64 {
+        self.latency.sample()
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.latency.seed(seed);
+    }
+
+    fn on_arrival(&mut self, _id: &str, order: &Order, quote: &Quote) -> Option<Execution> {
+        marketable(order, quote).then(|| take(order, quote))
+    }
+
+    fn on_quote(&mut self, _id: &str, order: &Order, remaining: f64, quote: &Quote) -> Option<Execution> {
+        crosses(order, quote).then_some(Execution { price: order.price, quantity: remaining, liquidity: Liquidity::Maker })
+    }
+
+    fn fee(&self, _order: &Order, execution: &Execution) -> f64 {
+        self.fees.fee(execution.price * execution.quantity, execution.liquidity)
+    }
+}
+
+/// Fills passive orders only once the size ahead of them has traded.
+///
+/// An order joining the best price queues behind the size displayed there;
+/// one improving it is first in line; one behind it joins the back of its
+/// level when that becomes the best price. Displayed size shrinking at the
+/// order's level moves it up. Each quote on the other side at the order's
+/// price is taken as that much volume trading there, first against the
+/// queue ahead, then the order. A quote through the order's price fills
+/// what is left. Marketable orders fill as in [`TopOfBook`].
+#[derive(Debug, Clone, Default)]
+pub struct QueuePosition {
+    latency: Latency,
+    fees: Fees,
+    /// Quantity ahead of each resting order, once it is at the best price
+    ahead: HashMap<String, Option<f64>>,
+}
+
+impl QueuePosition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_latency(mut self, latency: u64) -> Self {
+        self.latency.base = latency;
+        self
+    }
+
+    /// Delay each order by up to `jitter` more than the fixed latency
+    pub fn with_jitter(mut self, jitter: u64) -> Self {
+        self.latency.jitter = jitter;
+        self
+    }
+
+    pub fn with_fees(mut self, fees: Fees) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Best price and size on the order's side, and on the other side
+    fn levels(order: &Order, quote: &Quote) -> ((f64, f64), (f64, f64)) {
+        match order.side {
+            OrderSide::Buy => ((quote.bid, quote.bid_size), (quote.ask, quote.ask_size)),
+            OrderSide::Sell => ((quote.ask, quote.ask_size), (quote.bid, quote.bid_size)),
+        }
+    }
+
+    /// Whether `price` is better than `than` for the order's side
+    fn better(order: &Order, price: f64, than: f64) -> bool {
+        match order.side {
+            OrderSide::Buy => price > than,
+            OrderSide::Sell => price < than,
+        }
+    }
+
+    /// Update the queue ahead of a resting order for a quote that does not
+    /// trade through it
+    fn requeue(order: &Order, ahead: Option<f64>, quote: &Quote) -> Option<f64> {
+        let ((best, size), _) = Self::levels(order, quote);
+        if best == order.price {
+            Some(ahead.map_or(size, |ahead| ahead.min(size)))
+        } else if Self::better(order, order.price, best) {
+            // The level in front emptied, the order would be the best price
+            Some(0.0)
+        } else {
+            ahead
+        }
+    }
+}
+
+impl FillModel for QueuePosition {
+    fn latency(&mut self, _order: &Order) -> u64 {
+        self.latency.sample()
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.latency.seed(seed);
+    }
+
+    fn on_arrival(&mut self, id: &str, order: &Order, quote: &Quote) -> Option<Execution> {
+        if marketable(order, quote) {
+            return Some(take(order, quote));
+        }
+        if order.order_type == OrderType::Limit {
+            self.ahead.insert(id.to_string(), Self::requeue(order, None, quote));
+        }
+        None
+    }
+
+    fn on_quote(&mut self, id: &str, order: &Order, remaining: f64, quote: &Quote) -> Option<Execution> {
+        let ahead = self.ahead.get(id).copied().flatten();
+        let (_, (other, other_size)) = Self::levels(order, quote);
+        let fill = |quantity: f64| (quantity > 0.0).then_some(Execution {
+            price: order.price,
+            quantity: quantity.min(remaining),
+            liquidity: Liquidity::Maker,
+        });
+
+        if Self::better(order, order.price, other) {
+            // Traded through: everything at the order's price is gone
+            return fill(remaining);
+        }
+        if other != order.price {
+            let requeued = Self::requeue(order, ahead, quote);
+            self.ahead.insert(id.to_string(), requeued);
+            return None;
+        }
+        // Volume trading at the order's price works through the queue
+        let ahead = ahead.unwrap_or(0.0);
+        let consumed = ahead.min(other_size);
+        self.ahead.insert(id.to_string(), Some(ahead - consumed));
+        fill(other_size - consumed)
+    }
+
+    fn on_done(&mut self, id: &str) {
+        self.ahead.remove(id);
+    }
+
+    fn fee(&self, _order: &Order, execution: &Execution) -> f64 {
+        self.fees.fee(execution.price * execution.quantity, execution.liquidity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(bid: f64, bid_size: f64, ask: f64, ask_size: f64) -> Quote {
+        Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask,
+            bid_size,
+            ask_size,
+            venue: "SIM".into(),
+            timestamp: 0,
+        }
+    }
+
+    fn bid(price: f64, quantity: f64) -> Order {
+        Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity,
+            price,
+            venue: "SIM".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        }
+    }
+
+    #[test]
+    fn test_queue_position_waits_behind_displayed_size() {
+        let mut model = QueuePosition::new();
+        let order = bid(100.0, 2.0);
+        // Joins 5 already bid at 100
+        assert_eq!(model.on_arrival("1", &order, &quote(100.0, 5.0, 101.0, 1.0)), None);
+        // 3 of them cancel
+        assert_eq!(model.on_quote("1", &order, 2.0, &quote(100.0, 2.0, 101.0, 1.0)), None);
+        // 3 offered at 100 fill the 2 ahead, then 1 of the order
+        let fill = model.on_quote("1", &order, 2.0, &quote(99.0, 1.0, 100.0, 3.0)).unwrap();
+        assert_eq!((fill.price, fill.quantity, fill.liquidity), (100.0, 1.0, Liquidity::Maker));
+        // Trading through fills the rest
+        assert_eq!(model.on_quote("1", &order, 1.0, &quote(98.0, 1.0, 99.0, 1.0)).unwrap().quantity, 1.0);
+
+        // The naive model fills as soon as the price is reached
+        let mut naive = TopOfBook::new();
+        assert_eq!(naive.on_arrival("2", &order, &quote(100.0, 5.0, 101.0, 1.0)), None);
+        assert_eq!(naive.on_quote("2", &order, 2.0, &quote(99.0, 1.0, 100.0, 1.0)).unwrap().quantity, 2.0);
+    }
+
+    #[test]
+    fn test_queue_position_behind_the_touch() {
+        let mut model = QueuePosition::new();
+        let order = bid(99.0, 1.0);
+        assert_eq!(model.on_arrival("1", &order, &quote(100.0, 5.0, 101.0, 1.0)), None);
+        // 99 becomes the best bid with 4 displayed, which trade first
+        assert_eq!(model.on_quote("1", &order, 1.0, &quote(99.0, 4.0, 100.0, 1.0)), None);
+        assert_eq!(model.on_quote("1", &order, 1.0, &quote(98.0, 1.0, 99.0, 4.0)), None);
+        assert_eq!(model.on_quote("1", &order, 1.0, &quote(98.0, 1.0, 99.0, 2.0)).unwrap().quantity, 1.0);
+    }
+
+    #[test]
+    fn test_fees() {
+        let fees = Fees { maker_bps: -1.0, taker_bps: 5.0 };
+        let model = TopOfBook::new().with_fees(fees);
+        let taker = Execution { price: 100.0, quantity: 2.0, liquidity: Liquidity::Taker };
+        assert_eq!(model.fee(&bid(100.0, 2.0), &taker), 0.1);
+        assert_eq!(fees.fee(taker.price * taker.quantity, Liquidity::Maker), -0.02);
+    }
+}
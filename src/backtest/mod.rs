@@ -0,0 +1,559 @@
+//! Run strategies over recorded quotes with simulated fills.
+//!
+//! Fills are simulated against the top of book by a [`FillModel`], by
+//! default [`TopOfBook`]: an order that crosses the current quote fills
+//! immediately at the quote, a limit order that does not rests until a
+//! later quote trades through its price. A strategy's new limit order on a
+//! symbol and side replaces the one it had resting there, as a requote
+//! would on a venue. Orders delayed by the model's latency reach the venue
+//! with the first quote at or after their arrival time, against the book as
+//! it stood before that quote.
+//!
+//! Besides [`Backtest::run`] over a recording, a backtest can be stepped
+//! with live events, as shadow strategies are.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+use crate::book::{read_depth, DepthReplay};
+use crate::error::HftError;
+use crate::risk::{Position, PositionKey};
+use crate::signals::Signals;
+use crate::strategy::StrategyPlugin;
+use crate::feed::BookUpdate;
+use crate::types::{Fill, Order, OrderType, Quote, Symbol, Trade, VenueId};
+use crate::venues::{binance, frames};
+
+pub mod fill_model;
+pub mod optimize;
+pub use fill_model::{Execution, Fees, FillModel, Liquidity, QueuePosition, TopOfBook};
+
+/// Unfilled quantities below this are treated as filled
+const QUANTITY_EPSILON: f64 = 1e-12;
+
+/// Quotes loaded from a recording
+#[derive(Debug)]
+pub struct LoadedQuotes {
+    pub quotes: Vec<Quote>,
+    /// Frames for the venue that did not parse as quotes; always 0 for
+    /// depth recordings
+    pub rejected: usize,
+}
+
+/// Load the `bookTicker` quotes `venue` sent in a recording made with
+/// `HFT_RECORD_FRAMES`
+pub fn load_quotes(path: &Path, venue: &str) -> Result<LoadedQuotes, HftError> {
+    let recording = frames::read_frames(path)?;
+    let replayed = frames::replay(&recording, venue, binance::parse_book_ticker);
+    Ok(LoadedQuotes { quotes: replayed.parsed, rejected: replayed.rejected.len() })
+}
+
+/// Top-of-book quotes from a depth recording made with `HFT_RECORD_DEPTH`,
+/// one each time the best bid or offer `venue` had for a symbol changed,
+/// timestamped when it was recorded. Books with an empty side give none.
+pub fn load_depth_quotes(path: &Path, venue: &str) -> Result<LoadedQuotes, HftError> {
+    let mut replay = DepthReplay::new();
+    let mut tops: HashMap<Symbol, Quote> = HashMap::new();
+    let mut quotes = Vec::new();
+    for record in read_depth(path)?.iter().filter(|r| r.event.venue() == venue) {
+        let book = replay.apply(record);
+        let (Some((bid, bid_size)), Some((ask, ask_size))) = (book.best_bid(), book.best_ask()) else {
+            continue;
+        };
+        let quote = Quote {
+            symbol: book.symbol().into(),
+            bid,
+            ask,
+            bid_size,
+            ask_size,
+            venue: venue.into(),
+            timestamp: record.received_at,
+        };
+        let changed = tops.get(&quote.symbol).is_none_or(|top| {
+            (top.bid, top.bid_size, top.ask, top.ask_size) != (bid, bid_size, ask, ask_size)
+        });
+        if changed {
+            tops.insert(quote.symbol, quote.clone());
+            quotes.push(quote);
+        }
+    }
+    Ok(LoadedQuotes { quotes, rejected: 0 })
+}
+
+/// Outcome of a backtest
+#[derive(Debug, Clone, Default)]
+pub struct BacktestReport {
+    pub quotes: usize,
+    pub orders: usize,
+    pub fills: usize,
+    /// Filled quantity times price, summed over all fills
+    pub notional: f64,
+    /// Fees charged by the fill model, in the quote currency
+    pub fees: f64,
+    /// Final position per strategy, venue and symbol
+    pub positions: Vec<(PositionKey, Position)>,
+    /// Last mid per venue and symbol, which open positions are marked at
+    pub marks: HashMap<(String, String), f64>,
+}
+
+impl BacktestReport {
+    fn mark(&self, key: &PositionKey) -> f64 {
+        self.marks.get(&(key.venue.clone(), key.symbol.clone())).copied().unwrap_or(0.0)
+    }
+
+    /// Realized plus unrealized PnL across all positions, before fees
+    pub fn total_pnl(&self) -> f64 {
+        self.positions.iter().map(|(key, p)| p.realized_pnl + p.unrealized_pnl(self.mark(key))).sum()
+    }
+
+    /// Total PnL after fees
+    pub fn net_pnl(&self) -> f64 {
+        self.total_pnl() - self.fees
+    }
+}
+
+impl fmt::Display for BacktestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} quotes, {} orders, {} fills, {:.2} notional", self.quotes, self.orders, self.fills, self.notional)?;
+        for (key, position) in &self.positions {
+            writeln!(
+                f,
+                "{} {} {}: position {} realized {:.2} unrealized {:.2}",
+                key.strategy, key.venue, key.symbol,
+                position.quantity, position.realized_pnl, position.unrealized_pnl(self.mark(key)),
+            )?;
+        }
+        write!(f, "Total PnL {:.2}, fees {:.2}, net {:.2}", self.total_pnl(), self.fees, self.net_pnl())
+    }
+}
+
+/// A limit order waiting for the market to reach it
+struct Resting {
+    id: String,
+    plugin: usize,
+    order: Order,
+    remaining: f64,
+}
+
+/// An order on its way to the venue
+struct Pending {
+    id: String,
+    plugin: usize,
+    order: Order,
+    arrives_at: u64,
+}
+
+/// Replays quotes to strategy plugins and fills their orders against the
+/// quotes
+pub struct Backtest {
+    plugins: Vec<Box<dyn StrategyPlugin>>,
+    signals: Signals,
+    fill_model: Box<dyn FillModel>,
+    /// Latest quote per venue and symbol
+    books: HashMap<(VenueId, Symbol), Quote>,
+    pending: Vec<Pending>,
+    resting: Vec<Resting>,
+    positions: BTreeMap<(String, String, String), Position>,
+    report: BacktestReport,
+    next_id: u64,
+    seed: u64,
+    /// Whether the fill model was seeded, on the first event
+    seeded: bool,
+    /// Timestamp of the latest quote, when timers fire
+    now: u64,
+}
+
+impl Default for Backtest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Backtest {
+    pub fn new() -> Self {
+        Self {
+            plugins: Vec::new(),
+            signals: Signals::default(),
+            fill_model: Box::new(TopOfBook::new()),
+            books: HashMap::new(),
+            pending: Vec::new(),
+            resting: Vec::new(),
+            positions: BTreeMap::new(),
+            report: BacktestReport::default(),
+            next_id: 0,
+            seed: 0,
+            seeded: false,
+            now: 0,
+        }
+    }
+
+    pub fn with_strategy(mut self, mut plugin: Box<dyn StrategyPlugin>) -> Self {
+        plugin.attach_signals(&self.signals);
+        self.plugins.push(plugin);
+        self
+    }
+
+    /// Simulate latency, fills and fees with `fill_model` instead of
+    /// [`TopOfBook`]
+    pub fn with_fill_model(mut self, fill_model: Box<dyn FillModel>) -> Self {
+        self.fill_model = fill_model;
+        self
+    }
+
+    /// Seed the fill model's random draws, 0 by default, so a run can be
+    /// repeated exactly
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Feed `quotes` to every strategy in order and report the result
+    pub fn run(mut self, quotes: impl IntoIterator<Item = Quote>) -> BacktestReport {
+        for quote in quotes {
+            self.on_quote(quote);
+        }
+        self.report()
+    }
+
+    fn start(&mut self) {
+        if !self.seeded {
+            self.fill_model.seed(self.seed);
+            self.seeded = true;
+        }
+    }
+
+    /// Step the backtest with the next quote: fill orders it reaches, then
+    /// pass it to every strategy
+    pub fn on_quote(&mut self, quote: Quote) {
+        self.start();
+        self.now = quote.timestamp;
+        self.report.quotes += 1;
+        self.report.marks.insert((quote.venue.to_string(), quote.symbol.to_string()), (quote.bid + quote.ask) / 2.0);
+        self.signals.order_flow.on_quote(&quote);
+        self.signals.toxicity.on_quote(&quote);
+        self.signals.greeks.on_quote(&quote);
+
+        // Orders arriving before this quote meet the book it replaces
+        let (arrived, pending) = std::mem::take(&mut self.pending).into_iter()
+            .partition(|p| p.arrives_at <= quote.timestamp);
+        self.pending = pending;
+        for pending in arrived {
+            self.arrive(pending.plugin, pending.id, pending.order, quote.timestamp);
+        }
+        self.books.insert((quote.venue, quote.symbol), quote.clone());
+
+        // Resting orders see the new quote before strategies react to it
+        let resting = std::mem::take(&mut self.resting);
+        for mut resting in resting {
+            let same_book = resting.order.venue == quote.venue && resting.order.symbol == quote.symbol;
+            let execution = same_book
+                .then(|| self.fill_model.on_quote(&resting.id, &resting.order, resting.remaining, &quote))
+                .flatten();
+            match execution {
+                Some(execution) => {
+                    let (plugin, id, order) = (resting.plugin, resting.id.clone(), resting.order.clone());
+                    resting.remaining -= execution.quantity;
+                    if resting.remaining > QUANTITY_EPSILON {
+                        self.resting.push(resting);
+                    } else {
+                        self.fill_model.on_done(&id);
+                    }
+                    self.fill(plugin, id, &order, execution, quote.timestamp);
+                }
+                None => self.resting.push(resting),
+            }
+        }
+
+        for plugin in 0..self.plugins.len() {
+            let orders = self.plugins[plugin].on_quote(&quote);
+            self.submit(plugin, orders, quote.timestamp);
+        }
+    }
+
+    /// Keep the strategies' candles, queue position and toxicity estimates
+    /// current with the trade stream
+    pub fn on_trade(&mut self, trade: &Trade) {
+        self.signals.candles.on_trade(trade);
+        self.signals.queue.on_trade(trade);
+        self.signals.toxicity.on_trade(trade);
+    }
+
+    pub fn on_book_update(&mut self, update: &BookUpdate) {
+        self.signals.queue.on_book_update(update);
+    }
+
+    /// Fire `plugin`'s `timer`, at the latest quote's timestamp
+    pub fn on_timer(&mut self, plugin: &str, timer: &str) {
+        self.start();
+        if let Some(i) = self.plugins.iter().position(|p| p.name() == plugin) {
+            let orders = self.plugins[i].on_timer(timer);
+            self.submit(i, orders, self.now);
+        }
+    }
+
+    /// Every strategy's timers as `(plugin, timer, period)`
+    pub fn timers(&self) -> Vec<(String, String, std::time::Duration)> {
+        self.plugins.iter()
+            .flat_map(|plugin| plugin.timers().into_iter().map(move |(timer, period)| (plugin.name().to_string(), timer, period)))
+            .collect()
+    }
+
+    /// The result so far
+    pub fn report(&self) -> BacktestReport {
+        let mut report = self.report.clone();
+        report.positions = self.positions.iter()
+            .map(|((strategy, venue, symbol), position)| (PositionKey { strategy: strategy.clone(), venue: venue.clone(), symbol: symbol.clone() }, position.clone()))
+            .collect();
+        report
+    }
+
+    fn submit(&mut self, plugin: usize, orders: Vec<Order>, now: u64) {
+        for mut order in orders {
+            self.report.orders += 1;
+            self.next_id += 1;
+            let id = format!("bt-{}", self.next_id);
+            order.strategy = Some(self.plugins[plugin].name().to_string());
+
+            match self.fill_model.latency(&order) {
+                0 => self.arrive(plugin, id, order, now),
+                latency => self.pending.push(Pending { id, plugin, order, arrives_at: now + latency }),
+            }
+        }
+    }
+
+    /// Match an order reaching the venue against its book, resting what is
+    /// left of a limit order
+    fn arrive(&mut self, plugin: usize, id: String, order: Order, now: u64) {
+        let quote = self.books.get(&(order.venue, order.symbol)).cloned();
+        let execution = quote.and_then(|quote| self.fill_model.on_arrival(&id, &order, &quote));
+        let remaining = order.quantity - execution.map_or(0.0, |e| e.quantity);
+
+        if order.order_type == OrderType::Limit && remaining > QUANTITY_EPSILON {
+            let mut replaced = Vec::new();
+            self.resting.retain(|r| {
+                let keep = r.plugin != plugin || r.order.side != order.side || r.order.symbol != order.symbol || r.order.venue != order.venue;
+                if !keep {
+                    replaced.push(r.id.clone());
+                }
+                keep
+            });
+            for replaced in replaced {
+                self.fill_model.on_done(&replaced);
+            }
+            self.resting.push(Resting { id: id.clone(), plugin, order: order.clone(), remaining });
+        }
+        if let Some(execution) = execution {
+            self.fill(plugin, id, &order, execution, now);
+        }
+    }
+
+    fn fill(&mut self, plugin: usize, order_id: String, order: &Order, execution: Execution, now: u64) {
+        let fill = Fill {
+            order_id,
+            symbol: order.symbol,
+            venue: order.venue,
+            strategy: order.strategy_label().to_string(),
+            side: order.side,
+            quantity: execution.quantity,
+            price: execution.price,
+            timestamp: now,
+            commission: None,
+        };
+        self.report.fills += 1;
+        self.report.notional += fill.quantity * fill.price;
+        self.report.fees += self.fill_model.fee(order, &execution);
+        self.positions
+            .entry((fill.strategy.clone(), fill.venue.to_string(), fill.symbol.to_string()))
+            .or_default()
+            .apply_fill(fill.side, fill.quantity, fill.price);
+
+        let orders = self.plugins[plugin].on_fill(&fill);
+        self.submit(plugin, orders, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderSide;
+
+    /// Bids one below the inside bid, and offers one unit at the ask after
+    /// every buy
+    struct Scalper;
+
+    impl StrategyPlugin for Scalper {
+        fn name(&self) -> &str {
+            "scalper"
+        }
+
+        fn on_quote(&mut self, quote: &Quote) -> Vec<Order> {
+            vec![order(quote, OrderSide::Buy, quote.bid - 1.0)]
+        }
+
+        fn on_fill(&mut self, fill: &Fill) -> Vec<Order> {
+            if fill.side == OrderSide::Sell {
+                return Vec::new();
+            }
+            let mut sell = order(&quote(0, fill.price, fill.price + 2.0), OrderSide::Sell, fill.price + 2.0);
+            sell.symbol = fill.symbol;
+            vec![sell]
+        }
+    }
+
+    fn order(quote: &Quote, side: OrderSide, price: f64) -> Order {
+        Order {
+            symbol: quote.symbol,
+            side,
+            quantity: 1.0,
+            price,
+            venue: quote.venue,
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        }
+    }
+
+    fn quote(timestamp: u64, bid: f64, ask: f64) -> Quote {
+        Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "SIM".into(),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_resting_orders_fill_when_crossed() {
+        let report = Backtest::new()
+            .with_strategy(Box::new(Scalper))
+            .run(vec![
+                quote(1, 100.0, 101.0),
+                // Bid at 99 is requoted at 98, then the ask trades down to it
+                quote(2, 99.0, 100.0),
+                quote(3, 97.0, 98.0),
+                // Offer at 100 is lifted
+                quote(4, 100.0, 101.0),
+            ]);
+
+        assert_eq!(report.quotes, 4);
+        assert_eq!(report.fills, 2);
+        assert_eq!(report.notional, 198.0);
+        let (key, position) = &report.positions[0];
+        assert_eq!(key.strategy, "scalper");
+        assert!(position.is_flat());
+        assert_eq!(position.realized_pnl, 2.0);
+        assert_eq!(report.total_pnl(), 2.0);
+    }
+
+    /// Buys one unit at the bid, or at market, on the first quote
+    struct Taker {
+        sent: bool,
+        order_type: OrderType,
+    }
+
+    impl StrategyPlugin for Taker {
+        fn name(&self) -> &str {
+            "taker"
+        }
+
+        fn on_quote(&mut self, quote: &Quote) -> Vec<Order> {
+            if std::mem::replace(&mut self.sent, true) {
+                return Vec::new();
+            }
+            vec![Order { order_type: self.order_type, ..order(quote, OrderSide::Buy, quote.bid) }]
+        }
+
+        fn on_fill(&mut self, _fill: &Fill) -> Vec<Order> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_fill_model_latency_and_fees() {
+        let model = TopOfBook::new()
+            .with_latency(10)
+            .with_fees(Fees { maker_bps: 0.0, taker_bps: 10.0 });
+        let report = Backtest::new()
+            .with_strategy(Box::new(Taker { sent: false, order_type: OrderType::Market }))
+            .with_fill_model(Box::new(model))
+            .run(vec![quote(0, 100.0, 101.0), quote(5, 102.0, 103.0), quote(12, 104.0, 105.0)]);
+
+        // Arrives at 10, while the quote from 5 stands
+        assert_eq!(report.fills, 1);
+        assert_eq!(report.notional, 103.0);
+        assert!((report.fees - 0.103).abs() < 1e-12);
+        assert!((report.net_pnl() - (1.5 - 0.103)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_queue_position_fills_later_than_top_of_book() {
+        let quotes = vec![
+            // Bids at 100 behind 5 others
+            Quote { bid_size: 5.0, ..quote(1, 100.0, 101.0) },
+            // 1 offered at 100 trades with the queue ahead first
+            quote(2, 99.0, 100.0),
+        ];
+        let run = |model: Box<dyn FillModel>| Backtest::new()
+            .with_strategy(Box::new(Taker { sent: false, order_type: OrderType::Limit }))
+            .with_fill_model(model)
+            .run(quotes.clone());
+        assert_eq!(run(Box::new(TopOfBook::new())).fills, 1);
+        assert_eq!(run(Box::new(QueuePosition::new())).fills, 0);
+    }
+
+    #[test]
+    fn test_depth_quotes_follow_top_of_book() {
+        use crate::book::{BookDelta, DepthEvent, RecordedDepth};
+
+        let dir = std::env::temp_dir().join(format!("hft_backtest_depth_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("depth.jsonl");
+        let delta = |received_at, bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>| RecordedDepth {
+            received_at,
+            event: DepthEvent::Delta(BookDelta {
+                symbol: "BTCUSDT".into(),
+                venue: "SIM".into(),
+                bids,
+                asks,
+                ..BookDelta::default()
+            }),
+        };
+        let records = [
+            delta(1, vec![(100.0, 1.0)], vec![]),
+            delta(2, vec![], vec![(101.0, 2.0)]),
+            // Behind the touch, so no new quote
+            delta(3, vec![(99.0, 5.0)], vec![]),
+            delta(4, vec![(100.0, 0.0)], vec![]),
+        ];
+        let lines: Vec<String> = records.iter().map(|r| serde_json::to_string(r).unwrap()).collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let loaded = load_depth_quotes(&path, "SIM").unwrap();
+        let tops: Vec<_> = loaded.quotes.iter().map(|q| (q.timestamp, q.bid, q.bid_size, q.ask)).collect();
+        assert_eq!(tops, vec![(2, 100.0, 1.0, 101.0), (4, 99.0, 5.0, 101.0)]);
+        assert!(load_depth_quotes(&path, "OTHER").unwrap().quotes.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_seeded_latency_jitter() {
+        // The price rises every millisecond, so the fill price shows when
+        // the order arrived
+        let quotes: Vec<_> = (0..100).map(|t| quote(t, 100.0 + t as f64, 101.0 + t as f64)).collect();
+        let run = |seed| Backtest::new()
+            .with_strategy(Box::new(Taker { sent: false, order_type: OrderType::Market }))
+            .with_fill_model(Box::new(TopOfBook::new().with_latency(5).with_jitter(50)))
+            .with_seed(seed)
+            .run(quotes.clone())
+            .notional;
+        assert_eq!(run(7), run(7));
+        assert!((0..10).any(|seed| run(seed) != run(7)));
+        assert!((0..10).all(|seed| (106.0..=156.0).contains(&run(seed))));
+    }
+}
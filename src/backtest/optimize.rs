@@ -0,0 +1,347 @@
+//! Walk-forward parameter search over recorded quotes.
+//!
+//! The quotes are cut into `folds + 1` consecutive windows. In fold `i`
+//! every candidate is backtested on window `i`, in sample, and on window
+//! `i + 1`, out of sample; the candidate with the best in-sample net PnL is
+//! the fold's pick. Candidates are ranked by mean out-of-sample net PnL, so
+//! parameters that only fit one stretch of the data sink. Candidates run in
+//! parallel on the rayon thread pool.
+
+use std::fmt;
+use rand::Rng;
+use rayon::prelude::*;
+use serde_json::{Map, Value};
+
+use crate::error::HftError;
+use crate::strategy::{ParameterStore, StrategyPlugin};
+use crate::types::Quote;
+use super::{Backtest, BacktestReport, FillModel};
+
+/// One set of parameters to try
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    /// The values taken from the grid axes
+    pub varied: Value,
+    /// The base parameters with the varied values applied
+    pub params: Value,
+}
+
+/// Base strategy parameters and the values to try for some of them
+#[derive(Debug, Clone, Default)]
+pub struct ParameterGrid {
+    base: Map<String, Value>,
+    axes: Vec<(String, Vec<Value>)>,
+}
+
+impl ParameterGrid {
+    /// A grid over `base`, a JSON object of strategy parameters
+    pub fn new(base: Value) -> Self {
+        let base = match base {
+            Value::Object(base) => base,
+            _ => Map::new(),
+        };
+        Self { base, axes: Vec::new() }
+    }
+
+    /// Try each of `values` for the top level parameter `name`
+    pub fn with_axis(mut self, name: &str, values: Vec<Value>) -> Self {
+        self.axes.push((name.to_string(), values));
+        self
+    }
+
+    /// Axes from a JSON object mapping parameter names to arrays of values,
+    /// e.g. `{"base_spread_bps": [5, 10, 20], "vol_window": [50, 100]}`
+    pub fn parse(base: Value, axes: &Value) -> Result<Self, HftError> {
+        let axes = axes.as_object()
+            .ok_or_else(|| HftError::Config("Parameter grid must be a JSON object".to_string()))?;
+        let mut grid = Self::new(base);
+        for (name, values) in axes {
+            match values.as_array() {
+                Some(values) if !values.is_empty() => grid = grid.with_axis(name, values.clone()),
+                _ => return Err(HftError::Config(format!("Grid axis {} must be a non-empty array", name))),
+            }
+        }
+        Ok(grid)
+    }
+
+    /// Number of candidates in the full grid
+    pub fn len(&self) -> usize {
+        self.axes.iter().map(|(_, values)| values.len()).product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The `index`th combination, with the last axis varying fastest
+    fn candidate(&self, mut index: usize) -> Candidate {
+        let mut varied = Map::new();
+        for (name, values) in self.axes.iter().rev() {
+            varied.insert(name.clone(), values[index % values.len()].clone());
+            index /= values.len();
+        }
+        let mut params = self.base.clone();
+        params.extend(varied.clone());
+        Candidate { varied: Value::Object(varied), params: Value::Object(params) }
+    }
+
+    /// Every combination of axis values
+    pub fn candidates(&self) -> Vec<Candidate> {
+        (0..self.len()).map(|i| self.candidate(i)).collect()
+    }
+
+    /// `count` distinct combinations picked at random, or all of them when
+    /// the grid is no larger
+    pub fn sample(&self, count: usize, rng: &mut impl Rng) -> Vec<Candidate> {
+        if count >= self.len() {
+            return self.candidates();
+        }
+        let mut picked = rand::seq::index::sample(rng, self.len(), count).into_vec();
+        picked.sort_unstable();
+        picked.into_iter().map(|i| self.candidate(i)).collect()
+    }
+}
+
+/// How one candidate did across the folds
+#[derive(Debug, Clone)]
+pub struct CandidateResult {
+    pub candidate: Candidate,
+    /// Mean in-sample net PnL per fold
+    pub train_pnl: f64,
+    /// Mean out-of-sample net PnL per fold
+    pub test_pnl: f64,
+    /// Out-of-sample fills over all folds
+    pub test_fills: usize,
+    /// Folds the candidate was the in-sample best in
+    pub picked: usize,
+}
+
+/// The in-sample pick of one fold and how it did out of sample
+#[derive(Debug, Clone)]
+pub struct FoldResult {
+    /// Position of the pick in [`OptimizationReport::results`]
+    pub rank: usize,
+    pub train_pnl: f64,
+    pub test_pnl: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OptimizationReport {
+    /// Best out-of-sample PnL first
+    pub results: Vec<CandidateResult>,
+    pub folds: Vec<FoldResult>,
+}
+
+impl OptimizationReport {
+    pub fn best(&self) -> Option<&CandidateResult> {
+        self.results.first()
+    }
+}
+
+impl fmt::Display for OptimizationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, fold) in self.folds.iter().enumerate() {
+            writeln!(f, "Fold {}: picked #{}, in sample {:.2}, out of sample {:.2}", i + 1, fold.rank + 1, fold.train_pnl, fold.test_pnl)?;
+        }
+        write!(f, "{:>4}  {:>12}  {:>12}  {:>6}  {:>6}  Parameters", "Rank", "Test PnL", "Train PnL", "Fills", "Picked")?;
+        for (i, result) in self.results.iter().enumerate() {
+            write!(
+                f,
+                "\n{:>4}  {:>12.2}  {:>12.2}  {:>6}  {:>6}  {}",
+                i + 1, result.test_pnl, result.train_pnl, result.test_fills, result.picked, result.candidate.varied,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs one strategy's candidates through walk-forward backtests
+pub struct Optimizer<S, M> {
+    strategy: String,
+    build: S,
+    fill_model: M,
+    folds: usize,
+    seed: u64,
+}
+
+impl<S, M> Optimizer<S, M>
+where
+    S: Fn(ParameterStore) -> Box<dyn StrategyPlugin> + Sync,
+    M: Fn() -> Box<dyn FillModel> + Sync,
+{
+    /// Candidates are set as `strategy`'s parameters in the store `build`
+    /// gets; `fill_model` makes the fill model for each backtest
+    pub fn new(strategy: &str, build: S, fill_model: M) -> Self {
+        Self { strategy: strategy.to_string(), build, fill_model, folds: 3, seed: 0 }
+    }
+
+    pub fn with_folds(mut self, folds: usize) -> Self {
+        self.folds = folds.max(1);
+        self
+    }
+
+    /// Seed every backtest's fill model alike, so candidates are compared
+    /// on the same draws
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    fn backtest(&self, params: &Value, quotes: &[Quote]) -> BacktestReport {
+        let store = ParameterStore::new();
+        store.set(&self.strategy, params.clone());
+        Backtest::new()
+            .with_strategy((self.build)(store))
+            .with_fill_model((self.fill_model)())
+            .with_seed(self.seed)
+            .run(quotes.iter().cloned())
+    }
+
+    pub fn run(&self, quotes: &[Quote], candidates: Vec<Candidate>) -> OptimizationReport {
+        let window = quotes.len() / (self.folds + 1);
+        let windows: Vec<&[Quote]> = (0..=self.folds)
+            .map(|i| {
+                let end = if i == self.folds { quotes.len() } else { (i + 1) * window };
+                &quotes[i * window..end]
+            })
+            .collect();
+
+        // In and out of sample net PnL and out of sample fills, per fold
+        let runs: Vec<Vec<(f64, f64, usize)>> = candidates.par_iter()
+            .map(|candidate| {
+                (0..self.folds)
+                    .map(|i| {
+                        let train = self.backtest(&candidate.params, windows[i]);
+                        let test = self.backtest(&candidate.params, windows[i + 1]);
+                        (train.net_pnl(), test.net_pnl(), test.fills)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let picks: Vec<usize> = (0..self.folds)
+            .filter_map(|fold| (0..runs.len()).max_by(|&a, &b| runs[a][fold].0.total_cmp(&runs[b][fold].0)))
+            .collect();
+        let folds = self.folds as f64;
+        let mut results: Vec<(usize, CandidateResult)> = candidates.into_iter()
+            .zip(&runs)
+            .enumerate()
+            .map(|(i, (candidate, runs))| (i, CandidateResult {
+                candidate,
+                train_pnl: runs.iter().map(|r| r.0).sum::<f64>() / folds,
+                test_pnl: runs.iter().map(|r| r.1).sum::<f64>() / folds,
+                test_fills: runs.iter().map(|r| r.2).sum(),
+                picked: picks.iter().filter(|&&p| p == i).count(),
+            }))
+            .collect();
+        results.sort_by(|a, b| b.1.test_pnl.total_cmp(&a.1.test_pnl));
+
+        let folds = picks.iter().enumerate()
+            .map(|(fold, &pick)| FoldResult {
+                rank: results.iter().position(|(i, _)| *i == pick).unwrap_or_default(),
+                train_pnl: runs[pick][fold].0,
+                test_pnl: runs[pick][fold].1,
+            })
+            .collect();
+        OptimizationReport { results: results.into_iter().map(|(_, r)| r).collect(), folds }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use serde_json::json;
+    use crate::backtest::TopOfBook;
+    use crate::types::{Fill, Order, OrderSide, OrderType};
+
+    /// Bids `offset` below the bid; sells what it bought `offset` above the
+    /// fill
+    struct Dipper {
+        store: ParameterStore,
+    }
+
+    impl StrategyPlugin for Dipper {
+        fn name(&self) -> &str {
+            "dipper"
+        }
+
+        fn on_quote(&mut self, quote: &Quote) -> Vec<Order> {
+            let offset = self.store.get("dipper").and_then(|p| p["offset"].as_f64()).unwrap_or(1.0);
+            vec![order(quote, OrderSide::Buy, quote.bid - offset)]
+        }
+
+        fn on_fill(&mut self, fill: &Fill) -> Vec<Order> {
+            if fill.side == OrderSide::Sell {
+                return Vec::new();
+            }
+            let offset = self.store.get("dipper").and_then(|p| p["offset"].as_f64()).unwrap_or(1.0);
+            let quote = Quote { bid: fill.price, ask: fill.price, ..quote(0) };
+            vec![order(&quote, OrderSide::Sell, fill.price + offset)]
+        }
+    }
+
+    fn order(quote: &Quote, side: OrderSide, price: f64) -> Order {
+        Order {
+            symbol: quote.symbol,
+            side,
+            quantity: 1.0,
+            price,
+            venue: quote.venue,
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        }
+    }
+
+    fn quote(timestamp: u64) -> Quote {
+        // Dips of 3 every fourth quote
+        let bid = if timestamp % 4 == 2 { 97.0 } else { 100.0 };
+        Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask: bid + 1.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "SIM".into(),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_grid_candidates() {
+        let grid = ParameterGrid::parse(json!({"offset": 1, "size": 1}), &json!({"offset": [1, 2], "skew": [0, 5, 10]})).unwrap();
+        assert_eq!(grid.len(), 6);
+        let candidates = grid.candidates();
+        assert_eq!(candidates[1].varied, json!({"offset": 1, "skew": 5}));
+        assert_eq!(candidates[5].params, json!({"offset": 2, "size": 1, "skew": 10}));
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let sampled = grid.sample(3, &mut rng);
+        assert_eq!(sampled.len(), 3);
+        assert!(sampled.iter().all(|c| candidates.contains(c)));
+        assert!(ParameterGrid::parse(json!({}), &json!({"offset": []})).is_err());
+    }
+
+    #[test]
+    fn test_walk_forward_ranks_out_of_sample() {
+        let quotes: Vec<Quote> = (0..40).map(quote).collect();
+        let grid = ParameterGrid::new(json!({})).with_axis("offset", vec![json!(1.0), json!(2.0), json!(5.0)]);
+        let optimizer = Optimizer::new("dipper", |store| Box::new(Dipper { store }) as Box<dyn StrategyPlugin>, || {
+            Box::new(TopOfBook::new()) as Box<dyn FillModel>
+        }).with_folds(3);
+        let report = optimizer.run(&quotes, grid.candidates());
+
+        assert_eq!(report.results.len(), 3);
+        assert_eq!(report.folds.len(), 3);
+        // An offset of 2 buys the dips cheapest and sells back at 100, one
+        // of 5 never fills
+        let best = report.best().unwrap();
+        assert_eq!(best.candidate.varied, json!({"offset": 2.0}));
+        assert_eq!(best.picked, 3);
+        assert!(best.test_pnl > 0.0);
+        assert_eq!(report.results[2].test_fills, 0);
+        assert!(report.to_string().contains("{\"offset\":2.0}"));
+    }
+}
@@ -0,0 +1,20 @@
+use hft_engine::loadgen::{self, LoadConfig};
+
+/// Push synthetic quotes and orders through the engine pipeline and report
+/// the throughput and latency it sustained
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let config = LoadConfig::from_args(std::env::args().skip(1))?;
+    println!(
+        "Sending {} quotes ({}) and {} orders ({}) across {} symbols",
+        config.quotes,
+        config.quote_rate.map_or("unpaced".to_string(), |r| format!("{}/s", r)),
+        config.orders,
+        config.order_rate.map_or("unpaced".to_string(), |r| format!("{}/s", r)),
+        config.symbols,
+    );
+
+    let report = loadgen::run(&config).await?;
+    println!("{}", report);
+    Ok(())
+}
@@ -0,0 +1,224 @@
+//! Verification of venue-provided order book checksums.
+//!
+//! Venues such as Kraken and OKX send a CRC32 of their top levels with
+//! each depth update. The book builder recomputes it over its own copy
+//! after applying the update; a mismatch means an update was lost or
+//! misapplied, so the book is cleared and a fresh snapshot requested.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::metrics::{labels, BOOK_CHECKSUM_MISMATCHES};
+use super::OrderBook;
+
+/// Computes a venue's checksum over a book
+pub trait BookChecksum: Send + Sync {
+    fn checksum(&self, book: &OrderBook) -> u32;
+}
+
+/// Kraken's checksum: CRC32 of the best 10 asks then the best 10 bids,
+/// each price and size printed at the pair's precision with the decimal
+/// point and leading zeros removed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Kraken {
+    pub price_decimals: usize,
+    pub size_decimals: usize,
+}
+
+impl Kraken {
+    const DEPTH: usize = 10;
+
+    fn digits(value: f64, decimals: usize) -> String {
+        format!("{:.*}", decimals, value).replace('.', "").trim_start_matches('0').to_string()
+    }
+}
+
+impl BookChecksum for Kraken {
+    fn checksum(&self, book: &OrderBook) -> u32 {
+        let mut text = String::new();
+        for (price, size) in book.asks(Self::DEPTH).chain(book.bids(Self::DEPTH)) {
+            text.push_str(&Self::digits(price, self.price_decimals));
+            text.push_str(&Self::digits(size, self.size_decimals));
+        }
+        crc32fast::hash(text.as_bytes())
+    }
+}
+
+/// OKX's checksum: CRC32 of the best 25 bids and asks interleaved as
+/// `bid:size:ask:size:...`, the longer side continuing alone once the
+/// shorter runs out. OKX sends it as a signed integer; cast it to `u32`.
+///
+/// Prices and sizes are printed as OKX sends them, which is the shortest
+/// form unless `decimals` fixes the price and size precision.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Okx {
+    pub decimals: Option<(usize, usize)>,
+}
+
+impl Okx {
+    const DEPTH: usize = 25;
+
+    fn level(&self, (price, size): (f64, f64)) -> String {
+        match self.decimals {
+            Some((price_decimals, size_decimals)) => format!("{:.*}:{:.*}", price_decimals, price, size_decimals, size),
+            None => format!("{}:{}", price, size),
+        }
+    }
+}
+
+impl BookChecksum for Okx {
+    fn checksum(&self, book: &OrderBook) -> u32 {
+        let mut bids = book.bids(Self::DEPTH);
+        let mut asks = book.asks(Self::DEPTH);
+        let mut levels = Vec::new();
+        loop {
+            let (bid, ask) = (bids.next(), asks.next());
+            if bid.is_none() && ask.is_none() {
+                break;
+            }
+            levels.extend(bid.into_iter().chain(ask).map(|level| self.level(level)));
+        }
+        crc32fast::hash(levels.join(":").as_bytes())
+    }
+}
+
+/// A book that failed its checksum and needs a fresh snapshot from its
+/// venue
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resync {
+    pub venue: String,
+    pub symbol: String,
+}
+
+/// The checksum each venue's depth updates are verified with
+#[derive(Clone, Default)]
+pub struct BookChecksums {
+    venues: HashMap<String, Arc<dyn BookChecksum>>,
+    resync: Option<mpsc::Sender<Resync>>,
+}
+
+impl fmt::Debug for BookChecksums {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut venues: Vec<_> = self.venues.keys().collect();
+        venues.sort();
+        f.debug_struct("BookChecksums").field("venues", &venues).finish()
+    }
+}
+
+impl BookChecksums {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_venue(mut self, venue: &str, checksum: impl BookChecksum + 'static) -> Self {
+        self.venues.insert(venue.to_uppercase(), Arc::new(checksum));
+        self
+    }
+
+    /// Send resync requests for books that fail their checksum to the
+    /// market data connection that can snapshot them
+    pub fn with_resync(mut self, resync: mpsc::Sender<Resync>) -> Self {
+        self.resync = Some(resync);
+        self
+    }
+
+    /// Comma separated `VENUE=okx` or `VENUE=kraken:PRICE_DECIMALS:SIZE_DECIMALS`
+    /// entries, e.g. `OKX=okx,KRAKEN=kraken:1:8`
+    fn parse(spec: &str) -> Self {
+        let mut checksums = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((venue, algorithm)) = entry.split_once('=') else {
+                warn!(entry = entry, "Ignoring malformed book checksum");
+                continue;
+            };
+            let parts: Vec<&str> = algorithm.trim().split(':').collect();
+            let decimals = |price: &str, size: &str| Some((price.parse().ok()?, size.parse().ok()?));
+            let checksum: Option<Arc<dyn BookChecksum>> = match parts[..] {
+                ["okx"] => Some(Arc::new(Okx::default())),
+                ["okx", price, size] => decimals(price, size).map(|decimals| Arc::new(Okx { decimals: Some(decimals) }) as _),
+                ["kraken", price, size] => decimals(price, size).map(|(price_decimals, size_decimals)| {
+                    Arc::new(Kraken { price_decimals, size_decimals }) as _
+                }),
+                _ => None,
+            };
+            match checksum {
+                Some(checksum) => {
+                    checksums.venues.insert(venue.trim().to_uppercase(), checksum);
+                }
+                None => warn!(entry = entry, "Ignoring malformed book checksum"),
+            }
+        }
+        checksums
+    }
+
+    /// Read `HFT_BOOK_CHECKSUMS`, e.g. `OKX=okx,KRAKEN=kraken:1:8`
+    pub fn from_env() -> Self {
+        Self::parse(&std::env::var("HFT_BOOK_CHECKSUMS").unwrap_or_default())
+    }
+
+    /// Whether `book` matches the checksum `venue` sent for it; venues
+    /// without a configured checksum always match. Mismatches are counted.
+    pub(crate) fn verify(&self, venue: &str, book: &OrderBook, expected: u32) -> bool {
+        let Some(checksum) = self.venues.get(&venue.to_uppercase()) else {
+            return true;
+        };
+        let actual = checksum.checksum(book);
+        if actual == expected {
+            return true;
+        }
+        BOOK_CHECKSUM_MISMATCHES
+            .with_label_values(&[venue, labels::symbol("book_checksum_mismatches", book.symbol())])
+            .inc();
+        warn!(venue = venue, symbol = book.symbol(), expected, actual, "Book checksum mismatch, resyncing");
+        false
+    }
+
+    /// Ask for a fresh snapshot of `symbol` on `venue`
+    pub(crate) fn request_resync(&self, venue: &str, symbol: &str) {
+        let Some(resync) = &self.resync else {
+            return;
+        };
+        if resync.try_send(Resync { venue: venue.to_string(), symbol: symbol.to_string() }).is_err() {
+            warn!(venue = venue, symbol = symbol, "Resync request not delivered");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::BookSnapshot;
+
+    fn book(bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>) -> OrderBook {
+        OrderBook::from_snapshot(&BookSnapshot { symbol: "XBT/USD".to_string(), bids, asks })
+    }
+
+    #[test]
+    fn test_kraken_checksum() {
+        let book = book(vec![(0.05, 1.5), (0.049, 0.00000500)], vec![(0.05005, 0.00000500)]);
+        let kraken = Kraken { price_decimals: 5, size_decimals: 8 };
+        // Asks first, best first; then bids best first
+        let expected = crc32fast::hash(["5005", "500", "5000", "150000000", "4900", "500"].concat().as_bytes());
+        assert_eq!(kraken.checksum(&book), expected);
+    }
+
+    #[test]
+    fn test_okx_checksum_interleaves_sides() {
+        let book = book(vec![(3366.1, 7.0), (3366.0, 6.0)], vec![(3366.8, 9.0)]);
+        let expected = crc32fast::hash(b"3366.1:7:3366.8:9:3366:6");
+        assert_eq!(Okx::default().checksum(&book), expected);
+    }
+
+    #[test]
+    fn test_parse() {
+        let checksums = BookChecksums::parse("OKX=okx, KRAKEN=kraken:1:8, BAD=kraken:x, NONE");
+        assert_eq!(format!("{:?}", checksums), r#"BookChecksums { venues: ["KRAKEN", "OKX"] }"#);
+        let book = book(vec![(1.0, 1.0)], vec![(2.0, 1.0)]);
+        assert!(checksums.verify("UNCHECKED", &book, 0));
+        assert!(checksums.verify("okx", &book, crc32fast::hash(b"1:1:2:1")));
+        assert!(!checksums.verify("OKX", &book, 0));
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::metrics::{labels, BOOK_BEST_ASK, BOOK_BEST_BID, BOOK_SPREAD_BPS, BOOK_STALENESS};
+use super::OrderBook;
+
+/// How often staleness is recomputed for books that have not updated
+const STALENESS_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Exports each book's top of book, spread and time since its last update
+/// as gauges
+#[derive(Debug, Default)]
+pub(crate) struct BookGauges {
+    updated: HashMap<String, Instant>,
+    pub(super) refreshed: Option<Instant>,
+}
+
+impl BookGauges {
+    pub(crate) fn on_update(&mut self, book: &OrderBook) {
+        let symbol = labels::symbol("book_top", book.symbol());
+        let bid = book.best_bid().map(|(p, _)| p);
+        let ask = book.best_ask().map(|(p, _)| p);
+        if let Some(bid) = bid {
+            BOOK_BEST_BID.with_label_values(&[symbol]).set(bid);
+        }
+        if let Some(ask) = ask {
+            BOOK_BEST_ASK.with_label_values(&[symbol]).set(ask);
+        }
+        if let (Some(bid), Some(ask)) = (bid, ask) {
+            let mid = (bid + ask) / 2.0;
+            BOOK_SPREAD_BPS.with_label_values(&[symbol]).set((ask - bid) / mid * 10_000.0);
+        }
+        BOOK_STALENESS.with_label_values(&[symbol]).set(0.0);
+
+        match self.updated.get_mut(book.symbol()) {
+            Some(at) => *at = Instant::now(),
+            None => {
+                self.updated.insert(book.symbol().to_string(), Instant::now());
+            }
+        }
+    }
+
+    /// Bring staleness up to date, at most once per refresh interval
+    pub(crate) fn refresh(&mut self) {
+        let now = Instant::now();
+        if self.refreshed.is_some_and(|at| now - at < STALENESS_REFRESH_INTERVAL) {
+            return;
+        }
+        self.refreshed = Some(now);
+        for (symbol, at) in &self.updated {
+            BOOK_STALENESS
+                .with_label_values(&[labels::symbol("book_top", symbol)])
+                .set((now - *at).as_secs_f64());
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::metrics::{labels, BOOK_LEVELS, BOOK_LEVELS_PRUNED, BOOK_MEMORY_BYTES};
+use crate::types::Symbol;
+use super::OrderBook;
+
+const DEFAULT_COMPACT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Estimated bytes per price level: the key and size, with B-tree nodes
+/// around half full
+const LEVEL_BYTES: usize = 2 * std::mem::size_of::<(u64, f64)>();
+
+/// How deep books may grow. Far levels that venues never remove otherwise
+/// pile up for as long as the engine runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookLimits {
+    /// Price levels kept per side, nearest the touch first
+    pub max_levels: Option<usize>,
+    /// Levels further than this from the mid, in basis points, are dropped
+    /// when compacting
+    pub max_distance_bps: Option<f64>,
+    /// How often books are compacted and their size exported
+    pub compact_interval: Duration,
+}
+
+impl Default for BookLimits {
+    fn default() -> Self {
+        Self { max_levels: None, max_distance_bps: None, compact_interval: DEFAULT_COMPACT_INTERVAL }
+    }
+}
+
+impl BookLimits {
+    /// Parse comma separated `levels:N`, `distance_bps:BPS` and
+    /// `compact:SECS`, with unset ones at their defaults
+    fn parse(spec: &str) -> Self {
+        let mut limits = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = match entry.split_once(':') {
+                Some(("levels", n)) => n.trim().parse().ok().filter(|n| *n > 0).map(|n| limits.max_levels = Some(n)),
+                Some(("distance_bps", bps)) => bps.trim().parse().ok().filter(|bps: &f64| *bps > 0.0).map(|bps| limits.max_distance_bps = Some(bps)),
+                Some(("compact", secs)) => secs.trim().parse().ok().filter(|secs| *secs > 0).map(|secs| limits.compact_interval = Duration::from_secs(secs)),
+                _ => None,
+            };
+            if parsed.is_none() {
+                warn!(entry = entry, "Ignoring malformed book limit");
+            }
+        }
+        limits
+    }
+
+    /// Limits from `HFT_BOOK_LIMITS`, unlimited when unset
+    pub fn from_env() -> Self {
+        Self::parse(&std::env::var("HFT_BOOK_LIMITS").unwrap_or_default())
+    }
+}
+
+/// Applies [`BookLimits`] to the books and exports their size
+#[derive(Debug, Default)]
+pub(crate) struct Compactor {
+    limits: BookLimits,
+    compacted: Option<Instant>,
+}
+
+impl Compactor {
+    pub(crate) fn new(limits: BookLimits) -> Self {
+        Self { limits, compacted: None }
+    }
+
+    /// Trim `book` to the level limit after an update
+    pub(crate) fn on_update(&self, book: &mut OrderBook) {
+        if let Some(max_levels) = self.limits.max_levels {
+            let pruned = book.truncate(max_levels);
+            if pruned > 0 {
+                BOOK_LEVELS_PRUNED.with_label_values(&[labels::symbol("book_levels_pruned", book.symbol())]).inc_by(pruned as f64);
+            }
+        }
+    }
+
+    /// Whether a compaction pass is due
+    pub(crate) fn due(&self) -> bool {
+        self.compacted.is_none_or(|at| at.elapsed() >= self.limits.compact_interval)
+    }
+
+    /// Drop levels too far from the mid in every book and export each
+    /// book's level count and estimated memory
+    pub(crate) fn compact(&mut self, books: &mut HashMap<Symbol, OrderBook>) {
+        self.compacted = Some(Instant::now());
+        for book in books.values_mut() {
+            if let Some(bps) = self.limits.max_distance_bps {
+                let pruned = book.prune_beyond(bps);
+                if pruned > 0 {
+                    BOOK_LEVELS_PRUNED.with_label_values(&[labels::symbol("book_levels_pruned", book.symbol())]).inc_by(pruned as f64);
+                }
+            }
+            let symbol = labels::symbol("book_levels", book.symbol());
+            let (bids, asks) = book.level_counts();
+            BOOK_LEVELS.with_label_values(&[symbol, "bid"]).set(bids as f64);
+            BOOK_LEVELS.with_label_values(&[symbol, "ask"]).set(asks as f64);
+            BOOK_MEMORY_BYTES.with_label_values(&[symbol]).set(((bids + asks) * LEVEL_BYTES) as f64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_book_limits() {
+        let limits = BookLimits::parse("levels:200, distance_bps:500,compact:30,levels:0,depth:5");
+        assert_eq!(limits, BookLimits {
+            max_levels: Some(200),
+            max_distance_bps: Some(500.0),
+            compact_interval: Duration::from_secs(30),
+        });
+        assert_eq!(BookLimits::parse(""), BookLimits::default());
+    }
+}
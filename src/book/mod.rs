@@ -1,44 +1,241 @@
-use std::collections::{HashMap, BTreeMap};
+use std::collections::{HashMap, HashSet, BTreeMap};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
 use tokio::sync::{mpsc, RwLock};
-use crate::types::Quote;
-use crate::metrics::ORDERBOOK_UPDATES;
+use crate::types::{Quote, Symbol, VenueId};
+use crate::metrics::{labels, BOOK_APPLY_LATENCY, ORDERBOOK_UPDATES};
+use crate::health::Heartbeat;
+use crate::feed::{FeedMessage, FeedPublisher, StreamHub};
+use crate::risk::RiskManager;
+
+pub mod checksum;
+mod gauges;
+mod limits;
+pub mod snapshots;
+pub mod recorder;
+
+pub use checksum::{BookChecksum, BookChecksums, Resync};
+pub use recorder::{read_depth, DepthEvent, DepthRecorder, DepthRecordingConfig, DepthReplay, RecordedDepth};
+pub(crate) use gauges::BookGauges;
+pub use limits::BookLimits;
+pub(crate) use limits::Compactor;
+pub use snapshots::BookSnapshots;
+
+/// How often an idle loop reports that it is still alive
+const IDLE_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
 
 pub struct BookBuilder {
-    pub(crate) books: Arc<RwLock<HashMap<String, OrderBook>>>,
+    pub(crate) books: Arc<RwLock<HashMap<Symbol, OrderBook>>>,
     pub(crate) quote_rx: mpsc::Receiver<Quote>,
+    pub(crate) heartbeat: Option<Heartbeat>,
+    pub(crate) feed: Option<FeedPublisher>,
+    /// Book updates for GUI clients, when enabled
+    pub(crate) stream: Option<StreamHub>,
+    /// Top-of-book gauges, when enabled
+    pub(crate) gauges: Option<BookGauges>,
+    /// Depth updates from venues that publish them
+    pub(crate) deltas: Option<mpsc::Receiver<BookDelta>>,
+    pub(crate) checksums: BookChecksums,
+    /// Books cleared after a checksum mismatch, ignoring updates until
+    /// their venue sends a snapshot
+    pub(crate) resyncing: HashSet<Symbol>,
+    /// Records what is applied to the books, when enabled
+    pub(crate) recorder: Option<DepthRecorder>,
+    /// Keeps books within their depth limits
+    pub(crate) compactor: Compactor,
+    /// Republished after every update, for lock-free reads
+    pub(crate) snapshots: BookSnapshots,
+    /// Marks positions to each quote, when set
+    pub(crate) risk: Option<Arc<RiskManager>>,
+}
+
+/// Changes to a book's price levels from a venue's depth stream; a size of
+/// zero removes the level
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BookDelta {
+    pub symbol: Symbol,
+    pub venue: VenueId,
+    pub bids: Vec<(f64, f64)>,
+    pub asks: Vec<(f64, f64)>,
+    /// The levels replace the whole book
+    pub snapshot: bool,
+    /// The venue's checksum of the book after the update, if it sends one
+    pub checksum: Option<u32>,
+}
+
+enum Input {
+    Quote(Option<Quote>),
+    Delta(BookDelta),
 }
 
 impl BookBuilder {
-    async fn process_quote(&self, quote: Quote) {
+    /// Apply `quote`, which the builder took off its channel at `received`
+    async fn process_quote(&mut self, quote: Quote, received: Instant) {
         let mut books = self.books.write().await;
 
         let book = books
-            .entry(quote.symbol.clone())
-            .or_insert_with(|| OrderBook::new(quote.symbol.clone()));
+            .entry(quote.symbol)
+            .or_insert_with(|| OrderBook::new(quote.symbol));
 
         book.update(&quote);
+        self.compactor.on_update(book);
+        self.snapshots.publish(book);
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(DepthEvent::Quote(quote.clone()), book);
+        }
+        BOOK_APPLY_LATENCY
+            .with_label_values(&[labels::symbol("book_apply_latency", &quote.symbol)])
+            .observe(received.elapsed().as_secs_f64());
+        if let Some(gauges) = &mut self.gauges {
+            gauges.on_update(book);
+        }
+        if let Some(stream) = &self.stream {
+            stream.publish_book(book);
+        }
+
+        ORDERBOOK_UPDATES
+            .with_label_values(&[labels::symbol("orderbook_updates", &quote.symbol)])
+            .inc();
+
+        drop(books);
+        if let Some(risk) = &self.risk {
+            risk.on_quote(&quote).await;
+        }
+        if let Some(feed) = &self.feed {
+            feed.publish(&FeedMessage::Quote(quote));
+        }
+    }
+
+    /// Apply `delta` and check the result against the venue's checksum,
+    /// clearing the book and asking for a snapshot on a mismatch
+    async fn process_delta(&mut self, delta: BookDelta, received: Instant) {
+        if !delta.snapshot && self.resyncing.contains(&delta.symbol) {
+            return;
+        }
+        let mut books = self.books.write().await;
+        let book = books
+            .entry(delta.symbol)
+            .or_insert_with(|| OrderBook::new(delta.symbol));
+
+        book.apply(&delta);
+        if let Some(expected) = delta.checksum {
+            if !self.checksums.verify(&delta.venue, book, expected) {
+                *book = OrderBook::new(delta.symbol);
+                self.snapshots.publish(book);
+                if let Some(recorder) = &mut self.recorder {
+                    let cleared = BookDelta { symbol: delta.symbol, venue: delta.venue, snapshot: true, ..BookDelta::default() };
+                    recorder.record(DepthEvent::Delta(cleared), book);
+                }
+                self.resyncing.insert(delta.symbol);
+                self.checksums.request_resync(&delta.venue, &delta.symbol);
+                return;
+            }
+        }
+        self.resyncing.remove(&delta.symbol);
+        self.compactor.on_update(book);
+        self.snapshots.publish(book);
+        let symbol = delta.symbol;
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(DepthEvent::Delta(delta), book);
+        }
 
+        BOOK_APPLY_LATENCY
+            .with_label_values(&[labels::symbol("book_apply_latency", &symbol)])
+            .observe(received.elapsed().as_secs_f64());
+        if let Some(gauges) = &mut self.gauges {
+            gauges.on_update(book);
+        }
+        if let Some(stream) = &self.stream {
+            stream.publish_book(book);
+        }
         ORDERBOOK_UPDATES
-            .with_label_values(&[&quote.symbol])
+            .with_label_values(&[labels::symbol("orderbook_updates", &symbol)])
             .inc();
     }
 
     pub async fn run(&mut self) {
-        while let Some(quote) = self.quote_rx.recv().await {
-            self.process_quote(quote).await;
+        loop {
+            if let Some(heartbeat) = &self.heartbeat {
+                heartbeat.beat();
+            }
+
+            let (quotes, deltas) = (&mut self.quote_rx, &mut self.deltas);
+            let next = async {
+                let delta = async {
+                    match deltas {
+                        Some(deltas) => deltas.recv().await,
+                        None => std::future::pending().await,
+                    }
+                };
+                tokio::select! {
+                    quote = quotes.recv() => Input::Quote(quote),
+                    Some(delta) = delta => Input::Delta(delta),
+                }
+            };
+            match tokio::time::timeout(IDLE_HEARTBEAT_INTERVAL, next).await {
+                Ok(Input::Quote(Some(quote))) => self.process_quote(quote, Instant::now()).await,
+                Ok(Input::Quote(None)) => break,
+                Ok(Input::Delta(delta)) => self.process_delta(delta, Instant::now()).await,
+                Err(_) => {}
+            }
+            if let Some(gauges) = &mut self.gauges {
+                gauges.refresh();
+            }
+            if self.compactor.due() {
+                let mut books = self.books.write().await;
+                self.compactor.compact(&mut books);
+                for book in books.values() {
+                    self.snapshots.publish(book);
+                }
+            }
         }
     }
 }
 
+/// Serializable copy of a book's price levels, best level first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookSnapshot {
+    pub symbol: String,
+    pub bids: Vec<(f64, f64)>,
+    pub asks: Vec<(f64, f64)>,
+}
+
+impl BookSnapshot {
+    pub fn best_bid(&self) -> Option<(f64, f64)> {
+        self.bids.first().copied()
+    }
+
+    pub fn best_ask(&self) -> Option<(f64, f64)> {
+        self.asks.first().copied()
+    }
+
+    pub fn mid(&self) -> Option<f64> {
+        Some((self.best_bid()?.0 + self.best_ask()?.0) / 2.0)
+    }
+}
+
 pub struct OrderBook {
-    symbol: String,
-    bids: BTreeMap<i64, f64>,
-    asks: BTreeMap<i64, f64>,
+    symbol: Symbol,
+    bids: BTreeMap<u64, f64>,
+    asks: BTreeMap<u64, f64>,
+}
+
+/// Map a price onto an ordered book key.
+///
+/// For positive, finite prices the IEEE-754 bit pattern sorts in the same
+/// order as the price itself, so the key round-trips exactly without the
+/// rounding a fixed-point multiplier would introduce.
+fn price_key(price: f64) -> u64 {
+    price.to_bits()
+}
+
+fn key_price(key: u64) -> f64 {
+    f64::from_bits(key)
 }
 
 impl OrderBook {
-    pub fn new(symbol: String) -> Self {
+    pub fn new(symbol: Symbol) -> Self {
         Self {
             symbol,
             bids: BTreeMap::new(),
@@ -46,29 +243,147 @@ impl OrderBook {
         }
     }
 
-    pub fn update(&mut self, quote: &Quote) {
-        const PRICE_MULTIPLIER: f64 = 100_000_000.0;
+    pub fn from_snapshot(snapshot: &BookSnapshot) -> Self {
+        let mut book = Self::new(snapshot.symbol.as_str().into());
+        for &(price, size) in &snapshot.bids {
+            book.bids.insert(price_key(price), size);
+        }
+        for &(price, size) in &snapshot.asks {
+            book.asks.insert(price_key(price), size);
+        }
+        book
+    }
+
+    pub fn snapshot(&self) -> BookSnapshot {
+        BookSnapshot {
+            symbol: self.symbol.to_string(),
+            bids: self.bids.iter().rev().map(|(&p, &s)| (key_price(p), s)).collect(),
+            asks: self.asks.iter().map(|(&p, &s)| (key_price(p), s)).collect(),
+        }
+    }
+
+    /// Like [`snapshot`](Self::snapshot), keeping the best `levels` levels
+    /// per side
+    pub fn snapshot_top(&self, levels: usize) -> BookSnapshot {
+        BookSnapshot {
+            symbol: self.symbol.to_string(),
+            bids: self.bids(levels).collect(),
+            asks: self.asks(levels).collect(),
+        }
+    }
 
+    pub fn symbol(&self) -> &str {
+        self.symbol.as_str()
+    }
+
+    pub fn update(&mut self, quote: &Quote) {
+        let bid_key = (quote.bid > 0.0 && quote.bid_size > 0.0).then(|| price_key(quote.bid));
+        let ask_key = (quote.ask > 0.0 && quote.ask_size > 0.0).then(|| price_key(quote.ask));
         if quote.bid > 0.0 {
-            let bid_price = (quote.bid * PRICE_MULTIPLIER) as i64;
-            self.bids.insert(bid_price, quote.bid_size);
+            let bid_price = price_key(quote.bid);
+            if quote.bid_size > 0.0 {
+                self.bids.insert(bid_price, quote.bid_size);
+                // Drop any asks the new bid has crossed. The market moved up
+                // through them, so asks left below the quote's own ask are
+                // stale too.
+                let crossed = self.asks.first_key_value().is_some_and(|(&p, _)| p <= bid_price);
+                let floor = ask_key.filter(|_| crossed).unwrap_or(0);
+                self.asks.retain(|&p, _| p > bid_price && p >= floor);
+            } else {
+                self.bids.remove(&bid_price);
+            }
         }
         if quote.ask > 0.0 {
-            let ask_price = (quote.ask * PRICE_MULTIPLIER) as i64;
-            self.asks.insert(ask_price, quote.ask_size);
+            let ask_price = price_key(quote.ask);
+            if quote.ask_size > 0.0 {
+                self.asks.insert(ask_price, quote.ask_size);
+                // Drop any bids the new ask has crossed, and on the way down
+                // any bids left above the quote's own bid
+                let crossed = self.bids.last_key_value().is_some_and(|(&p, _)| p >= ask_price);
+                let ceiling = bid_key.filter(|_| crossed).unwrap_or(u64::MAX);
+                self.bids.retain(|&p, _| p < ask_price && p <= ceiling);
+            } else {
+                self.asks.remove(&ask_price);
+            }
         }
     }
 
+    /// Apply a venue's depth update
+    pub fn apply(&mut self, delta: &BookDelta) {
+        if delta.snapshot {
+            self.bids.clear();
+            self.asks.clear();
+        }
+        for (levels, updates) in [(&mut self.bids, &delta.bids), (&mut self.asks, &delta.asks)] {
+            for &(price, size) in updates {
+                if size > 0.0 {
+                    levels.insert(price_key(price), size);
+                } else {
+                    levels.remove(&price_key(price));
+                }
+            }
+        }
+    }
+
+    /// The best `levels` bid levels, best first
+    pub fn bids(&self, levels: usize) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.bids.iter().rev().take(levels).map(|(&p, &s)| (key_price(p), s))
+    }
+
+    /// The best `levels` ask levels, best first
+    pub fn asks(&self, levels: usize) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.asks.iter().take(levels).map(|(&p, &s)| (key_price(p), s))
+    }
+
     pub fn best_bid(&self) -> Option<(f64, f64)> {
-        const PRICE_MULTIPLIER: f64 = 100_000_000.0;
         self.bids.iter().next_back()
-            .map(|(&p, &s)| ((p as f64) / PRICE_MULTIPLIER, s))
+            .map(|(&p, &s)| (key_price(p), s))
     }
 
     pub fn best_ask(&self) -> Option<(f64, f64)> {
-        const PRICE_MULTIPLIER: f64 = 100_000_000.0;
         self.asks.iter().next()
-            .map(|(&p, &s)| ((p as f64) / PRICE_MULTIPLIER, s))
+            .map(|(&p, &s)| (key_price(p), s))
+    }
+
+    /// Number of bid and ask levels
+    pub fn level_counts(&self) -> (usize, usize) {
+        (self.bids.len(), self.asks.len())
+    }
+
+    /// Keep the best `levels` levels on each side, returning how many were
+    /// dropped
+    pub fn truncate(&mut self, levels: usize) -> usize {
+        let mut dropped = 0;
+        while self.bids.len() > levels {
+            self.bids.pop_first();
+            dropped += 1;
+        }
+        while self.asks.len() > levels {
+            self.asks.pop_last();
+            dropped += 1;
+        }
+        dropped
+    }
+
+    /// Drop levels more than `bps` basis points from the mid, returning how
+    /// many were dropped; one-sided books are left alone
+    pub fn prune_beyond(&mut self, bps: f64) -> usize {
+        let (Some((bid, _)), Some((ask, _))) = (self.best_bid(), self.best_ask()) else {
+            return 0;
+        };
+        let mid = (bid + ask) / 2.0;
+        let (floor, ceiling) = (price_key((mid * (1.0 - bps / 10_000.0)).max(0.0)), price_key(mid * (1.0 + bps / 10_000.0)));
+        let before = self.bids.len() + self.asks.len();
+        self.bids = self.bids.split_off(&floor);
+        self.asks.split_off(&ceiling.saturating_add(1));
+        before - self.bids.len() - self.asks.len()
+    }
+
+    /// Total size of the best `levels` price levels on each side
+    pub fn depth(&self, levels: usize) -> (f64, f64) {
+        let bids = self.bids.values().rev().take(levels).sum();
+        let asks = self.asks.values().take(levels).sum();
+        (bids, asks)
     }
 }
 
@@ -83,7 +398,7 @@ mod test {
 
     #[tokio::test]
     async fn test_order_book_creation() {
-        let book = OrderBook::new("BTCUSDT".to_string());
+        let book = OrderBook::new("BTCUSDT".into());
         assert_eq!(book.symbol, "BTCUSDT");
         assert!(book.bids.is_empty());
         assert!(book.asks.is_empty());
@@ -92,15 +407,15 @@ mod test {
 
     #[tokio::test]
     async fn test_order_book_update() {
-        let mut book = OrderBook::new("BTCUSDT".to_string());
+        let mut book = OrderBook::new("BTCUSDT".into());
 
         let quote = Quote {
-            symbol: "BTCUSDT".to_string(),
+            symbol: "BTCUSDT".into(),
             bid: 50000.0,
             ask: 50001.0,
             bid_size: 1.5,
             ask_size: 2.5,
-            venue: "TEST".to_string(),
+            venue: "TEST".into(),
             timestamp: 0,
         };
 
@@ -131,15 +446,15 @@ mod test {
         assert_eq!(denormalized, price);
 
         // Test in the context of order book
-        let mut book = OrderBook::new("BTCUSDT".to_string());
+        let mut book = OrderBook::new("BTCUSDT".into());
 
         let quote = Quote {
-            symbol: "BTCUSDT".to_string(),
+            symbol: "BTCUSDT".into(),
             bid: price,
             ask: price + 0.00000001, // Test smallest price increment
             bid_size: 1.0,
             ask_size: 1.0,
-            venue: "TEST".to_string(),
+            venue: "TEST".into(),
             timestamp: 0,
         };
 
@@ -155,35 +470,35 @@ mod test {
 
     #[tokio::test]
     async fn test_order_book_multiple_levels() {
-        let mut book = OrderBook::new("BTCUSDT".to_string());
+        let mut book = OrderBook::new("BTCUSDT".into());
 
         // Add multiple price levels
         let quotes = [
             Quote {
-                symbol: "BTCUSDT".to_string(),
+                symbol: "BTCUSDT".into(),
                 bid: 50000.0,
                 ask: 50010.0,
                 bid_size: 1.0,
                 ask_size: 1.0,
-                venue: "TEST".to_string(),
+                venue: "TEST".into(),
                 timestamp: 0,
             },
             Quote {
-                symbol: "BTCUSDT".to_string(),
+                symbol: "BTCUSDT".into(),
                 bid: 49990.0,
                 ask: 50020.0,
                 bid_size: 2.0,
                 ask_size: 2.0,
-                venue: "TEST".to_string(),
+                venue: "TEST".into(),
                 timestamp: 0,
             },
             Quote {
-                symbol: "BTCUSDT".to_string(),
+                symbol: "BTCUSDT".into(),
                 bid: 49980.0,
                 ask: 50030.0,
                 bid_size: 3.0,
                 ask_size: 3.0,
-                venue: "TEST".to_string(),
+                venue: "TEST".into(),
                 timestamp: 0,
             },
         ];
@@ -209,16 +524,16 @@ mod test {
 
     #[tokio::test]
     async fn test_order_book_update_existing_level() {
-        let mut book = OrderBook::new("BTCUSDT".to_string());
+        let mut book = OrderBook::new("BTCUSDT".into());
 
         // Add initial quote
         let quote1 = Quote {
-            symbol: "BTCUSDT".to_string(),
+            symbol: "BTCUSDT".into(),
             bid: 50000.0,
             ask: 50010.0,
             bid_size: 1.0,
             ask_size: 1.0,
-            venue: "TEST".to_string(),
+            venue: "TEST".into(),
             timestamp: 0,
         };
 
@@ -226,12 +541,12 @@ mod test {
 
         // Update with new sizes at same prices
         let quote2 = Quote {
-            symbol: "BTCUSDT".to_string(),
+            symbol: "BTCUSDT".into(),
             bid: 50000.0,
             ask: 50010.0,
             bid_size: 2.0,
             ask_size: 3.0,
-            venue: "TEST".to_string(),
+            venue: "TEST".into(),
             timestamp: 0,
         };
 
@@ -251,16 +566,16 @@ mod test {
 
     #[tokio::test]
     async fn test_order_book_remove_level() {
-        let mut book = OrderBook::new("BTCUSDT".to_string());
+        let mut book = OrderBook::new("BTCUSDT".into());
 
         // Add initial quote
         let quote1 = Quote {
-            symbol: "BTCUSDT".to_string(),
+            symbol: "BTCUSDT".into(),
             bid: 50000.0,
             ask: 50010.0,
             bid_size: 1.0,
             ask_size: 1.0,
-            venue: "TEST".to_string(),
+            venue: "TEST".into(),
             timestamp: 0,
         };
 
@@ -268,12 +583,12 @@ mod test {
 
         // Add a second level
         let quote2 = Quote {
-            symbol: "BTCUSDT".to_string(),
+            symbol: "BTCUSDT".into(),
             bid: 49990.0,
             ask: 50020.0,
             bid_size: 2.0,
             ask_size: 2.0,
-            venue: "TEST".to_string(),
+            venue: "TEST".into(),
             timestamp: 0,
         };
 
@@ -283,12 +598,12 @@ mod test {
 
         // Remove the top bid and bottom ask by setting size to 0
         let quote3 = Quote {
-            symbol: "BTCUSDT".to_string(),
+            symbol: "BTCUSDT".into(),
             bid: 50000.0,
             ask: 50010.0,
             bid_size: 0.0, // This should remove the level
             ask_size: 0.0, // This should remove the level
-            venue: "TEST".to_string(),
+            venue: "TEST".into(),
             timestamp: 0,
         };
 
@@ -308,9 +623,209 @@ mod test {
         assert_eq!(ask_size, 2.0);
     }
 
+    #[tokio::test]
+    async fn test_crossing_quote_purges_stale_levels() {
+        let mut book = OrderBook::new("BTCUSDT".into());
+        let quote = |bid: f64, ask: f64| Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "TEST".into(),
+            timestamp: 0,
+        };
+        book.update(&quote(50000.0, 50010.0));
+        book.update(&quote(49990.0, 50020.0));
+
+        // The market moves up through the old asks, and the ask left below
+        // the new best ask is gone too
+        book.update(&quote(50015.0, 50030.0));
+        assert_eq!(book.best_bid().unwrap().0, 50015.0);
+        assert_eq!(book.best_ask().unwrap().0, 50030.0);
+        assert_eq!(book.level_counts(), (3, 1));
+
+        // And back down through every bid
+        book.update(&quote(0.0, 49980.0));
+        assert!(book.best_bid().is_none());
+        assert_eq!(book.best_ask().unwrap().0, 49980.0);
+
+        // Bids and asks never overlap after an update
+        book.update(&quote(49970.0, 49975.0));
+        assert!(book.best_bid().unwrap().0 < book.best_ask().unwrap().0);
+
+        // Moving down through the bids drops those left above the new bid
+        book.update(&quote(49960.0, 49975.0));
+        book.update(&quote(49950.0, 49965.0));
+        assert_eq!(book.best_bid().unwrap().0, 49950.0);
+        assert_eq!(book.level_counts().0, 1);
+    }
+
+    #[tokio::test]
+    async fn test_order_book_snapshot_round_trip() {
+        let mut book = OrderBook::new("BTCUSDT".into());
+        for (bid, ask) in [(50000.0, 50010.0), (49990.0, 50020.0)] {
+            book.update(&Quote {
+                symbol: "BTCUSDT".into(),
+                bid,
+                ask,
+                bid_size: 1.0,
+                ask_size: 2.0,
+                venue: "TEST".into(),
+                timestamp: 0,
+            });
+        }
+
+        let snapshot = book.snapshot();
+        assert_eq!(snapshot.bids, vec![(50000.0, 1.0), (49990.0, 1.0)]);
+        assert_eq!(snapshot.asks, vec![(50010.0, 2.0), (50020.0, 2.0)]);
+
+        let restored = OrderBook::from_snapshot(&snapshot);
+        assert_eq!(restored.best_bid(), book.best_bid());
+        assert_eq!(restored.best_ask(), book.best_ask());
+        assert_eq!(restored.bids.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_apply_latency_recorded_per_symbol() {
+        let (quote_tx, quote_rx) = mpsc::channel(8);
+        let mut builder = BookBuilder {
+            books: Arc::new(RwLock::new(HashMap::new())),
+            quote_rx,
+            heartbeat: None,
+            feed: None,
+            stream: None,
+            gauges: None,
+            deltas: None,
+            checksums: BookChecksums::default(),
+            resyncing: HashSet::new(),
+            recorder: None,
+            compactor: Compactor::default(),
+            snapshots: BookSnapshots::default(),
+            risk: None,
+        };
+        let histogram = BOOK_APPLY_LATENCY.with_label_values(&["APPLYUSDT"]);
+        let before = histogram.get_sample_count();
+
+        for bid in [100.0, 101.0] {
+            quote_tx.send(Quote {
+                symbol: "APPLYUSDT".into(),
+                bid,
+                ask: bid + 1.0,
+                bid_size: 1.0,
+                ask_size: 1.0,
+                venue: "TEST".into(),
+                timestamp: 0,
+            }).await.unwrap();
+        }
+        drop(quote_tx);
+        builder.run().await;
+
+        assert_eq!(histogram.get_sample_count() - before, 2);
+        assert!(builder.books.read().await.contains_key("APPLYUSDT"));
+        assert_eq!(builder.snapshots.get("APPLYUSDT").unwrap().mid(), Some(101.5));
+    }
+
+    #[tokio::test]
+    async fn test_top_of_book_gauges() {
+        use crate::metrics::{BOOK_BEST_ASK, BOOK_BEST_BID, BOOK_SPREAD_BPS, BOOK_STALENESS};
+
+        let (quote_tx, quote_rx) = mpsc::channel(8);
+        let mut builder = BookBuilder {
+            books: Arc::new(RwLock::new(HashMap::new())),
+            quote_rx,
+            heartbeat: None,
+            feed: None,
+            stream: None,
+            gauges: Some(BookGauges::default()),
+            deltas: None,
+            checksums: BookChecksums::default(),
+            resyncing: HashSet::new(),
+            recorder: None,
+            compactor: Compactor::default(),
+            snapshots: BookSnapshots::default(),
+            risk: None,
+        };
+        quote_tx.send(Quote {
+            symbol: "GAUGEUSDT".into(),
+            bid: 99.0,
+            ask: 101.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "TEST".into(),
+            timestamp: 0,
+        }).await.unwrap();
+        drop(quote_tx);
+        builder.run().await;
+
+        assert_eq!(BOOK_BEST_BID.with_label_values(&["GAUGEUSDT"]).get(), 99.0);
+        assert_eq!(BOOK_BEST_ASK.with_label_values(&["GAUGEUSDT"]).get(), 101.0);
+        assert_eq!(BOOK_SPREAD_BPS.with_label_values(&["GAUGEUSDT"]).get(), 200.0);
+
+        let gauges = builder.gauges.as_mut().unwrap();
+        gauges.refreshed = None;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        gauges.refresh();
+        assert!(BOOK_STALENESS.with_label_values(&["GAUGEUSDT"]).get() >= 0.02);
+    }
+
+    #[tokio::test]
+    async fn test_checksum_mismatch_resyncs() {
+        use crate::book::checksum::Okx;
+        use crate::metrics::BOOK_CHECKSUM_MISMATCHES;
+
+        let (_quote_tx, quote_rx) = mpsc::channel(8);
+        let (delta_tx, delta_rx) = mpsc::channel(8);
+        let (resync_tx, mut resync_rx) = mpsc::channel(8);
+        let books = Arc::new(RwLock::new(HashMap::new()));
+        let mut builder = BookBuilder {
+            books: Arc::clone(&books),
+            quote_rx,
+            heartbeat: None,
+            feed: None,
+            stream: None,
+            gauges: None,
+            deltas: Some(delta_rx),
+            checksums: BookChecksums::new().with_venue("OKX", Okx::default()).with_resync(resync_tx),
+            resyncing: HashSet::new(),
+            recorder: None,
+            compactor: Compactor::default(),
+            snapshots: BookSnapshots::default(),
+            risk: None,
+        };
+        let delta = |bids: Vec<(f64, f64)>, snapshot, checksum: &[u8]| BookDelta {
+            symbol: "CRC-USDT".into(),
+            venue: "OKX".into(),
+            bids,
+            asks: vec![],
+            snapshot,
+            checksum: Some(crc32fast::hash(checksum)),
+        };
+        let mismatches = BOOK_CHECKSUM_MISMATCHES.with_label_values(&["OKX", "CRC-USDT"]);
+        let before = mismatches.get();
+
+        delta_tx.send(delta(vec![(100.0, 1.0)], true, b"100:1")).await.unwrap();
+        // An update was lost: the venue's book also has 99
+        delta_tx.send(delta(vec![(98.0, 1.0)], false, b"100:1:99:1:98:1")).await.unwrap();
+        delta_tx.send(delta(vec![(97.0, 1.0)], false, b"ignored")).await.unwrap();
+        let run = tokio::spawn(async move { builder.run().await });
+
+        assert_eq!(resync_rx.recv().await.unwrap(), Resync { venue: "OKX".to_string(), symbol: "CRC-USDT".to_string() });
+        assert_eq!(mismatches.get() - before, 1.0);
+        // Updates are ignored until the snapshot arrives
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(books.read().await["CRC-USDT"].best_bid(), None);
+
+        delta_tx.send(delta(vec![(100.0, 1.0), (99.0, 1.0)], true, b"100:1:99:1")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(books.read().await["CRC-USDT"].best_bid(), Some((100.0, 1.0)));
+        assert_eq!(mismatches.get() - before, 1.0);
+        run.abort();
+    }
+
     #[tokio::test]
     async fn test_order_book_empty() {
-        let book = OrderBook::new("BTCUSDT".to_string());
+        let book = OrderBook::new("BTCUSDT".into());
 
         // Empty book should return None for best bid/ask
         assert!(book.best_bid().is_none());
@@ -324,7 +839,7 @@ mod test {
         // Insert a book
         {
             let mut books_write = books.write().await;
-            books_write.insert("BTCUSDT".to_string(), OrderBook::new("BTCUSDT".to_string()));
+            books_write.insert("BTCUSDT".to_string(), OrderBook::new("BTCUSDT".into()));
         }
 
         // Create a bunch of update tasks
@@ -334,12 +849,12 @@ mod test {
             let task = task::spawn(async move {
                 let price_offset = i as f64 * 10.0;
                 let quote = Quote {
-                    symbol: "BTCUSDT".to_string(),
+                    symbol: "BTCUSDT".into(),
                     bid: 50000.0 - price_offset,
                     ask: 50010.0 + price_offset,
                     bid_size: 1.0,
                     ask_size: 1.0,
-                    venue: "TEST".to_string(),
+                    venue: "TEST".into(),
                     timestamp: 0,
                 };
 
@@ -374,16 +889,16 @@ mod test {
 
     #[tokio::test]
     async fn test_extreme_price_values() {
-        let mut book = OrderBook::new("BTCUSDT".to_string());
+        let mut book = OrderBook::new("BTCUSDT".into());
 
         // Test with very small prices
         let quote1 = Quote {
-            symbol: "BTCUSDT".to_string(),
+            symbol: "BTCUSDT".into(),
             bid: 0.00000001,
             ask: 0.00000002,
             bid_size: 1.0,
             ask_size: 1.0,
-            venue: "TEST".to_string(),
+            venue: "TEST".into(),
             timestamp: 0,
         };
 
@@ -397,12 +912,12 @@ mod test {
 
         // Test with very large prices
         let quote2 = Quote {
-            symbol: "BTCUSDT".to_string(),
+            symbol: "BTCUSDT".into(),
             bid: 1_000_000.0,
             ask: 1_000_001.0,
             bid_size: 1.0,
             ask_size: 1.0,
-            venue: "TEST".to_string(),
+            venue: "TEST".into(),
             timestamp: 0,
         };
 
@@ -414,4 +929,27 @@ mod test {
         assert_eq!(bid_price, 1_000_000.0);
         assert_eq!(ask_price, 1_000_001.0);
     }
+
+    #[test]
+    fn test_depth_limits_drop_far_levels() {
+        let deep = BookDelta {
+            symbol: "BTCUSDT".into(),
+            venue: "TEST".into(),
+            bids: vec![(100.0, 1.0), (99.0, 1.0), (98.0, 1.0), (90.0, 1.0)],
+            asks: vec![(101.0, 1.0), (102.0, 1.0), (110.0, 1.0)],
+            snapshot: true,
+            checksum: None,
+        };
+        let mut book = OrderBook::new("BTCUSDT".into());
+        book.apply(&deep);
+        assert_eq!(book.truncate(2), 3);
+        assert_eq!(book.bids(5).collect::<Vec<_>>(), vec![(100.0, 1.0), (99.0, 1.0)]);
+        assert_eq!(book.asks(5).collect::<Vec<_>>(), vec![(101.0, 1.0), (102.0, 1.0)]);
+
+        // 5% either side of the 100.5 mid
+        book.apply(&deep);
+        assert_eq!(book.prune_beyond(500.0), 2);
+        assert_eq!(book.level_counts(), (3, 2));
+        assert_eq!(book.prune_beyond(500.0), 0);
+    }
 }
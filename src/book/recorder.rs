@@ -0,0 +1,230 @@
+//! Depth-of-book recording.
+//!
+//! The book builder records every quote and depth update it applies, and
+//! periodically a full snapshot of the book it produced. Replaying the
+//! recording through [`DepthReplay`] applies them the same way, so the
+//! books are rebuilt exactly; a snapshot lets a replay start cold from any
+//! point in the recording.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::error::HftError;
+use crate::types::{Quote, Symbol};
+use crate::venues::frames;
+use super::{BookDelta, OrderBook};
+
+const DEFAULT_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Something the book builder applied to a book
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DepthEvent {
+    Quote(Quote),
+    /// A depth update, or a snapshot of the book as the builder had it
+    Delta(BookDelta),
+}
+
+impl DepthEvent {
+    pub fn symbol(&self) -> &str {
+        match self {
+            DepthEvent::Quote(quote) => &quote.symbol,
+            DepthEvent::Delta(delta) => &delta.symbol,
+        }
+    }
+
+    pub fn venue(&self) -> &str {
+        match self {
+            DepthEvent::Quote(quote) => &quote.venue,
+            DepthEvent::Delta(delta) => &delta.venue,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedDepth {
+    /// Milliseconds since the Unix epoch when the event was applied
+    pub received_at: u64,
+    pub event: DepthEvent,
+}
+
+/// Where depth is recorded and how often books are snapshotted
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthRecordingConfig {
+    pub path: PathBuf,
+    pub snapshot_interval: Duration,
+}
+
+impl DepthRecordingConfig {
+    /// Read `HFT_RECORD_DEPTH` and `HFT_RECORD_DEPTH_SNAPSHOT_SECS`
+    /// (default 60); returns `None` when recording is off
+    pub fn from_env() -> Option<Self> {
+        let path = PathBuf::from(std::env::var("HFT_RECORD_DEPTH").ok()?);
+        let snapshot_interval = std::env::var("HFT_RECORD_DEPTH_SNAPSHOT_SECS").ok()
+            .and_then(|secs| secs.parse().ok())
+            .map_or(DEFAULT_SNAPSHOT_INTERVAL, Duration::from_secs);
+        Some(Self { path, snapshot_interval })
+    }
+}
+
+/// Records the book builder's events to a file, one JSON event per line,
+/// written by a dedicated thread
+#[derive(Debug)]
+pub struct DepthRecorder {
+    tx: mpsc::UnboundedSender<RecordedDepth>,
+    snapshot_interval: Duration,
+    /// When each book was last snapshotted
+    snapshots: HashMap<String, Instant>,
+}
+
+impl DepthRecorder {
+    /// Append to the recording at `path`, creating it if needed
+    pub fn open(config: &DepthRecordingConfig) -> Result<Self, HftError> {
+        Ok(Self {
+            tx: frames::spawn_writer(&config.path, "depth-recorder")?,
+            snapshot_interval: config.snapshot_interval,
+            snapshots: HashMap::new(),
+        })
+    }
+
+    /// Record `event`, which left the book as `book`, followed by a
+    /// snapshot of `book` if its last one is older than the interval
+    pub(crate) fn record(&mut self, event: DepthEvent, book: &OrderBook) {
+        let received_at = chrono::Utc::now().timestamp_millis() as u64;
+        let venue = event.venue().to_string();
+        let _ = self.tx.send(RecordedDepth { received_at, event });
+
+        let due = self.snapshots.get(book.symbol()).is_none_or(|at| at.elapsed() >= self.snapshot_interval);
+        if due {
+            self.snapshots.insert(book.symbol().to_string(), Instant::now());
+            let snapshot = book.snapshot();
+            let _ = self.tx.send(RecordedDepth {
+                received_at,
+                event: DepthEvent::Delta(BookDelta {
+                    symbol: snapshot.symbol.into(),
+                    venue: venue.into(),
+                    bids: snapshot.bids,
+                    asks: snapshot.asks,
+                    snapshot: true,
+                    checksum: None,
+                }),
+            });
+        }
+    }
+}
+
+/// Read a recording made by [`DepthRecorder`], oldest event first
+pub fn read_depth(path: &Path) -> Result<Vec<RecordedDepth>, HftError> {
+    frames::read_lines(path)
+}
+
+/// Rebuilds books from a depth recording
+#[derive(Default)]
+pub struct DepthReplay {
+    books: HashMap<Symbol, OrderBook>,
+}
+
+impl DepthReplay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a recorded event; returns the book it changed
+    pub fn apply(&mut self, record: &RecordedDepth) -> &OrderBook {
+        let symbol = record.event.symbol();
+        let book = self.books
+            .entry(symbol.into())
+            .or_insert_with(|| OrderBook::new(symbol.into()));
+        match &record.event {
+            DepthEvent::Quote(quote) => book.update(quote),
+            DepthEvent::Delta(delta) => book.apply(delta),
+        }
+        book
+    }
+
+    pub fn book(&self, symbol: &str) -> Option<&OrderBook> {
+        self.books.get(symbol)
+    }
+
+    pub fn books(&self) -> impl Iterator<Item = &OrderBook> {
+        self.books.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>) -> BookDelta {
+        BookDelta {
+            symbol: "BTCUSDT".into(),
+            venue: "SIM".into(),
+            bids,
+            asks,
+            ..BookDelta::default()
+        }
+    }
+
+    #[test]
+    fn test_record_and_rebuild() {
+        let dir = std::env::temp_dir().join(format!("hft_depth_{}", std::process::id()));
+        let config = DepthRecordingConfig { path: dir.join("depth.jsonl"), snapshot_interval: Duration::from_secs(3600) };
+        let _ = std::fs::remove_file(&config.path);
+
+        let mut recorder = DepthRecorder::open(&config).unwrap();
+        let mut live = OrderBook::new("BTCUSDT".into());
+        let events = vec![
+            DepthEvent::Delta(delta(vec![(100.0, 1.0), (99.0, 2.0)], vec![(101.0, 1.0), (102.0, 3.0)])),
+            DepthEvent::Delta(delta(vec![(99.0, 0.0), (98.0, 4.0)], vec![])),
+            DepthEvent::Quote(Quote {
+                symbol: "BTCUSDT".into(),
+                bid: 100.5,
+                ask: 101.0,
+                bid_size: 1.0,
+                ask_size: 0.5,
+                venue: "SIM".into(),
+                timestamp: 0,
+            }),
+        ];
+        for event in events {
+            match &event {
+                DepthEvent::Quote(quote) => live.update(quote),
+                DepthEvent::Delta(delta) => live.apply(delta),
+            }
+            recorder.record(event, &live);
+        }
+        drop(recorder);
+
+        let mut records = Vec::new();
+        for _ in 0..100 {
+            records = read_depth(&config.path).unwrap();
+            if records.len() == 4 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        // The first event is followed by the book's first snapshot
+        assert_eq!(records.len(), 4);
+        assert!(matches!(&records[1].event, DepthEvent::Delta(d) if d.snapshot));
+
+        let mut replay = DepthReplay::new();
+        for record in &records {
+            replay.apply(record);
+        }
+        let rebuilt = replay.book("BTCUSDT").unwrap();
+        assert_eq!(rebuilt.bids(10).collect::<Vec<_>>(), live.bids(10).collect::<Vec<_>>());
+        assert_eq!(rebuilt.asks(10).collect::<Vec<_>>(), vec![(101.0, 0.5), (102.0, 3.0)]);
+
+        // Starting cold from the snapshot gives the same book
+        let mut cold = DepthReplay::new();
+        for record in &records[1..] {
+            cold.apply(record);
+        }
+        assert_eq!(cold.book("BTCUSDT").unwrap().snapshot().bids, rebuilt.snapshot().bids);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use arc_swap::ArcSwap;
+
+use crate::types::Symbol;
+use super::{BookSnapshot, OrderBook};
+
+/// Levels per side kept in published snapshots by default
+pub const DEFAULT_SNAPSHOT_DEPTH: usize = 20;
+
+/// Immutable copies of the books' top levels, republished by the book
+/// builder after every update it applies.
+///
+/// Readers load the latest snapshot without a lock, so they never wait on
+/// the builder and never see an update half applied. Each symbol has a
+/// slot of its own; the map of slots is only copied when a symbol first
+/// appears.
+#[derive(Clone)]
+pub struct BookSnapshots {
+    depth: usize,
+    books: Arc<ArcSwap<HashMap<Symbol, Arc<ArcSwap<BookSnapshot>>>>>,
+}
+
+impl Default for BookSnapshots {
+    fn default() -> Self {
+        Self::new(DEFAULT_SNAPSHOT_DEPTH)
+    }
+}
+
+impl fmt::Debug for BookSnapshots {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BookSnapshots")
+            .field("depth", &self.depth)
+            .field("symbols", &self.books.load().len())
+            .finish()
+    }
+}
+
+impl BookSnapshots {
+    /// Snapshots keeping `depth` levels per side
+    pub fn new(depth: usize) -> Self {
+        Self { depth, books: Arc::default() }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The latest snapshot of `symbol`'s book
+    pub fn get(&self, symbol: &str) -> Option<Arc<BookSnapshot>> {
+        self.books.load().get(symbol).map(|slot| slot.load_full())
+    }
+
+    pub fn symbols(&self) -> Vec<Symbol> {
+        self.books.load().keys().copied().collect()
+    }
+
+    /// Replace `book`'s snapshot with its current state
+    pub(crate) fn publish(&self, book: &OrderBook) {
+        let snapshot = Arc::new(book.snapshot_top(self.depth));
+        if let Some(slot) = self.books.load().get(book.symbol()) {
+            slot.store(snapshot);
+            return;
+        }
+        let symbol = Symbol::new(book.symbol());
+        self.books.rcu(|books| {
+            let mut books = HashMap::clone(books);
+            books.entry(symbol)
+                .or_insert_with(|| Arc::new(ArcSwap::new(Arc::clone(&snapshot))))
+                .store(Arc::clone(&snapshot));
+            books
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Quote;
+
+    #[test]
+    fn test_snapshots_unchanged_by_later_updates() {
+        let snapshots = BookSnapshots::new(1);
+        let mut book = OrderBook::new("BTCUSDT".into());
+        let quote = |bid: f64| Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask: 101.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "TEST".into(),
+            timestamp: 0,
+        };
+        assert!(snapshots.get("BTCUSDT").is_none());
+
+        book.update(&quote(100.0));
+        snapshots.publish(&book);
+        let held = snapshots.get("BTCUSDT").unwrap();
+        book.update(&quote(100.5));
+        snapshots.publish(&book);
+
+        assert_eq!(held.bids, vec![(100.0, 1.0)]);
+        let latest = snapshots.get("BTCUSDT").unwrap();
+        // Only the top level is kept
+        assert_eq!(latest.bids, vec![(100.5, 1.0)]);
+        assert_eq!(latest.best_ask(), Some((101.0, 1.0)));
+        assert_eq!(snapshots.symbols().len(), 1);
+    }
+}
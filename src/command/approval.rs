@@ -0,0 +1,143 @@
+//! Two-man rule for destructive admin actions.
+//!
+//! A guarded action only runs once two different tokens have asked for it
+//! within the approval window: the first request is held as pending and
+//! the second, from another principal, approves and runs it. Both must
+//! carry the same body, so an approval runs exactly what was asked for.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// How long a request waits for its approval unless configured
+const DEFAULT_WINDOW: Duration = Duration::from_secs(60);
+
+/// What a guarded request may do next
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Approval {
+    /// Not guarded, or approved by a second principal; `requester` is who
+    /// asked first
+    Granted { requester: Option<String> },
+    /// Held until another principal repeats it within `expires_in`
+    Pending { expires_in: Duration },
+}
+
+struct Pending {
+    requester: String,
+    expires_at: Instant,
+}
+
+/// Actions that need a second principal's approval, matched by path prefix
+/// on requests that change state
+#[derive(Clone, Default)]
+pub struct TwoManRule {
+    paths: Vec<String>,
+    window: Duration,
+    pending: Arc<Mutex<HashMap<String, Pending>>>,
+}
+
+impl TwoManRule {
+    pub fn new(window: Duration) -> Self {
+        Self { window, ..Self::default() }
+    }
+
+    /// Require approval for admin paths starting with `prefix`
+    pub fn with_path(mut self, prefix: &str) -> Self {
+        self.paths.push(prefix.to_string());
+        self
+    }
+
+    /// Read `HFT_TWO_MAN_RULE` as comma separated path prefixes, e.g.
+    /// `/admin/flatten,/admin/kill-switch/release`, and
+    /// `HFT_TWO_MAN_WINDOW_SECS`; returns `None` when no paths are set
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var("HFT_TWO_MAN_RULE").ok()?;
+        let window = std::env::var("HFT_TWO_MAN_WINDOW_SECS").ok()
+            .and_then(|secs| secs.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_WINDOW);
+        Some(Self::parse(&spec, window)).filter(|rule| rule.is_enabled())
+    }
+
+    fn parse(spec: &str, window: Duration) -> Self {
+        let mut rule = Self::new(window);
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            if entry.starts_with("/admin/") {
+                rule = rule.with_path(entry);
+            } else {
+                warn!(entry = entry, "Ignoring two-man rule path outside /admin/");
+            }
+        }
+        rule
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.paths.is_empty()
+    }
+
+    pub fn guards(&self, path: &str) -> bool {
+        self.paths.iter().any(|prefix| path.starts_with(prefix.as_str()))
+    }
+
+    /// Record `principal` asking for `action` on `path` with a request
+    /// body hashing to `body`; a request with another body is a different
+    /// action and waits for its own approval
+    pub fn check(&self, principal: &str, action: &str, path: &str, body: &str) -> Approval {
+        if !self.guards(path) {
+            return Approval::Granted { requester: None };
+        }
+        let key = format!("{} {}", action, body);
+        let now = Instant::now();
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, p| p.expires_at > now);
+
+        match pending.get(&key) {
+            Some(p) if p.requester != principal => {
+                let requester = pending.remove(&key).map(|p| p.requester);
+                Approval::Granted { requester }
+            }
+            // Asking again does not extend the window
+            Some(p) => Approval::Pending { expires_in: p.expires_at - now },
+            None => {
+                pending.insert(key, Pending { requester: principal.to_string(), expires_at: now + self.window });
+                Approval::Pending { expires_in: self.window }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_principal_approves() {
+        let rule = TwoManRule::parse("/admin/flatten, flatten", Duration::from_secs(60));
+        assert_eq!(rule.paths, vec!["/admin/flatten".to_string()]);
+        let action = "POST /admin/flatten";
+
+        assert_eq!(rule.check("alice", "POST /admin/toggles/symbol/X/disable", "/admin/toggles/symbol/X/disable", ""), Approval::Granted { requester: None });
+        assert!(matches!(rule.check("alice", action, "/admin/flatten", ""), Approval::Pending { .. }));
+        assert!(matches!(rule.check("alice", action, "/admin/flatten", ""), Approval::Pending { .. }));
+        assert_eq!(rule.check("bob", action, "/admin/flatten", ""), Approval::Granted { requester: Some("alice".to_string()) });
+        // Approval is used up
+        assert!(matches!(rule.check("bob", action, "/admin/flatten", ""), Approval::Pending { .. }));
+
+        let expiring = TwoManRule::new(Duration::ZERO).with_path("/admin/flatten");
+        assert!(matches!(expiring.check("alice", action, "/admin/flatten", ""), Approval::Pending { .. }));
+        assert!(matches!(expiring.check("bob", action, "/admin/flatten", ""), Approval::Pending { .. }));
+    }
+
+    #[test]
+    fn test_approval_is_bound_to_the_body() {
+        let rule = TwoManRule::new(Duration::from_secs(60)).with_path("/admin/flatten");
+        let action = "POST /admin/flatten";
+
+        assert!(matches!(rule.check("alice", action, "/admin/flatten", "strategy-mm"), Approval::Pending { .. }));
+        // Bob asking with another body does not approve Alice's request
+        assert!(matches!(rule.check("bob", action, "/admin/flatten", "portfolio"), Approval::Pending { .. }));
+        assert_eq!(rule.check("bob", action, "/admin/flatten", "strategy-mm"), Approval::Granted { requester: Some("alice".to_string()) });
+        assert_eq!(rule.check("alice", action, "/admin/flatten", "portfolio"), Approval::Granted { requester: Some("bob".to_string()) });
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+use warp::http::{HeaderValue, Method, Request, StatusCode};
+use warp::hyper::body::{Body, HttpBody};
+use warp::Filter;
+
+use crate::audit::{AuditEvent, AuditLog};
+use crate::command::approval::{Approval, TwoManRule};
+use crate::secrets::Secrets;
+
+/// Header [`digest_body`] sets to the SHA-256 of the request body
+const BODY_DIGEST: &str = "x-hft-body-digest";
+
+/// Largest request body [`digest_body`] reads
+const MAX_BODY: usize = 1 << 20;
+
+/// What an admin API token may do. Each role can do everything the ones
+/// before it can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminRole {
+    /// Read status, toggles and parameters, e.g. dashboards
+    Viewer,
+    /// Pause and resume symbols and strategies, and enter manual orders
+    Operator,
+    /// Change strategy parameters
+    Admin,
+}
+
+impl AdminRole {
+    fn parse(role: &str) -> Option<Self> {
+        match role.trim().to_lowercase().as_str() {
+            "viewer" => Some(AdminRole::Viewer),
+            "operator" => Some(AdminRole::Operator),
+            "admin" => Some(AdminRole::Admin),
+            _ => None,
+        }
+    }
+
+    /// The role a request to the admin API needs
+    pub fn required(method: &Method, path: &str) -> Self {
+        match *method {
+            Method::GET | Method::HEAD => AdminRole::Viewer,
+            _ if path.starts_with("/admin/params") => AdminRole::Admin,
+            _ => AdminRole::Operator,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Principal {
+    name: String,
+    role: AdminRole,
+}
+
+/// Bearer token authentication for the admin API.
+///
+/// Tokens are kept as SHA-256 digests. With no tokens configured every
+/// request is allowed, as before authentication existed. Control actions
+/// and refused requests are recorded to the audit log with the name of the
+/// token used.
+#[derive(Clone, Default)]
+pub struct AdminAuth {
+    tokens: Arc<HashMap<[u8; 32], Principal>>,
+    audit: Option<AuditLog>,
+    approvals: TwoManRule,
+}
+
+impl fmt::Debug for AdminAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdminAuth")
+            .field("principals", &self.tokens.values().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+fn digest(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+impl AdminAuth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept `token` as `name` with `role`
+    pub fn with_token(mut self, name: &str, role: AdminRole, token: &str) -> Self {
+        Arc::make_mut(&mut self.tokens).insert(digest(token), Principal { name: name.to_string(), role });
+        self
+    }
+
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Hold the actions `rule` guards until a second token approves them
+    pub fn with_two_man_rule(mut self, rule: TwoManRule) -> Self {
+        self.approvals = rule;
+        self
+    }
+
+    /// Read the `HFT_ADMIN_TOKENS` secret as comma separated
+    /// `NAME:ROLE:TOKEN` entries, e.g. `grafana:viewer:...,oncall:operator:...`
+    pub fn from_secrets(secrets: &Secrets) -> Option<Self> {
+        let spec = secrets.get("HFT_ADMIN_TOKENS")?;
+        Some(Self::parse(&spec))
+    }
+
+    fn parse(spec: &str) -> Self {
+        let mut auth = Self::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let mut fields = entry.splitn(3, ':');
+            match (fields.next(), fields.next().and_then(AdminRole::parse), fields.next()) {
+                (Some(name), Some(role), Some(token)) if !name.is_empty() && !token.is_empty() => {
+                    auth = auth.with_token(name, role, token);
+                }
+                // Log the name only, the entry holds the token
+                (name, _, _) => warn!(name = name.unwrap_or_default(), "Ignoring malformed admin token"),
+            }
+        }
+        auth
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    fn authorize(&self, method: &Method, path: &str, authorization: Option<&str>, body: Option<&str>) -> Result<(), warp::Rejection> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let action = format!("{} {}", method, path);
+        let required = AdminRole::required(method, path);
+        let principal = authorization
+            .and_then(|header| header.strip_prefix("Bearer "))
+            .and_then(|token| self.tokens.get(&digest(token.trim())));
+
+        let Some(principal) = principal else {
+            warn!(action = %action, "Admin request without a valid token");
+            self.record(None, &action, false);
+            return Err(warp::reject::custom(Unauthorized));
+        };
+        if principal.role < required {
+            warn!(principal = %principal.name, role = ?principal.role, action = %action, "Admin request refused");
+            self.record(Some(principal), &action, false);
+            return Err(warp::reject::custom(Forbidden));
+        }
+        if required > AdminRole::Viewer {
+            self.approve(principal, &action, path, body.unwrap_or_default())?;
+            info!(principal = %principal.name, action = %action, "Admin control action");
+            self.record(Some(principal), &action, true);
+        }
+        Ok(())
+    }
+
+    /// Apply the two-man rule to a control action by `principal` with a
+    /// request body hashing to `body`
+    fn approve(&self, principal: &Principal, action: &str, path: &str, body: &str) -> Result<(), warp::Rejection> {
+        match self.approvals.check(&principal.name, action, path, body) {
+            Approval::Granted { requester: None } => Ok(()),
+            Approval::Granted { requester: Some(requester) } => {
+                info!(requester = %requester, approver = %principal.name, action = %action, "Control action approved");
+                if let Some(audit) = &self.audit {
+                    audit.record(AuditEvent::ApprovalGranted {
+                        requester,
+                        approver: principal.name.clone(),
+                        action: action.to_string(),
+                    });
+                }
+                Ok(())
+            }
+            Approval::Pending { expires_in } => {
+                info!(principal = %principal.name, action = %action, expires_in = ?expires_in, "Control action awaiting approval");
+                if let Some(audit) = &self.audit {
+                    audit.record(AuditEvent::ApprovalRequested {
+                        principal: principal.name.clone(),
+                        action: action.to_string(),
+                        expires_in_secs: expires_in.as_secs(),
+                    });
+                }
+                Err(warp::reject::custom(AwaitingApproval { action: action.to_string(), expires_in }))
+            }
+        }
+    }
+
+    fn record(&self, principal: Option<&Principal>, action: &str, allowed: bool) {
+        if let Some(audit) = &self.audit {
+            audit.record(AuditEvent::ControlAction {
+                principal: principal.map(|p| p.name.clone()),
+                role: principal.map(|p| p.role),
+                action: action.to_string(),
+                allowed,
+            });
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Unauthorized;
+impl warp::reject::Reject for Unauthorized {}
+
+#[derive(Debug)]
+struct Forbidden;
+impl warp::reject::Reject for Forbidden {}
+
+#[derive(Debug)]
+struct AwaitingApproval {
+    action: String,
+    expires_in: std::time::Duration,
+}
+impl warp::reject::Reject for AwaitingApproval {}
+
+/// Checks the bearer token of requests under `/admin/` against the role
+/// the request needs; put it in front of the admin routes and
+/// [`recover`] at the end of the chain. Two-man rule approvals are bound
+/// to the body digest [`digest_body`] adds, so serve the routes through it.
+pub fn protect(auth: AdminAuth) -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    warp::method()
+        .and(warp::path::full())
+        .and(warp::header::optional::<String>("authorization"))
+        .and(warp::header::optional::<String>(BODY_DIGEST))
+        .and_then(move |method: Method, path: warp::path::FullPath, authorization: Option<String>, body: Option<String>| {
+            let auth = auth.clone();
+            async move {
+                if !path.as_str().starts_with("/admin/") {
+                    return Err(warp::reject::not_found());
+                }
+                auth.authorize(&method, path.as_str(), authorization.as_deref(), body.as_deref())
+            }
+        })
+        .untuple_one()
+}
+
+/// Hash the body of `request` into the header [`protect`] binds approvals
+/// to, replacing any digest the client sent. Filters cannot read the body
+/// without taking it from the routes, so this runs in front of the
+/// filters' service.
+pub async fn digest_body(request: Request<Body>) -> Result<Request<Body>, StatusCode> {
+    let (mut parts, mut body) = request.into_parts();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| StatusCode::BAD_REQUEST)?;
+        if bytes.len() + chunk.len() > MAX_BODY {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    let digest: String = Sha256::digest(&bytes).iter().map(|b| format!("{:02x}", b)).collect();
+    parts.headers.insert(BODY_DIGEST, HeaderValue::from_str(&digest).expect("hex is a valid header value"));
+    Ok(Request::from_parts(parts, Body::from(bytes)))
+}
+
+/// Turn refused admin requests into 401 and 403 responses, and actions
+/// held for approval into 202
+pub async fn recover(rejection: warp::Rejection) -> Result<impl warp::Reply, warp::Rejection> {
+    if let Some(pending) = rejection.find::<AwaitingApproval>() {
+        let reply = warp::reply::json(&serde_json::json!({
+            "status": "awaiting_approval",
+            "action": pending.action,
+            "expires_in_secs": pending.expires_in.as_secs(),
+        }));
+        return Ok(warp::reply::with_status(reply, StatusCode::ACCEPTED));
+    }
+    let status = if rejection.find::<Unauthorized>().is_some() {
+        StatusCode::UNAUTHORIZED
+    } else if rejection.find::<Forbidden>().is_some() {
+        StatusCode::FORBIDDEN
+    } else {
+        return Err(rejection);
+    };
+    let reply = warp::reply::json(&serde_json::json!({ "error": status.canonical_reason() }));
+    Ok(warp::reply::with_status(reply, status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditConfig;
+    use crate::risk::TradingToggles;
+    use crate::strategy::ParameterStore;
+    use crate::command::approval::TwoManRule;
+
+    #[tokio::test]
+    async fn test_roles_on_admin_routes() {
+        let dir = std::env::temp_dir().join(format!("hft_admin_auth_{}", std::process::id()));
+        let config = AuditConfig { path: dir.join("audit.log"), syslog: false };
+        let auth = AdminAuth::parse("grafana:viewer:view-token, oncall:operator:op-token, lead:ADMIN:admin-token, bad:root:x")
+            .with_audit(AuditLog::open(&config).unwrap());
+        let toggles = Arc::new(TradingToggles::new());
+        let api = protect(auth)
+            .and(crate::risk::toggles::routes(Arc::clone(&toggles)).or(crate::strategy::params::routes(ParameterStore::new())))
+            .recover(recover);
+
+        let request = |method: &str, path: &str, token: Option<&str>| {
+            let mut request = warp::test::request().method(method).path(path).json(&serde_json::json!({}));
+            if let Some(token) = token {
+                request = request.header("authorization", format!("Bearer {}", token));
+            }
+            request
+        };
+
+        assert_eq!(request("GET", "/admin/toggles", None).reply(&api).await.status(), 401);
+        assert_eq!(request("GET", "/admin/toggles", Some("x")).reply(&api).await.status(), 401);
+        assert_eq!(request("GET", "/admin/toggles", Some("view-token")).reply(&api).await.status(), 200);
+        assert_eq!(request("POST", "/admin/toggles/symbol/BTCUSDT/disable", Some("view-token")).reply(&api).await.status(), 403);
+        assert!(toggles.is_symbol_enabled("BTCUSDT"));
+        assert_eq!(request("POST", "/admin/toggles/symbol/BTCUSDT/disable", Some("op-token")).reply(&api).await.status(), 200);
+        assert!(!toggles.is_symbol_enabled("BTCUSDT"));
+        assert_eq!(request("PUT", "/admin/params/mm", Some("op-token")).reply(&api).await.status(), 403);
+        assert_eq!(request("PUT", "/admin/params/mm", Some("admin-token")).reply(&api).await.status(), 200);
+
+        // Refusals and control actions are audited, reads are not
+        let mut records = Vec::new();
+        for _ in 0..100 {
+            records = std::fs::read_to_string(&config.path).unwrap_or_default().lines().map(str::to_string).collect();
+            if records.len() >= 6 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(records.len(), 6);
+        assert!(records[3].contains(r#""principal":"oncall","role":"operator","action":"POST /admin/toggles/symbol/BTCUSDT/disable","allowed":true"#));
+        assert!(records[4].contains(r#""principal":"oncall""#) && records[4].contains(r#""allowed":false"#));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Without tokens the API stays open
+        let open = protect(AdminAuth::new()).and(crate::risk::toggles::routes(toggles)).recover(recover);
+        assert_eq!(request("POST", "/admin/toggles/symbol/BTCUSDT/enable", None).reply(&open).await.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_two_man_rule_holds_until_second_token() {
+        let auth = AdminAuth::parse("alice:operator:a-token, bob:operator:b-token")
+            .with_two_man_rule(TwoManRule::new(std::time::Duration::from_secs(60)).with_path("/admin/toggles/strategy"));
+        let toggles = Arc::new(TradingToggles::new());
+        let api = protect(auth).and(crate::risk::toggles::routes(Arc::clone(&toggles))).recover(recover);
+        let post = |path: &str, token: &str| {
+            warp::test::request().method("POST").path(path).header("authorization", format!("Bearer {}", token))
+        };
+
+        assert_eq!(post("/admin/toggles/symbol/BTCUSDT/disable", "a-token").reply(&api).await.status(), 200);
+        let response = post("/admin/toggles/strategy/mm/disable", "a-token").reply(&api).await;
+        assert_eq!(response.status(), 202);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["action"], "POST /admin/toggles/strategy/mm/disable");
+        assert_eq!(post("/admin/toggles/strategy/mm/disable", "a-token").reply(&api).await.status(), 202);
+        assert!(toggles.is_strategy_enabled("mm"));
+
+        assert_eq!(post("/admin/toggles/strategy/mm/disable", "b-token").reply(&api).await.status(), 200);
+        assert!(!toggles.is_strategy_enabled("mm"));
+    }
+
+    #[tokio::test]
+    async fn test_two_man_rule_binds_the_body() {
+        use warp::hyper::service::Service;
+
+        let auth = AdminAuth::parse("alice:admin:a-token, bob:admin:b-token")
+            .with_two_man_rule(TwoManRule::new(std::time::Duration::from_secs(60)).with_path("/admin/params"));
+        let store = ParameterStore::new();
+        let mut service = warp::service(protect(auth).and(crate::strategy::params::routes(store.clone())).recover(recover));
+        let put = |token: &str, body: &str| {
+            Request::builder()
+                .method("PUT")
+                .uri("/admin/params/mm")
+                .header("authorization", format!("Bearer {}", token))
+                // A digest sent by the client is replaced
+                .header(BODY_DIGEST, "forged")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let request = digest_body(put("a-token", r#"{"base_spread_bps":4.0}"#)).await.unwrap();
+        assert_ne!(request.headers()[BODY_DIGEST], "forged");
+        assert_eq!(service.call(request).await.unwrap().status(), 202);
+
+        // Bob sending other parameters does not approve Alice's change
+        let request = digest_body(put("b-token", r#"{"base_spread_bps":40.0}"#)).await.unwrap();
+        assert_eq!(service.call(request).await.unwrap().status(), 202);
+        assert!(store.get("mm").is_none());
+
+        let request = digest_body(put("b-token", r#"{"base_spread_bps":4.0}"#)).await.unwrap();
+        assert_eq!(service.call(request).await.unwrap().status(), 200);
+        assert_eq!(store.get("mm").unwrap()["base_spread_bps"], 4.0);
+    }
+}
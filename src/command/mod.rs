@@ -1,6 +1,21 @@
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use crate::services::Services;
+use crate::services::{EngineStatus, Services};
+use crate::failover::{Leadership, Role};
+use crate::gateways::{SubscriptionChanges, SubscriptionSpec};
+use crate::execution::{ManualOrderRequest, OpenOrder};
+use crate::types::Order;
+
+pub mod approval;
+pub mod auth;
+pub mod mode;
+pub mod preflight;
+
+pub use approval::TwoManRule;
+pub use auth::{AdminAuth, AdminRole};
+pub use mode::{TradingMode, TradingState};
+pub use preflight::{PreflightConfig, PreflightReport};
 
 pub struct CommandControl {
     services: Arc<RwLock<Services>>,
@@ -19,14 +34,168 @@ impl CommandControl {
         Ok(())
     }
 
+    /// Run the startup checks and print a pass/fail report
+    pub async fn preflight(&self, config: &PreflightConfig) -> PreflightReport {
+        let report = self.services.read().await.preflight(config).await;
+
+        println!("{}", report);
+        report
+    }
+
     pub async fn stop_trading(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // Implement shutdown logic
+        self.services.write().await.stop().await;
+
         println!("Trading stopped");
         Ok(())
     }
 
-    pub async fn status(&self) -> Result<String, Box<dyn std::error::Error>> {
-        // Implement status check
-        Ok("Trading system running".to_string())
+    /// Write engine state to `path` for a later `--restore`
+    pub async fn snapshot_state(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let snapshot = self.services.read().await.snapshot().await;
+        snapshot.save(path)?;
+
+        println!("State snapshot written to {}", path.display());
+        Ok(())
+    }
+
+    async fn leadership(&self) -> Result<Leadership, Box<dyn std::error::Error>> {
+        self.services.read().await.leadership()
+            .ok_or_else(|| "Failover is not configured".into())
+    }
+
+    /// This instance's failover role; an engine without failover always leads
+    pub async fn role(&self) -> Role {
+        match self.services.read().await.leadership() {
+            Some(leadership) => leadership.role(),
+            None => Role::Leader,
+        }
+    }
+
+    /// Manual failover: stop sending orders and hand the leader lock to the
+    /// standby
+    pub async fn fail_over(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let leadership = self.leadership().await?;
+        leadership.step_down().await?;
+
+        println!("{} stepped down to standby", leadership.node_id());
+        Ok(())
+    }
+
+    /// Promote this instance once the leader lock is free
+    pub async fn take_over(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        let leadership = self.leadership().await?;
+        let acquired = leadership.take_over().await;
+
+        if acquired {
+            println!("{} is now leader", leadership.node_id());
+        } else {
+            println!("{} is waiting for the leader lock", leadership.node_id());
+        }
+        Ok(acquired)
+    }
+
+    /// Load or hot-swap a strategy from a shared library on a trusted host
+    #[cfg(feature = "plugins-dylib")]
+    pub async fn load_strategy_library(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let strategy = crate::strategy::dylib::DylibStrategy::load(path)?;
+        self.services.write().await.add_strategy(Box::new(strategy));
+
+        println!("Strategy loaded from {}", path.display());
+        Ok(())
+    }
+
+    /// Pause quoting for a symbol; market data keeps flowing
+    pub async fn disable_symbol(&self, symbol: &str) {
+        self.services.read().await.toggles().disable_symbol(symbol);
+    }
+
+    pub async fn enable_symbol(&self, symbol: &str) {
+        self.services.read().await.toggles().enable_symbol(symbol);
+    }
+
+    /// Pause a strategy; it keeps receiving market data
+    pub async fn disable_strategy(&self, strategy: &str) {
+        self.services.read().await.toggles().disable_strategy(strategy);
+    }
+
+    pub async fn enable_strategy(&self, strategy: &str) {
+        self.services.read().await.toggles().enable_strategy(strategy);
+    }
+
+    /// Replace a strategy's parameters; running strategies pick them up on
+    /// their next quote
+    pub async fn set_strategy_params(&self, strategy: &str, params: serde_json::Value) -> u64 {
+        self.services.read().await.strategy_params().set(strategy, params)
+    }
+
+    /// Start streaming `symbols` without restarting; the subscription set
+    /// is persisted when a store is configured
+    pub async fn subscribe(&self, symbols: Vec<String>) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let added = self.services.read().await.subscribe(symbols).await?;
+
+        println!("Subscribed to {}", added.join(", "));
+        Ok(added)
+    }
+
+    pub async fn unsubscribe(&self, symbols: &[String]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let removed = self.services.read().await.unsubscribe(symbols).await?;
+
+        println!("Unsubscribed from {}", removed.join(", "));
+        Ok(removed)
+    }
+
+    pub async fn list_subscriptions(&self) -> Vec<String> {
+        self.services.read().await.subscriptions().await
+    }
+
+    /// Subscribe each venue to exactly the symbols `spec` lists for it,
+    /// e.g. BTC on Binance and ETH on Kraken only
+    pub async fn apply_subscriptions(&self, spec: &SubscriptionSpec) -> Result<SubscriptionChanges, Box<dyn std::error::Error>> {
+        let changes = self.services.read().await.apply_subscriptions(spec).await?;
+
+        for (venue, symbols) in &changes.added {
+            println!("Subscribed {} to {}", venue, symbols.join(", "));
+        }
+        for (venue, symbols) in &changes.removed {
+            println!("Unsubscribed {} from {}", venue, symbols.join(", "));
+        }
+        Ok(changes)
+    }
+
+    /// Subscribed symbols per venue
+    pub async fn subscription_spec(&self) -> SubscriptionSpec {
+        self.services.read().await.subscription_spec().await
+    }
+
+    /// Risk check a manual order and send it, e.g. to flatten a position
+    pub async fn submit_order(&self, request: &ManualOrderRequest) -> Result<Order, Box<dyn std::error::Error>> {
+        let order = self.services.read().await.manual_orders().submit(request).await?;
+
+        println!("Submitted {:?} {} {} on {}", order.side, order.quantity, order.symbol, order.venue);
+        Ok(order)
+    }
+
+    pub async fn cancel_order(&self, order_id: &str) -> Result<OpenOrder, Box<dyn std::error::Error>> {
+        let cancelled = self.services.read().await.manual_orders().cancel(order_id).await?;
+
+        println!("Cancelled {} on {}", order_id, cancelled.order.venue);
+        Ok(cancelled)
+    }
+
+    pub async fn trading_mode(&self) -> TradingMode {
+        self.services.read().await.trading_state().mode()
+    }
+
+    /// Move the engine to `mode`, e.g. `ReduceOnly` to wind positions down
+    pub async fn set_trading_mode(&self, mode: TradingMode, reason: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let previous = self.services.read().await.trading_state().transition(mode, reason)?;
+
+        println!("Trading mode changed from {} to {}", previous, mode);
+        Ok(())
+    }
+
+    /// Component states, venue connectivity and paused trading
+    pub async fn status(&self) -> EngineStatus {
+        self.services.read().await.status()
     }
 }
\ No newline at end of file
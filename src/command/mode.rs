@@ -0,0 +1,221 @@
+//! Engine-wide trading mode.
+//!
+//! The engine starts in `Init`, warms up once its services run and then
+//! trades in `Active`. Operators move it to `ReduceOnly` to wind positions
+//! down or `Halted` to stop sending orders; both can go back to `Active`.
+//! Every change is published on the event bus.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use warp::http::StatusCode;
+use warp::Filter;
+
+use crate::error::{ExecutionError, HftError};
+use crate::events::{EngineEvent, EventBus};
+use crate::metrics::TRADING_MODE;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradingMode {
+    /// Services are being configured; no orders
+    #[default]
+    Init,
+    /// Services run and strategies see market data; no orders yet
+    Warmup,
+    Active,
+    /// Only orders that shrink a position
+    ReduceOnly,
+    /// No orders
+    Halted,
+}
+
+impl TradingMode {
+    pub const ALL: [TradingMode; 5] = [
+        TradingMode::Init,
+        TradingMode::Warmup,
+        TradingMode::Active,
+        TradingMode::ReduceOnly,
+        TradingMode::Halted,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TradingMode::Init => "init",
+            TradingMode::Warmup => "warmup",
+            TradingMode::Active => "active",
+            TradingMode::ReduceOnly => "reduce_only",
+            TradingMode::Halted => "halted",
+        }
+    }
+
+    /// Whether the engine may move from this mode to `next`; staying put
+    /// is always allowed
+    pub fn can_transition_to(&self, next: TradingMode) -> bool {
+        use TradingMode::*;
+        *self == next || matches!(
+            (self, next),
+            (Init, Warmup | Active | Halted)
+                | (Warmup, Active | ReduceOnly | Halted)
+                | (Active, ReduceOnly | Halted)
+                | (ReduceOnly, Active | Halted)
+                | (Halted, Active | ReduceOnly)
+        )
+    }
+
+    /// Whether any orders may be sent
+    pub fn accepts_orders(&self) -> bool {
+        matches!(self, TradingMode::Active | TradingMode::ReduceOnly)
+    }
+
+    /// Whether an order that grows a position when `increases_position`
+    /// may be sent
+    pub fn permits(&self, increases_position: bool) -> bool {
+        match self {
+            TradingMode::Active => true,
+            TradingMode::ReduceOnly => !increases_position,
+            TradingMode::Init | TradingMode::Warmup | TradingMode::Halted => false,
+        }
+    }
+}
+
+impl fmt::Display for TradingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TradingMode {
+    type Err = HftError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_lowercase().replace('-', "_");
+        TradingMode::ALL.into_iter()
+            .find(|mode| mode.as_str() == normalized)
+            .ok_or_else(|| HftError::Config(format!("Unknown trading mode {}", s)))
+    }
+}
+
+/// The engine's current trading mode, shared by the risk checks, strategy
+/// runner and admin API
+#[derive(Debug, Clone)]
+pub struct TradingState {
+    mode: Arc<RwLock<TradingMode>>,
+    pub(crate) events: Option<EventBus>,
+}
+
+impl Default for TradingState {
+    fn default() -> Self {
+        set_gauge(TradingMode::Init);
+        Self { mode: Arc::new(RwLock::new(TradingMode::Init)), events: None }
+    }
+}
+
+fn set_gauge(current: TradingMode) {
+    for mode in TradingMode::ALL {
+        TRADING_MODE.with_label_values(&[mode.as_str()]).set(if mode == current { 1.0 } else { 0.0 });
+    }
+}
+
+impl TradingState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(&self) -> TradingMode {
+        *self.mode.read().unwrap()
+    }
+
+    /// Move to `next`, returning the previous mode
+    pub fn transition(&self, next: TradingMode, reason: &str) -> Result<TradingMode, HftError> {
+        let mut mode = self.mode.write().unwrap();
+        let previous = *mode;
+        if !previous.can_transition_to(next) {
+            return Err(ExecutionError::InvalidTransition(format!("{} to {}", previous, next)).into());
+        }
+        if previous == next {
+            return Ok(previous);
+        }
+        *mode = next;
+        drop(mode);
+
+        info!(from = %previous, to = %next, reason = %reason, "Trading mode changed");
+        set_gauge(next);
+        if let Some(events) = &self.events {
+            events.publish(EngineEvent::TradingModeChanged {
+                from: previous.to_string(),
+                to: next.to_string(),
+                reason: reason.to_string(),
+            });
+        }
+        Ok(previous)
+    }
+
+    /// Move to `next` only while in `from`, e.g. to end warm-up unless an
+    /// operator changed the mode meanwhile
+    pub fn transition_from(&self, from: TradingMode, next: TradingMode, reason: &str) -> bool {
+        self.mode() == from && self.transition(next, reason).is_ok()
+    }
+}
+
+/// Admin endpoints:
+/// - `GET /admin/mode` returns the current mode
+/// - `POST /admin/mode/{mode}` moves to `active`, `reduce_only` or
+///   `halted`
+pub fn routes(
+    state: TradingState,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let current = state.clone();
+    let get = warp::path!("admin" / "mode")
+        .and(warp::get())
+        .map(move || warp::reply::json(&serde_json::json!({ "mode": current.mode() })));
+
+    let change = warp::path!("admin" / "mode" / String)
+        .and(warp::post())
+        .map(move |mode: String| {
+            let result = mode.parse().and_then(|next| state.transition(next, "operator"));
+            let (body, status) = match result {
+                Ok(previous) => (serde_json::json!({ "previous": previous, "mode": state.mode() }), StatusCode::OK),
+                Err(e) => (serde_json::json!({ "error": e.to_string(), "mode": state.mode() }), StatusCode::CONFLICT),
+            };
+            warp::reply::with_status(warp::reply::json(&body), status)
+        });
+
+    get.or(change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitions_and_permissions() {
+        let state = TradingState::new();
+        assert_eq!(state.mode(), TradingMode::Init);
+        assert!(state.transition(TradingMode::ReduceOnly, "test").is_err());
+        assert!(state.transition_from(TradingMode::Init, TradingMode::Warmup, "started"));
+        assert!(!state.transition_from(TradingMode::Init, TradingMode::Active, "warm"));
+        assert_eq!(state.transition("reduce-only".parse().unwrap(), "test").unwrap(), TradingMode::Warmup);
+        assert!(state.transition(TradingMode::Warmup, "test").is_err());
+
+        assert!(TradingMode::ReduceOnly.permits(false) && !TradingMode::ReduceOnly.permits(true));
+        assert!(!TradingMode::Warmup.permits(false) && TradingMode::Active.permits(true));
+        assert!("paused".parse::<TradingMode>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mode_admin_route() {
+        let state = TradingState::new();
+        let api = routes(state.clone());
+
+        let response = warp::test::request().method("POST").path("/admin/mode/reduce_only").reply(&api).await;
+        assert_eq!(response.status(), 409);
+        let response = warp::test::request().method("POST").path("/admin/mode/halted").reply(&api).await;
+        assert_eq!(response.status(), 200);
+        let response = warp::test::request().path("/admin/mode").reply(&api).await;
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["mode"], "halted");
+    }
+}
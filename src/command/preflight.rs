@@ -0,0 +1,211 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::error::{HftError, VenueError};
+use crate::instruments::InstrumentMap;
+use crate::venues::VenueAdapter;
+
+const DEFAULT_MAX_CLOCK_SKEW: Duration = Duration::from_millis(500);
+
+/// What the preflight checks expect of the venues
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreflightConfig {
+    /// Canonical symbols the engine will trade
+    pub symbols: Vec<String>,
+    /// Largest difference allowed between a venue's clock and ours. Signed
+    /// requests are refused outside the venue's receive window.
+    pub max_clock_skew: Duration,
+}
+
+impl Default for PreflightConfig {
+    fn default() -> Self {
+        Self { symbols: Vec::new(), max_clock_skew: DEFAULT_MAX_CLOCK_SKEW }
+    }
+}
+
+impl PreflightConfig {
+    /// Read `HFT_SYMBOLS` (comma separated) and `HFT_PREFLIGHT_MAX_SKEW_MS`
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(symbols) = std::env::var("HFT_SYMBOLS") {
+            config.symbols = symbols.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect();
+        }
+        if let Ok(value) = std::env::var("HFT_PREFLIGHT_MAX_SKEW_MS") {
+            match value.parse() {
+                Ok(ms) => config.max_clock_skew = Duration::from_millis(ms),
+                Err(_) => warn!(value = %value, "Ignoring malformed HFT_PREFLIGHT_MAX_SKEW_MS"),
+            }
+        }
+        config
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Could not be checked, e.g. the venue has no endpoint for it; does not
+    /// block trading
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+/// Outcome of every preflight check, in the order they ran
+#[derive(Debug, Clone, Default)]
+pub struct PreflightReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl PreflightReport {
+    fn record(&mut self, name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) {
+        self.checks.push(CheckResult { name: name.into(), status, detail: detail.into() });
+    }
+
+    /// Record a venue call's outcome, downgrading unsupported calls to a
+    /// warning
+    fn record_result<T>(&mut self, name: String, result: &Result<T, HftError>, passed: impl FnOnce(&T) -> (CheckStatus, String)) {
+        match result {
+            Ok(value) => {
+                let (status, detail) = passed(value);
+                self.record(name, status, detail);
+            }
+            Err(HftError::Venue(VenueError::NotSupported(_))) => self.record(name, CheckStatus::Warn, "not supported by venue"),
+            Err(e) => self.record(name, CheckStatus::Fail, e.to_string()),
+        }
+    }
+
+    /// True when no check failed
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Fail)
+    }
+}
+
+impl fmt::Display for PreflightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let status = match check.status {
+                CheckStatus::Pass => "PASS",
+                CheckStatus::Warn => "WARN",
+                CheckStatus::Fail => "FAIL",
+            };
+            writeln!(f, "[{}] {}: {}", status, check.name, check.detail)?;
+        }
+        write!(f, "Preflight {}", if self.passed() { "passed" } else { "failed" })
+    }
+}
+
+/// Check each venue is reachable, accepts our credentials, keeps time with
+/// us and lists every configured symbol
+pub async fn run(venues: &[Arc<dyn VenueAdapter>], instruments: &InstrumentMap, config: &PreflightConfig) -> PreflightReport {
+    let mut report = PreflightReport::default();
+
+    if config.symbols.is_empty() {
+        report.record("config", CheckStatus::Fail, "no symbols configured, set HFT_SYMBOLS");
+    } else {
+        report.record("config", CheckStatus::Pass, format!("{} symbols configured", config.symbols.len()));
+    }
+    if venues.is_empty() {
+        report.record("config", CheckStatus::Fail, "no venues configured");
+    }
+
+    for venue in venues {
+        let name = venue.name().await;
+
+        // Assume the venue stamped its clock halfway through the round trip
+        let sent = chrono::Utc::now().timestamp_millis();
+        let server_time = venue.server_time().await;
+        let received = chrono::Utc::now().timestamp_millis();
+        let reachable = !matches!(server_time, Err(HftError::Venue(VenueError::ConnectionFailed(_))));
+        report.record_result(format!("{} clock", name), &server_time, |&server| {
+            let skew = server as i64 - (sent + received) / 2;
+            let status = if skew.unsigned_abs() as u128 > config.max_clock_skew.as_millis() {
+                CheckStatus::Fail
+            } else {
+                CheckStatus::Pass
+            };
+            (status, format!("skew {}ms, round trip {}ms", skew, received - sent))
+        });
+        if !reachable {
+            // Everything else needs the same REST endpoint
+            continue;
+        }
+
+        report.record(format!("{} capabilities", name), CheckStatus::Pass, venue.capabilities().to_string());
+
+        let credentials = venue.verify_credentials().await;
+        report.record_result(format!("{} credentials", name), &credentials, |_| (CheckStatus::Pass, "accepted".to_string()));
+
+        let listed = venue.tradable_symbols().await;
+        report.record_result(format!("{} symbols", name), &listed, |listed| {
+            let missing: Vec<String> = config.symbols.iter()
+                .map(|s| instruments.venue_symbol(name.as_str().into(), s.into()).to_string())
+                .filter(|s| !listed.contains(s))
+                .collect();
+            if missing.is_empty() {
+                (CheckStatus::Pass, format!("{} symbols trading", config.symbols.len()))
+            } else {
+                (CheckStatus::Fail, format!("not trading: {}", missing.join(", ")))
+            }
+        });
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::fake_exchange::FakeExchange;
+    use crate::venues::BinanceVenue;
+
+    fn venue(exchange: &FakeExchange, api_key: &str) -> Vec<Arc<dyn VenueAdapter>> {
+        vec![Arc::new(BinanceVenue::new(api_key.to_string(), "secret".to_string()).with_rest_url(exchange.rest_url()))]
+    }
+
+    fn status(report: &PreflightReport, name: &str) -> CheckStatus {
+        report.checks.iter().find(|c| c.name == name).unwrap().status
+    }
+
+    #[tokio::test]
+    async fn test_preflight_checks_venue() {
+        let exchange = FakeExchange::start().await;
+        exchange.require_api_key("key");
+        let config = PreflightConfig {
+            symbols: vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()],
+            ..PreflightConfig::default()
+        };
+
+        let report = run(&venue(&exchange, "key"), &InstrumentMap::new(), &config).await;
+        assert!(report.passed(), "{}", report);
+
+        // Skewed clock, rejected key and a delisted symbol all fail
+        exchange.set_clock_offset(2_000);
+        exchange.set_symbols(&["BTCUSDT"]);
+        let report = run(&venue(&exchange, "wrong"), &InstrumentMap::new(), &config).await;
+        assert!(!report.passed());
+        assert_eq!(status(&report, "BINANCE_FUTURES clock"), CheckStatus::Fail);
+        assert_eq!(status(&report, "BINANCE_FUTURES credentials"), CheckStatus::Fail);
+        assert_eq!(status(&report, "BINANCE_FUTURES symbols"), CheckStatus::Fail);
+        assert!(report.to_string().contains("not trading: ETHUSDT"));
+    }
+
+    #[tokio::test]
+    async fn test_preflight_unreachable_venue() {
+        let venues: Vec<Arc<dyn VenueAdapter>> = vec![Arc::new(
+            BinanceVenue::new("key".to_string(), "secret".to_string()).with_rest_url("http://127.0.0.1:1/fapi")
+        )];
+        let config = PreflightConfig { symbols: vec!["BTCUSDT".to_string()], ..PreflightConfig::default() };
+
+        let report = run(&venues, &InstrumentMap::new(), &config).await;
+        assert!(!report.passed());
+        assert_eq!(report.checks.len(), 2);
+    }
+}
@@ -1,6 +1,49 @@
 use std::fmt;
 use thiserror::Error;
 
+use crate::types::VenueId;
+
+/// Stable numeric codes for each kind of error, grouped by thousands:
+/// 1xxx venue, 2xxx gateway, 3xxx execution, 4xxx book and 5xxx engine.
+/// Codes are never reused once published to alerts and audit records.
+pub mod codes {
+    pub const CONNECTION_FAILED: u32 = 1001;
+    pub const AUTHENTICATION_FAILED: u32 = 1002;
+    pub const VENUE_SUBSCRIPTION_FAILED: u32 = 1003;
+    pub const ORDER_SUBMISSION_FAILED: u32 = 1004;
+    pub const RATE_LIMIT_EXCEEDED: u32 = 1005;
+    pub const WEBSOCKET_ERROR: u32 = 1006;
+    pub const PARSE_ERROR: u32 = 1007;
+    pub const NOT_SUPPORTED: u32 = 1008;
+    pub const UNKNOWN_ORDER: u32 = 1009;
+    pub const NO_RESPONSE: u32 = 1010;
+
+    pub const NO_VENUES_CONFIGURED: u32 = 2001;
+    pub const INVALID_SYMBOL: u32 = 2002;
+    pub const CHANNEL_CAPACITY_EXCEEDED: u32 = 2003;
+    pub const VENUE_NOT_FOUND: u32 = 2004;
+    pub const CHANNEL_SEND_FAILED: u32 = 2005;
+    pub const GATEWAY_SUBSCRIPTION_FAILED: u32 = 2006;
+    pub const GATEWAY_NOT_RUNNING: u32 = 2007;
+
+    pub const INVALID_ORDER: u32 = 3001;
+    pub const ORDER_REJECTED: u32 = 3002;
+    pub const RISK_LIMIT_EXCEEDED: u32 = 3003;
+    pub const TRADING_HALTED: u32 = 3004;
+    pub const PRICE_DEVIATION: u32 = 3005;
+    pub const INVALID_TRANSITION: u32 = 3006;
+
+    pub const INVALID_PRICE: u32 = 4001;
+    pub const INVALID_SIZE: u32 = 4002;
+    pub const INVALID_BOOK_STATE: u32 = 4003;
+
+    pub const CONFIG: u32 = 5001;
+    pub const IO: u32 = 5002;
+    pub const SERIALIZATION: u32 = 5003;
+    pub const SINK: u32 = 5004;
+    pub const UNKNOWN: u32 = 5999;
+}
+
 /// Core error types for the HFT engine
 #[derive(Error, Debug, Clone)]
 pub enum HftError {
@@ -22,8 +65,137 @@ pub enum HftError {
     #[error("IO error: {0}")]
     Io(String),
 
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    #[error("Sink error: {0}")]
+    Sink(String),
+
     #[error("Unknown error: {0}")]
     Unknown(String),
+
+    /// `source` with the venue and order it came from
+    #[error("{source} ({})", describe_context(venue, order_id))]
+    Context {
+        venue: Option<VenueId>,
+        order_id: Option<String>,
+        source: Box<HftError>,
+    },
+}
+
+fn describe_context(venue: &Option<VenueId>, order_id: &Option<String>) -> String {
+    match (venue, order_id) {
+        (Some(venue), Some(order_id)) => format!("venue {}, order {}", venue, order_id),
+        (Some(venue), None) => format!("venue {}", venue),
+        (None, Some(order_id)) => format!("order {}", order_id),
+        (None, None) => "no context".to_string(),
+    }
+}
+
+impl HftError {
+    /// The error without any venue or order context
+    pub fn root(&self) -> &HftError {
+        match self {
+            HftError::Context { source, .. } => source.root(),
+            _ => self,
+        }
+    }
+
+    fn into_context(self) -> (Option<VenueId>, Option<String>, Box<HftError>) {
+        match self {
+            HftError::Context { venue, order_id, source } => (venue, order_id, source),
+            error => (None, None, Box::new(error)),
+        }
+    }
+
+    /// Record the venue the error came from
+    pub fn with_venue(self, venue: VenueId) -> Self {
+        let (_, order_id, source) = self.into_context();
+        HftError::Context { venue: Some(venue), order_id, source }
+    }
+
+    /// Record the order the error came from
+    pub fn with_order(self, order_id: &str) -> Self {
+        let (venue, _, source) = self.into_context();
+        HftError::Context { venue, order_id: Some(order_id.to_string()), source }
+    }
+
+    /// Venue the error came from, when known
+    pub fn venue(&self) -> Option<VenueId> {
+        match self {
+            HftError::Context { venue, .. } => *venue,
+            _ => None,
+        }
+    }
+
+    /// Order the error came from, when known
+    pub fn order_id(&self) -> Option<&str> {
+        match self {
+            HftError::Context { order_id, .. } => order_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Stable numeric code for the kind of error, see [`codes`]
+    pub fn code(&self) -> u32 {
+        match self {
+            HftError::Venue(e) => e.code(),
+            HftError::Gateway(e) => e.code(),
+            HftError::Execution(e) => e.code(),
+            HftError::Book(e) => e.code(),
+            HftError::Config(_) => codes::CONFIG,
+            HftError::Io(_) => codes::IO,
+            HftError::Serialization(_) => codes::SERIALIZATION,
+            HftError::Sink(_) => codes::SINK,
+            HftError::Unknown(_) => codes::UNKNOWN,
+            HftError::Context { source, .. } => source.code(),
+        }
+    }
+
+    /// Whether the same request may succeed if tried again, on the same
+    /// venue later or on another one; rejections and invalid requests fail
+    /// the same way every time
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            HftError::Venue(e) => e.is_retryable(),
+            HftError::Gateway(e) => e.is_retryable(),
+            HftError::Io(_) | HftError::Sink(_) => true,
+            HftError::Context { source, .. } => source.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// Whether the request never reached the venue, so sending it again,
+    /// there or elsewhere, cannot make it happen twice
+    pub fn was_not_sent(&self) -> bool {
+        matches!(self.root(), HftError::Venue(VenueError::ConnectionFailed(_)))
+    }
+
+    /// Whether the request reached the venue with no answer, leaving its
+    /// outcome to be found out from the venue's own state
+    pub fn outcome_unknown(&self) -> bool {
+        matches!(self.root(), HftError::Venue(VenueError::WebSocketError(_) | VenueError::NoResponse(_)))
+    }
+
+    /// Short, fixed name for the kind of error, for metric labels
+    pub fn reason_label(&self) -> &'static str {
+        match self.root() {
+            HftError::Venue(VenueError::ConnectionFailed(_) | VenueError::WebSocketError(_) | VenueError::NoResponse(_)) => "connection",
+            HftError::Venue(VenueError::AuthenticationFailed(_)) => "auth",
+            HftError::Venue(VenueError::OrderSubmissionFailed(_)) => "venue_rejected",
+            HftError::Venue(VenueError::RateLimitExceeded) => "rate_limit",
+            HftError::Venue(VenueError::NotSupported(_)) => "not_supported",
+            HftError::Venue(VenueError::UnknownOrder(_)) => "unknown_order",
+            HftError::Venue(_) => "venue_error",
+            HftError::Execution(ExecutionError::InvalidOrder(_)) => "invalid_order",
+            HftError::Execution(ExecutionError::OrderRejected(_)) => "rejected",
+            HftError::Execution(ExecutionError::RiskLimitExceeded(_)) => "risk_limit",
+            HftError::Execution(ExecutionError::TradingHalted(_)) => "halted",
+            HftError::Execution(ExecutionError::PriceDeviation(_)) => "price_deviation",
+            HftError::Gateway(_) => "gateway",
+            _ => "other",
+        }
+    }
 }
 
 // Special implementation for std::io::Error since it's not cloneable
@@ -56,6 +228,47 @@ pub enum VenueError {
 
     #[error("Parse error: {0}")]
     ParseError(String),
+
+    #[error("Operation not supported: {0}")]
+    NotSupported(String),
+
+    #[error("Unknown order: {0}")]
+    UnknownOrder(String),
+
+    /// The request was sent but no reply came back, so the venue may or
+    /// may not have acted on it
+    #[error("No response: {0}")]
+    NoResponse(String),
+}
+
+impl VenueError {
+    pub fn code(&self) -> u32 {
+        match self {
+            VenueError::ConnectionFailed(_) => codes::CONNECTION_FAILED,
+            VenueError::AuthenticationFailed(_) => codes::AUTHENTICATION_FAILED,
+            VenueError::SubscriptionFailed(_) => codes::VENUE_SUBSCRIPTION_FAILED,
+            VenueError::OrderSubmissionFailed(_) => codes::ORDER_SUBMISSION_FAILED,
+            VenueError::RateLimitExceeded => codes::RATE_LIMIT_EXCEEDED,
+            VenueError::WebSocketError(_) => codes::WEBSOCKET_ERROR,
+            VenueError::ParseError(_) => codes::PARSE_ERROR,
+            VenueError::NotSupported(_) => codes::NOT_SUPPORTED,
+            VenueError::UnknownOrder(_) => codes::UNKNOWN_ORDER,
+            VenueError::NoResponse(_) => codes::NO_RESPONSE,
+        }
+    }
+
+    /// Connection trouble and rate limits pass; a venue refusing the
+    /// request does not
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            VenueError::ConnectionFailed(_)
+                | VenueError::SubscriptionFailed(_)
+                | VenueError::RateLimitExceeded
+                | VenueError::WebSocketError(_)
+                | VenueError::NoResponse(_)
+        )
+    }
 }
 
 /// Errors related to gateway operations
@@ -83,6 +296,26 @@ pub enum GatewayError {
     NotRunning,
 }
 
+impl GatewayError {
+    pub fn code(&self) -> u32 {
+        match self {
+            GatewayError::NoVenuesConfigured => codes::NO_VENUES_CONFIGURED,
+            GatewayError::InvalidSymbol(_) => codes::INVALID_SYMBOL,
+            GatewayError::ChannelCapacityExceeded => codes::CHANNEL_CAPACITY_EXCEEDED,
+            GatewayError::VenueNotFound(_) => codes::VENUE_NOT_FOUND,
+            GatewayError::ChannelSendFailed(_) => codes::CHANNEL_SEND_FAILED,
+            GatewayError::SubscriptionFailed(_) => codes::GATEWAY_SUBSCRIPTION_FAILED,
+            GatewayError::NotRunning => codes::GATEWAY_NOT_RUNNING,
+        }
+    }
+
+    /// A full channel drains and a subscription can be tried again; the
+    /// rest are configuration or shutdown
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, GatewayError::ChannelCapacityExceeded | GatewayError::SubscriptionFailed(_))
+    }
+}
+
 /// Errors related to execution engine
 #[derive(Error, Debug, Clone)]
 pub enum ExecutionError {
@@ -94,6 +327,28 @@ pub enum ExecutionError {
 
     #[error("Risk limit exceeded: {0}")]
     RiskLimitExceeded(String),
+
+    #[error("Trading halted: {0}")]
+    TradingHalted(String),
+
+    #[error("Price deviates from other venues: {0}")]
+    PriceDeviation(String),
+
+    #[error("Trading mode cannot change from {0}")]
+    InvalidTransition(String),
+}
+
+impl ExecutionError {
+    pub fn code(&self) -> u32 {
+        match self {
+            ExecutionError::InvalidOrder(_) => codes::INVALID_ORDER,
+            ExecutionError::OrderRejected(_) => codes::ORDER_REJECTED,
+            ExecutionError::RiskLimitExceeded(_) => codes::RISK_LIMIT_EXCEEDED,
+            ExecutionError::TradingHalted(_) => codes::TRADING_HALTED,
+            ExecutionError::PriceDeviation(_) => codes::PRICE_DEVIATION,
+            ExecutionError::InvalidTransition(_) => codes::INVALID_TRANSITION,
+        }
+    }
 }
 
 /// Errors related to order book operations
@@ -109,6 +364,16 @@ pub enum BookError {
     InvalidBookState,
 }
 
+impl BookError {
+    pub fn code(&self) -> u32 {
+        match self {
+            BookError::InvalidPrice(_) => codes::INVALID_PRICE,
+            BookError::InvalidSize(_) => codes::INVALID_SIZE,
+            BookError::InvalidBookState => codes::INVALID_BOOK_STATE,
+        }
+    }
+}
+
 // Context wrapper to add context to errors
 pub struct ErrorContext<E> {
     pub error: E,
@@ -139,4 +404,37 @@ impl<T, E: std::error::Error> ErrorExt<T, E> for Result<T, E> {
             context: context.to_string(),
         })
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_and_retryability_survive_context() {
+        let error = HftError::from(VenueError::RateLimitExceeded)
+            .with_venue(VenueId::new("BINANCE"))
+            .with_order("42");
+        assert_eq!(error.code(), codes::RATE_LIMIT_EXCEEDED);
+        assert!(error.is_retryable());
+        assert_eq!(error.reason_label(), "rate_limit");
+        assert_eq!((error.venue(), error.order_id()), (Some(VenueId::new("BINANCE")), Some("42")));
+        assert_eq!(error.to_string(), "Venue error: Rate limit exceeded (venue BINANCE, order 42)");
+        // Context is replaced rather than nested
+        assert!(matches!(error.with_order("43").root(), HftError::Venue(VenueError::RateLimitExceeded)));
+
+        let rejected = HftError::from(VenueError::OrderSubmissionFailed("insufficient margin".to_string()));
+        assert!(!rejected.is_retryable());
+        assert_eq!(rejected.code(), codes::ORDER_SUBMISSION_FAILED);
+        assert!(!HftError::from(ExecutionError::RiskLimitExceeded("max position".to_string())).is_retryable());
+        assert!(HftError::from(VenueError::ConnectionFailed("reset".to_string())).is_retryable());
+
+        // Only a request that never left can safely go elsewhere
+        let refused = HftError::from(VenueError::ConnectionFailed("refused".to_string())).with_venue(VenueId::new("BINANCE"));
+        assert!(refused.was_not_sent() && !refused.outcome_unknown());
+        let lost = HftError::from(VenueError::NoResponse("timed out".to_string())).with_venue(VenueId::new("BINANCE"));
+        assert!(!lost.was_not_sent() && lost.outcome_unknown());
+        assert!(HftError::from(VenueError::WebSocketError("closed".to_string())).outcome_unknown());
+        let limited = HftError::from(VenueError::RateLimitExceeded);
+        assert!(!limited.was_not_sent() && !limited.outcome_unknown());
+    }
+}
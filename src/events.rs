@@ -0,0 +1,115 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::gateways::quality::DataIssue;
+
+/// Operational events published by engine components
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EngineEvent {
+    VenueConnected { venue: String },
+    VenueDisconnected { venue: String, reason: String },
+    KillSwitchEngaged { reason: String },
+    KillSwitchReleased,
+    RiskBreach { scope: String, detail: String },
+    /// `code` is the error's code from [`crate::error::codes`], when the
+    /// rejection came from an error rather than the gateway's own policy
+    OrderRejected { venue: String, symbol: String, reason: String, code: Option<u32> },
+    /// Held by the order gateway until the venue reconnects
+    OrderQueued { venue: String, symbol: String },
+    /// Sent to a secondary venue because the target venue was down
+    OrderRerouted { symbol: String, from: String, to: String },
+    /// Queued for a venue that did not reconnect in time, then rejected
+    QueuedOrderExpired { venue: String, symbol: String },
+    RoleChanged { node: String, role: String },
+    /// A scheduler job ran; `detail` is its summary or error
+    ScheduledJob { job: String, ok: bool, detail: String },
+    /// A marketable order was held because its price strayed from the
+    /// other venues
+    PriceDeviation { venue: String, symbol: String, detail: String },
+    /// The quote gateway found a problem with a venue's market data
+    DataQuality { venue: String, symbol: String, issue: DataIssue, detail: String },
+    /// A venue's quote rate ran far past its baseline and its quotes are
+    /// being conflated while `active`
+    QuoteStorm { venue: String, active: bool, detail: String },
+    /// A symbol's leverage or margin mode could not be set to its
+    /// configured value at startup
+    MarginMismatch { venue: String, symbol: String, detail: String },
+    /// A venue position came close to liquidation, and is being partly
+    /// closed when `reducing`
+    LiquidationRisk { venue: String, symbol: String, distance_pct: f64, reducing: bool, detail: String },
+    /// Positions are held on a venue that stayed disconnected; `action` is
+    /// `hedging`, `awaiting_reconnect` or `flattening`
+    VenueOutage { venue: String, action: String, detail: String },
+    /// A dated contract reached a step of its expiry; `stage` is
+    /// `close_only`, `rolling` or `expired`
+    ContractExpiry { symbol: String, stage: String, detail: String },
+    /// The engine's trading mode changed
+    TradingModeChanged { from: String, to: String, reason: String },
+    /// An order's fills in the local journal differ from the venue's trade
+    /// history; `kind` is `missing`, `extra` or `mismatched`
+    TradeBreak { venue: String, symbol: String, kind: String, detail: String },
+    /// A venue balance changed outside of trading; `kind` is `deposit`,
+    /// `withdrawal`, `funding` or `other`, `amount` positive when credited
+    Transfer { venue: String, asset: String, kind: String, amount: f64, detail: String },
+}
+
+impl EngineEvent {
+    /// The venue the event concerns, if any; the source venue of a reroute
+    pub fn venue(&self) -> Option<&str> {
+        match self {
+            EngineEvent::VenueConnected { venue }
+            | EngineEvent::VenueDisconnected { venue, .. }
+            | EngineEvent::OrderRejected { venue, .. }
+            | EngineEvent::OrderQueued { venue, .. }
+            | EngineEvent::OrderRerouted { from: venue, .. }
+            | EngineEvent::QueuedOrderExpired { venue, .. }
+            | EngineEvent::PriceDeviation { venue, .. }
+            | EngineEvent::DataQuality { venue, .. }
+            | EngineEvent::QuoteStorm { venue, .. }
+            | EngineEvent::MarginMismatch { venue, .. }
+            | EngineEvent::LiquidationRisk { venue, .. }
+            | EngineEvent::VenueOutage { venue, .. }
+            | EngineEvent::TradeBreak { venue, .. }
+            | EngineEvent::Transfer { venue, .. } => Some(venue),
+            EngineEvent::KillSwitchEngaged { .. }
+            | EngineEvent::KillSwitchReleased
+            | EngineEvent::RiskBreach { .. }
+            | EngineEvent::RoleChanged { .. }
+            | EngineEvent::ScheduledJob { .. }
+            | EngineEvent::ContractExpiry { .. }
+            | EngineEvent::TradingModeChanged { .. } => None,
+        }
+    }
+}
+
+/// Fan-out channel for engine events.
+///
+/// Publishing never blocks; slow subscribers miss events rather than
+/// holding up the trading path.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<EngineEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        Self { tx }
+    }
+
+    pub fn publish(&self, event: EngineEvent) {
+        // No subscribers is not an error
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<EngineEvent> {
+        self.tx.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
@@ -0,0 +1,461 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{error, info, warn};
+
+use crate::error::{ExecutionError, GatewayError, HftError};
+use crate::execution::OrderTracker;
+use crate::feed::{wire, FeedMessage, FeedPublisher};
+use crate::gateways::order::CancelRequest;
+use crate::metrics::BRACKET_EXITS;
+use crate::sink::{EventSink, OrderEvent};
+use crate::types::{Fill, Order, OrderSide, OrderType, Quote};
+
+/// An entry order with a take-profit and a stop that close what it fills,
+/// one cancelling the other
+#[derive(Debug, Clone)]
+pub struct Bracket {
+    pub entry: Order,
+    /// Limit price of the take-profit
+    pub take_profit: f64,
+    /// Trigger price of the stop-market exit
+    pub stop_loss: f64,
+}
+
+impl Bracket {
+    fn validate(&self) -> Result<(), HftError> {
+        let ordered = match self.entry.side {
+            OrderSide::Buy => self.stop_loss < self.take_profit,
+            OrderSide::Sell => self.stop_loss > self.take_profit,
+        };
+        if self.stop_loss <= 0.0 || self.take_profit <= 0.0 || !ordered {
+            return Err(ExecutionError::InvalidOrder(format!(
+                "Bracket on {} needs the take-profit ({}) beyond the stop ({}) in the trade's direction",
+                self.entry.symbol, self.take_profit, self.stop_loss
+            )).into());
+        }
+        Ok(())
+    }
+
+    fn exit(&self, quantity: f64, order_type: OrderType, price: f64) -> Order {
+        Order {
+            symbol: self.entry.symbol,
+            side: self.entry.side.opposite(),
+            quantity,
+            price,
+            venue: self.entry.venue,
+            order_type,
+            expire_after: None,
+            strategy: self.entry.strategy.clone(),
+            bypass_kill_switch: false,
+        }
+    }
+
+    /// Whether the market has reached the stop
+    fn stop_triggered(&self, quote: &Quote) -> bool {
+        match self.entry.side {
+            OrderSide::Buy => quote.bid > 0.0 && quote.bid <= self.stop_loss,
+            OrderSide::Sell => quote.ask > 0.0 && quote.ask >= self.stop_loss,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Entry,
+    TakeProfit(usize),
+}
+
+/// Take-profit and stop protecting one entry fill. The take-profit rests on
+/// the venue and the engine watches quotes for the stop.
+struct Exits {
+    take_profit: Option<String>,
+    quantity: f64,
+    /// Whether the engine still watches for the stop
+    armed: bool,
+    /// One side filled or triggered and the other is cancelled
+    closed: bool,
+}
+
+struct Position {
+    bracket: Bracket,
+    exits: Vec<Exits>,
+}
+
+/// An order sent to the gateway whose ack or rejection has not come back
+struct Pending {
+    bracket: u64,
+    role: Role,
+    order: Order,
+}
+
+#[derive(Default)]
+struct State {
+    positions: HashMap<u64, Position>,
+    by_order: HashMap<String, (u64, Role)>,
+    pending: Vec<Pending>,
+}
+
+/// Places bracket orders through the order gateway and keeps their exits
+/// one-cancels-other, with the take-profit resting on the venue and the
+/// engine watching quotes for the stop.
+///
+/// The gateway reports acks and rejections and the fill router reports
+/// fills back through [`EventSink`]; quotes arrive through
+/// [`run`](Self::run).
+pub struct BracketManager {
+    order_tx: mpsc::Sender<Order>,
+    /// Cancels of a closed pair's take-profit go through the order gateway
+    cancel_tx: mpsc::Sender<CancelRequest>,
+    /// Fill progress of take-profits, recorded by the fill router
+    orders: Arc<OrderTracker>,
+    state: Mutex<State>,
+    next_id: AtomicU64,
+}
+
+impl BracketManager {
+    pub fn new(order_tx: mpsc::Sender<Order>, cancel_tx: mpsc::Sender<CancelRequest>, orders: Arc<OrderTracker>) -> Self {
+        Self {
+            order_tx,
+            cancel_tx,
+            orders,
+            state: Mutex::new(State::default()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Hand `order` to the gateway, remembering what it is for when
+    /// `role` is set
+    fn send(&self, bracket: u64, role: Option<Role>, order: Order) -> Result<(), HftError> {
+        if let Some(role) = role {
+            self.state.lock().unwrap().pending.push(Pending { bracket, role, order: order.clone() });
+        }
+        self.order_tx.try_send(order)
+            .map_err(|e| GatewayError::ChannelSendFailed(format!("bracket order: {}", e)).into())
+    }
+
+    /// Send the entry order; exits follow as it fills. Returns the bracket
+    /// id.
+    pub fn place(&self, bracket: Bracket) -> Result<u64, HftError> {
+        bracket.validate()?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.state.lock().unwrap().positions.insert(id, Position { bracket: bracket.clone(), exits: Vec::new() });
+        if let Err(e) = self.send(id, Some(Role::Entry), bracket.entry.clone()) {
+            let mut state = self.state.lock().unwrap();
+            state.positions.remove(&id);
+            state.pending.retain(|pending| pending.bracket != id);
+            return Err(e);
+        }
+        info!(bracket = id, symbol = %bracket.entry.symbol, take_profit = bracket.take_profit, stop_loss = bracket.stop_loss, "Bracket entry sent");
+        Ok(id)
+    }
+
+    /// Follow the gateway's report on an order this manager sent
+    fn on_order_event(&self, event: &OrderEvent) {
+        let (order, order_id) = match event {
+            OrderEvent::Submitted { order_id, order, .. } => (order, Some(order_id)),
+            OrderEvent::Rejected { order, .. } => (order, None),
+            _ => return,
+        };
+        let mut state = self.state.lock().unwrap();
+        let Some(index) = state.pending.iter().position(|pending| pending.order.is_reported_as(order)) else {
+            return;
+        };
+        let Pending { bracket, role, .. } = state.pending.remove(index);
+        match (order_id, role) {
+            (Some(order_id), Role::Entry) => {
+                // Exits go to wherever the entry ended up
+                if let Some(position) = state.positions.get_mut(&bracket) {
+                    position.bracket.entry.venue = order.venue;
+                }
+                state.by_order.insert(order_id.clone(), (bracket, role));
+            }
+            (Some(order_id), Role::TakeProfit(index)) => {
+                if let Some(exits) = state.positions.get_mut(&bracket).and_then(|p| p.exits.get_mut(index)) {
+                    exits.take_profit = Some(order_id.clone());
+                }
+                state.by_order.insert(order_id.clone(), (bracket, role));
+            }
+            (None, Role::Entry) => {
+                warn!(bracket = bracket, symbol = %order.symbol, "Bracket entry rejected");
+                state.positions.remove(&bracket);
+            }
+            (None, Role::TakeProfit(_)) => {
+                error!(bracket = bracket, symbol = %order.symbol, "Take-profit rejected, only the stop protects the position");
+            }
+        }
+    }
+
+    /// Track a fill: entry fills get exits of their own, and a take-profit
+    /// fill disarms its stop
+    pub async fn on_fill(&self, fill: &Fill) {
+        let Some((id, role)) = self.state.lock().unwrap().by_order.get(&fill.order_id).copied() else {
+            return;
+        };
+        match role {
+            Role::Entry => self.protect(id, fill.quantity),
+            Role::TakeProfit(exits) => self.close(id, exits, "take_profit"),
+        }
+    }
+
+    /// Fire the stops the quote has reached
+    pub async fn on_quote(&self, quote: &Quote) {
+        let triggered: Vec<(u64, usize)> = {
+            let mut state = self.state.lock().unwrap();
+            let mut triggered = Vec::new();
+            for (&id, position) in state.positions.iter_mut() {
+                let bracket = &position.bracket;
+                if bracket.entry.venue != quote.venue || bracket.entry.symbol != quote.symbol || !bracket.stop_triggered(quote) {
+                    continue;
+                }
+                for (i, exits) in position.exits.iter_mut().enumerate() {
+                    if exits.armed && !exits.closed {
+                        exits.armed = false;
+                        triggered.push((id, i));
+                    }
+                }
+            }
+            triggered
+        };
+        for (id, exits) in triggered {
+            self.stop_out(id, exits).await;
+        }
+    }
+
+    /// Send the take-profit and arm the stop for `quantity` of newly
+    /// filled entry
+    fn protect(&self, id: u64, quantity: f64) {
+        let (bracket, index) = {
+            let mut state = self.state.lock().unwrap();
+            let Some(position) = state.positions.get_mut(&id) else {
+                return;
+            };
+            position.exits.push(Exits { take_profit: None, quantity, armed: true, closed: false });
+            (position.bracket.clone(), position.exits.len() - 1)
+        };
+        let take_profit = bracket.exit(quantity, OrderType::Limit, bracket.take_profit);
+        if let Err(e) = self.send(id, Some(Role::TakeProfit(index)), take_profit) {
+            error!(bracket = id, error = %e, "Take-profit not sent, only the stop protects the position");
+        }
+    }
+
+    /// Close an exit pair once one side fills or triggers, cancelling the
+    /// take-profit when the stop fired
+    fn close(&self, id: u64, index: usize, exit: &'static str) {
+        let (bracket, cancel) = {
+            let mut state = self.state.lock().unwrap();
+            let Some(position) = state.positions.get_mut(&id) else {
+                return;
+            };
+            let exits = &mut position.exits[index];
+            if exits.closed {
+                return;
+            }
+            exits.closed = true;
+            exits.armed = false;
+            BRACKET_EXITS.with_label_values(&[exit]).inc();
+            let cancel = match exit {
+                "take_profit" => None,
+                _ => exits.take_profit.clone(),
+            };
+            (position.bracket.clone(), cancel)
+        };
+        info!(bracket = id, exit = exit, symbol = %bracket.entry.symbol, "Bracket exit hit");
+
+        let Some(order_id) = cancel else {
+            return;
+        };
+        let request = CancelRequest::order(order_id.clone(), "bracket", format!("bracket {} {} exit", id, exit));
+        if let Err(e) = self.cancel_tx.try_send(request) {
+            error!(bracket = id, order_id = %order_id, error = %e, "Take-profit cancel not sent, it still rests on the venue");
+        }
+    }
+
+    /// Cancel the take-profit of a triggered stop and close what it has not
+    /// filled with a market order through the gateway
+    async fn stop_out(&self, id: u64, index: usize) {
+        let (bracket, quantity, take_profit) = {
+            let state = self.state.lock().unwrap();
+            let Some(position) = state.positions.get(&id) else {
+                return;
+            };
+            let exits = &position.exits[index];
+            (position.bracket.clone(), exits.quantity, exits.take_profit.clone())
+        };
+        let filled = match &take_profit {
+            Some(order_id) => self.orders.get(order_id).await.map(|open| open.filled_quantity).unwrap_or(0.0),
+            None => 0.0,
+        };
+        self.close(id, index, "stop");
+        let remaining = quantity - filled;
+        if remaining <= f64::EPSILON {
+            return;
+        }
+
+        if let Err(e) = self.send(id, None, bracket.exit(remaining, OrderType::Market, 0.0)) {
+            error!(bracket = id, error = %e, "Stop order failed, position left open");
+        }
+    }
+
+    /// Watch the quotes on `feed` for stops
+    pub async fn run(self: Arc<Self>, feed: FeedPublisher) {
+        let mut frames = feed.subscribe();
+        loop {
+            match frames.recv().await {
+                Ok(frame) => {
+                    if let Ok(Some((FeedMessage::Quote(quote), _))) = wire::decode(&frame) {
+                        self.on_quote(&quote).await;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(skipped = skipped, "Bracket stops lagging the feed, quotes dropped");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for BracketManager {
+    fn name(&self) -> &str {
+        "brackets"
+    }
+
+    async fn publish(&self, events: &[OrderEvent]) -> Result<(), HftError> {
+        for event in events {
+            match event {
+                OrderEvent::Fill(fill) => self.on_fill(fill).await,
+                event => self.on_order_event(event),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gateways::order::CancelTarget;
+
+    fn bracket() -> Bracket {
+        Bracket {
+            entry: Order {
+                symbol: "BTCUSDT".into(),
+                side: OrderSide::Buy,
+                quantity: 1.0,
+                price: 100.0,
+                venue: "MOCK".into(),
+                order_type: OrderType::Limit,
+                expire_after: None,
+                strategy: Some("breakout".to_string()),
+                bypass_kill_switch: false,
+            },
+            take_profit: 110.0,
+            stop_loss: 95.0,
+        }
+    }
+
+    fn fill(order_id: &str, side: OrderSide, quantity: f64) -> Fill {
+        Fill {
+            order_id: order_id.to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "MOCK".into(),
+            strategy: "breakout".to_string(),
+            side,
+            quantity,
+            price: 100.0,
+            timestamp: 1,
+            commission: None,
+        }
+    }
+
+    fn manager() -> (BracketManager, mpsc::Receiver<Order>, mpsc::Receiver<CancelRequest>) {
+        let (order_tx, order_rx) = mpsc::channel(8);
+        let (cancel_tx, cancel_rx) = mpsc::channel(8);
+        (BracketManager::new(order_tx, cancel_tx, Arc::new(OrderTracker::new())), order_rx, cancel_rx)
+    }
+
+    fn submitted(order_id: &str, order: Order) -> OrderEvent {
+        OrderEvent::Submitted { order_id: order_id.to_string(), order, timestamp: 1 }
+    }
+
+    fn quote(bid: f64) -> Quote {
+        Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask: bid + 0.5,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "MOCK".into(),
+            timestamp: 2,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_engine_stop_cancels_take_profit() {
+        let (manager, mut order_rx, mut cancel_rx) = manager();
+        manager.place(bracket()).unwrap();
+        let entry = order_rx.try_recv().unwrap();
+        manager.publish(&[submitted("entry", entry)]).await.unwrap();
+        manager.publish(&[OrderEvent::Fill(fill("entry", OrderSide::Buy, 1.0))]).await.unwrap();
+
+        // The take-profit goes to the gateway
+        let take_profit = order_rx.try_recv().unwrap();
+        assert_eq!((take_profit.side, take_profit.price), (OrderSide::Sell, 110.0));
+        manager.orders.insert("take-profit".to_string(), take_profit.clone()).await;
+        manager.publish(&[submitted("take-profit", take_profit)]).await.unwrap();
+
+        manager.on_quote(&quote(96.0)).await;
+        assert!(order_rx.try_recv().is_err());
+
+        manager.on_quote(&quote(94.0)).await;
+        manager.on_quote(&quote(94.0)).await;
+        let stop = order_rx.try_recv().unwrap();
+        assert_eq!((stop.order_type, stop.side, stop.quantity), (OrderType::Market, OrderSide::Sell, 1.0));
+        assert!(order_rx.try_recv().is_err());
+        // and so does the take-profit's cancel, once
+        let cancel = cancel_rx.try_recv().unwrap();
+        assert_eq!((cancel.target, cancel.kind), (CancelTarget::Order("take-profit".to_string()), "bracket"));
+        assert!(cancel_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_take_profit_fill_disarms_stop() {
+        let (manager, mut order_rx, mut cancel_rx) = manager();
+        manager.place(bracket()).unwrap();
+        manager.publish(&[submitted("entry", order_rx.try_recv().unwrap())]).await.unwrap();
+        manager.on_fill(&fill("entry", OrderSide::Buy, 0.5)).await;
+        let take_profit = order_rx.try_recv().unwrap();
+        assert_eq!(take_profit.quantity, 0.5);
+        manager.publish(&[submitted("take-profit", take_profit)]).await.unwrap();
+
+        manager.on_fill(&fill("take-profit", OrderSide::Sell, 0.5)).await;
+        manager.on_quote(&quote(90.0)).await;
+        assert!(order_rx.try_recv().is_err());
+        assert!(cancel_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rejected_entry_drops_bracket() {
+        let (manager, mut order_rx, _) = manager();
+        manager.place(bracket()).unwrap();
+        let entry = order_rx.try_recv().unwrap();
+        manager.publish(&[OrderEvent::Rejected { order: entry, reason: "halted".to_string(), timestamp: 1 }]).await.unwrap();
+        assert!(manager.state.lock().unwrap().positions.is_empty());
+
+        // A fill for an order id never acked is not the bracket's
+        manager.on_fill(&fill("entry", OrderSide::Buy, 1.0)).await;
+        assert!(order_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stop_must_sit_behind_take_profit() {
+        let (manager, mut order_rx, _) = manager();
+        let inverted = Bracket { stop_loss: 120.0, ..bracket() };
+        assert!(manager.place(inverted).is_err());
+        assert!(order_rx.try_recv().is_err());
+    }
+}
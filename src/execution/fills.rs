@@ -0,0 +1,49 @@
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::execution::OrderTracker;
+use crate::risk::RiskManager;
+use crate::sink::{OrderEvent, SinkHandle};
+use crate::types::Fill;
+
+/// Takes live fills from the venues' user data streams, books them against
+/// positions and open orders, then hands them to the strategies and to
+/// order algos following their own orders.
+pub struct FillRouter {
+    pub(crate) fill_rx: mpsc::Receiver<Fill>,
+    pub(crate) risk: Arc<RiskManager>,
+    pub(crate) orders: Arc<OrderTracker>,
+    /// The strategy runner's fill channel
+    pub(crate) strategies: mpsc::Sender<Fill>,
+    /// Algos that follow fills as order events, e.g. brackets
+    pub(crate) sinks: Vec<SinkHandle>,
+}
+
+impl FillRouter {
+    /// Attribute `fill` to the strategy of the order it filled, which
+    /// venues do not know, and record it against the order
+    async fn book(&self, mut fill: Fill) -> Fill {
+        if fill.strategy.is_empty() {
+            if let Some(strategy) = self.orders.get(&fill.order_id).await.and_then(|open| open.order.strategy) {
+                fill.strategy = strategy;
+            }
+        }
+        self.orders.record_fill(&fill.order_id, fill.quantity).await;
+        self.risk.on_fill(&fill).await;
+        fill
+    }
+
+    pub async fn run(&mut self) {
+        while let Some(fill) = self.fill_rx.recv().await {
+            let fill = self.book(fill).await;
+            for sink in &self.sinks {
+                sink.send(OrderEvent::Fill(fill.clone()));
+            }
+            if let Err(e) = self.strategies.try_send(fill) {
+                let fill = e.into_inner();
+                warn!(venue = %fill.venue, order_id = %fill.order_id, "Strategies behind, fill not passed on");
+            }
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Orders entered by an operator, e.g. to flatten or correct a position,
+//! and emergency controls.
+//!
+//! Manual orders take the path strategy orders do: pre-trade risk checks,
+//! the audit log and the order gateway. They are tagged with the `manual`
+//! strategy so their positions and PnL are kept apart, and are only sent
+//! when the request is explicitly confirmed.
+
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use warp::http::StatusCode;
+use warp::Filter;
+
+use crate::audit::{AuditEvent, AuditLog};
+use crate::error::{ExecutionError, GatewayError, HftError};
+use crate::execution::{ExecutionEngine, OpenOrder, OrderStatus, OrderTracker};
+use crate::instruments::InstrumentMap;
+use crate::metrics::ORDER_CANCELS;
+use crate::risk::LossScope;
+use crate::types::{Order, OrderSide, OrderType};
+use crate::venues::VenueRegistry;
+
+/// Strategy manual orders are attributed to
+pub const MANUAL_STRATEGY: &str = "manual";
+
+/// An order to enter by hand
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManualOrderRequest {
+    pub venue: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: f64,
+    /// Limit price; a market order when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    /// Nothing is sent unless this is set
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+impl ManualOrderRequest {
+    pub fn order(&self) -> Result<Order, HftError> {
+        if !(self.quantity > 0.0 && self.quantity.is_finite()) {
+            return Err(ExecutionError::InvalidOrder(format!("quantity must be positive, got {}", self.quantity)).into());
+        }
+        if let Some(price) = self.price.filter(|price| !(*price > 0.0 && price.is_finite())) {
+            return Err(ExecutionError::InvalidOrder(format!("price must be positive, got {}", price)).into());
+        }
+        Ok(Order {
+            symbol: self.symbol.as_str().into(),
+            side: self.side,
+            quantity: self.quantity,
+            price: self.price.unwrap_or(0.0),
+            venue: self.venue.as_str().into(),
+            order_type: if self.price.is_some() { OrderType::Limit } else { OrderType::Market },
+            expire_after: None,
+            strategy: Some(MANUAL_STRATEGY.to_string()),
+            bypass_kill_switch: false,
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Confirmation {
+    #[serde(default)]
+    confirm: bool,
+}
+
+/// Submits and cancels manual orders
+#[derive(Clone)]
+pub struct ManualOrders {
+    pub(crate) execution: ExecutionEngine,
+    pub(crate) orders: Arc<OrderTracker>,
+    pub(crate) venues: VenueRegistry,
+    pub(crate) instruments: Arc<InstrumentMap>,
+    pub(crate) audit: Option<AuditLog>,
+}
+
+impl ManualOrders {
+    /// Risk check `request` and hand it to the order gateway
+    pub async fn submit(&self, request: &ManualOrderRequest) -> Result<Order, HftError> {
+        let order = request.order()?;
+        if self.venues.get(&order.venue).is_none() {
+            return Err(GatewayError::InvalidSymbol(format!("No venue configured for {}", order.venue)).into());
+        }
+
+        info!(venue = %order.venue, symbol = %order.symbol, side = ?order.side, quantity = %order.quantity, price = %order.price, "Manual order");
+        if let Err(e) = self.execution.execute_order(order.clone()).await {
+            warn!(venue = %order.venue, symbol = %order.symbol, error = %e, "Manual order refused");
+            return Err(e);
+        }
+        Ok(order)
+    }
+
+    /// Cancel the open order `order_id` on its venue
+    pub async fn cancel(&self, order_id: &str) -> Result<OpenOrder, HftError> {
+        let open = self.orders.get(order_id).await
+            .ok_or_else(|| ExecutionError::InvalidOrder(format!("Unknown order {}", order_id)))?;
+        let venue = self.venues.get(&open.order.venue)
+            .ok_or_else(|| GatewayError::InvalidSymbol(format!("No venue configured for {}", open.order.venue)))?;
+
+        let symbol = self.instruments.venue_symbol(open.order.venue, open.order.symbol);
+        venue.cancel_order(order_id, &symbol).await?;
+        info!(venue = %open.order.venue, order_id = %order_id, "Manual cancel");
+        ORDER_CANCELS.with_label_values(&[&open.order.venue, open.order.strategy_label(), "manual"]).inc();
+        if let Some(audit) = &self.audit {
+            audit.record(AuditEvent::Cancel {
+                venue: open.order.venue.to_string(),
+                order_id: Some(order_id.to_string()),
+                reason: "manual".to_string(),
+            });
+        }
+        Ok(self.orders.update_status(order_id, OrderStatus::Cancelled).await.unwrap_or(open))
+    }
+
+    /// Engage the kill switch, cancel every open order and send market
+    /// orders flattening every position, returning those orders. Both go
+    /// through the order gateway, the orders past the kill switch.
+    pub async fn flatten(&self) -> Vec<Order> {
+        let risk = &self.execution.risk;
+        risk.kill_switch().engage("manual flatten").await;
+        risk.cancel_all("manual", "manual flatten").await;
+
+        let orders = risk.flatten(&LossScope::Portfolio).await;
+        if let Some(audit) = &self.audit {
+            for order in &orders {
+                audit.record(AuditEvent::OrderRequest { strategy: Some(MANUAL_STRATEGY.to_string()), order: order.clone() });
+            }
+        }
+        orders
+    }
+
+    /// Halt all new orders until released
+    pub async fn engage_kill_switch(&self, reason: &str) {
+        self.execution.risk.kill_switch().engage(reason).await;
+    }
+
+    pub async fn release_kill_switch(&self) {
+        self.execution.risk.kill_switch().release().await;
+    }
+}
+
+fn reply<T: Serialize>(result: Result<T, HftError>) -> warp::reply::WithStatus<warp::reply::Json> {
+    let (body, status) = match result {
+        Ok(value) => (serde_json::json!(value), StatusCode::OK),
+        Err(e @ HftError::Execution(_)) => (serde_json::json!({ "error": e.to_string() }), StatusCode::UNPROCESSABLE_ENTITY),
+        Err(e) => (serde_json::json!({ "error": e.to_string() }), StatusCode::BAD_GATEWAY),
+    };
+    warp::reply::with_status(warp::reply::json(&body), status)
+}
+
+fn unconfirmed() -> warp::reply::WithStatus<warp::reply::Json> {
+    warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({ "error": "not confirmed, set \"confirm\": true to send" })),
+        StatusCode::BAD_REQUEST,
+    )
+}
+
+/// Admin endpoints:
+/// - `POST /admin/orders` submits the [`ManualOrderRequest`] in the body
+/// - `POST /admin/orders/{id}/cancel` cancels an open order, with
+///   `{"confirm": true}` as the body
+/// - `POST /admin/flatten` halts trading, cancels all orders and flattens
+///   all positions
+/// - `POST /admin/kill-switch/{engage|release}`
+pub fn routes(
+    manual: ManualOrders,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let submit = {
+        let manual = manual.clone();
+        warp::path!("admin" / "orders")
+            .and(warp::post())
+            .and(warp::body::json())
+            .then(move |request: ManualOrderRequest| {
+                let manual = manual.clone();
+                async move {
+                    if !request.confirm {
+                        return unconfirmed();
+                    }
+                    reply(manual.submit(&request).await)
+                }
+            })
+    };
+
+    let cancel = {
+        let manual = manual.clone();
+        warp::path!("admin" / "orders" / String / "cancel")
+            .and(warp::post())
+            .and(warp::body::json())
+            .then(move |order_id: String, confirmation: Confirmation| {
+                let manual = manual.clone();
+                async move {
+                    if !confirmation.confirm {
+                        return unconfirmed();
+                    }
+                    reply(manual.cancel(&order_id).await)
+                }
+            })
+    };
+
+    let flatten = {
+        let manual = manual.clone();
+        warp::path!("admin" / "flatten")
+            .and(warp::post())
+            .then(move || {
+                let manual = manual.clone();
+                async move { warp::reply::json(&serde_json::json!({ "orders": manual.flatten().await })) }
+            })
+    };
+
+    let kill_switch = warp::path!("admin" / "kill-switch" / String)
+        .and(warp::post())
+        .and_then(move |action: String| {
+            let manual = manual.clone();
+            async move {
+                match action.as_str() {
+                    "engage" => manual.engage_kill_switch("engaged by operator").await,
+                    "release" => manual.release_kill_switch().await,
+                    _ => return Err(warp::reject::not_found()),
+                }
+                let reason = manual.execution.risk.kill_switch().reason().await;
+                Ok(warp::reply::json(&serde_json::json!({ "engaged": reason.is_some(), "reason": reason })))
+            }
+        });
+
+    submit.or(cancel).or(flatten).or(kill_switch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+    use crate::mocks::mock_venue::{MockVenue, MockVenueConfig};
+    use crate::risk::{LossLimits, RiskManager};
+    use crate::venues::VenueAdapter;
+    use crate::command::mode::TradingMode;
+    use crate::gateways::order::CancelRequest;
+
+    async fn manual() -> (ManualOrders, mpsc::Receiver<Order>, mpsc::Receiver<CancelRequest>, Arc<RiskManager>, Arc<MockVenue>) {
+        let (order_tx, order_rx) = mpsc::channel(8);
+        let (cancel_tx, cancel_rx) = mpsc::channel(8);
+        let risk = Arc::new(RiskManager::new(LossLimits::default()).with_gateway(order_tx.clone(), cancel_tx));
+        risk.trading_state().transition(TradingMode::Active, "test").unwrap();
+        let venue = Arc::new(MockVenue::new("MOCK", MockVenueConfig { error_probability: 0.0, ..MockVenueConfig::default() }));
+        let manual = ManualOrders {
+            execution: ExecutionEngine { order_tx, risk: Arc::clone(&risk), audit: None },
+            orders: Arc::new(OrderTracker::new()),
+            venues: VenueRegistry::from_venues(vec![venue.clone() as Arc<dyn VenueAdapter>]).await,
+            instruments: Arc::new(InstrumentMap::new()),
+            audit: None,
+        };
+        (manual, order_rx, cancel_rx, risk, venue)
+    }
+
+    fn request(confirm: bool) -> serde_json::Value {
+        serde_json::json!({ "venue": "MOCK", "symbol": "BTCUSDT", "side": "Sell", "quantity": 0.5, "confirm": confirm })
+    }
+
+    #[tokio::test]
+    async fn test_submit_needs_confirmation_and_passes_risk_checks() {
+        let (manual, mut order_rx, _, risk, _) = manual().await;
+        let api = routes(manual);
+
+        let response = warp::test::request().method("POST").path("/admin/orders").json(&request(false)).reply(&api).await;
+        assert_eq!(response.status(), 400);
+        assert!(order_rx.try_recv().is_err());
+
+        let response = warp::test::request().method("POST").path("/admin/orders").json(&request(true)).reply(&api).await;
+        assert_eq!(response.status(), 200);
+        let order = order_rx.try_recv().unwrap();
+        assert_eq!((order.order_type, order.strategy.as_deref()), (OrderType::Market, Some(MANUAL_STRATEGY)));
+
+        risk.kill_switch().engage("daily loss").await;
+        let response = warp::test::request().method("POST").path("/admin/orders").json(&request(true)).reply(&api).await;
+        assert_eq!(response.status(), 422);
+        assert!(order_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_open_order() {
+        let (manual, _, _, _, venue) = manual().await;
+        let order = ManualOrderRequest {
+            venue: "MOCK".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price: Some(100.0),
+            confirm: true,
+        }.order().unwrap();
+        let order_id = venue.submit_order(order.clone()).await.unwrap();
+        manual.orders.insert(order_id.clone(), order).await;
+        let api = routes(manual.clone());
+
+        let path = format!("/admin/orders/{}/cancel", order_id);
+        let response = warp::test::request().method("POST").path(&path).json(&serde_json::json!({})).reply(&api).await;
+        assert_eq!(response.status(), 400);
+
+        let response = warp::test::request().method("POST").path(&path).json(&serde_json::json!({ "confirm": true })).reply(&api).await;
+        assert_eq!(response.status(), 200);
+        assert!(venue.open_orders().await.unwrap().is_empty());
+        assert!(manual.orders.get(&order_id).await.is_none());
+
+        let response = warp::test::request().method("POST").path("/admin/orders/missing/cancel").json(&serde_json::json!({ "confirm": true })).reply(&api).await;
+        assert_eq!(response.status(), 422);
+    }
+
+    #[tokio::test]
+    async fn test_flatten_halts_and_closes_positions() {
+        let (manual, mut order_rx, mut cancel_rx, risk, _) = manual().await;
+        risk.on_fill(&crate::types::Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "MOCK".into(),
+            strategy: "mm".to_string(),
+            side: OrderSide::Buy,
+            quantity: 2.0,
+            price: 100.0,
+            timestamp: 1,
+            commission: None,
+        }).await;
+        let api = routes(manual);
+
+        let response = warp::test::request().method("POST").path("/admin/flatten").reply(&api).await;
+        assert_eq!(response.status(), 200);
+        assert!(risk.kill_switch().is_engaged().await);
+        assert_eq!(cancel_rx.try_recv().unwrap(), CancelRequest::all(None, "manual", "manual flatten"));
+        let sent = order_rx.try_recv().unwrap();
+        assert!(order_rx.try_recv().is_err());
+        assert_eq!((sent.side, sent.quantity, sent.bypass_kill_switch), (OrderSide::Sell, 2.0, true));
+
+        let response = warp::test::request().method("POST").path("/admin/kill-switch/release").reply(&api).await;
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["engaged"], false);
+    }
+}
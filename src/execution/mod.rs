@@ -1,25 +1,67 @@
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use crate::types::Order;
-use crate::metrics::{ORDER_LATENCY, ACTIVE_ORDERS};
+use crate::risk::RiskManager;
+use crate::error::{HftError, GatewayError};
+use crate::metrics::{ORDER_LATENCY, ORDER_REJECTS};
+use crate::audit::{AuditEvent, AuditLog};
 use std::time::Instant;
 
+pub mod orders;
+pub mod fills;
+pub mod bracket;
+pub mod manual;
+pub mod multileg;
+pub mod quoting;
+pub mod trailing;
+
+pub use orders::{OpenOrder, OrderStatus, OrderTracker};
+pub use fills::FillRouter;
+pub use bracket::{Bracket, BracketManager};
+pub use manual::{ManualOrderRequest, ManualOrders};
+pub use multileg::{LegCoordinator, LegOutPolicy};
+pub use quoting::{QuoteThrottle, QuoteThrottleConfig};
+pub use trailing::{ProtectedPosition, TrailDistance, TrailingStopConfig, TrailingStops};
+
+#[derive(Clone)]
 pub struct ExecutionEngine {
     pub(crate) order_tx: mpsc::Sender<Order>,
+    pub(crate) risk: Arc<RiskManager>,
+    pub(crate) audit: Option<AuditLog>,
 }
 
 impl ExecutionEngine {
-    async fn execute_order(&self, order: Order) {
+    pub async fn execute_order(&self, order: Order) -> Result<(), HftError> {
         let start = Instant::now();
 
-        // Order execution logic here
+        let decision = self.risk.check_order(&order).await;
+        if let Some(audit) = &self.audit {
+            audit.record(AuditEvent::OrderRequest { strategy: order.strategy.clone(), order: order.clone() });
+            audit.record(AuditEvent::RiskDecision {
+                order: order.clone(),
+                accepted: decision.is_ok(),
+                reason: decision.as_ref().err().map(|e| e.to_string()),
+            });
+        }
+        if let Err(e) = decision {
+            ORDER_REJECTS
+                .with_label_values(&[&order.venue, order.strategy_label(), e.reason_label()])
+                .inc();
+            return Err(e);
+        }
+
+        let venue = order.venue;
+        let order_type = order.order_type.to_string();
+
+        // Hand the order to the order gateway for routing
+        self.order_tx.send(order).await
+            .map_err(|e| GatewayError::ChannelSendFailed(format!("Failed to send order: {}", e)))?;
 
         let duration = start.elapsed();
         ORDER_LATENCY
-            .with_label_values(&[&order.venue, &order.order_type.to_string()])
+            .with_label_values(&[&venue, &order_type])
             .observe(duration.as_secs_f64());
 
-        ACTIVE_ORDERS
-            .with_label_values(&[&order.venue])
-            .inc();
+        Ok(())
     }
-}
\ No newline at end of file
+}
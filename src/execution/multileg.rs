@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::error::{ExecutionError, HftError};
+use crate::metrics::{LEGGING_EVENTS, LEG_REMEDIATION_ORDERS};
+use crate::sink::{EventSink, OrderEvent};
+use crate::types::{Fill, Order, OrderType};
+
+/// What to do when some legs of a multi-leg order are live and another
+/// is rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegOutPolicy {
+    /// Resend the rejected leg as a market order, falling back to `Hedge`
+    /// after `max_attempts` rejections
+    Chase { max_attempts: u32 },
+    /// Unwind whatever the live legs have filled, including later fills
+    Hedge,
+    /// Keep the exposure and only report the legging event
+    Abandon,
+}
+
+impl fmt::Display for LegOutPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LegOutPolicy::Chase { .. } => write!(f, "chase"),
+            LegOutPolicy::Hedge => write!(f, "hedge"),
+            LegOutPolicy::Abandon => write!(f, "abandon"),
+        }
+    }
+}
+
+struct Leg {
+    order: Order,
+    order_id: Option<String>,
+    filled: f64,
+    /// Filled quantity already offset by unwind orders
+    unwound: f64,
+    attempts: u32,
+    rejected: bool,
+}
+
+impl Leg {
+    fn is_live(&self) -> bool {
+        self.order_id.is_some() || self.filled > 0.0
+    }
+}
+
+struct Execution {
+    legs: Vec<Leg>,
+    policy: LegOutPolicy,
+    legged: bool,
+    unwinding: bool,
+    /// Legs sent to the gateway whose ack or rejection has not come back
+    outstanding: usize,
+}
+
+enum Action {
+    Resubmit { leg: usize, order: Order },
+    Unwind(Order),
+}
+
+/// A leg sent to the gateway whose ack or rejection has not come back
+struct PendingLeg {
+    execution: u64,
+    leg: usize,
+    order: Order,
+}
+
+#[derive(Default)]
+struct State {
+    executions: HashMap<u64, Execution>,
+    by_order: HashMap<String, (u64, usize)>,
+    pending: Vec<PendingLeg>,
+}
+
+impl State {
+    /// Record the gateway's ack, with the venue's order id, or rejection of
+    /// a leg, returning the remediation once every leg sent is accounted
+    /// for
+    fn record(&mut self, id: u64, leg: usize, order_id: Option<&str>) -> Vec<Action> {
+        let Some(execution) = self.executions.get_mut(&id) else {
+            return Vec::new();
+        };
+        execution.outstanding = execution.outstanding.saturating_sub(1);
+        let leg_state = &mut execution.legs[leg];
+        leg_state.attempts += 1;
+        match order_id {
+            Some(order_id) => {
+                leg_state.order_id = Some(order_id.to_string());
+                leg_state.rejected = false;
+                self.by_order.insert(order_id.to_string(), (id, leg));
+            }
+            None => leg_state.rejected = true,
+        }
+        if execution.outstanding > 0 {
+            return Vec::new();
+        }
+        if !execution.legs.iter().any(Leg::is_live) {
+            error!(execution = id, "All legs of multi-leg order rejected");
+            return Vec::new();
+        }
+        self.remediate(id)
+    }
+
+    /// Decide remediation once all outstanding submissions for `id` are known
+    fn remediate(&mut self, id: u64) -> Vec<Action> {
+        let Some(execution) = self.executions.get_mut(&id) else {
+            return Vec::new();
+        };
+        let rejected: Vec<usize> = (0..execution.legs.len()).filter(|&i| execution.legs[i].rejected).collect();
+        if rejected.is_empty() || !execution.legs.iter().any(Leg::is_live) {
+            return Vec::new();
+        }
+
+        if !execution.legged {
+            execution.legged = true;
+            LEGGING_EVENTS.with_label_values(&[&execution.policy.to_string()]).inc();
+            warn!(execution = id, policy = %execution.policy, rejected = ?rejected, "Multi-leg order legged");
+        }
+
+        let mut actions = Vec::new();
+        match execution.policy {
+            LegOutPolicy::Abandon => {
+                for &i in &rejected {
+                    execution.legs[i].rejected = false;
+                }
+            }
+            LegOutPolicy::Chase { max_attempts } => {
+                for &i in &rejected {
+                    let leg = &mut execution.legs[i];
+                    if leg.attempts < max_attempts {
+                        leg.rejected = false;
+                        let mut order = leg.order.clone();
+                        order.order_type = OrderType::Market;
+                        order.price = 0.0;
+                        execution.outstanding += 1;
+                        actions.push(Action::Resubmit { leg: i, order });
+                    } else {
+                        execution.unwinding = true;
+                    }
+                }
+            }
+            LegOutPolicy::Hedge => execution.unwinding = true,
+        }
+
+        if execution.unwinding {
+            for leg in &mut execution.legs {
+                leg.rejected = false;
+                if leg.filled > leg.unwound {
+                    actions.push(Action::Unwind(unwind_order(&leg.order, leg.filled - leg.unwound)));
+                    leg.unwound = leg.filled;
+                }
+            }
+        }
+
+        actions
+    }
+
+    fn on_fill(&mut self, fill: &Fill) -> Option<Order> {
+        let &(id, leg) = self.by_order.get(&fill.order_id)?;
+        let execution = self.executions.get_mut(&id)?;
+        let leg = &mut execution.legs[leg];
+        leg.filled += fill.quantity;
+
+        if execution.unwinding && leg.filled > leg.unwound {
+            let order = unwind_order(&leg.order, leg.filled - leg.unwound);
+            leg.unwound = leg.filled;
+            return Some(order);
+        }
+        None
+    }
+}
+
+fn unwind_order(leg: &Order, quantity: f64) -> Order {
+    Order {
+        symbol: leg.symbol,
+        side: leg.side.opposite(),
+        quantity,
+        price: 0.0,
+        venue: leg.venue,
+        order_type: OrderType::Market,
+        expire_after: None,
+        strategy: leg.strategy.clone(),
+        bypass_kill_switch: false,
+    }
+}
+
+/// Sends the legs of a multi-leg order together through the order gateway
+/// and applies a leg-out policy when some legs go live and others are
+/// rejected.
+///
+/// The gateway reports each leg's ack or rejection, risk check failures
+/// included, and the fill router reports fills back through [`EventSink`].
+pub struct LegCoordinator {
+    order_tx: mpsc::Sender<Order>,
+    state: Mutex<State>,
+    next_id: AtomicU64,
+}
+
+impl LegCoordinator {
+    pub fn new(order_tx: mpsc::Sender<Order>) -> Self {
+        Self {
+            order_tx,
+            state: Mutex::new(State::default()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Hand leg `leg` of execution `id`, already counted as outstanding, to
+    /// the gateway; a leg the gateway never got counts as rejected
+    fn send_leg(&self, id: u64, leg: usize, order: Order) -> Vec<Action> {
+        let mut state = self.state.lock().unwrap();
+        state.pending.push(PendingLeg { execution: id, leg, order: order.clone() });
+        if let Err(e) = self.order_tx.try_send(order) {
+            error!(execution = id, leg = leg, error = %e, "Failed to send leg to the order gateway");
+            state.pending.pop();
+            return state.record(id, leg, None);
+        }
+        Vec::new()
+    }
+
+    fn apply(&self, id: u64, actions: Vec<Action>) {
+        let mut actions = actions;
+        while let Some(action) = actions.pop() {
+            match action {
+                Action::Resubmit { leg, order } => {
+                    LEG_REMEDIATION_ORDERS.with_label_values(&["chase"]).inc();
+                    actions.extend(self.send_leg(id, leg, order));
+                }
+                Action::Unwind(order) => self.unwind(order),
+            }
+        }
+    }
+
+    fn unwind(&self, order: Order) {
+        LEG_REMEDIATION_ORDERS.with_label_values(&["unwind"]).inc();
+        info!(symbol = %order.symbol, venue = %order.venue, side = ?order.side, quantity = order.quantity, "Unwinding legged fill");
+        if let Err(e) = self.order_tx.try_send(order) {
+            error!(error = %e, "Failed to unwind legged fill, position left open");
+        }
+    }
+
+    /// Send all legs at once; once the gateway has reported on each, the
+    /// legs left out are remediated per `policy` until every leg is live or
+    /// the policy gives up. Returns the execution id.
+    pub fn execute(&self, legs: Vec<Order>, policy: LegOutPolicy) -> Result<u64, HftError> {
+        if legs.is_empty() {
+            return Err(ExecutionError::InvalidOrder("Multi-leg order has no legs".to_string()).into());
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.state.lock().unwrap().executions.insert(id, Execution {
+            legs: legs
+                .iter()
+                .map(|order| Leg { order: order.clone(), order_id: None, filled: 0.0, unwound: 0.0, attempts: 0, rejected: false })
+                .collect(),
+            policy,
+            legged: false,
+            unwinding: false,
+            outstanding: legs.len(),
+        });
+        for (leg, order) in legs.into_iter().enumerate() {
+            let actions = self.send_leg(id, leg, order);
+            self.apply(id, actions);
+        }
+        Ok(id)
+    }
+
+    /// Follow the gateway's report on a leg this coordinator sent
+    fn on_order_event(&self, event: &OrderEvent) {
+        let (order, order_id) = match event {
+            OrderEvent::Submitted { order_id, order, .. } => (order, Some(order_id.as_str())),
+            OrderEvent::Rejected { order, .. } => (order, None),
+            _ => return,
+        };
+        let (id, actions) = {
+            let mut state = self.state.lock().unwrap();
+            let Some(index) = state.pending.iter().position(|pending| pending.order.is_reported_as(order)) else {
+                return;
+            };
+            let PendingLeg { execution, leg, .. } = state.pending.remove(index);
+            (execution, state.record(execution, leg, order_id))
+        };
+        self.apply(id, actions);
+    }
+
+    /// Track a fill; fills on legged executions being unwound are offset
+    /// immediately
+    pub fn on_fill(&self, fill: &Fill) {
+        let unwind = self.state.lock().unwrap().on_fill(fill);
+        if let Some(order) = unwind {
+            self.unwind(order);
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for LegCoordinator {
+    fn name(&self) -> &str {
+        "multileg"
+    }
+
+    async fn publish(&self, events: &[OrderEvent]) -> Result<(), HftError> {
+        for event in events {
+            match event {
+                OrderEvent::Fill(fill) => self.on_fill(fill),
+                event => self.on_order_event(event),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderSide;
+
+    fn leg(symbol: &str, side: OrderSide) -> Order {
+        Order {
+            symbol: symbol.into(),
+            side,
+            quantity: 1.0,
+            price: 100.0,
+            venue: "MOCK".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        }
+    }
+
+    /// Ack every leg sent to the gateway but ETHUSDT sells, which are
+    /// rejected, returning the other orders sent meanwhile
+    async fn gateway(coordinator: &LegCoordinator, order_rx: &mut mpsc::Receiver<Order>) -> Vec<Order> {
+        let mut unwinds = Vec::new();
+        let mut acked = 0;
+        while let Ok(order) = order_rx.try_recv() {
+            let event = if order.symbol == "ETHUSDT" && order.side == OrderSide::Sell {
+                OrderEvent::Rejected { order, reason: "rejected".to_string(), timestamp: 1 }
+            } else if coordinator.state.lock().unwrap().pending.iter().any(|p| p.order.is_reported_as(&order)) {
+                acked += 1;
+                OrderEvent::Submitted { order_id: format!("leg_{}", acked), order, timestamp: 1 }
+            } else {
+                unwinds.push(order);
+                continue;
+            };
+            coordinator.publish(&[event]).await.unwrap();
+        }
+        unwinds
+    }
+
+    #[tokio::test]
+    async fn test_hedge_unwinds_fills_on_live_leg() {
+        let (order_tx, mut order_rx) = mpsc::channel(16);
+        let coordinator = LegCoordinator::new(order_tx);
+
+        let id = coordinator
+            .execute(vec![leg("BTCUSDT", OrderSide::Buy), leg("ETHUSDT", OrderSide::Sell)], LegOutPolicy::Hedge)
+            .unwrap();
+        assert_eq!(id, 1);
+        assert!(gateway(&coordinator, &mut order_rx).await.is_empty());
+
+        let order_id = coordinator.state.lock().unwrap().executions[&id].legs[0].order_id.clone().unwrap();
+        coordinator.on_fill(&Fill {
+            order_id,
+            symbol: "BTCUSDT".into(),
+            venue: "MOCK".into(),
+            strategy: "spread".to_string(),
+            side: OrderSide::Buy,
+            quantity: 0.4,
+            price: 100.0,
+            timestamp: 1,
+            commission: None,
+        });
+
+        // The unwind goes to the gateway like the legs did
+        let unwind = order_rx.try_recv().unwrap();
+        assert_eq!(unwind.side, OrderSide::Sell);
+        assert_eq!(unwind.quantity, 0.4);
+        assert_eq!(unwind.order_type, OrderType::Market);
+    }
+
+    #[tokio::test]
+    async fn test_chase_retries_then_falls_back_to_hedge() {
+        let (order_tx, mut order_rx) = mpsc::channel(16);
+        let coordinator = LegCoordinator::new(order_tx);
+
+        coordinator
+            .execute(
+                vec![leg("BTCUSDT", OrderSide::Buy), leg("ETHUSDT", OrderSide::Sell)],
+                LegOutPolicy::Chase { max_attempts: 3 },
+            )
+            .unwrap();
+        gateway(&coordinator, &mut order_rx).await;
+
+        let state = coordinator.state.lock().unwrap();
+        let execution = &state.executions[&1];
+        assert_eq!(execution.legs[1].attempts, 3);
+        assert!(execution.legged);
+        assert!(execution.unwinding);
+        assert!(state.pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_all_legs_rejected_is_not_remediated() {
+        let (order_tx, mut order_rx) = mpsc::channel(16);
+        let coordinator = LegCoordinator::new(order_tx);
+
+        coordinator.execute(vec![leg("ETHUSDT", OrderSide::Sell)], LegOutPolicy::Chase { max_attempts: 3 }).unwrap();
+        assert!(gateway(&coordinator, &mut order_rx).await.is_empty());
+        let state = coordinator.state.lock().unwrap();
+        assert_eq!(state.executions[&1].legs[0].attempts, 1);
+        assert!(!state.executions[&1].legged);
+        assert!(coordinator.execute(Vec::new(), LegOutPolicy::Hedge).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::types::Order;
+use crate::scheduler::timers::TimerWheel;
+use crate::metrics::ACTIVE_ORDERS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+impl OrderStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderStatus::Filled | OrderStatus::Cancelled | OrderStatus::Rejected)
+    }
+}
+
+/// An order acknowledged by a venue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenOrder {
+    pub order_id: String,
+    pub order: Order,
+    pub status: OrderStatus,
+    pub filled_quantity: f64,
+    /// Milliseconds since the Unix epoch
+    pub created_at: u64,
+}
+
+impl OpenOrder {
+    /// When the order's TTL runs out, if it has one
+    fn deadline(&self) -> Option<Instant> {
+        let expires_at = self.created_at + self.order.expire_after?;
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        Some(Instant::now() + Duration::from_millis(expires_at.saturating_sub(now)))
+    }
+}
+
+/// Resolution of the expiry timer wheel
+const EXPIRY_TICK: Duration = Duration::from_millis(10);
+
+/// Orders acknowledged by venues that have not reached a terminal state.
+///
+/// Keeps `hft_active_orders` in step with the orders it tracks.
+pub struct OrderTracker {
+    orders: RwLock<HashMap<String, OpenOrder>>,
+    expiries: Mutex<TimerWheel<String>>,
+}
+
+impl Default for OrderTracker {
+    fn default() -> Self {
+        Self {
+            orders: RwLock::new(HashMap::new()),
+            expiries: Mutex::new(TimerWheel::new(EXPIRY_TICK)),
+        }
+    }
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn schedule_expiry(&self, open: &OpenOrder) {
+        if let Some(deadline) = open.deadline() {
+            self.expiries.lock().unwrap().schedule(open.order_id.clone(), deadline);
+        }
+    }
+
+    pub async fn insert(&self, order_id: String, order: Order) {
+        let open = OpenOrder {
+            order_id: order_id.clone(),
+            order,
+            status: OrderStatus::New,
+            filled_quantity: 0.0,
+            created_at: chrono::Utc::now().timestamp_millis() as u64,
+        };
+        self.schedule_expiry(&open);
+        track(&mut *self.orders.write().await, open);
+    }
+
+    /// Re-insert an order as it was, e.g. from a snapshot
+    pub async fn restore(&self, open: OpenOrder) {
+        self.schedule_expiry(&open);
+        track(&mut *self.orders.write().await, open);
+    }
+
+    /// Tracked orders whose TTL has elapsed by `now`. Each is returned once;
+    /// the caller cancels it on the venue.
+    pub async fn expired(&self, now: Instant) -> Vec<OpenOrder> {
+        let due = self.expiries.lock().unwrap().advance(now);
+        if due.is_empty() {
+            return Vec::new();
+        }
+
+        let orders = self.orders.read().await;
+        due.iter().filter_map(|order_id| orders.get(order_id).cloned()).collect()
+    }
+
+    /// Try an expired order again after `delay`, e.g. when its cancel failed
+    pub fn retry_expiry(&self, order_id: String, delay: Duration) {
+        self.expiries.lock().unwrap().schedule(order_id, Instant::now() + delay);
+    }
+
+    /// Update an order's status, dropping it once terminal.
+    ///
+    /// Returns the order as last tracked, or `None` if it was unknown.
+    pub async fn update_status(&self, order_id: &str, status: OrderStatus) -> Option<OpenOrder> {
+        let mut orders = self.orders.write().await;
+        let open = orders.get_mut(order_id)?;
+        open.status = status;
+
+        if status.is_terminal() {
+            untrack(&mut orders, order_id)
+        } else {
+            Some(open.clone())
+        }
+    }
+
+    /// Add a fill to an order's progress, dropping it once fully filled.
+    ///
+    /// Returns the order as last tracked, or `None` if it was unknown.
+    pub async fn record_fill(&self, order_id: &str, quantity: f64) -> Option<OpenOrder> {
+        let mut orders = self.orders.write().await;
+        let open = orders.get_mut(order_id)?;
+        open.filled_quantity += quantity;
+        if open.filled_quantity + f64::EPSILON < open.order.quantity {
+            open.status = OrderStatus::PartiallyFilled;
+            return Some(open.clone());
+        }
+        open.status = OrderStatus::Filled;
+        untrack(&mut orders, order_id)
+    }
+
+    /// Apply an amend acknowledged by the venue, keeping fill progress
+    pub async fn amend(&self, order_id: &str, new_order_id: String, price: f64, quantity: f64) -> Option<OpenOrder> {
+        let mut orders = self.orders.write().await;
+        let mut open = orders.remove(order_id)?;
+        open.order_id = new_order_id.clone();
+        open.order.price = price;
+        open.order.quantity = quantity;
+        if new_order_id != order_id {
+            self.schedule_expiry(&open);
+        }
+        orders.insert(new_order_id, open.clone());
+        Some(open)
+    }
+
+    /// Replace all tracked orders, e.g. with a leader's mirrored state
+    pub async fn replace_all(&self, orders: Vec<OpenOrder>) {
+        for open in &orders {
+            self.schedule_expiry(open);
+        }
+        let mut tracked = self.orders.write().await;
+        for open in tracked.values() {
+            ACTIVE_ORDERS.with_label_values(&[&open.order.venue]).set(0.0);
+        }
+        *tracked = HashMap::new();
+        for open in orders {
+            track(&mut tracked, open);
+        }
+    }
+
+    pub async fn remove(&self, order_id: &str) -> Option<OpenOrder> {
+        untrack(&mut *self.orders.write().await, order_id)
+    }
+
+    /// Bring tracking for `venue` in line with the orders the venue reports
+    /// open. Tracked orders the venue no longer has are dropped and
+    /// returned, along with the IDs of open orders nobody was tracking.
+    pub async fn reconcile(&self, venue: &str, open_on_venue: &[String]) -> (Vec<OpenOrder>, Vec<String>) {
+        let mut orders = self.orders.write().await;
+        let gone: Vec<String> = orders.values()
+            .filter(|o| o.order.venue == venue && !open_on_venue.contains(&o.order_id))
+            .map(|o| o.order_id.clone())
+            .collect();
+        let dropped = gone.iter().filter_map(|id| orders.remove(id)).collect();
+        let untracked = open_on_venue.iter().filter(|id| !orders.contains_key(*id)).cloned().collect();
+
+        ACTIVE_ORDERS.with_label_values(&[venue]).set(open_on_venue.len() as f64);
+        (dropped, untracked)
+    }
+
+    pub async fn get(&self, order_id: &str) -> Option<OpenOrder> {
+        self.orders.read().await.get(order_id).cloned()
+    }
+
+    pub async fn open_orders(&self) -> Vec<OpenOrder> {
+        let mut orders: Vec<_> = self.orders.read().await.values().cloned().collect();
+        orders.sort_by_key(|o| o.created_at);
+        orders
+    }
+
+    pub async fn len(&self) -> usize {
+        self.orders.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.orders.read().await.is_empty()
+    }
+}
+
+fn track(orders: &mut HashMap<String, OpenOrder>, open: OpenOrder) {
+    let venue = open.order.venue;
+    if orders.insert(open.order_id.clone(), open).is_none() {
+        ACTIVE_ORDERS.with_label_values(&[&venue]).inc();
+    }
+}
+
+fn untrack(orders: &mut HashMap<String, OpenOrder>, order_id: &str) -> Option<OpenOrder> {
+    let open = orders.remove(order_id)?;
+    ACTIVE_ORDERS.with_label_values(&[&open.order.venue]).dec();
+    Some(open)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderSide, OrderType};
+
+    fn order(expire_after: Option<u64>) -> Order {
+        Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price: 50000.0,
+            venue: "MOCK".into(),
+            order_type: OrderType::Limit,
+            expire_after,
+            strategy: None,
+            bypass_kill_switch: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_orders_expire_after_ttl() {
+        let tracker = OrderTracker::new();
+        let start = Instant::now();
+        tracker.insert("short".to_string(), order(Some(50))).await;
+        tracker.insert("long".to_string(), order(Some(60_000))).await;
+        tracker.insert("gtc".to_string(), order(None)).await;
+        tracker.insert("filled".to_string(), order(Some(50))).await;
+        tracker.update_status("filled", OrderStatus::Filled).await;
+
+        assert!(tracker.expired(start).await.is_empty());
+
+        let expired = tracker.expired(start + Duration::from_millis(100)).await;
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].order_id, "short");
+        // Reported once, then left to the caller to cancel
+        assert!(tracker.expired(start + Duration::from_millis(200)).await.is_empty());
+
+        // More than one turn of the wheel ahead
+        let expired = tracker.expired(start + Duration::from_secs(61)).await;
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].order_id, "long");
+    }
+}
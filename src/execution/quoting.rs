@@ -0,0 +1,524 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::error::{GatewayError, HftError};
+use crate::execution::OrderTracker;
+use crate::gateways::order::AmendRequest;
+use crate::metrics::QUOTE_UPDATES_SKIPPED;
+use crate::sink::{EventSink, OrderEvent};
+use crate::types::{Fill, Order, OrderSide, OrderType, Symbol, VenueId};
+
+const DEFAULT_MAX_UPDATES_PER_SEC: usize = 10;
+const DEFAULT_PRICE_TOLERANCE: f64 = 0.0;
+const DEFAULT_SIZE_TOLERANCE: f64 = 0.0;
+
+/// Window the update rate is measured over
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// How long a new quote may go without an ack or rejection before it is
+/// taken as lost and the next update is sent as a new order
+const ACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub struct QuoteThrottleConfig {
+    /// Quote updates sent per symbol per second
+    pub max_updates_per_sec: usize,
+    /// Price changes up to this amount are treated as no-ops
+    pub price_tolerance: f64,
+    /// Size changes up to this amount are treated as no-ops
+    pub size_tolerance: f64,
+}
+
+impl Default for QuoteThrottleConfig {
+    fn default() -> Self {
+        Self {
+            max_updates_per_sec: DEFAULT_MAX_UPDATES_PER_SEC,
+            price_tolerance: DEFAULT_PRICE_TOLERANCE,
+            size_tolerance: DEFAULT_SIZE_TOLERANCE,
+        }
+    }
+}
+
+impl QuoteThrottleConfig {
+    /// Read `HFT_QUOTE_MAX_UPDATES_PER_SEC`, `HFT_QUOTE_PRICE_TOLERANCE` and
+    /// `HFT_QUOTE_SIZE_TOLERANCE`; returns `None` when no rate is configured
+    pub fn from_env() -> Option<Self> {
+        let max_updates_per_sec = std::env::var("HFT_QUOTE_MAX_UPDATES_PER_SEC").ok()?.parse().ok()?;
+        let tolerance = |key: &str, default: f64| {
+            std::env::var(key).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+        };
+        Some(Self {
+            max_updates_per_sec,
+            price_tolerance: tolerance("HFT_QUOTE_PRICE_TOLERANCE", DEFAULT_PRICE_TOLERANCE),
+            size_tolerance: tolerance("HFT_QUOTE_SIZE_TOLERANCE", DEFAULT_SIZE_TOLERANCE),
+        })
+    }
+}
+
+type QuoteKey = (Option<String>, VenueId, Symbol, OrderSide);
+
+fn key(order: &Order) -> QuoteKey {
+    (order.strategy.clone(), order.venue, order.symbol, order.side)
+}
+
+/// What a quote update is sent as
+#[derive(Debug)]
+enum Update {
+    /// A new order, as nothing rests for its key, or a market order
+    New(Order),
+    /// An amend of the quote resting for `key` under `order_id`
+    Amend { key: QuoteKey, order_id: String, order: Order },
+}
+
+#[derive(Default)]
+struct QuoteState {
+    /// Last quote sent per key
+    sent: HashMap<QuoteKey, Order>,
+    /// Order ID of each key's resting quote
+    resting: HashMap<QuoteKey, String>,
+    /// Keys whose new quote is neither acked nor rejected yet, with when
+    /// it was sent
+    unacked: HashMap<QuoteKey, Instant>,
+    /// Keys with an amend in flight
+    amending: HashSet<QuoteKey>,
+}
+
+impl QuoteState {
+    fn is_noop(&self, order: &Order, config: &QuoteThrottleConfig) -> bool {
+        self.sent.get(&key(order)).is_some_and(|last| {
+            (last.price - order.price).abs() <= config.price_tolerance
+                && (last.quantity - order.quantity).abs() <= config.size_tolerance
+        })
+    }
+
+    /// Whether the key's last update has been answered, so the next may
+    /// follow it
+    fn is_settled(&self, key: &QuoteKey) -> bool {
+        !self.unacked.contains_key(key) && !self.amending.contains(key)
+    }
+
+    /// Forget new quotes unanswered for too long, e.g. dropped on a standby
+    fn expire_unacked(&mut self, now: Instant) {
+        let lost: Vec<QuoteKey> = self.unacked.iter()
+            .filter(|(_, sent_at)| now.duration_since(**sent_at) >= ACK_TIMEOUT)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in lost {
+            warn!(venue = %key.1, symbol = %key.2, side = ?key.3, "Quote never acked, sending the next update as a new order");
+            self.forget(&key);
+        }
+    }
+
+    /// Record `order` as sent for `key`, as an amend of its resting quote
+    /// if there is one
+    fn send(&mut self, key: QuoteKey, order: Order, now: Instant) -> Update {
+        self.sent.insert(key.clone(), order.clone());
+        match self.resting.get(&key) {
+            Some(order_id) => {
+                self.amending.insert(key.clone());
+                Update::Amend { order_id: order_id.clone(), key, order }
+            }
+            None => {
+                self.unacked.insert(key, now);
+                Update::New(order)
+            }
+        }
+    }
+
+    /// Drop the key's quote, so its next update goes out as a new order
+    fn forget(&mut self, key: &QuoteKey) {
+        self.sent.remove(key);
+        self.resting.remove(key);
+        self.unacked.remove(key);
+    }
+
+    fn key_of(&self, order_id: &str) -> Option<QuoteKey> {
+        self.resting.iter().find(|(_, id)| *id == order_id).map(|(key, _)| key.clone())
+    }
+}
+
+/// The quotes a [`QuoteThrottle`] has resting on the venues. The order
+/// gateway reports acks, rejections, amends and cancels and the fill router
+/// reports fills back through [`EventSink`].
+pub(crate) struct RestingQuotes {
+    /// Fill progress of the quotes, recorded by the fill router
+    orders: Arc<OrderTracker>,
+    state: Mutex<QuoteState>,
+}
+
+impl RestingQuotes {
+    fn on_order_event(&self, event: &OrderEvent) {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            OrderEvent::Submitted { order_id, order, .. } => {
+                // Acks of new quotes and of cancel-replace amends alike
+                let key = key(order);
+                if state.sent.get(&key).is_some_and(|sent| sent.is_reported_as(order)) {
+                    state.unacked.remove(&key);
+                    state.resting.insert(key, order_id.clone());
+                }
+            }
+            OrderEvent::Rejected { order, .. } => {
+                let key = key(order);
+                if state.unacked.contains_key(&key) && state.sent.get(&key).is_some_and(|sent| sent.is_reported_as(order)) {
+                    state.forget(&key);
+                }
+            }
+            OrderEvent::StatusChanged { order_id, status, .. } if status.is_terminal() => {
+                let Some(key) = state.key_of(order_id) else {
+                    return;
+                };
+                // A cancel-replace cancels the quote before its replacement
+                // is acked
+                if state.amending.contains(&key) {
+                    state.resting.remove(&key);
+                } else {
+                    state.forget(&key);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn on_fill(&self, fill: &Fill) {
+        // Recorded by the fill router first, so a filled quote is no
+        // longer tracked
+        if self.orders.get(&fill.order_id).await.is_some() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if let Some(key) = state.key_of(&fill.order_id) {
+            state.forget(&key);
+        }
+    }
+
+    /// Follow the outcome of amending the quote resting for `key` under
+    /// `order_id`
+    async fn amended(&self, key: QuoteKey, order_id: &str, result: Result<String, HftError>) {
+        let still_open = self.orders.get(order_id).await.is_some();
+        let mut state = self.state.lock().unwrap();
+        state.amending.remove(&key);
+        match result {
+            Ok(new_order_id) => {
+                state.resting.insert(key, new_order_id);
+            }
+            Err(e) => {
+                debug!(order_id = %order_id, error = %e, "Quote amend failed");
+                if still_open {
+                    // The quote rests unchanged; the next update amends it
+                    state.sent.remove(&key);
+                } else {
+                    state.forget(&key);
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for RestingQuotes {
+    fn name(&self) -> &str {
+        "quote_throttle"
+    }
+
+    async fn publish(&self, events: &[OrderEvent]) -> Result<(), HftError> {
+        for event in events {
+            match event {
+                OrderEvent::Fill(fill) => self.on_fill(fill).await,
+                event => self.on_order_event(event),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Coalesces quote updates from market-making strategies before they reach
+/// the venues.
+///
+/// Limit orders are treated as the desired quote for their strategy, venue,
+/// symbol and side. The first is sent as a new order and later ones amend
+/// it while it rests, through the order gateway, which falls back to
+/// cancel-replace on venues without amends. Updates within tolerance of the
+/// last quote sent are dropped, and once a symbol has used its updates for
+/// the current second only the latest desired quote per side is kept and
+/// sent when the budget frees up; so is an update arriving before the last
+/// one was answered. A quote that fills, is cancelled or is rejected is
+/// forgotten, so the next update places a new one. Market orders pass
+/// straight through.
+pub struct QuoteThrottle {
+    config: QuoteThrottleConfig,
+    order_rx: mpsc::Receiver<Order>,
+    order_tx: mpsc::Sender<Order>,
+    /// The order gateway's amend queue
+    amend_tx: mpsc::Sender<AmendRequest>,
+    quotes: Arc<RestingQuotes>,
+    /// Latest quote waiting for rate budget or for its last update to be
+    /// answered
+    pending: HashMap<QuoteKey, Order>,
+    /// Send times per symbol within the rate window
+    updates: HashMap<String, VecDeque<Instant>>,
+}
+
+impl QuoteThrottle {
+    pub fn new(
+        config: QuoteThrottleConfig,
+        order_rx: mpsc::Receiver<Order>,
+        order_tx: mpsc::Sender<Order>,
+        amend_tx: mpsc::Sender<AmendRequest>,
+        orders: Arc<OrderTracker>,
+    ) -> Self {
+        Self {
+            config,
+            order_rx,
+            order_tx,
+            amend_tx,
+            quotes: Arc::new(RestingQuotes { orders, state: Mutex::new(QuoteState::default()) }),
+            pending: HashMap::new(),
+            updates: HashMap::new(),
+        }
+    }
+
+    /// The sink the order gateway and fill router report the quotes to
+    pub(crate) fn resting_quotes(&self) -> Arc<RestingQuotes> {
+        Arc::clone(&self.quotes)
+    }
+
+    /// Take one update from the symbol's budget if any is left
+    fn try_spend(&mut self, symbol: &str, now: Instant) -> bool {
+        let sent = self.updates.entry(symbol.to_string()).or_default();
+        while sent.front().is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW) {
+            sent.pop_front();
+        }
+        if sent.len() >= self.config.max_updates_per_sec {
+            return false;
+        }
+        sent.push_back(now);
+        true
+    }
+
+    /// Offer an order, returning what to send now if anything
+    fn offer(&mut self, order: Order, now: Instant) -> Option<Update> {
+        if order.order_type == OrderType::Market {
+            return Some(Update::New(order));
+        }
+
+        let quotes = Arc::clone(&self.quotes);
+        let mut state = quotes.state.lock().unwrap();
+        state.expire_unacked(now);
+        let key = key(&order);
+        if state.is_noop(&order, &self.config) {
+            self.pending.remove(&key);
+            QUOTE_UPDATES_SKIPPED.with_label_values(&["noop"]).inc();
+            return None;
+        }
+
+        if state.is_settled(&key) && self.try_spend(&order.symbol, now) {
+            self.pending.remove(&key);
+            return Some(state.send(key, order, now));
+        }
+
+        if self.pending.insert(key, order).is_some() {
+            QUOTE_UPDATES_SKIPPED.with_label_values(&["coalesced"]).inc();
+        }
+        None
+    }
+
+    /// Pending quotes that can now be sent
+    fn flush(&mut self, now: Instant) -> Vec<Update> {
+        let quotes = Arc::clone(&self.quotes);
+        let mut state = quotes.state.lock().unwrap();
+        state.expire_unacked(now);
+        let keys: Vec<QuoteKey> = self.pending.keys().cloned().collect();
+        let mut ready = Vec::new();
+        for key in keys {
+            if state.is_settled(&key) && self.try_spend(&key.2, now) {
+                let order = self.pending.remove(&key).expect("pending key");
+                ready.push(state.send(key, order, now));
+            }
+        }
+        ready
+    }
+
+    fn forward(&self, update: Update) {
+        match update {
+            Update::New(order) => {
+                if let Err(e) = self.order_tx.try_send(order) {
+                    warn!(error = %e, "Failed to queue throttled order");
+                    let order = e.into_inner();
+                    if order.order_type == OrderType::Limit {
+                        self.quotes.state.lock().unwrap().forget(&key(&order));
+                    }
+                }
+            }
+            Update::Amend { key, order_id, order } => {
+                let (request, reply) = AmendRequest::new(order_id.clone(), order.price, order.quantity).with_reply();
+                if let Err(e) = self.amend_tx.try_send(request) {
+                    warn!(error = %e, "Failed to queue quote amend");
+                    let mut state = self.quotes.state.lock().unwrap();
+                    state.amending.remove(&key);
+                    state.sent.remove(&key);
+                    return;
+                }
+                let quotes = Arc::clone(&self.quotes);
+                tokio::spawn(async move {
+                    let result = reply.await
+                        .unwrap_or_else(|_| Err(GatewayError::ChannelSendFailed("order gateway stopped".to_string()).into()));
+                    quotes.amended(key, &order_id, result).await;
+                });
+            }
+        }
+    }
+
+    pub async fn run(mut self) {
+        let period = RATE_WINDOW / self.config.max_updates_per_sec.max(1) as u32;
+        let mut interval = tokio::time::interval(period);
+        loop {
+            tokio::select! {
+                order = self.order_rx.recv() => {
+                    let Some(order) = order else { break };
+                    if let Some(update) = self.offer(order, Instant::now()) {
+                        self.forward(update);
+                    }
+                }
+                _ = interval.tick() => {
+                    for update in self.flush(Instant::now()) {
+                        self.forward(update);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::OrderStatus;
+
+    fn quote(side: OrderSide, price: f64, quantity: f64) -> Order {
+        Order {
+            symbol: "BTCUSDT".into(),
+            side,
+            quantity,
+            price,
+            venue: "MOCK".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: Some("mm".to_string()),
+            bypass_kill_switch: false,
+        }
+    }
+
+    fn throttle(config: QuoteThrottleConfig) -> QuoteThrottle {
+        let (_, order_rx) = mpsc::channel(1);
+        let (order_tx, _) = mpsc::channel(1);
+        let (amend_tx, _) = mpsc::channel(1);
+        QuoteThrottle::new(config, order_rx, order_tx, amend_tx, Arc::new(OrderTracker::new()))
+    }
+
+    fn sent(update: Option<Update>) -> Order {
+        match update {
+            Some(Update::New(order)) => order,
+            update => panic!("expected a new order, got {:?}", update),
+        }
+    }
+
+    fn amended(update: Option<Update>) -> (String, Order) {
+        match update {
+            Some(Update::Amend { order_id, order, .. }) => (order_id, order),
+            update => panic!("expected an amend, got {:?}", update),
+        }
+    }
+
+    fn ack(throttle: &QuoteThrottle, order_id: &str, order: Order) {
+        throttle.quotes.on_order_event(&OrderEvent::Submitted { order_id: order_id.to_string(), order, timestamp: 0 });
+    }
+
+    #[test]
+    fn test_coalesces_and_skips_noops() {
+        let mut throttle = throttle(QuoteThrottleConfig { max_updates_per_sec: 2, price_tolerance: 0.5, size_tolerance: 0.0 });
+        let start = Instant::now();
+
+        let bid = sent(throttle.offer(quote(OrderSide::Buy, 100.0, 1.0), start));
+        ack(&throttle, "bid", bid);
+        // Within price tolerance of the last quote sent
+        assert!(throttle.offer(quote(OrderSide::Buy, 100.4, 1.0), start).is_none());
+        sent(throttle.offer(quote(OrderSide::Sell, 101.0, 1.0), start));
+
+        // The symbol's budget is spent, so only the latest bid is kept
+        assert!(throttle.offer(quote(OrderSide::Buy, 99.0, 1.0), start).is_none());
+        assert!(throttle.offer(quote(OrderSide::Buy, 98.0, 1.0), start).is_none());
+        let market = Order { order_type: OrderType::Market, ..quote(OrderSide::Sell, 0.0, 1.0) };
+        sent(throttle.offer(market, start));
+        assert!(throttle.flush(start + Duration::from_millis(500)).is_empty());
+
+        // It amends the resting bid
+        let mut flushed = throttle.flush(start + RATE_WINDOW);
+        assert_eq!(flushed.len(), 1);
+        let (order_id, order) = amended(flushed.pop());
+        assert_eq!((order_id.as_str(), order.price), ("bid", 98.0));
+    }
+
+    #[tokio::test]
+    async fn test_updates_amend_the_resting_quote_until_it_ends() {
+        let mut throttle = throttle(QuoteThrottleConfig { max_updates_per_sec: 100, price_tolerance: 0.0, size_tolerance: 0.0 });
+        let now = Instant::now();
+
+        // Unanswered quotes hold back the next update
+        let bid = sent(throttle.offer(quote(OrderSide::Buy, 100.0, 1.0), now));
+        assert!(throttle.offer(quote(OrderSide::Buy, 99.0, 1.0), now).is_none());
+        ack(&throttle, "1", bid);
+        let (order_id, order) = amended(throttle.flush(now).pop());
+        assert_eq!((order_id.as_str(), order.price), ("1", 99.0));
+        assert!(throttle.offer(quote(OrderSide::Buy, 98.0, 1.0), now).is_none());
+
+        // Replaced under a new ID, which the next amend goes to
+        let key = key(&order);
+        throttle.quotes.on_order_event(&OrderEvent::StatusChanged { order_id: "1".to_string(), status: OrderStatus::Cancelled, timestamp: 0 });
+        throttle.quotes.amended(key.clone(), "1", Ok("2".to_string())).await;
+        let (order_id, order) = amended(throttle.flush(now).pop());
+        assert_eq!((order_id.as_str(), order.price), ("2", 98.0));
+        throttle.quotes.amended(key, "2", Ok("2".to_string())).await;
+
+        // Once cancelled, the same quote goes out again as a new order
+        assert!(throttle.offer(quote(OrderSide::Buy, 98.0, 1.0), now).is_none());
+        throttle.quotes.on_order_event(&OrderEvent::StatusChanged { order_id: "2".to_string(), status: OrderStatus::Cancelled, timestamp: 0 });
+        let bid = sent(throttle.offer(quote(OrderSide::Buy, 98.0, 1.0), now));
+
+        // As after a rejection, or a fill
+        throttle.quotes.on_order_event(&OrderEvent::Rejected { order: bid, reason: "post only".to_string(), timestamp: 0 });
+        let bid = sent(throttle.offer(quote(OrderSide::Buy, 98.0, 1.0), now));
+        ack(&throttle, "3", bid.clone());
+        throttle.quotes.on_fill(&Fill {
+            order_id: "3".to_string(),
+            symbol: bid.symbol,
+            venue: bid.venue,
+            strategy: "mm".to_string(),
+            side: bid.side,
+            quantity: 1.0,
+            price: 98.0,
+            timestamp: 0,
+            commission: None,
+        }).await;
+        sent(throttle.offer(quote(OrderSide::Buy, 98.0, 1.0), now));
+
+        // A quote never answered is given up on
+        let later = now + ACK_TIMEOUT;
+        sent(throttle.offer(quote(OrderSide::Buy, 98.0, 1.0), later));
+    }
+
+    #[test]
+    fn test_strategies_quote_independently() {
+        let mut throttle = throttle(QuoteThrottleConfig { max_updates_per_sec: 10, price_tolerance: 0.0, size_tolerance: 0.0 });
+        let now = Instant::now();
+
+        let bid = sent(throttle.offer(quote(OrderSide::Buy, 100.0, 1.0), now));
+        ack(&throttle, "1", bid);
+        let other = Order { strategy: Some("arb".to_string()), ..quote(OrderSide::Buy, 100.0, 1.0) };
+        assert_eq!(sent(throttle.offer(other, now)).strategy.as_deref(), Some("arb"));
+    }
+}
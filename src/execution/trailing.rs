@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, info, warn};
+
+use crate::audit::{AuditEvent, AuditLog};
+use crate::feed::{wire, FeedMessage, FeedPublisher};
+use crate::metrics::TRAILING_STOPS_TRIGGERED;
+use crate::types::{Order, OrderSide, OrderType, Quote};
+
+/// How far a trailing stop sits behind the best price seen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrailDistance {
+    /// Percentage of the watermark
+    Percent(f64),
+    /// Absolute price distance
+    Price(f64),
+}
+
+impl TrailDistance {
+    /// `PCT%` or an absolute price distance
+    fn parse(spec: &str) -> Option<Self> {
+        let distance = match spec.strip_suffix('%') {
+            Some(pct) => TrailDistance::Percent(pct.trim().parse().ok().filter(|p: &f64| *p > 0.0 && *p < 100.0)?),
+            None => TrailDistance::Price(spec.parse().ok().filter(|p: &f64| *p > 0.0)?),
+        };
+        Some(distance)
+    }
+
+    /// Stop price for a position on `side` whose best price so far is
+    /// `watermark`
+    fn stop(&self, watermark: f64, side: OrderSide) -> f64 {
+        let offset = match self {
+            TrailDistance::Percent(pct) => watermark * pct / 100.0,
+            TrailDistance::Price(price) => *price,
+        };
+        match side {
+            OrderSide::Buy => watermark - offset,
+            OrderSide::Sell => watermark + offset,
+        }
+    }
+}
+
+/// Trail distances by strategy
+#[derive(Debug, Clone, Default)]
+pub struct TrailingStopConfig {
+    strategies: HashMap<String, TrailDistance>,
+    /// Distance for strategies without their own
+    default: Option<TrailDistance>,
+}
+
+impl TrailingStopConfig {
+    pub fn with_strategy(mut self, strategy: &str, distance: TrailDistance) -> Self {
+        self.strategies.insert(strategy.to_string(), distance);
+        self
+    }
+
+    pub fn with_default(mut self, distance: TrailDistance) -> Self {
+        self.default = Some(distance);
+        self
+    }
+
+    /// Comma separated `STRATEGY=DISTANCE` entries, `*` naming the default,
+    /// e.g. `breakout=1.5%,mm=20,*=2%`
+    fn parse(spec: &str) -> Self {
+        let mut config = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=')
+                .and_then(|(strategy, distance)| Some((strategy.trim(), TrailDistance::parse(distance.trim())?)));
+            match parsed {
+                Some(("*", distance)) => config.default = Some(distance),
+                Some((strategy, distance)) if !strategy.is_empty() => config = config.with_strategy(strategy, distance),
+                _ => warn!(entry = entry, "Ignoring malformed trailing stop entry"),
+            }
+        }
+        config
+    }
+
+    /// Read `HFT_TRAILING_STOPS` (see [`parse`](Self::parse)); returns
+    /// `None` when unset
+    pub fn from_env() -> Option<Self> {
+        Some(Self::parse(&std::env::var("HFT_TRAILING_STOPS").ok()?))
+    }
+
+    pub fn distance_for(&self, strategy: &str) -> Option<TrailDistance> {
+        self.strategies.get(strategy).copied().or(self.default)
+    }
+}
+
+/// A position to protect with a trailing stop
+#[derive(Debug, Clone)]
+pub struct ProtectedPosition {
+    pub venue: String,
+    pub symbol: String,
+    pub strategy: String,
+    /// `Buy` for a long position, `Sell` for a short one
+    pub side: OrderSide,
+    pub quantity: f64,
+    pub entry_price: f64,
+}
+
+struct Trailed {
+    position: ProtectedPosition,
+    distance: TrailDistance,
+    /// Best price seen: the highest bid of a long, the lowest ask of a short
+    watermark: f64,
+    stop: f64,
+}
+
+/// Trails protective stops behind designated positions on the quote stream
+/// and closes a position with a market order once the market comes back
+/// to its stop
+#[derive(Clone)]
+pub struct TrailingStops {
+    config: Arc<TrailingStopConfig>,
+    positions: Arc<Mutex<HashMap<u64, Trailed>>>,
+    next_id: Arc<AtomicU64>,
+    order_tx: mpsc::Sender<Order>,
+    audit: Option<AuditLog>,
+}
+
+impl TrailingStops {
+    pub fn new(config: TrailingStopConfig, order_tx: mpsc::Sender<Order>) -> Self {
+        Self {
+            config: Arc::new(config),
+            positions: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            order_tx,
+            audit: None,
+        }
+    }
+
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Start trailing a stop behind `position` at its strategy's distance.
+    /// Returns the stop's id, or `None` when the strategy has no trail
+    /// configured.
+    pub fn designate(&self, position: ProtectedPosition) -> Option<u64> {
+        let distance = self.config.distance_for(&position.strategy)?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let watermark = position.entry_price;
+        let stop = distance.stop(watermark, position.side);
+        info!(id = id, symbol = %position.symbol, strategy = %position.strategy, stop = stop, "Trailing stop set");
+        self.positions.lock().unwrap().insert(id, Trailed { position, distance, watermark, stop });
+        Some(id)
+    }
+
+    /// Stop trailing, e.g. once the position was closed some other way
+    pub fn release(&self, id: u64) -> bool {
+        self.positions.lock().unwrap().remove(&id).is_some()
+    }
+
+    /// Current stop price of a trailed position
+    pub fn stop_price(&self, id: u64) -> Option<f64> {
+        self.positions.lock().unwrap().get(&id).map(|t| t.stop)
+    }
+
+    /// Move stops up behind new highs (down behind new lows for shorts)
+    /// and close positions whose stop the quote reached. Returns how many
+    /// stops triggered.
+    pub fn on_quote(&self, quote: &Quote) -> usize {
+        let triggered: Vec<Trailed> = {
+            let mut positions = self.positions.lock().unwrap();
+            let mut hit = Vec::new();
+            for (&id, trailed) in positions.iter_mut() {
+                let position = &trailed.position;
+                if position.venue != quote.venue || position.symbol != quote.symbol {
+                    continue;
+                }
+                let (mark, better) = match position.side {
+                    OrderSide::Buy => (quote.bid, quote.bid > trailed.watermark),
+                    OrderSide::Sell => (quote.ask, quote.ask < trailed.watermark),
+                };
+                if mark <= 0.0 {
+                    continue;
+                }
+                if better {
+                    trailed.watermark = mark;
+                    trailed.stop = trailed.distance.stop(mark, position.side);
+                    debug!(id = id, symbol = %position.symbol, stop = trailed.stop, "Trailing stop moved");
+                }
+                let reached = match position.side {
+                    OrderSide::Buy => mark <= trailed.stop,
+                    OrderSide::Sell => mark >= trailed.stop,
+                };
+                if reached {
+                    hit.push(id);
+                }
+            }
+            hit.iter().filter_map(|id| positions.remove(id)).collect()
+        };
+
+        for trailed in &triggered {
+            let position = &trailed.position;
+            info!(symbol = %position.symbol, strategy = %position.strategy, stop = trailed.stop, watermark = trailed.watermark, "Trailing stop triggered");
+            TRAILING_STOPS_TRIGGERED.with_label_values(&[&position.strategy]).inc();
+            let order = Order {
+                symbol: position.symbol.as_str().into(),
+                side: position.side.opposite(),
+                quantity: position.quantity,
+                price: 0.0,
+                venue: position.venue.as_str().into(),
+                order_type: OrderType::Market,
+                expire_after: None,
+                strategy: Some(position.strategy.clone()),
+                bypass_kill_switch: false,
+            };
+            if let Some(audit) = &self.audit {
+                audit.record(AuditEvent::OrderRequest { strategy: order.strategy.clone(), order: order.clone() });
+            }
+            if let Err(e) = self.order_tx.try_send(order) {
+                warn!(symbol = %position.symbol, error = %e, "Dropping trailing stop order, position left open");
+            }
+        }
+        triggered.len()
+    }
+
+    /// Follow quotes published on the market data feed
+    pub async fn run(self, feed: FeedPublisher) {
+        let mut frames = feed.subscribe();
+        loop {
+            match frames.recv().await {
+                Ok(frame) => {
+                    if let Ok(Some((FeedMessage::Quote(quote), _))) = wire::decode(&frame) {
+                        self.on_quote(&quote);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(skipped = skipped, "Trailing stops lagging the feed, quotes dropped");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(bid: f64, ask: f64) -> Quote {
+        Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "MOCK".into(),
+            timestamp: 1,
+        }
+    }
+
+    fn position(strategy: &str, side: OrderSide) -> ProtectedPosition {
+        ProtectedPosition {
+            venue: "MOCK".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            strategy: strategy.to_string(),
+            side,
+            quantity: 2.0,
+            entry_price: 100.0,
+        }
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = TrailingStopConfig::parse("breakout=1.5%, mm=20, *=2%, bad=0%, nodistance");
+        assert_eq!(config.distance_for("breakout"), Some(TrailDistance::Percent(1.5)));
+        assert_eq!(config.distance_for("mm"), Some(TrailDistance::Price(20.0)));
+        assert_eq!(config.distance_for("bad"), Some(TrailDistance::Percent(2.0)));
+        assert!(TrailingStopConfig::default().distance_for("mm").is_none());
+    }
+
+    #[test]
+    fn test_stop_trails_highs_then_triggers() {
+        let (order_tx, mut order_rx) = mpsc::channel(8);
+        let config = TrailingStopConfig::default()
+            .with_strategy("breakout", TrailDistance::Percent(2.0))
+            .with_strategy("fade", TrailDistance::Price(3.0));
+        let stops = TrailingStops::new(config, order_tx);
+        let long = stops.designate(position("breakout", OrderSide::Buy)).unwrap();
+        assert!(stops.designate(position("untrailed", OrderSide::Buy)).is_none());
+        assert_eq!(stops.stop_price(long), Some(98.0));
+
+        assert_eq!(stops.on_quote(&quote(105.0, 105.5)), 0);
+        assert!((stops.stop_price(long).unwrap() - 102.9).abs() < 1e-9);
+        // A pullback above the stop leaves it where it is
+        assert_eq!(stops.on_quote(&quote(103.0, 103.5)), 0);
+        assert!((stops.stop_price(long).unwrap() - 102.9).abs() < 1e-9);
+
+        assert_eq!(stops.on_quote(&quote(102.5, 103.0)), 1);
+        let order = order_rx.try_recv().unwrap();
+        assert_eq!((order.side, order.order_type, order.quantity), (OrderSide::Sell, OrderType::Market, 2.0));
+        assert!(stops.stop_price(long).is_none());
+
+        // The short's stop sits 3 above the lowest ask
+        let short = stops.designate(position("fade", OrderSide::Sell)).unwrap();
+        assert_eq!(stops.on_quote(&quote(95.0, 95.5)), 0);
+        assert_eq!(stops.stop_price(short), Some(98.5));
+        assert_eq!(stops.on_quote(&quote(98.0, 98.5)), 1);
+        assert_eq!(order_rx.try_recv().unwrap().side, OrderSide::Buy);
+    }
+}
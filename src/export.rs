@@ -0,0 +1,339 @@
+//! Parquet export of recorded market data and fills for research.
+//!
+//! Each table is written under `<out>/<table>/date=YYYY-MM-DD/symbol=SYMBOL/`
+//! as one file per source recording, named after it, so Polars, pandas and
+//! pyarrow read the tree as a hive-partitioned dataset and re-exporting a
+//! recording replaces its files. Dates are UTC.
+//!
+//! | Table | Source | Rows |
+//! |-------|--------|------|
+//! | `quotes` | Frame recordings, depth recordings | Best bid and offer updates |
+//! | `depth` | Depth recordings | Price level changes, with snapshots flagged |
+//! | `fills` | Audit logs | Fills |
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use chrono::{DateTime, NaiveDate};
+use parquet::basic::Compression;
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+
+use crate::audit;
+use crate::book::{read_depth, DepthEvent};
+use crate::error::HftError;
+use crate::types::OrderSide;
+use crate::venues::{binance, frames};
+
+/// A column's values, in row order
+enum Column {
+    /// Milliseconds since the Unix epoch
+    Timestamp(Vec<i64>),
+    Int(Vec<i64>),
+    Float(Vec<f64>),
+    Text(Vec<String>),
+    Flag(Vec<bool>),
+}
+
+impl Column {
+    fn schema(&self, name: &str) -> String {
+        match self {
+            Column::Timestamp(_) => format!("REQUIRED INT64 {} (TIMESTAMP(MILLIS,true));", name),
+            Column::Int(_) => format!("REQUIRED INT64 {};", name),
+            Column::Float(_) => format!("REQUIRED DOUBLE {};", name),
+            Column::Text(_) => format!("REQUIRED BYTE_ARRAY {} (UTF8);", name),
+            Column::Flag(_) => format!("REQUIRED BOOLEAN {};", name),
+        }
+    }
+}
+
+/// Rows of one partition of a table, as columns
+type Columns = Vec<(&'static str, Column)>;
+
+fn write_parquet(path: &Path, columns: &Columns) -> Result<(), HftError> {
+    let failed = |e: parquet::errors::ParquetError| HftError::Serialization(format!("Parquet export to {} failed: {}", path.display(), e));
+    let fields: String = columns.iter().map(|(name, column)| column.schema(name)).collect();
+    let schema = Arc::new(parse_message_type(&format!("message record {{ {} }}", fields)).map_err(failed)?);
+    let properties = Arc::new(WriterProperties::builder().set_compression(Compression::SNAPPY).build());
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut writer = SerializedFileWriter::new(File::create(path)?, schema, properties).map_err(failed)?;
+    let mut row_group = writer.next_row_group().map_err(failed)?;
+    for (_, column) in columns {
+        let Some(mut writer) = row_group.next_column().map_err(failed)? else {
+            break;
+        };
+        match column {
+            Column::Timestamp(values) | Column::Int(values) => writer.typed::<Int64Type>().write_batch(values, None, None),
+            Column::Float(values) => writer.typed::<DoubleType>().write_batch(values, None, None),
+            Column::Text(values) => {
+                let values: Vec<ByteArray> = values.iter().map(|v| ByteArray::from(v.as_str())).collect();
+                writer.typed::<ByteArrayType>().write_batch(&values, None, None)
+            }
+            Column::Flag(values) => writer.typed::<BoolType>().write_batch(values, None, None),
+        }
+        .map_err(failed)?;
+        writer.close().map_err(failed)?;
+    }
+    row_group.close().map_err(failed)?;
+    writer.close().map_err(failed)?;
+    Ok(())
+}
+
+fn date(millis: u64) -> NaiveDate {
+    DateTime::from_timestamp_millis(millis as i64).unwrap_or_default().date_naive()
+}
+
+/// Rows of a table grouped by date and symbol
+struct Partitions<R> {
+    table: &'static str,
+    rows: BTreeMap<(NaiveDate, String), Vec<R>>,
+}
+
+impl<R> Partitions<R> {
+    fn new(table: &'static str) -> Self {
+        Self { table, rows: BTreeMap::new() }
+    }
+
+    fn push(&mut self, at: u64, symbol: &str, row: R) {
+        self.rows.entry((date(at), symbol.to_string())).or_default().push(row);
+    }
+
+    /// Write every partition as a file named after `source`
+    fn write(self, out: &Path, source: &Path, columns: impl Fn(&[R]) -> Columns) -> Result<Vec<PathBuf>, HftError> {
+        let name = format!("{}.parquet", source.file_stem().unwrap_or_default().to_string_lossy());
+        let mut written = Vec::new();
+        for ((date, symbol), rows) in self.rows {
+            // Symbols such as `BTC/USD` would otherwise nest directories
+            let symbol = symbol.replace(['/', '\\'], "-");
+            let path = out
+                .join(self.table)
+                .join(format!("date={}", date))
+                .join(format!("symbol={}", symbol))
+                .join(&name);
+            write_parquet(&path, &columns(&rows))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+struct QuoteRow {
+    received_at: u64,
+    venue: String,
+    bid: f64,
+    ask: f64,
+    bid_size: f64,
+    ask_size: f64,
+    /// Venue timestamp
+    timestamp: u64,
+}
+
+fn quote_columns(rows: &[QuoteRow]) -> Columns {
+    vec![
+        ("received_at", Column::Timestamp(rows.iter().map(|r| r.received_at as i64).collect())),
+        ("venue", Column::Text(rows.iter().map(|r| r.venue.clone()).collect())),
+        ("bid", Column::Float(rows.iter().map(|r| r.bid).collect())),
+        ("ask", Column::Float(rows.iter().map(|r| r.ask).collect())),
+        ("bid_size", Column::Float(rows.iter().map(|r| r.bid_size).collect())),
+        ("ask_size", Column::Float(rows.iter().map(|r| r.ask_size).collect())),
+        ("timestamp", Column::Timestamp(rows.iter().map(|r| r.timestamp as i64).collect())),
+    ]
+}
+
+struct LevelRow {
+    received_at: u64,
+    venue: String,
+    side: &'static str,
+    price: f64,
+    /// 0 when the level was removed
+    size: f64,
+    /// Part of a full snapshot rather than an update
+    snapshot: bool,
+}
+
+fn level_columns(rows: &[LevelRow]) -> Columns {
+    vec![
+        ("received_at", Column::Timestamp(rows.iter().map(|r| r.received_at as i64).collect())),
+        ("venue", Column::Text(rows.iter().map(|r| r.venue.clone()).collect())),
+        ("side", Column::Text(rows.iter().map(|r| r.side.to_string()).collect())),
+        ("price", Column::Float(rows.iter().map(|r| r.price).collect())),
+        ("size", Column::Float(rows.iter().map(|r| r.size).collect())),
+        ("snapshot", Column::Flag(rows.iter().map(|r| r.snapshot).collect())),
+    ]
+}
+
+/// Export the `bookTicker` quotes `venue` sent in a frame recording to the
+/// `quotes` table; returns the files written
+pub fn export_frames(recording: &Path, venue: &str, out: &Path) -> Result<Vec<PathBuf>, HftError> {
+    let mut quotes = Partitions::new("quotes");
+    for frame in frames::read_frames(recording)?.iter().filter(|f| f.venue == venue) {
+        if let Ok(quote) = binance::parse_book_ticker(&frame.text) {
+            quotes.push(frame.received_at, &quote.symbol, QuoteRow {
+                received_at: frame.received_at,
+                venue: quote.venue.to_string(),
+                bid: quote.bid,
+                ask: quote.ask,
+                bid_size: quote.bid_size,
+                ask_size: quote.ask_size,
+                timestamp: quote.timestamp,
+            });
+        }
+    }
+    quotes.write(out, recording, quote_columns)
+}
+
+/// Export a depth recording's quotes to the `quotes` table and its level
+/// changes to the `depth` table; returns the files written
+pub fn export_depth(recording: &Path, out: &Path) -> Result<Vec<PathBuf>, HftError> {
+    let mut quotes = Partitions::new("quotes");
+    let mut levels = Partitions::new("depth");
+    for record in read_depth(recording)? {
+        let at = record.received_at;
+        match record.event {
+            DepthEvent::Quote(quote) => quotes.push(at, &quote.symbol, QuoteRow {
+                received_at: at,
+                venue: quote.venue.to_string(),
+                bid: quote.bid,
+                ask: quote.ask,
+                bid_size: quote.bid_size,
+                ask_size: quote.ask_size,
+                timestamp: quote.timestamp,
+            }),
+            DepthEvent::Delta(delta) => {
+                let sides = [("bid", &delta.bids), ("ask", &delta.asks)];
+                for (side, changes) in sides {
+                    for &(price, size) in changes {
+                        levels.push(at, &delta.symbol, LevelRow {
+                            received_at: at,
+                            venue: delta.venue.to_string(),
+                            side,
+                            price,
+                            size,
+                            snapshot: delta.snapshot,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    let mut written = quotes.write(out, recording, quote_columns)?;
+    written.extend(levels.write(out, recording, level_columns)?);
+    Ok(written)
+}
+
+struct FillRow {
+    seq: u64,
+    timestamp: u64,
+    order_id: String,
+    strategy: String,
+    venue: String,
+    side: OrderSide,
+    quantity: f64,
+    price: f64,
+}
+
+fn fill_columns(rows: &[FillRow]) -> Columns {
+    vec![
+        ("seq", Column::Int(rows.iter().map(|r| r.seq as i64).collect())),
+        ("timestamp", Column::Timestamp(rows.iter().map(|r| r.timestamp as i64).collect())),
+        ("order_id", Column::Text(rows.iter().map(|r| r.order_id.clone()).collect())),
+        ("strategy", Column::Text(rows.iter().map(|r| r.strategy.clone()).collect())),
+        ("venue", Column::Text(rows.iter().map(|r| r.venue.clone()).collect())),
+        ("side", Column::Text(rows.iter().map(|r| format!("{:?}", r.side).to_lowercase()).collect())),
+        ("quantity", Column::Float(rows.iter().map(|r| r.quantity).collect())),
+        ("price", Column::Float(rows.iter().map(|r| r.price).collect())),
+    ]
+}
+
+/// Export the fills in an audit log to the `fills` table, partitioned by
+/// the fills' own timestamps; returns the files written
+pub fn export_fills(audit_log: &Path, out: &Path) -> Result<Vec<PathBuf>, HftError> {
+    let mut fills = Partitions::new("fills");
+    for (seq, fill) in audit::read_fills(audit_log)? {
+        fills.push(fill.timestamp, &fill.symbol, FillRow {
+            seq,
+            timestamp: fill.timestamp,
+            order_id: fill.order_id,
+            strategy: fill.strategy,
+            venue: fill.venue.to_string(),
+            side: fill.side,
+            quantity: fill.quantity,
+            price: fill.price,
+        });
+    }
+    fills.write(out, audit_log, fill_columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+    use crate::book::{BookDelta, RecordedDepth};
+    use crate::types::Quote;
+
+    #[test]
+    fn test_export_depth_partitions_by_date_and_symbol() {
+        let dir = std::env::temp_dir().join(format!("hft_export_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let recording = dir.join("depth-1.jsonl");
+        // 2026-10-14 23:59:59.999 UTC, then the next day
+        let (late, early) = (1_792_022_399_999, 1_792_022_400_000);
+        let records = [
+            RecordedDepth {
+                received_at: late,
+                event: DepthEvent::Delta(BookDelta {
+                    symbol: "BTC/USD".into(),
+                    venue: "KRAKEN".into(),
+                    bids: vec![(100.0, 1.0)],
+                    asks: vec![(101.0, 2.0), (102.0, 0.0)],
+                    snapshot: true,
+                    checksum: None,
+                }),
+            },
+            RecordedDepth {
+                received_at: early,
+                event: DepthEvent::Quote(Quote {
+                    symbol: "BTC/USD".into(),
+                    bid: 100.5,
+                    ask: 101.0,
+                    bid_size: 1.0,
+                    ask_size: 2.0,
+                    venue: "KRAKEN".into(),
+                    timestamp: 7,
+                }),
+            },
+        ];
+        let lines: Vec<String> = records.iter().map(|r| serde_json::to_string(r).unwrap()).collect();
+        std::fs::write(&recording, lines.join("\n")).unwrap();
+
+        let out = dir.join("out");
+        let written = export_depth(&recording, &out).unwrap();
+        assert_eq!(written, vec![
+            out.join("quotes/date=2026-10-15/symbol=BTC-USD/depth-1.parquet"),
+            out.join("depth/date=2026-10-14/symbol=BTC-USD/depth-1.parquet"),
+        ]);
+
+        let reader = SerializedFileReader::new(File::open(&written[1]).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().map(|row| {
+            let row = row.unwrap();
+            (row.get_string(2).unwrap().clone(), row.get_double(3).unwrap(), row.get_double(4).unwrap(), row.get_bool(5).unwrap())
+        }).collect();
+        assert_eq!(rows[0], ("bid".to_string(), 100.0, 1.0, true));
+        assert_eq!(rows[2], ("ask".to_string(), 102.0, 0.0, true));
+
+        let reader = SerializedFileReader::new(File::open(&written[0]).unwrap()).unwrap();
+        let quote = reader.get_row_iter(None).unwrap().next().unwrap().unwrap();
+        assert_eq!(quote.get_timestamp_millis(0).unwrap(), early as i64);
+        assert_eq!(quote.get_double(2).unwrap(), 100.5);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
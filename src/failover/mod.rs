@@ -0,0 +1,218 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use async_trait::async_trait;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+use crate::error::HftError;
+use crate::events::{EngineEvent, EventBus};
+use crate::metrics::ENGINE_LEADER;
+
+#[cfg(feature = "redis")]
+pub mod redis;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Leader,
+    Standby,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Leader => write!(f, "leader"),
+            Role::Standby => write!(f, "standby"),
+        }
+    }
+}
+
+/// Lock shared between engine instances; only its holder may send orders
+#[async_trait]
+pub trait LeaderLock: Send + Sync {
+    /// Take the lock for `holder` for `ttl`, or extend it if `holder`
+    /// already owns it. Returns whether `holder` owns the lock afterwards.
+    async fn acquire(&self, holder: &str, ttl: Duration) -> Result<bool, HftError>;
+
+    /// Give up the lock if `holder` owns it
+    async fn release(&self, holder: &str) -> Result<(), HftError>;
+}
+
+/// In-process lock, for a single host or tests
+#[derive(Default)]
+pub struct LocalLock {
+    owner: Mutex<Option<(String, Instant)>>,
+}
+
+impl LocalLock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl LeaderLock for LocalLock {
+    async fn acquire(&self, holder: &str, ttl: Duration) -> Result<bool, HftError> {
+        let mut owner = self.owner.lock().await;
+        let free = match owner.as_ref() {
+            None => true,
+            Some((current, expires)) => current == holder || *expires <= Instant::now(),
+        };
+        if free {
+            *owner = Some((holder.to_string(), Instant::now() + ttl));
+        }
+        Ok(free)
+    }
+
+    async fn release(&self, holder: &str) -> Result<(), HftError> {
+        let mut owner = self.owner.lock().await;
+        if owner.as_ref().is_some_and(|(current, _)| current == holder) {
+            *owner = None;
+        }
+        Ok(())
+    }
+}
+
+/// This instance's role in a hot/standby pair.
+///
+/// The leader renews the lock every third of its TTL. A standby keeps
+/// consuming market data and mirrored state but the order gateway drops its
+/// orders. If the lock cannot be renewed the node steps down, so at most one
+/// instance trades even when the lock store is unreachable.
+#[derive(Clone)]
+pub struct Leadership {
+    node_id: String,
+    lock: Arc<dyn LeaderLock>,
+    ttl: Duration,
+    leader: Arc<AtomicBool>,
+    /// Set by a manual step-down; the node stays standby until `take_over`
+    held_back: Arc<AtomicBool>,
+    events: Option<EventBus>,
+}
+
+impl Leadership {
+    pub fn new(node_id: impl Into<String>, lock: Arc<dyn LeaderLock>, ttl: Duration) -> Self {
+        Self {
+            node_id: node_id.into(),
+            lock,
+            ttl,
+            leader: Arc::new(AtomicBool::new(false)),
+            held_back: Arc::new(AtomicBool::new(false)),
+            events: None,
+        }
+    }
+
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.leader.load(Ordering::Acquire)
+    }
+
+    pub fn role(&self) -> Role {
+        if self.is_leader() { Role::Leader } else { Role::Standby }
+    }
+
+    fn set_role(&self, role: Role) {
+        let leader = role == Role::Leader;
+        if self.leader.swap(leader, Ordering::AcqRel) == leader {
+            return;
+        }
+
+        info!(node = %self.node_id, role = %role, "Engine role changed");
+        ENGINE_LEADER.set(if leader { 1.0 } else { 0.0 });
+        if let Some(events) = &self.events {
+            events.publish(EngineEvent::RoleChanged {
+                node: self.node_id.clone(),
+                role: role.to_string(),
+            });
+        }
+    }
+
+    /// Acquire or renew the lock once and update the role
+    pub async fn tick(&self) -> Role {
+        if self.held_back.load(Ordering::Acquire) {
+            self.set_role(Role::Standby);
+            return Role::Standby;
+        }
+
+        match self.lock.acquire(&self.node_id, self.ttl).await {
+            Ok(true) => self.set_role(Role::Leader),
+            Ok(false) => self.set_role(Role::Standby),
+            Err(e) => {
+                warn!(node = %self.node_id, error = %e, "Leader lock unavailable, standing by");
+                self.set_role(Role::Standby);
+            }
+        }
+        self.role()
+    }
+
+    /// Manual failover: stop trading and release the lock so the peer can
+    /// take over on its next tick
+    pub async fn step_down(&self) -> Result<(), HftError> {
+        self.held_back.store(true, Ordering::Release);
+        self.set_role(Role::Standby);
+        self.lock.release(&self.node_id).await
+    }
+
+    /// Allow this node to lead again and try to take the lock now.
+    ///
+    /// Returns `false` while the peer still holds the lock; it becomes
+    /// available once the peer steps down or its lease expires.
+    pub async fn take_over(&self) -> bool {
+        self.held_back.store(false, Ordering::Release);
+        self.tick().await == Role::Leader
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(self.ttl / 3);
+        loop {
+            interval.tick().await;
+            self.tick().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_manual_failover() {
+        let lock: Arc<dyn LeaderLock> = Arc::new(LocalLock::new());
+        let primary = Leadership::new("a", Arc::clone(&lock), Duration::from_secs(30));
+        let standby = Leadership::new("b", Arc::clone(&lock), Duration::from_secs(30));
+
+        assert_eq!(primary.tick().await, Role::Leader);
+        assert_eq!(standby.tick().await, Role::Standby);
+        assert!(!standby.take_over().await);
+
+        primary.step_down().await.unwrap();
+        assert!(!primary.is_leader());
+        assert!(standby.take_over().await);
+
+        // A stepped-down node does not grab the lock back on its own
+        assert_eq!(primary.tick().await, Role::Standby);
+        assert_eq!(standby.tick().await, Role::Leader);
+    }
+
+    #[tokio::test]
+    async fn test_expired_lease_is_taken() {
+        let lock = LocalLock::new();
+        assert!(lock.acquire("a", Duration::from_millis(10)).await.unwrap());
+        assert!(!lock.acquire("b", Duration::from_millis(10)).await.unwrap());
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(lock.acquire("b", Duration::from_millis(10)).await.unwrap());
+    }
+}
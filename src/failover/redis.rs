@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::Script;
+use tokio::time::Duration;
+
+use crate::error::HftError;
+use super::LeaderLock;
+
+const ACQUIRE: &str = r#"
+local owner = redis.call('GET', KEYS[1])
+if not owner then
+    redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2])
+    return 1
+end
+if owner == ARGV[1] then
+    redis.call('PEXPIRE', KEYS[1], ARGV[2])
+    return 1
+end
+return 0
+"#;
+
+const RELEASE: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Leader lock held as a Redis key with a millisecond expiry
+pub struct RedisLock {
+    conn: ConnectionManager,
+    key: String,
+}
+
+impl RedisLock {
+    pub async fn connect(url: &str, key: impl Into<String>) -> Result<Self, HftError> {
+        let client = redis::Client::open(url)
+            .map_err(|e| HftError::Config(format!("Invalid Redis URL: {}", e)))?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .map_err(|e| HftError::Sink(format!("Failed to connect to Redis: {}", e)))?;
+
+        Ok(Self { conn, key: key.into() })
+    }
+}
+
+#[async_trait]
+impl LeaderLock for RedisLock {
+    async fn acquire(&self, holder: &str, ttl: Duration) -> Result<bool, HftError> {
+        let acquired: i32 = Script::new(ACQUIRE)
+            .key(&self.key)
+            .arg(holder)
+            .arg(ttl.as_millis() as u64)
+            .invoke_async(&mut self.conn.clone())
+            .await
+            .map_err(|e| HftError::Sink(format!("Leader lock acquire failed: {}", e)))?;
+
+        Ok(acquired == 1)
+    }
+
+    async fn release(&self, holder: &str) -> Result<(), HftError> {
+        let _: i32 = Script::new(RELEASE)
+            .key(&self.key)
+            .arg(holder)
+            .invoke_async(&mut self.conn.clone())
+            .await
+            .map_err(|e| HftError::Sink(format!("Leader lock release failed: {}", e)))?;
+
+        Ok(())
+    }
+}
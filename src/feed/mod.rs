@@ -0,0 +1,158 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, UnixListener};
+use tokio::sync::broadcast;
+use tracing::{info, warn, debug};
+
+use crate::error::HftError;
+
+pub mod stream;
+pub mod wire;
+
+pub use stream::{StreamHub, StreamMessage, Topic};
+pub use wire::{BookUpdate, FeedMessage};
+
+/// Re-broadcasts normalized market data to downstream consumers.
+///
+/// Messages are encoded once on `publish` and fanned out to every connected
+/// client by its own task; the publishing side never waits on a socket.
+#[derive(Clone)]
+pub struct FeedPublisher {
+    tx: broadcast::Sender<Arc<Vec<u8>>>,
+}
+
+impl Default for FeedPublisher {
+    fn default() -> Self {
+        Self::new(8192)
+    }
+}
+
+impl FeedPublisher {
+    /// `capacity` is the number of frames a slow client may fall behind
+    /// before it starts dropping messages
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        Self { tx }
+    }
+
+    pub fn publish(&self, message: &FeedMessage) {
+        if self.tx.receiver_count() == 0 {
+            return;
+        }
+        let _ = self.tx.send(Arc::new(wire::encode(message)));
+    }
+
+    /// Encoded frames in publish order, as a connected client receives them
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<Arc<Vec<u8>>> {
+        self.tx.subscribe()
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    async fn stream_to<W: AsyncWrite + Unpin>(mut frames: broadcast::Receiver<Arc<Vec<u8>>>, mut writer: W, peer: String) {
+        loop {
+            match frames.recv().await {
+                Ok(frame) => {
+                    if let Err(e) = writer.write_all(&frame).await {
+                        debug!(peer = %peer, error = %e, "Feed client disconnected");
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(peer = %peer, skipped = skipped, "Feed client lagging, frames dropped");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    /// Accept TCP clients on `addr` until the listener fails
+    pub async fn serve_tcp(self, addr: String) -> Result<(), HftError> {
+        let listener = TcpListener::bind(&addr).await?;
+        info!(addr = %addr, "Market data feed listening on TCP");
+
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            stream.set_nodelay(true)?;
+            info!(peer = %peer, "Feed client connected");
+            tokio::spawn(Self::stream_to(self.tx.subscribe(), stream, peer.to_string()));
+        }
+    }
+
+    /// Accept clients on a Unix domain socket at `path`, replacing any stale
+    /// socket file
+    pub async fn serve_uds(self, path: PathBuf) -> Result<(), HftError> {
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        let listener = UnixListener::bind(&path)?;
+        info!(path = %path.display(), "Market data feed listening on Unix socket");
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            info!(path = %path.display(), "Feed client connected");
+            tokio::spawn(Self::stream_to(self.tx.subscribe(), stream, path.display().to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpStream;
+    use tokio::time::Duration;
+    use crate::types::Quote;
+
+    #[tokio::test]
+    async fn test_tcp_client_receives_frames() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        let feed = FeedPublisher::new(16);
+        tokio::spawn(feed.clone().serve_tcp(addr.clone()));
+
+        // Wait for the listener and the client to be registered
+        let mut client = loop {
+            if let Ok(stream) = TcpStream::connect(&addr).await {
+                break stream;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        while feed.client_count() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        feed.publish(&FeedMessage::Quote(Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 50000.0,
+            ask: 50001.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "MOCK".into(),
+            timestamp: 1,
+        }));
+
+        let mut buf = vec![0u8; 256];
+        let mut received = Vec::new();
+        let message = loop {
+            let n = tokio::time::timeout(Duration::from_secs(1), client.read(&mut buf))
+                .await
+                .unwrap()
+                .unwrap();
+            received.extend_from_slice(&buf[..n]);
+            if let Some((message, _)) = wire::decode(&received).unwrap() {
+                break message;
+            }
+        };
+
+        match message {
+            FeedMessage::Quote(q) => assert_eq!(q.symbol, "BTCUSDT"),
+            other => panic!("Expected quote, got {:?}", other),
+        }
+    }
+}
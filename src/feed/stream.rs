@@ -0,0 +1,333 @@
+//! JSON over WebSocket for GUI clients.
+//!
+//! Clients connect to `/stream` and choose what they receive with
+//! subscription messages, e.g.
+//! `{"op":"subscribe","topics":["book","orders"],"symbols":["BTCUSDT"]}`
+//! or `{"op":"unsubscribe","topics":["orders"]}`. Messages are encoded once
+//! and fanned out to every client by its own task, so a slow browser only
+//! drops its own messages.
+
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio::time::Duration;
+use tracing::{debug, info, warn};
+use warp::ws::{Message, WebSocket};
+use warp::Filter;
+
+use crate::book::OrderBook;
+use crate::error::HftError;
+use crate::risk::RiskManager;
+use crate::sink::{EventSink, OrderEvent};
+
+/// Price levels per side in book messages
+const BOOK_LEVELS: usize = 10;
+
+/// How often strategy PnL is compared for changes
+const PNL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What a client can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Topic {
+    Book,
+    Orders,
+    Pnl,
+}
+
+/// A message sent to clients
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamMessage {
+    /// Top levels of a symbol's book after an update, best first
+    Book { symbol: String, bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>, timestamp: u64 },
+    Order { event: OrderEvent },
+    /// A strategy's PnL in the reporting currency and its change since the
+    /// last message
+    Pnl { strategy: String, pnl: f64, delta: f64, timestamp: u64 },
+    /// The client's subscriptions after a request
+    Subscribed { topics: Vec<Topic>, symbols: Vec<String> },
+    Error { message: String },
+}
+
+impl StreamMessage {
+    fn topic(&self) -> Option<Topic> {
+        match self {
+            StreamMessage::Book { .. } => Some(Topic::Book),
+            StreamMessage::Order { .. } => Some(Topic::Orders),
+            StreamMessage::Pnl { .. } => Some(Topic::Pnl),
+            StreamMessage::Subscribed { .. } | StreamMessage::Error { .. } => None,
+        }
+    }
+
+    fn symbol(&self) -> Option<&str> {
+        match self {
+            StreamMessage::Book { symbol, .. } => Some(symbol),
+            StreamMessage::Order { event } => match event {
+                OrderEvent::Submitted { order, .. } | OrderEvent::Rejected { order, .. } => Some(&order.symbol),
+                OrderEvent::Fill(fill) => Some(&fill.symbol),
+                OrderEvent::StatusChanged { .. } | OrderEvent::Amended { .. } => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// A request from a client
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Request {
+    /// Add `topics`; `symbols`, when given, replace the symbols book and
+    /// order messages are limited to
+    Subscribe {
+        topics: Vec<Topic>,
+        #[serde(default)]
+        symbols: Vec<String>,
+    },
+    Unsubscribe { topics: Vec<Topic> },
+}
+
+/// An encoded message with what clients filter it on
+struct Frame {
+    topic: Topic,
+    symbol: Option<String>,
+    json: String,
+}
+
+/// What a client receives; book and order messages for any symbol while
+/// `symbols` is empty
+#[derive(Debug, Default)]
+struct Subscription {
+    topics: HashSet<Topic>,
+    symbols: HashSet<String>,
+}
+
+impl Subscription {
+    fn wants(&self, frame: &Frame) -> bool {
+        self.topics.contains(&frame.topic)
+            && (self.symbols.is_empty() || frame.symbol.as_ref().is_none_or(|s| self.symbols.contains(s)))
+    }
+
+    /// Apply `text`, returning the reply to send
+    fn handle(&mut self, text: &str) -> StreamMessage {
+        match serde_json::from_str(text) {
+            Ok(Request::Subscribe { topics, symbols }) => {
+                self.topics.extend(topics);
+                if !symbols.is_empty() {
+                    self.symbols = symbols.into_iter().collect();
+                }
+            }
+            Ok(Request::Unsubscribe { topics }) => {
+                for topic in topics {
+                    self.topics.remove(&topic);
+                }
+            }
+            Err(e) => return StreamMessage::Error { message: format!("Invalid request: {}", e) },
+        }
+        let mut topics: Vec<_> = self.topics.iter().copied().collect();
+        topics.sort_by_key(|topic| *topic as u8);
+        let mut symbols: Vec<_> = self.symbols.iter().cloned().collect();
+        symbols.sort();
+        StreamMessage::Subscribed { topics, symbols }
+    }
+}
+
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
+/// Streams book updates, order events and PnL changes to WebSocket clients.
+/// Cloning shares the clients; nothing is encoded while none are connected.
+#[derive(Clone)]
+pub struct StreamHub {
+    tx: broadcast::Sender<Arc<Frame>>,
+}
+
+impl Default for StreamHub {
+    fn default() -> Self {
+        Self::new(4096)
+    }
+}
+
+impl StreamHub {
+    /// `capacity` is the number of messages a slow client may fall behind
+    /// before it starts dropping them
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        Self { tx }
+    }
+
+    pub fn publish(&self, message: &StreamMessage) {
+        let Some(topic) = message.topic() else {
+            return;
+        };
+        if self.tx.receiver_count() == 0 {
+            return;
+        }
+        let Ok(json) = serde_json::to_string(message) else {
+            return;
+        };
+        let _ = self.tx.send(Arc::new(Frame { topic, symbol: message.symbol().map(str::to_string), json }));
+    }
+
+    /// Publish the top of `book` after an update
+    pub fn publish_book(&self, book: &OrderBook) {
+        if self.tx.receiver_count() == 0 {
+            return;
+        }
+        self.publish(&StreamMessage::Book {
+            symbol: book.symbol().to_string(),
+            bids: book.bids(BOOK_LEVELS).collect(),
+            asks: book.asks(BOOK_LEVELS).collect(),
+            timestamp: now_ms(),
+        });
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// Publish each strategy's PnL whenever it changes, until the task is
+    /// dropped
+    pub async fn track_pnl(self, risk: Arc<RiskManager>) {
+        let mut last: HashMap<String, f64> = HashMap::new();
+        let mut interval = tokio::time::interval(PNL_INTERVAL);
+        loop {
+            interval.tick().await;
+            let mut strategies: HashMap<String, f64> = HashMap::new();
+            for (key, _, pnl) in risk.position_pnl().await {
+                *strategies.entry(key.strategy).or_insert(0.0) += pnl;
+            }
+            for (strategy, pnl) in strategies {
+                let previous = last.insert(strategy.clone(), pnl).unwrap_or(0.0);
+                if (pnl - previous).abs() > f64::EPSILON {
+                    self.publish(&StreamMessage::Pnl { strategy, pnl, delta: pnl - previous, timestamp: now_ms() });
+                }
+            }
+        }
+    }
+
+    async fn session(self, socket: WebSocket) {
+        let (mut tx, mut rx) = socket.split();
+        let mut frames = self.tx.subscribe();
+        let mut subscription = Subscription::default();
+        loop {
+            tokio::select! {
+                incoming = rx.next() => {
+                    let message = match incoming {
+                        Some(Ok(message)) => message,
+                        Some(Err(e)) => {
+                            debug!(error = %e, "Stream client disconnected");
+                            break;
+                        }
+                        None => break,
+                    };
+                    if message.is_close() {
+                        break;
+                    }
+                    let Ok(text) = message.to_str() else {
+                        continue;
+                    };
+                    let reply = subscription.handle(text);
+                    let Ok(json) = serde_json::to_string(&reply) else {
+                        continue;
+                    };
+                    if tx.send(Message::text(json)).await.is_err() {
+                        break;
+                    }
+                }
+                frame = frames.recv() => match frame {
+                    Ok(frame) if subscription.wants(&frame) => {
+                        if tx.send(Message::text(frame.json.clone())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(skipped = skipped, "Stream client lagging, messages dropped");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            }
+        }
+    }
+
+    /// `GET /stream`, upgraded to a WebSocket
+    pub fn routes(self) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("stream")
+            .and(warp::ws())
+            .map(move |ws: warp::ws::Ws| {
+                let hub = self.clone();
+                ws.on_upgrade(move |socket| hub.session(socket))
+            })
+    }
+
+    /// Accept clients on `addr`
+    pub async fn serve(self, addr: SocketAddr) {
+        info!(addr = %addr, "GUI stream listening");
+        warp::serve(self.routes()).run(addr).await;
+    }
+}
+
+#[async_trait]
+impl EventSink for StreamHub {
+    fn name(&self) -> &str {
+        "stream"
+    }
+
+    async fn publish(&self, events: &[OrderEvent]) -> Result<(), HftError> {
+        for event in events {
+            StreamHub::publish(self, &StreamMessage::Order { event: event.clone() });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::OrderStatus;
+    use crate::types::Quote;
+
+    #[tokio::test]
+    async fn test_client_receives_subscribed_topics() {
+        let hub = StreamHub::new(16);
+        let mut client = warp::test::ws().path("/stream").handshake(hub.clone().routes()).await.unwrap();
+
+        client.send_text(r#"{"op":"subscribe","topics":["book","orders"],"symbols":["BTCUSDT"]}"#).await;
+        let reply: serde_json::Value = serde_json::from_str(client.recv().await.unwrap().to_str().unwrap()).unwrap();
+        assert_eq!(reply, serde_json::json!({ "type": "subscribed", "topics": ["book", "orders"], "symbols": ["BTCUSDT"] }));
+
+        let mut book = OrderBook::new("ETHUSDT".into());
+        hub.publish_book(&book);
+        hub.publish(&StreamMessage::Pnl { strategy: "mm".to_string(), pnl: 1.0, delta: 1.0, timestamp: 0 });
+        book = OrderBook::new("BTCUSDT".into());
+        book.update(&Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 100.0,
+            ask: 101.0,
+            bid_size: 2.0,
+            ask_size: 3.0,
+            venue: "MOCK".into(),
+            timestamp: 0,
+        });
+        hub.publish_book(&book);
+        EventSink::publish(&hub, &[OrderEvent::StatusChanged { order_id: "1".to_string(), status: OrderStatus::Filled, timestamp: 0 }]).await.unwrap();
+
+        // Only the BTCUSDT book and the order event get through
+        let message: serde_json::Value = serde_json::from_str(client.recv().await.unwrap().to_str().unwrap()).unwrap();
+        assert_eq!((&message["type"], &message["symbol"]), (&serde_json::json!("book"), &serde_json::json!("BTCUSDT")));
+        assert_eq!(message["bids"], serde_json::json!([[100.0, 2.0]]));
+        let message: serde_json::Value = serde_json::from_str(client.recv().await.unwrap().to_str().unwrap()).unwrap();
+        assert_eq!(message["type"], "order");
+        assert_eq!(message["event"]["status"], "Filled");
+
+        client.send_text(r#"{"op":"resubscribe"}"#).await;
+        let reply: serde_json::Value = serde_json::from_str(client.recv().await.unwrap().to_str().unwrap()).unwrap();
+        assert_eq!(reply["type"], "error");
+    }
+}
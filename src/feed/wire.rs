@@ -0,0 +1,230 @@
+//! Compact little-endian framing for the outbound market data feed.
+//!
+//! Every frame is `[u32 body length][u8 message type][body]`. Strings are
+//! encoded as a `u8` length followed by UTF-8 bytes, sides as `0` (buy) or
+//! `1` (sell), and all numbers as fixed-width little-endian values.
+
+use crate::error::HftError;
+use crate::types::{OrderSide, Quote, Trade};
+
+const MSG_QUOTE: u8 = 1;
+const MSG_TRADE: u8 = 2;
+const MSG_BOOK_UPDATE: u8 = 3;
+
+/// Frame header size: body length plus message type
+pub const HEADER_LEN: usize = 5;
+
+/// Change to a single price level; a size of zero removes the level
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookUpdate {
+    pub symbol: String,
+    pub venue: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub size: f64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum FeedMessage {
+    Quote(Quote),
+    Trade(Trade),
+    BookUpdate(BookUpdate),
+}
+
+fn put_str(buf: &mut Vec<u8>, s: &str) {
+    let bytes = &s.as_bytes()[..s.len().min(u8::MAX as usize)];
+    buf.push(bytes.len() as u8);
+    buf.extend_from_slice(bytes);
+}
+
+fn put_side(buf: &mut Vec<u8>, side: OrderSide) {
+    buf.push(match side {
+        OrderSide::Buy => 0,
+        OrderSide::Sell => 1,
+    });
+}
+
+pub fn encode(message: &FeedMessage) -> Vec<u8> {
+    let mut body = Vec::with_capacity(64);
+
+    let msg_type = match message {
+        FeedMessage::Quote(q) => {
+            put_str(&mut body, &q.symbol);
+            put_str(&mut body, &q.venue);
+            for v in [q.bid, q.ask, q.bid_size, q.ask_size] {
+                body.extend_from_slice(&v.to_le_bytes());
+            }
+            body.extend_from_slice(&q.timestamp.to_le_bytes());
+            MSG_QUOTE
+        }
+        FeedMessage::Trade(t) => {
+            put_str(&mut body, &t.symbol);
+            put_str(&mut body, &t.venue);
+            body.extend_from_slice(&t.price.to_le_bytes());
+            body.extend_from_slice(&t.quantity.to_le_bytes());
+            put_side(&mut body, t.side);
+            body.extend_from_slice(&t.timestamp.to_le_bytes());
+            MSG_TRADE
+        }
+        FeedMessage::BookUpdate(u) => {
+            put_str(&mut body, &u.symbol);
+            put_str(&mut body, &u.venue);
+            put_side(&mut body, u.side);
+            body.extend_from_slice(&u.price.to_le_bytes());
+            body.extend_from_slice(&u.size.to_le_bytes());
+            body.extend_from_slice(&u.timestamp.to_le_bytes());
+            MSG_BOOK_UPDATE
+        }
+    };
+
+    let mut frame = Vec::with_capacity(HEADER_LEN + body.len());
+    frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    frame.push(msg_type);
+    frame.extend_from_slice(&body);
+    frame
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], HftError> {
+        let end = self.pos + n;
+        let slice = self.buf.get(self.pos..end)
+            .ok_or_else(|| HftError::Serialization("Truncated feed frame".to_string()))?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, HftError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, HftError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f64(&mut self) -> Result<f64, HftError> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn str(&mut self) -> Result<String, HftError> {
+        let len = self.u8()? as usize;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|e| HftError::Serialization(format!("Invalid string in feed frame: {}", e)))
+    }
+
+    fn side(&mut self) -> Result<OrderSide, HftError> {
+        match self.u8()? {
+            0 => Ok(OrderSide::Buy),
+            1 => Ok(OrderSide::Sell),
+            other => Err(HftError::Serialization(format!("Invalid side {}", other))),
+        }
+    }
+}
+
+/// Decode one frame from the front of `buf`.
+///
+/// Returns `Ok(None)` if `buf` does not yet hold a complete frame, otherwise
+/// the message and the number of bytes consumed.
+pub fn decode(buf: &[u8]) -> Result<Option<(FeedMessage, usize)>, HftError> {
+    if buf.len() < HEADER_LEN {
+        return Ok(None);
+    }
+
+    let body_len = u32::from_le_bytes(buf[..4].try_into().unwrap()) as usize;
+    let frame_len = HEADER_LEN + body_len;
+    if buf.len() < frame_len {
+        return Ok(None);
+    }
+
+    let mut r = Reader { buf: &buf[HEADER_LEN..frame_len], pos: 0 };
+    let message = match buf[4] {
+        MSG_QUOTE => FeedMessage::Quote(Quote {
+            symbol: r.str()?.into(),
+            venue: r.str()?.into(),
+            bid: r.f64()?,
+            ask: r.f64()?,
+            bid_size: r.f64()?,
+            ask_size: r.f64()?,
+            timestamp: r.u64()?,
+        }),
+        MSG_TRADE => FeedMessage::Trade(Trade {
+            symbol: r.str()?.into(),
+            venue: r.str()?.into(),
+            price: r.f64()?,
+            quantity: r.f64()?,
+            side: r.side()?,
+            timestamp: r.u64()?,
+        }),
+        MSG_BOOK_UPDATE => FeedMessage::BookUpdate(BookUpdate {
+            symbol: r.str()?,
+            venue: r.str()?,
+            side: r.side()?,
+            price: r.f64()?,
+            size: r.f64()?,
+            timestamp: r.u64()?,
+        }),
+        other => return Err(HftError::Serialization(format!("Unknown feed message type {}", other))),
+    };
+
+    Ok(Some((message, frame_len)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_partial_frames() {
+        let quote = Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 50000.5,
+            ask: 50001.0,
+            bid_size: 1.25,
+            ask_size: 3.0,
+            venue: "BINANCE_FUTURES".into(),
+            timestamp: 1_700_000_000_000,
+        };
+        let update = BookUpdate {
+            symbol: "ETHUSDT".to_string(),
+            venue: "MOCK".to_string(),
+            side: OrderSide::Sell,
+            price: 3000.25,
+            size: 0.0,
+            timestamp: 7,
+        };
+
+        let mut stream = encode(&FeedMessage::Quote(quote));
+        stream.extend(encode(&FeedMessage::BookUpdate(update.clone())));
+
+        // A partial header is not an error
+        assert!(decode(&stream[..3]).unwrap().is_none());
+
+        let (first, used) = decode(&stream).unwrap().unwrap();
+        match first {
+            FeedMessage::Quote(q) => {
+                assert_eq!(q.symbol, "BTCUSDT");
+                assert_eq!(q.bid, 50000.5);
+                assert_eq!(q.timestamp, 1_700_000_000_000);
+            }
+            other => panic!("Expected quote, got {:?}", other),
+        }
+
+        let (second, rest) = decode(&stream[used..]).unwrap().unwrap();
+        assert_eq!(used + rest, stream.len());
+        match second {
+            FeedMessage::BookUpdate(u) => assert_eq!(u, update),
+            other => panic!("Expected book update, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unknown_message_type() {
+        let frame = [0, 0, 0, 0, 99];
+        assert!(decode(&frame).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! Maker/taker fee schedules.
+//!
+//! Each venue's schedule lists its fee tiers by trading volume. The shared
+//! [`FeeModel`] charges a venue's current tier, moving up tiers as fills
+//! add to the venue's volume. PnL reporting, failover routing and
+//! strategies all read fees from it.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::warn;
+
+use crate::types::Fill;
+
+/// Whether a fill added or took liquidity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+/// Maker and taker fees in basis points of notional; negative fees are
+/// rebates
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Fees {
+    pub maker_bps: f64,
+    pub taker_bps: f64,
+}
+
+impl Fees {
+    pub fn bps(&self, liquidity: Liquidity) -> f64 {
+        match liquidity {
+            Liquidity::Maker => self.maker_bps,
+            Liquidity::Taker => self.taker_bps,
+        }
+    }
+
+    /// Fee on `notional` traded with `liquidity`, in the notional's currency
+    pub fn fee(&self, notional: f64, liquidity: Liquidity) -> f64 {
+        notional * self.bps(liquidity) / 10_000.0
+    }
+}
+
+/// Fees charged from a trading volume up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeTier {
+    pub min_volume: f64,
+    pub fees: Fees,
+}
+
+/// A venue's fee tiers, lowest volume first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeeSchedule {
+    tiers: Vec<FeeTier>,
+}
+
+impl FeeSchedule {
+    /// A schedule charging `fees` at any volume
+    pub fn new(fees: Fees) -> Self {
+        Self::default().with_tier(0.0, fees)
+    }
+
+    /// Charge `fees` from `min_volume` up, replacing a tier starting there
+    pub fn with_tier(mut self, min_volume: f64, fees: Fees) -> Self {
+        self.tiers.retain(|tier| tier.min_volume != min_volume);
+        self.tiers.push(FeeTier { min_volume, fees });
+        self.tiers.sort_by(|a, b| a.min_volume.total_cmp(&b.min_volume));
+        self
+    }
+
+    pub fn tiers(&self) -> &[FeeTier] {
+        &self.tiers
+    }
+
+    /// Fees of the highest tier `volume` reaches
+    pub fn fees(&self, volume: f64) -> Fees {
+        self.tiers.iter()
+            .rev()
+            .find(|tier| volume >= tier.min_volume)
+            .map(|tier| tier.fees)
+            .unwrap_or_default()
+    }
+}
+
+/// Fee schedules per venue, with a default for the rest, and each venue's
+/// trading volume before the engine started
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeeSchedules {
+    venues: HashMap<String, FeeSchedule>,
+    default: Option<FeeSchedule>,
+    volumes: HashMap<String, f64>,
+}
+
+impl FeeSchedules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_venue(mut self, venue: &str, schedule: FeeSchedule) -> Self {
+        self.venues.insert(venue.to_uppercase(), schedule);
+        self
+    }
+
+    /// Schedule for venues without their own
+    pub fn with_default(mut self, schedule: FeeSchedule) -> Self {
+        self.default = Some(schedule);
+        self
+    }
+
+    /// Volume already traded on `venue` in the period its tier is based
+    /// on, in the quote currency
+    pub fn with_volume(mut self, venue: &str, volume: f64) -> Self {
+        self.volumes.insert(venue.to_uppercase(), volume);
+        self
+    }
+
+    /// Comma separated `VENUE=MAKER/TAKER` entries in basis points, with
+    /// `VENUE@VOLUME=MAKER/TAKER` for the tier from that volume up and `*`
+    /// for the default, e.g. `BINANCE=1/5,BINANCE@1000000=0.5/4,*=10/10`
+    fn parse(spec: &str) -> Self {
+        let mut schedules = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=').and_then(|(venue, fees)| {
+                let (venue, min_volume) = match venue.split_once('@') {
+                    Some((venue, volume)) => (venue.trim(), volume.trim().parse::<f64>().ok()?),
+                    None => (venue.trim(), 0.0),
+                };
+                let (maker, taker) = fees.split_once('/')?;
+                let fees = Fees { maker_bps: maker.trim().parse().ok()?, taker_bps: taker.trim().parse().ok()? };
+                (!venue.is_empty()).then_some((venue, min_volume, fees))
+            });
+            let Some((venue, min_volume, fees)) = parsed else {
+                warn!(entry = entry, "Ignoring malformed fee schedule entry");
+                continue;
+            };
+            let schedule = match venue {
+                "*" => schedules.default.get_or_insert_with(FeeSchedule::default),
+                venue => schedules.venues.entry(venue.to_uppercase()).or_default(),
+            };
+            *schedule = std::mem::take(schedule).with_tier(min_volume, fees);
+        }
+        schedules
+    }
+
+    /// Read `HFT_FEES` (see [`parse`](Self::parse)) and the comma separated
+    /// `VENUE=VOLUME` entries in `HFT_FEE_VOLUMES`; returns `None` when no
+    /// fees are set
+    pub fn from_env() -> Option<Self> {
+        let mut schedules = Self::parse(&std::env::var("HFT_FEES").ok()?);
+        let volumes = std::env::var("HFT_FEE_VOLUMES").unwrap_or_default();
+        for entry in volumes.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once('=').and_then(|(venue, volume)| Some((venue.trim(), volume.trim().parse::<f64>().ok()?))) {
+                Some((venue, volume)) => schedules = schedules.with_volume(venue, volume),
+                None => warn!(entry = entry, "Ignoring malformed fee volume"),
+            }
+        }
+        Some(schedules)
+    }
+
+    fn schedule(&self, venue: &str) -> Option<&FeeSchedule> {
+        self.venues.get(venue).or(self.default.as_ref())
+    }
+}
+
+/// The fees each venue charges at its current tier. Cloning shares the
+/// schedules and volumes; until configured, every venue is free.
+#[derive(Debug, Clone, Default)]
+pub struct FeeModel {
+    schedules: Arc<RwLock<FeeSchedules>>,
+}
+
+impl FeeModel {
+    pub fn new(schedules: FeeSchedules) -> Self {
+        let model = Self::default();
+        model.configure(schedules);
+        model
+    }
+
+    /// Replace the schedules and starting volumes
+    pub fn configure(&self, schedules: FeeSchedules) {
+        *self.schedules.write().unwrap() = schedules;
+    }
+
+    /// Fees `venue` charges at its current tier, if it has a schedule
+    pub fn configured(&self, venue: &str) -> Option<Fees> {
+        let schedules = self.schedules.read().unwrap();
+        let venue = venue.to_uppercase();
+        let volume = schedules.volumes.get(&venue).copied().unwrap_or(0.0);
+        schedules.schedule(&venue).map(|schedule| schedule.fees(volume))
+    }
+
+    /// Fees `venue` charges at its current tier
+    pub fn fees(&self, venue: &str) -> Fees {
+        self.configured(venue).unwrap_or_default()
+    }
+
+    /// Fee on `notional` traded on `venue` with `liquidity`
+    pub fn fee(&self, venue: &str, notional: f64, liquidity: Liquidity) -> f64 {
+        self.fees(venue).fee(notional, liquidity)
+    }
+
+    /// Volume traded on `venue` toward its tier, in the quote currency
+    pub fn volume(&self, venue: &str) -> f64 {
+        self.schedules.read().unwrap().volumes.get(&venue.to_uppercase()).copied().unwrap_or(0.0)
+    }
+
+    /// Add the fill's notional to its venue's volume
+    pub fn on_fill(&self, fill: &Fill) {
+        *self.schedules.write().unwrap()
+            .volumes
+            .entry(fill.venue.to_uppercase())
+            .or_insert(0.0) += fill.price * fill.quantity;
+    }
+
+    /// The venue in `venues` with the lowest fee for `liquidity`, the
+    /// first listed on a tie
+    pub fn cheapest<'a>(&self, venues: &'a [String], liquidity: Liquidity) -> Option<&'a String> {
+        venues.iter()
+            .map(|venue| (venue, self.fees(venue).bps(liquidity)))
+            .reduce(|best, next| if next.1 < best.1 { next } else { best })
+            .map(|(venue, _)| venue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderSide;
+
+    #[test]
+    fn test_tiers_follow_volume() {
+        let model = FeeModel::new(FeeSchedules::parse("binance=1/5, BINANCE@1000000=0.5/4, *=10/10, BAD=1, X@y=1/1")
+            .with_volume("BINANCE", 900_000.0));
+        assert_eq!(model.fees("BINANCE"), Fees { maker_bps: 1.0, taker_bps: 5.0 });
+        assert_eq!(model.fees("OTHER"), Fees { maker_bps: 10.0, taker_bps: 10.0 });
+        assert_eq!(model.configured("X"), Some(Fees { maker_bps: 10.0, taker_bps: 10.0 }));
+
+        model.on_fill(&Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "BINANCE".into(),
+            strategy: String::new(),
+            side: OrderSide::Buy,
+            quantity: 2.0,
+            price: 50_000.0,
+            timestamp: 0,
+            commission: None,
+        });
+        assert_eq!(model.volume("binance"), 1_000_000.0);
+        assert_eq!(model.fee("BINANCE", 10_000.0, Liquidity::Taker), 4.0);
+
+        let venues = vec!["OTHER".to_string(), "BINANCE".to_string()];
+        assert_eq!(model.cheapest(&venues, Liquidity::Maker), Some(&venues[1]));
+        assert_eq!(FeeModel::default().cheapest(&venues, Liquidity::Maker), Some(&venues[0]));
+    }
+}
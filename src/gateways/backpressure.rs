@@ -0,0 +1,180 @@
+//! What the quote gateway does when the book builder falls behind.
+//!
+//! Waiting for room in the quote channel stalls the venue reader feeding the
+//! gateway, and the exchange buffers its WebSocket stream behind it. The
+//! dropping policies never wait: quotes that do not fit are held back per
+//! symbol and venue, the oldest giving way when too many pile up, and a
+//! background task hands them on as the channel frees up.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::types::{Quote, Symbol, VenueId};
+
+const DEFAULT_DROP_DEPTH: usize = 8;
+
+/// How the quote gateway handles a full quote channel
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum QuoteBackpressure {
+    /// Wait for room, stalling the venue's reader
+    #[default]
+    Block,
+    /// Hold back up to `depth` quotes per symbol and venue, dropping the
+    /// oldest beyond that
+    DropOldest { depth: usize },
+    /// Hold back only the latest quote per symbol and venue
+    Conflate,
+}
+
+impl QuoteBackpressure {
+    /// `block`, `conflate`, or `drop_oldest` with an optional `:DEPTH`
+    /// (default 8)
+    fn parse(spec: &str) -> Option<Self> {
+        let policy = match spec.split_once(':').map(|(policy, depth)| (policy.trim(), depth.trim())) {
+            Some(("drop_oldest", depth)) => QuoteBackpressure::DropOldest { depth: depth.parse().ok().filter(|d| *d > 0)? },
+            Some(_) => return None,
+            None => match spec.trim() {
+                "block" => QuoteBackpressure::Block,
+                "drop_oldest" => QuoteBackpressure::DropOldest { depth: DEFAULT_DROP_DEPTH },
+                "conflate" => QuoteBackpressure::Conflate,
+                _ => return None,
+            },
+        };
+        Some(policy)
+    }
+
+    /// Read `HFT_QUOTE_BACKPRESSURE` (see [`parse`](Self::parse)); returns
+    /// `None` when unset or malformed
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var("HFT_QUOTE_BACKPRESSURE").ok()?;
+        let policy = Self::parse(&spec);
+        if policy.is_none() {
+            warn!(spec = %spec, "Ignoring malformed quote backpressure policy");
+        }
+        policy
+    }
+
+    /// Quotes held back per symbol and venue, or `None` when blocking
+    pub(crate) fn depth(&self) -> Option<usize> {
+        match self {
+            QuoteBackpressure::Block => None,
+            QuoteBackpressure::DropOldest { depth } => Some(*depth),
+            QuoteBackpressure::Conflate => Some(1),
+        }
+    }
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            QuoteBackpressure::Block => "block",
+            QuoteBackpressure::DropOldest { .. } => "drop_oldest",
+            QuoteBackpressure::Conflate => "conflate",
+        }
+    }
+}
+
+type StreamKey = (Symbol, VenueId);
+
+/// Quotes held back from a full channel, per symbol and venue
+#[derive(Default)]
+pub(crate) struct PendingQuotes {
+    queues: HashMap<StreamKey, VecDeque<Quote>>,
+    /// Streams with quotes held back, taken in turn as room frees up
+    ready: VecDeque<StreamKey>,
+    /// Whether a task is handing the held back quotes on
+    pub(crate) draining: bool,
+}
+
+impl PendingQuotes {
+    /// Whether quotes for `quote`'s stream are held back, so it has to
+    /// queue behind them
+    pub(crate) fn holds(&self, quote: &Quote) -> bool {
+        self.queues.contains_key(&(quote.symbol, quote.venue))
+    }
+
+    /// Hold `quote` back, returning the quote dropped to make room for it
+    pub(crate) fn push(&mut self, quote: Quote, depth: usize) -> Option<Quote> {
+        let key = (quote.symbol, quote.venue);
+        let queue = self.queues.entry(key).or_insert_with(|| {
+            self.ready.push_back(key);
+            VecDeque::new()
+        });
+        let dropped = if queue.len() >= depth { queue.pop_front() } else { None };
+        queue.push_back(quote);
+        dropped
+    }
+
+    /// The oldest held back quote of the next stream in turn
+    pub(crate) fn pop(&mut self) -> Option<Quote> {
+        let key = self.ready.pop_front()?;
+        let queue = self.queues.get_mut(&key)?;
+        let quote = queue.pop_front();
+        if queue.is_empty() {
+            self.queues.remove(&key);
+        } else {
+            self.ready.push_back(key);
+        }
+        quote
+    }
+
+    fn clear(&mut self) {
+        self.queues.clear();
+        self.ready.clear();
+    }
+}
+
+/// Hand held back quotes to `tx` as it frees up, until none are left. Sends
+/// happen under the lock, so quotes for a stream stay in order with ones
+/// the gateway sends directly.
+pub(crate) async fn drain(pending: Arc<Mutex<PendingQuotes>>, tx: mpsc::Sender<Quote>) {
+    loop {
+        let permit = tx.reserve().await;
+        let mut pending = pending.lock().unwrap();
+        let Ok(permit) = permit else {
+            pending.clear();
+            pending.draining = false;
+            return;
+        };
+        match pending.pop() {
+            Some(quote) => permit.send(quote),
+            None => {
+                pending.draining = false;
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(symbol: &str, bid: f64) -> Quote {
+        Quote { symbol: symbol.into(), bid, ask: bid + 1.0, bid_size: 1.0, ask_size: 1.0, venue: "MOCK".into(), timestamp: 1 }
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!(QuoteBackpressure::parse("block"), Some(QuoteBackpressure::Block));
+        assert_eq!(QuoteBackpressure::parse("conflate"), Some(QuoteBackpressure::Conflate));
+        assert_eq!(QuoteBackpressure::parse("drop_oldest"), Some(QuoteBackpressure::DropOldest { depth: 8 }));
+        assert_eq!(QuoteBackpressure::parse("drop_oldest:3"), Some(QuoteBackpressure::DropOldest { depth: 3 }));
+        assert_eq!(QuoteBackpressure::parse("drop_oldest:0"), None);
+        assert_eq!(QuoteBackpressure::parse("conflate:2"), None);
+    }
+
+    #[test]
+    fn test_pending_drops_oldest_and_takes_streams_in_turn() {
+        let mut pending = PendingQuotes::default();
+        assert!(pending.push(quote("BTCUSDT", 1.0), 2).is_none());
+        assert!(pending.push(quote("BTCUSDT", 2.0), 2).is_none());
+        assert!(pending.push(quote("ETHUSDT", 10.0), 2).is_none());
+        assert_eq!(pending.push(quote("BTCUSDT", 3.0), 2).map(|q| q.bid), Some(1.0));
+        assert!(pending.holds(&quote("BTCUSDT", 0.0)));
+
+        let order: Vec<f64> = std::iter::from_fn(|| pending.pop()).map(|q| q.bid).collect();
+        assert_eq!(order, vec![2.0, 10.0, 3.0]);
+        assert!(!pending.holds(&quote("BTCUSDT", 0.0)));
+    }
+}
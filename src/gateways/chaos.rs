@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use rand::Rng;
+
+use crate::metrics::CHAOS_FAULTS;
+use crate::rng::{component_rng, seed_from_env, SimRng};
+
+/// Faults the gateways inject into their own traffic so resilience logic
+/// can be exercised end to end. For test environments only.
+#[derive(Debug, Clone, Default)]
+pub struct ChaosConfig {
+    /// Added to every quote and order
+    pub latency: Duration,
+    /// Extra random delay of up to this much
+    pub jitter: Duration,
+    /// Share of quotes dropped and of orders failed as if the connection
+    /// had dropped, between 0 and 1
+    pub drop_probability: f64,
+    /// Seed for the faults' random draws, each component drawing from its
+    /// own stream; unseeded faults are not reproducible
+    pub seed: Option<u64>,
+    pub(crate) rngs: Arc<Mutex<HashMap<String, SimRng>>>,
+}
+
+impl ChaosConfig {
+    /// Read `HFT_CHAOS_LATENCY_MS`, `HFT_CHAOS_JITTER_MS` and
+    /// `HFT_CHAOS_DROP_PROBABILITY`, seeded from `HFT_SEED`; returns `None`
+    /// when none are set
+    pub fn from_env() -> Option<Self> {
+        let var = |key: &str| std::env::var(key).ok().and_then(|s| s.parse::<f64>().ok());
+        let latency = var("HFT_CHAOS_LATENCY_MS");
+        let jitter = var("HFT_CHAOS_JITTER_MS");
+        let drop_probability = var("HFT_CHAOS_DROP_PROBABILITY");
+        if latency.is_none() && jitter.is_none() && drop_probability.is_none() {
+            return None;
+        }
+
+        let millis = |ms: Option<f64>| Duration::from_secs_f64(ms.unwrap_or(0.0).max(0.0) / 1000.0);
+        Some(Self {
+            latency: millis(latency),
+            jitter: millis(jitter),
+            drop_probability: drop_probability.unwrap_or(0.0).clamp(0.0, 1.0),
+            seed: Some(seed_from_env()),
+            rngs: Arc::default(),
+        })
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Uniform draws in `[0, 1)` for the jitter and the drop decision
+    fn draw(&self, component: &str) -> (f64, f64) {
+        let Some(seed) = self.seed else {
+            let mut rng = rand::rng();
+            return (rng.random(), rng.random());
+        };
+        let mut rngs = self.rngs.lock().unwrap();
+        let rng = rngs.entry(component.to_string()).or_insert_with(|| component_rng(seed, component));
+        (rng.random(), rng.random())
+    }
+
+    /// Delay the caller, then decide whether its message gets through.
+    /// Injected faults are counted under `component`.
+    pub(crate) async fn pass(&self, component: &str) -> bool {
+        let (jitter, drop) = self.draw(component);
+        let delay = self.latency + self.jitter.mul_f64(jitter);
+        let dropped = drop < self.drop_probability;
+
+        if !delay.is_zero() {
+            CHAOS_FAULTS.with_label_values(&[component, "latency"]).inc();
+            tokio::time::sleep(delay).await;
+        }
+        if dropped {
+            CHAOS_FAULTS.with_label_values(&[component, "drop"]).inc();
+        }
+        !dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_seeded_drops_replay() {
+        let run = |seed| async move {
+            let chaos = ChaosConfig { drop_probability: 0.5, ..ChaosConfig::default() }.with_seed(seed);
+            let mut passed = Vec::new();
+            for _ in 0..20 {
+                passed.push(chaos.pass("chaos_test").await);
+            }
+            passed
+        };
+        assert_eq!(run(1).await, run(1).await);
+        assert_ne!(run(1).await, run(2).await);
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use tracing::warn;
+
+/// What the order gateway does with an order for a venue that is down
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VenueFailover {
+    /// Reject the order straight away
+    Reject,
+    /// Hold the order until the venue reconnects, rejecting it after `ttl`
+    Queue { ttl: Duration },
+    /// Send the order to another venue under the same symbol, the one with
+    /// the lowest fee when several are listed
+    Reroute { venues: Vec<String> },
+}
+
+impl fmt::Display for VenueFailover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VenueFailover::Reject => write!(f, "reject"),
+            VenueFailover::Queue { .. } => write!(f, "queue"),
+            VenueFailover::Reroute { .. } => write!(f, "reroute"),
+        }
+    }
+}
+
+impl VenueFailover {
+    /// Parse `reject`, `queue:<secs>` or `reroute:<venue>[|<venue>...]`
+    fn parse(s: &str) -> Option<Self> {
+        match s.split_once(':') {
+            None if s == "reject" => Some(VenueFailover::Reject),
+            Some(("queue", secs)) => Some(VenueFailover::Queue {
+                ttl: Duration::from_secs_f64(secs.parse().ok()?),
+            }),
+            Some(("reroute", venues)) if !venues.split('|').any(str::is_empty) => Some(VenueFailover::Reroute {
+                venues: venues.split('|').map(str::to_string).collect(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Failover policy per symbol, with a default for the rest
+#[derive(Debug, Clone)]
+pub struct FailoverPolicies {
+    pub default: VenueFailover,
+    pub symbols: HashMap<String, VenueFailover>,
+}
+
+impl Default for FailoverPolicies {
+    fn default() -> Self {
+        Self {
+            default: VenueFailover::Reject,
+            symbols: HashMap::new(),
+        }
+    }
+}
+
+impl FailoverPolicies {
+    /// Read `HFT_VENUE_FAILOVER` as comma separated `SYMBOL=POLICY` entries,
+    /// with `*` for the default, e.g.
+    /// `BTCUSDT=reroute:BINANCE_SPOT,ETHUSDT=queue:5,*=reject`
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var("HFT_VENUE_FAILOVER").ok()?;
+
+        let mut policies = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once('=').and_then(|(symbol, policy)| Some((symbol, VenueFailover::parse(policy)?))) {
+                Some(("*", policy)) => policies.default = policy,
+                Some((symbol, policy)) => {
+                    policies.symbols.insert(symbol.to_uppercase(), policy);
+                }
+                None => warn!(entry = entry, "Ignoring malformed venue failover policy"),
+            }
+        }
+        Some(policies)
+    }
+
+    pub fn for_symbol(&self, symbol: &str) -> &VenueFailover {
+        self.symbols.get(symbol).unwrap_or(&self.default)
+    }
+}
@@ -1,2 +1,15 @@
 pub mod quote;
-pub mod order;
\ No newline at end of file
+pub mod order;
+pub mod failover;
+pub mod chaos;
+pub mod subscriptions;
+pub mod quality;
+pub mod storm;
+pub mod backpressure;
+
+pub use backpressure::QuoteBackpressure;
+pub use chaos::ChaosConfig;
+pub use quality::{DataIssue, DataQualityConfig, DataQualityMonitor};
+pub use storm::{QuoteStormConfig, QuoteStormGuard};
+pub use failover::{FailoverPolicies, VenueFailover};
+pub use subscriptions::{SubscriptionChanges, SubscriptionSpec, SubscriptionStore};
\ No newline at end of file
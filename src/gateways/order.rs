@@ -1,9 +1,1175 @@
-use std::sync::Arc;
-use tokio::sync::mpsc;
-use crate::types::Order;
-use crate::venues::VenueAdapter;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tracing::{debug, error, info, warn};
+
+use crate::types::{Order, OrderType};
+use crate::venues::{Regions, VenueAdapter, VenueRegistry};
+use crate::error::{HftError, ExecutionError, GatewayError, VenueError};
+use crate::execution::{OpenOrder, OrderStatus};
+use crate::metrics::{ACKED_QUANTITY, ORDERS_EXPIRED, ORDER_ACKS, ORDER_AMENDS, ORDER_CANCELS, ORDER_RECONCILE_DRIFT, ORDER_REJECTS, VENUE_FAILOVERS};
+use crate::gateways::failover::{FailoverPolicies, VenueFailover};
+use crate::events::{EngineEvent, EventBus};
+use crate::health::Heartbeat;
+use crate::execution::OrderTracker;
+use crate::sink::{OrderEvent, SinkHandle};
+use crate::failover::Leadership;
+use crate::audit::{AuditEvent, AuditLog};
+use crate::instruments::InstrumentMap;
+use crate::gateways::chaos::ChaosConfig;
+use crate::fees::{FeeModel, Liquidity};
+use crate::risk::RiskManager;
+
+/// Queued orders above this count mark the gateway as not draining
+const MAX_ORDER_BACKLOG: usize = 500;
+
+/// How often an idle loop reports that it is still alive
+const IDLE_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often resting orders are checked against their TTL
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Delay before retrying a failed cancel of an expired order
+const EXPIRY_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// How often tracked orders are checked against the venues' open orders
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Wait after an order's outcome is lost before reconciling, giving the
+/// venue time to act on it
+const UNKNOWN_OUTCOME_RECONCILE_DELAY: Duration = Duration::from_secs(1);
+
+/// How long a venue that failed an order is skipped before the next order
+/// tries it again
+const VENUE_DOWN_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Which resting orders a [`CancelRequest`] is for
+#[derive(Debug, Clone, PartialEq)]
+pub enum CancelTarget {
+    /// One tracked order
+    Order(String),
+    /// Every open order on the named venue, or on every venue when `None`
+    All(Option<String>),
+}
+
+/// A cancel the engine itself originates, e.g. on a loss limit breach,
+/// sent through the order gateway so it is audited and tracked like any
+/// other
+#[derive(Debug, Clone, PartialEq)]
+pub struct CancelRequest {
+    pub target: CancelTarget,
+    /// Fixed name for the cause, used as the metric label
+    pub kind: &'static str,
+    /// Recorded in the audit log
+    pub reason: String,
+}
+
+impl CancelRequest {
+    pub fn order(order_id: impl Into<String>, kind: &'static str, reason: impl Into<String>) -> Self {
+        Self { target: CancelTarget::Order(order_id.into()), kind, reason: reason.into() }
+    }
+
+    /// Every open order on `venue`, or on every venue when `None`
+    pub fn all(venue: Option<String>, kind: &'static str, reason: impl Into<String>) -> Self {
+        Self { target: CancelTarget::All(venue), kind, reason: reason.into() }
+    }
+}
+
+/// A change to the price and quantity of a resting order, sent to the
+/// running gateway. The ID the order is tracked under afterwards, or why
+/// the amend failed, goes back on `reply` when set.
+#[derive(Debug)]
+pub struct AmendRequest {
+    pub order_id: String,
+    pub price: f64,
+    pub quantity: f64,
+    pub reply: Option<oneshot::Sender<Result<String, HftError>>>,
+}
+
+impl AmendRequest {
+    pub fn new(order_id: impl Into<String>, price: f64, quantity: f64) -> Self {
+        Self { order_id: order_id.into(), price, quantity, reply: None }
+    }
+
+    /// The request along with the receiver its outcome arrives on
+    pub fn with_reply(mut self) -> (Self, oneshot::Receiver<Result<String, HftError>>) {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.reply = Some(reply_tx);
+        (self, reply_rx)
+    }
+}
 
 pub struct OrderGateway {
-    pub(crate) venues: Vec<Arc<dyn VenueAdapter>>,
+    pub(crate) venues: VenueRegistry,
     pub(crate) order_rx: mpsc::Receiver<Order>,
-}
\ No newline at end of file
+    /// Cancels from risk, the scheduler and other components, taken ahead
+    /// of queued orders
+    pub(crate) cancel_rx: Option<mpsc::Receiver<CancelRequest>>,
+    /// Amends of resting orders, taken after cancels and ahead of queued
+    /// orders
+    pub(crate) amend_rx: Option<mpsc::Receiver<AmendRequest>>,
+    pub(crate) events: EventBus,
+    pub(crate) heartbeat: Option<Heartbeat>,
+    pub(crate) orders: Arc<OrderTracker>,
+    /// Downstream consumers of order lifecycle events
+    pub(crate) sinks: Vec<SinkHandle>,
+    /// When set, orders are only sent while this instance is leader
+    pub(crate) leadership: Option<Leadership>,
+    pub(crate) audit: Option<AuditLog>,
+    pub(crate) failover: FailoverPolicies,
+    /// Venues known to be unreachable, each until its backoff ends or an
+    /// order reaches it
+    pub(crate) down: HashMap<String, Instant>,
+    /// Set when an order's outcome was lost, to reconcile sooner
+    pub(crate) reconcile_soon: bool,
+    pub(crate) queued: VecDeque<QueuedOrder>,
+    /// Orders carry canonical instrument IDs until they reach the venue
+    pub(crate) instruments: Arc<InstrumentMap>,
+    /// Injected latency and dropped requests, for resilience tests
+    pub(crate) chaos: Option<ChaosConfig>,
+    /// Picks the cheapest of several failover venues
+    pub(crate) fees: FeeModel,
+    /// Runs every order through the pre-trade risk checks when set, whoever
+    /// sent it
+    pub(crate) risk: Option<Arc<RiskManager>>,
+    /// Submission times per venue within the last second, against the
+    /// venue's order rate limit
+    pub(crate) recent_orders: Mutex<HashMap<String, VecDeque<Instant>>>,
+    /// Failover venues in the engine's region win fee ties
+    pub(crate) regions: Regions,
+}
+
+/// Window a venue's order rate limit is measured over
+const ORDER_RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// An order held back until its venue reconnects
+pub(crate) struct QueuedOrder {
+    order: Order,
+    expires_at: Instant,
+}
+
+impl OrderGateway {
+    fn report_drain_status(&self) {
+        let Some(heartbeat) = &self.heartbeat else {
+            return;
+        };
+
+        heartbeat.beat();
+        let backlog = self.order_rx.len();
+        if backlog > MAX_ORDER_BACKLOG {
+            heartbeat.set_unhealthy(&format!("order queue backlog {}", backlog));
+        } else {
+            heartbeat.set_healthy();
+        }
+    }
+
+    fn venue(&self, name: &str) -> Option<Arc<dyn VenueAdapter>> {
+        self.venues.get(name)
+    }
+
+    fn is_down(&self, venue: &str) -> bool {
+        self.down.get(venue).is_some_and(|until| Instant::now() < *until)
+    }
+
+    fn mark_down(&mut self, venue: &str) {
+        self.down.insert(venue.to_string(), Instant::now() + VENUE_DOWN_BACKOFF);
+    }
+
+    /// `order` with its symbol as its venue calls it
+    fn for_venue(&self, order: &Order) -> Order {
+        Order {
+            symbol: self.instruments.venue_symbol(order.venue, order.symbol),
+            ..order.clone()
+        }
+    }
+
+    /// Change the price and quantity of an open order, returning the order
+    /// ID it is tracked under afterwards.
+    ///
+    /// Venues that support amends modify the order in place. Elsewhere the
+    /// order is cancelled and a replacement submitted, which gets a new ID
+    /// and loses queue position. If the venue no longer knows the order it
+    /// is dropped from tracking. The amended order goes through the
+    /// pre-trade risk checks first, like any new order.
+    async fn amend_order(&self, order_id: &str, price: f64, quantity: f64) -> Result<String, HftError> {
+        if let Some(leadership) = &self.leadership {
+            if !leadership.is_leader() {
+                return Err(ExecutionError::TradingHalted("standby instance".to_string()).into());
+            }
+        }
+
+        let open = self.orders.get(order_id).await
+            .ok_or_else(|| ExecutionError::InvalidOrder(format!("Unknown order {}", order_id)))?;
+        let venue = self.venue(&open.order.venue)
+            .ok_or_else(|| GatewayError::InvalidSymbol(format!("No venue configured for {}", open.order.venue)))?;
+
+        let mut amended = open.order.clone();
+        amended.price = price;
+        amended.quantity = quantity;
+
+        if let Some(risk) = &self.risk {
+            if let Err(e) = risk.check_order(&amended).await {
+                warn!(venue = %amended.venue, order_id = %order_id, error = %e, "Order amend refused by risk checks");
+                return Err(e);
+            }
+        }
+
+        let native = if venue.capabilities().amend {
+            venue.amend_order(order_id, &self.for_venue(&amended)).await
+        } else {
+            Err(VenueError::NotSupported("amend_order".to_string()).into())
+        };
+        let result = match native {
+            Err(HftError::Venue(VenueError::NotSupported(_))) => self.cancel_replace(&*venue, order_id, amended.clone()).await,
+            result => {
+                if result.is_ok() {
+                    ORDER_AMENDS.with_label_values(&[&amended.venue, "amend"]).inc();
+                }
+                result
+            }
+        };
+
+        let timestamp = chrono::Utc::now().timestamp_millis() as u64;
+        match result {
+            Ok(new_order_id) => {
+                debug!(venue = %amended.venue, order_id = %order_id, new_order_id = %new_order_id, "Order amended");
+                if let Some(audit) = &self.audit {
+                    audit.record(AuditEvent::Amend {
+                        order_id: new_order_id.clone(),
+                        venue: amended.venue.to_string(),
+                        price,
+                        quantity,
+                    });
+                }
+                if new_order_id != order_id {
+                    self.orders.remove(order_id).await;
+                    self.orders.insert(new_order_id.clone(), amended.clone()).await;
+                    self.emit(|| OrderEvent::StatusChanged { order_id: order_id.to_string(), status: OrderStatus::Cancelled, timestamp });
+                    self.emit(|| OrderEvent::Submitted { order_id: new_order_id.clone(), order: amended, timestamp });
+                } else {
+                    self.orders.amend(order_id, new_order_id.clone(), price, quantity).await;
+                    self.emit(|| OrderEvent::Amended { order_id: new_order_id.clone(), price, quantity, timestamp });
+                }
+                Ok(new_order_id)
+            }
+            Err(HftError::Venue(VenueError::UnknownOrder(reason))) => {
+                warn!(venue = %amended.venue, order_id = %order_id, reason = %reason, "Venue no longer knows order, dropping it");
+                self.orders.remove(order_id).await;
+                self.emit(|| OrderEvent::StatusChanged { order_id: order_id.to_string(), status: OrderStatus::Cancelled, timestamp });
+                Err(VenueError::UnknownOrder(reason).into())
+            }
+            Err(e) => {
+                error!(venue = %amended.venue, order_id = %order_id, error = ?e, "Order amend failed");
+                Err(e.with_venue(amended.venue).with_order(order_id))
+            }
+        }
+    }
+
+    /// Amend on behalf of another component, answering on its reply
+    /// channel
+    async fn amend(&self, request: AmendRequest) {
+        let result = self.amend_order(&request.order_id, request.price, request.quantity).await;
+        if let Some(reply) = request.reply {
+            // The requester may have stopped waiting
+            let _ = reply.send(result);
+        }
+    }
+
+    async fn cancel_replace(&self, venue: &dyn VenueAdapter, order_id: &str, order: Order) -> Result<String, HftError> {
+        let venue_order = self.for_venue(&order);
+        venue.cancel_order(order_id, &venue_order.symbol).await?;
+        ORDER_CANCELS.with_label_values(&[&order.venue, order.strategy_label(), "replaced"]).inc();
+        if let Some(audit) = &self.audit {
+            audit.record(AuditEvent::Cancel {
+                venue: order.venue.to_string(),
+                order_id: Some(order_id.to_string()),
+                reason: "amend".to_string(),
+            });
+        }
+
+        ORDER_AMENDS.with_label_values(&[&order.venue, "cancel_replace"]).inc();
+        match venue.submit_order(venue_order).await {
+            Ok(new_order_id) => Ok(new_order_id),
+            Err(e) => {
+                // The original is gone either way
+                self.orders.remove(order_id).await;
+                ORDER_REJECTS.with_label_values(&[&order.venue, order.strategy_label(), e.reason_label()]).inc();
+                if let Some(audit) = &self.audit {
+                    audit.record(AuditEvent::Reject { order, reason: e.to_string() });
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Send the event `event` builds to every sink, building it only when
+    /// there is one
+    fn emit(&self, event: impl FnOnce() -> OrderEvent) {
+        let Some((last, rest)) = self.sinks.split_last() else {
+            return;
+        };
+        let event = event();
+        for sink in rest {
+            sink.send(event.clone());
+        }
+        last.send(event);
+    }
+
+    /// Whether another order fits under `venue`'s order rate limit,
+    /// counting it if so
+    fn within_order_rate(&self, venue: &str, limit: Option<u32>, now: Instant) -> bool {
+        let Some(limit) = limit else {
+            return true;
+        };
+        let mut recent = self.recent_orders.lock().unwrap();
+        let sent = recent.entry(venue.to_string()).or_default();
+        while sent.front().is_some_and(|t| now.saturating_duration_since(*t) >= ORDER_RATE_WINDOW) {
+            sent.pop_front();
+        }
+        if sent.len() >= limit as usize {
+            return false;
+        }
+        sent.push_back(now);
+        true
+    }
+
+    /// `kind` is a fixed name for the cause, used as the metric label
+    fn reject(&self, order: Order, kind: &str, reason: String, code: Option<u32>) {
+        error!(venue = %order.venue, symbol = %order.symbol, error = %reason, "Order submission failed");
+        ORDER_REJECTS.with_label_values(&[&order.venue, order.strategy_label(), kind]).inc();
+        if let Some(audit) = &self.audit {
+            audit.record(AuditEvent::Reject { order: order.clone(), reason: reason.clone() });
+        }
+        self.events.publish(EngineEvent::OrderRejected {
+            venue: order.venue.to_string(),
+            symbol: order.symbol.to_string(),
+            reason: reason.clone(),
+            code,
+        });
+        self.emit(|| OrderEvent::Rejected {
+            order,
+            reason,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        });
+    }
+
+    /// Refuse an order the pre-trade risk checks did not pass. Halts are
+    /// expected while winding down, so only breaches raise a rejection
+    /// event; either way the order's sinks hear of it.
+    fn refuse(&self, order: Order, e: HftError) {
+        if !matches!(e, HftError::Execution(ExecutionError::TradingHalted(_))) {
+            self.reject(order, e.reason_label(), e.to_string(), Some(e.code()));
+            return;
+        }
+        debug!(venue = %order.venue, symbol = %order.symbol, error = %e, "Order refused while trading is halted");
+        ORDER_REJECTS.with_label_values(&[&order.venue, order.strategy_label(), e.reason_label()]).inc();
+        if let Some(audit) = &self.audit {
+            audit.record(AuditEvent::Reject { order: order.clone(), reason: e.to_string() });
+        }
+        self.emit(|| OrderEvent::Rejected {
+            order,
+            reason: e.to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        });
+    }
+
+    /// Send an order to its venue. Orders that never reached the venue,
+    /// including ones for a venue not configured, are handed back for
+    /// failover; any other outcome is final. An order sent without an
+    /// answer may be live, so it is rejected here rather than sent again,
+    /// and left for reconciliation to find.
+    async fn submit(&mut self, order: Order) -> Result<(), (Order, HftError)> {
+        let Some(venue) = self.venue(&order.venue) else {
+            let e = HftError::from(GatewayError::VenueNotFound(order.venue.to_string()));
+            return Err((order, e));
+        };
+
+        // Refused here rather than by the venue, which would count against
+        // us and may ban the key; the venue is not down
+        if !self.within_order_rate(&order.venue, venue.capabilities().max_orders_per_sec, Instant::now()) {
+            let e = HftError::from(VenueError::RateLimitExceeded).with_venue(order.venue);
+            self.reject(order, e.reason_label(), e.to_string(), Some(e.code()));
+            return Ok(());
+        }
+
+        let result = match &self.chaos {
+            Some(chaos) if !chaos.pass("order_gateway").await => {
+                Err(VenueError::ConnectionFailed("request dropped by chaos testing".to_string()).into())
+            }
+            _ => venue.submit_order(self.for_venue(&order)).await,
+        };
+        match result {
+            Ok(order_id) => {
+                debug!(venue = %order.venue, order_id = %order_id, "Order submitted");
+                ORDER_ACKS.with_label_values(&[&order.venue, order.strategy_label()]).inc();
+                ACKED_QUANTITY.with_label_values(&[&order.venue, order.strategy_label()]).inc_by(order.quantity);
+                if let Some(audit) = &self.audit {
+                    audit.record(AuditEvent::Ack { order_id: order_id.clone(), order: order.clone() });
+                }
+                self.emit(|| OrderEvent::Submitted {
+                    order_id: order_id.clone(),
+                    order: order.clone(),
+                    timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                });
+                self.orders.insert(order_id, order).await;
+                Ok(())
+            }
+            Err(e) => {
+                let e = e.with_venue(order.venue);
+                if e.was_not_sent() {
+                    return Err((order, e));
+                }
+                if e.outcome_unknown() {
+                    warn!(venue = %order.venue, symbol = %order.symbol, error = %e, "Order outcome unknown, reconciling with venue");
+                    self.reconcile_soon = true;
+                    self.reject(order, "unknown_outcome", e.to_string(), Some(e.code()));
+                    return Ok(());
+                }
+                self.reject(order, e.reason_label(), e.to_string(), Some(e.code()));
+                Ok(())
+            }
+        }
+    }
+
+    /// Apply the symbol's failover policy to an order whose venue is down,
+    /// returning the order if it should be sent elsewhere
+    fn fail_over(&mut self, mut order: Order, reason: String) -> Option<Order> {
+        let policy = self.failover.for_symbol(&order.symbol).clone();
+        VENUE_FAILOVERS.with_label_values(&[&order.venue, &policy.to_string()]).inc();
+
+        match policy {
+            VenueFailover::Reject => {
+                self.reject(order, "venue_down", reason, None);
+                None
+            }
+            VenueFailover::Queue { ttl } => {
+                info!(venue = %order.venue, symbol = %order.symbol, ttl = ?ttl, "Venue down, queueing order");
+                self.events.publish(EngineEvent::OrderQueued {
+                    venue: order.venue.to_string(),
+                    symbol: order.symbol.to_string(),
+                });
+                self.queued.push_back(QueuedOrder { order, expires_at: Instant::now() + ttl });
+                None
+            }
+            VenueFailover::Reroute { venues } => {
+                let mut available: Vec<String> = venues.iter()
+                    .filter(|venue| **venue != order.venue && !self.is_down(venue))
+                    .cloned()
+                    .collect();
+                self.regions.prefer_local(&mut available);
+                // Resting orders are assumed to make liquidity
+                let liquidity = match order.order_type {
+                    OrderType::Market => Liquidity::Taker,
+                    OrderType::Limit => Liquidity::Maker,
+                };
+                let Some(venue) = self.fees.cheapest(&available, liquidity).cloned() else {
+                    self.reject(order, "venue_down", format!("{}; failover venues {} also unavailable", reason, venues.join(", ")), None);
+                    return None;
+                };
+                info!(from = %order.venue, to = %venue, symbol = %order.symbol, "Venue down, rerouting order");
+                self.events.publish(EngineEvent::OrderRerouted {
+                    symbol: order.symbol.to_string(),
+                    from: order.venue.to_string(),
+                    to: venue.clone(),
+                });
+                order.venue = venue.into();
+                Some(order)
+            }
+        }
+    }
+
+    async fn route(&mut self, mut order: Order) {
+        if let Some(leadership) = &self.leadership {
+            if !leadership.is_leader() {
+                warn!(venue = %order.venue, symbol = %order.symbol, "Standby instance, order dropped");
+                return;
+            }
+        }
+
+        if let Some(risk) = &self.risk {
+            if let Err(e) = risk.check_order(&order).await {
+                self.refuse(order, e);
+                return;
+            }
+        }
+
+        // An order fails over at most once
+        let mut rerouted = false;
+        loop {
+            let reason = if self.is_down(order.venue.as_str()) {
+                format!("{} is disconnected", order.venue)
+            } else {
+                let venue = order.venue;
+                match self.submit(order).await {
+                    Ok(()) => {
+                        // It answered, so it is reachable whatever it said
+                        self.down.remove(venue.as_str());
+                        return;
+                    }
+                    Err((failed, e)) => {
+                        warn!(venue = %failed.venue, error = %e, code = e.code(), "Venue unavailable, marking it down");
+                        self.mark_down(failed.venue.as_str());
+                        order = failed;
+                        e.to_string()
+                    }
+                }
+            };
+
+            if rerouted {
+                self.reject(order, "venue_down", reason, None);
+                return;
+            }
+            rerouted = true;
+            match self.fail_over(order, reason) {
+                Some(next) => order = next,
+                None => return,
+            }
+        }
+    }
+
+    /// Reject queued orders whose TTL has passed
+    fn expire_queued(&mut self) {
+        let now = Instant::now();
+        let (expired, live): (VecDeque<_>, VecDeque<_>) = self.queued.drain(..).partition(|q| q.expires_at <= now);
+        self.queued = live;
+
+        for QueuedOrder { order, .. } in expired {
+            self.events.publish(EngineEvent::QueuedOrderExpired {
+                venue: order.venue.to_string(),
+                symbol: order.symbol.to_string(),
+            });
+            let reason = format!("{} did not reconnect before the order expired", order.venue);
+            self.reject(order, "queue_expired", reason, None);
+        }
+    }
+
+    async fn on_venue_event(&mut self, event: EngineEvent) {
+        match event {
+            EngineEvent::VenueDisconnected { venue, .. } => {
+                self.mark_down(&venue);
+            }
+            EngineEvent::VenueConnected { venue } => {
+                self.down.remove(&venue);
+                self.expire_queued();
+                let (ready, waiting): (VecDeque<_>, VecDeque<_>) = self.queued.drain(..).partition(|q| q.order.venue == venue);
+                self.queued = waiting;
+                if !ready.is_empty() {
+                    info!(venue = %venue, orders = ready.len(), "Venue reconnected, sending queued orders");
+                }
+                for QueuedOrder { order, .. } in ready {
+                    self.route(order).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Cancel resting orders whose `expire_after` has elapsed
+    async fn cancel_expired(&self) {
+        let expired = self.orders.expired(Instant::now()).await;
+        if expired.is_empty() {
+            return;
+        }
+        if self.leadership.as_ref().is_some_and(|l| !l.is_leader()) {
+            return;
+        }
+
+        for open in expired {
+            let Some(venue) = self.venue(&open.order.venue) else {
+                continue;
+            };
+
+            let symbol = self.instruments.venue_symbol(open.order.venue, open.order.symbol);
+            match venue.cancel_order(&open.order_id, &symbol).await {
+                Ok(()) | Err(HftError::Venue(VenueError::UnknownOrder(_))) => {
+                    info!(venue = %open.order.venue, order_id = %open.order_id, "Resting order expired, cancelled");
+                    ORDERS_EXPIRED.with_label_values(&[&open.order.venue]).inc();
+                    ORDER_CANCELS.with_label_values(&[&open.order.venue, open.order.strategy_label(), "expired"]).inc();
+                    if let Some(audit) = &self.audit {
+                        audit.record(AuditEvent::Cancel {
+                            venue: open.order.venue.to_string(),
+                            order_id: Some(open.order_id.clone()),
+                            reason: "expired".to_string(),
+                        });
+                    }
+                    self.emit(|| OrderEvent::StatusChanged {
+                        order_id: open.order_id.clone(),
+                        status: OrderStatus::Cancelled,
+                        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                    });
+                    self.orders.update_status(&open.order_id, OrderStatus::Cancelled).await;
+                }
+                Err(e @ HftError::Venue(VenueError::NotSupported(_))) => {
+                    warn!(venue = %open.order.venue, order_id = %open.order_id, error = %e, "Venue cannot cancel single orders, expiry ignored");
+                }
+                Err(e) if e.is_retryable() => {
+                    warn!(venue = %open.order.venue, order_id = %open.order_id, error = %e, "Failed to cancel expired order, retrying");
+                    self.orders.retry_expiry(open.order_id, EXPIRY_RETRY_DELAY);
+                }
+                Err(e) => {
+                    error!(venue = %open.order.venue, order_id = %open.order_id, error = %e, code = e.code(), "Venue refused to cancel expired order, expiry abandoned");
+                }
+            }
+        }
+    }
+
+    /// Send a cancel on behalf of another component. Only the leader
+    /// cancels; a standby's view of the venues' orders is not its own.
+    async fn cancel(&self, request: CancelRequest) {
+        if self.leadership.as_ref().is_some_and(|l| !l.is_leader()) {
+            warn!(reason = %request.reason, "Standby instance, cancel dropped");
+            return;
+        }
+
+        match request.target {
+            CancelTarget::Order(order_id) => {
+                let Some(open) = self.orders.get(&order_id).await else {
+                    debug!(order_id = %order_id, "Cancel for an order no longer tracked");
+                    return;
+                };
+                let Some(venue) = self.venue(&open.order.venue) else {
+                    warn!(venue = %open.order.venue, order_id = %order_id, "No venue configured for cancel");
+                    return;
+                };
+                let symbol = self.instruments.venue_symbol(open.order.venue, open.order.symbol);
+                match venue.cancel_order(&order_id, &symbol).await {
+                    Ok(()) | Err(HftError::Venue(VenueError::UnknownOrder(_))) => {
+                        info!(venue = %open.order.venue, order_id = %order_id, reason = %request.reason, "Order cancelled");
+                        if let Some(audit) = &self.audit {
+                            audit.record(AuditEvent::Cancel {
+                                venue: open.order.venue.to_string(),
+                                order_id: Some(order_id),
+                                reason: request.reason,
+                            });
+                        }
+                        self.cancelled(&open, request.kind).await;
+                    }
+                    Err(e) => {
+                        error!(venue = %open.order.venue, order_id = %order_id, error = %e, code = e.code(), "Failed to cancel order");
+                    }
+                }
+            }
+            CancelTarget::All(venue) => {
+                let venues = match &venue {
+                    Some(name) => self.venue(name).into_iter().collect(),
+                    None => self.venues.all(),
+                };
+                for venue in venues {
+                    let name = venue.name().await;
+                    if let Some(audit) = &self.audit {
+                        audit.record(AuditEvent::Cancel { venue: name.clone(), order_id: None, reason: request.reason.clone() });
+                    }
+                    if let Err(e) = venue.cancel_all_orders().await {
+                        error!(venue = %name, error = %e, code = e.code(), reason = %request.reason, "Failed to cancel all orders");
+                        continue;
+                    }
+                    info!(venue = %name, reason = %request.reason, "All orders cancelled");
+                    for open in self.orders.open_orders().await.into_iter().filter(|open| open.order.venue == name.as_str()) {
+                        self.cancelled(&open, request.kind).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Stop tracking `open` once its venue has cancelled it
+    async fn cancelled(&self, open: &OpenOrder, kind: &str) {
+        ORDER_CANCELS.with_label_values(&[&open.order.venue, open.order.strategy_label(), kind]).inc();
+        self.emit(|| OrderEvent::StatusChanged {
+            order_id: open.order_id.clone(),
+            status: OrderStatus::Cancelled,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        });
+        self.orders.update_status(&open.order_id, OrderStatus::Cancelled).await;
+    }
+
+    /// Correct tracked orders against each venue's own list of open orders,
+    /// catching fills and cancels the engine never heard about
+    async fn reconcile(&self) {
+        if self.leadership.as_ref().is_some_and(|l| !l.is_leader()) {
+            return;
+        }
+
+        for venue in self.venues.all() {
+            let name = venue.name().await;
+            if self.is_down(&name) {
+                continue;
+            }
+            let open = match venue.open_orders().await {
+                Ok(open) => open,
+                Err(HftError::Venue(VenueError::NotSupported(_))) => continue,
+                Err(e) => {
+                    warn!(venue = %name, error = %e, "Failed to fetch open orders for reconciliation");
+                    continue;
+                }
+            };
+
+            let (dropped, untracked) = self.orders.reconcile(&name, &open).await;
+            for gone in &dropped {
+                warn!(venue = %name, order_id = %gone.order_id, "Tracked order no longer open on venue, dropping it");
+            }
+            if !untracked.is_empty() {
+                warn!(venue = %name, orders = ?untracked, "Venue has open orders the engine is not tracking");
+            }
+            ORDER_RECONCILE_DRIFT.with_label_values(&[&name, "missing"]).inc_by(dropped.len() as f64);
+            ORDER_RECONCILE_DRIFT.with_label_values(&[&name, "untracked"]).inc_by(untracked.len() as f64);
+        }
+    }
+
+    /// Route queued orders to the venue named on each order, applying the
+    /// failover policy when that venue is down
+    pub async fn run(&mut self) {
+        let mut venue_events = self.events.subscribe();
+        let mut expiry_check = tokio::time::interval(EXPIRY_CHECK_INTERVAL);
+        let mut reconcile = tokio::time::interval(RECONCILE_INTERVAL);
+        // The first tick is immediate; nothing has been placed yet
+        reconcile.tick().await;
+        loop {
+            self.report_drain_status();
+            self.expire_queued();
+
+            let next = tokio::select! {
+                biased;
+                Some(request) = recv_cancel(&mut self.cancel_rx) => Next::Cancel(request),
+                Some(request) = recv_amend(&mut self.amend_rx) => Next::Amend(request),
+                order = tokio::time::timeout(IDLE_HEARTBEAT_INTERVAL, self.order_rx.recv()) => match order {
+                    Ok(Some(order)) => Next::Order(order),
+                    Ok(None) => break,
+                    Err(_) => continue,
+                },
+                event = venue_events.recv() => match event {
+                    Ok(event) => Next::Event(event),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!(missed, "Order gateway missed venue events");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = expiry_check.tick() => Next::ExpiryCheck,
+                _ = reconcile.tick() => Next::Reconcile,
+            };
+
+            match next {
+                Next::Order(order) => self.route(order).await,
+                Next::Cancel(request) => self.cancel(request).await,
+                Next::Amend(request) => self.amend(request).await,
+                Next::Event(event) => self.on_venue_event(event).await,
+                Next::ExpiryCheck => self.cancel_expired().await,
+                Next::Reconcile => self.reconcile().await,
+            }
+            if std::mem::take(&mut self.reconcile_soon) {
+                reconcile.reset_after(UNKNOWN_OUTCOME_RECONCILE_DELAY);
+            }
+        }
+    }
+}
+
+/// The next cancel, or never when there is no cancel channel
+async fn recv_cancel(cancel_rx: &mut Option<mpsc::Receiver<CancelRequest>>) -> Option<CancelRequest> {
+    match cancel_rx {
+        Some(cancel_rx) => cancel_rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// The next amend, or never when there is no amend channel
+async fn recv_amend(amend_rx: &mut Option<mpsc::Receiver<AmendRequest>>) -> Option<AmendRequest> {
+    match amend_rx {
+        Some(amend_rx) => amend_rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+enum Next {
+    Order(Order),
+    Cancel(CancelRequest),
+    Amend(AmendRequest),
+    Event(EngineEvent),
+    ExpiryCheck,
+    Reconcile,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::mock_venue::{MockVenue, MockVenueConfig};
+    use crate::metrics::ACTIVE_ORDERS;
+    use crate::types::OrderSide;
+    use crate::fees::{FeeSchedule, FeeSchedules, Fees};
+
+    fn mock_venue(name: &str) -> Arc<MockVenue> {
+        Arc::new(MockVenue::new(name, MockVenueConfig {
+            error_probability: 0.0,
+            latency_ms: 2,
+            ..MockVenueConfig::default()
+        }))
+    }
+
+    async fn gateway(venues: Vec<Arc<MockVenue>>) -> OrderGateway {
+        let (_, order_rx) = mpsc::channel(1);
+        OrderGateway {
+            venues: VenueRegistry::from_venues(venues.into_iter().map(|v| v as Arc<dyn VenueAdapter>).collect()).await,
+            order_rx,
+            cancel_rx: None,
+            amend_rx: None,
+            events: EventBus::default(),
+            heartbeat: None,
+            orders: Arc::new(OrderTracker::new()),
+            sinks: Vec::new(),
+            leadership: None,
+            audit: None,
+            failover: FailoverPolicies::default(),
+            down: HashMap::new(),
+            reconcile_soon: false,
+            queued: VecDeque::new(),
+            instruments: Arc::new(InstrumentMap::new()),
+            chaos: None,
+            fees: FeeModel::default(),
+            risk: None,
+            recent_orders: Default::default(),
+            regions: Regions::default(),
+        }
+    }
+
+    fn order(symbol: &str, venue: &str) -> Order {
+        Order {
+            symbol: symbol.into(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price: 50000.0,
+            venue: venue.into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_amend_falls_back_to_cancel_replace() {
+        let venue = mock_venue("MOCK");
+        let gateway = gateway(vec![venue.clone()]).await;
+
+        let order = order("BTCUSDT", "MOCK");
+        let order_id = venue.submit_order(order.clone()).await.unwrap();
+        gateway.orders.insert(order_id.clone(), order.clone()).await;
+
+        // The mock venue has no native amend, so the order is replaced
+        let new_order_id = gateway.amend_order(&order_id, 49990.0, 2.0).await.unwrap();
+        assert_ne!(new_order_id, order_id);
+        assert!(gateway.orders.get(&order_id).await.is_none());
+        let replaced = gateway.orders.get(&new_order_id).await.unwrap();
+        assert_eq!(replaced.order.price, 49990.0);
+        assert_eq!(replaced.order.quantity, 2.0);
+        assert_eq!(venue.open_orders().await.unwrap(), vec![new_order_id]);
+
+        // An order the venue no longer knows is dropped from tracking
+        gateway.orders.insert("gone".to_string(), order).await;
+        let result = gateway.amend_order("gone", 49980.0, 1.0).await;
+        assert!(matches!(result, Err(HftError::Venue(VenueError::UnknownOrder(_)))));
+        assert!(gateway.orders.get("gone").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_order_rate_limit_per_venue() {
+        let gateway = gateway(vec![]).await;
+        let start = Instant::now();
+        assert!(gateway.within_order_rate("MOCK", Some(2), start));
+        assert!(gateway.within_order_rate("MOCK", Some(2), start));
+        assert!(!gateway.within_order_rate("MOCK", Some(2), start + Duration::from_millis(500)));
+        assert!(gateway.within_order_rate("OTHER", Some(2), start));
+        assert!(gateway.within_order_rate("UNLIMITED", None, start));
+        assert!(gateway.within_order_rate("MOCK", Some(2), start + Duration::from_secs(1)));
+    }
+
+    #[tokio::test]
+    async fn test_failover_policies() {
+        let primary = mock_venue("PRIMARY");
+        let secondary = mock_venue("SECONDARY");
+        let mut gateway = gateway(vec![primary.clone(), secondary.clone()]).await;
+        gateway.failover.symbols.insert("BTCUSDT".to_string(), VenueFailover::Reroute { venues: vec!["SECONDARY".to_string()] });
+        gateway.failover.symbols.insert("ETHUSDT".to_string(), VenueFailover::Queue { ttl: Duration::from_secs(60) });
+        let mut events = gateway.events.subscribe();
+
+        gateway.on_venue_event(EngineEvent::VenueDisconnected { venue: "PRIMARY".to_string(), reason: "test".to_string() }).await;
+
+        gateway.route(order("BTCUSDT", "PRIMARY")).await;
+        assert_eq!(secondary.submitted_orders().await.len(), 1);
+        assert!(matches!(events.try_recv(), Ok(EngineEvent::OrderRerouted { .. })));
+
+        gateway.route(order("ETHUSDT", "PRIMARY")).await;
+        assert_eq!(gateway.queued.len(), 1);
+        assert!(matches!(events.try_recv(), Ok(EngineEvent::OrderQueued { .. })));
+
+        // Symbols without a policy are rejected
+        gateway.route(order("SOLUSDT", "PRIMARY")).await;
+        assert!(matches!(events.try_recv(), Ok(EngineEvent::OrderRejected { .. })));
+
+        gateway.on_venue_event(EngineEvent::VenueConnected { venue: "PRIMARY".to_string() }).await;
+        assert!(gateway.queued.is_empty());
+        let sent = primary.submitted_orders().await;
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].symbol, "ETHUSDT");
+
+        // A queued order whose venue stays down past its TTL is rejected
+        gateway.failover.symbols.insert("ETHUSDT".to_string(), VenueFailover::Queue { ttl: Duration::ZERO });
+        gateway.mark_down("PRIMARY");
+        gateway.route(order("ETHUSDT", "PRIMARY")).await;
+        gateway.expire_queued();
+        assert!(gateway.queued.is_empty());
+        let _ = events.try_recv();
+        assert!(matches!(events.try_recv(), Ok(EngineEvent::QueuedOrderExpired { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_orders_for_unknown_venues_fail_over() {
+        let secondary = mock_venue("SECONDARY");
+        let mut gateway = gateway(vec![secondary.clone()]).await;
+        gateway.failover.symbols.insert("BTCUSDT".to_string(), VenueFailover::Reroute { venues: vec!["SECONDARY".to_string()] });
+        let mut events = gateway.events.subscribe();
+
+        let result = gateway.submit(order("ETHUSDT", "NOWHERE")).await;
+        assert!(matches!(result, Err((_, HftError::Gateway(GatewayError::VenueNotFound(_))))));
+
+        // Rejected without a failover policy, rerouted with one
+        gateway.route(order("ETHUSDT", "NOWHERE")).await;
+        match events.try_recv() {
+            Ok(EngineEvent::OrderRejected { venue, reason, .. }) => {
+                assert_eq!(venue, "NOWHERE");
+                assert!(reason.contains("Venue not found"), "{}", reason);
+            }
+            event => panic!("expected a rejection, got {:?}", event),
+        }
+        gateway.route(order("BTCUSDT", "NOWHERE")).await;
+        assert_eq!(secondary.submitted_orders().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_execution_quality_metrics() {
+        let venue = mock_venue("QUALITY");
+        let mut gateway = gateway(vec![venue.clone()]).await;
+        let order = Order { strategy: Some("quality".to_string()), ..order("BTCUSDT", "QUALITY") };
+        let acks = ORDER_ACKS.with_label_values(&["QUALITY", "quality"]);
+        let rejects = |reason| ORDER_REJECTS.with_label_values(&["QUALITY", "quality", reason]).get();
+
+        gateway.route(order.clone()).await;
+        assert_eq!(acks.get(), 1.0);
+        assert_eq!(ACKED_QUANTITY.with_label_values(&["QUALITY", "quality"]).get(), 1.0);
+
+        venue.set_order_response("BTCUSDT", OrderSide::Buy, Err(VenueError::OrderSubmissionFailed("margin".to_string()).into())).await;
+        gateway.route(order.clone()).await;
+        assert_eq!(rejects("venue_rejected"), 1.0);
+
+        gateway.mark_down("QUALITY");
+        gateway.route(order).await;
+        assert_eq!(rejects("venue_down"), 1.0);
+        assert_eq!(acks.get(), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_corrects_active_orders() {
+        let venue = mock_venue("RECONCILE");
+        let gateway = gateway(vec![venue.clone()]).await;
+        let active = ACTIVE_ORDERS.with_label_values(&["RECONCILE"]);
+
+        for _ in 0..3 {
+            let order = order("BTCUSDT", "RECONCILE");
+            let order_id = venue.submit_order(order.clone()).await.unwrap();
+            gateway.orders.insert(order_id, order).await;
+        }
+        assert_eq!(active.get(), 3.0);
+
+        let open = venue.open_orders().await.unwrap();
+        gateway.orders.update_status(&open[0], OrderStatus::Filled).await;
+        assert_eq!(active.get(), 2.0);
+
+        // The venue cancels one behind the engine's back
+        venue.cancel_order(&open[1], "BTCUSDT").await.unwrap();
+        gateway.reconcile().await;
+        assert!(gateway.orders.get(&open[1]).await.is_none());
+        assert!(gateway.orders.get(&open[2]).await.is_some());
+        // The mock never saw the fill, so the venue's count wins and the
+        // filled order shows up as untracked
+        assert_eq!(active.get(), 2.0);
+        assert_eq!(ORDER_RECONCILE_DRIFT.with_label_values(&["RECONCILE", "untracked"]).get(), 1.0);
+        assert_eq!(ORDER_RECONCILE_DRIFT.with_label_values(&["RECONCILE", "missing"]).get(), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_chaos_drops_take_failover_path() {
+        let primary = mock_venue("PRIMARY");
+        let secondary = mock_venue("SECONDARY");
+        let mut gateway = gateway(vec![primary.clone(), secondary.clone()]).await;
+        gateway.failover.symbols.insert("BTCUSDT".to_string(), VenueFailover::Reroute { venues: vec!["SECONDARY".to_string()] });
+        gateway.chaos = Some(ChaosConfig { drop_probability: 1.0, ..ChaosConfig::default() });
+        let mut events = gateway.events.subscribe();
+
+        // Every request is dropped, so the reroute fails too
+        gateway.route(order("BTCUSDT", "PRIMARY")).await;
+        assert!(primary.submitted_orders().await.is_empty());
+        assert!(secondary.submitted_orders().await.is_empty());
+        assert!(gateway.is_down("PRIMARY"));
+        assert!(gateway.is_down("SECONDARY"));
+        let mut rejected = false;
+        while let Ok(event) = events.try_recv() {
+            rejected |= matches!(event, EngineEvent::OrderRejected { .. });
+        }
+        assert!(rejected);
+    }
+
+    #[tokio::test]
+    async fn test_only_unsent_orders_fail_over() {
+        let primary = mock_venue("PRIMARY");
+        let secondary = mock_venue("SECONDARY");
+        let mut gateway = gateway(vec![primary.clone(), secondary.clone()]).await;
+        gateway.failover.symbols.insert("BTCUSDT".to_string(), VenueFailover::Reroute { venues: vec!["SECONDARY".to_string()] });
+        let mut events = gateway.events.subscribe();
+
+        // Sent without an answer, the order may be live on the venue
+        primary.set_order_response("BTCUSDT", OrderSide::Buy, Err(VenueError::NoResponse("timed out".to_string()).into())).await;
+        gateway.route(order("BTCUSDT", "PRIMARY")).await;
+        assert!(secondary.submitted_orders().await.is_empty());
+        assert!(!gateway.is_down("PRIMARY"));
+        assert!(std::mem::take(&mut gateway.reconcile_soon));
+        assert!(matches!(events.try_recv(), Ok(EngineEvent::OrderRejected { .. })));
+
+        // A venue rate limiting us is up and the order was refused
+        primary.set_order_response("BTCUSDT", OrderSide::Buy, Err(VenueError::RateLimitExceeded.into())).await;
+        gateway.route(order("BTCUSDT", "PRIMARY")).await;
+        assert!(secondary.submitted_orders().await.is_empty());
+        assert!(!gateway.is_down("PRIMARY"));
+        assert!(!gateway.reconcile_soon);
+
+        // Refused connections are the only failures rerouted
+        primary.set_order_response("BTCUSDT", OrderSide::Buy, Err(VenueError::ConnectionFailed("refused".to_string()).into())).await;
+        gateway.route(order("BTCUSDT", "PRIMARY")).await;
+        assert_eq!(secondary.submitted_orders().await.len(), 1);
+        assert!(gateway.is_down("PRIMARY"));
+
+        // Once the backoff has passed the next order tries the venue again,
+        // and reaching it clears the mark
+        gateway.down.insert("PRIMARY".to_string(), Instant::now());
+        assert!(!gateway.is_down("PRIMARY"));
+        gateway.route(order("ETHUSDT", "PRIMARY")).await;
+        assert_eq!(primary.submitted_orders().await.len(), 1);
+        assert!(!gateway.down.contains_key("PRIMARY"));
+    }
+
+    #[tokio::test]
+    async fn test_orders_reach_venue_under_venue_symbol() {
+        let primary = mock_venue("PRIMARY");
+        let secondary = mock_venue("SECONDARY");
+        let mut gateway = gateway(vec![primary.clone(), secondary.clone()]).await;
+        let mut instruments = InstrumentMap::new();
+        instruments.insert("BTC-USD", "PRIMARY", "BTCUSDT");
+        instruments.insert("BTC-USD", "SECONDARY", "BTC-USDT");
+        gateway.instruments = Arc::new(instruments);
+        gateway.failover.default = VenueFailover::Reroute { venues: vec!["SECONDARY".to_string()] };
+
+        gateway.route(order("BTC-USD", "PRIMARY")).await;
+        assert_eq!(primary.submitted_orders().await[0].symbol, "BTCUSDT");
+        // Tracked under the canonical ID
+        let tracked = gateway.orders.open_orders().await;
+        assert_eq!(tracked[0].order.symbol, "BTC-USD");
+
+        // A rerouted order takes the new venue's symbol
+        gateway.mark_down("PRIMARY");
+        gateway.route(order("BTC-USD", "PRIMARY")).await;
+        assert_eq!(secondary.submitted_orders().await[0].symbol, "BTC-USDT");
+    }
+
+    #[tokio::test]
+    async fn test_reroute_picks_cheapest_venue() {
+        let (primary, dear, cheap) = (mock_venue("PRIMARY"), mock_venue("DEAR"), mock_venue("CHEAP"));
+        let mut gateway = gateway(vec![primary.clone(), dear.clone(), cheap.clone()]).await;
+        gateway.failover.default = VenueFailover::Reroute { venues: vec!["DEAR".to_string(), "CHEAP".to_string()] };
+        gateway.fees.configure(FeeSchedules::new()
+            .with_venue("DEAR", FeeSchedule::new(Fees { maker_bps: 2.0, taker_bps: 5.0 }))
+            .with_venue("CHEAP", FeeSchedule::new(Fees { maker_bps: 1.0, taker_bps: 4.0 })));
+        gateway.mark_down("PRIMARY");
+
+        gateway.route(order("BTCUSDT", "PRIMARY")).await;
+        assert_eq!(cheap.submitted_orders().await.len(), 1);
+
+        // The cheapest venue being down leaves the next one
+        gateway.mark_down("CHEAP");
+        gateway.route(order("BTCUSDT", "PRIMARY")).await;
+        assert_eq!(dear.submitted_orders().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reroute_prefers_local_region_on_fee_tie() {
+        let (primary, tokyo, london) = (mock_venue("PRIMARY"), mock_venue("TOKYO"), mock_venue("LONDON"));
+        let mut gateway = gateway(vec![primary.clone(), tokyo.clone(), london.clone()]).await;
+        gateway.failover.default = VenueFailover::Reroute { venues: vec!["TOKYO".to_string(), "LONDON".to_string()] };
+        gateway.regions = Regions::default()
+            .with_local("eu-west-2")
+            .with_venue("TOKYO", "ap-northeast-1")
+            .with_venue("LONDON", "eu-west-2");
+        gateway.mark_down("PRIMARY");
+
+        gateway.route(order("BTCUSDT", "PRIMARY")).await;
+        assert_eq!(london.submitted_orders().await.len(), 1);
+        assert!(tokyo.submitted_orders().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_every_order_is_risk_checked() {
+        use crate::command::mode::TradingMode;
+        use crate::risk::{ExposureLimit, ExposureLimits, LossLimits};
+
+        let venue = mock_venue("MOCK");
+        let mut gateway = gateway(vec![venue.clone()]).await;
+        let risk = Arc::new(RiskManager::new(LossLimits::default())
+            .with_exposure_limits(ExposureLimits {
+                assets: HashMap::from([("BTC".to_string(), ExposureLimit { max_gross: None, max_net: Some(75_000.0) })]),
+                ..Default::default()
+            }));
+        risk.trading_state().transition(TradingMode::Active, "test").unwrap();
+        gateway.risk = Some(Arc::clone(&risk));
+        let mut events = gateway.events.subscribe();
+
+        gateway.route(order("BTCUSDT", "MOCK")).await;
+        assert_eq!(venue.submitted_orders().await.len(), 1);
+
+        // Twice the size breaches the net limit, so never reaches the venue
+        gateway.route(Order { quantity: 2.0, ..order("BTCUSDT", "MOCK") }).await;
+        assert_eq!(venue.submitted_orders().await.len(), 1);
+        assert!(matches!(events.try_recv(), Ok(EngineEvent::OrderRejected { .. })));
+
+        // Nor does anything while the kill switch is engaged
+        risk.kill_switch().engage("test").await;
+        gateway.route(order("BTCUSDT", "MOCK")).await;
+        assert_eq!(venue.submitted_orders().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancels_are_tracked_and_leader_only() {
+        use crate::failover::{LeaderLock, LocalLock};
+
+        let venue = mock_venue("MOCK");
+        let mut gateway = gateway(vec![venue.clone()]).await;
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let order_id = venue.submit_order(order("BTCUSDT", "MOCK")).await.unwrap();
+            gateway.orders.insert(order_id.clone(), order("BTCUSDT", "MOCK")).await;
+            ids.push(order_id);
+        }
+
+        // A standby leaves the orders alone
+        let lock: Arc<dyn LeaderLock> = Arc::new(LocalLock::new());
+        gateway.leadership = Some(Leadership::new("standby", lock, Duration::from_secs(30)));
+        gateway.cancel(CancelRequest::all(None, "loss_limit", "daily loss")).await;
+        assert_eq!((venue.cancel_all_count().await, gateway.orders.len().await), (0, 3));
+
+        gateway.leadership = None;
+        gateway.cancel(CancelRequest::order(ids[0].clone(), "bracket", "sibling filled")).await;
+        assert!(gateway.orders.get(&ids[0]).await.is_none());
+        assert_eq!(venue.open_orders().await.unwrap().len(), 2);
+
+        gateway.cancel(CancelRequest::all(Some("MOCK".to_string()), "loss_limit", "daily loss")).await;
+        assert_eq!(venue.cancel_all_count().await, 1);
+        assert!(gateway.orders.is_empty().await);
+    }
+}
@@ -0,0 +1,332 @@
+//! Live market data quality checks.
+//!
+//! The quote gateway passes every quote it receives through a
+//! [`DataQualityMonitor`], which flags crossed books, mid prices that jump
+//! further than the recent volatility allows, timestamps a venue repeats
+//! and symbols a venue stops quoting. Each issue is counted, published as
+//! an [`EngineEvent::DataQuality`] and lowers the venue's quality score.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::events::{EngineEvent, EventBus};
+use crate::metrics::{DATA_QUALITY_ISSUES, DATA_QUALITY_SCORE};
+use crate::types::{Quote, Symbol, VenueId};
+
+/// How often symbols are checked for silence
+const SILENCE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Thresholds of the data quality checks
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataQualityConfig {
+    /// Mid price moves larger than this many standard deviations of the
+    /// recent moves are jumps
+    pub jump_sigma: f64,
+    /// Number of recent mid price moves the deviation is taken over, and
+    /// the number of quotes the score is averaged over
+    pub window: usize,
+    /// Moves needed before jumps are flagged
+    pub min_samples: usize,
+    /// A symbol a venue has not quoted for this long is silent
+    pub silence: Duration,
+}
+
+impl Default for DataQualityConfig {
+    fn default() -> Self {
+        Self {
+            jump_sigma: 6.0,
+            window: 100,
+            min_samples: 20,
+            silence: Duration::from_secs(5),
+        }
+    }
+}
+
+impl DataQualityConfig {
+    /// Read `HFT_DATA_QUALITY=1`, with `HFT_DATA_QUALITY_JUMP_SIGMA`,
+    /// `HFT_DATA_QUALITY_WINDOW`, `HFT_DATA_QUALITY_MIN_SAMPLES` and
+    /// `HFT_DATA_QUALITY_SILENCE_SECS` overriding the defaults; returns
+    /// `None` when monitoring is off
+    pub fn from_env() -> Option<Self> {
+        if !std::env::var("HFT_DATA_QUALITY").is_ok_and(|v| v == "1" || v == "true") {
+            return None;
+        }
+
+        fn var<T: std::str::FromStr>(key: &str) -> Option<T> {
+            let value = std::env::var(key).ok()?;
+            let parsed = value.parse().ok();
+            if parsed.is_none() {
+                warn!(key = key, value = %value, "Ignoring malformed data quality setting");
+            }
+            parsed
+        }
+        let mut config = Self::default();
+        config.jump_sigma = var("HFT_DATA_QUALITY_JUMP_SIGMA").unwrap_or(config.jump_sigma);
+        config.window = var("HFT_DATA_QUALITY_WINDOW").unwrap_or(config.window).max(1);
+        config.min_samples = var("HFT_DATA_QUALITY_MIN_SAMPLES").unwrap_or(config.min_samples);
+        if let Some(secs) = var::<f64>("HFT_DATA_QUALITY_SILENCE_SECS").filter(|secs| *secs > 0.0) {
+            config.silence = Duration::from_secs_f64(secs);
+        }
+        Some(config)
+    }
+}
+
+/// A problem with a venue's market data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataIssue {
+    /// The ask was below the bid
+    NegativeSpread,
+    /// The mid moved further than the recent volatility allows
+    PriceJump,
+    /// The quote carried the same timestamp as the previous one
+    DuplicateTimestamp,
+    /// No quote arrived for longer than the silence threshold
+    Silence,
+}
+
+impl DataIssue {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataIssue::NegativeSpread => "negative_spread",
+            DataIssue::PriceJump => "price_jump",
+            DataIssue::DuplicateTimestamp => "duplicate_timestamp",
+            DataIssue::Silence => "silence",
+        }
+    }
+}
+
+impl fmt::Display for DataIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What is known about one symbol on one venue
+#[derive(Debug)]
+struct Series {
+    mid: Option<f64>,
+    timestamp: Option<u64>,
+    /// Recent log returns of the mid, without the ones flagged as jumps
+    returns: VecDeque<f64>,
+    last_seen: Instant,
+    silent: bool,
+}
+
+impl Series {
+    fn new(now: Instant) -> Self {
+        Self { mid: None, timestamp: None, returns: VecDeque::new(), last_seen: now, silent: false }
+    }
+
+    fn deviation(&self) -> f64 {
+        let n = self.returns.len() as f64;
+        let mean = self.returns.iter().sum::<f64>() / n;
+        (self.returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n).sqrt()
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    series: HashMap<(VenueId, Symbol), Series>,
+    /// Moving share of clean quotes per venue
+    scores: HashMap<String, f64>,
+}
+
+/// Checks quotes as they arrive and scores each venue's data
+#[derive(Debug)]
+pub struct DataQualityMonitor {
+    config: DataQualityConfig,
+    events: EventBus,
+    state: Mutex<State>,
+}
+
+impl DataQualityMonitor {
+    pub fn new(config: DataQualityConfig, events: EventBus) -> Self {
+        Self { config, events, state: Mutex::new(State::default()) }
+    }
+
+    /// Check `quote`, returning the issues found with it
+    pub fn on_quote(&self, quote: &Quote) -> Vec<DataIssue> {
+        self.check(quote, Instant::now())
+    }
+
+    fn check(&self, quote: &Quote, now: Instant) -> Vec<DataIssue> {
+        let mut issues = Vec::new();
+        let mut state = self.state.lock().unwrap();
+        let series = state.series
+            .entry((quote.venue, quote.symbol))
+            .or_insert_with(|| Series::new(now));
+        series.last_seen = now;
+        series.silent = false;
+
+        if quote.timestamp != 0 && series.timestamp == Some(quote.timestamp) {
+            issues.push(DataIssue::DuplicateTimestamp);
+        }
+        series.timestamp = Some(quote.timestamp);
+
+        if quote.bid > 0.0 && quote.ask > 0.0 && quote.ask < quote.bid {
+            issues.push(DataIssue::NegativeSpread);
+        } else if quote.bid > 0.0 && quote.ask > 0.0 {
+            let mid = (quote.bid + quote.ask) / 2.0;
+            if let Some(last) = series.mid.replace(mid) {
+                let change = (mid / last).ln();
+                let jumped = series.returns.len() >= self.config.min_samples.max(2)
+                    && change.abs() > self.config.jump_sigma * series.deviation();
+                if jumped {
+                    issues.push(DataIssue::PriceJump);
+                } else {
+                    if series.returns.len() == self.config.window {
+                        series.returns.pop_front();
+                    }
+                    series.returns.push_back(change);
+                }
+            }
+        }
+
+        self.rescore(&mut state, &quote.venue, issues.is_empty());
+        drop(state);
+        for issue in &issues {
+            self.report(quote, *issue);
+        }
+        issues
+    }
+
+    /// Flag the symbols no quote has arrived for since the silence
+    /// threshold, once per silence; returns them as (venue, symbol)
+    pub fn check_silence(&self) -> Vec<(String, String)> {
+        self.silent_at(Instant::now())
+    }
+
+    fn silent_at(&self, now: Instant) -> Vec<(String, String)> {
+        let mut state = self.state.lock().unwrap();
+        let mut silent = Vec::new();
+        for ((venue, symbol), series) in state.series.iter_mut() {
+            if !series.silent && now.duration_since(series.last_seen) >= self.config.silence {
+                series.silent = true;
+                silent.push((venue.to_string(), symbol.to_string()));
+            }
+        }
+        silent.sort();
+        for (venue, _) in &silent {
+            self.rescore(&mut state, venue, false);
+        }
+        drop(state);
+
+        for (venue, symbol) in &silent {
+            DATA_QUALITY_ISSUES.with_label_values(&[venue, DataIssue::Silence.as_str()]).inc();
+            warn!(venue = %venue, symbol = %symbol, silence = ?self.config.silence, "Market data silent");
+            self.events.publish(EngineEvent::DataQuality {
+                venue: venue.clone(),
+                symbol: symbol.clone(),
+                issue: DataIssue::Silence,
+                detail: format!("no quote for {:?}", self.config.silence),
+            });
+        }
+        silent
+    }
+
+    /// The venue's quality score: the moving share of its quotes without
+    /// issues, from 0 to 1
+    pub fn score(&self, venue: &str) -> Option<f64> {
+        self.state.lock().unwrap().scores.get(venue).copied()
+    }
+
+    /// Check for silent symbols every second
+    pub async fn run(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(SILENCE_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.check_silence();
+        }
+    }
+
+    fn rescore(&self, state: &mut State, venue: &str, clean: bool) {
+        let alpha = 1.0 / self.config.window.max(1) as f64;
+        let score = state.scores.entry(venue.to_string()).or_insert(1.0);
+        *score += alpha * (if clean { 1.0 } else { 0.0 } - *score);
+        DATA_QUALITY_SCORE.with_label_values(&[venue]).set(*score);
+    }
+
+    fn report(&self, quote: &Quote, issue: DataIssue) {
+        DATA_QUALITY_ISSUES.with_label_values(&[&quote.venue, issue.as_str()]).inc();
+        let detail = match issue {
+            DataIssue::NegativeSpread => format!("ask {} below bid {}", quote.ask, quote.bid),
+            DataIssue::PriceJump => format!("mid jumped to {}", (quote.bid + quote.ask) / 2.0),
+            DataIssue::DuplicateTimestamp => format!("timestamp {} repeated", quote.timestamp),
+            DataIssue::Silence => String::new(),
+        };
+        warn!(venue = %quote.venue, symbol = %quote.symbol, issue = %issue, detail = %detail, "Market data issue");
+        self.events.publish(EngineEvent::DataQuality {
+            venue: quote.venue.to_string(),
+            symbol: quote.symbol.to_string(),
+            issue,
+            detail,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(bid: f64, ask: f64, timestamp: u64) -> Quote {
+        Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "SIM".into(),
+            timestamp,
+        }
+    }
+
+    fn monitor() -> DataQualityMonitor {
+        let config = DataQualityConfig { window: 10, min_samples: 5, ..DataQualityConfig::default() };
+        DataQualityMonitor::new(config, EventBus::default())
+    }
+
+    #[test]
+    fn test_flags_quote_issues() {
+        let monitor = monitor();
+        let mut events = monitor.events.subscribe();
+        let start = Instant::now();
+        for i in 0..10 {
+            let bid = if i % 2 == 0 { 100.0 } else { 100.1 };
+            assert!(monitor.check(&quote(bid, bid + 0.1, i + 1), start).is_empty());
+        }
+        assert_eq!(monitor.score("SIM"), Some(1.0));
+
+        assert_eq!(monitor.check(&quote(101.0, 100.9, 11), start), vec![DataIssue::NegativeSpread]);
+        assert_eq!(monitor.check(&quote(100.0, 100.1, 11), start), vec![DataIssue::DuplicateTimestamp]);
+        assert_eq!(monitor.check(&quote(110.0, 110.1, 12), start), vec![DataIssue::PriceJump]);
+        // The jump is not part of the deviation the next move is judged by
+        assert!(monitor.check(&quote(110.1, 110.2, 13), start).is_empty());
+        assert!(monitor.score("SIM").unwrap() < 0.8);
+
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            EngineEvent::DataQuality { issue: DataIssue::NegativeSpread, .. }
+        ));
+    }
+
+    #[test]
+    fn test_flags_silence_once() {
+        let monitor = monitor();
+        let start = Instant::now();
+        monitor.check(&quote(100.0, 100.1, 1), start);
+
+        assert!(monitor.silent_at(start + Duration::from_secs(1)).is_empty());
+        let silent = monitor.silent_at(start + Duration::from_secs(5));
+        assert_eq!(silent, vec![("SIM".to_string(), "BTCUSDT".to_string())]);
+        assert!(monitor.silent_at(start + Duration::from_secs(10)).is_empty());
+
+        // Quoting again ends the silence
+        monitor.check(&quote(100.0, 100.1, 2), start + Duration::from_secs(11));
+        assert_eq!(monitor.silent_at(start + Duration::from_secs(16)).len(), 1);
+    }
+}
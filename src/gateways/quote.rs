@@ -1,101 +1,349 @@
-use std::sync::Arc;
-use std::any::Any;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
+use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::{mpsc, RwLock};
-use tokio::time::Duration;
 use tracing::{info, warn, error, debug};
 
-use crate::types::Quote;
-use crate::venues::VenueAdapter;
-use crate::error::{HftError, GatewayError};
-use crate::metrics::QUOTE_GATEWAY_THROUGHPUT;
+use crate::types::{Quote, Symbol, VenueId};
+use crate::venues::{VenueAdapter, VenueRegistry};
+use crate::error::{HftError, GatewayError, VenueError};
+use crate::metrics::{labels, QUOTES_BACKPRESSURE_DROPPED, QUOTES_DEDUPLICATED, QUOTES_PAUSED, QUOTE_GATEWAY_THROUGHPUT};
+use crate::health::Heartbeat;
+use crate::instruments::InstrumentMap;
+use crate::gateways::backpressure::{self, PendingQuotes, QuoteBackpressure};
+use crate::gateways::chaos::ChaosConfig;
+use crate::gateways::quality::DataQualityMonitor;
+use crate::gateways::storm::QuoteStormGuard;
+use crate::risk::PriceSanity;
+use crate::gateways::subscriptions::{SubscriptionChanges, SubscriptionSpec, SubscriptionStore};
 
 #[cfg(test)]
 use crate::mocks::mock_venue::{MockVenue, MockVenueConfig};
+
+/// Top of book last forwarded for a (symbol, venue): bid, ask, bid size, ask size
+type LastQuotes = HashMap<(Symbol, VenueId), [f64; 4]>;
+
+const DEFAULT_READER_CAPACITY: usize = 1000;
+
+/// A venue's own quote channel, read by a task of its own
+struct VenueReader {
+    /// Taken when the reader task starts
+    rx: Option<mpsc::Receiver<Quote>>,
+    paused: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+pub(crate) struct Readers {
+    venues: HashMap<String, VenueReader>,
+    /// Whether readers start as soon as they are added
+    started: bool,
+}
+
 pub struct QuoteGateway {
-    pub(crate) venues: RwLock<Vec<Arc<dyn VenueAdapter>>>,
+    pub(crate) venues: VenueRegistry,
     pub(crate) quote_tx: mpsc::Sender<Quote>,
     pub(crate) subscriptions: RwLock<HashMap<String, Vec<String>>>,
     pub(crate) is_running: RwLock<bool>,
+    pub(crate) heartbeat: Option<Heartbeat>,
+    pub(crate) last_quotes: Option<Mutex<LastQuotes>>,
+    pub(crate) instruments: Arc<InstrumentMap>,
+    pub(crate) chaos: Option<ChaosConfig>,
+    /// Persists the subscribed symbols whenever they change
+    pub(crate) store: Option<SubscriptionStore>,
+    pub(crate) quality: Option<Arc<DataQualityMonitor>>,
+    /// Given each forwarded quote to check orders against other venues
+    pub(crate) sanity: Option<PriceSanity>,
+    pub(crate) storm: Option<QuoteStormGuard>,
+    pub(crate) backpressure: QuoteBackpressure,
+    /// Quotes held back from a full channel by a dropping policy
+    pub(crate) pending: Arc<Mutex<PendingQuotes>>,
+    /// Per-venue quote channels, each read by its own task into `quote_tx`
+    pub(crate) readers: Mutex<Readers>,
+    /// Quotes buffered per venue ahead of its reader
+    pub(crate) reader_capacity: usize,
 }
 
 impl QuoteGateway {
     pub fn new(quote_tx: mpsc::Sender<Quote>) -> Self {
         Self {
-            venues: RwLock::new(Vec::new()),
+            venues: VenueRegistry::new(),
             quote_tx,
             subscriptions: RwLock::new(HashMap::new()),
             is_running: RwLock::new(false),
+            heartbeat: None,
+            last_quotes: None,
+            instruments: Arc::new(InstrumentMap::new()),
+            chaos: None,
+            store: None,
+            quality: None,
+            sanity: None,
+            storm: None,
+            backpressure: QuoteBackpressure::default(),
+            pending: Arc::new(Mutex::new(PendingQuotes::default())),
+            readers: Mutex::new(Readers::default()),
+            reader_capacity: DEFAULT_READER_CAPACITY,
         }
     }
 
-    /// Add a venue to the quote gateway
+    /// Report the time of the last processed quote to the health registry
+    pub fn with_heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    /// Share `venues` with the order gateway, so venues added here are
+    /// routable too
+    pub fn with_venues(mut self, venues: VenueRegistry) -> Self {
+        self.venues = venues;
+        self
+    }
+
+    /// Translate venue symbols to canonical instrument IDs
+    pub fn with_instruments(mut self, instruments: Arc<InstrumentMap>) -> Self {
+        self.instruments = instruments;
+        self
+    }
+
+    /// Delay and drop incoming quotes, for resilience tests
+    pub fn with_chaos(mut self, chaos: ChaosConfig) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+
+    /// Save the subscribed symbols to `store` on every change
+    pub fn with_subscription_store(mut self, store: SubscriptionStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Check incoming quotes for crossed books, jumps, repeated timestamps
+    /// and silence
+    pub fn with_quality(mut self, quality: Arc<DataQualityMonitor>) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
+    /// Keep `sanity` up to date with every venue's best bid and offer
+    pub fn with_price_sanity(mut self, sanity: PriceSanity) -> Self {
+        self.sanity = Some(sanity);
+        self
+    }
+
+    /// Conflate the quotes of venues whose quote rate storms past its
+    /// baseline
+    pub fn with_storm_guard(mut self, storm: QuoteStormGuard) -> Self {
+        self.storm = Some(storm);
+        self
+    }
+
+    /// Quotes buffered per venue ahead of its reader task
+    pub fn with_reader_capacity(mut self, capacity: usize) -> Self {
+        self.reader_capacity = capacity;
+        self
+    }
+
+    /// What to do with quotes when the book builder's channel is full
+    pub fn with_backpressure(mut self, backpressure: QuoteBackpressure) -> Self {
+        self.backpressure = backpressure;
+        self
+    }
+
+    /// Send `quote` without waiting, holding it back when the channel is
+    /// full and dropping the oldest held back quote of its stream beyond
+    /// `depth`
+    fn forward(&self, quote: Quote, depth: usize) -> Result<(), HftError> {
+        let mut pending = self.pending.lock().unwrap();
+        // Behind any quotes already held back for the symbol and venue
+        let quote = if pending.holds(&quote) {
+            quote
+        } else {
+            match self.quote_tx.try_send(quote) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Full(quote)) => quote,
+                Err(TrySendError::Closed(_)) => {
+                    return Err(GatewayError::ChannelSendFailed("Failed to send quote: channel closed".to_string()).into());
+                }
+            }
+        };
+
+        let venue = quote.venue;
+        if let Some(dropped) = pending.push(quote, depth) {
+            QUOTES_BACKPRESSURE_DROPPED.with_label_values(&[&venue, self.backpressure.label()]).inc();
+            debug!(venue = %venue, symbol = %dropped.symbol, error = %GatewayError::ChannelCapacityExceeded, "Dropped quote");
+        }
+        if !pending.draining {
+            pending.draining = true;
+            tokio::spawn(backpressure::drain(Arc::clone(&self.pending), self.quote_tx.clone()));
+        }
+        Ok(())
+    }
+
+    /// `symbols` as `venue` calls them
+    fn venue_symbols(&self, venue: &str, symbols: &[String]) -> Vec<String> {
+        symbols.iter().map(|s| self.instruments.venue_symbol(venue.into(), s.into()).to_string()).collect()
+    }
+
+    /// Drop quotes whose prices and sizes repeat the previous quote for the
+    /// same symbol and venue
+    pub fn with_dedup(mut self) -> Self {
+        self.last_quotes = Some(Mutex::new(HashMap::new()));
+        self
+    }
+
+    /// Whether `quote` repeats the last quote forwarded for its symbol and venue
+    fn is_duplicate(&self, quote: &Quote) -> bool {
+        let Some(last_quotes) = &self.last_quotes else {
+            return false;
+        };
+
+        let top = [quote.bid, quote.ask, quote.bid_size, quote.ask_size];
+        let mut last_quotes = last_quotes.lock().unwrap();
+        match last_quotes.get_mut(&(quote.symbol, quote.venue)) {
+            Some(last) if *last == top => true,
+            Some(last) => {
+                *last = top;
+                false
+            }
+            None => {
+                last_quotes.insert((quote.symbol, quote.venue), top);
+                false
+            }
+        }
+    }
+
+    /// Register a venue for quotes and, through the shared registry, for
+    /// order routing
     pub async fn add_venue(&self, venue: Arc<dyn VenueAdapter>) {
         let venue_name = venue.name().await;
         debug!(venue = %venue_name, "Adding venue to quote gateway");
 
-        let mut venues = self.venues.write().await;
-        venues.push(venue.clone());
+        if let Some(replaced) = self.venues.add(venue.clone()).await {
+            if let Err(e) = replaced.stop().await {
+                warn!(venue = %venue_name, error = ?e, "Failed to stop replaced venue");
+            }
+        }
 
-        // If we already have subscriptions and the gateway is running,
-        // subscribe the new venue to existing symbols
+        // Once the gateway is running, a new venue streams the symbols the
+        // others are subscribed to
         if *self.is_running.read().await {
-            let subscriptions = self.subscriptions.read().await;
-            for (venue_name, symbols) in subscriptions.iter() {
-                if venue_name == &venue.name().await && !symbols.is_empty() {
-                    if let Err(e) = venue.subscribe_quotes(symbols.clone()).await {
-                        error!(
-                            venue = %venue_name,
-                            symbols = ?symbols,
-                            error = ?e,
-                            "Failed to subscribe new venue to existing symbols"
-                        );
-                    }
+            let mut symbols: Vec<String> = self.subscriptions.read().await
+                .iter()
+                .filter(|(name, _)| **name != venue_name)
+                .flat_map(|(_, symbols)| symbols.iter().cloned())
+                .collect();
+            symbols.sort();
+            symbols.dedup();
+            if symbols.is_empty() {
+                return;
+            }
+            match venue.subscribe_quotes(self.venue_symbols(&venue_name, &symbols)).await {
+                Ok(()) => {
+                    self.subscriptions.write().await.insert(venue_name, symbols);
                 }
+                Err(e) => error!(
+                    venue = %venue_name,
+                    symbols = ?symbols,
+                    error = ?e,
+                    "Failed to subscribe new venue to existing symbols"
+                ),
             }
         }
     }
 
-    pub async fn remove_venue(&self, venue_name: &str) -> Result<(), HftError> {
-        debug!(venue = %venue_name, "Removing venue from quote gateway");
+    /// Read `venue`'s quotes from `rx` on a task of its own, replacing any
+    /// earlier channel for it. The task starts with the other readers, or
+    /// straight away once they have.
+    pub fn add_reader(self: &Arc<Self>, venue: &str, rx: mpsc::Receiver<Quote>) {
+        let started = {
+            let mut readers = self.readers.lock().unwrap();
+            readers.venues.insert(venue.to_string(), VenueReader { rx: Some(rx), paused: Arc::new(AtomicBool::new(false)) });
+            readers.started
+        };
+        if started {
+            self.start_readers();
+        }
+    }
 
-        let mut venues = self.venues.write().await;
-        let original_len = venues.len();
+    /// A new quote channel for `venue` and its reader, e.g. for a venue
+    /// added while the engine runs
+    pub fn venue_sender(self: &Arc<Self>, venue: &str) -> mpsc::Sender<Quote> {
+        let (tx, rx) = mpsc::channel(self.reader_capacity);
+        self.add_reader(venue, rx);
+        tx
+    }
 
-        // Create a new vector to hold venues we want to keep
-        let mut new_venues = Vec::new();
-        let mut removed_venue = None;
+    /// Start a reader task for every venue channel, and for channels added
+    /// from now on
+    pub fn start_readers(self: &Arc<Self>) {
+        let mut readers = self.readers.lock().unwrap();
+        readers.started = true;
+        for (venue, reader) in readers.venues.iter_mut() {
+            if let Some(rx) = reader.rx.take() {
+                tokio::spawn(Arc::clone(self).read_venue(venue.clone(), rx, Arc::clone(&reader.paused)));
+            }
+        }
+    }
 
-        // Check each venue and only keep those with a different name
-        for venue in venues.drain(..) {
-            if venue.name().await != venue_name {
-                new_venues.push(venue);
-            } else {
-                removed_venue = Some(venue);
+    /// Normalize one venue's quotes into the shared channel to the book
+    /// builder. A venue that bursts or stalls only holds up its own
+    /// channel; the others keep flowing.
+    async fn read_venue(self: Arc<Self>, venue: String, mut rx: mpsc::Receiver<Quote>, paused: Arc<AtomicBool>) {
+        debug!(venue = %venue, "Quote reader started");
+        while let Some(quote) = rx.recv().await {
+            if paused.load(Ordering::Relaxed) {
+                QUOTES_PAUSED.with_label_values(&[&venue]).inc();
+                continue;
+            }
+            if let Err(e) = self.process_quote(quote).await {
+                error!(venue = %venue, error = %e, "Quote reader stopped");
+                return;
             }
         }
+        debug!(venue = %venue, "Quote reader finished");
+    }
 
-        // Check if we actually removed any venue
-        if new_venues.len() == original_len {
-            warn!(venue = %venue_name, "Attempted to remove venue that was not found");
+    fn set_paused(&self, venue: &str, paused: bool) -> bool {
+        let readers = self.readers.lock().unwrap();
+        let Some(reader) = readers.venues.get(venue) else {
+            return false;
+        };
+        reader.paused.store(paused, Ordering::Relaxed);
+        info!(venue = %venue, paused = paused, "Venue quote reader {}", if paused { "paused" } else { "resumed" });
+        true
+    }
 
-            // Put all venues back since we didn't find the one to remove
-            *venues = new_venues;
-            return Err(GatewayError::VenueNotFound(venue_name.to_string()).into());
-        }
+    /// Discard `venue`'s quotes as they arrive, leaving the other venues
+    /// flowing, until resumed. Returns false for a venue without a reader.
+    pub fn pause_venue(&self, venue: &str) -> bool {
+        self.set_paused(venue, true)
+    }
 
-        // Update the venues with our filtered list
-        *venues = new_venues;
+    /// Forward `venue`'s quotes again after [`pause_venue`](Self::pause_venue)
+    pub fn resume_venue(&self, venue: &str) -> bool {
+        self.set_paused(venue, false)
+    }
 
-        // Stop the removed venue if we found one
-        if let Some(venue) = removed_venue {
-            venue.stop().await?;
-        }
+    pub fn is_paused(&self, venue: &str) -> bool {
+        self.readers.lock().unwrap().venues.get(venue).is_some_and(|r| r.paused.load(Ordering::Relaxed))
+    }
+
+    pub async fn remove_venue(&self, venue_name: &str) -> Result<(), HftError> {
+        debug!(venue = %venue_name, "Removing venue from quote gateway");
+
+        let Some(removed_venue) = self.venues.remove(venue_name) else {
+            warn!(venue = %venue_name, "Attempted to remove venue that was not found");
+            return Err(GatewayError::VenueNotFound(venue_name.to_string()).into());
+        };
+
+        // Its reader finishes once the venue drops its sender
+        self.readers.lock().unwrap().venues.remove(venue_name);
+        removed_venue.stop().await?;
 
         Ok(())
     }
 
-    /// Subscribe to quotes for the given symbols on all venues
+    /// Subscribe to quotes for the given canonical instruments on all venues
     pub async fn subscribe(&self, symbols: Vec<String>) -> Result<(), HftError> {
         if symbols.is_empty() {
             return Err(GatewayError::InvalidSymbol("Empty symbol list".to_string()).into());
@@ -103,7 +351,7 @@ impl QuoteGateway {
 
         info!(symbols = ?symbols, "Subscribing to symbols");
 
-        let venues = self.venues.read().await;
+        let venues = self.venues.all();
         if venues.is_empty() {
             return Err(GatewayError::NoVenuesConfigured.into());
         }
@@ -116,12 +364,15 @@ impl QuoteGateway {
             let venue_name = venue.name().await;
             debug!(venue = %venue_name, symbols = ?symbols, "Subscribing venue to symbols");
 
-            match venue.subscribe_quotes(symbols.clone()).await {
+            match venue.subscribe_quotes(self.venue_symbols(&venue_name, &symbols)).await {
                 Ok(_) => {
                     debug!(venue = %venue_name, "Subscription successful");
                     // Store successful subscription
                     let mut subscriptions = self.subscriptions.write().await;
-                    subscriptions.insert(venue_name, symbols.clone());
+                    let subscribed = subscriptions.entry(venue_name).or_default();
+                    subscribed.extend(symbols.iter().cloned());
+                    subscribed.sort();
+                    subscribed.dedup();
                 },
                 Err(e) => {
                     error!(venue = %venue_name, error = ?e, "Failed to subscribe to symbols");
@@ -141,26 +392,187 @@ impl QuoteGateway {
         }
 
         *self.is_running.write().await = true;
+        self.persist().await;
 
         Ok(())
     }
 
+    /// Subscribe every venue to the symbols in `symbols` it is not already
+    /// streaming, returning the ones that were new on any venue
+    pub async fn add_symbols(&self, symbols: Vec<String>) -> Result<Vec<String>, HftError> {
+        let mut spec = self.spec().await;
+        for venue in self.venues.names() {
+            spec = spec.with_venue(&venue, symbols.iter().cloned());
+        }
+        Ok(self.apply(&spec).await?.added_symbols())
+    }
+
+    /// Stop forwarding quotes for `symbols` on every venue, returning the
+    /// ones that were subscribed. Removing the last symbol unsubscribes the
+    /// gateway.
+    pub async fn remove_symbols(&self, symbols: &[String]) -> Result<Vec<String>, HftError> {
+        let mut spec = SubscriptionSpec::new();
+        for (venue, subscribed) in self.subscriptions.read().await.iter() {
+            spec = spec.with_venue(venue, subscribed.iter().filter(|s| !symbols.contains(s)).cloned());
+        }
+        Ok(self.apply(&spec).await?.removed_symbols())
+    }
+
+    /// Subscribe each venue to the symbols `spec` lists for it and drop the
+    /// ones it no longer lists, leaving symbols subscribed in both alone.
+    /// Registered venues `spec` does not mention are unsubscribed entirely.
+    ///
+    /// A venue that fails to subscribe keeps its previous symbols; the
+    /// others are still applied and the failures returned as one error.
+    pub async fn apply(&self, spec: &SubscriptionSpec) -> Result<SubscriptionChanges, HftError> {
+        let venues = self.venues.all();
+        if venues.is_empty() && !spec.is_empty() {
+            return Err(GatewayError::NoVenuesConfigured.into());
+        }
+        let names = self.venues.names();
+        for unknown in spec.venues().filter(|v| !names.contains(v)) {
+            warn!(venue = %unknown, "Ignoring subscriptions for unregistered venue");
+        }
+
+        let mut changes = SubscriptionChanges::default();
+        let mut errors = Vec::new();
+        for (venue, venue_name) in venues.iter().zip(names) {
+            let current: Vec<String> = self.subscriptions.read().await.get(&venue_name).cloned().unwrap_or_default();
+            let added: Vec<String> = spec.symbols(&venue_name).filter(|s| !current.contains(s)).cloned().collect();
+            let removed: Vec<String> = current.iter().filter(|s| !spec.symbols(&venue_name).any(|w| w == *s)).cloned().collect();
+
+            if !added.is_empty() {
+                debug!(venue = %venue_name, symbols = ?added, "Subscribing venue to symbols");
+                if let Err(e) = venue.subscribe_quotes(self.venue_symbols(&venue_name, &added)).await {
+                    error!(venue = %venue_name, error = ?e, "Failed to subscribe to symbols");
+                    errors.push(format!("{}: {:?}", venue_name, e));
+                    continue;
+                }
+            }
+            if added.is_empty() && removed.is_empty() {
+                continue;
+            }
+            if !removed.is_empty() {
+                let last = added.is_empty() && removed.len() == current.len();
+                if let Err(e) = self.stop_streaming(&**venue, &venue_name, &removed, last).await {
+                    warn!(venue = %venue_name, symbols = ?removed, error = ?e, "Failed to unsubscribe, filtering its quotes");
+                }
+            }
+
+            // Removed symbols keep an empty entry so the venue's stray
+            // quotes are still filtered
+            let mut subscriptions = self.subscriptions.write().await;
+            let subscribed = subscriptions.entry(venue_name.clone()).or_default();
+            subscribed.retain(|s| !removed.contains(s));
+            subscribed.extend(added.iter().cloned());
+            subscribed.sort();
+            if !added.is_empty() {
+                changes.added.insert(venue_name.clone(), added);
+            }
+            if !removed.is_empty() {
+                changes.removed.insert(venue_name, removed);
+            }
+        }
+
+        if !changes.added.is_empty() || !changes.removed.is_empty() {
+            info!(added = ?changes.added, removed = ?changes.removed, "Subscriptions changed");
+            if self.spec().await.is_empty() {
+                self.unsubscribe_all().await?;
+            } else {
+                *self.is_running.write().await = true;
+            }
+            self.persist().await;
+        }
+
+        if errors.is_empty() {
+            Ok(changes)
+        } else {
+            Err(GatewayError::SubscriptionFailed(errors.join(", ")).into())
+        }
+    }
+
+    /// Subscribed symbols per venue
+    pub async fn spec(&self) -> SubscriptionSpec {
+        let mut spec = SubscriptionSpec::new();
+        for (venue, symbols) in self.subscriptions.read().await.iter() {
+            spec = spec.with_venue(venue, symbols.iter().cloned());
+        }
+        spec
+    }
+
+    /// Canonical symbols subscribed on any venue, sorted
+    pub async fn symbols(&self) -> Vec<String> {
+        self.spec().await.all_symbols()
+    }
+
+    async fn persist(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if let Err(e) = store.save(&self.spec().await) {
+            error!(path = %store.path().display(), error = ?e, "Failed to persist subscriptions");
+        }
+    }
+
     /// Process an incoming quote from a venue
-    pub async fn process_quote(&self, quote: Quote) -> Result<(), HftError> {
+    pub async fn process_quote(&self, mut quote: Quote) -> Result<(), HftError> {
+        if let Some(heartbeat) = &self.heartbeat {
+            heartbeat.beat();
+        }
+
+        if let Some(chaos) = &self.chaos {
+            if !chaos.pass("quote_gateway").await {
+                return Ok(());
+            }
+        }
+
+        quote.symbol = self.instruments.canonical(quote.venue, quote.symbol);
+
+        // Venues may keep streaming symbols removed at runtime
+        if self.subscriptions.read().await.get(quote.venue.as_str()).is_some_and(|s| !s.iter().any(|s| *s == quote.symbol)) {
+            return Ok(());
+        }
+
+        // Checked before deduplication, which would hide repeated timestamps
+        if let Some(quality) = &self.quality {
+            quality.on_quote(&quote);
+        }
+
+        // Repeats still show the venue's quote is current
+        if let Some(sanity) = &self.sanity {
+            sanity.on_quote(&quote);
+        }
+
+        if let Some(storm) = &self.storm {
+            if !storm.admit(&quote, std::time::Instant::now()) {
+                return Ok(());
+            }
+        }
+
+        if self.is_duplicate(&quote) {
+            QUOTES_DEDUPLICATED
+                .with_label_values(&[labels::symbol("quotes_deduplicated", &quote.symbol), &quote.venue])
+                .inc();
+            return Ok(());
+        }
+
         // Update metrics
-        let symbol = quote.symbol.clone();
+        let symbol = quote.symbol;
         QUOTE_GATEWAY_THROUGHPUT
-            .with_label_values(&[&symbol, &quote.venue])
+            .with_label_values(&[labels::symbol("quote_gateway_throughput", &symbol), &quote.venue])
             .inc();
 
         // Forward the quote to the book builder
-        self.quote_tx.send(quote).await
-            .map_err(|e| GatewayError::ChannelSendFailed(format!("Failed to send quote: {}", e)))?;
+        match self.backpressure.depth() {
+            Some(depth) => self.forward(quote, depth)?,
+            None => self.quote_tx.send(quote).await
+                .map_err(|e| GatewayError::ChannelSendFailed(format!("Failed to send quote: {}", e)))?,
+        }
 
         Ok(())
     }
 
-    /// Unsubscribe from all symbols
+    /// Unsubscribe from all symbols, telling each venue to stop streaming
     pub async fn unsubscribe_all(&self) -> Result<(), HftError> {
         info!("Unsubscribing from all symbols");
 
@@ -168,10 +580,37 @@ impl QuoteGateway {
         *self.is_running.write().await = false;
 
         // Clear subscriptions
-        let mut subscriptions = self.subscriptions.write().await;
-        subscriptions.clear();
+        let subscriptions = std::mem::take(&mut *self.subscriptions.write().await);
+        let mut errors = Vec::new();
+        for (venue_name, symbols) in subscriptions.into_iter().filter(|(_, s)| !s.is_empty()) {
+            let Some(venue) = self.venues.get(&venue_name) else {
+                continue;
+            };
+            if let Err(e) = self.stop_streaming(&*venue, &venue_name, &symbols, true).await {
+                error!(venue = %venue_name, error = ?e, "Failed to unsubscribe venue");
+                errors.push(format!("{}: {:?}", venue_name, e));
+            }
+        }
 
-        Ok(())
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(GatewayError::SubscriptionFailed(errors.join(", ")).into())
+        }
+    }
+
+    /// Ask `venue` to stop streaming `symbols`. A venue that cannot
+    /// unsubscribe is stopped when none of its symbols remain; otherwise its
+    /// quotes for `symbols` are dropped here.
+    async fn stop_streaming(&self, venue: &dyn VenueAdapter, venue_name: &str, symbols: &[String], last: bool) -> Result<(), HftError> {
+        match venue.unsubscribe_quotes(self.venue_symbols(venue_name, symbols)).await {
+            Err(HftError::Venue(VenueError::NotSupported(_))) if last => venue.stop().await,
+            Err(HftError::Venue(VenueError::NotSupported(_))) => {
+                debug!(venue = %venue_name, symbols = ?symbols, "Venue cannot unsubscribe, filtering its quotes");
+                Ok(())
+            }
+            result => result,
+        }
     }
 
     /// Check if the gateway is currently running
@@ -189,6 +628,7 @@ impl QuoteGateway {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeMap;
     use std::sync::Arc;
     use tokio::sync::mpsc;
     use tokio::time::Duration;
@@ -207,8 +647,7 @@ async fn test_quote_gateway_add_venue() {
     gateway.add_venue(venue.clone()).await;
 
     // Check that venue was added
-    let venues = gateway.venues.read().await;
-    assert_eq!(venues.len(), 1);
+    assert_eq!(gateway.venues.len(), 1);
 }
 
 #[tokio::test]
@@ -228,9 +667,7 @@ async fn test_quote_gateway_remove_venue() {
     gateway.add_venue(venue2.clone()).await;
 
     // Check that venues were added
-    let venues = gateway.venues.read().await;
-    assert_eq!(venues.len(), 2);
-    drop(venues);  // Explicitly drop the lock
+    assert_eq!(gateway.venues.len(), 2);
 
     // Stop venues explicitly for test cleanup
     venue1.stop().await;
@@ -241,9 +678,7 @@ async fn test_quote_gateway_remove_venue() {
     assert!(result.is_ok());
 
     // Check that venue was removed
-    let venues = gateway.venues.read().await;
-    assert_eq!(venues.len(), 1);
-    drop(venues);  // Explicitly drop the lock
+    assert_eq!(gateway.venues.len(), 1);
 
     // Try to remove a venue that doesn't exist
     let result = gateway.remove_venue("NONEXISTENT").await;
@@ -331,12 +766,12 @@ async fn test_quote_gateway_process_quote() {
 
     // Process a quote
     let quote = Quote {
-        symbol: "BTCUSDT".to_string(),
+        symbol: "BTCUSDT".into(),
         bid: 50000.0,
         ask: 50001.0,
         bid_size: 1.0,
         ask_size: 1.0,
-        venue: "TEST".to_string(),
+        venue: "TEST".into(),
         timestamp: 0,
     };
 
@@ -353,6 +788,162 @@ async fn test_quote_gateway_process_quote() {
     assert_eq!(received.ask, quote.ask);
 }
 
+#[tokio::test]
+async fn test_quote_gateway_conflates_when_channel_full() {
+    let (quote_tx, mut quote_rx) = mpsc::channel(1);
+    let gateway = QuoteGateway::new(quote_tx).with_backpressure(QuoteBackpressure::Conflate);
+    let quote = |symbol: &str, bid: f64| Quote {
+        symbol: symbol.into(),
+        bid,
+        ask: bid + 1.0,
+        bid_size: 1.0,
+        ask_size: 1.0,
+        venue: "TEST".into(),
+        timestamp: 0,
+    };
+
+    // Never waits on the full channel; only the latest held back quote per
+    // symbol survives
+    for bid in [1.0, 2.0, 3.0, 4.0] {
+        gateway.process_quote(quote("BTCUSDT", bid)).await.unwrap();
+    }
+    gateway.process_quote(quote("ETHUSDT", 10.0)).await.unwrap();
+
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        let quote = tokio::time::timeout(Duration::from_secs(1), quote_rx.recv()).await.unwrap().unwrap();
+        received.push((quote.symbol.to_string(), quote.bid));
+    }
+    assert_eq!(received, vec![("BTCUSDT".to_string(), 1.0), ("BTCUSDT".to_string(), 4.0), ("ETHUSDT".to_string(), 10.0)]);
+    assert_eq!(QUOTES_BACKPRESSURE_DROPPED.with_label_values(&["TEST", "conflate"]).get(), 2.0);
+}
+
+#[tokio::test]
+async fn test_venue_readers_pause_independently() {
+    let (quote_tx, mut quote_rx) = mpsc::channel(16);
+    let gateway = Arc::new(QuoteGateway::new(quote_tx).with_reader_capacity(4));
+    let slow = gateway.venue_sender("SLOW");
+    let fast = gateway.venue_sender("FAST");
+    gateway.start_readers();
+    let quote = |venue: &str, bid: f64| Quote {
+        symbol: "BTCUSDT".into(),
+        bid,
+        ask: bid + 1.0,
+        bid_size: 1.0,
+        ask_size: 1.0,
+        venue: venue.into(),
+        timestamp: 0,
+    };
+    let next = |rx: &mut mpsc::Receiver<Quote>| {
+        let quote = rx.try_recv();
+        quote.map(|q| (q.venue.to_string(), q.bid))
+    };
+
+    assert!(gateway.pause_venue("SLOW"));
+    assert!(!gateway.pause_venue("UNKNOWN"));
+    slow.send(quote("SLOW", 1.0)).await.unwrap();
+    fast.send(quote("FAST", 2.0)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(next(&mut quote_rx).unwrap(), ("FAST".to_string(), 2.0));
+    assert!(next(&mut quote_rx).is_err());
+    assert!(gateway.is_paused("SLOW"));
+
+    gateway.resume_venue("SLOW");
+    slow.send(quote("SLOW", 3.0)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(next(&mut quote_rx).unwrap(), ("SLOW".to_string(), 3.0));
+}
+
+#[tokio::test]
+async fn test_quote_gateway_chaos() {
+    let (quote_tx, mut quote_rx) = mpsc::channel(100);
+    let quote = Quote {
+        symbol: "BTCUSDT".into(),
+        bid: 50000.0,
+        ask: 50001.0,
+        bid_size: 1.0,
+        ask_size: 1.0,
+        venue: "TEST".into(),
+        timestamp: 0,
+    };
+
+    let gateway = QuoteGateway::new(quote_tx.clone()).with_chaos(ChaosConfig {
+        latency: Duration::from_millis(50),
+        ..ChaosConfig::default()
+    });
+    let start = std::time::Instant::now();
+    gateway.process_quote(quote.clone()).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert!(quote_rx.try_recv().is_ok());
+
+    let gateway = QuoteGateway::new(quote_tx).with_chaos(ChaosConfig {
+        drop_probability: 1.0,
+        ..ChaosConfig::default()
+    });
+    gateway.process_quote(quote).await.unwrap();
+    assert!(quote_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_quote_gateway_canonical_symbols() {
+    let (quote_tx, mut quote_rx) = mpsc::channel(100);
+    let (venue_tx, mut venue_rx) = mpsc::channel(100);
+    let mut instruments = InstrumentMap::new();
+    instruments.insert("BTC-USD", "MOCK", "BTCUSDT");
+    let gateway = QuoteGateway::new(quote_tx).with_instruments(Arc::new(instruments));
+
+    let venue = Arc::new(MockVenue::new("MOCK", MockVenueConfig::default())
+        .with_quote_sender(venue_tx));
+    gateway.add_venue(venue.clone()).await;
+
+    // Subscriptions stay canonical, the venue gets its own symbol
+    gateway.subscribe(vec!["BTC-USD".to_string()]).await.unwrap();
+    assert_eq!(gateway.get_subscriptions().await["MOCK"], vec!["BTC-USD".to_string()]);
+    let raw = tokio::time::timeout(Duration::from_millis(1000), venue_rx.recv()).await.unwrap().unwrap();
+    assert_eq!(raw.symbol, "BTCUSDT");
+    venue.stop().await;
+
+    gateway.process_quote(raw).await.unwrap();
+    assert_eq!(quote_rx.recv().await.unwrap().symbol, "BTC-USD");
+}
+
+#[tokio::test]
+async fn test_quote_gateway_dedup() {
+    let (quote_tx, mut quote_rx) = mpsc::channel(100);
+    let gateway = QuoteGateway::new(quote_tx).with_dedup();
+
+    let quote = Quote {
+        symbol: "BTCUSDT".into(),
+        bid: 50000.0,
+        ask: 50001.0,
+        bid_size: 1.0,
+        ask_size: 1.0,
+        venue: "TEST".into(),
+        timestamp: 0,
+    };
+
+    // Only the timestamp changes on a resent BBO
+    gateway.process_quote(quote.clone()).await.unwrap();
+    gateway.process_quote(Quote { timestamp: 1, ..quote.clone() }).await.unwrap();
+    // Same prices on another venue are not echoes
+    gateway.process_quote(Quote { venue: "OTHER".into(), ..quote.clone() }).await.unwrap();
+    gateway.process_quote(Quote { bid_size: 2.0, ..quote.clone() }).await.unwrap();
+    // Back to the original size is a change from the last quote
+    gateway.process_quote(quote.clone()).await.unwrap();
+    drop(gateway);
+
+    let mut received = Vec::new();
+    while let Some(quote) = quote_rx.recv().await {
+        received.push((quote.venue.to_string(), quote.bid_size));
+    }
+    assert_eq!(received, vec![
+        ("TEST".to_string(), 1.0),
+        ("OTHER".to_string(), 1.0),
+        ("TEST".to_string(), 2.0),
+        ("TEST".to_string(), 1.0),
+    ]);
+}
+
 #[tokio::test]
 async fn test_quote_gateway_multiple_venues() {
     // Create channels
@@ -362,11 +953,15 @@ async fn test_quote_gateway_multiple_venues() {
     let gateway = QuoteGateway::new(quote_tx);
 
     // Create multiple venues with different configurations
-    let mut config1 = MockVenueConfig::default();
-    config1.quote_interval_ms = 50; // Faster updates
+    let config1 = MockVenueConfig {
+        quote_interval_ms: 50, // Faster updates
+        ..Default::default()
+    };
 
-    let mut config2 = MockVenueConfig::default();
-    config2.quote_interval_ms = 100; // Slower updates
+    let config2 = MockVenueConfig {
+        quote_interval_ms: 100, // Slower updates
+        ..Default::default()
+    };
 
     let venue1 = Arc::new(MockVenue::new("MOCK1", config1)
         .with_quote_sender(gateway.quote_tx.clone()));
@@ -409,4 +1004,98 @@ async fn test_quote_gateway_multiple_venues() {
     let result = gateway.unsubscribe_all().await;
     assert!(result.is_ok());
 }
+
+#[tokio::test]
+async fn test_runtime_subscriptions_are_persisted() {
+    let dir = std::env::temp_dir().join(format!("hft_subscriptions_{}", std::process::id()));
+    let store = SubscriptionStore::new(dir.join("subscriptions.json"));
+    let (quote_tx, mut quote_rx) = mpsc::channel(100);
+    let gateway = QuoteGateway::new(quote_tx).with_subscription_store(store.clone());
+    let (venue_tx, _venue_rx) = mpsc::channel(100);
+    let venue = Arc::new(MockVenue::new("MOCK", MockVenueConfig::default()).with_quote_sender(venue_tx));
+    gateway.add_venue(venue.clone()).await;
+
+    let symbols = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    assert_eq!(gateway.add_symbols(symbols(&["BTCUSDT", "ETHUSDT"])).await.unwrap(), symbols(&["BTCUSDT", "ETHUSDT"]));
+    assert_eq!(gateway.add_symbols(symbols(&["ETHUSDT", "SOLUSDT"])).await.unwrap(), symbols(&["SOLUSDT"]));
+    assert_eq!(gateway.remove_symbols(&symbols(&["ETHUSDT", "XRPUSDT"])).await.unwrap(), symbols(&["ETHUSDT"]));
+    assert_eq!(gateway.symbols().await, symbols(&["BTCUSDT", "SOLUSDT"]));
+    assert_eq!(store.load().unwrap().all_symbols(), symbols(&["BTCUSDT", "SOLUSDT"]));
+
+    // The venue may keep streaming a removed symbol; the gateway drops it
+    let quote = |symbol: &str| Quote {
+        symbol: symbol.into(),
+        bid: 100.0,
+        ask: 101.0,
+        bid_size: 1.0,
+        ask_size: 1.0,
+        venue: "MOCK".into(),
+        timestamp: 0,
+    };
+    gateway.process_quote(quote("ETHUSDT")).await.unwrap();
+    gateway.process_quote(quote("BTCUSDT")).await.unwrap();
+    assert_eq!(quote_rx.recv().await.unwrap().symbol, "BTCUSDT");
+
+    gateway.remove_symbols(&symbols(&["BTCUSDT", "SOLUSDT"])).await.unwrap();
+    assert!(!gateway.is_running().await);
+    assert!(store.load().unwrap().is_empty());
+
+    venue.stop().await;
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_apply_per_venue_subscriptions() {
+    let (quote_tx, _quote_rx) = mpsc::channel(100);
+    let gateway = QuoteGateway::new(quote_tx);
+    let (venue_tx, _venue_rx) = mpsc::channel(100);
+    let binance = Arc::new(MockVenue::new("BINANCE", MockVenueConfig::default()).with_quote_sender(venue_tx.clone()));
+    let kraken = Arc::new(MockVenue::new("KRAKEN", MockVenueConfig::default()).with_quote_sender(venue_tx));
+    gateway.add_venue(binance.clone()).await;
+    gateway.add_venue(kraken.clone()).await;
+
+    let spec = SubscriptionSpec::new()
+        .with_venue("BINANCE", ["BTCUSDT", "ETHUSDT"])
+        .with_venue("KRAKEN", ["ETHUSDT"]);
+    let changes = gateway.apply(&spec).await.unwrap();
+    assert_eq!(changes.added["BINANCE"], vec!["BTCUSDT", "ETHUSDT"]);
+    assert_eq!(changes.added["KRAKEN"], vec!["ETHUSDT"]);
+    assert!(changes.removed.is_empty());
+
+    // Only the difference is sent to the venues
+    let spec = SubscriptionSpec::new()
+        .with_venue("BINANCE", ["BTCUSDT"])
+        .with_venue("KRAKEN", ["ETHUSDT", "SOLUSDT"]);
+    let changes = gateway.apply(&spec).await.unwrap();
+    assert_eq!(changes.added, BTreeMap::from([("KRAKEN".to_string(), vec!["SOLUSDT".to_string()])]));
+    assert_eq!(changes.removed, BTreeMap::from([("BINANCE".to_string(), vec!["ETHUSDT".to_string()])]));
+    assert_eq!(gateway.spec().await, spec);
+    assert!(gateway.apply(&spec).await.unwrap().added.is_empty());
+
+    binance.stop().await;
+    kraken.stop().await;
+}
+
+#[tokio::test]
+async fn test_unsubscribe_all_stops_venue_quotes() {
+    let (quote_tx, mut quote_rx) = mpsc::channel(1000);
+    let gateway = QuoteGateway::new(quote_tx.clone());
+    let venue = Arc::new(MockVenue::new("MOCK", MockVenueConfig {
+        error_probability: 0.0,
+        disconnect_probability: 0.0,
+        latency_ms: 1,
+        quote_interval_ms: 5,
+        ..MockVenueConfig::default()
+    }).with_quote_sender(quote_tx));
+    gateway.add_venue(venue.clone()).await;
+
+    gateway.subscribe(vec!["BTCUSDT".to_string()]).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(1), quote_rx.recv()).await.unwrap().unwrap();
+
+    gateway.unsubscribe_all().await.unwrap();
+    // Let a quote already in flight land, then expect silence
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    while quote_rx.try_recv().is_ok() {}
+    assert!(tokio::time::timeout(Duration::from_millis(200), quote_rx.recv()).await.is_err());
+}
 }
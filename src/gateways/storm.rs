@@ -0,0 +1,263 @@
+//! Inbound quote storm protection.
+//!
+//! A venue that suddenly streams quotes at many times its usual rate, from a
+//! feed handler bug or a replay after reconnecting, can swamp the book
+//! builder and everything behind it. The guard tracks each venue's quote
+//! rate over one second windows against a moving baseline; when a window
+//! runs past the configured multiple, the venue is switched to conflated
+//! mode, forwarding at most one quote per symbol per conflation interval,
+//! until its rate has been back to normal for a few windows.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::events::{EngineEvent, EventBus};
+use crate::metrics::{QUOTES_CONFLATED, QUOTE_STORM_ACTIVE, QUOTE_STORM_INTERVALS};
+use crate::types::{Quote, Symbol, VenueId};
+
+/// Interval each venue's quote rate is measured over
+const STORM_WINDOW: Duration = Duration::from_secs(1);
+
+/// Weight of the latest normal window in the baseline rate
+const BASELINE_WEIGHT: f64 = 0.1;
+
+/// Consecutive windows back under the threshold before a venue leaves
+/// conflated mode
+const RECOVERY_WINDOWS: u32 = 3;
+
+/// When a venue's quote rate counts as a storm and how it is conflated
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteStormConfig {
+    /// Multiple of the venue's baseline rate that starts a storm
+    pub multiple: f64,
+    /// Quotes per second below which a venue is never throttled, so quiet
+    /// venues waking up are not mistaken for storms
+    pub min_rate: f64,
+    /// Shortest gap between quotes forwarded for a symbol while conflated
+    pub conflate_interval: Duration,
+}
+
+impl Default for QuoteStormConfig {
+    fn default() -> Self {
+        Self {
+            multiple: 100.0,
+            min_rate: 1000.0,
+            conflate_interval: Duration::from_millis(100),
+        }
+    }
+}
+
+impl QuoteStormConfig {
+    /// Comma separated `multiple:X`, `min_rate:QPS` and `conflate_ms:MS`
+    /// entries, e.g. `multiple:100,min_rate:1000,conflate_ms:100`; unset ones
+    /// keep their defaults
+    fn parse(spec: &str) -> Self {
+        let mut config = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once(':')
+                .and_then(|(key, value)| Some((key.trim(), value.trim().parse::<f64>().ok().filter(|v| *v > 0.0)?)));
+            match parsed {
+                Some(("multiple", multiple)) if multiple > 1.0 => config.multiple = multiple,
+                Some(("min_rate", rate)) => config.min_rate = rate,
+                Some(("conflate_ms", ms)) => config.conflate_interval = Duration::from_secs_f64(ms / 1000.0),
+                _ => warn!(entry = entry, "Ignoring malformed quote storm entry"),
+            }
+        }
+        config
+    }
+
+    /// Read `HFT_QUOTE_STORM` (see [`parse`](Self::parse)); returns `None`
+    /// when the guard is off
+    pub fn from_env() -> Option<Self> {
+        Some(Self::parse(&std::env::var("HFT_QUOTE_STORM").ok()?))
+    }
+}
+
+struct VenueRate {
+    window_start: Instant,
+    /// Quotes received in the current window
+    count: u64,
+    /// Moving quotes per second over normal windows
+    baseline: Option<f64>,
+    conflated: bool,
+    /// Windows in a row back under the threshold while conflated
+    calm: u32,
+    /// When a quote was last forwarded per symbol while conflated
+    forwarded: HashMap<Symbol, Instant>,
+}
+
+impl VenueRate {
+    fn new(now: Instant) -> Self {
+        Self { window_start: now, count: 0, baseline: None, conflated: false, calm: 0, forwarded: HashMap::new() }
+    }
+}
+
+/// Detects quote storms per venue and conflates the storming venue's quotes
+pub struct QuoteStormGuard {
+    config: QuoteStormConfig,
+    events: Option<EventBus>,
+    venues: Mutex<HashMap<VenueId, VenueRate>>,
+}
+
+impl QuoteStormGuard {
+    pub fn new(config: QuoteStormConfig) -> Self {
+        Self { config, events: None, venues: Mutex::new(HashMap::new()) }
+    }
+
+    /// Alert as venues enter and leave conflated mode
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Quotes per second a window must exceed to count as a storm
+    fn threshold(&self, baseline: Option<f64>) -> f64 {
+        (baseline.unwrap_or(0.0) * self.config.multiple).max(self.config.min_rate)
+    }
+
+    /// Whether `venue` is conflated
+    pub fn is_conflated(&self, venue: &str) -> bool {
+        self.venues.lock().unwrap().get(venue).is_some_and(|v| v.conflated)
+    }
+
+    fn publish(&self, venue: &str, active: bool, detail: String) {
+        if let Some(events) = &self.events {
+            events.publish(EngineEvent::QuoteStorm { venue: venue.to_string(), active, detail });
+        }
+    }
+
+    /// Count `quote` against its venue's rate, returning whether it should
+    /// be forwarded
+    pub fn admit(&self, quote: &Quote, now: Instant) -> bool {
+        let mut transition = None;
+        let forward = {
+            let mut venues = self.venues.lock().unwrap();
+            let rate = venues.entry(quote.venue).or_insert_with(|| VenueRate::new(now));
+
+            let elapsed = now.saturating_duration_since(rate.window_start);
+            if elapsed >= STORM_WINDOW {
+                let qps = rate.count as f64 / elapsed.as_secs_f64();
+                let threshold = self.threshold(rate.baseline);
+                if rate.conflated {
+                    QUOTE_STORM_INTERVALS.with_label_values(&[&quote.venue]).inc();
+                    rate.calm = if qps <= threshold { rate.calm + 1 } else { 0 };
+                    if rate.calm >= RECOVERY_WINDOWS {
+                        rate.conflated = false;
+                        rate.calm = 0;
+                        rate.forwarded.clear();
+                        transition = Some((false, format!("{:.0} quotes/s, threshold {:.0}", qps, threshold)));
+                    }
+                } else if qps > threshold {
+                    rate.conflated = true;
+                    transition = Some((true, format!("{:.0} quotes/s, threshold {:.0}", qps, threshold)));
+                } else {
+                    rate.baseline = Some(match rate.baseline {
+                        Some(baseline) => baseline + BASELINE_WEIGHT * (qps - baseline),
+                        None => qps,
+                    });
+                }
+                rate.window_start = now;
+                rate.count = 0;
+            }
+            rate.count += 1;
+
+            // Catch a storm within its first window rather than after it
+            if !rate.conflated && rate.count as f64 > self.threshold(rate.baseline) * STORM_WINDOW.as_secs_f64() {
+                rate.conflated = true;
+                transition = Some((true, format!("{} quotes within {:?}", rate.count, now.saturating_duration_since(rate.window_start))));
+            }
+
+            if !rate.conflated {
+                true
+            } else {
+                let due = rate.forwarded.get(&quote.symbol)
+                    .is_none_or(|last| now.saturating_duration_since(*last) >= self.config.conflate_interval);
+                if due {
+                    rate.forwarded.insert(quote.symbol, now);
+                }
+                due
+            }
+        };
+
+        match transition {
+            Some((true, detail)) => {
+                warn!(venue = %quote.venue, detail = %detail, "Quote storm, conflating venue");
+                QUOTE_STORM_ACTIVE.with_label_values(&[&quote.venue]).set(1.0);
+                self.publish(&quote.venue, true, detail);
+            }
+            Some((false, detail)) => {
+                info!(venue = %quote.venue, detail = %detail, "Quote storm over");
+                QUOTE_STORM_ACTIVE.with_label_values(&[&quote.venue]).set(0.0);
+                self.publish(&quote.venue, false, detail);
+            }
+            None => {}
+        }
+
+        if !forward {
+            QUOTES_CONFLATED.with_label_values(&[&quote.venue]).inc();
+        }
+        forward
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(venue: &str, symbol: &str) -> Quote {
+        Quote {
+            symbol: symbol.into(),
+            bid: 100.0,
+            ask: 101.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: venue.into(),
+            timestamp: 1,
+        }
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = QuoteStormConfig::parse("multiple:50, min_rate:200, conflate_ms:20, multiple:0.5, bogus");
+        assert_eq!(config, QuoteStormConfig { multiple: 50.0, min_rate: 200.0, conflate_interval: Duration::from_millis(20) });
+    }
+
+    #[test]
+    fn test_storm_conflates_then_recovers() {
+        let events = EventBus::new(16);
+        let mut alerts = events.subscribe();
+        let config = QuoteStormConfig { multiple: 10.0, min_rate: 50.0, conflate_interval: Duration::from_millis(100) };
+        let guard = QuoteStormGuard::new(config).with_events(events);
+        let start = Instant::now();
+        let ms = |ms: u64| start + Duration::from_millis(ms);
+
+        // Ten quotes a second establish the baseline
+        for i in 0..30 {
+            assert!(guard.admit(&quote("MOCK", "BTCUSDT"), ms(i * 100)));
+        }
+        assert!(!guard.is_conflated("MOCK"));
+
+        // 101 quotes inside a millisecond are past 10x the baseline
+        let admitted = (0..200).filter(|_| guard.admit(&quote("MOCK", "BTCUSDT"), ms(3000))).count();
+        assert!(guard.is_conflated("MOCK"));
+        assert!(matches!(alerts.try_recv(), Ok(EngineEvent::QuoteStorm { active: true, .. })));
+        assert_eq!(admitted, 101);
+
+        // Conflated: one quote per symbol per 100 ms, other venues untouched
+        assert!(guard.admit(&quote("MOCK", "ETHUSDT"), ms(3010)));
+        assert!(!guard.admit(&quote("MOCK", "BTCUSDT"), ms(3050)));
+        assert!(guard.admit(&quote("MOCK", "BTCUSDT"), ms(3100)));
+        assert!(guard.admit(&quote("OTHER", "BTCUSDT"), ms(3050)));
+
+        // The storm's window is still over the threshold, then three quiet
+        // ones bring the venue back
+        for second in 4..=7 {
+            guard.admit(&quote("MOCK", "BTCUSDT"), ms(second * 1000 + 500));
+        }
+        assert!(!guard.is_conflated("MOCK"));
+        assert!(matches!(alerts.try_recv(), Ok(EngineEvent::QuoteStorm { active: false, .. })));
+        assert!(guard.admit(&quote("MOCK", "BTCUSDT"), ms(7501)));
+    }
+}
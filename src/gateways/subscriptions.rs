@@ -0,0 +1,159 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::HftError;
+
+/// The canonical symbols each venue should stream, e.g. BTC on Binance and
+/// ETH on Kraken only. Venues without an entry stream nothing.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SubscriptionSpec {
+    venues: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl SubscriptionSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stream `symbols` on `venue`, in addition to any already listed
+    pub fn with_venue<I, S>(mut self, venue: &str, symbols: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.venues.entry(venue.to_string()).or_default().extend(symbols.into_iter().map(Into::into));
+        self
+    }
+
+    /// Read `HFT_SUBSCRIPTIONS` as comma separated `VENUE=SYMBOL;SYMBOL`
+    /// entries, e.g. `BINANCE_FUTURES=BTC-USD;ETH-USD,KRAKEN=ETH-USD`
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var("HFT_SUBSCRIPTIONS").ok()?;
+        Some(Self::parse(&spec))
+    }
+
+    fn parse(spec: &str) -> Self {
+        let mut parsed = Self::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once('=') {
+                Some((venue, symbols)) if !venue.trim().is_empty() => {
+                    let symbols = symbols.split(';').map(str::trim).filter(|s| !s.is_empty());
+                    parsed = parsed.with_venue(&venue.trim().to_uppercase(), symbols);
+                }
+                _ => warn!(entry = entry, "Ignoring malformed subscription"),
+            }
+        }
+        parsed
+    }
+
+    /// Symbols `venue` should stream
+    pub fn symbols(&self, venue: &str) -> impl Iterator<Item = &String> {
+        self.venues.get(venue).into_iter().flatten()
+    }
+
+    /// Venues with an entry, subscribed or not
+    pub fn venues(&self) -> impl Iterator<Item = &String> {
+        self.venues.keys()
+    }
+
+    /// Every symbol on any venue, sorted
+    pub fn all_symbols(&self) -> Vec<String> {
+        let symbols: BTreeSet<&String> = self.venues.values().flatten().collect();
+        symbols.into_iter().cloned().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.venues.values().all(BTreeSet::is_empty)
+    }
+}
+
+/// What [`QuoteGateway::apply`](super::quote::QuoteGateway::apply) changed,
+/// per venue
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubscriptionChanges {
+    pub added: BTreeMap<String, Vec<String>>,
+    pub removed: BTreeMap<String, Vec<String>>,
+}
+
+impl SubscriptionChanges {
+    /// Symbols added on at least one venue, sorted
+    pub fn added_symbols(&self) -> Vec<String> {
+        let symbols: BTreeSet<&String> = self.added.values().flatten().collect();
+        symbols.into_iter().cloned().collect()
+    }
+
+    /// Symbols removed from at least one venue, sorted
+    pub fn removed_symbols(&self) -> Vec<String> {
+        let symbols: BTreeSet<&String> = self.removed.values().flatten().collect();
+        symbols.into_iter().cloned().collect()
+    }
+}
+
+/// File holding the subscribed symbols per venue, rewritten whenever they
+/// change so intraday subscriptions survive a restart
+#[derive(Debug, Clone)]
+pub struct SubscriptionStore {
+    path: PathBuf,
+}
+
+impl SubscriptionStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Store at `HFT_SUBSCRIPTIONS_FILE`, if set
+    pub fn from_env() -> Option<Self> {
+        std::env::var("HFT_SUBSCRIPTIONS_FILE").ok()
+            .filter(|path| !path.is_empty())
+            .map(Self::new)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The persisted subscriptions; a missing file means none
+    pub fn load(&self) -> Result<SubscriptionSpec, HftError> {
+        let json = match std::fs::read(&self.path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(SubscriptionSpec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_slice(&json)
+            .map_err(|e| HftError::Config(format!("Invalid subscriptions {}: {}", self.path.display(), e)))
+    }
+
+    /// Replace the persisted subscriptions, atomically like snapshots
+    pub fn save(&self, spec: &SubscriptionSpec) -> Result<(), HftError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_vec_pretty(spec)
+            .map_err(|e| HftError::Serialization(format!("Failed to serialize subscriptions: {}", e)))?;
+
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &self.path)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spec() {
+        let spec = SubscriptionSpec::parse("binance_futures=BTC-USD; ETH-USD,KRAKEN=ETH-USD,,bogus");
+
+        assert_eq!(spec.symbols("BINANCE_FUTURES").collect::<Vec<_>>(), vec!["BTC-USD", "ETH-USD"]);
+        assert_eq!(spec.symbols("KRAKEN").collect::<Vec<_>>(), vec!["ETH-USD"]);
+        assert_eq!(spec.symbols("COINBASE").count(), 0);
+        assert_eq!(spec.all_symbols(), vec!["BTC-USD", "ETH-USD"]);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use warp::http::StatusCode;
+use warp::Filter;
+
+use crate::events::EngineEvent;
+
+/// Which probe a component counts towards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Probe {
+    /// Failing components make both `/healthz` and `/readyz` fail
+    Liveness,
+    /// Failing components only make `/readyz` fail
+    Readiness,
+}
+
+struct ComponentState {
+    probe: Probe,
+    /// Component is unhealthy if it has not beaten for this long
+    max_silence: Option<Duration>,
+    last_beat_ms: AtomicU64,
+    healthy: AtomicBool,
+    detail: Mutex<Option<String>>,
+}
+
+/// Handle a component uses to report its health.
+///
+/// Beating is a single atomic store, cheap enough for the quote path.
+#[derive(Clone)]
+pub struct Heartbeat {
+    epoch: Instant,
+    state: Arc<ComponentState>,
+}
+
+impl Heartbeat {
+    pub fn beat(&self) {
+        let elapsed = self.epoch.elapsed().as_millis() as u64;
+        self.state.last_beat_ms.store(elapsed, Ordering::Relaxed);
+    }
+
+    pub fn set_healthy(&self) {
+        self.state.healthy.store(true, Ordering::Relaxed);
+        *self.state.detail.lock().unwrap() = None;
+    }
+
+    pub fn set_unhealthy(&self, detail: &str) {
+        self.state.healthy.store(false, Ordering::Relaxed);
+        *self.state.detail.lock().unwrap() = Some(detail.to_string());
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentStatus {
+    pub healthy: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub last_beat_ms_ago: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub status: &'static str,
+    pub failing: Vec<String>,
+    pub components: BTreeMap<String, ComponentStatus>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.failing.is_empty()
+    }
+}
+
+/// Aggregates component heartbeats for the health endpoints
+#[derive(Clone)]
+pub struct HealthRegistry {
+    epoch: Instant,
+    components: Arc<RwLock<BTreeMap<String, Arc<ComponentState>>>>,
+}
+
+impl Default for HealthRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthRegistry {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            components: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    /// Register a component, replacing any existing one with the same name.
+    ///
+    /// Components start healthy with a fresh heartbeat.
+    pub fn register(&self, name: &str, probe: Probe, max_silence: Option<Duration>) -> Heartbeat {
+        let state = Arc::new(ComponentState {
+            probe,
+            max_silence,
+            last_beat_ms: AtomicU64::new(self.epoch.elapsed().as_millis() as u64),
+            healthy: AtomicBool::new(true),
+            detail: Mutex::new(None),
+        });
+
+        self.components.write().unwrap().insert(name.to_string(), Arc::clone(&state));
+
+        Heartbeat {
+            epoch: self.epoch,
+            state,
+        }
+    }
+
+    /// Register a venue as a readiness component that starts disconnected
+    pub fn register_venue(&self, venue: &str) -> Heartbeat {
+        let heartbeat = self.register(&format!("venue:{}", venue), Probe::Readiness, None);
+        heartbeat.set_unhealthy("not connected");
+        heartbeat
+    }
+
+    fn component(&self, name: &str) -> Option<Heartbeat> {
+        self.components.read().unwrap().get(name).map(|state| Heartbeat {
+            epoch: self.epoch,
+            state: Arc::clone(state),
+        })
+    }
+
+    /// Build a report for the given probe
+    pub fn report(&self, probe: Probe) -> HealthReport {
+        let now_ms = self.epoch.elapsed().as_millis() as u64;
+        let mut failing = Vec::new();
+        let mut statuses = BTreeMap::new();
+
+        for (name, state) in self.components.read().unwrap().iter() {
+            if probe == Probe::Liveness && state.probe != Probe::Liveness {
+                continue;
+            }
+
+            let last_beat_ms_ago = now_ms.saturating_sub(state.last_beat_ms.load(Ordering::Relaxed));
+            let mut healthy = state.healthy.load(Ordering::Relaxed);
+            let mut detail = state.detail.lock().unwrap().clone();
+
+            if let Some(max_silence) = state.max_silence {
+                if healthy && last_beat_ms_ago > max_silence.as_millis() as u64 {
+                    healthy = false;
+                    detail = Some(format!("no heartbeat for {}ms", last_beat_ms_ago));
+                }
+            }
+
+            if !healthy {
+                failing.push(name.clone());
+            }
+
+            statuses.insert(name.clone(), ComponentStatus {
+                healthy,
+                detail,
+                last_beat_ms_ago,
+            });
+        }
+
+        HealthReport {
+            status: if failing.is_empty() { "ok" } else { "unhealthy" },
+            failing,
+            components: statuses,
+        }
+    }
+
+    /// Follow venue connect/disconnect events
+    pub async fn track_venues(self, mut events: broadcast::Receiver<EngineEvent>) {
+        loop {
+            match events.recv().await {
+                Ok(EngineEvent::VenueConnected { venue }) => {
+                    let heartbeat = self.component(&format!("venue:{}", venue))
+                        .unwrap_or_else(|| self.register_venue(&venue));
+                    heartbeat.set_healthy();
+                    heartbeat.beat();
+                }
+                Ok(EngineEvent::VenueDisconnected { venue, reason }) => {
+                    let heartbeat = self.component(&format!("venue:{}", venue))
+                        .unwrap_or_else(|| self.register_venue(&venue));
+                    heartbeat.set_unhealthy(&reason);
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+fn reply(report: HealthReport) -> warp::reply::WithStatus<warp::reply::Json> {
+    let status = if report.is_healthy() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    warp::reply::with_status(warp::reply::json(&report), status)
+}
+
+/// `/healthz` (liveness) and `/readyz` (readiness) routes
+pub fn routes(
+    registry: HealthRegistry,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let live = registry.clone();
+    let healthz = warp::path("healthz")
+        .and(warp::get())
+        .map(move || reply(live.report(Probe::Liveness)));
+
+    let readyz = warp::path("readyz")
+        .and(warp::get())
+        .map(move || reply(registry.report(Probe::Readiness)));
+
+    healthz.or(readyz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_heartbeat_fails_liveness() {
+        let registry = HealthRegistry::new();
+        let book_builder = registry.register("book_builder", Probe::Liveness, Some(Duration::from_millis(0)));
+        registry.register_venue("MOCK");
+
+        std::thread::sleep(Duration::from_millis(5));
+        let report = registry.report(Probe::Liveness);
+        assert_eq!(report.failing, vec!["book_builder".to_string()]);
+
+        // The disconnected venue only shows up in readiness
+        assert!(!report.components.contains_key("venue:MOCK"));
+
+        book_builder.beat();
+        let report = registry.report(Probe::Readiness);
+        assert_eq!(report.failing, vec!["venue:MOCK".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_endpoints_return_503_naming_component() {
+        let registry = HealthRegistry::new();
+        registry.register("order_gateway", Probe::Liveness, None)
+            .set_unhealthy("order queue backlog 900");
+
+        let response = warp::test::request()
+            .path("/healthz")
+            .reply(&routes(registry.clone()))
+            .await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["failing"][0], "order_gateway");
+        assert_eq!(body["components"]["order_gateway"]["detail"], "order queue backlog 900");
+
+        registry.component("order_gateway").unwrap().set_healthy();
+        let response = warp::test::request()
+            .path("/readyz")
+            .reply(&routes(registry))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, RwLock};
+use tracing::{info, warn};
+
+use crate::book::OrderBook;
+use crate::metrics::HEDGE_ORDERS;
+use crate::risk::RiskManager;
+use crate::types::{Order, OrderSide, OrderType, Symbol};
+
+const DEFAULT_PASSIVE_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    /// Rest at the near touch
+    Passive,
+    /// Cross the spread
+    Aggressive,
+}
+
+impl fmt::Display for Urgency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Urgency::Passive => write!(f, "passive"),
+            Urgency::Aggressive => write!(f, "aggressive"),
+        }
+    }
+}
+
+/// Where and when to hedge one asset
+#[derive(Debug, Clone)]
+pub struct HedgeTarget {
+    pub venue: String,
+    pub symbol: String,
+    /// Net position tolerated either side of flat before hedging
+    pub band: f64,
+    /// How long a passive hedge may work before crossing the spread
+    pub passive_timeout: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct HedgeConfig {
+    /// Hedge targets keyed by base asset
+    pub assets: HashMap<String, HedgeTarget>,
+    pub interval: Duration,
+}
+
+impl Default for HedgeConfig {
+    fn default() -> Self {
+        Self {
+            assets: HashMap::new(),
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+}
+
+impl HedgeConfig {
+    /// Read `HFT_HEDGE_TARGETS` as comma separated `ASSET=VENUE/SYMBOL/BAND`
+    /// entries, e.g. `BTC=BINANCE_FUTURES/BTCUSDT/0.5`, and the passive
+    /// timeout from `HFT_HEDGE_PASSIVE_SECS`
+    pub fn from_env() -> Option<Self> {
+        let targets = std::env::var("HFT_HEDGE_TARGETS").ok()?;
+        let passive_timeout = std::env::var("HFT_HEDGE_PASSIVE_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Duration::from_secs_f64)
+            .unwrap_or(DEFAULT_PASSIVE_TIMEOUT);
+
+        let mut config = Self::default();
+        for entry in targets.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=').and_then(|(asset, target)| {
+                let mut parts = target.split('/');
+                let venue = parts.next()?.to_string();
+                let symbol = parts.next()?.to_string();
+                let band = parts.next()?.parse().ok()?;
+                Some((asset.to_uppercase(), HedgeTarget { venue, symbol, band, passive_timeout }))
+            });
+            match parsed {
+                Some((asset, target)) => {
+                    config.assets.insert(asset, target);
+                }
+                None => warn!(entry = entry, "Ignoring malformed hedge target"),
+            }
+        }
+
+        (!config.assets.is_empty()).then_some(config)
+    }
+}
+
+struct WorkingHedge {
+    side: OrderSide,
+    sent_at: Instant,
+}
+
+/// Keeps net inventory per asset inside its band.
+///
+/// When an asset drifts out of band the hedger first rests an order at the
+/// near touch on the hedge venue. If the position is still out of band
+/// after the passive timeout it sends a market order for the remainder,
+/// repeating at that interval until the position is back inside the band.
+pub struct Hedger {
+    config: HedgeConfig,
+    risk: Arc<RiskManager>,
+    books: Arc<RwLock<HashMap<Symbol, OrderBook>>>,
+    /// The order gateway's queue, which risk-checks hedges like any order
+    order_tx: mpsc::Sender<Order>,
+    working: HashMap<String, WorkingHedge>,
+}
+
+impl Hedger {
+    pub fn new(
+        config: HedgeConfig,
+        risk: Arc<RiskManager>,
+        books: Arc<RwLock<HashMap<Symbol, OrderBook>>>,
+        order_tx: mpsc::Sender<Order>,
+    ) -> Self {
+        Self { config, risk, books, order_tx, working: HashMap::new() }
+    }
+
+    /// Decide the hedge for one asset given its current net position
+    fn plan(&mut self, asset: &str, net: f64, book: Option<&OrderBook>, now: Instant) -> Option<(Order, Urgency)> {
+        let target = self.config.assets.get(asset)?;
+        if net.abs() <= target.band {
+            self.working.remove(asset);
+            return None;
+        }
+
+        let side = if net > 0.0 { OrderSide::Sell } else { OrderSide::Buy };
+        let urgency = match self.working.get(asset) {
+            Some(w) if w.side == side && now.duration_since(w.sent_at) < target.passive_timeout => return None,
+            Some(w) if w.side == side => Urgency::Aggressive,
+            _ => Urgency::Passive,
+        };
+
+        let passive_timeout = target.passive_timeout;
+        let (price, order_type) = match urgency {
+            Urgency::Passive => {
+                let touch = match side {
+                    OrderSide::Buy => book.and_then(OrderBook::best_bid),
+                    OrderSide::Sell => book.and_then(OrderBook::best_ask),
+                };
+                match touch {
+                    Some((price, _)) => (price, OrderType::Limit),
+                    // Without a book there is nothing to rest against
+                    None => (0.0, OrderType::Market),
+                }
+            }
+            Urgency::Aggressive => (0.0, OrderType::Market),
+        };
+
+        self.working.insert(asset.to_string(), WorkingHedge { side, sent_at: now });
+        Some((
+            Order {
+                symbol: target.symbol.as_str().into(),
+                side,
+                quantity: net.abs(),
+                price,
+                venue: target.venue.as_str().into(),
+                order_type,
+                // A passive hedge is replaced by a market order once it times out
+                expire_after: (order_type == OrderType::Limit).then_some(passive_timeout.as_millis() as u64),
+                strategy: Some("hedger".to_string()),
+                bypass_kill_switch: false,
+            },
+            urgency,
+        ))
+    }
+
+    pub async fn tick(&mut self) {
+        let nets = self.risk.net_by_asset().await;
+        let books = Arc::clone(&self.books);
+        let books = books.read().await;
+        let now = Instant::now();
+
+        let assets: Vec<String> = self.config.assets.keys().cloned().collect();
+        let mut orders = Vec::new();
+        for asset in assets {
+            let net = nets.get(&asset).copied().unwrap_or(0.0);
+            let symbol = &self.config.assets[&asset].symbol;
+            let book = books.get(symbol.as_str());
+            if let Some(planned) = self.plan(&asset, net, book, now) {
+                orders.push((asset, net, planned));
+            }
+        }
+        drop(books);
+
+        // The order gateway runs the pre-trade risk checks
+        for (asset, net, (order, urgency)) in orders {
+            info!(asset = %asset, net = net, side = ?order.side, quantity = order.quantity, urgency = %urgency, "Sending hedge");
+            HEDGE_ORDERS.with_label_values(&[&asset, &urgency.to_string()]).inc();
+            if let Err(e) = self.order_tx.try_send(order) {
+                warn!(asset = %asset, error = %e, "Failed to queue hedge order");
+            }
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(self.config.interval);
+        loop {
+            interval.tick().await;
+            self.tick().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::LossLimits;
+    use crate::types::Quote;
+
+    fn hedger() -> Hedger {
+        let (order_tx, _) = mpsc::channel(8);
+        let config = HedgeConfig {
+            assets: HashMap::from([(
+                "BTC".to_string(),
+                HedgeTarget {
+                    venue: "HEDGE".to_string(),
+                    symbol: "BTCUSDT".to_string(),
+                    band: 0.5,
+                    passive_timeout: Duration::from_secs(5),
+                },
+            )]),
+            ..HedgeConfig::default()
+        };
+        Hedger::new(
+            config,
+            Arc::new(RiskManager::new(LossLimits::default())),
+            Arc::new(RwLock::new(HashMap::new())),
+            order_tx,
+        )
+    }
+
+    #[test]
+    fn test_passive_then_aggressive() {
+        let mut hedger = hedger();
+        let mut book = OrderBook::new("BTCUSDT".into());
+        book.update(&Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 50000.0,
+            ask: 50001.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "HEDGE".into(),
+            timestamp: 1,
+        });
+        let start = Instant::now();
+
+        // Inside the band nothing happens
+        assert!(hedger.plan("BTC", 0.4, Some(&book), start).is_none());
+
+        // Long inventory is first offered at the ask
+        let (order, urgency) = hedger.plan("BTC", 2.0, Some(&book), start).unwrap();
+        assert_eq!(urgency, Urgency::Passive);
+        assert_eq!(order.side, OrderSide::Sell);
+        assert_eq!(order.price, 50001.0);
+        assert_eq!(order.quantity, 2.0);
+
+        // The passive order is left to work until the timeout
+        assert!(hedger.plan("BTC", 1.5, Some(&book), start + Duration::from_secs(1)).is_none());
+
+        let (order, urgency) = hedger.plan("BTC", 1.5, Some(&book), start + Duration::from_secs(6)).unwrap();
+        assert_eq!(urgency, Urgency::Aggressive);
+        assert_eq!(order.order_type, OrderType::Market);
+        assert_eq!(order.quantity, 1.5);
+
+        // Back inside the band resets, so the next drift starts passive again
+        assert!(hedger.plan("BTC", 0.1, Some(&book), start + Duration::from_secs(7)).is_none());
+        let (_, urgency) = hedger.plan("BTC", -1.0, Some(&book), start + Duration::from_secs(8)).unwrap();
+        assert_eq!(urgency, Urgency::Passive);
+    }
+}
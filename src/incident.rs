@@ -0,0 +1,261 @@
+//! The last raw venue messages and engine events, kept in memory per venue
+//! and dumped to disk when something goes wrong, so an incident can be
+//! pieced together without verbose logging left on.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+use warp::Filter;
+
+use crate::error::HftError;
+use crate::events::EngineEvent;
+
+const DEFAULT_CAPACITY: usize = 1000;
+const DEFAULT_DIR: &str = "incidents";
+
+/// Automatic dumps closer together than this are skipped, so a component
+/// panicking in a loop writes one dump rather than one per panic
+const AUTO_DUMP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Where events that concern no venue are kept
+const ENGINE: &str = "engine";
+
+/// How much is kept and where dumps go
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncidentConfig {
+    /// Entries kept per venue, oldest dropped first; 0 keeps nothing
+    pub capacity: usize,
+    pub dir: PathBuf,
+}
+
+impl Default for IncidentConfig {
+    fn default() -> Self {
+        Self { capacity: DEFAULT_CAPACITY, dir: PathBuf::from(DEFAULT_DIR) }
+    }
+}
+
+impl IncidentConfig {
+    /// Read `HFT_INCIDENT_BUFFER` and `HFT_INCIDENT_DIR`, with unset ones
+    /// at their defaults
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(capacity) = std::env::var("HFT_INCIDENT_BUFFER") {
+            match capacity.parse() {
+                Ok(capacity) => config.capacity = capacity,
+                Err(_) => warn!("Ignoring malformed HFT_INCIDENT_BUFFER"),
+            }
+        }
+        if let Ok(dir) = std::env::var("HFT_INCIDENT_DIR") {
+            config.dir = PathBuf::from(dir);
+        }
+        config
+    }
+}
+
+/// A raw message or an engine event, stamped with when it was seen in
+/// milliseconds since the epoch
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IncidentEntry {
+    Message { at: i64, text: String },
+    Event { at: i64, event: EngineEvent },
+}
+
+impl IncidentEntry {
+    pub fn at(&self) -> i64 {
+        match self {
+            IncidentEntry::Message { at, .. } | IncidentEntry::Event { at, .. } => *at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Dump<'a> {
+    reason: &'a str,
+    dumped_at: i64,
+    /// Oldest entry first per venue, with venue-less events under `engine`
+    venues: BTreeMap<String, Vec<IncidentEntry>>,
+}
+
+/// Ring buffers of recent entries per venue. Clones share the buffers.
+#[derive(Debug, Clone, Default)]
+pub struct IncidentLog {
+    config: Arc<IncidentConfig>,
+    venues: Arc<Mutex<HashMap<String, VecDeque<IncidentEntry>>>>,
+    auto_dumped: Arc<Mutex<Option<Instant>>>,
+}
+
+impl IncidentLog {
+    pub fn new(config: IncidentConfig) -> Self {
+        Self { config: Arc::new(config), ..Self::default() }
+    }
+
+    /// Where dumps are written
+    pub fn dir(&self) -> &Path {
+        &self.config.dir
+    }
+
+    fn push(&self, venue: &str, entry: IncidentEntry) {
+        if self.config.capacity == 0 {
+            return;
+        }
+        // A panic elsewhere while holding the lock leaves the buffers intact
+        let mut venues = self.venues.lock().unwrap_or_else(PoisonError::into_inner);
+        let buffer = match venues.get_mut(venue) {
+            Some(buffer) => buffer,
+            None => venues.entry(venue.to_string()).or_default(),
+        };
+        if buffer.len() >= self.config.capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(entry);
+    }
+
+    /// Keep a raw message as `venue` sent it
+    pub fn record_message(&self, venue: &str, text: &str) {
+        self.push(venue, IncidentEntry::Message { at: Utc::now().timestamp_millis(), text: text.to_string() });
+    }
+
+    /// Keep an engine event with the venue it concerns
+    pub fn record_event(&self, event: &EngineEvent) {
+        let venue = event.venue().unwrap_or(ENGINE).to_string();
+        self.push(&venue, IncidentEntry::Event { at: Utc::now().timestamp_millis(), event: event.clone() });
+    }
+
+    /// Everything kept so far, oldest first per venue
+    pub fn entries(&self) -> BTreeMap<String, Vec<IncidentEntry>> {
+        self.venues.lock().unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(venue, buffer)| (venue.clone(), buffer.iter().cloned().collect()))
+            .collect()
+    }
+
+    /// Write everything kept to a new file in the dump directory, returning
+    /// its path. The buffers are left as they are.
+    pub fn dump(&self, reason: &str) -> Result<PathBuf, HftError> {
+        let dumped_at = Utc::now().timestamp_millis();
+        let dump = Dump { reason, dumped_at, venues: self.entries() };
+        std::fs::create_dir_all(&self.config.dir)?;
+        let path = (0..)
+            .map(|n| match n {
+                0 => self.config.dir.join(format!("incident-{}.json", dumped_at)),
+                n => self.config.dir.join(format!("incident-{}-{}.json", dumped_at, n)),
+            })
+            .find(|path| !path.exists())
+            .expect("unbounded candidates");
+        let json = serde_json::to_vec_pretty(&dump)
+            .map_err(|e| HftError::Config(format!("Failed to serialize incident dump: {}", e)))?;
+        std::fs::write(&path, json)?;
+        info!(path = %path.display(), reason, "Dumped incident log");
+        Ok(path)
+    }
+
+    /// Dump unless another automatic dump was written within
+    /// [`AUTO_DUMP_INTERVAL`]
+    fn auto_dump(&self, reason: &str) -> Option<PathBuf> {
+        {
+            let mut dumped = self.auto_dumped.lock().unwrap_or_else(PoisonError::into_inner);
+            if dumped.is_some_and(|at| at.elapsed() < AUTO_DUMP_INTERVAL) {
+                return None;
+            }
+            *dumped = Some(Instant::now());
+        }
+        match self.dump(reason) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                error!(error = %e, reason, "Failed to dump incident log");
+                None
+            }
+        }
+    }
+
+    /// Keep every engine event, dumping when the kill switch engages
+    pub async fn run(self, mut events: broadcast::Receiver<EngineEvent>) {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    self.record_event(&event);
+                    if let EngineEvent::KillSwitchEngaged { reason } = &event {
+                        self.auto_dump(&format!("kill switch: {}", reason));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(missed)) => warn!(missed, "Incident log missed events"),
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    }
+
+    /// Dump on any panic before the panic hook already installed runs
+    pub fn dump_on_panic(&self) {
+        let log = self.clone();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |panic| {
+            log.auto_dump(&format!("panic: {}", panic));
+            previous(panic);
+        }));
+    }
+}
+
+/// `POST /admin/incidents/dump` to write the incident log to disk
+pub fn routes(
+    incidents: IncidentLog,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "incidents" / "dump")
+        .and(warp::post())
+        .map(move || {
+            let (reply, status) = match incidents.dump("admin request") {
+                Ok(path) => (serde_json::json!({ "path": path }), warp::http::StatusCode::OK),
+                Err(e) => (serde_json::json!({ "error": e.to_string() }), warp::http::StatusCode::INTERNAL_SERVER_ERROR),
+            };
+            warp::reply::with_status(warp::reply::json(&reply), status)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+
+    #[tokio::test]
+    async fn test_ring_buffers_dumped_on_kill_switch_and_request() {
+        let dir = std::env::temp_dir().join(format!("hft_incidents_{}", std::process::id()));
+        let log = IncidentLog::new(IncidentConfig { capacity: 2, dir: dir.clone() });
+        for text in ["1", "2", "3"] {
+            log.record_message("BINANCE_FUTURES", text);
+        }
+
+        let events = EventBus::new(16);
+        let task = tokio::spawn(log.clone().run(events.subscribe()));
+        events.publish(EngineEvent::VenueDisconnected { venue: "BINANCE_FUTURES".to_string(), reason: "eof".to_string() });
+        events.publish(EngineEvent::KillSwitchEngaged { reason: "daily loss".to_string() });
+        // Within the interval of the first, the second dump is skipped
+        events.publish(EngineEvent::KillSwitchEngaged { reason: "again".to_string() });
+        drop(events);
+        task.await.unwrap();
+
+        let dumps: Vec<PathBuf> = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).collect();
+        assert_eq!(dumps.len(), 1);
+        let dump: serde_json::Value = serde_json::from_slice(&std::fs::read(&dumps[0]).unwrap()).unwrap();
+        assert_eq!(dump["reason"], "kill switch: daily loss");
+        let binance = dump["venues"]["BINANCE_FUTURES"].as_array().unwrap();
+        assert_eq!(binance.len(), 2);
+        assert_eq!((&binance[0]["kind"], &binance[0]["text"]), (&"message".into(), &"3".into()));
+        assert_eq!(binance[1]["event"]["type"], "venue_disconnected");
+        assert_eq!(dump["venues"]["engine"][0]["event"]["reason"], "daily loss");
+
+        // Requests always dump, with the buffers as they are now
+        let response = warp::test::request().method("POST").path("/admin/incidents/dump").reply(&routes(log.clone())).await;
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        let dump: serde_json::Value = serde_json::from_slice(&std::fs::read(body["path"].as_str().unwrap()).unwrap()).unwrap();
+        assert_eq!(dump["reason"], "admin request");
+        assert_eq!(dump["venues"]["engine"].as_array().unwrap().len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use tracing::warn;
+
+use crate::types::{Symbol, VenueId};
+
+/// Canonical instrument IDs and what each venue calls them.
+///
+/// Strategies, books, risk and the order tracker deal only in canonical
+/// IDs; the gateways translate at the venue boundary. Symbols without a
+/// mapping pass through unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentMap {
+    /// `(venue, canonical ID)` to venue symbol
+    to_venue: HashMap<(VenueId, Symbol), Symbol>,
+    /// `(venue, venue symbol)` to canonical ID
+    to_canonical: HashMap<(VenueId, Symbol), Symbol>,
+}
+
+impl InstrumentMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map `canonical` to `venue_symbol` on `venue`
+    pub fn insert(&mut self, canonical: &str, venue: &str, venue_symbol: &str) {
+        let (canonical, venue, venue_symbol) = (Symbol::new(canonical), VenueId::new(venue), Symbol::new(venue_symbol));
+        self.to_venue.insert((venue, canonical), venue_symbol);
+        self.to_canonical.insert((venue, venue_symbol), canonical);
+    }
+
+    /// Read `HFT_INSTRUMENTS` as comma separated `ID=VENUE:SYMBOL;VENUE:SYMBOL`
+    /// entries, e.g. `BTC-USD=BINANCE_FUTURES:BTCUSDT;KRAKEN:XBT/USD`
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var("HFT_INSTRUMENTS").ok()?;
+        Some(Self::parse(&spec))
+    }
+
+    fn parse(spec: &str) -> Self {
+        let mut map = Self::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((canonical, venues)) = entry.split_once('=') else {
+                warn!(entry = entry, "Ignoring malformed instrument mapping");
+                continue;
+            };
+            for mapping in venues.split(';').map(str::trim).filter(|m| !m.is_empty()) {
+                match mapping.split_once(':') {
+                    Some((venue, symbol)) if !venue.is_empty() && !symbol.is_empty() => {
+                        map.insert(canonical.trim(), &venue.to_uppercase(), symbol);
+                    }
+                    _ => warn!(instrument = canonical, mapping = mapping, "Ignoring malformed venue symbol"),
+                }
+            }
+        }
+        map
+    }
+
+    /// What `venue` calls the canonical instrument `canonical`
+    pub fn venue_symbol(&self, venue: VenueId, canonical: Symbol) -> Symbol {
+        self.to_venue.get(&(venue, canonical)).copied().unwrap_or(canonical)
+    }
+
+    /// The canonical instrument `venue` calls `venue_symbol`
+    pub fn canonical(&self, venue: VenueId, venue_symbol: Symbol) -> Symbol {
+        self.to_canonical.get(&(venue, venue_symbol)).copied().unwrap_or(venue_symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_translate() {
+        let map = InstrumentMap::parse("BTC-USD=BINANCE_FUTURES:BTCUSDT;kraken:XBT/USD;COINBASE:BTC-USD, ETH-USD=KRAKEN:ETH/USD, bogus");
+
+        assert_eq!(map.venue_symbol("BINANCE_FUTURES".into(), "BTC-USD".into()), "BTCUSDT");
+        assert_eq!(map.venue_symbol("KRAKEN".into(), "BTC-USD".into()), "XBT/USD");
+        assert_eq!(map.venue_symbol("COINBASE".into(), "BTC-USD".into()), "BTC-USD");
+        assert_eq!(map.canonical("KRAKEN".into(), "XBT/USD".into()), "BTC-USD");
+        assert_eq!(map.canonical("KRAKEN".into(), "ETH/USD".into()), "ETH-USD");
+
+        // Unmapped symbols and venues pass through
+        assert_eq!(map.venue_symbol("BINANCE_FUTURES".into(), "ETH-USD".into()), "ETH-USD");
+        assert_eq!(map.canonical("BINANCE_FUTURES".into(), "SOLUSDT".into()), "SOLUSDT");
+    }
+}
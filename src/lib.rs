@@ -1,13 +1,44 @@
+// The metrics lazy_static! block outgrows the default macro recursion limit
+#![recursion_limit = "256"]
+
 pub mod types;
+pub mod instruments;
 pub mod venues;
 pub mod gateways;
 pub mod book;
 pub mod strategy;
+pub mod signals;
 pub mod execution;
+pub mod risk;
 pub mod services;
 pub mod command;
 pub mod metrics;
 pub mod error;
+pub mod events;
+pub mod incident;
+pub mod alerts;
+pub mod health;
+pub mod snapshot;
+pub mod feed;
+pub mod sink;
+pub mod mirror;
+pub mod failover;
+pub mod audit;
+pub mod secrets;
+pub mod scheduler;
+pub mod report;
+pub mod hedger;
+pub mod loadgen;
+pub mod backtest;
+pub mod rng;
+pub mod fees;
+pub mod export;
+
+#[cfg(feature = "python")]
+mod python;
+
+#[cfg(feature = "tui")]
+pub mod tui;
 
 #[cfg(test)]
 pub mod mocks;
\ No newline at end of file
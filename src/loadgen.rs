@@ -0,0 +1,365 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use tokio::sync::{broadcast, mpsc, RwLock};
+
+use crate::book::{BookBuilder, BookChecksums, BookSnapshots, Compactor};
+use crate::command::mode::TradingMode;
+use crate::error::HftError;
+use crate::events::EventBus;
+use crate::execution::{ExecutionEngine, OrderTracker};
+use crate::feed::FeedPublisher;
+use crate::fees::FeeModel;
+use crate::gateways::{order::OrderGateway, quote::QuoteGateway, FailoverPolicies};
+use crate::instruments::InstrumentMap;
+use crate::risk::{LossLimits, RiskManager};
+use crate::types::{Order, OrderSide, OrderType, Quote};
+use crate::venues::{VenueAdapter, VenueRegistry};
+
+/// Venue name carried by synthetic quotes and orders
+const VENUE: &str = "LOADGEN";
+
+/// Feed frames the latency probe may fall behind before frames are dropped
+const FEED_CAPACITY: usize = 1 << 16;
+
+/// How much synthetic traffic to push and how fast
+#[derive(Debug, Clone)]
+pub struct LoadConfig {
+    pub quotes: usize,
+    /// Quotes per second; `None` sends as fast as the pipeline accepts them
+    pub quote_rate: Option<f64>,
+    pub orders: usize,
+    /// Orders per second; `None` sends as fast as the pipeline accepts them
+    pub order_rate: Option<f64>,
+    /// Distinct symbols the traffic is spread across
+    pub symbols: usize,
+}
+
+impl Default for LoadConfig {
+    fn default() -> Self {
+        Self {
+            quotes: 1_000_000,
+            quote_rate: None,
+            orders: 100_000,
+            order_rate: None,
+            symbols: 10,
+        }
+    }
+}
+
+impl LoadConfig {
+    /// Parse `--quotes N --quote-rate R --orders N --order-rate R --symbols N`.
+    /// A rate of 0 means unpaced.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, HftError> {
+        let mut config = Self::default();
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            let value = args.next()
+                .ok_or_else(|| HftError::Config(format!("Missing value for {}", flag)))?;
+            let invalid = |e: &dyn fmt::Display| HftError::Config(format!("Invalid {} {:?}: {}", flag, value, e));
+            let count = || value.replace('_', "").parse::<usize>().map_err(|e| invalid(&e));
+            let rate = || value.replace('_', "").parse::<f64>()
+                .map_err(|e| invalid(&e))
+                .map(|r| Some(r).filter(|r| *r > 0.0));
+            match flag.as_str() {
+                "--quotes" => config.quotes = count()?,
+                "--quote-rate" => config.quote_rate = rate()?,
+                "--orders" => config.orders = count()?,
+                "--order-rate" => config.order_rate = rate()?,
+                "--symbols" => config.symbols = count()?.max(1),
+                _ => return Err(HftError::Config(format!(
+                    "Unknown flag {}; expected --quotes, --quote-rate, --orders, --order-rate or --symbols",
+                    flag
+                ))),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Latency percentiles over every message that made it through
+#[derive(Debug, Clone, Default)]
+pub struct LatencySummary {
+    pub p50: Duration,
+    pub p99: Duration,
+    pub p999: Duration,
+    pub max: Duration,
+}
+
+impl LatencySummary {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+        let at = |q: f64| samples[((samples.len() - 1) as f64 * q).round() as usize];
+        Self { p50: at(0.5), p99: at(0.99), p999: at(0.999), max: samples[samples.len() - 1] }
+    }
+}
+
+/// Outcome of pushing one kind of message through the pipeline
+#[derive(Debug, Clone)]
+pub struct StageReport {
+    pub sent: usize,
+    /// Messages that reached the end of the stage; quotes the latency probe
+    /// fell too far behind to see are not counted
+    pub completed: usize,
+    pub elapsed: Duration,
+    pub latency: LatencySummary,
+}
+
+impl StageReport {
+    /// Completed messages per second
+    pub fn throughput(&self) -> f64 {
+        self.completed as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    fn new(sent: &[Instant], done: Vec<(usize, Instant)>, started: Instant) -> Self {
+        let elapsed = done.iter().map(|(_, at)| *at).max().unwrap_or(started) - started;
+        Self {
+            sent: sent.len(),
+            completed: done.len(),
+            elapsed,
+            latency: LatencySummary::from_samples(
+                done.iter().map(|(i, at)| at.saturating_duration_since(sent[*i])).collect()
+            ),
+        }
+    }
+}
+
+impl fmt::Display for StageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} in {:.2?} ({:.0}/s), p50 {:.1?} p99 {:.1?} p999 {:.1?} max {:.1?}",
+            self.completed, self.sent, self.elapsed, self.throughput(),
+            self.latency.p50, self.latency.p99, self.latency.p999, self.latency.max
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoadReport {
+    /// Quote gateway to book update, timed at the market data feed
+    pub quotes: StageReport,
+    /// Risk check to venue submission
+    pub orders: StageReport,
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "quotes: {}", self.quotes)?;
+        write!(f, "orders: {}", self.orders)
+    }
+}
+
+/// Push synthetic quotes, then synthetic orders, through the same components
+/// the engine runs and report what they sustained
+pub async fn run(config: &LoadConfig) -> Result<LoadReport, HftError> {
+    let symbols: Vec<String> = (0..config.symbols.max(1)).map(|i| format!("LOAD{}USDT", i)).collect();
+    Ok(LoadReport {
+        quotes: run_quotes(config, &symbols).await?,
+        orders: run_orders(config, &symbols).await?,
+    })
+}
+
+/// Sleep until message `i` is due. Latency is measured from when a message
+/// was due rather than when it went out, so a stalled pipeline cannot hide
+/// the messages queued up behind the stall.
+async fn pace(started: Instant, rate: Option<f64>, i: usize) -> Instant {
+    match rate {
+        Some(rate) => {
+            let due = started + Duration::from_secs_f64(i as f64 / rate);
+            tokio::time::sleep_until(due.into()).await;
+            due
+        }
+        None => Instant::now(),
+    }
+}
+
+async fn run_quotes(config: &LoadConfig, symbols: &[String]) -> Result<StageReport, HftError> {
+    let (quote_tx, quote_rx) = mpsc::channel(1000);
+    let feed = FeedPublisher::new(FEED_CAPACITY);
+    let frames = feed.subscribe();
+    let mut book_builder = BookBuilder {
+        books: Arc::new(RwLock::new(HashMap::new())),
+        quote_rx,
+        heartbeat: None,
+        feed: Some(feed),
+        stream: None,
+        gauges: None,
+        deltas: None,
+        checksums: BookChecksums::default(),
+        resyncing: HashSet::new(),
+        recorder: None,
+        compactor: Compactor::default(),
+        snapshots: BookSnapshots::default(),
+        risk: None,
+    };
+    let builder = tokio::spawn(async move { book_builder.run().await });
+    let probe = tokio::spawn(probe_feed(frames, config.quotes));
+
+    let gateway = QuoteGateway::new(quote_tx);
+    let started = Instant::now();
+    let mut sent = Vec::with_capacity(config.quotes);
+    for i in 0..config.quotes {
+        let bid = 100.0 + (i % 100) as f64 * 0.01;
+        let quote = Quote {
+            symbol: symbols[i % symbols.len()].as_str().into(),
+            bid,
+            ask: bid + 0.01,
+            bid_size: 1.0 + (i % 7) as f64,
+            ask_size: 1.0 + (i % 5) as f64,
+            venue: VENUE.into(),
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        };
+        sent.push(pace(started, config.quote_rate, i).await);
+        gateway.process_quote(quote).await?;
+    }
+
+    // Closing the gateway stops the book builder, which closes the feed
+    drop(gateway);
+    let _ = builder.await;
+    let done = probe.await.unwrap_or_default();
+    Ok(StageReport::new(&sent, done, started))
+}
+
+/// Timestamp feed frames in publish order. Frames the probe fell behind on
+/// are skipped over so later frames still line up with their quotes.
+async fn probe_feed(mut frames: broadcast::Receiver<Arc<Vec<u8>>>, expected: usize) -> Vec<(usize, Instant)> {
+    let mut done = Vec::with_capacity(expected);
+    let mut next = 0;
+    loop {
+        match frames.recv().await {
+            Ok(_) => {
+                done.push((next, Instant::now()));
+                next += 1;
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => next += skipped as usize,
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+    done
+}
+
+async fn run_orders(config: &LoadConfig, symbols: &[String]) -> Result<StageReport, HftError> {
+    let (order_tx, order_rx) = mpsc::channel(1000);
+    let venue = Arc::new(LoadVenue::default());
+    let mut order_gateway = OrderGateway {
+        venues: VenueRegistry::from_venues(vec![venue.clone() as Arc<dyn VenueAdapter>]).await,
+        order_rx,
+        cancel_rx: None,
+        amend_rx: None,
+        events: EventBus::default(),
+        heartbeat: None,
+        orders: Arc::new(OrderTracker::new()),
+        sinks: Vec::new(),
+        leadership: None,
+        audit: None,
+        failover: FailoverPolicies::default(),
+        down: HashMap::new(),
+        reconcile_soon: false,
+        queued: VecDeque::new(),
+        instruments: Arc::new(InstrumentMap::new()),
+        chaos: None,
+        fees: FeeModel::default(),
+        risk: None,
+        recent_orders: Default::default(),
+        regions: Default::default(),
+    };
+    let router = tokio::spawn(async move { order_gateway.run().await });
+
+    let risk = Arc::new(RiskManager::new(LossLimits::default()));
+    risk.trading_state().transition(TradingMode::Active, "load test")?;
+    let execution = ExecutionEngine {
+        order_tx,
+        risk,
+        audit: None,
+    };
+    let started = Instant::now();
+    let mut sent = Vec::with_capacity(config.orders);
+    for i in 0..config.orders {
+        let order = Order {
+            symbol: symbols[i % symbols.len()].as_str().into(),
+            side: if i % 2 == 0 { OrderSide::Buy } else { OrderSide::Sell },
+            quantity: 1.0,
+            price: 100.0 + (i % 100) as f64 * 0.01,
+            venue: VENUE.into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        };
+        sent.push(pace(started, config.order_rate, i).await);
+        execution.execute_order(order).await?;
+    }
+
+    drop(execution);
+    let _ = router.await;
+    // The gateway submits orders one at a time, so arrivals are in send order
+    let done = venue.arrivals.lock().unwrap().drain(..).enumerate().collect();
+    Ok(StageReport::new(&sent, done, started))
+}
+
+/// Acknowledges every order immediately, noting when it arrived
+#[derive(Default)]
+struct LoadVenue {
+    arrivals: Mutex<Vec<Instant>>,
+}
+
+#[async_trait]
+impl VenueAdapter for LoadVenue {
+    async fn name(&self) -> String {
+        VENUE.to_string()
+    }
+
+    async fn subscribe_quotes(&self, _symbols: Vec<String>) -> Result<(), HftError> {
+        Ok(())
+    }
+
+    async fn submit_order(&self, _order: Order) -> Result<String, HftError> {
+        let mut arrivals = self.arrivals.lock().unwrap();
+        arrivals.push(Instant::now());
+        Ok(format!("load_{}", arrivals.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_args() {
+        let args = ["--quotes", "2_000_000", "--quote-rate", "500000", "--order-rate", "0", "--symbols", "3"];
+        let config = LoadConfig::from_args(args.iter().map(|s| s.to_string())).unwrap();
+        assert_eq!(config.quotes, 2_000_000);
+        assert_eq!(config.quote_rate, Some(500_000.0));
+        assert_eq!(config.order_rate, None);
+        assert_eq!(config.symbols, 3);
+        assert_eq!(config.orders, LoadConfig::default().orders);
+
+        assert!(LoadConfig::from_args(["--quotes".to_string()]).is_err());
+        assert!(LoadConfig::from_args(["--bogus".to_string(), "1".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_small_run_completes() {
+        let config = LoadConfig {
+            quotes: 5_000,
+            quote_rate: None,
+            orders: 500,
+            order_rate: Some(50_000.0),
+            symbols: 4,
+        };
+        let report = run(&config).await.unwrap();
+
+        assert_eq!(report.quotes.sent, 5_000);
+        assert!(report.quotes.completed > 0);
+        assert_eq!(report.orders.sent, 500);
+        assert_eq!(report.orders.completed, 500);
+        assert!(report.orders.latency.p50 <= report.orders.latency.p999);
+        assert!(report.orders.throughput() > 0.0);
+    }
+}
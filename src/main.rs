@@ -1,20 +1,625 @@
+use std::path::PathBuf;
 use std::sync::Arc;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use tokio::sync::RwLock;
 use hft_engine::{
-    services::Services,
-    command::CommandControl,
-    venues::binance::BinanceVenue,
+    backtest::{self, Backtest, Fees, FillModel, QueuePosition, TopOfBook},
+    backtest::optimize::{Optimizer, ParameterGrid},
+    export,
+    fees::FeeSchedules,
+    feed::StreamHub,
+    book::{self, BookChecksums, BookLimits, DepthRecorder, DepthRecordingConfig, DepthReplay, OrderBook},
+    services::{EngineStatus, Services},
+    command::{AdminAuth, CommandControl, PreflightConfig, TwoManRule},
+    alerts::{AlertConfig, AlertManager},
+    metrics::{self, init_metrics_server, LabelConfig, MetricsBackendConfig},
+    snapshot::EngineSnapshot,
+    audit::{AuditConfig, AuditLog},
+    scheduler::SchedulerConfig,
+    report::{DailyReport, ReportConfig},
+    sink::{dropcopy::DropCopyConfig, SinkHandle},
+    secrets,
+    hedger::HedgeConfig,
+    execution::{ManualOrderRequest, QuoteThrottleConfig, TrailingStopConfig},
+    gateways::{ChaosConfig, DataQualityConfig, FailoverPolicies, QuoteBackpressure, QuoteStormConfig, SubscriptionSpec, SubscriptionStore},
+    instruments::InstrumentMap,
+    risk::{BalanceConfig, ExpiryConfig, FinancingRates, FxConversion, GreekLimits, LiquidationConfig, OutageConfig, PriceSanityConfig, StressConfig},
+    signals::{CandleConfig, GreeksConfig, ToxicityConfig},
+    strategy::{MarketMaker, ParameterStore, ShadowSpec, StrategyPlugin},
+    types::OrderSide,
+    venues::{BinanceVenue, FrameRecorder, MarginConfig, VenueAdapter},
 };
 
+const DEFAULT_SNAPSHOT_PATH: &str = "state/snapshot.json";
+
+#[cfg(feature = "redis")]
+const LEADER_LOCK_TTL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// One binary for live trading and the offline workflows around it
+#[derive(Parser)]
+#[command(name = "hft_engine", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Fill models selectable for `backtest`
+#[derive(Clone, Copy, ValueEnum)]
+enum FillModelKind {
+    /// Fill at the quote as soon as the price is reached
+    TopOfBook,
+    /// Make passive orders wait behind the size displayed at their price
+    Queue,
+}
+
+/// How backtests simulate fills
+#[derive(Args, Clone, Copy)]
+struct FillModelArgs {
+    /// How orders are filled
+    #[arg(long, value_enum, default_value = "top-of-book")]
+    fill_model: FillModelKind,
+    /// Delay before orders reach the simulated venue
+    #[arg(long, default_value_t = 0)]
+    latency_ms: u64,
+    /// Random extra delay of up to this much per order
+    #[arg(long, default_value_t = 0)]
+    latency_jitter_ms: u64,
+    #[arg(long, default_value_t = 0.0)]
+    maker_bps: f64,
+    #[arg(long, default_value_t = 0.0)]
+    taker_bps: f64,
+    /// Seed for the simulation's random draws; a run repeats exactly with
+    /// the same seed
+    #[arg(long, env = "HFT_SEED", default_value_t = 0)]
+    seed: u64,
+}
+
+impl FillModelArgs {
+    fn build(&self) -> Box<dyn FillModel> {
+        let fees = Fees { maker_bps: self.maker_bps, taker_bps: self.taker_bps };
+        match self.fill_model {
+            FillModelKind::TopOfBook => Box::new(TopOfBook::new()
+                .with_latency(self.latency_ms)
+                .with_jitter(self.latency_jitter_ms)
+                .with_fees(fees)),
+            FillModelKind::Queue => Box::new(QueuePosition::new()
+                .with_latency(self.latency_ms)
+                .with_jitter(self.latency_jitter_ms)
+                .with_fees(fees)),
+        }
+    }
+}
+
+#[derive(Args)]
+struct RecordingArgs {
+    /// Frame recording made with `record` or `HFT_RECORD_FRAMES`, or with
+    /// `--depth` a depth recording made with `HFT_RECORD_DEPTH`
+    recording: PathBuf,
+    #[arg(long, default_value = "BINANCE_FUTURES")]
+    venue: String,
+    #[arg(long)]
+    depth: bool,
+}
+
+impl RecordingArgs {
+    fn load(&self) -> Result<backtest::LoadedQuotes, hft_engine::error::HftError> {
+        if self.depth {
+            backtest::load_depth_quotes(&self.recording, &self.venue)
+        } else {
+            backtest::load_quotes(&self.recording, &self.venue)
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SideArg {
+    Buy,
+    Sell,
+}
+
+/// A manual order action for `order`
+#[derive(Subcommand)]
+enum OrderAction {
+    /// Buy or sell, at market unless `--price` is given
+    Submit {
+        #[arg(long)]
+        venue: String,
+        #[arg(long)]
+        symbol: String,
+        #[arg(long, value_enum)]
+        side: SideArg,
+        #[arg(long)]
+        quantity: f64,
+        /// Limit price
+        #[arg(long)]
+        price: Option<f64>,
+    },
+    /// Cancel an open order
+    Cancel {
+        order_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Trade live; the default when no command is given
+    Run {
+        /// Reload the last snapshot instead of starting cold
+        #[arg(long)]
+        restore: bool,
+        /// Show a terminal dashboard until quit with `q`; needs the `tui`
+        /// feature
+        #[arg(long)]
+        tui: bool,
+    },
+    /// Run market makers over a recording with simulated fills
+    Backtest {
+        #[command(flatten)]
+        recording: RecordingArgs,
+        /// Market maker to run, parameterised from `HFT_STRATEGY_PARAMS`;
+        /// repeat for several
+        #[arg(long = "market-maker", default_value = "market_maker")]
+        market_makers: Vec<String>,
+        #[command(flatten)]
+        fills: FillModelArgs,
+    },
+    /// Tune a market maker's parameters with walk-forward backtests over a
+    /// recording
+    Optimize {
+        #[command(flatten)]
+        recording: RecordingArgs,
+        /// JSON object of parameter names to arrays of values to try
+        #[arg(long)]
+        grid: PathBuf,
+        /// Market maker to tune; its parameters in `HFT_STRATEGY_PARAMS`
+        /// are the base the grid varies
+        #[arg(long = "market-maker", default_value = "market_maker")]
+        market_maker: String,
+        #[arg(long, default_value_t = 3)]
+        folds: usize,
+        /// Try this many random grid points, picked with `--seed`, instead
+        /// of all of them
+        #[arg(long)]
+        samples: Option<usize>,
+        #[command(flatten)]
+        fills: FillModelArgs,
+    },
+    /// Parse a recording and print the books it builds
+    Replay {
+        #[command(flatten)]
+        recording: RecordingArgs,
+    },
+    /// Convert recordings and audit log fills to Parquet, partitioned by
+    /// date and symbol
+    Export {
+        /// Directory the tables are written under
+        output: PathBuf,
+        /// Frame recording made with `record` or `HFT_RECORD_FRAMES`;
+        /// repeat for several
+        #[arg(long)]
+        frames: Vec<PathBuf>,
+        /// Depth recording made with `HFT_RECORD_DEPTH`
+        #[arg(long)]
+        depth: Vec<PathBuf>,
+        /// Audit log made with `HFT_AUDIT_LOG`
+        #[arg(long)]
+        audit: Vec<PathBuf>,
+        /// Venue whose frames to export
+        #[arg(long, default_value = "BINANCE_FUTURES")]
+        venue: String,
+    },
+    /// Record raw Binance market data frames until interrupted
+    Record {
+        /// File the frames are appended to
+        output: PathBuf,
+        #[arg(long, env = "HFT_SYMBOLS", value_delimiter = ',', required = true)]
+        symbols: Vec<String>,
+    },
+    /// Check config, venue connectivity, credentials, clock skew and
+    /// symbols, without trading
+    Preflight,
+    /// Print the status of a running engine from its admin API
+    Status {
+        #[arg(long, default_value = "http://127.0.0.1:9090")]
+        url: String,
+        /// Admin API token, when the engine requires one
+        #[arg(long, env = "HFT_ADMIN_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+    },
+    /// Print today's PnL and activity report from a running engine's admin
+    /// API
+    Report {
+        #[arg(long, default_value = "http://127.0.0.1:9090")]
+        url: String,
+        #[arg(long, env = "HFT_ADMIN_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+        /// Also have the engine write the report to disk and push it to the
+        /// report webhook
+        #[arg(long)]
+        write: bool,
+    },
+    /// Submit or cancel a manual order on a running engine through its
+    /// admin API, subject to the engine's risk checks
+    Order {
+        #[arg(long, global = true, default_value = "http://127.0.0.1:9090")]
+        url: String,
+        #[arg(long, global = true, env = "HFT_ADMIN_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+        /// Send the request; without it the order is only printed
+        #[arg(long, global = true)]
+        confirm: bool,
+        #[command(subcommand)]
+        action: OrderAction,
+    },
+    /// Print a summary of a saved state snapshot
+    Snapshot {
+        /// Defaults to `HFT_SNAPSHOT_PATH`
+        path: Option<PathBuf>,
+    },
+    /// Encrypt a JSON object of secrets for `HFT_SECRETS_FILE`, under the
+    /// passphrase from `HFT_SECRETS_PASSPHRASE_FILE` or
+    /// `HFT_SECRETS_PASSPHRASE`
+    SealSecrets {
+        /// Plaintext JSON, e.g. `{"BINANCE_API_KEY": "..."}`
+        input: PathBuf,
+        output: PathBuf,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut services = Services::new().await;
+    let snapshot_path = PathBuf::from(
+        std::env::var("HFT_SNAPSHOT_PATH").unwrap_or_else(|_| DEFAULT_SNAPSHOT_PATH.to_string())
+    );
+
+    match Cli::parse().command.unwrap_or(Command::Run { restore: false, tui: false }) {
+        Command::Run { restore, tui } => run(snapshot_path, restore, tui).await,
+        Command::Backtest { recording, market_makers, fills } => run_backtest(&recording, &market_makers, fills.build(), fills.seed),
+        Command::Optimize { recording, grid, market_maker, folds, samples, fills } => {
+            let params = strategy_params()?;
+            let axes: serde_json::Value = serde_json::from_slice(&std::fs::read(&grid)?)?;
+            let grid = ParameterGrid::parse(params.get(&market_maker).unwrap_or_default(), &axes)?;
+            let candidates = match samples {
+                Some(samples) => grid.sample(samples, &mut <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(fills.seed)),
+                None => grid.candidates(),
+            };
+
+            let loaded = recording.load()?;
+            println!("Loaded {} quotes from {}, trying {} of {} parameter sets", loaded.quotes.len(), recording.recording.display(), candidates.len(), grid.len());
+            let optimizer = Optimizer::new(
+                &market_maker,
+                |store| Box::new(MarketMaker::new(&market_maker, store)) as Box<dyn StrategyPlugin>,
+                || fills.build(),
+            ).with_folds(folds).with_seed(fills.seed);
+            println!("{}", optimizer.run(&loaded.quotes, candidates));
+            Ok(())
+        }
+        Command::Replay { recording } if recording.depth => replay_depth(&recording),
+        Command::Replay { recording } => replay(&recording),
+        Command::Export { output, frames, depth, audit, venue } => {
+            let exports = frames.iter().map(|path| (path, export::export_frames(path, &venue, &output)))
+                .chain(depth.iter().map(|path| (path, export::export_depth(path, &output))))
+                .chain(audit.iter().map(|path| (path, export::export_fills(path, &output))));
+            for (path, written) in exports {
+                println!("{}: {} files written", path.display(), written?.len());
+            }
+            Ok(())
+        }
+        Command::Record { output, symbols } => record(&output, symbols).await,
+        Command::Preflight => preflight().await,
+        Command::Status { url, token } => {
+            let mut request = reqwest::Client::new().get(format!("{}/admin/status", url));
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
+            }
+            let status: EngineStatus = request.send().await?.error_for_status()?.json().await?;
+            println!("{}", status);
+            Ok(())
+        }
+        Command::Report { url, token, write } => {
+            let client = reqwest::Client::new();
+            let url = format!("{}/admin/report", url);
+            let authorize = |request: reqwest::RequestBuilder| match &token {
+                Some(token) => request.bearer_auth(token),
+                None => request,
+            };
+            let report: DailyReport = authorize(client.get(&url)).send().await?.error_for_status()?.json().await?;
+            println!("{}", report);
+            if write {
+                let reply: serde_json::Value = authorize(client.post(&url)).send().await?.error_for_status()?.json().await?;
+                println!("{}", reply["detail"].as_str().unwrap_or_default());
+            }
+            Ok(())
+        }
+        Command::Order { url, token, confirm, action } => manual_order(&url, token, confirm, action).await,
+        Command::Snapshot { path } => {
+            println!("{}", EngineSnapshot::load(&path.unwrap_or(snapshot_path))?);
+            Ok(())
+        }
+        Command::SealSecrets { input, output } => {
+            let passphrase = secrets::passphrase_from_env().ok_or("HFT_SECRETS_PASSPHRASE is not set")?;
+            let values = serde_json::from_slice(&std::fs::read(&input)?)?;
+            std::fs::write(&output, secrets::file::seal(&values, &passphrase)?)?;
+            println!("Sealed {} secrets into {}", values.len(), output.display());
+            Ok(())
+        }
+    }
+}
+
+/// Engine services with the configuration every live command shares
+async fn configure() -> Result<Services, Box<dyn std::error::Error>> {
+    if let Some(config) = LabelConfig::from_env() {
+        metrics::labels::configure(config);
+    }
+
+    let mut services = Services::new().await?;
+    if let Some(instruments) = InstrumentMap::from_env() {
+        services = services.with_instruments(instruments)?;
+    }
+    if let Some(policies) = FailoverPolicies::from_env() {
+        services = services.with_venue_failover(policies)?;
+    }
+    if let Some(fx) = FxConversion::from_env() {
+        services = services.with_fx_conversion(fx);
+    }
+    // Strategy parameters, changeable at runtime through `/admin/params`
+    if let Ok(path) = std::env::var("HFT_STRATEGY_PARAMS") {
+        services = services.with_strategy_params(ParameterStore::load(std::path::Path::new(&path))?);
+    }
+    if let Some(chaos) = ChaosConfig::from_env() {
+        eprintln!(
+            "WARNING: chaos testing enabled ({:?} latency, {:?} jitter, {} drop probability); do not run against production venues",
+            chaos.latency, chaos.jitter, chaos.drop_probability
+        );
+        services = services.with_chaos(chaos)?;
+    }
+    services = services.with_reports(ReportConfig::from_env());
+    services = services.with_book_checksums(BookChecksums::from_env())?;
+    services = services.with_book_limits(BookLimits::from_env())?;
+    if let Some(config) = DepthRecordingConfig::from_env() {
+        services = services.with_depth_recorder(DepthRecorder::open(&config)?)?;
+    }
+    if let Some(schedule) = SchedulerConfig::from_env() {
+        services = services.with_scheduler(schedule);
+    }
+    if let Some(store) = SubscriptionStore::from_env() {
+        services = services.with_subscription_store(store)?;
+    }
+    if std::env::var("HFT_QUOTE_DEDUP").is_ok_and(|v| v == "1" || v == "true") {
+        services = services.with_quote_dedup()?;
+    }
+    if let Some(rates) = FinancingRates::from_env() {
+        services = services.with_financing(rates);
+    }
+    if let Some(schedules) = FeeSchedules::from_env() {
+        services = services.with_fee_schedules(schedules);
+    }
+    if let Some(config) = PriceSanityConfig::from_env() {
+        services = services.with_price_sanity(config)?;
+    }
+    if let Some(backpressure) = QuoteBackpressure::from_env() {
+        services = services.with_quote_backpressure(backpressure)?;
+    }
+    if let Some(config) = QuoteStormConfig::from_env() {
+        services = services.with_quote_storm_guard(config)?;
+    }
+    if let Some(config) = DataQualityConfig::from_env() {
+        services = services.with_data_quality(config)?;
+    }
+    if let Some(config) = LiquidationConfig::from_env() {
+        services = services.with_liquidation_guard(config);
+    }
+    if let Some(config) = MetricsBackendConfig::from_env() {
+        services = services.with_metrics_backend(config);
+    }
+    if let Some(config) = OutageConfig::from_env() {
+        services = services.with_outage_guard(config);
+    }
+    if let Some(config) = ExpiryConfig::from_env() {
+        services = services.with_contract_expiries(config);
+    }
+    if std::env::var("HFT_BOOK_GAUGES").is_ok_and(|v| v == "1" || v == "true") {
+        services = services.with_book_gauges()?;
+    }
+
+    Ok(services)
+}
+
+async fn run(snapshot_path: PathBuf, restore: bool, tui: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if tui && !cfg!(feature = "tui") {
+        return Err("--tui needs hft_engine built with the `tui` feature".into());
+    }
+    let mut services = configure().await?;
+
+    // Stream order events to Kafka/Redpanda when brokers are configured
+    #[cfg(feature = "kafka")]
+    if let Some(config) = hft_engine::sink::kafka::KafkaConfig::from_env() {
+        let sink = hft_engine::sink::kafka::KafkaSink::connect(&config).await?;
+        services = services.with_order_sink(hft_engine::sink::SinkHandle::spawn(Arc::new(sink), 8192))?;
+        println!("Publishing order events to Kafka topic {}", config.topic);
+    }
+
+    // Stream books, order events and PnL to GUI clients over WebSocket
+    if let Ok(addr) = std::env::var("HFT_STREAM_ADDR") {
+        let stream = StreamHub::default();
+        tokio::spawn(stream.clone().serve(addr.parse()?));
+        services = services.with_stream(stream)?;
+    }
+
+    // Mirror positions and open orders to Redis for dashboards and standbys.
+    // With `HFT_NODE_ID` set, the instance joins a hot/standby pair and only
+    // sends orders while it holds the leader lock.
+    #[cfg(feature = "redis")]
+    if let Some(config) = hft_engine::mirror::MirrorConfig::from_env() {
+        use hft_engine::failover::{Leadership, redis::RedisLock};
+
+        let mut mirror = hft_engine::mirror::redis::RedisMirror::connect(config.clone()).await?;
+        if let Ok(node_id) = std::env::var("HFT_NODE_ID") {
+            let lock = RedisLock::connect(&config.url, format!("{}:leader", config.prefix)).await?;
+            let leadership = Leadership::new(node_id, Arc::new(lock), LEADER_LOCK_TTL)
+                .with_event_bus(services.events());
+            leadership.tick().await;
+            tokio::spawn(leadership.clone().run());
+
+            services = services.with_leadership(leadership.clone())?;
+            mirror = mirror.with_leadership(leadership);
+        }
+        tokio::spawn(mirror.run(services.mirror_source()));
+    }
+
+    // Run the reference market maker under the name in `HFT_MARKET_MAKER`
+    if let Ok(name) = std::env::var("HFT_MARKET_MAKER") {
+        let strategy = MarketMaker::new(&name, services.strategy_params());
+        services.add_strategy(Box::new(strategy));
+    }
+
+    // Shadow market makers from `HFT_SHADOW_MARKET_MAKERS` simulate their
+    // orders next to the live strategy they would replace
+    for spec in ShadowSpec::from_env() {
+        let strategy = MarketMaker::new(&spec.name, services.strategy_params());
+        services.add_shadow_strategy(Box::new(strategy), spec.live)?;
+    }
+
+    // Load WASM strategies listed in `HFT_WASM_STRATEGIES` (comma separated)
+    #[cfg(feature = "wasm")]
+    if let Ok(paths) = std::env::var("HFT_WASM_STRATEGIES") {
+        for path in paths.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let strategy = hft_engine::strategy::wasm::WasmStrategy::load(std::path::Path::new(path))?;
+            services.add_strategy(Box::new(strategy));
+        }
+    }
+
+    // Load shared-library strategies listed in `HFT_DYLIB_STRATEGIES`
+    #[cfg(feature = "plugins-dylib")]
+    if let Ok(paths) = std::env::var("HFT_DYLIB_STRATEGIES") {
+        for path in paths.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let strategy = hft_engine::strategy::dylib::DylibStrategy::load(std::path::Path::new(path))?;
+            services.add_strategy(Box::new(strategy));
+        }
+    }
+
+    // Compliance audit trail, separate from debug logging
+    if let Some(config) = AuditConfig::from_env() {
+        services = services.with_audit(AuditLog::open(&config)?)?;
+        println!("Writing audit log to {}", config.path.display());
+    }
+
+    // Drop copy of every execution to FIX and/or write-once files
+    if let Some(config) = DropCopyConfig::from_env() {
+        let sinks = config.open()?.into_iter().map(|sink| SinkHandle::spawn(sink, 65536)).collect();
+        services = services.with_drop_copy(sinks)?;
+        println!("Mirroring executions to drop copy");
+    }
+
+    // `--restore` reloads the last snapshot instead of starting cold
+    if restore {
+        let snapshot = EngineSnapshot::load(&snapshot_path)?;
+        services.restore(snapshot).await?;
+        println!("Restored engine state from {}", snapshot_path.display());
+    }
+
+    // Pull or widen strategy quotes while order flow is toxic
+    if let Some(config) = ToxicityConfig::from_env() {
+        services = services.with_toxicity(config);
+    }
+    if let Some(config) = GreeksConfig::from_env() {
+        services = services.with_greeks(config);
+    }
+    if let Some(limits) = GreekLimits::from_env() {
+        services = services.with_greek_limits(limits);
+    }
+    if let Some(config) = StressConfig::from_env() {
+        services = services.with_stress_tests(config);
+    }
+    if let Some(config) = BalanceConfig::from_env() {
+        services = services.with_balance_monitor(config);
+    }
+
+    // Give strategies recent candles before they see live data
+    if let Some(config) = CandleConfig::from_env() {
+        services.warm_up_candles(&config).await;
+    }
+
+    // Watch market data for `HFT_WARMUP_SECS` after starting before
+    // sending orders
+    if let Some(secs) = std::env::var("HFT_WARMUP_SECS").ok().and_then(|secs| secs.parse().ok()) {
+        services = services.with_warmup(std::time::Duration::from_secs(secs));
+    }
 
-    // Add venues
-    let venue = Arc::new(BinanceVenue::new(
-        std::env::var("BINANCE_API_KEY").unwrap_or_default(),
-        std::env::var("BINANCE_API_SECRET").unwrap_or_default(),
-    ));
+    // `HFT_PREFLIGHT=1` runs the `preflight` checks before every start
+    if std::env::var("HFT_PREFLIGHT").is_ok_and(|v| v == "1" || v == "true") {
+        let report = services.preflight(&PreflightConfig::from_env()).await;
+        println!("{}", report);
+        if !report.passed() {
+            std::process::exit(1);
+        }
+    }
+
+    // Push operational alerts to webhooks when any are configured
+    if let Some(config) = AlertConfig::from_env() {
+        tokio::spawn(AlertManager::new(config).run(services.events().subscribe()));
+    }
+
+    // Set per-symbol leverage and margin mode, alerting on any left unapplied
+    if let Some(config) = MarginConfig::from_env() {
+        services.configure_margin(&config).await;
+    }
+
+    // Admin API tokens and roles; without any the admin API is open
+    let mut auth = AdminAuth::from_secrets(&services.secrets()).unwrap_or_default();
+    if let Some(audit) = services.audit() {
+        auth = auth.with_audit(audit);
+    }
+    if !auth.is_enabled() {
+        eprintln!("WARNING: HFT_ADMIN_TOKENS is not set, the admin API on port 9090 accepts unauthenticated requests");
+    }
+    // Hold destructive control actions until a second token approves them
+    if let Some(rule) = TwoManRule::from_env() {
+        if !auth.is_enabled() {
+            eprintln!("WARNING: HFT_TWO_MAN_RULE needs HFT_ADMIN_TOKENS to tell principals apart and is not enforced");
+        }
+        auth = auth.with_two_man_rule(rule);
+    }
+
+    // Serve metrics, health probes, status and trading toggles, following venue connectivity events
+    tokio::spawn(services.health().track_venues(services.events().subscribe()));
+    init_metrics_server(services.metrics(), services.health(), services.strategy_params(), services.status_source(), services.reporter(), services.handles().risk, services.manual_orders(), services.shadows(), services.incidents(), auth).await;
+
+    // Keep recent venue messages and engine events, dumped on kill switch or panic
+    let incidents = services.incidents();
+    incidents.dump_on_panic();
+    tokio::spawn(incidents.run(services.events().subscribe()));
+
+    // On a crash, cancel everything, dump state and exit with EMERGENCY_EXIT_CODE
+    services.emergency().install();
+
+    // Coalesce strategy quote updates to stay under venue rate limits
+    if let Some(config) = QuoteThrottleConfig::from_env() {
+        tokio::spawn(services.quote_throttle(config)?.run());
+    }
+
+    // Keep market-making inventory flat on the hedge venues
+    if let Some(config) = HedgeConfig::from_env() {
+        tokio::spawn(services.hedger(config).run());
+    }
+
+    // Protect bracket entries with exits that follow their fills, the
+    // stops watched on the feed once started
+    services.brackets()?;
+    // Remediate multi-leg orders that leg out, unwinding on later fills
+    services.legs()?;
+    // Trail stops behind designated positions, followed on the feed
+    if let Some(config) = TrailingStopConfig::from_env() {
+        services.trailing_stops(config);
+    }
+
+    // Re-broadcast market data to colocated consumers
+    if let Ok(addr) = std::env::var("HFT_FEED_TCP_ADDR") {
+        tokio::spawn(services.feed().serve_tcp(addr));
+    }
+    if let Ok(path) = std::env::var("HFT_FEED_UDS_PATH") {
+        tokio::spawn(services.feed().serve_uds(PathBuf::from(path)));
+    }
 
     // Initialize command & control
     let services_arc = Arc::new(RwLock::new(services));
@@ -23,8 +628,170 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Start trading
     command_control.start_trading().await?;
 
-    tokio::signal::ctrl_c().await?;  // Wait for Ctrl+C signal
+    // Pick up symbols subscribed intraday before the last shutdown, or
+    // start from `HFT_SUBSCRIPTIONS`
+    let initial = SubscriptionSpec::from_env().unwrap_or_default();
+    let resumed = services_arc.read().await.resume_subscriptions(&initial).await?;
+    for (venue, symbols) in &resumed.added {
+        println!("Subscribed {} to {}", venue, symbols.join(", "));
+    }
+
+    wait_for_shutdown(&services_arc, tui).await?;
 
     println!("Shutting down HFT Engine");
+    command_control.stop_trading().await?;
+    command_control.snapshot_state(&snapshot_path).await?;
+    Ok(())
+}
+
+/// Wait for Ctrl+C, or for the operator to quit the dashboard
+async fn wait_for_shutdown(services: &Arc<RwLock<Services>>, tui: bool) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "tui")]
+    if tui {
+        let handles = services.read().await.handles();
+        return Ok(hft_engine::tui::run(handles).await?);
+    }
+    #[cfg(not(feature = "tui"))]
+    let _ = (services, tui);
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
+
+/// Send a manual order action to a running engine's admin API, or only
+/// describe it without `--confirm`
+async fn manual_order(url: &str, token: Option<String>, confirm: bool, action: OrderAction) -> Result<(), Box<dyn std::error::Error>> {
+    let (request, description) = match action {
+        OrderAction::Submit { venue, symbol, side, quantity, price } => {
+            let (side, verb) = match side {
+                SideArg::Buy => (OrderSide::Buy, "buy"),
+                SideArg::Sell => (OrderSide::Sell, "sell"),
+            };
+            let description = format!(
+                "{} {} {} on {} at {}",
+                verb, quantity, symbol, venue, price.map_or("market".to_string(), |p| p.to_string()),
+            );
+            let order = ManualOrderRequest { venue, symbol, side, quantity, price, confirm };
+            (reqwest::Client::new().post(format!("{}/admin/orders", url)).json(&order), description)
+        }
+        OrderAction::Cancel { order_id } => {
+            let request = reqwest::Client::new()
+                .post(format!("{}/admin/orders/{}/cancel", url, order_id))
+                .json(&serde_json::json!({ "confirm": confirm }));
+            (request, format!("cancel order {}", order_id))
+        }
+    };
+    if !confirm {
+        println!("Would {}; rerun with --confirm to send", description);
+        return Ok(());
+    }
+
+    let request = match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    };
+    let response = request.send().await?;
+    let status = response.status();
+    let reply: serde_json::Value = response.json().await?;
+    if !status.is_success() {
+        return Err(format!("{} refused: {}", description, reply["error"].as_str().unwrap_or(status.as_str())).into());
+    }
+    if status == reqwest::StatusCode::ACCEPTED {
+        println!("Held {} until a second token repeats it within {}s", description, reply["expires_in_secs"]);
+        return Ok(());
+    }
+    println!("Sent {}", description);
+    Ok(())
+}
+
+async fn preflight() -> Result<(), Box<dyn std::error::Error>> {
+    let services = Arc::new(RwLock::new(configure().await?));
+    let report = CommandControl::new(services).await.preflight(&PreflightConfig::from_env()).await;
+    if !report.passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Strategy parameters from the file at `HFT_STRATEGY_PARAMS`, if set
+fn strategy_params() -> Result<ParameterStore, Box<dyn std::error::Error>> {
+    Ok(match std::env::var("HFT_STRATEGY_PARAMS") {
+        Ok(path) => ParameterStore::load(std::path::Path::new(&path))?,
+        Err(_) => ParameterStore::new(),
+    })
+}
+
+fn run_backtest(recording: &RecordingArgs, market_makers: &[String], fill_model: Box<dyn FillModel>, seed: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let params = strategy_params()?;
+    let loaded = recording.load()?;
+    println!("Loaded {} quotes from {} ({} frames rejected)", loaded.quotes.len(), recording.recording.display(), loaded.rejected);
+
+    let mut backtest = Backtest::new().with_fill_model(fill_model).with_seed(seed);
+    for name in market_makers {
+        backtest = backtest.with_strategy(Box::new(MarketMaker::new(name, params.clone())));
+    }
+    println!("{}", backtest.run(loaded.quotes));
+    Ok(())
+}
+
+fn replay(recording: &RecordingArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let loaded = recording.load()?;
+    let mut books = std::collections::BTreeMap::new();
+    for quote in &loaded.quotes {
+        books.entry(quote.symbol)
+            .or_insert_with(|| OrderBook::new(quote.symbol))
+            .update(quote);
+    }
+
+    println!("{} quotes parsed, {} frames rejected", loaded.quotes.len(), loaded.rejected);
+    for (symbol, book) in &books {
+        let level = |level: Option<(f64, f64)>| level.map_or("-".to_string(), |(p, q)| format!("{}x{}", q, p));
+        println!("{}: bid {} ask {}", symbol, level(book.best_bid()), level(book.best_ask()));
+    }
+    Ok(())
+}
+
+fn replay_depth(recording: &RecordingArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let records = book::read_depth(&recording.recording)?;
+    let mut replay = DepthReplay::new();
+    let mut applied = 0;
+    for record in records.iter().filter(|r| r.event.venue() == recording.venue) {
+        replay.apply(record);
+        applied += 1;
+    }
+
+    println!("{} of {} events applied", applied, records.len());
+    let mut books: Vec<_> = replay.books().collect();
+    books.sort_by_key(|book| book.symbol().to_string());
+    for book in books {
+        let snapshot = book.snapshot();
+        let level = |level: Option<&(f64, f64)>| level.map_or("-".to_string(), |(p, q)| format!("{}x{}", q, p));
+        println!(
+            "{}: bid {} ask {}, {} bid and {} ask levels",
+            book.symbol(), level(snapshot.bids.first()), level(snapshot.asks.first()), snapshot.bids.len(), snapshot.asks.len(),
+        );
+    }
+    Ok(())
+}
+
+async fn record(output: &std::path::Path, symbols: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let (quote_tx, mut quote_rx) = tokio::sync::mpsc::channel(1000);
+    let venue = BinanceVenue::new(String::new(), String::new())
+        .with_quote_sender(quote_tx)
+        .with_frame_recorder(FrameRecorder::open(output)?);
+    venue.subscribe_quotes(symbols.clone()).await?;
+    println!("Recording {} to {}", symbols.join(", "), output.display());
+
+    let mut quotes = 0u64;
+    loop {
+        tokio::select! {
+            quote = quote_rx.recv() => match quote {
+                Some(_) => quotes += 1,
+                None => break,
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    println!("Recorded {} quotes", quotes);
     Ok(())
-}
\ No newline at end of file
+}
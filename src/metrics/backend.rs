@@ -0,0 +1,410 @@
+//! Pushing the engine's metrics to backends other than a Prometheus scrape.
+//!
+//! Components keep counting into the process-wide metric families, a few
+//! atomic operations and no allocation per event whatever the backend. On
+//! every flush interval the exporter gathers the registry and hands the
+//! snapshot to the configured [`MetricsSink`], so formatting and network
+//! writes stay off the trading path.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use prometheus::proto::{LabelPair, Metric, MetricFamily, MetricType};
+use serde_json::{json, Value};
+use tokio::net::UdpSocket;
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+use crate::error::HftError;
+use crate::metrics::{MetricsRegistry, METRICS_EXPORT_FAILURES};
+
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Largest statsd datagram, keeping clear of fragmentation on a 1500 byte MTU
+const MAX_DATAGRAM: usize = 1432;
+
+/// Where the engine's metrics go
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricsBackend {
+    /// Scraped from `/metrics`; nothing is pushed
+    Prometheus,
+    /// DogStatsD datagrams to `addr`, labels sent as tags
+    Statsd { addr: String },
+    /// OTLP/HTTP JSON posted to a collector's metrics `endpoint`
+    Otlp { endpoint: String },
+}
+
+/// Which backend metrics are pushed to and how often
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsBackendConfig {
+    pub backend: MetricsBackend,
+    /// Prepended to statsd metric names, e.g. `hft.`
+    pub prefix: String,
+    pub flush_interval: Duration,
+}
+
+impl Default for MetricsBackendConfig {
+    fn default() -> Self {
+        Self { backend: MetricsBackend::Prometheus, prefix: String::new(), flush_interval: DEFAULT_FLUSH_INTERVAL }
+    }
+}
+
+impl MetricsBackendConfig {
+    /// Comma separated entries: the backend as `prometheus`,
+    /// `statsd:HOST:PORT` or `otlp:URL`, plus optional `prefix:NAME` and
+    /// `flush_ms:MS`, e.g. `statsd:127.0.0.1:8125,prefix:hft.,flush_ms:5000`
+    fn parse(spec: &str) -> Self {
+        let mut config = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once(':').map(|(key, value)| (key.trim(), value.trim())) {
+                None if entry == "prometheus" => config.backend = MetricsBackend::Prometheus,
+                Some(("statsd", addr)) if !addr.is_empty() => config.backend = MetricsBackend::Statsd { addr: addr.to_string() },
+                Some(("otlp", endpoint)) if endpoint.starts_with("http") => {
+                    config.backend = MetricsBackend::Otlp { endpoint: endpoint.to_string() };
+                }
+                Some(("prefix", prefix)) => config.prefix = prefix.to_string(),
+                Some(("flush_ms", ms)) => match ms.parse::<u64>() {
+                    Ok(ms) if ms > 0 => config.flush_interval = Duration::from_millis(ms),
+                    _ => warn!(entry = entry, "Ignoring malformed metrics backend entry"),
+                },
+                _ => warn!(entry = entry, "Ignoring malformed metrics backend entry"),
+            }
+        }
+        config
+    }
+
+    /// Read `HFT_METRICS_BACKEND` (see [`parse`](Self::parse)); returns
+    /// `None` when unset
+    pub fn from_env() -> Option<Self> {
+        Some(Self::parse(&std::env::var("HFT_METRICS_BACKEND").ok()?))
+    }
+
+    /// The sink metrics are pushed to, or `None` for Prometheus, which is
+    /// scraped instead
+    pub async fn connect(&self) -> Result<Option<Arc<dyn MetricsSink>>, HftError> {
+        let sink: Arc<dyn MetricsSink> = match &self.backend {
+            MetricsBackend::Prometheus => return Ok(None),
+            MetricsBackend::Statsd { addr } => Arc::new(StatsdSink::connect(addr, &self.prefix).await?),
+            MetricsBackend::Otlp { endpoint } => Arc::new(OtlpSink::new(endpoint)),
+        };
+        Ok(Some(sink))
+    }
+}
+
+/// Destination metric snapshots are pushed to
+#[async_trait]
+pub trait MetricsSink: Send + Sync {
+    fn name(&self) -> &str;
+    async fn export(&self, families: &[MetricFamily]) -> Result<(), HftError>;
+}
+
+/// Series name with its labels, identifying a counter across snapshots
+fn series_key(name: &str, labels: &[LabelPair]) -> String {
+    let mut key = name.to_string();
+    for label in labels {
+        key.push_str(&format!(",{}={}", label.get_name(), label.get_value()));
+    }
+    key
+}
+
+/// Sends metrics as DogStatsD datagrams. Counters and histogram counts and
+/// sums are sent as the increase since the previous snapshot, gauges as
+/// their current value.
+pub struct StatsdSink {
+    socket: UdpSocket,
+    prefix: String,
+    /// Cumulative value last seen per counter series
+    sent: Mutex<HashMap<String, f64>>,
+}
+
+impl StatsdSink {
+    pub async fn connect(addr: &str, prefix: &str) -> Result<Self, HftError> {
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+        socket.connect(addr).await?;
+        Ok(Self { socket, prefix: prefix.to_string(), sent: Mutex::new(HashMap::new()) })
+    }
+
+    fn line(&self, name: &str, value: f64, kind: &str, labels: &[LabelPair]) -> String {
+        let mut line = format!("{}{}:{}|{}", self.prefix, name, value, kind);
+        for (i, label) in labels.iter().enumerate() {
+            line.push_str(if i == 0 { "|#" } else { "," });
+            line.push_str(&format!("{}:{}", label.get_name(), label.get_value()));
+        }
+        line
+    }
+
+    /// Increase of a cumulative series since it was last seen, or `None`
+    /// when it has not moved. A series that went backwards was reset and
+    /// counts from zero.
+    fn delta(sent: &mut HashMap<String, f64>, key: String, value: f64) -> Option<f64> {
+        let previous = sent.insert(key, value).unwrap_or(0.0);
+        let delta = if value < previous { value } else { value - previous };
+        (delta > 0.0).then_some(delta)
+    }
+
+    fn lines(&self, families: &[MetricFamily]) -> Vec<String> {
+        let mut sent = self.sent.lock().unwrap();
+        let mut lines = Vec::new();
+        for family in families {
+            let name = family.get_name();
+            for metric in family.get_metric() {
+                let labels = metric.get_label();
+                match family.get_field_type() {
+                    MetricType::COUNTER => {
+                        let value = metric.get_counter().get_value();
+                        if let Some(delta) = Self::delta(&mut sent, series_key(name, labels), value) {
+                            lines.push(self.line(name, delta, "c", labels));
+                        }
+                    }
+                    MetricType::GAUGE => lines.push(self.line(name, metric.get_gauge().get_value(), "g", labels)),
+                    MetricType::HISTOGRAM => {
+                        let histogram = metric.get_histogram();
+                        let count = histogram.get_sample_count() as f64;
+                        if let Some(delta) = Self::delta(&mut sent, series_key(&format!("{}.count", name), labels), count) {
+                            lines.push(self.line(&format!("{}.count", name), delta, "c", labels));
+                        }
+                        if let Some(delta) = Self::delta(&mut sent, series_key(&format!("{}.sum", name), labels), histogram.get_sample_sum()) {
+                            lines.push(self.line(&format!("{}.sum", name), delta, "c", labels));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        lines
+    }
+}
+
+#[async_trait]
+impl MetricsSink for StatsdSink {
+    fn name(&self) -> &str {
+        "statsd"
+    }
+
+    async fn export(&self, families: &[MetricFamily]) -> Result<(), HftError> {
+        let mut datagram = String::new();
+        for line in self.lines(families) {
+            if !datagram.is_empty() && datagram.len() + 1 + line.len() > MAX_DATAGRAM {
+                self.socket.send(datagram.as_bytes()).await?;
+                datagram.clear();
+            }
+            if !datagram.is_empty() {
+                datagram.push('\n');
+            }
+            datagram.push_str(&line);
+        }
+        if !datagram.is_empty() {
+            self.socket.send(datagram.as_bytes()).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Posts metrics to an OpenTelemetry collector over OTLP/HTTP with JSON
+/// encoding, as cumulative sums, gauges and histograms
+pub struct OtlpSink {
+    endpoint: String,
+    client: reqwest::Client,
+    /// When the engine started counting, the start of every cumulative series
+    start_nanos: u128,
+}
+
+fn unix_nanos() -> u128 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos()
+}
+
+impl OtlpSink {
+    pub fn new(endpoint: &str) -> Self {
+        Self { endpoint: endpoint.to_string(), client: reqwest::Client::new(), start_nanos: unix_nanos() }
+    }
+
+    fn data_point(&self, metric: &Metric, now_nanos: u128) -> Value {
+        let attributes: Vec<Value> = metric.get_label().iter()
+            .map(|l| json!({ "key": l.get_name(), "value": { "stringValue": l.get_value() } }))
+            .collect();
+        json!({
+            "attributes": attributes,
+            "startTimeUnixNano": self.start_nanos.to_string(),
+            "timeUnixNano": now_nanos.to_string(),
+        })
+    }
+
+    fn payload(&self, families: &[MetricFamily], now_nanos: u128) -> Value {
+        let mut metrics = Vec::new();
+        for family in families {
+            let points = family.get_metric().iter().map(|metric| {
+                let mut point = self.data_point(metric, now_nanos);
+                match family.get_field_type() {
+                    MetricType::COUNTER => point["asDouble"] = json!(metric.get_counter().get_value()),
+                    MetricType::GAUGE => point["asDouble"] = json!(metric.get_gauge().get_value()),
+                    MetricType::HISTOGRAM => {
+                        // Prometheus buckets are cumulative, OTLP's are not and
+                        // end with an overflow bucket
+                        let histogram = metric.get_histogram();
+                        let mut below = 0;
+                        let mut counts = Vec::new();
+                        let mut bounds = Vec::new();
+                        for bucket in histogram.get_bucket() {
+                            counts.push((bucket.get_cumulative_count() - below).to_string());
+                            below = bucket.get_cumulative_count();
+                            bounds.push(bucket.get_upper_bound());
+                        }
+                        counts.push(histogram.get_sample_count().saturating_sub(below).to_string());
+                        point["count"] = json!(histogram.get_sample_count().to_string());
+                        point["sum"] = json!(histogram.get_sample_sum());
+                        point["bucketCounts"] = json!(counts);
+                        point["explicitBounds"] = json!(bounds);
+                    }
+                    _ => {}
+                }
+                point
+            });
+            let points: Vec<Value> = points.collect();
+            let data = match family.get_field_type() {
+                MetricType::COUNTER => json!({ "sum": { "dataPoints": points, "aggregationTemporality": 2, "isMonotonic": true } }),
+                MetricType::GAUGE => json!({ "gauge": { "dataPoints": points } }),
+                MetricType::HISTOGRAM => json!({ "histogram": { "dataPoints": points, "aggregationTemporality": 2 } }),
+                _ => continue,
+            };
+            let mut metric = json!({ "name": family.get_name(), "description": family.get_help() });
+            if let (Some(metric), Value::Object(data)) = (metric.as_object_mut(), data) {
+                metric.extend(data);
+            }
+            metrics.push(metric);
+        }
+        json!({
+            "resourceMetrics": [{
+                "resource": { "attributes": [{ "key": "service.name", "value": { "stringValue": "hft-engine" } }] },
+                "scopeMetrics": [{ "scope": { "name": "hft-engine" }, "metrics": metrics }],
+            }]
+        })
+    }
+}
+
+#[async_trait]
+impl MetricsSink for OtlpSink {
+    fn name(&self) -> &str {
+        "otlp"
+    }
+
+    async fn export(&self, families: &[MetricFamily]) -> Result<(), HftError> {
+        self.client.post(&self.endpoint)
+            .json(&self.payload(families, unix_nanos()))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| HftError::Sink(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Pushes snapshots of a [`MetricsRegistry`] to a sink every flush interval
+pub struct MetricsExporter {
+    sink: Arc<dyn MetricsSink>,
+    registry: MetricsRegistry,
+    flush_interval: Duration,
+}
+
+impl MetricsExporter {
+    pub fn new(sink: Arc<dyn MetricsSink>, registry: MetricsRegistry, flush_interval: Duration) -> Self {
+        Self { sink, registry, flush_interval }
+    }
+
+    pub async fn flush(&self) {
+        if let Err(e) = self.sink.export(&self.registry.gather()).await {
+            METRICS_EXPORT_FAILURES.with_label_values(&[self.sink.name()]).inc();
+            warn!(backend = self.sink.name(), error = %e, "Failed to export metrics");
+        }
+    }
+
+    /// Flush every interval until the task is dropped
+    pub async fn run(self: Arc<Self>) {
+        info!(backend = self.sink.name(), interval = ?self.flush_interval, "Exporting metrics");
+        let mut interval = tokio::time::interval(self.flush_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.flush().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::{CounterVec, Gauge, HistogramOpts, HistogramVec, Opts, Registry};
+
+    fn registry() -> (Registry, CounterVec, Gauge, HistogramVec) {
+        let registry = Registry::new();
+        let counter = CounterVec::new(Opts::new("acks_total", "acks"), &["venue"]).unwrap();
+        let gauge = Gauge::new("depth", "depth").unwrap();
+        let histogram = HistogramVec::new(HistogramOpts::new("latency", "latency").buckets(vec![0.1, 1.0]), &["venue"]).unwrap();
+        registry.register(Box::new(counter.clone())).unwrap();
+        registry.register(Box::new(gauge.clone())).unwrap();
+        registry.register(Box::new(histogram.clone())).unwrap();
+        (registry, counter, gauge, histogram)
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = MetricsBackendConfig::parse("statsd:127.0.0.1:8125, prefix:hft., flush_ms:500, flush_ms:x, bogus");
+        assert_eq!(config, MetricsBackendConfig {
+            backend: MetricsBackend::Statsd { addr: "127.0.0.1:8125".to_string() },
+            prefix: "hft.".to_string(),
+            flush_interval: Duration::from_millis(500),
+        });
+        let config = MetricsBackendConfig::parse("otlp:http://collector:4318/v1/metrics");
+        assert_eq!(config.backend, MetricsBackend::Otlp { endpoint: "http://collector:4318/v1/metrics".to_string() });
+        assert_eq!(MetricsBackendConfig::parse("prometheus").backend, MetricsBackend::Prometheus);
+    }
+
+    #[tokio::test]
+    async fn test_statsd_sends_counter_deltas() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sink = StatsdSink::connect(&receiver.local_addr().unwrap().to_string(), "hft.").await.unwrap();
+        let (registry, counter, gauge, histogram) = registry();
+
+        counter.with_label_values(&["MOCK"]).inc_by(3.0);
+        gauge.set(7.0);
+        histogram.with_label_values(&["MOCK"]).observe(0.5);
+        sink.export(&registry.gather()).await.unwrap();
+        let mut buffer = [0u8; MAX_DATAGRAM];
+        let len = receiver.recv(&mut buffer).await.unwrap();
+        let datagram = std::str::from_utf8(&buffer[..len]).unwrap();
+        assert_eq!(datagram.lines().collect::<Vec<_>>(), vec![
+            "hft.acks_total:3|c|#venue:MOCK",
+            "hft.depth:7|g",
+            "hft.latency.count:1|c|#venue:MOCK",
+            "hft.latency.sum:0.5|c|#venue:MOCK",
+        ]);
+
+        // Only what moved since the last snapshot, gauges always
+        counter.with_label_values(&["MOCK"]).inc_by(2.0);
+        let lines = sink.lines(&registry.gather());
+        assert_eq!(lines, vec!["hft.acks_total:2|c|#venue:MOCK", "hft.depth:7|g"]);
+    }
+
+    #[test]
+    fn test_otlp_payload() {
+        let (registry, counter, _, histogram) = registry();
+        counter.with_label_values(&["MOCK"]).inc();
+        for latency in [0.05, 0.5, 5.0] {
+            histogram.with_label_values(&["MOCK"]).observe(latency);
+        }
+        let sink = OtlpSink::new("http://localhost:4318/v1/metrics");
+        let payload = sink.payload(&registry.gather(), 42);
+        let metrics = &payload["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+
+        assert_eq!(metrics[0]["name"], "acks_total");
+        assert_eq!(metrics[0]["sum"]["isMonotonic"], true);
+        let point = &metrics[0]["sum"]["dataPoints"][0];
+        assert_eq!((point["asDouble"].as_f64(), point["timeUnixNano"].as_str()), (Some(1.0), Some("42")));
+        assert_eq!(point["attributes"][0]["value"]["stringValue"], "MOCK");
+
+        let point = &metrics[2]["histogram"]["dataPoints"][0];
+        assert_eq!(point["bucketCounts"], json!(["1", "1", "1"]));
+        assert_eq!(point["explicitBounds"], json!([0.1, 1.0]));
+        assert_eq!(point["count"], "3");
+    }
+}
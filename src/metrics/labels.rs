@@ -0,0 +1,127 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use lazy_static::lazy_static;
+use tracing::{info, warn};
+
+use super::METRIC_LABELS_OVERFLOWED;
+
+/// Label used for symbols beyond a metric's cap
+pub const OTHER: &str = "other";
+
+/// Label used for every symbol when per-symbol labels are off
+pub const ALL: &str = "all";
+
+const DEFAULT_MAX_SYMBOLS: usize = 1000;
+
+/// Limits on the symbol labels metrics are exported with
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelConfig {
+    /// Distinct symbols each metric is labelled with; later symbols are
+    /// folded into `other`
+    pub max_symbols: usize,
+    /// When false every symbol is reported as `all`
+    pub per_symbol: bool,
+}
+
+impl Default for LabelConfig {
+    fn default() -> Self {
+        Self { max_symbols: DEFAULT_MAX_SYMBOLS, per_symbol: true }
+    }
+}
+
+impl LabelConfig {
+    /// Read `HFT_METRICS_MAX_SYMBOLS` and `HFT_METRICS_PER_SYMBOL`; returns
+    /// `None` when neither is set
+    pub fn from_env() -> Option<Self> {
+        let max_symbols = std::env::var("HFT_METRICS_MAX_SYMBOLS").ok();
+        let per_symbol = std::env::var("HFT_METRICS_PER_SYMBOL").ok();
+        if max_symbols.is_none() && per_symbol.is_none() {
+            return None;
+        }
+
+        let mut config = Self::default();
+        if let Some(value) = max_symbols {
+            match value.parse() {
+                Ok(max) => config.max_symbols = max,
+                Err(_) => warn!(value = %value, "Ignoring malformed HFT_METRICS_MAX_SYMBOLS"),
+            }
+        }
+        if let Some(value) = per_symbol {
+            config.per_symbol = !matches!(value.as_str(), "0" | "false");
+        }
+        Some(config)
+    }
+}
+
+struct Guard {
+    config: LabelConfig,
+    /// Symbols admitted so far, by metric name
+    admitted: HashMap<&'static str, HashSet<String>>,
+}
+
+impl Guard {
+    fn new(config: LabelConfig) -> Self {
+        Self { config, admitted: HashMap::new() }
+    }
+
+    /// The label for a symbol already decided on, without taking a write lock
+    fn lookup<'a>(&self, metric: &str, symbol: &'a str) -> Option<&'a str> {
+        if !self.config.per_symbol {
+            return Some(ALL);
+        }
+        self.admitted.get(metric).is_some_and(|s| s.contains(symbol)).then_some(symbol)
+    }
+
+    fn admit<'a>(&mut self, metric: &'static str, symbol: &'a str) -> &'a str {
+        if let Some(label) = self.lookup(metric, symbol) {
+            return label;
+        }
+        let max_symbols = self.config.max_symbols;
+        let admitted = self.admitted.entry(metric).or_default();
+        if admitted.len() < max_symbols {
+            admitted.insert(symbol.to_string());
+            return symbol;
+        }
+        METRIC_LABELS_OVERFLOWED.with_label_values(&[metric]).inc();
+        OTHER
+    }
+}
+
+lazy_static! {
+    static ref GUARD: RwLock<Guard> = RwLock::new(Guard::new(LabelConfig::default()));
+}
+
+/// Apply `config` to symbol labels from now on
+pub fn configure(config: LabelConfig) {
+    info!(max_symbols = config.max_symbols, per_symbol = config.per_symbol, "Metric symbol labels configured");
+    *GUARD.write().unwrap() = Guard::new(config);
+}
+
+/// The label to report `symbol` under for `metric`. The first
+/// `max_symbols` symbols a metric sees keep their own label; the rest share
+/// `other`.
+pub fn symbol<'a>(metric: &'static str, symbol: &'a str) -> &'a str {
+    if let Some(label) = GUARD.read().unwrap().lookup(metric, symbol) {
+        return label;
+    }
+    GUARD.write().unwrap().admit(metric, symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbols_past_cap_share_other() {
+        let mut guard = Guard::new(LabelConfig { max_symbols: 2, per_symbol: true });
+        assert_eq!(guard.admit("book_updates", "A"), "A");
+        assert_eq!(guard.admit("book_updates", "B"), "B");
+        assert_eq!(guard.admit("book_updates", "C"), OTHER);
+        assert_eq!(guard.admit("book_updates", "A"), "A");
+        // Each metric has its own budget
+        assert_eq!(guard.admit("quote_throughput", "C"), "C");
+
+        let mut guard = Guard::new(LabelConfig { max_symbols: 2, per_symbol: false });
+        assert_eq!(guard.admit("book_updates", "A"), ALL);
+    }
+}
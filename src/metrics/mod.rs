@@ -1,84 +1,627 @@
 use lazy_static::lazy_static;
-use prometheus::{register_histogram_vec, register_counter_vec, register_gauge_vec};
-use prometheus::{HistogramVec, CounterVec, GaugeVec, Encoder, TextEncoder};
+use prometheus::core::Collector;
+use prometheus::{HistogramOpts, HistogramVec, CounterVec, Gauge, GaugeVec, Opts};
+use std::convert::Infallible;
+use std::sync::Arc;
+use warp::hyper::service::{make_service_fn, service_fn, Service};
+use warp::hyper::{Body, Response, Server};
 use warp::Filter;
 
+use crate::health::{self, HealthRegistry};
+use crate::incident::{self, IncidentLog};
+use crate::command::auth::{self, AdminAuth};
+use crate::risk::{balances, greeks, heatmap, stress, RiskManager};
+use crate::risk::toggles;
+use crate::command::mode;
+use crate::strategy::params::{self, ParameterStore};
+use crate::strategy::{shadow, Shadows};
+use crate::services::status::{self, StatusSource};
+use crate::report::{self, Reporter};
+use crate::execution::manual::{self, ManualOrders};
+
+pub mod backend;
+pub mod labels;
+pub mod registry;
+
+pub use backend::{MetricsBackend, MetricsBackendConfig, MetricsExporter, MetricsSink};
+pub use labels::LabelConfig;
+pub use registry::MetricsRegistry;
+
+// Metric names, help and labels are fixed here, so building them only fails
+// on a typo in this file, which the registry tests catch
+
+fn counter_vec(name: &str, help: &str, labels: &[&str]) -> CounterVec {
+    CounterVec::new(Opts::new(name, help), labels).expect("valid counter definition")
+}
+
+fn gauge_vec(name: &str, help: &str, labels: &[&str]) -> GaugeVec {
+    GaugeVec::new(Opts::new(name, help), labels).expect("valid gauge definition")
+}
+
+fn gauge(name: &str, help: &str) -> Gauge {
+    Gauge::new(name, help).expect("valid gauge definition")
+}
+
+fn histogram_vec(name: &str, help: &str, labels: &[&str], buckets: Vec<f64>) -> HistogramVec {
+    HistogramVec::new(HistogramOpts::new(name, help).buckets(buckets), labels).expect("valid histogram definition")
+}
+
 lazy_static! {
     // Order execution metrics
-    pub static ref ORDER_LATENCY: HistogramVec = register_histogram_vec!(
+    pub static ref ORDER_LATENCY: HistogramVec = histogram_vec(
         "hft_order_latency_seconds",
         "Order execution latency in seconds",
         &["venue", "order_type"],
         vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]
-    ).unwrap();
+    );
 
     // Order book metrics
-    pub static ref ORDERBOOK_UPDATES: CounterVec = register_counter_vec!(
+    pub static ref ORDERBOOK_UPDATES: CounterVec = counter_vec(
         "hft_orderbook_updates_total",
         "Total number of orderbook updates",
         &["symbol"]
-    ).unwrap();
+    );
 
     // Order tracking metrics
-    pub static ref ACTIVE_ORDERS: GaugeVec = register_gauge_vec!(
+    pub static ref ORDER_AMENDS: CounterVec = counter_vec(
+        "hft_order_amends_total",
+        "Order amends by venue and how they were carried out",
+        &["venue", "method"]
+    );
+
+    pub static ref ORDER_ENTRY_REQUESTS: CounterVec = counter_vec(
+        "hft_order_entry_requests_total",
+        "Order entry requests by venue and transport",
+        &["venue", "transport"]
+    );
+
+    pub static ref VENUE_FAILOVERS: CounterVec = counter_vec(
+        "hft_venue_failovers_total",
+        "Orders for unavailable venues by failover action",
+        &["venue", "action"]
+    );
+
+    pub static ref ORDER_ACKS: CounterVec = counter_vec(
+        "hft_order_acks_total",
+        "Orders acknowledged by the venue",
+        &["venue", "strategy"]
+    );
+
+    pub static ref ORDER_REJECTS: CounterVec = counter_vec(
+        "hft_order_rejects_total",
+        "Orders rejected by risk checks, the order gateway or the venue",
+        &["venue", "strategy", "reason"]
+    );
+
+    pub static ref ORDER_CANCELS: CounterVec = counter_vec(
+        "hft_order_cancels_total",
+        "Resting orders cancelled by the engine",
+        &["venue", "strategy", "reason"]
+    );
+
+    pub static ref ORDER_FILLS: CounterVec = counter_vec(
+        "hft_order_fills_total",
+        "Fills received",
+        &["venue", "strategy"]
+    );
+
+    pub static ref ACKED_QUANTITY: CounterVec = counter_vec(
+        "hft_order_acked_quantity_total",
+        "Quantity of orders acknowledged by the venue",
+        &["venue", "strategy"]
+    );
+
+    pub static ref FILLED_QUANTITY: CounterVec = counter_vec(
+        "hft_order_filled_quantity_total",
+        "Quantity filled",
+        &["venue", "strategy"]
+    );
+
+    pub static ref ORDER_RECONCILE_DRIFT: CounterVec = counter_vec(
+        "hft_order_reconcile_drift_total",
+        "Orders found out of step with the venue when reconciling open orders",
+        &["venue", "kind"]
+    );
+
+    pub static ref COMMISSIONS: GaugeVec = gauge_vec(
+        "hft_commissions",
+        "Commissions venues charged on fills since start, in the commission asset; negative for net rebates",
+        &["venue", "asset"]
+    );
+
+    pub static ref ACCOUNT_BALANCE: GaugeVec = gauge_vec(
+        "hft_account_balance",
+        "Venue balances as last polled; kind is total or available",
+        &["venue", "asset", "kind"]
+    );
+
+    pub static ref BALANCE_TRANSFERS: CounterVec = counter_vec(
+        "hft_balance_transfers_total",
+        "Deposits, withdrawals, funding and other balance changes made outside of trading",
+        &["venue", "kind"]
+    );
+
+    pub static ref TRADE_RECONCILE_BREAKS: CounterVec = counter_vec(
+        "hft_trade_reconcile_breaks_total",
+        "Orders whose journalled fills differ from the venue's trade history",
+        &["venue", "kind"]
+    );
+
+    pub static ref ORDERS_EXPIRED: CounterVec = counter_vec(
+        "hft_orders_expired_total",
+        "Resting orders cancelled because their TTL elapsed",
+        &["venue"]
+    );
+
+    pub static ref ACTIVE_ORDERS: GaugeVec = gauge_vec(
         "hft_active_orders",
         "Number of active orders",
         &["venue"]
-    ).unwrap();
+    );
 
     // Quote gateway metrics
-    pub static ref QUOTE_GATEWAY_THROUGHPUT: CounterVec = register_counter_vec!(
+    pub static ref QUOTE_GATEWAY_THROUGHPUT: CounterVec = counter_vec(
         "hft_quote_gateway_throughput_total",
         "Total number of quotes processed by the gateway",
         &["symbol", "venue"]
-    ).unwrap();
+    );
 
-    pub static ref QUOTE_GATEWAY_ERRORS: CounterVec = register_counter_vec!(
+    pub static ref QUOTE_GATEWAY_ERRORS: CounterVec = counter_vec(
         "hft_quote_gateway_errors_total",
         "Total number of errors in the quote gateway",
         &["venue", "error_type"]
-    ).unwrap();
+    );
+
+    pub static ref QUOTES_DEDUPLICATED: CounterVec = counter_vec(
+        "hft_quotes_deduplicated_total",
+        "Quotes dropped by the gateway as repeats of the previous quote",
+        &["symbol", "venue"]
+    );
 
-    pub static ref QUOTE_LATENCY: HistogramVec = register_histogram_vec!(
+    pub static ref DATA_QUALITY_ISSUES: CounterVec = counter_vec(
+        "hft_data_quality_issues_total",
+        "Market data issues found by the quote gateway, by venue and issue",
+        &["venue", "issue"]
+    );
+
+    pub static ref QUOTE_STORM_ACTIVE: GaugeVec = gauge_vec(
+        "hft_quote_storm_active",
+        "Whether each venue's quotes are being conflated because of a quote storm, 0 or 1",
+        &["venue"]
+    );
+
+    pub static ref QUOTE_STORM_INTERVALS: CounterVec = counter_vec(
+        "hft_quote_storm_intervals_total",
+        "One second intervals each venue spent conflated because of a quote storm",
+        &["venue"]
+    );
+
+    pub static ref QUOTES_CONFLATED: CounterVec = counter_vec(
+        "hft_quotes_conflated_total",
+        "Quotes dropped by the gateway while their venue was conflated",
+        &["venue"]
+    );
+
+    pub static ref QUOTES_PAUSED: CounterVec = counter_vec(
+        "hft_quotes_paused_total",
+        "Quotes discarded while their venue's quote reader was paused",
+        &["venue"]
+    );
+
+    pub static ref QUOTES_BACKPRESSURE_DROPPED: CounterVec = counter_vec(
+        "hft_quotes_backpressure_dropped_total",
+        "Quotes the quote gateway dropped because the book builder's channel was full",
+        &["venue", "policy"]
+    );
+
+    pub static ref DATA_QUALITY_SCORE: GaugeVec = gauge_vec(
+        "hft_data_quality_score",
+        "Moving share of each venue's quotes without data quality issues, from 0 to 1",
+        &["venue"]
+    );
+
+    pub static ref LIQUIDATION_DISTANCE: GaugeVec = gauge_vec(
+        "hft_liquidation_distance_pct",
+        "Distance from each venue position's mark price to its liquidation price, as a percentage of the mark",
+        &["venue", "symbol"]
+    );
+
+    pub static ref QUOTE_LATENCY: HistogramVec = histogram_vec(
         "hft_quote_latency_seconds",
         "Quote processing latency in seconds",
         &["venue", "symbol"],
         vec![0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1]
-    ).unwrap();
+    );
+
+    pub static ref BOOK_APPLY_LATENCY: HistogramVec = histogram_vec(
+        "hft_book_apply_latency_seconds",
+        "Time from the book builder receiving a quote to the book reflecting it",
+        &["symbol"],
+        vec![0.000001, 0.000005, 0.00001, 0.000025, 0.00005, 0.0001, 0.00025, 0.0005, 0.001, 0.005, 0.01]
+    );
+
+    pub static ref TIMER_LAG: HistogramVec = histogram_vec(
+        "hft_timer_lag_seconds",
+        "Time from a shared timer's deadline to its callback running",
+        &["timer"],
+        vec![0.0001, 0.0005, 0.001, 0.002, 0.005, 0.01, 0.05]
+    );
 
     // Venue metrics
-    pub static ref VENUE_CONNECTIONS: GaugeVec = register_gauge_vec!(
+    pub static ref BOOK_BEST_BID: GaugeVec = gauge_vec(
+        "hft_book_best_bid",
+        "Best bid price in the book",
+        &["symbol"]
+    );
+
+    pub static ref BOOK_BEST_ASK: GaugeVec = gauge_vec(
+        "hft_book_best_ask",
+        "Best ask price in the book",
+        &["symbol"]
+    );
+
+    pub static ref BOOK_SPREAD_BPS: GaugeVec = gauge_vec(
+        "hft_book_spread_bps",
+        "Best ask minus best bid, in basis points of the mid",
+        &["symbol"]
+    );
+
+    pub static ref BOOK_STALENESS: GaugeVec = gauge_vec(
+        "hft_book_staleness_seconds",
+        "Seconds since the book last updated",
+        &["symbol"]
+    );
+
+    pub static ref BOOK_LEVELS: GaugeVec = gauge_vec(
+        "hft_book_levels",
+        "Price levels held per book side",
+        &["symbol", "side"]
+    );
+
+    pub static ref BOOK_MEMORY_BYTES: GaugeVec = gauge_vec(
+        "hft_book_memory_bytes",
+        "Estimated memory held by each book's price levels",
+        &["symbol"]
+    );
+
+    pub static ref BOOK_LEVELS_PRUNED: CounterVec = counter_vec(
+        "hft_book_levels_pruned_total",
+        "Price levels dropped for exceeding a book's depth limits",
+        &["symbol"]
+    );
+
+    pub static ref BOOK_CHECKSUM_MISMATCHES: CounterVec = counter_vec(
+        "hft_book_checksum_mismatches_total",
+        "Depth updates after which the book no longer matched the venue's checksum",
+        &["venue", "symbol"]
+    );
+
+    pub static ref VENUE_CONNECTIONS: GaugeVec = gauge_vec(
         "hft_venue_connections",
         "Connection status for venues (1=connected, 0=disconnected)",
         &["venue"]
-    ).unwrap();
+    );
 
-    pub static ref VENUE_RECONNECTS: CounterVec = register_counter_vec!(
+    pub static ref VENUE_RECONNECTS: CounterVec = counter_vec(
         "hft_venue_reconnects_total",
         "Total number of venue reconnection attempts",
         &["venue"]
-    ).unwrap();
+    );
+
+    pub static ref VENUE_MESSAGES: CounterVec = counter_vec(
+        "hft_venue_messages_total",
+        "WebSocket messages received per venue connection",
+        &["venue", "connection"]
+    );
+
+    pub static ref VENUE_PARSE_FAILURES: CounterVec = counter_vec(
+        "hft_venue_parse_failures_total",
+        "WebSocket messages per venue connection that could not be parsed",
+        &["venue", "connection"]
+    );
+
+    pub static ref VENUE_MESSAGE_BYTES: HistogramVec = histogram_vec(
+        "hft_venue_message_bytes",
+        "Size of WebSocket messages received per venue connection",
+        &["venue", "connection"],
+        vec![64.0, 128.0, 256.0, 512.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0]
+    );
+
+    pub static ref VENUE_REGION: GaugeVec = gauge_vec(
+        "hft_venue_region_info",
+        "Region each venue runs in, always 1",
+        &["venue", "region"]
+    );
+
+    pub static ref ENGINE_REGION: GaugeVec = gauge_vec(
+        "hft_engine_region_info",
+        "Region this engine runs in, always 1",
+        &["region"]
+    );
+
+    // Risk metrics
+    pub static ref KILL_SWITCH_ENGAGED: Gauge = gauge(
+        "hft_kill_switch_engaged",
+        "Kill switch state (1=trading halted, 0=trading enabled)"
+    );
+
+    pub static ref TRADING_MODE: GaugeVec = gauge_vec(
+        "hft_trading_mode",
+        "Engine trading mode (1=current mode)",
+        &["mode"]
+    );
+
+    pub static ref STRATEGY_PNL: GaugeVec = gauge_vec(
+        "hft_strategy_daily_pnl",
+        "Realized plus unrealized PnL for the current trading day",
+        &["strategy"]
+    );
+
+    pub static ref EXPOSURE_NOTIONAL: GaugeVec = gauge_vec(
+        "hft_exposure_notional",
+        "Notional exposure per asset or group",
+        &["scope", "name", "kind"]
+    );
+
+    pub static ref STRESS_PNL: GaugeVec = gauge_vec(
+        "hft_stress_pnl",
+        "Hypothetical PnL of current positions per stress scenario",
+        &["scenario"]
+    );
+
+    pub static ref PORTFOLIO_GREEKS: GaugeVec = gauge_vec(
+        "hft_portfolio_greeks",
+        "Net portfolio greek per underlying, delta in underlying units",
+        &["underlying", "greek"]
+    );
+
+    pub static ref LOSS_LIMIT_BREACHES: CounterVec = counter_vec(
+        "hft_loss_limit_breaches_total",
+        "Total number of daily loss limit breaches",
+        &["scope"]
+    );
+
+    pub static ref TRADING_DISABLED: GaugeVec = gauge_vec(
+        "hft_trading_disabled",
+        "Operator pause per symbol or strategy (1=paused)",
+        &["scope", "name"]
+    );
+
+    pub static ref HEDGE_ORDERS: CounterVec = counter_vec(
+        "hft_hedge_orders_total",
+        "Hedge orders sent per asset and urgency",
+        &["asset", "urgency"]
+    );
+
+    // Execution metrics
+    pub static ref LEGGING_EVENTS: CounterVec = counter_vec(
+        "hft_legging_events_total",
+        "Multi-leg orders left with some legs live and others rejected",
+        &["policy"]
+    );
+
+    pub static ref LEG_REMEDIATION_ORDERS: CounterVec = counter_vec(
+        "hft_leg_remediation_orders_total",
+        "Orders sent to repair legged multi-leg orders",
+        &["action"]
+    );
+
+    pub static ref OUTAGE_ORDERS: CounterVec = counter_vec(
+        "hft_outage_orders_total",
+        "Orders sent to hedge, flatten or unwind positions stranded on a disconnected venue",
+        &["venue", "action"]
+    );
+
+    pub static ref CONTRACT_ROLL_ORDERS: CounterVec = counter_vec(
+        "hft_contract_roll_orders_total",
+        "Orders sent to roll or close positions in expiring contracts, by action (close, open)",
+        &["venue", "action"]
+    );
+
+    pub static ref TRAILING_STOPS_TRIGGERED: CounterVec = counter_vec(
+        "hft_trailing_stops_triggered_total",
+        "Positions closed by their trailing stop, by strategy",
+        &["strategy"]
+    );
+
+    pub static ref BRACKET_EXITS: CounterVec = counter_vec(
+        "hft_bracket_exits_total",
+        "Bracket positions closed by their take-profit or stop",
+        &["exit"]
+    );
+
+    pub static ref QUOTE_UPDATES_SKIPPED: CounterVec = counter_vec(
+        "hft_quote_updates_skipped_total",
+        "Quote updates withheld by the quote throttle",
+        &["reason"]
+    );
+
+    pub static ref TOXICITY_VPIN: GaugeVec = gauge_vec(
+        "hft_toxicity_vpin",
+        "Volume-synchronized probability of informed trading",
+        &["venue", "symbol"]
+    );
+
+    pub static ref TOXIC_QUOTES_WITHHELD: CounterVec = counter_vec(
+        "hft_toxic_quotes_withheld_total",
+        "Strategy quotes withheld while order flow is toxic",
+        &["strategy", "symbol"]
+    );
+
+    pub static ref CHAOS_FAULTS: CounterVec = counter_vec(
+        "hft_chaos_faults_total",
+        "Faults injected by the gateways for resilience testing",
+        &["component", "fault"]
+    );
+
+    pub static ref FX_RATES: GaugeVec = gauge_vec(
+        "hft_fx_rate",
+        "Value of one unit of a currency in the reporting currency",
+        &["currency", "reporting_currency"]
+    );
+
+    pub static ref ENGINE_LEADER: Gauge = gauge(
+        "hft_engine_leader",
+        "Failover role (1=leader sending orders, 0=standby)"
+    );
+
+    pub static ref METRIC_LABELS_OVERFLOWED: CounterVec = counter_vec(
+        "hft_metric_labels_overflowed_total",
+        "Observations reported under the `other` symbol because the metric hit its label cap",
+        &["metric"]
+    );
+
+    pub static ref COMPONENT_RESTARTS: CounterVec = counter_vec(
+        "hft_component_restarts_total",
+        "Times the supervisor restarted a component task after it ended",
+        &["component"]
+    );
+
+    // Downstream sink metrics
+    pub static ref SINK_DROPPED_EVENTS: CounterVec = counter_vec(
+        "hft_sink_dropped_events_total",
+        "Order events dropped before reaching a downstream sink",
+        &["sink"]
+    );
+
+    pub static ref METRICS_EXPORT_FAILURES: CounterVec = counter_vec(
+        "hft_metrics_export_failures_total",
+        "Metric snapshots that failed to reach a pushed metrics backend",
+        &["backend"]
+    );
 }
 
-async fn metrics_handler() -> Result<impl warp::Reply, warp::Rejection> {
-    let encoder = TextEncoder::new();
-    let mut buffer = vec![];
-    encoder.encode(&prometheus::gather(), &mut buffer).unwrap();
+/// Every metric above, for registering with a [`MetricsRegistry`]
+pub(crate) fn engine_metrics() -> Vec<Box<dyn Collector>> {
+    vec![
+        Box::new(ORDER_LATENCY.clone()),
+        Box::new(ORDERBOOK_UPDATES.clone()),
+        Box::new(ORDER_AMENDS.clone()),
+        Box::new(ORDER_ENTRY_REQUESTS.clone()),
+        Box::new(VENUE_FAILOVERS.clone()),
+        Box::new(ORDER_ACKS.clone()),
+        Box::new(ORDER_REJECTS.clone()),
+        Box::new(ORDER_CANCELS.clone()),
+        Box::new(ORDER_FILLS.clone()),
+        Box::new(ACKED_QUANTITY.clone()),
+        Box::new(FILLED_QUANTITY.clone()),
+        Box::new(ORDER_RECONCILE_DRIFT.clone()),
+        Box::new(TRADE_RECONCILE_BREAKS.clone()),
+        Box::new(COMMISSIONS.clone()),
+        Box::new(ACCOUNT_BALANCE.clone()),
+        Box::new(BALANCE_TRANSFERS.clone()),
+        Box::new(ORDERS_EXPIRED.clone()),
+        Box::new(ACTIVE_ORDERS.clone()),
+        Box::new(QUOTE_GATEWAY_THROUGHPUT.clone()),
+        Box::new(QUOTE_GATEWAY_ERRORS.clone()),
+        Box::new(QUOTES_DEDUPLICATED.clone()),
+        Box::new(DATA_QUALITY_ISSUES.clone()),
+        Box::new(QUOTE_STORM_ACTIVE.clone()),
+        Box::new(QUOTE_STORM_INTERVALS.clone()),
+        Box::new(QUOTES_CONFLATED.clone()),
+        Box::new(QUOTES_PAUSED.clone()),
+        Box::new(QUOTES_BACKPRESSURE_DROPPED.clone()),
+        Box::new(DATA_QUALITY_SCORE.clone()),
+        Box::new(LIQUIDATION_DISTANCE.clone()),
+        Box::new(QUOTE_LATENCY.clone()),
+        Box::new(BOOK_APPLY_LATENCY.clone()),
+        Box::new(TIMER_LAG.clone()),
+        Box::new(BOOK_BEST_BID.clone()),
+        Box::new(BOOK_BEST_ASK.clone()),
+        Box::new(BOOK_SPREAD_BPS.clone()),
+        Box::new(BOOK_STALENESS.clone()),
+        Box::new(BOOK_LEVELS.clone()),
+        Box::new(BOOK_MEMORY_BYTES.clone()),
+        Box::new(BOOK_LEVELS_PRUNED.clone()),
+        Box::new(BOOK_CHECKSUM_MISMATCHES.clone()),
+        Box::new(VENUE_CONNECTIONS.clone()),
+        Box::new(VENUE_RECONNECTS.clone()),
+        Box::new(VENUE_MESSAGES.clone()),
+        Box::new(VENUE_PARSE_FAILURES.clone()),
+        Box::new(VENUE_MESSAGE_BYTES.clone()),
+        Box::new(VENUE_REGION.clone()),
+        Box::new(ENGINE_REGION.clone()),
+        Box::new(KILL_SWITCH_ENGAGED.clone()),
+        Box::new(TRADING_MODE.clone()),
+        Box::new(STRATEGY_PNL.clone()),
+        Box::new(EXPOSURE_NOTIONAL.clone()),
+        Box::new(PORTFOLIO_GREEKS.clone()),
+        Box::new(STRESS_PNL.clone()),
+        Box::new(LOSS_LIMIT_BREACHES.clone()),
+        Box::new(TRADING_DISABLED.clone()),
+        Box::new(HEDGE_ORDERS.clone()),
+        Box::new(LEGGING_EVENTS.clone()),
+        Box::new(LEG_REMEDIATION_ORDERS.clone()),
+        Box::new(OUTAGE_ORDERS.clone()),
+        Box::new(CONTRACT_ROLL_ORDERS.clone()),
+        Box::new(TRAILING_STOPS_TRIGGERED.clone()),
+        Box::new(BRACKET_EXITS.clone()),
+        Box::new(QUOTE_UPDATES_SKIPPED.clone()),
+        Box::new(TOXICITY_VPIN.clone()),
+        Box::new(TOXIC_QUOTES_WITHHELD.clone()),
+        Box::new(CHAOS_FAULTS.clone()),
+        Box::new(FX_RATES.clone()),
+        Box::new(ENGINE_LEADER.clone()),
+        Box::new(METRIC_LABELS_OVERFLOWED.clone()),
+        Box::new(COMPONENT_RESTARTS.clone()),
+        Box::new(SINK_DROPPED_EVENTS.clone()),
+        Box::new(METRICS_EXPORT_FAILURES.clone()),
+    ]
+}
 
-    Ok(warp::reply::with_header(
-        String::from_utf8(buffer).unwrap(),
-        "content-type",
-        encoder.format_type(),
-    ))
+async fn metrics_handler(metrics: MetricsRegistry) -> Result<impl warp::Reply, warp::Rejection> {
+    let body = match metrics.encode() {
+        Ok(buffer) => String::from_utf8_lossy(&buffer).into_owned(),
+        Err(e) => format!("# failed to encode metrics: {}\n", e),
+    };
+    Ok(warp::reply::with_header(body, "content-type", prometheus::TEXT_FORMAT))
 }
 
-pub async fn init_metrics_server() {
+/// Serve metrics and health probes openly, and the admin API behind `auth`
+#[allow(clippy::too_many_arguments)]
+pub async fn init_metrics_server(metrics: MetricsRegistry, health: HealthRegistry, params: ParameterStore, status: StatusSource, reports: Reporter, risk: Arc<RiskManager>, orders: ManualOrders, shadows: Shadows, incidents: IncidentLog, auth: AdminAuth) {
     let metrics_route = warp::path("metrics")
         .and(warp::get())
+        .and(warp::any().map(move || metrics.clone()))
         .and_then(metrics_handler);
 
+    let admin = toggles::routes(risk.toggles())
+        .or(mode::routes(risk.trading_state()))
+        .or(params::routes(params))
+        .or(status::routes(status))
+        .or(report::routes(reports))
+        .or(heatmap::routes(Arc::clone(&risk)))
+        .or(greeks::routes(Arc::clone(&risk)))
+        .or(stress::routes(Arc::clone(&risk)))
+        .or(balances::routes(risk))
+        .or(shadow::routes(shadows))
+        .or(incident::routes(incidents))
+        .or(manual::routes(orders));
+
+    let routes = metrics_route
+        .or(health::routes(health))
+        .or(auth::protect(auth).and(admin))
+        .recover(auth::recover);
+
+    // Bodies are hashed ahead of the filters so the two-man rule can bind
+    // approvals to them
+    let service = warp::service(routes);
+    let make_service = make_service_fn(move |_| {
+        let service = service.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let mut service = service.clone();
+                async move {
+                    match auth::digest_body(request).await {
+                        Ok(request) => service.call(request).await,
+                        Err(status) => Ok(Response::builder().status(status).body(Body::empty()).unwrap()),
+                    }
+                }
+            }))
+        }
+    });
+
     println!("Starting metrics server on port 9090");
 
-    tokio::spawn(warp::serve(metrics_route)
-        .run(([0, 0, 0, 0], 9090)));
+    tokio::spawn(Server::bind(&([0, 0, 0, 0], 9090).into()).serve(make_service));
 }
\ No newline at end of file
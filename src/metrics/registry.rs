@@ -0,0 +1,99 @@
+//! Where the engine's metrics are registered for export.
+//!
+//! The metric families themselves are process-wide statics, so any
+//! component can count without being handed anything. Which registry they
+//! are exported from is chosen by whoever builds the engine: the global
+//! `prometheus` registry by default, or an isolated one for tests and for
+//! embedding several engines in one process. Registering into a registry
+//! that already holds the engine's metrics is a no-op rather than an error.
+
+use prometheus::core::Collector;
+use prometheus::proto::MetricFamily;
+use prometheus::{Encoder, Registry, TextEncoder};
+use tracing::{debug, warn};
+
+use crate::error::HftError;
+
+/// A registry the engine's metrics are exported from; cloning shares it
+#[derive(Clone)]
+pub struct MetricsRegistry {
+    registry: Registry,
+}
+
+impl Default for MetricsRegistry {
+    fn default() -> Self {
+        Self::global()
+    }
+}
+
+impl MetricsRegistry {
+    /// The process-wide `prometheus` registry, shared with anything else in
+    /// the process that registers there
+    pub fn global() -> Self {
+        Self { registry: prometheus::default_registry().clone() }
+    }
+
+    /// A registry of its own, seeing only what is registered with it
+    pub fn isolated() -> Self {
+        Self { registry: Registry::new() }
+    }
+
+    /// Register `collector`, returning false when it was already
+    /// registered or could not be
+    pub fn register(&self, collector: Box<dyn Collector>) -> bool {
+        let names: Vec<String> = collector.desc().iter().map(|d| d.fq_name.clone()).collect();
+        match self.registry.register(collector) {
+            Ok(()) => true,
+            Err(prometheus::Error::AlreadyReg) => {
+                debug!(metrics = ?names, "Metrics already registered");
+                false
+            }
+            Err(e) => {
+                warn!(metrics = ?names, error = %e, "Failed to register metrics");
+                false
+            }
+        }
+    }
+
+    /// Register every engine metric, returning how many were new
+    pub fn register_engine_metrics(&self) -> usize {
+        super::engine_metrics().into_iter()
+            .map(|collector| self.register(collector))
+            .filter(|registered| *registered)
+            .count()
+    }
+
+    pub fn gather(&self) -> Vec<MetricFamily> {
+        self.registry.gather()
+    }
+
+    /// The registered metrics in the Prometheus text format
+    pub fn encode(&self) -> Result<Vec<u8>, HftError> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.gather(), &mut buffer)
+            .map_err(|e| HftError::Serialization(e.to_string()))?;
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::ORDER_ACKS;
+
+    #[test]
+    fn test_isolated_registration_is_idempotent() {
+        let first = MetricsRegistry::isolated();
+        let registered = first.register_engine_metrics();
+        assert_eq!(registered, crate::metrics::engine_metrics().len());
+        assert_eq!(first.register_engine_metrics(), 0);
+
+        // A second engine in the process gets its own registry
+        let second = MetricsRegistry::isolated();
+        assert_eq!(second.register_engine_metrics(), registered);
+
+        ORDER_ACKS.with_label_values(&["REGISTRY_TEST", "none"]).inc();
+        let text = String::from_utf8(second.encode().unwrap()).unwrap();
+        assert!(text.contains("hft_order_acks_total{strategy=\"none\",venue=\"REGISTRY_TEST\"}"));
+    }
+}
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+
+use crate::execution::{OpenOrder, OrderTracker};
+use crate::risk::{Position, PositionKey, RiskManager};
+
+#[cfg(feature = "redis")]
+pub mod redis;
+
+const DEFAULT_PREFIX: &str = "hft";
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Point-in-time view of trading state mirrored to external observers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirrorState {
+    /// Milliseconds since the Unix epoch
+    pub updated_at: u64,
+    pub positions: Vec<(PositionKey, Position)>,
+    pub open_orders: Vec<OpenOrder>,
+}
+
+/// Where mirrored state is read from
+#[derive(Clone)]
+pub struct MirrorSource {
+    pub(crate) risk: Arc<RiskManager>,
+    pub(crate) orders: Arc<OrderTracker>,
+}
+
+impl MirrorSource {
+    pub async fn capture(&self) -> MirrorState {
+        MirrorState {
+            updated_at: chrono::Utc::now().timestamp_millis() as u64,
+            positions: self.risk.positions().await,
+            open_orders: self.orders.open_orders().await,
+        }
+    }
+
+    /// Adopt state mirrored by another instance
+    pub async fn apply(&self, state: MirrorState) {
+        self.risk.restore_positions(state.positions).await;
+        self.orders.replace_all(state.open_orders).await;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MirrorConfig {
+    pub url: String,
+    /// Prepended to every key, so several engines can share one server
+    pub prefix: String,
+    pub flush_interval: Duration,
+}
+
+impl MirrorConfig {
+    /// Read `HFT_REDIS_URL`, `HFT_REDIS_PREFIX` and `HFT_REDIS_FLUSH_MS`;
+    /// returns `None` when no URL is configured
+    pub fn from_env() -> Option<Self> {
+        let url = std::env::var("HFT_REDIS_URL").ok()?;
+        let flush_interval = std::env::var("HFT_REDIS_FLUSH_MS")
+            .ok()
+            .and_then(|ms| ms.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_FLUSH_INTERVAL);
+
+        Some(Self {
+            url,
+            prefix: std::env::var("HFT_REDIS_PREFIX").unwrap_or_else(|_| DEFAULT_PREFIX.to_string()),
+            flush_interval,
+        })
+    }
+
+    /// Latest positions as JSON
+    pub fn positions_key(&self) -> String {
+        format!("{}:positions", self.prefix)
+    }
+
+    /// Latest open orders as JSON
+    pub fn orders_key(&self) -> String {
+        format!("{}:open_orders", self.prefix)
+    }
+
+    /// Stream of full state updates, capped in length
+    pub fn stream_key(&self) -> String {
+        format!("{}:state", self.prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::LossLimits;
+    use crate::types::{Fill, OrderSide};
+
+    #[tokio::test]
+    async fn test_capture_includes_positions() {
+        let source = MirrorSource {
+            risk: Arc::new(RiskManager::new(LossLimits::default())),
+            orders: Arc::new(OrderTracker::new()),
+        };
+        source.risk.on_fill(&Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "MOCK".into(),
+            strategy: "mm".to_string(),
+            side: OrderSide::Buy,
+            quantity: 2.0,
+            price: 50000.0,
+            timestamp: 1,
+            commission: None,
+        }).await;
+
+        let state = source.capture().await;
+        assert_eq!(state.positions.len(), 1);
+        assert_eq!(state.positions[0].1.quantity, 2.0);
+        assert!(state.open_orders.is_empty());
+
+        let config = MirrorConfig {
+            url: "redis://localhost".to_string(),
+            prefix: "engine-a".to_string(),
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+        };
+        assert_eq!(config.positions_key(), "engine-a:positions");
+    }
+}
@@ -0,0 +1,112 @@
+use redis::aio::ConnectionManager;
+use redis::streams::StreamRangeReply;
+use redis::AsyncCommands;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::error::HftError;
+use crate::failover::Leadership;
+use super::{MirrorConfig, MirrorSource, MirrorState};
+
+/// Entries kept in the state stream
+const STREAM_MAX_LEN: usize = 1000;
+
+/// Mirrors positions and open orders to Redis on a fixed interval.
+///
+/// Each flush overwrites the latest-value keys and appends the full state
+/// to a capped stream. The connection manager reconnects on its own; failed
+/// flushes are logged and retried on the next tick.
+///
+/// With a leadership handle, a standby instance reads the leader's state
+/// instead of writing its own, so it is current when it takes over.
+pub struct RedisMirror {
+    config: MirrorConfig,
+    conn: ConnectionManager,
+    leadership: Option<Leadership>,
+}
+
+impl RedisMirror {
+    pub async fn connect(config: MirrorConfig) -> Result<Self, HftError> {
+        let client = redis::Client::open(config.url.as_str())
+            .map_err(|e| HftError::Config(format!("Invalid Redis URL: {}", e)))?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .map_err(|e| HftError::Sink(format!("Failed to connect to Redis: {}", e)))?;
+
+        Ok(Self { config, conn, leadership: None })
+    }
+
+    pub fn with_leadership(mut self, leadership: Leadership) -> Self {
+        self.leadership = Some(leadership);
+        self
+    }
+
+    /// Read the most recently mirrored state, if any has been written
+    pub async fn load(&mut self) -> Result<Option<MirrorState>, HftError> {
+        let reply: StreamRangeReply = self.conn
+            .xrevrange_count(self.config.stream_key(), "+", "-", 1)
+            .await
+            .map_err(|e| HftError::Sink(format!("Redis read failed: {}", e)))?;
+
+        let Some(json) = reply.ids.first().and_then(|entry| entry.get::<String>("state")) else {
+            return Ok(None);
+        };
+
+        serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| HftError::Serialization(format!("Invalid mirrored state: {}", e)))
+    }
+
+    async fn flush(&mut self, state: &MirrorState) -> Result<(), HftError> {
+        let positions = to_json(&state.positions)?;
+        let orders = to_json(&state.open_orders)?;
+        let full = to_json(state)?;
+
+        redis::pipe()
+            .atomic()
+            .set(self.config.positions_key(), positions)
+            .ignore()
+            .set(self.config.orders_key(), orders)
+            .ignore()
+            .cmd("XADD")
+            .arg(self.config.stream_key())
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(STREAM_MAX_LEN)
+            .arg("*")
+            .arg("state")
+            .arg(full)
+            .ignore()
+            .query_async::<()>(&mut self.conn)
+            .await
+            .map_err(|e| HftError::Sink(format!("Redis flush failed: {}", e)))
+    }
+
+    pub async fn run(mut self, source: MirrorSource) {
+        info!(prefix = %self.config.prefix, "Mirroring engine state to Redis");
+        let mut interval = tokio::time::interval(self.config.flush_interval);
+
+        loop {
+            interval.tick().await;
+            let standby = self.leadership.as_ref().is_some_and(|l| !l.is_leader());
+            if standby {
+                match self.load().await {
+                    Ok(Some(state)) => source.apply(state).await,
+                    Ok(None) => {}
+                    Err(e) => warn!(error = %e, "Failed to read mirrored state from Redis"),
+                }
+                continue;
+            }
+
+            let state = source.capture().await;
+            if let Err(e) = self.flush(&state).await {
+                warn!(error = %e, "Failed to mirror state to Redis");
+            }
+        }
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, HftError> {
+    serde_json::to_string(value)
+        .map_err(|e| HftError::Serialization(format!("Failed to serialize mirror state: {}", e)))
+}
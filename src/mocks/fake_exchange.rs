@@ -0,0 +1,522 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+use warp::http::StatusCode;
+use warp::ws::{Message, WebSocket};
+use warp::Filter;
+
+/// Listen key handed out by `POST /fapi/v1/listenKey`; the user data
+/// stream is the market data connection carrying it as its stream
+const LISTEN_KEY: &str = "fake-listen-key";
+
+/// A frame pushed to market data connections
+#[derive(Debug, Clone)]
+enum Frame {
+    /// Sent to connections subscribed to `stream`, or to all when `None`
+    Text { stream: Option<String>, text: String },
+    Close,
+}
+
+/// An order entry request the exchange received
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// `POST /fapi/v1/order` style for REST, the method name (`order.place`)
+    /// for the WebSocket API
+    pub method: String,
+    pub params: HashMap<String, String>,
+    pub api_key: Option<String>,
+}
+
+struct State {
+    frames: broadcast::Sender<Frame>,
+    /// Open market data connections
+    connections: AtomicUsize,
+    /// Market data connections accepted so far
+    connects: AtomicUsize,
+    /// Streams requested by each market data connection
+    streams: Mutex<Vec<Vec<String>>>,
+    requests: Mutex<Vec<RecordedRequest>>,
+    next_order_id: AtomicU64,
+    /// (HTTP status, Binance error code, message) returned to order requests
+    reject: Mutex<Option<(u16, i64, String)>>,
+    klines: Mutex<Vec<Value>>,
+    /// Orders listed as open, removed by cancel-all for their symbol
+    open_orders: Mutex<Vec<Value>>,
+    user_trades: Mutex<Vec<Value>>,
+    balances: Mutex<Vec<Value>>,
+    incomes: Mutex<Vec<Value>>,
+    /// Added to the local clock for `/fapi/v1/time`
+    clock_offset_ms: Mutex<i64>,
+    /// Symbols listed as trading in exchange info
+    symbols: Mutex<Vec<String>>,
+    /// API key signed requests must carry
+    api_key: Mutex<Option<String>>,
+}
+
+impl State {
+    fn record(&self, method: String, params: HashMap<String, String>, api_key: Option<String>) {
+        self.requests.lock().unwrap().push(RecordedRequest { method, params, api_key });
+    }
+
+    /// Response to an order request: the rejection if one is set, otherwise
+    /// an ack carrying the request's order ID or a new one
+    fn order_response(&self, params: &HashMap<String, String>) -> (u16, Value) {
+        if let Some((status, code, msg)) = self.reject.lock().unwrap().clone() {
+            return (status, json!({ "code": code, "msg": msg }));
+        }
+        let order_id = params
+            .get("orderId")
+            .and_then(|id| id.parse().ok())
+            .unwrap_or_else(|| self.next_order_id.fetch_add(1, Ordering::Relaxed));
+        (200, json!({
+            "orderId": order_id,
+            "symbol": params.get("symbol"),
+            "status": "NEW",
+        }))
+    }
+}
+
+/// In-process Binance Futures stand-in for integration tests.
+///
+/// Serves the market data streams (`bookTicker` and depth diffs) and the
+/// user data stream at `ws_url`, the order and listen key REST endpoints, user trades, balances, income and
+/// klines under `rest_url`, and the WebSocket order entry API at `ws_api_url`, all on
+/// one local port. Tests push market data, inspect the order requests
+/// received, make orders fail and drop connections to exercise reconnect
+/// paths.
+pub struct FakeExchange {
+    addr: SocketAddr,
+    state: Arc<State>,
+}
+
+impl FakeExchange {
+    pub async fn start() -> Self {
+        let (frames, _) = broadcast::channel(1024);
+        let state = Arc::new(State {
+            frames,
+            connections: AtomicUsize::new(0),
+            connects: AtomicUsize::new(0),
+            streams: Mutex::new(Vec::new()),
+            requests: Mutex::new(Vec::new()),
+            next_order_id: AtomicU64::new(1),
+            reject: Mutex::new(None),
+            klines: Mutex::new(Vec::new()),
+            open_orders: Mutex::new(Vec::new()),
+            user_trades: Mutex::new(Vec::new()),
+            balances: Mutex::new(Vec::new()),
+            incomes: Mutex::new(Vec::new()),
+            clock_offset_ms: Mutex::new(0),
+            symbols: Mutex::new(vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]),
+            api_key: Mutex::new(None),
+        });
+
+        let with_state = {
+            let state = Arc::clone(&state);
+            warp::any().map(move || Arc::clone(&state))
+        };
+
+        let market_data = warp::path("ws")
+            .and(warp::path::tail())
+            .and(warp::ws())
+            .and(with_state.clone())
+            .map(|tail: warp::path::Tail, ws: warp::ws::Ws, state: Arc<State>| {
+                let streams = tail.as_str().split('/').filter(|s| !s.is_empty()).map(str::to_string).collect();
+                ws.on_upgrade(move |socket| serve_market_data(socket, streams, state))
+            });
+
+        let ws_api = warp::path!("ws-fapi" / "v1")
+            .and(warp::ws())
+            .and(with_state.clone())
+            .map(|ws: warp::ws::Ws, state: Arc<State>| ws.on_upgrade(move |socket| serve_ws_api(socket, state)));
+
+        let order = warp::path!("fapi" / "v1" / "order")
+            .and(warp::post().or(warp::put()).unify().or(warp::delete()).unify())
+            .and(warp::method())
+            .and(warp::query::<HashMap<String, String>>())
+            .and(warp::header::optional::<String>("x-mbx-apikey"))
+            .and(with_state.clone())
+            .map(|method: warp::http::Method, params: HashMap<String, String>, api_key: Option<String>, state: Arc<State>| {
+                let (status, body) = state.order_response(&params);
+                state.record(format!("{} /fapi/v1/order", method), params, api_key);
+                reply(status, body)
+            });
+
+        let cancel_all = warp::path!("fapi" / "v1" / "allOpenOrders")
+            .and(warp::delete())
+            .and(warp::query::<HashMap<String, String>>())
+            .and(warp::header::optional::<String>("x-mbx-apikey"))
+            .and(with_state.clone())
+            .map(|params: HashMap<String, String>, api_key: Option<String>, state: Arc<State>| {
+                let Some(symbol) = params.get("symbol").cloned() else {
+                    return reply(400, json!({ "code": -1102, "msg": "Mandatory parameter 'symbol' was not sent." }));
+                };
+                state.open_orders.lock().unwrap().retain(|order| order["symbol"] != symbol.as_str());
+                state.record("DELETE /fapi/v1/allOpenOrders".to_string(), params, api_key);
+                reply(200, json!({ "code": 200, "msg": "The operation of cancel all open order is done." }))
+            });
+
+        let open_orders = warp::path!("fapi" / "v1" / "openOrders")
+            .and(warp::get())
+            .and(warp::query::<HashMap<String, String>>())
+            .and(warp::header::optional::<String>("x-mbx-apikey"))
+            .and(with_state.clone())
+            .map(|params: HashMap<String, String>, api_key: Option<String>, state: Arc<State>| {
+                let orders: Vec<Value> = state.open_orders.lock().unwrap()
+                    .iter()
+                    .filter(|order| params.get("symbol").is_none_or(|symbol| order["symbol"] == symbol.as_str()))
+                    .cloned()
+                    .collect();
+                state.record("GET /fapi/v1/openOrders".to_string(), params, api_key);
+                reply(200, Value::Array(orders))
+            });
+
+        let listen_key = warp::path!("fapi" / "v1" / "listenKey")
+            .and(warp::post().or(warp::put()).unify())
+            .and(warp::method())
+            .and(warp::header::optional::<String>("x-mbx-apikey"))
+            .and(with_state.clone())
+            .map(|method: warp::http::Method, api_key: Option<String>, state: Arc<State>| {
+                state.record(format!("{} /fapi/v1/listenKey", method), HashMap::new(), api_key);
+                reply(200, json!({ "listenKey": LISTEN_KEY }))
+            });
+
+        let klines = warp::path!("fapi" / "v1" / "klines")
+            .and(warp::get())
+            .and(with_state.clone())
+            .map(|state: Arc<State>| reply(200, Value::Array(state.klines.lock().unwrap().clone())));
+
+        let user_trades = warp::path!("fapi" / "v1" / "userTrades")
+            .and(warp::get())
+            .and(warp::query::<HashMap<String, String>>())
+            .and(warp::header::optional::<String>("x-mbx-apikey"))
+            .and(with_state.clone())
+            .map(|params: HashMap<String, String>, api_key: Option<String>, state: Arc<State>| {
+                let trades: Vec<Value> = state.user_trades.lock().unwrap()
+                    .iter()
+                    .filter(|trade| params.get("symbol").is_some_and(|symbol| trade["symbol"] == symbol.as_str()))
+                    .cloned()
+                    .collect();
+                state.record("GET /fapi/v1/userTrades".to_string(), params, api_key);
+                reply(200, Value::Array(trades))
+            });
+
+        let incomes = warp::path!("fapi" / "v1" / "income")
+            .and(warp::get())
+            .and(warp::query::<HashMap<String, String>>())
+            .and(warp::header::optional::<String>("x-mbx-apikey"))
+            .and(with_state.clone())
+            .map(|params: HashMap<String, String>, api_key: Option<String>, state: Arc<State>| {
+                let since = params.get("startTime").and_then(|t| t.parse::<u64>().ok()).unwrap_or(0);
+                let incomes: Vec<Value> = state.incomes.lock().unwrap()
+                    .iter()
+                    .filter(|income| income["time"].as_u64().is_some_and(|time| time >= since))
+                    .cloned()
+                    .collect();
+                state.record("GET /fapi/v1/income".to_string(), params, api_key);
+                reply(200, Value::Array(incomes))
+            });
+
+        let time = warp::path!("fapi" / "v1" / "time")
+            .and(warp::get())
+            .and(with_state.clone())
+            .map(|state: Arc<State>| {
+                let now = chrono::Utc::now().timestamp_millis() + *state.clock_offset_ms.lock().unwrap();
+                reply(200, json!({ "serverTime": now }))
+            });
+
+        let exchange_info = warp::path!("fapi" / "v1" / "exchangeInfo")
+            .and(warp::get())
+            .and(with_state.clone())
+            .map(|state: Arc<State>| {
+                let symbols: Vec<Value> = state.symbols.lock().unwrap()
+                    .iter()
+                    .map(|s| json!({ "symbol": s, "status": "TRADING" }))
+                    .collect();
+                reply(200, json!({ "symbols": symbols }))
+            });
+
+        let balance = warp::path!("fapi" / "v2" / "balance")
+            .and(warp::get())
+            .and(warp::header::optional::<String>("x-mbx-apikey"))
+            .and(with_state)
+            .map(|api_key: Option<String>, state: Arc<State>| {
+                let expected = state.api_key.lock().unwrap().clone();
+                if expected.is_some_and(|expected| api_key.as_ref() != Some(&expected)) {
+                    return reply(401, json!({ "code": -2015, "msg": "Invalid API-key, IP, or permissions for action." }));
+                }
+                reply(200, Value::Array(state.balances.lock().unwrap().clone()))
+            });
+
+        let routes = market_data.or(ws_api).or(order).or(cancel_all).or(open_orders).or(listen_key).or(klines).or(user_trades).or(incomes).or(time).or(exchange_info).or(balance);
+        let (addr, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        Self { addr, state }
+    }
+
+    /// Base URL for market data streams, as passed to `with_ws_url`
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}/ws", self.addr)
+    }
+
+    /// Base URL for REST calls, as passed to `with_rest_url`
+    pub fn rest_url(&self) -> String {
+        format!("http://{}/fapi", self.addr)
+    }
+
+    /// WebSocket order entry API, as passed to `with_ws_order_entry`
+    pub fn ws_api_url(&self) -> String {
+        format!("ws://{}/ws-fapi/v1", self.addr)
+    }
+
+    /// Push a `bookTicker` update to connections subscribed to the symbol
+    pub fn push_book_ticker(&self, symbol: &str, bid: f64, bid_qty: f64, ask: f64, ask_qty: f64, time: u64) {
+        let text = json!({
+            "e": "bookTicker",
+            "u": time,
+            "s": symbol,
+            "b": bid.to_string(),
+            "B": bid_qty.to_string(),
+            "a": ask.to_string(),
+            "A": ask_qty.to_string(),
+            "T": time,
+            "E": time,
+        });
+        self.push(Some(format!("{}@bookTicker", symbol.to_lowercase())), text.to_string());
+    }
+
+    /// Push a `depthUpdate` diff of (price, quantity) levels to connections
+    /// subscribed to the symbol's depth stream
+    pub fn push_depth(&self, symbol: &str, bids: &[(f64, f64)], asks: &[(f64, f64)], first_id: u64, last_id: u64, time: u64) {
+        let levels = |levels: &[(f64, f64)]| -> Vec<[String; 2]> {
+            levels.iter().map(|(p, q)| [p.to_string(), q.to_string()]).collect()
+        };
+        let text = json!({
+            "e": "depthUpdate",
+            "E": time,
+            "T": time,
+            "s": symbol,
+            "U": first_id,
+            "u": last_id,
+            "b": levels(bids),
+            "a": levels(asks),
+        });
+        self.push(Some(format!("{}@depth", symbol.to_lowercase())), text.to_string());
+    }
+
+    /// Push an `ORDER_TRADE_UPDATE` for a trade on `order_id` to the user
+    /// data stream
+    pub fn push_order_trade(&self, symbol: &str, order_id: u64, side: &str, quantity: f64, price: f64, time: u64) {
+        let text = json!({
+            "e": "ORDER_TRADE_UPDATE",
+            "E": time,
+            "T": time,
+            "o": {
+                "s": symbol,
+                "S": side,
+                "o": "LIMIT",
+                "x": "TRADE",
+                "X": "FILLED",
+                "i": order_id,
+                "l": quantity.to_string(),
+                "L": price.to_string(),
+                "n": "0.01",
+                "N": "USDT",
+                "T": time,
+            },
+        });
+        self.push(Some(LISTEN_KEY.to_string()), text.to_string());
+    }
+
+    /// Send a raw text frame to every market data connection
+    pub fn send_raw(&self, text: &str) {
+        self.push(None, text.to_string());
+    }
+
+    fn push(&self, stream: Option<String>, text: String) {
+        let _ = self.state.frames.send(Frame::Text { stream, text });
+    }
+
+    /// Close every market data connection
+    pub fn drop_connections(&self) {
+        let _ = self.state.frames.send(Frame::Close);
+    }
+
+    /// Open market data connections
+    pub fn connections(&self) -> usize {
+        self.state.connections.load(Ordering::SeqCst)
+    }
+
+    /// Market data connections accepted since start
+    pub fn connects(&self) -> usize {
+        self.state.connects.load(Ordering::SeqCst)
+    }
+
+    /// Streams the most recent market data connection carries, including
+    /// live `SUBSCRIBE`/`UNSUBSCRIBE` changes
+    pub fn streams(&self) -> Vec<String> {
+        self.state.streams.lock().unwrap().last().cloned().unwrap_or_default()
+    }
+
+    /// Wait until `n` market data connections are open, so pushed frames
+    /// reach them
+    pub async fn wait_for_connections(&self, n: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while self.connections() != n {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("expected {} connections, have {}", n, self.connections()));
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.requests.lock().unwrap().clone()
+    }
+
+    /// Fail order requests with a Binance error until `accept_orders`
+    pub fn reject_orders(&self, status: u16, code: i64, msg: &str) {
+        *self.state.reject.lock().unwrap() = Some((status, code, msg.to_string()));
+    }
+
+    pub fn accept_orders(&self) {
+        *self.state.reject.lock().unwrap() = None;
+    }
+
+    /// Run the exchange clock `offset_ms` ahead of the local one
+    pub fn set_clock_offset(&self, offset_ms: i64) {
+        *self.state.clock_offset_ms.lock().unwrap() = offset_ms;
+    }
+
+    /// Symbols exchange info lists as trading
+    pub fn set_symbols(&self, symbols: &[&str]) {
+        *self.state.symbols.lock().unwrap() = symbols.iter().map(|s| s.to_string()).collect();
+    }
+
+    /// Reject signed account requests that do not carry `api_key`
+    pub fn require_api_key(&self, api_key: &str) {
+        *self.state.api_key.lock().unwrap() = Some(api_key.to_string());
+    }
+
+    /// Orders returned by the open orders endpoint, each with at least
+    /// `orderId` and `symbol`
+    pub fn set_open_orders(&self, orders: Vec<Value>) {
+        *self.state.open_orders.lock().unwrap() = orders;
+    }
+
+    /// Trades returned by the user trades endpoint, filtered by symbol
+    pub fn set_user_trades(&self, trades: Vec<Value>) {
+        *self.state.user_trades.lock().unwrap() = trades;
+    }
+
+    /// Assets returned by the balance endpoint
+    pub fn set_balances(&self, balances: Vec<Value>) {
+        *self.state.balances.lock().unwrap() = balances;
+    }
+
+    /// Entries returned by the income endpoint, filtered by start time
+    pub fn set_incomes(&self, incomes: Vec<Value>) {
+        *self.state.incomes.lock().unwrap() = incomes;
+    }
+
+    /// Rows returned by the klines endpoint
+    pub fn set_klines(&self, rows: Vec<Value>) {
+        *self.state.klines.lock().unwrap() = rows;
+    }
+}
+
+fn reply(status: u16, body: Value) -> warp::reply::WithStatus<warp::reply::Json> {
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    warp::reply::with_status(warp::reply::json(&body), status)
+}
+
+async fn serve_market_data(socket: WebSocket, mut streams: Vec<String>, state: Arc<State>) {
+    // Subscribe before counting the connection so no pushed frame is missed
+    let mut frames = state.frames.subscribe();
+    let index = {
+        let mut connections = state.streams.lock().unwrap();
+        connections.push(streams.clone());
+        connections.len() - 1
+    };
+    state.connects.fetch_add(1, Ordering::SeqCst);
+    state.connections.fetch_add(1, Ordering::SeqCst);
+
+    let (mut write, mut read) = socket.split();
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Ok(Frame::Text { stream, text }) => {
+                    if stream.is_some_and(|s| !streams.contains(&s)) {
+                        continue;
+                    }
+                    if write.send(Message::text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(Frame::Close) | Err(broadcast::error::RecvError::Closed) => {
+                    let _ = write.send(Message::close()).await;
+                    break;
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+            },
+            message = read.next() => match message {
+                Some(Ok(message)) if message.is_close() => break,
+                Some(Ok(message)) => {
+                    // Live `SUBSCRIBE`/`UNSUBSCRIBE` requests change the streams
+                    let Some(request) = message.to_str().ok().and_then(|t| serde_json::from_str::<Value>(t).ok()) else { continue };
+                    let names = request["params"].as_array().into_iter().flatten().filter_map(Value::as_str);
+                    match request["method"].as_str() {
+                        Some("SUBSCRIBE") => streams.extend(names.map(str::to_string)),
+                        Some("UNSUBSCRIBE") => {
+                            let names: Vec<&str> = names.collect();
+                            streams.retain(|s| !names.contains(&s.as_str()));
+                        }
+                        _ => continue,
+                    }
+                    state.streams.lock().unwrap()[index] = streams.clone();
+                    let response = json!({ "result": null, "id": request["id"] });
+                    if write.send(Message::text(response.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+                _ => break,
+            },
+        }
+    }
+    state.connections.fetch_sub(1, Ordering::SeqCst);
+}
+
+/// Answer `order.*` requests on the WebSocket API like the REST endpoints
+async fn serve_ws_api(socket: WebSocket, state: Arc<State>) {
+    let (mut write, mut read) = socket.split();
+    while let Some(Ok(message)) = read.next().await {
+        let Ok(text) = message.to_str() else { continue };
+        let Ok(request) = serde_json::from_str::<Value>(text) else { continue };
+
+        let params: HashMap<String, String> = request["params"]
+            .as_object()
+            .map(|params| {
+                params.iter()
+                    .map(|(k, v)| (k.clone(), v.as_str().map_or_else(|| v.to_string(), str::to_string)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let (status, body) = state.order_response(&params);
+        let api_key = params.get("apiKey").cloned();
+        state.record(request["method"].as_str().unwrap_or_default().to_string(), params, api_key);
+
+        let response = if status == 200 {
+            json!({ "id": request["id"], "status": status, "result": body })
+        } else {
+            json!({ "id": request["id"], "status": status, "error": body })
+        };
+        if write.send(Message::text(response.to_string())).await.is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Local forward proxy for venue transport tests, speaking HTTP (`CONNECT`
+/// and absolute-form requests) and SOCKS5 without authentication on the
+/// same port. Records the `host:port` of every connection it relays.
+pub struct FakeProxy {
+    addr: SocketAddr,
+    targets: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeProxy {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let targets = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = Arc::clone(&targets);
+        tokio::spawn(async move {
+            while let Ok((client, _)) = listener.accept().await {
+                let recorded = Arc::clone(&recorded);
+                tokio::spawn(async move {
+                    if let Some((target, mut client, mut upstream)) = open(client).await {
+                        recorded.lock().unwrap().push(target);
+                        let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+                    }
+                });
+            }
+        });
+
+        Self { addr, targets }
+    }
+
+    /// e.g. `http://127.0.0.1:port`
+    pub fn url(&self, scheme: &str) -> String {
+        format!("{}://{}", scheme, self.addr)
+    }
+
+    /// Targets relayed so far, in order
+    pub fn targets(&self) -> Vec<String> {
+        self.targets.lock().unwrap().clone()
+    }
+}
+
+/// Handshake with the client and connect upstream
+async fn open(mut client: TcpStream) -> Option<(String, TcpStream, TcpStream)> {
+    let first = client.read_u8().await.ok()?;
+    if first == 0x05 {
+        let methods = client.read_u8().await.ok()?;
+        client.read_exact(&mut vec![0u8; methods as usize]).await.ok()?;
+        client.write_all(&[0x05, 0x00]).await.ok()?;
+
+        let mut header = [0u8; 4];
+        client.read_exact(&mut header).await.ok()?;
+        let host = match header[3] {
+            0x01 => {
+                let mut ip = [0u8; 4];
+                client.read_exact(&mut ip).await.ok()?;
+                std::net::Ipv4Addr::from(ip).to_string()
+            }
+            0x03 => {
+                let mut name = vec![0u8; client.read_u8().await.ok()? as usize];
+                client.read_exact(&mut name).await.ok()?;
+                String::from_utf8(name).ok()?
+            }
+            _ => return None,
+        };
+        let target = format!("{}:{}", host, client.read_u16().await.ok()?);
+        let upstream = TcpStream::connect(&target).await.ok()?;
+        client.write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]).await.ok()?;
+        return Some((target, client, upstream));
+    }
+
+    // HTTP, starting from the byte already read
+    http(client, vec![first]).await
+}
+
+async fn http(mut client: TcpStream, mut head: Vec<u8>) -> Option<(String, TcpStream, TcpStream)> {
+    while !head.ends_with(b"\r\n\r\n") {
+        head.push(client.read_u8().await.ok()?);
+    }
+    let request = String::from_utf8_lossy(&head).to_string();
+    let mut words = request.split_whitespace();
+    let method = words.next()?;
+    let target = words.next()?;
+    if method == "CONNECT" {
+        let upstream = TcpStream::connect(target).await.ok()?;
+        client.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n").await.ok()?;
+        return Some((target.to_string(), client, upstream));
+    }
+
+    // Absolute-form `GET http://host:port/path`, passed on as is
+    let authority = target.strip_prefix("http://")?.split('/').next()?.to_string();
+    let mut upstream = TcpStream::connect(&authority).await.ok()?;
+    upstream.write_all(&head).await.ok()?;
+    Some((authority, client, upstream))
+}
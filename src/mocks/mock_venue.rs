@@ -1,11 +1,13 @@
 #[cfg(test)]
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 #[cfg(test)]
 use std::collections::HashMap;
 #[cfg(test)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(test)]
 use tokio::sync::{mpsc, RwLock};
 #[cfg(test)]
-use tokio::time::{Duration, sleep};
+use tokio::time::Duration;
 #[cfg(test)]
 use async_trait::async_trait;
 #[cfg(test)]
@@ -16,9 +18,48 @@ use chrono::Utc;
 #[cfg(test)]
 use crate::error::{HftError, VenueError};
 #[cfg(test)]
-use crate::types::{Order, Quote, OrderSide, OrderType};
+use crate::types::{Fill, Order, Quote, OrderSide, OrderType};
+#[cfg(test)]
+use crate::venues::{AssetBalance, Transfer, VenueAdapter, VenueCapabilities};
+#[cfg(test)]
+use crate::venues::margin::{MarginMode, MarginSettings, PositionRisk};
+#[cfg(test)]
+use crate::rng::{component_rng, env_seed, SimRng};
+
+// Margin settings of symbols never changed
+#[cfg(test)]
+const DEFAULT_MARGIN: MarginSettings = MarginSettings { leverage: 20, mode: MarginMode::Cross };
+
+// A failure the venue stages on purpose
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault {
+    // Hold the order's ack back; its fill goes out on time and overtakes it
+    DelayAck(Duration),
+    // Report the order's fill twice
+    DuplicateFill,
+    // Fail the order as if the connection had dropped
+    DropOrder,
+    // Send the quote after the one that follows it
+    ReorderQuote,
+    // Quote stream goes silent before this quote while order entry keeps working
+    QuoteOutage(Duration),
+}
+
 #[cfg(test)]
-use crate::venues::VenueAdapter;
+impl Fault {
+    fn affects_orders(&self) -> bool {
+        matches!(self, Fault::DelayAck(_) | Fault::DuplicateFill | Fault::DropOrder)
+    }
+}
+
+// `fault` hits the `step`th order or quote, counting from 1
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptedFault {
+    pub step: usize,
+    pub fault: Fault,
+}
 
 #[cfg(test)]
 #[derive(Clone)]
@@ -28,6 +69,19 @@ pub struct MockVenueConfig {
     pub latency_ms: u64,
     pub error_probability: f64,
     pub disconnect_probability: f64,
+    // Failures staged at fixed steps, on top of the random ones
+    pub scenario: Vec<ScriptedFault>,
+    // Seed for quotes and random failures, so a failing run can be replayed
+    pub seed: u64,
+}
+
+#[cfg(test)]
+impl MockVenueConfig {
+    fn fault_at(&self, step: usize, orders: bool) -> Option<Fault> {
+        self.scenario.iter()
+            .find(|s| s.step == step && s.fault.affects_orders() == orders)
+            .map(|s| s.fault.clone())
+    }
 }
 
 #[cfg(test)]
@@ -43,6 +97,8 @@ impl Default for MockVenueConfig {
             latency_ms: 5,
             error_probability: 0.01,
             disconnect_probability: 0.001,
+            scenario: Vec::new(),
+            seed: env_seed().unwrap_or(0),
         }
     }
 }
@@ -53,28 +109,64 @@ pub struct MockVenue {
     config: MockVenueConfig,
     subscribed_symbols: Arc<RwLock<Vec<String>>>,
     quote_tx: Option<mpsc::Sender<Quote>>,
+    fill_tx: Option<mpsc::Sender<Fill>>,
+    order_steps: Arc<AtomicUsize>,
     is_running: Arc<RwLock<bool>>,
     order_responses: Arc<RwLock<HashMap<String, Result<String, HftError>>>>,
+    submitted_orders: Arc<RwLock<Vec<Order>>>,
+    cancel_all_count: Arc<RwLock<usize>>,
+    open_order_ids: Arc<RwLock<Vec<String>>>,
+    order_rng: Arc<Mutex<SimRng>>,
+    margin: Arc<RwLock<HashMap<String, MarginSettings>>>,
+    max_leverage: Arc<RwLock<Option<u32>>>,
+    position_risks: Arc<RwLock<Vec<PositionRisk>>>,
+    trades: Arc<RwLock<Vec<Fill>>>,
+    balances: Arc<RwLock<Vec<AssetBalance>>>,
+    transfers: Arc<RwLock<Vec<Transfer>>>,
 }
 
 #[cfg(test)]
 impl MockVenue {
     pub fn new(name: &str, config: MockVenueConfig) -> Self {
+        let order_rng = component_rng(config.seed, &format!("{}/orders", name));
         Self {
             name: name.to_string(),
             config,
             subscribed_symbols: Arc::new(RwLock::new(Vec::new())),
             quote_tx: None,
+            fill_tx: None,
+            order_steps: Arc::new(AtomicUsize::new(0)),
             is_running: Arc::new(RwLock::new(false)),
             order_responses: Arc::new(RwLock::new(HashMap::new())),
+            submitted_orders: Arc::new(RwLock::new(Vec::new())),
+            cancel_all_count: Arc::new(RwLock::new(0)),
+            open_order_ids: Arc::new(RwLock::new(Vec::new())),
+            order_rng: Arc::new(Mutex::new(order_rng)),
+            margin: Arc::new(RwLock::new(HashMap::new())),
+            max_leverage: Arc::new(RwLock::new(None)),
+            position_risks: Arc::new(RwLock::new(Vec::new())),
+            trades: Arc::new(RwLock::new(Vec::new())),
+            balances: Arc::new(RwLock::new(Vec::new())),
+            transfers: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
+    // Venue order IDs still open
+    pub async fn open_order_ids(&self) -> Vec<String> {
+        self.open_order_ids.read().await.clone()
+    }
+
     pub fn with_quote_sender(mut self, quote_tx: mpsc::Sender<Quote>) -> Self {
         self.quote_tx = Some(quote_tx);
         self
     }
 
+    // Fill every accepted order in full at its limit price
+    pub fn with_fill_sender(mut self, fill_tx: mpsc::Sender<Fill>) -> Self {
+        self.fill_tx = Some(fill_tx);
+        self
+    }
+
     // Configure a specific response for an order with the given symbol and side
     pub async fn set_order_response(&self, symbol: &str, side: OrderSide, response: Result<String, HftError>) {
         let key = format!("{}:{:?}", symbol, side);
@@ -82,63 +174,39 @@ impl MockVenue {
         responses.insert(key, response);
     }
 
-    // Helper function to generate and send a single quote
-    async fn generate_and_send_quote(
-        symbol: &str,
-        config: &MockVenueConfig,
-        venue_name: &str,
-        quote_tx: &mpsc::Sender<Quote>
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Create RNG inside this function (not carried across await points)
-        let mut rng = rand::thread_rng();
-
-        // Simulate random connectivity issues
-        if rng.gen::<f64>() < config.disconnect_probability {
-            sleep(Duration::from_millis(500)).await;
-            return Ok(());
-        }
+    // Orders that made it past validation, in submission order
+    pub async fn submitted_orders(&self) -> Vec<Order> {
+        self.submitted_orders.read().await.clone()
+    }
 
-        // Simulate random errors
-        if rng.gen::<f64>() < config.error_probability {
-            return Ok(());
-        }
+    // Number of times cancel_all_orders has been called
+    pub async fn cancel_all_count(&self) -> usize {
+        *self.cancel_all_count.read().await
+    }
 
-        // Get base price for this symbol
-        let base_price = *config.symbol_base_prices.get(symbol).unwrap_or(&100.0);
-
-        // Generate random price movements (±0.5%)
-        let price_movement = (rng.gen::<f64>() - 0.5) * 0.01 * base_price;
-        let mid_price = base_price + price_movement;
-
-        // Create spread around mid price
-        let spread = mid_price * 0.0002; // 0.02% spread
-        let bid = mid_price - spread / 2.0;
-        let ask = mid_price + spread / 2.0;
-
-        // Random sizes
-        let bid_size = rng.gen_range(0.1..10.0);
-        let ask_size = rng.gen_range(0.1..10.0);
-
-        let quote = Quote {
-            symbol: symbol.to_string(),
-            bid,
-            ask,
-            bid_size,
-            ask_size,
-            venue: venue_name.to_string(),
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_else(|_| std::time::Duration::from_secs(0))
-                .as_millis() as u64,
-        };
+    // Quietly cap leverage set from now on, as a venue's leverage brackets do
+    pub async fn cap_leverage(&self, max: u32) {
+        *self.max_leverage.write().await = Some(max);
+    }
 
-        // Simulate network latency
-        sleep(Duration::from_millis(config.latency_ms)).await;
+    // Executions reported by trade_history
+    pub async fn set_trades(&self, trades: Vec<Fill>) {
+        *self.trades.write().await = trades;
+    }
 
-        // Send quote
-        quote_tx.send(quote).await?;
+    // Positions reported by position_risks
+    pub async fn set_position_risks(&self, risks: Vec<PositionRisk>) {
+        *self.position_risks.write().await = risks;
+    }
 
-        Ok(())
+    // Balances reported by balances
+    pub async fn set_balances(&self, balances: Vec<AssetBalance>) {
+        *self.balances.write().await = balances;
+    }
+
+    // Balance changes reported by transfers
+    pub async fn set_transfers(&self, transfers: Vec<Transfer>) {
+        *self.transfers.write().await = transfers;
     }
 
     #[cfg(test)]
@@ -154,40 +222,23 @@ impl MockVenue {
         let is_running = self.is_running.clone();
 
         *is_running.write().await = true;
+        let mut step = 0;
+        let mut held: Option<Quote> = None;
+        let mut rng = component_rng(config.seed, &format!("{}/quotes", venue_name));
 
-        // Completely avoid using random number generation in the async task
-        // by precomputing all the necessary values in a separate task
         tokio::spawn(async move {
-            use std::sync::Arc;
-            use tokio::sync::Mutex;
-
             while *is_running.read().await {
                 // Read symbols
                 let symbols = subscribed_symbols.read().await.clone();
 
                 // Process each symbol independently
                 for symbol in &symbols {
-                    // Generate all random values in a sync context before we send them to async
-                    // This approach allows us to use ThreadRng safely
-                    let should_skip_disconnect;
-                    let should_skip_error;
-                    let price_movement;
-                    let bid_size;
-                    let ask_size;
-
-                    {
-                        // Create a new rng just for this scope
-                        // It won't cross any await points
-                        let mut rng = rand::thread_rng();
-
-                        should_skip_disconnect = rng.gen::<f64>() < config.disconnect_probability;
-                        should_skip_error = rng.gen::<f64>() < config.error_probability;
-                        price_movement = (rng.gen::<f64>() - 0.5) * 0.01;
-                        bid_size = rng.gen_range(0.1..10.0);
-                        ask_size = rng.gen_range(0.1..10.0);
-                    }
+                    let should_skip_disconnect = rng.random::<f64>() < config.disconnect_probability;
+                    let should_skip_error = rng.random::<f64>() < config.error_probability;
+                    let price_movement = (rng.random::<f64>() - 0.5) * 0.01;
+                    let bid_size = rng.random_range(0.1..10.0);
+                    let ask_size = rng.random_range(0.1..10.0);
 
-                    // Now we can use the precomputed random values in the async context
                     if should_skip_disconnect {
                         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
                         continue;
@@ -207,24 +258,41 @@ impl MockVenue {
                     let ask = mid_price + spread / 2.0;
 
                     let quote = Quote {
-                        symbol: symbol.clone(),
+                        symbol: symbol.as_str().into(),
                         bid,
                         ask,
                         bid_size,
                         ask_size,
-                        venue: venue_name.clone(),
+                        venue: venue_name.as_str().into(),
                         timestamp: std::time::SystemTime::now()
                             .duration_since(std::time::UNIX_EPOCH)
                             .unwrap_or_else(|_| std::time::Duration::from_secs(0))
                             .as_millis() as u64,
                     };
 
+                    step += 1;
+                    match config.fault_at(step, false) {
+                        Some(Fault::ReorderQuote) => {
+                            held = Some(quote);
+                            continue;
+                        }
+                        Some(Fault::QuoteOutage(outage)) => tokio::time::sleep(outage).await,
+                        _ => {}
+                    }
+
                     // Simulate network latency
                     tokio::time::sleep(tokio::time::Duration::from_millis(config.latency_ms)).await;
 
-                    // Send quote
-                    if let Err(e) = quote_tx.send(quote).await {
-                        eprintln!("Failed to send mock quote: {}", e);
+                    // Send quote, then any quote held back to arrive after it
+                    let mut failed = false;
+                    for quote in std::iter::once(quote).chain(held.take()) {
+                        if let Err(e) = quote_tx.send(quote).await {
+                            eprintln!("Failed to send mock quote: {}", e);
+                            failed = true;
+                            break;
+                        }
+                    }
+                    if failed {
                         break;
                     }
                 }
@@ -249,16 +317,21 @@ impl VenueAdapter for MockVenue {
         self.name.clone()
     }
 
+    fn capabilities(&self) -> VenueCapabilities {
+        VenueCapabilities { cancel_all: true, ..VenueCapabilities::default() }
+    }
+
     async fn subscribe_quotes(&self, symbols: Vec<String>) -> Result<(), HftError> {
         if symbols.is_empty() {
             return Err(VenueError::SubscriptionFailed("Empty symbol list".to_string()).into());
         }
 
-        // Store subscribed symbols
+        // Add to the subscribed symbols, as a new stream on a real venue would
         {
             let mut subscribed = self.subscribed_symbols.write().await;
-            subscribed.clear();
             subscribed.extend(symbols);
+            subscribed.sort();
+            subscribed.dedup();
         }
 
         // Start generating quotes if not already running
@@ -269,11 +342,26 @@ impl VenueAdapter for MockVenue {
         Ok(())
     }
 
+    async fn unsubscribe_quotes(&self, symbols: Vec<String>) -> Result<(), HftError> {
+        let mut subscribed = self.subscribed_symbols.write().await;
+        subscribed.retain(|s| !symbols.contains(s));
+        if subscribed.is_empty() {
+            *self.is_running.write().await = false;
+        }
+        Ok(())
+    }
+
     #[cfg(test)]
     async fn submit_order(&self, order: Order) -> Result<String, HftError> {
         // Simulate network latency first
         tokio::time::sleep(tokio::time::Duration::from_millis(self.config.latency_ms)).await;
 
+        let step = self.order_steps.fetch_add(1, Ordering::SeqCst) + 1;
+        let fault = self.config.fault_at(step, true);
+        if fault == Some(Fault::DropOrder) {
+            return Err(VenueError::ConnectionFailed("Connection dropped".to_string()).into());
+        }
+
         // Check for configured response
         let key = format!("{}:{:?}", order.symbol, order.side);
         let responses = self.order_responses.read().await;
@@ -297,11 +385,7 @@ impl VenueAdapter for MockVenue {
             ).into());
         }
 
-        // Compute random value before any other await points
-        let should_fail = {
-            let mut rng = rand::thread_rng();
-            rng.gen::<f64>() < self.config.error_probability
-        };
+        let should_fail = self.order_rng.lock().unwrap().random::<f64>() < self.config.error_probability;
 
         if should_fail {
             return Err(VenueError::OrderSubmissionFailed("Random failure".to_string()).into());
@@ -311,9 +395,93 @@ impl VenueAdapter for MockVenue {
         let timestamp = Utc::now().timestamp_millis();
         let order_id = format!("mock_order_{}_{}", order.symbol.to_lowercase(), timestamp);
 
+        if let Some(fill_tx) = self.fill_tx.clone() {
+            let fill = Fill {
+                order_id: order_id.clone(),
+                symbol: order.symbol,
+                venue: self.name.as_str().into(),
+                strategy: String::new(),
+                side: order.side,
+                quantity: order.quantity,
+                price: order.price,
+                timestamp: timestamp as u64,
+                commission: None,
+            };
+            let copies = if fault == Some(Fault::DuplicateFill) { 2 } else { 1 };
+            let latency = Duration::from_millis(self.config.latency_ms);
+            tokio::spawn(async move {
+                tokio::time::sleep(latency).await;
+                for _ in 0..copies {
+                    let _ = fill_tx.send(fill.clone()).await;
+                }
+            });
+        }
+
+        self.submitted_orders.write().await.push(order);
+        self.open_order_ids.write().await.push(order_id.clone());
+
+        if let Some(Fault::DelayAck(delay)) = fault {
+            tokio::time::sleep(delay).await;
+        }
+
         Ok(order_id)
     }
 
+    async fn cancel_order(&self, order_id: &str, _symbol: &str) -> Result<(), HftError> {
+        let mut open = self.open_order_ids.write().await;
+        let index = open.iter().position(|id| id == order_id)
+            .ok_or_else(|| VenueError::UnknownOrder(order_id.to_string()))?;
+        open.remove(index);
+        Ok(())
+    }
+
+    async fn cancel_all_orders(&self) -> Result<(), HftError> {
+        *self.cancel_all_count.write().await += 1;
+        self.open_order_ids.write().await.clear();
+        Ok(())
+    }
+
+    async fn open_orders(&self) -> Result<Vec<String>, HftError> {
+        Ok(self.open_order_ids.read().await.clone())
+    }
+
+    async fn set_leverage(&self, symbol: &str, leverage: u32) -> Result<(), HftError> {
+        let leverage = self.max_leverage.read().await.map_or(leverage, |max| leverage.min(max));
+        let mut margin = self.margin.write().await;
+        margin.entry(symbol.to_string()).or_insert(DEFAULT_MARGIN).leverage = leverage;
+        Ok(())
+    }
+
+    async fn set_margin_mode(&self, symbol: &str, mode: MarginMode) -> Result<(), HftError> {
+        let mut margin = self.margin.write().await;
+        margin.entry(symbol.to_string()).or_insert(DEFAULT_MARGIN).mode = mode;
+        Ok(())
+    }
+
+    async fn margin_settings(&self, symbol: &str) -> Result<MarginSettings, HftError> {
+        Ok(self.margin.read().await.get(symbol).copied().unwrap_or(DEFAULT_MARGIN))
+    }
+
+    async fn position_risks(&self) -> Result<Vec<PositionRisk>, HftError> {
+        Ok(self.position_risks.read().await.clone())
+    }
+
+    async fn trade_history(&self, symbol: &str, since: u64, until: u64) -> Result<Vec<Fill>, HftError> {
+        Ok(self.trades.read().await
+            .iter()
+            .filter(|fill| &*fill.symbol == symbol && (since..=until).contains(&fill.timestamp))
+            .cloned()
+            .collect())
+    }
+
+    async fn balances(&self) -> Result<Vec<AssetBalance>, HftError> {
+        Ok(self.balances.read().await.clone())
+    }
+
+    async fn transfers(&self, since: u64) -> Result<Vec<Transfer>, HftError> {
+        Ok(self.transfers.read().await.iter().filter(|t| t.timestamp >= since).cloned().collect())
+    }
+
     async fn stop(&self) -> Result<(), HftError> {
         self.stop().await;
         Ok(())
@@ -355,12 +523,15 @@ mod tests {
         venue.set_order_response("BTCUSDT", OrderSide::Buy, Err(error)).await;
 
         let order = Order {
-            symbol: "BTCUSDT".to_string(),
+            symbol: "BTCUSDT".into(),
             side: OrderSide::Buy,
             quantity: 1.0,
             price: 50000.0,
-            venue: "MOCK".to_string(),
+            venue: "MOCK".into(),
             order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
         };
 
         let result = venue.submit_order(order).await;
@@ -370,16 +541,134 @@ mod tests {
         venue.set_order_response("ETHUSDT", OrderSide::Sell, Ok("specific_order_id".to_string())).await;
 
         let order = Order {
-            symbol: "ETHUSDT".to_string(),
+            symbol: "ETHUSDT".into(),
             side: OrderSide::Sell,
             quantity: 1.0,
             price: 3000.0,
-            venue: "MOCK".to_string(),
+            venue: "MOCK".into(),
             order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
         };
 
         let result = venue.submit_order(order).await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "specific_order_id");
     }
-}
\ No newline at end of file
+
+    fn order(quantity: f64) -> Order {
+        Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity,
+            price: 50000.0,
+            venue: "MOCK".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scripted_order_faults() {
+        let (fill_tx, mut fill_rx) = mpsc::channel(16);
+        let venue = MockVenue::new("MOCK", MockVenueConfig {
+            latency_ms: 0,
+            error_probability: 0.0,
+            scenario: vec![
+                ScriptedFault { step: 2, fault: Fault::DuplicateFill },
+                ScriptedFault { step: 3, fault: Fault::DropOrder },
+                ScriptedFault { step: 4, fault: Fault::DelayAck(Duration::from_millis(200)) },
+                // Quote faults leave orders alone
+                ScriptedFault { step: 1, fault: Fault::QuoteOutage(Duration::from_secs(1)) },
+            ],
+            ..MockVenueConfig::default()
+        }).with_fill_sender(fill_tx);
+
+        venue.submit_order(order(1.0)).await.unwrap();
+        assert_eq!(fill_rx.recv().await.unwrap().quantity, 1.0);
+
+        let id = venue.submit_order(order(2.0)).await.unwrap();
+        for _ in 0..2 {
+            assert_eq!(fill_rx.recv().await.unwrap().order_id, id);
+        }
+
+        let dropped = venue.submit_order(order(3.0)).await;
+        assert!(matches!(dropped, Err(HftError::Venue(VenueError::ConnectionFailed(_)))));
+        assert_eq!(venue.submitted_orders().await.len(), 2);
+
+        // The fill for the fourth order lands while its ack is still held
+        let venue = Arc::new(venue);
+        let ack = tokio::spawn({
+            let venue = Arc::clone(&venue);
+            async move { venue.submit_order(order(4.0)).await }
+        });
+        let fill = tokio::time::timeout(Duration::from_millis(150), fill_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(fill.quantity, 4.0);
+        assert!(!ack.is_finished());
+        assert_eq!(ack.await.unwrap().unwrap(), fill.order_id);
+        assert!(fill_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scripted_quote_faults() {
+        let (tx, mut rx) = mpsc::channel(100);
+        let venue = MockVenue::new("MOCK", MockVenueConfig {
+            quote_interval_ms: 20,
+            latency_ms: 0,
+            error_probability: 0.0,
+            disconnect_probability: 0.0,
+            scenario: vec![
+                ScriptedFault { step: 2, fault: Fault::ReorderQuote },
+                ScriptedFault { step: 4, fault: Fault::QuoteOutage(Duration::from_millis(300)) },
+            ],
+            ..MockVenueConfig::default()
+        }).with_quote_sender(tx);
+        venue.subscribe_quotes(vec!["BTCUSDT".to_string()]).await.unwrap();
+
+        let mut quotes = Vec::new();
+        let mut received = Vec::new();
+        for _ in 0..4 {
+            quotes.push(rx.recv().await.unwrap());
+            received.push(std::time::Instant::now());
+        }
+        venue.stop().await;
+
+        // Quote 2 arrives after quote 3
+        assert!(quotes[1].timestamp > quotes[2].timestamp);
+        assert!(received[3] - received[2] >= Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_seed_replays_quotes_and_failures() {
+        async fn run(seed: u64) -> (Vec<(f64, f64, f64)>, Vec<bool>) {
+            let (tx, mut rx) = mpsc::channel(100);
+            let venue = MockVenue::new("MOCK", MockVenueConfig {
+                quote_interval_ms: 1,
+                latency_ms: 0,
+                error_probability: 0.5,
+                disconnect_probability: 0.0,
+                seed,
+                ..MockVenueConfig::default()
+            }).with_quote_sender(tx);
+            venue.subscribe_quotes(vec!["BTCUSDT".to_string()]).await.unwrap();
+            let mut quotes = Vec::new();
+            for _ in 0..5 {
+                let quote = rx.recv().await.unwrap();
+                quotes.push((quote.bid, quote.bid_size, quote.ask_size));
+            }
+            venue.stop().await;
+
+            let mut accepted = Vec::new();
+            for _ in 0..10 {
+                accepted.push(venue.submit_order(order(1.0)).await.is_ok());
+            }
+            (quotes, accepted)
+        }
+
+        assert_eq!(run(42).await, run(42).await);
+        assert_ne!(run(42).await, run(43).await);
+    }
+}
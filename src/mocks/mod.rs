@@ -1,2 +1,6 @@
 #[cfg(test)]
-pub mod mock_venue;
\ No newline at end of file
+pub mod mock_venue;
+#[cfg(test)]
+pub mod fake_exchange;
+#[cfg(test)]
+pub mod fake_proxy;
@@ -0,0 +1,359 @@
+//! Python bindings for research notebooks, built as the `hft_engine_py`
+//! extension module with `maturin develop`.
+//!
+//! Books, recordings and backtests are driven by the same code as
+//! production, so results in a notebook match what the engine would have
+//! seen.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::backtest::{self, Backtest, BacktestReport, Fees, FillModel, QueuePosition, TopOfBook};
+use crate::book::OrderBook;
+use crate::snapshot::EngineSnapshot;
+use crate::strategy::{MarketMaker, ParameterStore};
+use crate::types::Quote;
+
+#[pyclass(name = "OrderBook")]
+struct PyOrderBook {
+    inner: OrderBook,
+}
+
+#[pymethods]
+impl PyOrderBook {
+    #[new]
+    fn new(symbol: String) -> Self {
+        Self { inner: OrderBook::new(symbol.into()) }
+    }
+
+    #[getter]
+    fn symbol(&self) -> &str {
+        self.inner.symbol()
+    }
+
+    /// Apply a top-of-book quote; a size of zero removes the level
+    #[pyo3(signature = (bid, ask, bid_size, ask_size, timestamp = 0))]
+    fn update(&mut self, bid: f64, ask: f64, bid_size: f64, ask_size: f64, timestamp: u64) {
+        self.inner.update(&Quote {
+            symbol: self.inner.symbol().into(),
+            bid,
+            ask,
+            bid_size,
+            ask_size,
+            venue: Default::default(),
+            timestamp,
+        });
+    }
+
+    fn best_bid(&self) -> Option<(f64, f64)> {
+        self.inner.best_bid()
+    }
+
+    fn best_ask(&self) -> Option<(f64, f64)> {
+        self.inner.best_ask()
+    }
+
+    fn mid(&self) -> Option<f64> {
+        let (bid, _) = self.inner.best_bid()?;
+        let (ask, _) = self.inner.best_ask()?;
+        Some((bid + ask) / 2.0)
+    }
+
+    /// `(price, size)` levels, best first
+    fn bids(&self) -> Vec<(f64, f64)> {
+        self.inner.snapshot().bids
+    }
+
+    /// `(price, size)` levels, best first
+    fn asks(&self) -> Vec<(f64, f64)> {
+        self.inner.snapshot().asks
+    }
+
+    fn __repr__(&self) -> String {
+        let level = |level: Option<(f64, f64)>| match level {
+            Some((price, size)) => format!("({:?}, {:?})", price, size),
+            None => "None".to_string(),
+        };
+        format!(
+            "OrderBook(symbol='{}', best_bid={}, best_ask={})",
+            self.inner.symbol(),
+            level(self.inner.best_bid()),
+            level(self.inner.best_ask())
+        )
+    }
+}
+
+/// Books recorded in an engine snapshot, one per symbol
+#[pyfunction]
+fn load_snapshot_books(path: &str) -> PyResult<Vec<PyOrderBook>> {
+    let snapshot = EngineSnapshot::load(Path::new(path))
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+    Ok(snapshot.books
+        .iter()
+        .map(|book| PyOrderBook { inner: OrderBook::from_snapshot(book) })
+        .collect())
+}
+
+/// Quotes replayed from a frame recording (`HFT_RECORD_FRAMES`), or with
+/// `depth` a depth recording (`HFT_RECORD_DEPTH`), as the backtester sees
+/// them
+#[pyclass(name = "Replay")]
+struct PyReplay {
+    quotes: Vec<Quote>,
+    rejected: usize,
+}
+
+#[pymethods]
+impl PyReplay {
+    #[new]
+    #[pyo3(signature = (path, venue = "BINANCE_FUTURES", depth = false))]
+    fn new(path: &str, venue: &str, depth: bool) -> PyResult<Self> {
+        let loaded = if depth {
+            backtest::load_depth_quotes(Path::new(path), venue)
+        } else {
+            backtest::load_quotes(Path::new(path), venue)
+        };
+        let loaded = loaded.map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self { quotes: loaded.quotes, rejected: loaded.rejected })
+    }
+
+    /// Frames that did not parse as quotes
+    #[getter]
+    fn rejected(&self) -> usize {
+        self.rejected
+    }
+
+    fn __len__(&self) -> usize {
+        self.quotes.len()
+    }
+
+    /// `(venue, symbol, bid, ask, bid_size, ask_size, timestamp)` tuples in
+    /// replay order
+    #[allow(clippy::type_complexity)]
+    fn quotes(&self) -> Vec<(String, String, f64, f64, f64, f64, u64)> {
+        self.quotes
+            .iter()
+            .map(|q| (q.venue.to_string(), q.symbol.to_string(), q.bid, q.ask, q.bid_size, q.ask_size, q.timestamp))
+            .collect()
+    }
+
+    /// Books as they stood at the end of the recording, by symbol
+    fn books(&self) -> Vec<PyOrderBook> {
+        let mut books = BTreeMap::new();
+        for quote in &self.quotes {
+            books.entry(quote.symbol.to_string())
+                .or_insert_with(|| OrderBook::new(quote.symbol))
+                .update(quote);
+        }
+        books.into_values().map(|inner| PyOrderBook { inner }).collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Replay(quotes={}, rejected={})", self.quotes.len(), self.rejected)
+    }
+}
+
+/// Market makers run over a replay with simulated fills; see the
+/// `backtest` command
+#[pyclass(name = "Backtest")]
+struct PyBacktest {
+    /// Market makers by name, with their parameters
+    market_makers: Vec<(String, Option<serde_json::Value>)>,
+    /// Queue position fills instead of top of book
+    queue: bool,
+    latency_ms: u64,
+    latency_jitter_ms: u64,
+    fees: Fees,
+    seed: u64,
+}
+
+#[pymethods]
+impl PyBacktest {
+    #[new]
+    #[pyo3(signature = (fill_model = "top_of_book", latency_ms = 0, latency_jitter_ms = 0, maker_bps = 0.0, taker_bps = 0.0, seed = 0))]
+    fn new(fill_model: &str, latency_ms: u64, latency_jitter_ms: u64, maker_bps: f64, taker_bps: f64, seed: u64) -> PyResult<Self> {
+        let queue = match fill_model {
+            "top_of_book" => false,
+            "queue" => true,
+            other => return Err(PyValueError::new_err(format!("Unknown fill model {:?}, expected top_of_book or queue", other))),
+        };
+        Ok(Self {
+            market_makers: Vec::new(),
+            queue,
+            latency_ms,
+            latency_jitter_ms,
+            fees: Fees { maker_bps, taker_bps },
+            seed,
+        })
+    }
+
+    /// Run a market maker named `name`, with `params` as a JSON object of
+    /// `MarketMakerParams` fields; missing fields keep their defaults
+    #[pyo3(signature = (name, params = None))]
+    fn add_market_maker(&mut self, name: String, params: Option<&str>) -> PyResult<()> {
+        let params = params
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(format!("Invalid parameters for {}: {}", name, e)))?;
+        self.market_makers.push((name, params));
+        Ok(())
+    }
+
+    /// Replay `replay`'s quotes to the market makers; a backtest can be run
+    /// again, from scratch, on the same or another replay
+    fn run(&self, replay: &PyReplay) -> PyBacktestReport {
+        let store = ParameterStore::new();
+        let fill_model: Box<dyn FillModel> = if self.queue {
+            Box::new(QueuePosition::new().with_latency(self.latency_ms).with_jitter(self.latency_jitter_ms).with_fees(self.fees))
+        } else {
+            Box::new(TopOfBook::new().with_latency(self.latency_ms).with_jitter(self.latency_jitter_ms).with_fees(self.fees))
+        };
+        let mut backtest = Backtest::new().with_fill_model(fill_model).with_seed(self.seed);
+        for (name, params) in &self.market_makers {
+            if let Some(params) = params {
+                store.set(name, params.clone());
+            }
+            backtest = backtest.with_strategy(Box::new(MarketMaker::new(name, store.clone())));
+        }
+        PyBacktestReport { inner: backtest.run(replay.quotes.iter().cloned()) }
+    }
+}
+
+#[pyclass(name = "BacktestReport")]
+struct PyBacktestReport {
+    inner: BacktestReport,
+}
+
+#[pymethods]
+impl PyBacktestReport {
+    #[getter]
+    fn quotes(&self) -> usize {
+        self.inner.quotes
+    }
+
+    #[getter]
+    fn orders(&self) -> usize {
+        self.inner.orders
+    }
+
+    #[getter]
+    fn fills(&self) -> usize {
+        self.inner.fills
+    }
+
+    #[getter]
+    fn notional(&self) -> f64 {
+        self.inner.notional
+    }
+
+    #[getter]
+    fn fees(&self) -> f64 {
+        self.inner.fees
+    }
+
+    /// Realized plus unrealized PnL, before fees
+    #[getter]
+    fn total_pnl(&self) -> f64 {
+        self.inner.total_pnl()
+    }
+
+    #[getter]
+    fn net_pnl(&self) -> f64 {
+        self.inner.net_pnl()
+    }
+
+    /// `(strategy, venue, symbol, quantity, realized_pnl, unrealized_pnl)`
+    /// per final position, marked at the last mid
+    #[allow(clippy::type_complexity)]
+    fn positions(&self) -> Vec<(String, String, String, f64, f64, f64)> {
+        self.inner.positions
+            .iter()
+            .map(|(key, position)| {
+                let mark = self.inner.marks.get(&(key.venue.clone(), key.symbol.clone())).copied().unwrap_or(0.0);
+                (key.strategy.clone(), key.venue.clone(), key.symbol.clone(), position.quantity, position.realized_pnl, position.unrealized_pnl(mark))
+            })
+            .collect()
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "BacktestReport(quotes={}, orders={}, fills={}, net_pnl={:?})",
+            self.inner.quotes, self.inner.orders, self.inner.fills, self.inner.net_pnl()
+        )
+    }
+}
+
+#[pymodule]
+fn hft_engine_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyOrderBook>()?;
+    m.add_class::<PyReplay>()?;
+    m.add_class::<PyBacktest>()?;
+    m.add_class::<PyBacktestReport>()?;
+    m.add_function(wrap_pyfunction!(load_snapshot_books, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::venues::frames::RecordedFrame;
+
+    /// A frame recording of `bookTicker` quotes at `(bid, ask)`
+    fn recording(name: &str, tops: &[(f64, f64)]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("hft_python_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("frames.jsonl");
+        let lines: Vec<_> = tops.iter().enumerate().map(|(t, (bid, ask))| {
+            let text = format!(r#"{{"s":"BTCUSDT","b":"{}","B":"1","a":"{}","A":"1","T":{}}}"#, bid, ask, t + 1);
+            serde_json::to_string(&RecordedFrame { received_at: t as u64 + 1, venue: "BINANCE_FUTURES".to_string(), text }).unwrap()
+        }).collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_replay_loads_quotes_and_books() {
+        let path = recording("replay", &[(100.0, 100.5), (101.0, 101.5)]);
+        let replay = PyReplay::new(path.to_str().unwrap(), "BINANCE_FUTURES", false).unwrap();
+        assert_eq!((replay.__len__(), replay.rejected()), (2, 0));
+        assert_eq!(replay.quotes()[1], ("BINANCE_FUTURES".to_string(), "BTCUSDT".to_string(), 101.0, 101.5, 1.0, 1.0, 2));
+        let books = replay.books();
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].mid(), Some(101.25));
+
+        assert_eq!(PyReplay::new(path.to_str().unwrap(), "OTHER", false).unwrap().__len__(), 0);
+        assert!(PyReplay::new("/nonexistent/frames.jsonl", "BINANCE_FUTURES", false).is_err());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_backtest_runs_market_makers_over_a_replay() {
+        // The market trades through both sides of the maker's quotes
+        let path = recording("backtest", &[(100.0, 100.1), (98.0, 98.1), (102.0, 102.1), (100.0, 100.1)]);
+        let replay = PyReplay::new(path.to_str().unwrap(), "BINANCE_FUTURES", false).unwrap();
+
+        let mut backtest = PyBacktest::new("top_of_book", 0, 0, 1.0, 2.0, 0).unwrap();
+        backtest.add_market_maker("mm".to_string(), Some(r#"{"base_spread_bps": 20.0}"#)).unwrap();
+        let report = backtest.run(&replay);
+        assert_eq!(report.quotes(), 4);
+        assert!(report.orders() > 0 && report.fills() > 0, "{}", report.__str__());
+        assert!(report.fees() > 0.0);
+        assert_eq!(report.positions().len(), 1);
+        assert_eq!(report.positions()[0].0, "mm");
+
+        // Runs start from scratch, so they repeat exactly
+        let again = backtest.run(&replay);
+        assert_eq!((again.fills(), again.net_pnl()), (report.fills(), report.net_pnl()));
+
+        assert!(PyBacktest::new("vwap", 0, 0, 0.0, 0.0, 0).is_err());
+        assert!(backtest.add_market_maker("mm".to_string(), Some("{")).is_err());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use chrono::{NaiveDate, Utc};
+use prometheus::core::Collector;
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::{ACKED_QUANTITY, FILLED_QUANTITY, ORDER_ACKS, ORDER_CANCELS, ORDER_FILLS, ORDER_REJECTS};
+use crate::risk::PositionKey;
+use crate::types::{Fill, OrderSide};
+
+/// What a strategy traded on a venue and symbol over a day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Volume {
+    pub fills: u64,
+    pub bought: f64,
+    pub sold: f64,
+    /// Sum of quantity times price, in the symbol's quote currency
+    pub notional: f64,
+    /// Commissions venues reported on the fills, in the symbol's quote
+    /// currency; `None` when no fill carried one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commission: Option<f64>,
+}
+
+/// Orders acknowledged, rejected, cancelled and filled for one venue and
+/// strategy, as counted by the order metrics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrderFlow {
+    pub acks: f64,
+    pub rejects: f64,
+    pub cancels: f64,
+    pub fills: f64,
+    pub acked_quantity: f64,
+    pub filled_quantity: f64,
+}
+
+impl OrderFlow {
+    fn since(&self, earlier: &OrderFlow) -> OrderFlow {
+        OrderFlow {
+            acks: self.acks - earlier.acks,
+            rejects: self.rejects - earlier.rejects,
+            cancels: self.cancels - earlier.cancels,
+            fills: self.fills - earlier.fills,
+            acked_quantity: self.acked_quantity - earlier.acked_quantity,
+            filled_quantity: self.filled_quantity - earlier.filled_quantity,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == OrderFlow::default()
+    }
+}
+
+/// Order flow by venue and strategy
+pub type OrderFlows = BTreeMap<(String, String), OrderFlow>;
+
+/// The order counters as they stand, summed over reject and cancel reasons
+fn order_flows() -> OrderFlows {
+    let mut flows = OrderFlows::new();
+    let mut add = |counter: &dyn Collector, field: fn(&mut OrderFlow) -> &mut f64| {
+        for family in counter.collect() {
+            for metric in family.get_metric() {
+                let label = |name: &str| metric.get_label().iter()
+                    .find(|l| l.get_name() == name)
+                    .map(|l| l.get_value().to_string())
+                    .unwrap_or_default();
+                *field(flows.entry((label("venue"), label("strategy"))).or_default()) += metric.get_counter().get_value();
+            }
+        }
+    };
+    add(&*ORDER_ACKS, |f| &mut f.acks);
+    add(&*ORDER_REJECTS, |f| &mut f.rejects);
+    add(&*ORDER_CANCELS, |f| &mut f.cancels);
+    add(&*ORDER_FILLS, |f| &mut f.fills);
+    add(&*ACKED_QUANTITY, |f| &mut f.acked_quantity);
+    add(&*FILLED_QUANTITY, |f| &mut f.filled_quantity);
+    flows
+}
+
+struct Day {
+    date: NaiveDate,
+    volumes: HashMap<PositionKey, Volume>,
+    /// Order counters when the day started, and when it ended
+    opening: OrderFlows,
+    closing: Option<OrderFlows>,
+}
+
+impl Day {
+    fn open(date: NaiveDate, opening: OrderFlows) -> Self {
+        Self { date, volumes: HashMap::new(), opening, closing: None }
+    }
+}
+
+/// Fills and order flow of one day
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DayActivity {
+    pub volumes: Vec<(PositionKey, Volume)>,
+    pub order_flow: Vec<((String, String), OrderFlow)>,
+}
+
+/// Volume traded per position key and order flow per venue and strategy,
+/// for today and yesterday.
+///
+/// Days follow UTC. The order counters are read when a day starts, which is
+/// noticed on the first fill or report after midnight.
+pub struct ActivityTracker {
+    days: Mutex<(Day, Option<Day>)>,
+}
+
+impl Default for ActivityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ActivityTracker {
+    pub fn new() -> Self {
+        Self { days: Mutex::new((Day::open(Utc::now().date_naive(), order_flows()), None)) }
+    }
+
+    /// Record `fill`, with the commission charged on it in the quote
+    /// currency
+    pub fn record_fill(&self, fill: &Fill, commission: Option<f64>) {
+        self.record_fill_on(Utc::now().date_naive(), fill, commission);
+    }
+
+    fn record_fill_on(&self, today: NaiveDate, fill: &Fill, commission: Option<f64>) {
+        let mut days = self.days.lock().unwrap();
+        Self::roll(&mut days, today);
+        let key = PositionKey {
+            strategy: fill.strategy.clone(),
+            venue: fill.venue.to_string(),
+            symbol: fill.symbol.to_string(),
+        };
+        let volume = days.0.volumes.entry(key).or_default();
+        volume.fills += 1;
+        match fill.side {
+            OrderSide::Buy => volume.bought += fill.quantity,
+            OrderSide::Sell => volume.sold += fill.quantity,
+        }
+        volume.notional += fill.quantity * fill.price;
+        if let Some(commission) = commission {
+            *volume.commission.get_or_insert(0.0) += commission;
+        }
+    }
+
+    fn roll(days: &mut (Day, Option<Day>), today: NaiveDate) {
+        if days.0.date == today {
+            return;
+        }
+        let flows = order_flows();
+        let mut ended = std::mem::replace(&mut days.0, Day::open(today, flows.clone()));
+        ended.closing = Some(flows);
+        days.1 = Some(ended);
+    }
+
+    /// What happened on `date`; empty unless it is today or yesterday
+    pub fn day(&self, date: NaiveDate) -> DayActivity {
+        self.day_on(Utc::now().date_naive(), date)
+    }
+
+    fn day_on(&self, today: NaiveDate, date: NaiveDate) -> DayActivity {
+        let mut days = self.days.lock().unwrap();
+        Self::roll(&mut days, today);
+        let (current, previous) = &*days;
+        let Some(day) = [Some(current), previous.as_ref()].into_iter().flatten().find(|d| d.date == date) else {
+            return DayActivity::default();
+        };
+
+        let closing = day.closing.clone().unwrap_or_else(order_flows);
+        let mut volumes: Vec<_> = day.volumes.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        volumes.sort_by(|a, b| a.0.cmp(&b.0));
+        let order_flow = closing.into_iter()
+            .map(|(key, flow)| {
+                let flow = flow.since(&day.opening.get(&key).cloned().unwrap_or_default());
+                (key, flow)
+            })
+            .filter(|(_, flow)| !flow.is_empty())
+            .collect();
+        DayActivity { volumes, order_flow }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_roll_with_order_flow() {
+        let date = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        let fill = |side, quantity| Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "ACTIVITY".into(),
+            strategy: "mm".to_string(),
+            side,
+            quantity,
+            price: 50000.0,
+            timestamp: 1,
+            commission: None,
+        };
+        let acks = ORDER_ACKS.with_label_values(&["ACTIVITY", "mm"]);
+        acks.inc();
+
+        let tracker = ActivityTracker::new();
+        tracker.record_fill_on(date(15), &fill(OrderSide::Buy, 2.0), None);
+        acks.inc();
+        tracker.record_fill_on(date(16), &fill(OrderSide::Sell, 1.0), Some(2.5));
+        acks.inc_by(3.0);
+
+        let yesterday = tracker.day_on(date(16), date(15));
+        assert_eq!(yesterday.volumes.len(), 1);
+        assert_eq!(yesterday.volumes[0].1, Volume { fills: 1, bought: 2.0, sold: 0.0, notional: 100000.0, commission: None });
+        let flow = |day: &DayActivity| day.order_flow.iter()
+            .find(|(key, _)| key.0 == "ACTIVITY")
+            .map(|(_, flow)| flow.acks);
+        // Counted from when the tracker started
+        assert_eq!(flow(&yesterday), Some(1.0));
+
+        let today = tracker.day_on(date(16), date(16));
+        assert_eq!(today.volumes[0].1.sold, 1.0);
+        assert_eq!(today.volumes[0].1.commission, Some(2.5));
+        assert_eq!(flow(&today), Some(3.0));
+
+        assert_eq!(tracker.day_on(date(16), date(14)), DayActivity::default());
+        assert!(tracker.day_on(date(17), date(15)).volumes.is_empty());
+    }
+}
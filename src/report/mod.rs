@@ -0,0 +1,465 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use warp::Filter;
+
+use crate::error::HftError;
+use crate::fees::Liquidity;
+use crate::risk::{PositionKey, RiskManager};
+
+pub mod activity;
+pub mod reconcile;
+pub use activity::{ActivityTracker, DayActivity, OrderFlow, Volume};
+pub use reconcile::{reconcile_trades, BreakKind, TradeBreak, TradeReconciliation};
+
+const DEFAULT_REPORT_DIR: &str = "reports";
+
+/// Flat fee per venue in basis points of traded notional
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeeRates {
+    venues: HashMap<String, f64>,
+    default: f64,
+}
+
+impl FeeRates {
+    pub fn with_venue(mut self, venue: &str, bps: f64) -> Self {
+        self.venues.insert(venue.to_uppercase(), bps);
+        self
+    }
+
+    /// Comma separated `VENUE=BPS` entries with `*` for the default, e.g.
+    /// `BINANCE=7.5,*=10`
+    fn parse(spec: &str) -> Self {
+        let mut rates = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once('=').and_then(|(venue, bps)| Some((venue.trim(), bps.trim().parse::<f64>().ok()?))) {
+                Some(("*", bps)) => rates.default = bps,
+                Some((venue, bps)) => rates = rates.with_venue(venue, bps),
+                None => warn!(entry = entry, "Ignoring malformed fee rate"),
+            }
+        }
+        rates
+    }
+
+    pub fn fee(&self, venue: &str, notional: f64) -> f64 {
+        notional * self.venues.get(&venue.to_uppercase()).copied().unwrap_or(self.default) / 10_000.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportConfig {
+    /// Where reports requested through the admin API are written
+    pub dir: PathBuf,
+    /// Receives each written report as JSON
+    pub webhook: Option<String>,
+    pub fees: FeeRates,
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self { dir: DEFAULT_REPORT_DIR.into(), webhook: None, fees: FeeRates::default() }
+    }
+}
+
+impl ReportConfig {
+    /// Read `HFT_REPORT_DIR`, `HFT_REPORT_WEBHOOK_URL` and
+    /// `HFT_REPORT_FEE_BPS` (see [`FeeRates`])
+    pub fn from_env() -> Self {
+        Self {
+            dir: std::env::var("HFT_REPORT_DIR").map(PathBuf::from).unwrap_or_else(|_| DEFAULT_REPORT_DIR.into()),
+            webhook: std::env::var("HFT_REPORT_WEBHOOK_URL").ok().filter(|url| !url.is_empty()),
+            fees: FeeRates::parse(&std::env::var("HFT_REPORT_FEE_BPS").unwrap_or_default()),
+        }
+    }
+}
+
+/// Trading of one strategy on one venue and symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityRow {
+    #[serde(flatten)]
+    pub key: PositionKey,
+    #[serde(flatten)]
+    pub volume: Volume,
+    pub fees: f64,
+    /// Open quantity, positive when long
+    pub position: f64,
+    /// Realized and unrealized PnL of the position since it was opened,
+    /// including financing
+    pub pnl: f64,
+    /// Funding and borrow PnL of the position since it was opened
+    #[serde(default)]
+    pub financing: f64,
+}
+
+/// Execution quality of one strategy on one venue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionRow {
+    pub venue: String,
+    pub strategy: String,
+    #[serde(flatten)]
+    pub flow: OrderFlow,
+    /// Filled over acknowledged quantity
+    pub fill_ratio: Option<f64>,
+    /// Rejected over acknowledged and rejected orders
+    pub reject_rate: Option<f64>,
+}
+
+impl ExecutionRow {
+    fn new(venue: String, strategy: String, flow: OrderFlow) -> Self {
+        let fill_ratio = (flow.acked_quantity > 0.0).then(|| flow.filled_quantity / flow.acked_quantity);
+        let sent = flow.acks + flow.rejects;
+        let reject_rate = (sent > 0.0).then(|| flow.rejects / sent);
+        Self { venue, strategy, flow, fill_ratio, reject_rate }
+    }
+}
+
+/// PnL, fees, volume and execution quality for one day. Amounts are in the
+/// reporting currency, volumes and notionals in each symbol's quote currency.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyReport {
+    pub date: String,
+    pub generated_at: String,
+    /// Daily PnL before fees
+    pub pnl: f64,
+    pub fees: f64,
+    pub net_pnl: f64,
+    /// Funding and borrow PnL of the positions since they were opened,
+    /// already part of their PnL
+    #[serde(default)]
+    pub financing: f64,
+    /// Daily PnL per strategy before fees
+    pub strategies: BTreeMap<String, f64>,
+    pub activity: Vec<ActivityRow>,
+    pub execution: Vec<ExecutionRow>,
+}
+
+/// A CSV field, quoted when it needs to be
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_ratio(ratio: Option<f64>) -> String {
+    ratio.map(|r| r.to_string()).unwrap_or_default()
+}
+
+impl DailyReport {
+    /// The activity rows as CSV
+    pub fn activity_csv(&self) -> String {
+        let mut csv = String::from("strategy,venue,symbol,fills,bought,sold,notional,fees,position,pnl,financing\n");
+        for row in &self.activity {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{}\n",
+                csv_field(&row.key.strategy), csv_field(&row.key.venue), csv_field(&row.key.symbol),
+                row.volume.fills, row.volume.bought, row.volume.sold, row.volume.notional, row.fees, row.position, row.pnl,
+                row.financing,
+            ));
+        }
+        csv
+    }
+
+    /// The execution rows as CSV
+    pub fn execution_csv(&self) -> String {
+        let mut csv = String::from("venue,strategy,acks,rejects,cancels,fills,acked_quantity,filled_quantity,fill_ratio,reject_rate\n");
+        for row in &self.execution {
+            let flow = &row.flow;
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{}\n",
+                csv_field(&row.venue), csv_field(&row.strategy), flow.acks, flow.rejects, flow.cancels, flow.fills,
+                flow.acked_quantity, flow.filled_quantity, csv_ratio(row.fill_ratio), csv_ratio(row.reject_rate),
+            ));
+        }
+        csv
+    }
+}
+
+impl fmt::Display for DailyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Report for {}: PnL {:.2} (financing {:.2}), fees {:.2}, net {:.2}",
+            self.date, self.pnl, self.financing, self.fees, self.net_pnl
+        )?;
+        for row in &self.activity {
+            write!(
+                f,
+                "\n  {} {} {}: {} fills, bought {}, sold {}, notional {:.2}, fees {:.2}, PnL {:.2}",
+                row.key.strategy, row.key.venue, row.key.symbol, row.volume.fills, row.volume.bought,
+                row.volume.sold, row.volume.notional, row.fees, row.pnl,
+            )?;
+        }
+        for row in &self.execution {
+            write!(f, "\n  {} {}: {} acks, {} rejects, {} cancels", row.strategy, row.venue, row.flow.acks, row.flow.rejects, row.flow.cancels)?;
+            if let Some(ratio) = row.fill_ratio {
+                write!(f, ", {:.1}% filled", ratio * 100.0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builds [`DailyReport`]s from the risk manager's positions and activity,
+/// writes them to disk and pushes them to the report webhook
+#[derive(Clone)]
+pub struct Reporter {
+    config: Arc<ReportConfig>,
+    risk: Arc<RiskManager>,
+    client: reqwest::Client,
+}
+
+impl Reporter {
+    pub fn new(config: ReportConfig, risk: Arc<RiskManager>) -> Self {
+        Self { config: Arc::new(config), risk, client: reqwest::Client::new() }
+    }
+
+    pub fn config(&self) -> &ReportConfig {
+        &self.config
+    }
+
+    /// The report for the day of `at`, with PnL as it stands now
+    pub async fn build(&self, at: DateTime<Utc>) -> DailyReport {
+        let (mut pnl, mut strategies) = self.risk.daily_pnl().await;
+        let day = self.risk.activity().day(at.date_naive());
+
+        let mut rows: BTreeMap<PositionKey, ActivityRow> = BTreeMap::new();
+        for (key, volume) in day.volumes {
+            // Fees venues reported are already taken out of PnL; estimate
+            // them for venues that report none
+            let fees = match (volume.commission, self.risk.fees().configured(&key.venue)) {
+                (Some(commission), _) => commission,
+                (None, Some(fees)) => fees.fee(volume.notional, Liquidity::Taker),
+                (None, None) => self.config.fees.fee(&key.venue, volume.notional),
+            };
+            let fees = self.risk.in_reporting_currency(&key.symbol, fees).await;
+            if volume.commission.is_some() {
+                pnl += fees;
+                *strategies.entry(key.strategy.clone()).or_insert(0.0) += fees;
+            }
+            rows.insert(key.clone(), ActivityRow { key, volume, fees, position: 0.0, pnl: 0.0, financing: 0.0 });
+        }
+        for (key, position, pnl) in self.risk.position_pnl().await {
+            // Untraded flat positions add nothing
+            if position.is_flat() && !rows.contains_key(&key) {
+                continue;
+            }
+            let financing = self.risk.in_reporting_currency(&key.symbol, position.financing).await;
+            let row = rows.entry(key.clone()).or_insert_with(|| ActivityRow {
+                key,
+                volume: Volume::default(),
+                fees: 0.0,
+                position: 0.0,
+                pnl: 0.0,
+                financing: 0.0,
+            });
+            row.position = position.quantity;
+            row.pnl = pnl;
+            row.financing = financing;
+        }
+        let fees = rows.values().map(|row| row.fees).sum();
+        let financing = rows.values().map(|row| row.financing).sum();
+
+        DailyReport {
+            date: at.date_naive().to_string(),
+            generated_at: at.to_rfc3339(),
+            pnl,
+            fees,
+            net_pnl: pnl - fees,
+            financing,
+            strategies: strategies.into_iter().collect(),
+            activity: rows.into_values().collect(),
+            execution: day.order_flow.into_iter()
+                .map(|((venue, strategy), flow)| ExecutionRow::new(venue, strategy, flow))
+                .collect(),
+        }
+    }
+
+    /// Write `report` as `report-DATE.json`, `activity-DATE.csv` and
+    /// `execution-DATE.csv` into `dir`
+    pub fn write(&self, report: &DailyReport, dir: &Path) -> Result<Vec<PathBuf>, HftError> {
+        std::fs::create_dir_all(dir)?;
+        let json = serde_json::to_vec_pretty(report).map_err(|e| HftError::Serialization(e.to_string()))?;
+        let files = [
+            (format!("report-{}.json", report.date), json),
+            (format!("activity-{}.csv", report.date), report.activity_csv().into_bytes()),
+            (format!("execution-{}.csv", report.date), report.execution_csv().into_bytes()),
+        ];
+        let mut paths = Vec::new();
+        for (name, contents) in files {
+            let path = dir.join(name);
+            std::fs::write(&path, contents)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// POST `report` as JSON to the webhook, if one is configured
+    pub async fn publish(&self, report: &DailyReport) -> Result<(), HftError> {
+        let Some(url) = &self.config.webhook else {
+            return Ok(());
+        };
+        self.client.post(url)
+            .json(report)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| HftError::Io(format!("Failed to push report: {}", e)))?;
+        Ok(())
+    }
+
+    /// Build the report for the day of `at`, write it into `dir` and push
+    /// it, returning a summary
+    pub async fn generate(&self, dir: &Path, at: DateTime<Utc>) -> Result<String, HftError> {
+        let report = self.build(at).await;
+        let paths = self.write(&report, dir)?;
+        info!(date = %report.date, pnl = report.pnl, fees = report.fees, "Wrote daily report");
+        self.publish(&report).await?;
+        Ok(format!("wrote {}", paths[0].display()))
+    }
+}
+
+/// `GET /admin/report` for today's report, `POST /admin/report` to also
+/// write and push it
+pub fn routes(
+    reporter: Reporter,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let view = {
+        let reporter = reporter.clone();
+        warp::path!("admin" / "report")
+            .and(warp::get())
+            .then(move || {
+                let reporter = reporter.clone();
+                async move { warp::reply::json(&reporter.build(Utc::now()).await) }
+            })
+    };
+    let generate = warp::path!("admin" / "report")
+        .and(warp::post())
+        .then(move || {
+            let reporter = reporter.clone();
+            async move {
+                let (reply, status) = match reporter.generate(&reporter.config.dir, Utc::now()).await {
+                    Ok(detail) => (serde_json::json!({ "detail": detail }), warp::http::StatusCode::OK),
+                    Err(e) => (serde_json::json!({ "error": e.to_string() }), warp::http::StatusCode::INTERNAL_SERVER_ERROR),
+                };
+                warp::reply::with_status(warp::reply::json(&reply), status)
+            }
+        });
+    view.or(generate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::{ACKED_QUANTITY, ORDER_ACKS, ORDER_REJECTS};
+    use crate::risk::LossLimits;
+    use crate::types::{Fill, OrderSide, Quote};
+
+    #[test]
+    fn test_fee_rates() {
+        let rates = FeeRates::parse("binance=7.5, *=10, bad=x");
+        assert_eq!(rates.fee("BINANCE", 10_000.0), 7.5);
+        assert_eq!(rates.fee("COINBASE", 10_000.0), 10.0);
+        assert_eq!(rates.venues.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_report_written_and_pushed() {
+        let dir = std::env::temp_dir().join(format!("hft_report_{}", std::process::id()));
+        let risk = Arc::new(RiskManager::new(LossLimits::default()));
+        ORDER_ACKS.with_label_values(&["REPORT", "mm"]).inc_by(4.0);
+        ACKED_QUANTITY.with_label_values(&["REPORT", "mm"]).inc_by(4.0);
+        ORDER_REJECTS.with_label_values(&["REPORT", "mm", "risk"]).inc();
+        for (side, price) in [(OrderSide::Buy, 100.0), (OrderSide::Sell, 110.0), (OrderSide::Buy, 105.0)] {
+            risk.on_fill(&Fill {
+                order_id: "1".to_string(),
+                symbol: "SOL,USDT".into(),
+                venue: "REPORT".into(),
+                strategy: "mm".to_string(),
+                side,
+                quantity: 1.0,
+                price,
+                timestamp: 1,
+                commission: None,
+            }).await;
+        }
+        risk.on_quote(&Quote {
+            symbol: "SOL,USDT".into(),
+            bid: 107.0,
+            ask: 107.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "REPORT".into(),
+            timestamp: 1,
+        }).await;
+
+        let (tx, mut pushed) = tokio::sync::mpsc::unbounded_channel();
+        let webhook = warp::post().and(warp::body::json()).map(move |report: DailyReport| {
+            tx.send(report).unwrap();
+            warp::reply()
+        });
+        let (addr, server) = warp::serve(webhook).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let config = ReportConfig {
+            dir: dir.clone(),
+            webhook: Some(format!("http://{}/reports", addr)),
+            fees: FeeRates::default().with_venue("REPORT", 10.0),
+        };
+        let reporter = Reporter::new(config, risk);
+        let now = Utc::now();
+        reporter.generate(&dir, now).await.unwrap();
+
+        let report = pushed.recv().await.unwrap();
+        assert_eq!(report.activity.len(), 1);
+        let row = &report.activity[0];
+        assert_eq!((row.volume.fills, row.volume.bought, row.volume.sold), (3, 2.0, 1.0));
+        assert!((row.fees - 0.315).abs() < 1e-9);
+        // 10 realized, 2 unrealized on the long opened at 105
+        assert!((row.pnl - 12.0).abs() < 1e-9);
+        assert!((report.net_pnl - (report.pnl - report.fees)).abs() < 1e-9);
+        let execution = report.execution.iter().find(|row| row.venue == "REPORT").unwrap();
+        assert_eq!(execution.reject_rate, Some(0.2));
+        assert_eq!(execution.fill_ratio, Some(0.75));
+
+        let date = now.date_naive();
+        let written: DailyReport = serde_json::from_slice(&std::fs::read(dir.join(format!("report-{}.json", date))).unwrap()).unwrap();
+        assert_eq!(written, report);
+        let csv = std::fs::read_to_string(dir.join(format!("activity-{}.csv", date))).unwrap();
+        assert!(csv.lines().nth(1).unwrap().starts_with("mm,REPORT,\"SOL,USDT\",3,2,1,315,"));
+        assert!(dir.join(format!("execution-{}.csv", date)).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reported_commissions_replace_estimated_fees() {
+        let risk = Arc::new(RiskManager::new(LossLimits::default()));
+        for (side, price, amount) in [(OrderSide::Buy, 100.0, 0.04), (OrderSide::Sell, 110.0, 0.05)] {
+            risk.on_fill(&Fill {
+                order_id: "1".to_string(),
+                symbol: "SOLUSDT".into(),
+                venue: "COMMISSION".into(),
+                strategy: "mm".to_string(),
+                side,
+                quantity: 1.0,
+                price,
+                timestamp: 1,
+                commission: Some(crate::types::Commission { amount, asset: "USDT".to_string() }),
+            }).await;
+        }
+        let config = ReportConfig { fees: FeeRates::default().with_venue("COMMISSION", 10.0), ..Default::default() };
+        let report = Reporter::new(config, risk).build(Utc::now()).await;
+
+        let row = report.activity.iter().find(|row| row.key.venue == "COMMISSION").unwrap();
+        assert!((row.fees - 0.09).abs() < 1e-9);
+        assert_eq!(row.volume.commission, Some(row.fees));
+        // Position PnL is net of commissions, the report's PnL before fees
+        assert!((row.pnl - 9.91).abs() < 1e-9);
+        assert!((report.net_pnl - (report.pnl - report.fees)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::types::{Fill, OrderSide};
+use crate::venues::VenueRegistry;
+
+/// Relative difference in quantity or average price tolerated between the
+/// journal and the venue
+const TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakKind {
+    /// Executed on the venue but not in the journal
+    Missing,
+    /// In the journal but not executed on the venue
+    Extra,
+    /// In both with a different side, quantity, average price or
+    /// commission
+    Mismatched,
+}
+
+impl fmt::Display for BreakKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreakKind::Missing => write!(f, "missing"),
+            BreakKind::Extra => write!(f, "extra"),
+            BreakKind::Mismatched => write!(f, "mismatched"),
+        }
+    }
+}
+
+/// The fills of one order, summed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Executed {
+    pub side: OrderSide,
+    pub quantity: f64,
+    pub avg_price: f64,
+    pub fills: usize,
+    /// Summed over the fills that carry one
+    pub commission: Option<f64>,
+}
+
+impl Executed {
+    fn add(&mut self, fill: &Fill) {
+        let quantity = self.quantity + fill.quantity;
+        if quantity > 0.0 {
+            self.avg_price = (self.avg_price * self.quantity + fill.price * fill.quantity) / quantity;
+        }
+        self.quantity = quantity;
+        self.fills += 1;
+        if let Some(commission) = &fill.commission {
+            *self.commission.get_or_insert(0.0) += commission.amount;
+        }
+    }
+
+    fn agrees(&self, other: &Executed) -> bool {
+        let close = |a: f64, b: f64| (a - b).abs() <= TOLERANCE * a.abs().max(b.abs()).max(1.0);
+        // Commissions are only compared when both sides know them
+        let commissions_agree = match (self.commission, other.commission) {
+            (Some(ours), Some(theirs)) => close(ours, theirs),
+            _ => true,
+        };
+        self.side == other.side && close(self.quantity, other.quantity) && close(self.avg_price, other.avg_price) && commissions_agree
+    }
+}
+
+impl fmt::Display for Executed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {} @ {} in {} fills", self.side, self.quantity, self.avg_price, self.fills)?;
+        match self.commission {
+            Some(commission) => write!(f, " with {} commission", commission),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An order whose executions differ between the journal and the venue
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TradeBreak {
+    pub venue: String,
+    pub symbol: String,
+    pub order_id: String,
+    pub kind: BreakKind,
+    pub journal: Option<Executed>,
+    pub reported: Option<Executed>,
+}
+
+impl fmt::Display for TradeBreak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |executed: &Option<Executed>| executed.map_or("nothing".to_string(), |e| e.to_string());
+        write!(
+            f,
+            "{} fills for order {} in {} on {}: journal has {}, venue reports {}",
+            self.kind, self.order_id, self.symbol, self.venue, show(&self.journal), show(&self.reported),
+        )
+    }
+}
+
+/// Outcome of reconciling the fills journal against venue trade history
+/// over a window
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TradeReconciliation {
+    /// Window start and end, in milliseconds since the Unix epoch
+    pub from: u64,
+    pub until: u64,
+    /// Orders whose executions agree
+    pub matched: usize,
+    pub breaks: Vec<TradeBreak>,
+    /// Trade history that could not be fetched, and why
+    pub failures: Vec<String>,
+}
+
+impl TradeReconciliation {
+    pub fn count(&self, kind: BreakKind) -> usize {
+        self.breaks.iter().filter(|b| b.kind == kind).count()
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} orders matched, {} missing, {} extra, {} mismatched",
+            self.matched,
+            self.count(BreakKind::Missing),
+            self.count(BreakKind::Extra),
+            self.count(BreakKind::Mismatched),
+        );
+        if !self.failures.is_empty() {
+            summary.push_str(&format!(", {} fetches failed", self.failures.len()));
+        }
+        summary
+    }
+}
+
+/// Fills per symbol and order
+fn by_order<'a>(fills: impl Iterator<Item = &'a Fill>) -> BTreeMap<(String, String), Executed> {
+    let mut orders: BTreeMap<(String, String), Executed> = BTreeMap::new();
+    for fill in fills {
+        orders.entry((fill.symbol.to_string(), fill.order_id.clone()))
+            .or_insert(Executed { side: fill.side, quantity: 0.0, avg_price: 0.0, fills: 0, commission: None })
+            .add(fill);
+    }
+    orders
+}
+
+/// Compare the journal's fills on `venue` with the fills the venue reports,
+/// order by order
+fn diff(venue: &str, journal: &[&Fill], reported: &[Fill]) -> (usize, Vec<TradeBreak>) {
+    let journal = by_order(journal.iter().copied());
+    let reported = by_order(reported.iter());
+    let keys: BTreeSet<_> = journal.keys().chain(reported.keys()).collect();
+
+    let mut matched = 0;
+    let mut breaks = Vec::new();
+    for key in keys {
+        let (ours, theirs) = (journal.get(key).copied(), reported.get(key).copied());
+        let kind = match (&ours, &theirs) {
+            (Some(ours), Some(theirs)) if ours.agrees(theirs) => {
+                matched += 1;
+                continue;
+            }
+            (Some(_), Some(_)) => BreakKind::Mismatched,
+            (None, _) => BreakKind::Missing,
+            (_, None) => BreakKind::Extra,
+        };
+        breaks.push(TradeBreak {
+            venue: venue.to_string(),
+            symbol: key.0.clone(),
+            order_id: key.1.clone(),
+            kind,
+            journal: ours,
+            reported: theirs,
+        });
+    }
+    (matched, breaks)
+}
+
+/// Reconcile `journal` against each venue's trade history between `from`
+/// and `until`. Trade history is fetched for every symbol the journal has
+/// fills in on the venue, and for the `held` (venue, symbol) pairs, since
+/// venues only report trades symbol by symbol.
+pub async fn reconcile_trades(
+    venues: &VenueRegistry,
+    journal: &[Fill],
+    held: &[(String, String)],
+    from: u64,
+    until: u64,
+) -> TradeReconciliation {
+    let mut reconciliation = TradeReconciliation { from, until, ..Default::default() };
+    for venue in venues.all() {
+        let name = venue.name().await;
+        let local: Vec<&Fill> = journal.iter()
+            .filter(|fill| *fill.venue == *name && (from..=until).contains(&fill.timestamp))
+            .collect();
+        let symbols: BTreeSet<String> = local.iter().map(|fill| fill.symbol.to_string())
+            .chain(held.iter().filter(|(venue, _)| *venue == name).map(|(_, symbol)| symbol.clone()))
+            .collect();
+
+        for symbol in symbols {
+            let reported = match venue.trade_history(&symbol, from, until).await {
+                Ok(reported) => reported,
+                Err(e) => {
+                    warn!(venue = %name, symbol = %symbol, error = %e, "Failed to fetch trade history");
+                    reconciliation.failures.push(format!("{} {}: {}", name, symbol, e));
+                    continue;
+                }
+            };
+            let local: Vec<&Fill> = local.iter().copied().filter(|fill| *fill.symbol == *symbol).collect();
+            let (matched, breaks) = diff(&name, &local, &reported);
+            reconciliation.matched += matched;
+            reconciliation.breaks.extend(breaks);
+        }
+    }
+    reconciliation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::mocks::mock_venue::{MockVenue, MockVenueConfig};
+
+    fn fill(order_id: &str, symbol: &str, quantity: f64, price: f64, timestamp: u64) -> Fill {
+        Fill {
+            order_id: order_id.to_string(),
+            symbol: symbol.into(),
+            venue: "RECON".into(),
+            strategy: "mm".to_string(),
+            side: OrderSide::Buy,
+            quantity,
+            price,
+            timestamp,
+            commission: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_extra_and_mismatched_fills() {
+        let venue = Arc::new(MockVenue::new("RECON", MockVenueConfig::default()));
+        venue.set_trades(vec![
+            // Journalled as one fill, reported as two
+            fill("1", "BTCUSDT", 0.4, 100.0, 1_000),
+            fill("1", "BTCUSDT", 0.6, 100.0, 1_001),
+            fill("2", "BTCUSDT", 1.0, 101.0, 2_000),
+            fill("3", "BTCUSDT", 1.0, 102.0, 3_000),
+            fill("5", "ETHUSDT", 2.0, 10.0, 4_000),
+        ]).await;
+        let venues = VenueRegistry::from_venues(vec![venue]).await;
+        let journal = vec![
+            fill("1", "BTCUSDT", 1.0, 100.0, 1_000),
+            fill("3", "BTCUSDT", 1.0, 102.5, 3_000),
+            fill("4", "BTCUSDT", 1.0, 103.0, 3_500),
+            // Outside the window
+            fill("9", "BTCUSDT", 1.0, 100.0, 90_000),
+        ];
+
+        let report = reconcile_trades(&venues, &journal, &[("RECON".to_string(), "ETHUSDT".to_string())], 0, 10_000).await;
+        assert_eq!(report.matched, 1);
+        let kinds: Vec<_> = report.breaks.iter().map(|b| (b.order_id.as_str(), b.kind)).collect();
+        assert_eq!(kinds, vec![
+            ("2", BreakKind::Missing),
+            ("3", BreakKind::Mismatched),
+            ("4", BreakKind::Extra),
+            ("5", BreakKind::Missing),
+        ]);
+        assert_eq!(report.summary(), "1 orders matched, 2 missing, 1 extra, 1 mismatched");
+        assert!(report.breaks[1].to_string().contains("journal has Buy 1 @ 102.5"));
+    }
+
+    #[test]
+    fn test_commissions_compared_when_both_sides_report_them() {
+        let charged = |order_id: &str, amount: f64| Fill {
+            commission: Some(crate::types::Commission { amount, asset: "USDT".to_string() }),
+            ..fill(order_id, "BTCUSDT", 1.0, 100.0, 1_000)
+        };
+        let journal = [charged("1", 0.05), fill("2", "BTCUSDT", 1.0, 100.0, 1_000), charged("3", 0.05)];
+        let reported = [charged("1", 0.05), charged("2", 0.05), charged("3", 0.06)];
+        let (matched, breaks) = diff("RECON", &journal.iter().collect::<Vec<_>>(), &reported);
+        assert_eq!(matched, 2);
+        assert_eq!((breaks[0].order_id.as_str(), breaks[0].kind), ("3", BreakKind::Mismatched));
+        assert!(breaks[0].to_string().ends_with("venue reports Buy 1 @ 100 in 1 fills with 0.06 commission"));
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use chrono::Utc;
+use tracing::{debug, info, warn};
+use warp::Filter;
+
+use super::RiskManager;
+use crate::error::{HftError, VenueError};
+use crate::events::{EngineEvent, EventBus};
+use crate::metrics::{ACCOUNT_BALANCE, BALANCE_TRANSFERS};
+use crate::venues::{AssetBalance, Transfer, VenueAdapter, VenueRegistry};
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Each venue's balances as last polled
+#[derive(Debug, Default)]
+pub struct Balances {
+    venues: RwLock<BTreeMap<String, Vec<AssetBalance>>>,
+}
+
+impl Balances {
+    pub fn update(&self, venue: &str, balances: Vec<AssetBalance>) {
+        self.venues.write().unwrap().insert(venue.to_string(), balances);
+    }
+
+    /// `venue`'s balances, `None` until polled
+    pub fn venue(&self, venue: &str) -> Option<Vec<AssetBalance>> {
+        self.venues.read().unwrap().get(venue).cloned()
+    }
+
+    pub fn all(&self) -> BTreeMap<String, Vec<AssetBalance>> {
+        self.venues.read().unwrap().clone()
+    }
+}
+
+/// How often balances are polled, and how much leverage orders may take on
+/// the capital available
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceConfig {
+    pub interval: Duration,
+    /// Orders growing a position are refused when their notional over this
+    /// exceeds the venue's available balance
+    pub max_leverage: Option<f64>,
+}
+
+impl Default for BalanceConfig {
+    fn default() -> Self {
+        Self { interval: DEFAULT_POLL_INTERVAL, max_leverage: None }
+    }
+}
+
+impl BalanceConfig {
+    /// Read `HFT_BALANCE_POLL_SECS` and `HFT_MAX_LEVERAGE`; returns `None`
+    /// when neither is set
+    pub fn from_env() -> Option<Self> {
+        let interval = std::env::var("HFT_BALANCE_POLL_SECS").ok();
+        let max_leverage = std::env::var("HFT_MAX_LEVERAGE").ok();
+        if interval.is_none() && max_leverage.is_none() {
+            return None;
+        }
+        let mut config = Self::default();
+        match interval.map(|secs| secs.parse::<u64>().ok().filter(|secs| *secs > 0)) {
+            Some(Some(secs)) => config.interval = Duration::from_secs(secs),
+            Some(None) => warn!("Ignoring malformed HFT_BALANCE_POLL_SECS"),
+            None => {}
+        }
+        match max_leverage.map(|leverage| leverage.parse::<f64>().ok().filter(|l| l.is_finite() && *l > 0.0)) {
+            Some(Some(leverage)) => config.max_leverage = Some(leverage),
+            Some(None) => warn!("Ignoring malformed HFT_MAX_LEVERAGE"),
+            None => {}
+        }
+        Some(config)
+    }
+}
+
+/// Balance changes seen on a venue by id, with their timestamps, from the
+/// latest timestamp on
+#[derive(Debug)]
+struct Seen {
+    since: u64,
+    ids: HashMap<String, u64>,
+}
+
+/// Polls each venue's balances into the risk manager, and publishes
+/// deposits, withdrawals, funding and other balance changes made outside of
+/// trading as they appear
+pub struct BalanceMonitor {
+    risk: Arc<RiskManager>,
+    venues: VenueRegistry,
+    events: EventBus,
+    interval: Duration,
+    /// Changes before the monitor started are not published
+    seen: Mutex<HashMap<String, Seen>>,
+    started: u64,
+}
+
+impl BalanceMonitor {
+    pub fn new(risk: Arc<RiskManager>, venues: VenueRegistry, events: EventBus, interval: Duration) -> Self {
+        let started = Utc::now().timestamp_millis().max(0) as u64;
+        Self { risk, venues, events, interval, seen: Mutex::default(), started }
+    }
+
+    /// Changes in `transfers` not seen before on `venue`
+    fn fresh(&self, venue: &str, transfers: Vec<Transfer>) -> Vec<Transfer> {
+        let mut seen = self.seen.lock().unwrap();
+        let seen = seen.entry(venue.to_string()).or_insert_with(|| Seen { since: self.started, ids: HashMap::new() });
+        let fresh: Vec<Transfer> = transfers.into_iter()
+            .filter(|transfer| transfer.timestamp >= seen.since && seen.ids.insert(transfer.id.clone(), transfer.timestamp).is_none())
+            .collect();
+        seen.since = seen.ids.values().copied().fold(seen.since, u64::max);
+        let since = seen.since;
+        seen.ids.retain(|_, timestamp| *timestamp >= since);
+        fresh
+    }
+
+    /// Poll `venue`'s balances and changes since the last poll
+    pub async fn check(&self, venue: &dyn VenueAdapter) -> Result<(), HftError> {
+        let name = venue.name().await;
+        let balances = venue.balances().await?;
+        for balance in &balances {
+            ACCOUNT_BALANCE.with_label_values(&[&name, &balance.asset, "total"]).set(balance.total);
+            ACCOUNT_BALANCE.with_label_values(&[&name, &balance.asset, "available"]).set(balance.available);
+        }
+        self.risk.balances().update(&name, balances);
+
+        let since = self.seen.lock().unwrap().get(&name).map_or(self.started, |seen| seen.since);
+        let transfers = match venue.transfers(since).await {
+            Ok(transfers) => transfers,
+            Err(HftError::Venue(VenueError::NotSupported(_))) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        for transfer in self.fresh(&name, transfers) {
+            BALANCE_TRANSFERS.with_label_values(&[&name, &transfer.kind.to_string()]).inc();
+            let detail = format!("{} {} {} (id {})", transfer.kind, transfer.amount, transfer.asset, transfer.id);
+            info!(venue = %name, detail = %detail, "Balance changed outside of trading");
+            self.events.publish(EngineEvent::Transfer {
+                venue: name.clone(),
+                asset: transfer.asset,
+                kind: transfer.kind.to_string(),
+                amount: transfer.amount,
+                detail,
+            });
+        }
+        Ok(())
+    }
+
+    /// Poll every venue each interval
+    pub async fn run(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            for venue in self.venues.all() {
+                let result = self.check(&*venue).await;
+                let name = venue.name().await;
+                match result {
+                    Ok(()) => {}
+                    Err(HftError::Venue(VenueError::NotSupported(_))) => debug!(venue = %name, "Venue reports no balances"),
+                    Err(e) => warn!(venue = %name, error = %e, "Failed to poll balances"),
+                }
+            }
+        }
+    }
+}
+
+/// `GET /admin/balances` for each venue's balances as last polled
+pub fn routes(
+    risk: Arc<RiskManager>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "balances")
+        .and(warp::get())
+        .map(move || warp::reply::json(&risk.balances().all()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::mock_venue::{MockVenue, MockVenueConfig};
+    use crate::risk::LossLimits;
+    use crate::types::{Order, OrderSide, OrderType, Quote};
+    use crate::venues::TransferKind;
+
+    fn transfer(id: &str, amount: f64, kind: TransferKind, timestamp: u64) -> Transfer {
+        Transfer { id: id.to_string(), asset: "USDT".to_string(), amount, kind, timestamp }
+    }
+
+    #[tokio::test]
+    async fn test_transfers_published_once_and_balances_limit_orders() {
+        let risk = Arc::new(RiskManager::new(LossLimits::default()));
+        risk.set_max_leverage(5.0);
+        let events = EventBus::new(16);
+        let mut rx = events.subscribe();
+        let venue = Arc::new(MockVenue::new("BAL", MockVenueConfig::default()));
+        let monitor = BalanceMonitor::new(Arc::clone(&risk), VenueRegistry::new(), events, DEFAULT_POLL_INTERVAL);
+
+        let order = Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price: 50_000.0,
+            venue: "BAL".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        };
+        risk.trading_state().transition(crate::command::mode::TradingMode::Active, "test").unwrap();
+        // Unlimited until balances are known
+        assert!(risk.check_order(&order).await.is_ok());
+
+        venue.set_balances(vec![AssetBalance { asset: "USDT".to_string(), total: 20_000.0, available: 12_000.0 }]).await;
+        venue.set_transfers(vec![
+            // Before the monitor started
+            transfer("1", 5_000.0, TransferKind::Deposit, monitor.started - 1),
+            transfer("2", -8_000.0, TransferKind::Withdrawal, monitor.started + 1),
+            transfer("3", -1.5, TransferKind::Funding, monitor.started + 2),
+        ]).await;
+        monitor.check(&*venue).await.unwrap();
+        monitor.check(&*venue).await.unwrap();
+        assert!(matches!(rx.try_recv(), Ok(EngineEvent::Transfer { kind, amount, .. }) if kind == "withdrawal" && amount == -8_000.0));
+        assert!(matches!(rx.try_recv(), Ok(EngineEvent::Transfer { kind, .. }) if kind == "funding"));
+        assert!(rx.try_recv().is_err());
+
+        // 10,000 of margin at 5x fits in 12,000 available
+        assert!(risk.check_order(&order).await.is_ok());
+        venue.set_balances(vec![AssetBalance { asset: "USDT".to_string(), total: 12_000.0, available: 4_000.0 }]).await;
+        monitor.check(&*venue).await.unwrap();
+        assert!(risk.check_order(&order).await.is_err());
+        // Marked lower, the order needs less margin
+        risk.on_quote(&Quote { symbol: "BTCUSDT".into(), bid: 19_999.0, ask: 20_001.0, bid_size: 1.0, ask_size: 1.0, venue: "BAL".into(), timestamp: 1 }).await;
+        assert!(risk.check_order(&order).await.is_ok());
+
+        let response = warp::test::request().path("/admin/balances").reply(&routes(risk)).await;
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["BAL"][0]["available"], 4_000.0);
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::{DateTime, TimeZone, Utc};
+use tokio::sync::{mpsc, RwLock};
+use tracing::{error, info, warn};
+
+use crate::book::OrderBook;
+use crate::events::{EngineEvent, EventBus};
+use crate::gateways::quote::QuoteGateway;
+use crate::metrics::CONTRACT_ROLL_ORDERS;
+use crate::risk::RiskManager;
+use crate::types::{Order, OrderSide, OrderType, Symbol};
+
+const DEFAULT_CLOSE_ONLY_BEFORE: Duration = Duration::from_secs(3600);
+const DEFAULT_ROLL_BEFORE: Duration = Duration::from_secs(1800);
+
+/// How often contracts are checked against their expiry
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A dated contract's expiry and the contract its positions roll into
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contract {
+    pub expiry: DateTime<Utc>,
+    /// Without one, positions are closed rather than rolled
+    pub next: Option<String>,
+}
+
+/// Where a contract is in its life at a given time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ContractPhase {
+    Open,
+    /// Orders may only shrink positions
+    CloseOnly,
+    /// Positions are moved into the next contract
+    Roll,
+    Expired,
+}
+
+/// Expiries of the dated contracts traded, and how long before expiry
+/// positions stop growing and are rolled
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpiryConfig {
+    pub contracts: HashMap<String, Contract>,
+    pub close_only_before: Duration,
+    pub roll_before: Duration,
+}
+
+impl Default for ExpiryConfig {
+    fn default() -> Self {
+        Self {
+            contracts: HashMap::new(),
+            close_only_before: DEFAULT_CLOSE_ONLY_BEFORE,
+            roll_before: DEFAULT_ROLL_BEFORE,
+        }
+    }
+}
+
+impl ExpiryConfig {
+    pub fn with_contract(mut self, symbol: &str, expiry: DateTime<Utc>, next: Option<&str>) -> Self {
+        self.contracts.insert(symbol.to_uppercase(), Contract { expiry, next: next.map(str::to_uppercase) });
+        self
+    }
+
+    /// Comma separated `SYMBOL=EXPIRY[>NEXT]` entries, the expiry in RFC 3339
+    /// or milliseconds since the epoch, e.g.
+    /// `BTCUSD_260327=2026-03-27T08:00:00Z>BTCUSD_260626`
+    fn parse_contracts(mut self, spec: &str) -> Self {
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=').and_then(|(symbol, contract)| {
+                let (expiry, next) = match contract.split_once('>') {
+                    Some((expiry, next)) => (expiry.trim(), Some(next.trim()).filter(|n| !n.is_empty())),
+                    None => (contract.trim(), None),
+                };
+                let expiry = DateTime::parse_from_rfc3339(expiry).map(|e| e.with_timezone(&Utc)).ok()
+                    .or_else(|| Utc.timestamp_millis_opt(expiry.parse().ok()?).single())?;
+                Some((symbol.trim(), expiry, next))
+            });
+            match parsed {
+                Some((symbol, expiry, next)) if !symbol.is_empty() => self = self.with_contract(symbol, expiry, next),
+                _ => warn!(entry = entry, "Ignoring malformed contract expiry"),
+            }
+        }
+        self
+    }
+
+    /// Comma separated `close_only:SECS` and `roll:SECS` entries, each the
+    /// time before expiry; unset ones keep their defaults of an hour and
+    /// half an hour
+    fn parse_schedule(mut self, spec: &str) -> Self {
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once(':')
+                .and_then(|(key, secs)| Some((key.trim(), secs.trim().parse::<f64>().ok().filter(|s| *s >= 0.0)?)));
+            match parsed {
+                Some(("close_only", secs)) => self.close_only_before = Duration::from_secs_f64(secs),
+                Some(("roll", secs)) => self.roll_before = Duration::from_secs_f64(secs),
+                _ => warn!(entry = entry, "Ignoring malformed expiry schedule entry"),
+            }
+        }
+        self
+    }
+
+    /// Read the contracts from `HFT_EXPIRIES` and the schedule from
+    /// `HFT_EXPIRY_SCHEDULE`; returns `None` when no contracts are set
+    pub fn from_env() -> Option<Self> {
+        let config = Self::default().parse_contracts(&std::env::var("HFT_EXPIRIES").ok()?);
+        Some(match std::env::var("HFT_EXPIRY_SCHEDULE") {
+            Ok(schedule) => config.parse_schedule(&schedule),
+            Err(_) => config,
+        })
+    }
+
+    pub fn contract(&self, symbol: &str) -> Option<&Contract> {
+        self.contracts.get(symbol)
+    }
+
+    /// `symbol`'s phase at `now`; symbols without an expiry are always open
+    pub fn phase(&self, symbol: &str, now: DateTime<Utc>) -> ContractPhase {
+        let Some(contract) = self.contract(symbol) else {
+            return ContractPhase::Open;
+        };
+        let before = |window: Duration| contract.expiry - chrono::Duration::from_std(window).unwrap_or(chrono::Duration::MAX);
+        if now >= contract.expiry {
+            ContractPhase::Expired
+        } else if now >= before(self.roll_before) {
+            ContractPhase::Roll
+        } else if now >= before(self.close_only_before) {
+            ContractPhase::CloseOnly
+        } else {
+            ContractPhase::Open
+        }
+    }
+}
+
+/// Contracts already taken through each phase, so each step happens once
+#[derive(Default)]
+struct State {
+    phases: HashMap<String, ContractPhase>,
+    /// Contracts whose books could not be delisted yet
+    pending_delist: HashSet<String>,
+}
+
+/// Walks dated contracts through their expiry: subscribes the next contract
+/// once positions may only close, rolls positions into it, and delists the
+/// expired contract from the book builder. Orders growing positions near
+/// expiry are refused by [`RiskManager::check_order`] once
+/// [`RiskManager::set_expiries`] is given the same config.
+pub struct ExpiryManager {
+    config: ExpiryConfig,
+    risk: Arc<RiskManager>,
+    order_tx: mpsc::Sender<Order>,
+    quote_gateway: Arc<QuoteGateway>,
+    books: Arc<RwLock<HashMap<Symbol, OrderBook>>>,
+    events: EventBus,
+    state: Mutex<State>,
+}
+
+impl ExpiryManager {
+    pub fn new(
+        config: ExpiryConfig,
+        risk: Arc<RiskManager>,
+        order_tx: mpsc::Sender<Order>,
+        quote_gateway: Arc<QuoteGateway>,
+        books: Arc<RwLock<HashMap<Symbol, OrderBook>>>,
+        events: EventBus,
+    ) -> Self {
+        Self { config, risk, order_tx, quote_gateway, books, events, state: Mutex::default() }
+    }
+
+    fn alert(&self, symbol: &str, stage: &str, detail: String) {
+        info!(symbol = symbol, stage = stage, detail = %detail, "Contract expiry");
+        self.events.publish(EngineEvent::ContractExpiry {
+            symbol: symbol.to_string(),
+            stage: stage.to_string(),
+            detail,
+        });
+    }
+
+    fn send(&self, order: Order, action: &str) {
+        CONTRACT_ROLL_ORDERS.with_label_values(&[&order.venue, action]).inc();
+        if let Err(e) = self.order_tx.try_send(order) {
+            error!(error = %e, action = action, "Failed to send contract roll order");
+        }
+    }
+
+    /// Orders closing each strategy's position in `symbol` and, when there is
+    /// a next contract, reopening it there
+    async fn roll_orders(&self, symbol: &str, next: Option<&str>) -> Vec<(Order, &'static str)> {
+        let mut orders = Vec::new();
+        for (key, position) in self.risk.positions().await {
+            if key.symbol != symbol || position.is_flat() {
+                continue;
+            }
+            let close = Order {
+                symbol: symbol.into(),
+                side: if position.quantity > 0.0 { OrderSide::Sell } else { OrderSide::Buy },
+                quantity: position.quantity.abs(),
+                price: 0.0,
+                venue: key.venue.as_str().into(),
+                order_type: OrderType::Market,
+                expire_after: None,
+                strategy: Some(key.strategy.clone()),
+                bypass_kill_switch: false,
+            };
+            if let Some(next) = next {
+                orders.push((Order { symbol: next.into(), side: close.side.opposite(), ..close.clone() }, "open"));
+            }
+            orders.push((close, "close"));
+        }
+        // Close before reopening
+        orders.sort_by_key(|(_, action)| *action != "close");
+        orders
+    }
+
+    async fn delist(&self, symbol: &str) -> bool {
+        if let Err(e) = self.quote_gateway.remove_symbols(&[symbol.to_string()]).await {
+            warn!(symbol = symbol, error = %e, "Failed to unsubscribe expired contract");
+            return false;
+        }
+        self.books.write().await.remove(symbol);
+        true
+    }
+
+    /// Take each contract through the steps of the phase it is in at `now`
+    pub async fn check(&self, now: DateTime<Utc>) {
+        for (symbol, contract) in &self.config.contracts {
+            let phase = self.config.phase(symbol, now);
+            let previous = {
+                let mut state = self.state.lock().unwrap();
+                let previous = state.phases.get(symbol).copied().unwrap_or(ContractPhase::Open);
+                if phase <= previous {
+                    None
+                } else {
+                    state.phases.insert(symbol.clone(), phase);
+                    Some(previous)
+                }
+            };
+
+            if let Some(previous) = previous {
+                if previous < ContractPhase::CloseOnly {
+                    if let Some(next) = &contract.next {
+                        if let Err(e) = self.quote_gateway.add_symbols(vec![next.clone()]).await {
+                            warn!(symbol = %symbol, next = %next, error = %e, "Failed to subscribe next contract");
+                        }
+                    }
+                    self.alert(symbol, "close_only", format!("{} expires at {}, positions may only be reduced", symbol, contract.expiry));
+                }
+                // An expired contract has settled, leaving nothing to roll
+                if phase == ContractPhase::Roll {
+                    let orders = self.roll_orders(symbol, contract.next.as_deref()).await;
+                    let detail = match &contract.next {
+                        Some(next) => format!("rolling {} positions into {} before expiry at {}", orders.iter().filter(|(_, a)| *a == "close").count(), next, contract.expiry),
+                        None => format!("closing {} positions before expiry at {}", orders.len(), contract.expiry),
+                    };
+                    self.alert(symbol, "rolling", detail);
+                    for (order, action) in orders {
+                        self.send(order, action);
+                    }
+                }
+                if phase == ContractPhase::Expired {
+                    self.state.lock().unwrap().pending_delist.insert(symbol.clone());
+                }
+            }
+
+            let pending = self.state.lock().unwrap().pending_delist.contains(symbol);
+            if pending && self.delist(symbol).await {
+                self.state.lock().unwrap().pending_delist.remove(symbol);
+                self.alert(symbol, "expired", format!("{} expired at {} and was delisted", symbol, contract.expiry));
+            }
+        }
+    }
+
+    /// Check contracts every few seconds
+    pub async fn run(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(EXPIRY_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.check(Utc::now()).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Fill;
+
+    fn expiry() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 27, 8, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_phases() {
+        let config = ExpiryConfig::default()
+            .parse_contracts("btcusd_260327=2026-03-27T08:00:00Z>btcusd_260626, BTCUSD_260626=1782460800000, bad=soon, =1")
+            .parse_schedule("close_only:7200, roll:600, nope:1");
+        assert_eq!(config.contract("BTCUSD_260327"), Some(&Contract { expiry: expiry(), next: Some("BTCUSD_260626".to_string()) }));
+        assert_eq!(config.contract("BTCUSD_260626").unwrap().next, None);
+        assert_eq!(config.contracts.len(), 2);
+        assert_eq!(config.close_only_before, Duration::from_secs(7200));
+
+        let at = |minutes: i64| expiry() - chrono::Duration::minutes(minutes);
+        assert_eq!(config.phase("BTCUSD_260327", at(121)), ContractPhase::Open);
+        assert_eq!(config.phase("BTCUSD_260327", at(119)), ContractPhase::CloseOnly);
+        assert_eq!(config.phase("BTCUSD_260327", at(9)), ContractPhase::Roll);
+        assert_eq!(config.phase("BTCUSD_260327", at(0)), ContractPhase::Expired);
+        assert_eq!(config.phase("BTCUSDT", at(0)), ContractPhase::Open);
+    }
+
+    #[tokio::test]
+    async fn test_rolls_positions_and_blocks_growth() {
+        // Within the roll window now, for the pre-trade check
+        let expiry = Utc::now() + chrono::Duration::minutes(20);
+        let config = ExpiryConfig::default().with_contract("BTCUSD_260327", expiry, Some("BTCUSD_260626"));
+        let risk = Arc::new(RiskManager::new(Default::default()));
+        risk.set_expiries(config.clone());
+        risk.on_fill(&Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSD_260327".into(),
+            venue: "MOCK".into(),
+            strategy: "basis".to_string(),
+            side: OrderSide::Buy,
+            quantity: 2.0,
+            price: 50000.0,
+            timestamp: 0,
+            commission: None,
+        }).await;
+
+        let (quote_tx, _quote_rx) = mpsc::channel(8);
+        let (order_tx, mut order_rx) = mpsc::channel(8);
+        let manager = ExpiryManager::new(
+            config,
+            Arc::clone(&risk),
+            order_tx,
+            Arc::new(QuoteGateway::new(quote_tx)),
+            Arc::new(RwLock::new(HashMap::new())),
+            EventBus::new(16),
+        );
+
+        manager.check(Utc::now()).await;
+        let close = order_rx.recv().await.unwrap();
+        assert_eq!((close.symbol.as_str(), close.side, close.quantity), ("BTCUSD_260327", OrderSide::Sell, 2.0));
+        assert_eq!(close.strategy.as_deref(), Some("basis"));
+        let open = order_rx.recv().await.unwrap();
+        assert_eq!((open.symbol.as_str(), open.side, open.quantity), ("BTCUSD_260626", OrderSide::Buy, 2.0));
+
+        // Rolled once only
+        manager.check(expiry - chrono::Duration::minutes(10)).await;
+        assert!(order_rx.try_recv().is_err());
+
+        // Growing the expiring position is refused, reducing it is not
+        risk.trading_state().transition(crate::command::mode::TradingMode::Active, "test").unwrap();
+        let order = |side| Order { side, symbol: "BTCUSD_260327".into(), ..close.clone() };
+        assert!(risk.check_order(&order(OrderSide::Buy)).await.is_err());
+        assert!(risk.check_order(&order(OrderSide::Sell)).await.is_ok());
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::fmt;
+use tracing::warn;
+
+/// Quote currencies stripped from a symbol to find its base asset
+const QUOTE_CURRENCIES: [&str; 8] = ["USDT", "USDC", "BUSD", "FDUSD", "USD", "EUR", "BTC", "ETH"];
+
+/// Split a symbol into base asset and quote currency, e.g. `BTCUSDT` ->
+/// (`BTC`, `USDT`)
+pub fn split_symbol(symbol: &str) -> Option<(String, &'static str)> {
+    let upper = symbol.to_uppercase();
+    QUOTE_CURRENCIES.into_iter().find_map(|quote| {
+        let base = upper.strip_suffix(quote)?.trim_end_matches(['-', '_', '/']);
+        (!base.is_empty()).then(|| (base.to_string(), quote))
+    })
+}
+
+/// Gross and net notional caps, in the reporting currency
+#[derive(Debug, Clone, Default)]
+pub struct ExposureLimit {
+    pub max_gross: Option<f64>,
+    pub max_net: Option<f64>,
+}
+
+/// Symbols or assets whose exposure counts against a shared limit
+#[derive(Debug, Clone, Default)]
+pub struct ExposureGroup {
+    /// Symbols (e.g. `BTCUSDT`) or base assets (e.g. `BTC`)
+    pub members: Vec<String>,
+    pub limit: ExposureLimit,
+}
+
+impl ExposureGroup {
+    fn contains(&self, symbol: &str, asset: &str) -> bool {
+        self.members.iter().any(|m| m == symbol || m == asset)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExposureLimits {
+    /// Limits keyed by base asset
+    pub assets: HashMap<String, ExposureLimit>,
+    /// Limits keyed by group name
+    pub groups: HashMap<String, ExposureGroup>,
+    /// Explicit symbol to base asset mapping, for symbols the quote-currency
+    /// heuristic gets wrong
+    pub symbol_assets: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Exposure {
+    pub gross: f64,
+    pub net: f64,
+}
+
+impl Exposure {
+    fn add(&mut self, notional: f64) {
+        self.gross += notional.abs();
+        self.net += notional;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExposureScope {
+    Asset(String),
+    Group(String),
+}
+
+impl fmt::Display for ExposureScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExposureScope::Asset(name) => write!(f, "asset:{}", name),
+            ExposureScope::Group(name) => write!(f, "group:{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExposureBreach {
+    pub scope: ExposureScope,
+    pub kind: &'static str,
+    pub exposure: f64,
+    pub limit: f64,
+}
+
+impl fmt::Display for ExposureBreach {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} exposure {:.2} exceeds limit {:.2}", self.scope, self.kind, self.exposure, self.limit)
+    }
+}
+
+impl ExposureLimits {
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty() && self.groups.is_empty()
+    }
+
+    /// Comma separated `NAME.gross=MAX` and `NAME.net=MAX` limits, with
+    /// `NAME.members=A|B` making NAME a group of those symbols or assets
+    /// rather than a base asset, e.g.
+    /// `BTC.net=250000,majors.members=BTC|ETH,majors.gross=1000000`
+    fn parse(spec: &str) -> Self {
+        let mut limits: HashMap<String, ExposureLimit> = HashMap::new();
+        let mut members: HashMap<String, Vec<String>> = HashMap::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((name, key, value)) = entry.split_once('=')
+                .and_then(|(scope, value)| scope.trim().split_once('.').map(|(name, key)| (name, key, value.trim())))
+                .filter(|(name, _, _)| !name.is_empty())
+            else {
+                warn!(entry = entry, "Ignoring malformed exposure limit");
+                continue;
+            };
+            let max = || value.parse::<f64>().ok().filter(|max| max.is_finite() && *max >= 0.0);
+            match (key, max()) {
+                ("gross", Some(max)) => limits.entry(name.to_string()).or_default().max_gross = Some(max),
+                ("net", Some(max)) => limits.entry(name.to_string()).or_default().max_net = Some(max),
+                ("members", _) => {
+                    members.insert(name.to_string(), value.split('|').map(|m| m.trim().to_uppercase()).filter(|m| !m.is_empty()).collect());
+                }
+                _ => warn!(entry = entry, "Ignoring malformed exposure limit"),
+            }
+        }
+
+        let mut parsed = Self::default();
+        for (name, limit) in limits {
+            match members.remove(&name) {
+                Some(members) => {
+                    parsed.groups.insert(name, ExposureGroup { members, limit });
+                }
+                None => {
+                    parsed.assets.insert(name.to_uppercase(), limit);
+                }
+            }
+        }
+        parsed
+    }
+
+    /// Read `HFT_EXPOSURE_LIMITS` (see [`parse`](Self::parse)); returns
+    /// `None` when unset
+    pub fn from_env() -> Option<Self> {
+        Some(Self::parse(&std::env::var("HFT_EXPOSURE_LIMITS").ok()?))
+    }
+
+    /// Base asset for a symbol, e.g. `BTCUSDT` -> `BTC`
+    pub fn asset_for(&self, symbol: &str) -> String {
+        if let Some(asset) = self.symbol_assets.get(symbol) {
+            return asset.clone();
+        }
+
+        split_symbol(symbol)
+            .map(|(base, _)| base)
+            .unwrap_or_else(|| symbol.to_uppercase())
+    }
+
+    /// Aggregate per-symbol signed notionals into asset and group exposures
+    pub fn aggregate(&self, notionals: &HashMap<String, f64>) -> HashMap<ExposureScope, Exposure> {
+        let mut exposures: HashMap<ExposureScope, Exposure> = HashMap::new();
+
+        for (symbol, &notional) in notionals {
+            let asset = self.asset_for(symbol);
+            exposures
+                .entry(ExposureScope::Asset(asset.clone()))
+                .or_default()
+                .add(notional);
+
+            for (name, group) in &self.groups {
+                if group.contains(symbol, &asset) {
+                    exposures
+                        .entry(ExposureScope::Group(name.clone()))
+                        .or_default()
+                        .add(notional);
+                }
+            }
+        }
+
+        exposures
+    }
+
+    /// First configured limit exceeded by `exposures`
+    pub fn check(&self, exposures: &HashMap<ExposureScope, Exposure>) -> Option<ExposureBreach> {
+        let mut scopes: Vec<_> = exposures.iter().collect();
+        scopes.sort_by_key(|(scope, _)| scope.to_string());
+
+        for (scope, exposure) in scopes {
+            let limit = match scope {
+                ExposureScope::Asset(name) => self.assets.get(name),
+                ExposureScope::Group(name) => self.groups.get(name).map(|g| &g.limit),
+            };
+            let Some(limit) = limit else { continue };
+
+            if let Some(max) = limit.max_gross {
+                if exposure.gross > max {
+                    return Some(ExposureBreach { scope: scope.clone(), kind: "gross", exposure: exposure.gross, limit: max });
+                }
+            }
+            if let Some(max) = limit.max_net {
+                if exposure.net.abs() > max {
+                    return Some(ExposureBreach { scope: scope.clone(), kind: "net", exposure: exposure.net, limit: max });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_for_symbol() {
+        let limits = ExposureLimits {
+            symbol_assets: HashMap::from([("XBTUSD".to_string(), "BTC".to_string())]),
+            ..Default::default()
+        };
+
+        assert_eq!(limits.asset_for("BTCUSDT"), "BTC");
+        assert_eq!(limits.asset_for("eth-usdc"), "ETH");
+        assert_eq!(limits.asset_for("ETHBTC"), "ETH");
+        assert_eq!(limits.asset_for("XBTUSD"), "BTC");
+    }
+
+    #[test]
+    fn test_group_aggregates_correlated_symbols() {
+        let limits = ExposureLimits {
+            groups: HashMap::from([("btc".to_string(), ExposureGroup {
+                members: vec!["BTC".to_string()],
+                limit: ExposureLimit { max_gross: Some(150.0), max_net: Some(50.0) },
+            })]),
+            ..Default::default()
+        };
+
+        let notionals = HashMap::from([
+            ("BTCUSDT".to_string(), 100.0),
+            ("BTCUSDC".to_string(), -80.0),
+            ("ETHUSDT".to_string(), 500.0),
+        ]);
+
+        let exposures = limits.aggregate(&notionals);
+        let group = exposures[&ExposureScope::Group("btc".to_string())];
+        assert_eq!(group.gross, 180.0);
+        assert_eq!(group.net, 20.0);
+
+        let breach = limits.check(&exposures).unwrap();
+        assert_eq!(breach.scope, ExposureScope::Group("btc".to_string()));
+        assert_eq!(breach.kind, "gross");
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let limits = ExposureLimits::parse("btc.net=250000, majors.members=BTC|ethusdt, majors.gross=1000000, ETH.gross=-1, bad");
+
+        assert_eq!(limits.assets.len(), 1);
+        assert_eq!(limits.assets["BTC"].max_net, Some(250000.0));
+        assert_eq!(limits.assets["BTC"].max_gross, None);
+
+        let group = &limits.groups["majors"];
+        assert_eq!(group.members, vec!["BTC", "ETHUSDT"]);
+        assert_eq!(group.limit.max_gross, Some(1000000.0));
+        assert_eq!(group.limit.max_net, None);
+    }
+}
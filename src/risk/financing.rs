@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::warn;
+
+use super::positions::Position;
+
+const DEFAULT_FUNDING_INTERVAL: Duration = Duration::from_secs(8 * 3600);
+const YEAR: Duration = Duration::from_secs(365 * 24 * 3600);
+
+/// How holding a symbol is financed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Carry {
+    /// Perpetual swap funding per funding interval: longs pay shorts when
+    /// positive, shorts pay longs when negative
+    Funding { rate: f64 },
+    /// Annual rate paid on the notional of a margin short
+    Borrow { annual_rate: f64 },
+}
+
+impl Carry {
+    /// Parse `funding:<rate>` or `borrow:<annual rate>`
+    fn parse(s: &str) -> Option<Self> {
+        let (kind, rate) = s.split_once(':')?;
+        let rate: f64 = rate.trim().parse().ok()?;
+        match kind.trim() {
+            "funding" => Some(Carry::Funding { rate }),
+            "borrow" => Some(Carry::Borrow { annual_rate: rate }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Rates {
+    symbols: HashMap<String, Carry>,
+}
+
+/// Funding and borrow rates per symbol, applied to open positions as they
+/// are held. Cloning shares the rates, so a venue feed can keep funding
+/// rates current with [`set_funding_rate`](Self::set_funding_rate).
+#[derive(Debug, Clone)]
+pub struct FinancingRates {
+    rates: Arc<RwLock<Rates>>,
+    funding_interval: Duration,
+}
+
+impl Default for FinancingRates {
+    fn default() -> Self {
+        Self { rates: Arc::default(), funding_interval: DEFAULT_FUNDING_INTERVAL }
+    }
+}
+
+impl FinancingRates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_carry(self, symbol: &str, carry: Carry) -> Self {
+        self.rates.write().unwrap().symbols.insert(symbol.to_string(), carry);
+        self
+    }
+
+    /// Time between perpetual funding payments (default 8 hours)
+    pub fn with_funding_interval(mut self, interval: Duration) -> Self {
+        self.funding_interval = interval;
+        self
+    }
+
+    /// Comma separated `SYMBOL=funding:RATE` or `SYMBOL=borrow:ANNUAL_RATE`
+    /// entries, e.g. `BTCUSDT-PERP=funding:0.0001,ETHUSDT=borrow:0.08`
+    fn parse(spec: &str) -> Self {
+        let mut rates = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once('=').and_then(|(symbol, carry)| Some((symbol.trim(), Carry::parse(carry)?))) {
+                Some((symbol, carry)) if !symbol.is_empty() => rates = rates.with_carry(symbol, carry),
+                _ => warn!(entry = entry, "Ignoring malformed financing rate"),
+            }
+        }
+        rates
+    }
+
+    /// Read `HFT_FINANCING` (see [`parse`](Self::parse)) and
+    /// `HFT_FUNDING_INTERVAL_HOURS` (default 8); returns `None` when no
+    /// rates are set
+    pub fn from_env() -> Option<Self> {
+        let mut rates = Self::parse(&std::env::var("HFT_FINANCING").ok()?);
+        if let Some(hours) = std::env::var("HFT_FUNDING_INTERVAL_HOURS").ok().and_then(|h| h.parse::<f64>().ok()).filter(|h| *h > 0.0) {
+            rates.funding_interval = Duration::from_secs_f64(hours * 3600.0);
+        }
+        Some(rates)
+    }
+
+    /// Replace `symbol`'s funding rate, e.g. from the venue's latest
+    pub fn set_funding_rate(&self, symbol: &str, rate: f64) {
+        self.rates.write().unwrap().symbols.insert(symbol.to_string(), Carry::Funding { rate });
+    }
+
+    pub fn carry(&self, symbol: &str) -> Option<Carry> {
+        self.rates.read().unwrap().symbols.get(symbol).copied()
+    }
+
+    /// PnL from financing `position` in `symbol` at `mark` for `elapsed`;
+    /// negative when the position pays
+    pub fn accrual(&self, symbol: &str, position: &Position, mark: f64, elapsed: Duration) -> f64 {
+        let notional = position.quantity * mark;
+        match self.carry(symbol) {
+            Some(Carry::Funding { rate }) => {
+                -notional * rate * elapsed.as_secs_f64() / self.funding_interval.as_secs_f64()
+            }
+            Some(Carry::Borrow { annual_rate }) if position.quantity < 0.0 => {
+                notional * annual_rate * elapsed.as_secs_f64() / YEAR.as_secs_f64()
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(quantity: f64) -> Position {
+        Position { quantity, avg_price: 100.0, ..Position::default() }
+    }
+
+    #[test]
+    fn test_accrual() {
+        let rates = FinancingRates::parse("PERP=funding:0.0001, SPOT=borrow:0.0365, BAD=borrow, X=lend:1");
+        let interval = DEFAULT_FUNDING_INTERVAL;
+
+        // Longs pay positive funding, shorts receive it
+        assert!((rates.accrual("PERP", &position(10.0), 100.0, interval) + 0.1).abs() < 1e-12);
+        assert!((rates.accrual("PERP", &position(-10.0), 100.0, interval / 2) - 0.05).abs() < 1e-12);
+        rates.set_funding_rate("PERP", -0.0002);
+        assert!((rates.accrual("PERP", &position(10.0), 100.0, interval) - 0.2).abs() < 1e-12);
+
+        // Borrow is paid by shorts only
+        let day = Duration::from_secs(24 * 3600);
+        assert!((rates.accrual("SPOT", &position(-10.0), 100.0, day) + 0.1).abs() < 1e-12);
+        assert_eq!(rates.accrual("SPOT", &position(10.0), 100.0, day), 0.0);
+        assert_eq!(rates.carry("BAD"), None);
+        assert_eq!(rates.carry("X"), None);
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use tracing::warn;
+
+use super::exposure::split_symbol;
+use super::positions::PositionTracker;
+
+/// Converts amounts in each symbol's quote currency into one reporting
+/// currency, using the mids of FX pairs the engine already marks (e.g.
+/// `USDCUSDT`, `EURUSDT`).
+#[derive(Debug, Clone)]
+pub struct FxConversion {
+    pub reporting_currency: String,
+    /// FX pair symbol to its (base, quote) currencies
+    pairs: HashMap<String, (String, String)>,
+}
+
+impl FxConversion {
+    pub fn new(reporting_currency: &str) -> Self {
+        Self {
+            reporting_currency: reporting_currency.to_uppercase(),
+            pairs: HashMap::new(),
+        }
+    }
+
+    /// Price `base` in `quote` from the mid of `symbol`
+    pub fn with_pair(mut self, symbol: &str, base: &str, quote: &str) -> Self {
+        self.pairs.insert(symbol.to_string(), (base.to_uppercase(), quote.to_uppercase()));
+        self
+    }
+
+    /// Read `HFT_REPORTING_CURRENCY` and the comma separated FX pair symbols in
+    /// `HFT_FX_PAIRS`, e.g. `USDCUSDT,EURUSDT`
+    pub fn from_env() -> Option<Self> {
+        let reporting_currency = std::env::var("HFT_REPORTING_CURRENCY").ok()?;
+
+        let mut fx = Self::new(&reporting_currency);
+        let pairs = std::env::var("HFT_FX_PAIRS").unwrap_or_default();
+        for symbol in pairs.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            match split_symbol(symbol) {
+                Some((base, quote)) => fx = fx.with_pair(symbol, &base, quote),
+                None => warn!(symbol = symbol, "Ignoring FX pair with unknown quote currency"),
+            }
+        }
+        Some(fx)
+    }
+
+    /// Currencies the configured pairs can price
+    pub fn currencies(&self) -> Vec<String> {
+        let mut currencies: Vec<String> = self.pairs
+            .values()
+            .flat_map(|(base, quote)| [base.clone(), quote.clone()])
+            .collect();
+        currencies.sort();
+        currencies.dedup();
+        currencies
+    }
+
+    /// Units of `to` per unit of `from` from a single marked pair, either way
+    /// round
+    fn direct(&self, from: &str, to: &str, positions: &PositionTracker) -> Option<f64> {
+        self.pairs.iter().find_map(|(symbol, (base, quote))| {
+            let mid = positions.mark_price(symbol)?;
+            if base == from && quote == to {
+                Some(mid)
+            } else if base == to && quote == from {
+                Some(1.0 / mid)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Value of one unit of `currency` in the reporting currency, directly or
+    /// through one intermediate currency. `None` until the pairs are marked.
+    pub fn rate(&self, currency: &str, positions: &PositionTracker) -> Option<f64> {
+        let currency = currency.to_uppercase();
+        let reporting = &self.reporting_currency;
+        if &currency == reporting {
+            return Some(1.0);
+        }
+
+        self.direct(&currency, reporting, positions).or_else(|| {
+            self.currencies().iter().find_map(|via| {
+                Some(self.direct(&currency, via, positions)? * self.direct(via, reporting, positions)?)
+            })
+        })
+    }
+
+    /// Value of `amount`, in `symbol`'s quote currency, in the reporting
+    /// currency. Amounts that cannot be converted yet are returned as is.
+    pub fn convert(&self, symbol: &str, amount: f64, positions: &PositionTracker) -> f64 {
+        let Some((_, quote)) = split_symbol(symbol) else {
+            return amount;
+        };
+        match self.rate(quote, positions) {
+            Some(rate) => amount * rate,
+            None => amount,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates_from_marked_pairs() {
+        let fx = FxConversion::new("usdt")
+            .with_pair("USDCUSDT", "USDC", "USDT")
+            .with_pair("EURUSDC", "EUR", "USDC");
+        let mut positions = PositionTracker::new();
+
+        assert_eq!(fx.rate("USDT", &positions), Some(1.0));
+        assert_eq!(fx.rate("USDC", &positions), None);
+        // Unconverted until the pair has a mid
+        assert_eq!(fx.convert("BTCUSDC", 100.0, &positions), 100.0);
+
+        positions.mark("USDCUSDT", 0.5);
+        positions.mark("EURUSDC", 2.0);
+        assert_eq!(fx.rate("USDC", &positions), Some(0.5));
+        // Through USDC
+        assert_eq!(fx.rate("EUR", &positions), Some(1.0));
+        assert_eq!(fx.convert("BTCUSDC", 100.0, &positions), 50.0);
+        assert_eq!(fx.convert("BTCUSDT", 100.0, &positions), 100.0);
+
+        // Inverted pair
+        let fx = FxConversion::new("USDC").with_pair("USDCUSDT", "USDC", "USDT");
+        assert_eq!(fx.rate("USDT", &positions), Some(2.0));
+    }
+}
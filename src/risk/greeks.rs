@@ -0,0 +1,281 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use serde::Serialize;
+use tracing::warn;
+use warp::Filter;
+
+use super::RiskManager;
+
+/// Net sensitivities of every position on one underlying
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct UnderlyingGreeks {
+    /// Underlying units, with linear positions counting one each
+    pub delta: f64,
+    /// `delta` at the underlying's price, in the reporting currency
+    pub delta_notional: f64,
+    pub gamma: f64,
+    /// Per volatility point
+    pub vega: f64,
+    /// Per day
+    pub theta: f64,
+}
+
+impl std::ops::AddAssign for UnderlyingGreeks {
+    fn add_assign(&mut self, other: Self) {
+        self.delta += other.delta;
+        self.delta_notional += other.delta_notional;
+        self.gamma += other.gamma;
+        self.vega += other.vega;
+        self.theta += other.theta;
+    }
+}
+
+/// Portfolio greeks by underlying, and net delta weighted by each
+/// underlying's beta to the benchmark
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PortfolioGreeks {
+    /// Currency of notionals; each symbol's quote currency when there is no
+    /// FX conversion
+    pub currency: Option<String>,
+    pub underlyings: BTreeMap<String, UnderlyingGreeks>,
+    pub beta_weighted_delta: f64,
+}
+
+/// Which greek a limit caps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Greek {
+    Delta,
+    Gamma,
+    Vega,
+}
+
+impl Greek {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "delta" => Some(Greek::Delta),
+            "gamma" => Some(Greek::Gamma),
+            "vega" => Some(Greek::Vega),
+            _ => None,
+        }
+    }
+
+    fn of(&self, greeks: &UnderlyingGreeks) -> f64 {
+        match self {
+            Greek::Delta => greeks.delta,
+            Greek::Gamma => greeks.gamma,
+            Greek::Vega => greeks.vega,
+        }
+    }
+}
+
+impl fmt::Display for Greek {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Greek::Delta => write!(f, "delta"),
+            Greek::Gamma => write!(f, "gamma"),
+            Greek::Vega => write!(f, "vega"),
+        }
+    }
+}
+
+/// Caps on the absolute net greeks of each underlying and on beta-weighted
+/// delta, with the betas to weight by
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GreekLimits {
+    pub limits: HashMap<(String, Greek), f64>,
+    /// Beta-weighted delta notional cap, in the reporting currency
+    pub max_beta_weighted_delta: Option<f64>,
+    /// Beta of each underlying to the benchmark; 1 when not listed
+    pub betas: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreekBreach {
+    /// The underlying, or `None` for beta-weighted delta
+    pub underlying: Option<String>,
+    pub greek: Greek,
+    pub exposure: f64,
+    pub limit: f64,
+}
+
+impl GreekBreach {
+    /// Scope reported in risk breach events, e.g. `greek:BTC.vega`
+    pub fn scope(&self) -> String {
+        match &self.underlying {
+            Some(underlying) => format!("greek:{}.{}", underlying, self.greek),
+            None => "greek:beta_delta".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for GreekBreach {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.underlying {
+            Some(underlying) => write!(f, "{} {} {:.4} exceeds limit {:.4}", underlying, self.greek, self.exposure, self.limit),
+            None => write!(f, "beta-weighted delta {:.2} exceeds limit {:.2}", self.exposure, self.limit),
+        }
+    }
+}
+
+impl GreekLimits {
+    pub fn is_empty(&self) -> bool {
+        self.limits.is_empty() && self.max_beta_weighted_delta.is_none()
+    }
+
+    pub fn beta(&self, underlying: &str) -> f64 {
+        self.betas.get(underlying).copied().unwrap_or(1.0)
+    }
+
+    /// Comma separated `UNDERLYING.GREEK=MAX` limits, with GREEK one of
+    /// `delta`, `gamma` and `vega`, `beta_delta=MAX` for beta-weighted delta
+    /// and `UNDERLYING.beta=BETA` betas, e.g.
+    /// `BTC.delta=50,BTC.vega=20000,ETH.beta=1.3,beta_delta=2000000`
+    fn parse(spec: &str) -> Self {
+        let mut limits = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=')
+                .and_then(|(key, value)| Some((key.trim(), value.trim().parse::<f64>().ok().filter(|v| v.is_finite())?)));
+            match parsed {
+                Some(("beta_delta", max)) if max >= 0.0 => limits.max_beta_weighted_delta = Some(max),
+                Some((key, value)) => match key.split_once('.') {
+                    Some((underlying, "beta")) if !underlying.is_empty() => {
+                        limits.betas.insert(underlying.to_uppercase(), value);
+                    }
+                    Some((underlying, greek)) if !underlying.is_empty() && value >= 0.0 => match Greek::parse(greek) {
+                        Some(greek) => {
+                            limits.limits.insert((underlying.to_uppercase(), greek), value);
+                        }
+                        None => warn!(entry = entry, "Ignoring malformed greek limit"),
+                    },
+                    _ => warn!(entry = entry, "Ignoring malformed greek limit"),
+                },
+                None => warn!(entry = entry, "Ignoring malformed greek limit"),
+            }
+        }
+        limits
+    }
+
+    /// Read `HFT_GREEK_LIMITS` (see [`parse`](Self::parse)); returns `None`
+    /// when unset
+    pub fn from_env() -> Option<Self> {
+        Some(Self::parse(&std::env::var("HFT_GREEK_LIMITS").ok()?))
+    }
+
+    /// First limit `greeks` exceeds
+    pub fn check(&self, greeks: &PortfolioGreeks) -> Option<GreekBreach> {
+        let mut limits: Vec<_> = self.limits.iter().collect();
+        limits.sort_by(|a, b| a.0.cmp(b.0));
+        for ((underlying, greek), &limit) in limits {
+            let exposure = greeks.underlyings.get(underlying).map(|g| greek.of(g)).unwrap_or(0.0);
+            if exposure.abs() > limit {
+                return Some(GreekBreach { underlying: Some(underlying.clone()), greek: *greek, exposure, limit });
+            }
+        }
+        self.max_beta_weighted_delta
+            .filter(|limit| greeks.beta_weighted_delta.abs() > *limit)
+            .map(|limit| GreekBreach { underlying: None, greek: Greek::Delta, exposure: greeks.beta_weighted_delta, limit })
+    }
+}
+
+/// `GET /admin/greeks` for portfolio greeks as of the latest fill or mark
+pub fn routes(
+    risk: Arc<RiskManager>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "greeks")
+        .and(warp::get())
+        .map(move || warp::reply::json(&risk.portfolio_greeks()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::LossLimits;
+    use crate::signals::{GreeksConfig, GreeksFeed};
+    use crate::types::{Fill, OptionInstrument, OrderSide, OrderType, Order, Quote};
+
+    fn quote(symbol: &str, bid: f64, ask: f64, timestamp: u64) -> Quote {
+        Quote { symbol: symbol.into(), bid, ask, bid_size: 1.0, ask_size: 1.0, venue: "DERIBIT".into(), timestamp }
+    }
+
+    fn fill(symbol: &str, side: OrderSide, quantity: f64, price: f64) -> Fill {
+        Fill {
+            order_id: "1".to_string(),
+            symbol: symbol.into(),
+            venue: "DERIBIT".into(),
+            strategy: "vol".to_string(),
+            side,
+            quantity,
+            price,
+            timestamp: 0,
+            commission: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let limits = GreekLimits::parse("btc.delta=50, BTC.vega=20000, ETH.beta=1.3, beta_delta=2000000, BTC.rho=1, x=y");
+        assert_eq!(limits.limits.get(&("BTC".to_string(), Greek::Delta)), Some(&50.0));
+        assert_eq!(limits.limits.len(), 2);
+        assert_eq!(limits.beta("ETH"), 1.3);
+        assert_eq!(limits.beta("SOL"), 1.0);
+        assert_eq!(limits.max_beta_weighted_delta, Some(2_000_000.0));
+    }
+
+    #[tokio::test]
+    async fn test_aggregates_options_and_linear_positions() {
+        let feed = GreeksFeed::new(GreeksConfig { premium_in_underlying: true, ..Default::default() });
+        let risk = Arc::new(RiskManager::new(LossLimits::default()).with_greeks(feed.clone()));
+        risk.set_greek_limits(GreekLimits::parse("BTC.delta=4,ETH.beta=2"));
+
+        let option = "BTC-27MAR26-50000-C";
+        let now = OptionInstrument::parse(option).unwrap().expiry - 30 * 86_400_000;
+        for quote in [
+            quote(option, 0.05, 0.06, now),
+            quote("BTC", 49_990.0, 50_010.0, now),
+            quote("BTCUSDT", 49_990.0, 50_010.0, now),
+            quote("ETHUSDT", 2_999.0, 3_001.0, now),
+        ] {
+            feed.on_quote(&quote);
+            risk.on_quote(&quote).await;
+        }
+        risk.on_fill(&fill(option, OrderSide::Sell, 10.0, 0.055)).await;
+        risk.on_fill(&fill("BTCUSDT", OrderSide::Buy, 2.0, 50_000.0)).await;
+        risk.on_fill(&fill("ETHUSDT", OrderSide::Buy, 1.0, 3_000.0)).await;
+
+        let option_greeks = feed.greeks(option).unwrap().greeks;
+        let portfolio = risk.portfolio_greeks();
+        let btc = portfolio.underlyings["BTC"];
+        assert!((btc.delta - (2.0 - 10.0 * option_greeks.delta)).abs() < 1e-9);
+        assert!((btc.vega + 10.0 * option_greeks.vega).abs() < 1e-9);
+        assert_eq!(portfolio.underlyings["ETH"].delta, 1.0);
+        assert!((btc.delta_notional - btc.delta * 50_000.0).abs() < 1e-6);
+        assert!((portfolio.beta_weighted_delta - (btc.delta_notional + 2.0 * 3_000.0)).abs() < 1e-6);
+
+        // A mark of the underlying reprices the options
+        feed.on_quote(&quote("BTC", 50_490.0, 50_510.0, now));
+        risk.on_quote(&quote("BTC", 50_490.0, 50_510.0, now)).await;
+        assert_ne!(risk.portfolio_greeks().underlyings["BTC"], btc);
+
+        // Going long twice as many calls as were sold takes BTC delta past
+        // its limit
+        risk.trading_state().transition(crate::command::mode::TradingMode::Active, "test").unwrap();
+        let order = Order {
+            symbol: option.into(),
+            side: OrderSide::Buy,
+            quantity: 20.0,
+            price: 0.06,
+            venue: "DERIBIT".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        };
+        assert!(risk.check_order(&order).await.is_err());
+        assert!(risk.check_order(&Order { quantity: 1.0, ..order }).await.is_ok());
+
+        let response = warp::test::request().path("/admin/greeks").reply(&routes(risk)).await;
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["underlyings"]["ETH"]["delta"], 1.0);
+    }
+}
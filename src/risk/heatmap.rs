@@ -0,0 +1,108 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use serde::Serialize;
+use warp::Filter;
+
+use super::RiskManager;
+
+/// Exposure of one strategy in one symbol on one venue, in the reporting
+/// currency
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeatMapCell {
+    pub symbol: String,
+    pub venue: String,
+    pub strategy: String,
+    /// Signed quantity, positive when long
+    pub quantity: f64,
+    /// Absolute notional at the mark price
+    pub notional: f64,
+    /// Signed notional at the mark price, negative when short
+    pub delta: f64,
+    /// Realized, unrealized and financing PnL since the position opened
+    pub pnl: f64,
+}
+
+/// Current exposure by symbol, venue and strategy, with the values of each
+/// axis sorted so dashboards can lay the cells out as a matrix
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HeatMap {
+    /// Currency of notionals and PnL; each symbol's quote currency when
+    /// there is no FX conversion
+    pub currency: Option<String>,
+    pub symbols: Vec<String>,
+    pub venues: Vec<String>,
+    pub strategies: Vec<String>,
+    pub cells: Vec<HeatMapCell>,
+}
+
+impl HeatMap {
+    pub(crate) fn new(currency: Option<String>, mut cells: Vec<HeatMapCell>) -> Self {
+        cells.sort_by(|a, b| (&a.symbol, &a.venue, &a.strategy).cmp(&(&b.symbol, &b.venue, &b.strategy)));
+        let axis = |field: fn(&HeatMapCell) -> &String| {
+            cells.iter().map(field).cloned().collect::<BTreeSet<_>>().into_iter().collect()
+        };
+        Self {
+            currency,
+            symbols: axis(|cell| &cell.symbol),
+            venues: axis(|cell| &cell.venue),
+            strategies: axis(|cell| &cell.strategy),
+            cells,
+        }
+    }
+}
+
+/// `GET /admin/heatmap` for exposure by symbol, venue and strategy as it
+/// stands now
+pub fn routes(
+    risk: Arc<RiskManager>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "heatmap")
+        .and(warp::get())
+        .then(move || {
+            let risk = Arc::clone(&risk);
+            async move { warp::reply::json(&risk.heat_map().await) }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::LossLimits;
+    use crate::types::{Fill, OrderSide, Quote};
+
+    #[tokio::test]
+    async fn test_heatmap_route() {
+        let risk = Arc::new(RiskManager::new(LossLimits::default()));
+        for (strategy, venue, side, price) in [("mm", "A", OrderSide::Buy, 100.0), ("arb", "B", OrderSide::Sell, 104.0)] {
+            risk.on_fill(&Fill {
+                order_id: "1".to_string(),
+                symbol: "ETHUSDT".into(),
+                venue: venue.into(),
+                strategy: strategy.to_string(),
+                side,
+                quantity: 2.0,
+                price,
+                timestamp: 0,
+                commission: None,
+            }).await;
+        }
+        risk.on_quote(&Quote {
+            symbol: "ETHUSDT".into(),
+            bid: 102.0,
+            ask: 102.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "A".into(),
+            timestamp: 0,
+        }).await;
+
+        let response = warp::test::request().path("/admin/heatmap").reply(&routes(risk)).await;
+        assert_eq!(response.status(), 200);
+        let map: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(map["venues"], serde_json::json!(["A", "B"]));
+        assert_eq!(map["strategies"], serde_json::json!(["arb", "mm"]));
+        let short = &map["cells"][1];
+        assert_eq!((short["venue"].as_str(), short["delta"].as_f64()), (Some("B"), Some(-204.0)));
+        assert_eq!((short["notional"].as_f64(), short["pnl"].as_f64()), (Some(204.0), Some(4.0)));
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{debug, error, warn};
+
+use crate::error::{HftError, VenueError};
+use crate::events::{EngineEvent, EventBus};
+use crate::instruments::InstrumentMap;
+use crate::metrics::LIQUIDATION_DISTANCE;
+use crate::types::{Order, OrderSide, OrderType};
+use crate::venues::{PositionRisk, VenueAdapter, VenueRegistry};
+
+const DEFAULT_WARN_PCT: f64 = 10.0;
+const DEFAULT_REDUCE_PCT: f64 = 5.0;
+const DEFAULT_REDUCE_FRACTION: f64 = 0.25;
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// How often venue positions are checked
+const LIQUIDATION_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How close to liquidation positions may get before alerting and reducing
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiquidationConfig {
+    /// Alert when the mark price is within this percentage of liquidation
+    pub warn_pct: f64,
+    /// Reduce the position when within this percentage
+    pub reduce_pct: f64,
+    /// Share of the position each reduction closes
+    pub reduce_fraction: f64,
+    /// Least time between reductions of the same position, letting the
+    /// venue update its liquidation price
+    pub cooldown: Duration,
+}
+
+impl Default for LiquidationConfig {
+    fn default() -> Self {
+        Self {
+            warn_pct: DEFAULT_WARN_PCT,
+            reduce_pct: DEFAULT_REDUCE_PCT,
+            reduce_fraction: DEFAULT_REDUCE_FRACTION,
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+}
+
+impl LiquidationConfig {
+    /// Comma separated `warn:PCT`, `reduce:PCT`, `fraction:SHARE` and
+    /// `cooldown:SECS` entries, e.g. `warn:10,reduce:5,fraction:0.25`;
+    /// unset ones keep their defaults
+    fn parse(spec: &str) -> Self {
+        let mut config = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once(':')
+                .and_then(|(key, value)| Some((key.trim(), value.trim().parse::<f64>().ok().filter(|v| *v > 0.0)?)));
+            match parsed {
+                Some(("warn", pct)) => config.warn_pct = pct,
+                Some(("reduce", pct)) => config.reduce_pct = pct,
+                Some(("fraction", share)) if share <= 1.0 => config.reduce_fraction = share,
+                Some(("cooldown", secs)) => config.cooldown = Duration::from_secs_f64(secs),
+                _ => warn!(entry = entry, "Ignoring malformed liquidation guard entry"),
+            }
+        }
+        config
+    }
+
+    /// Read `HFT_LIQUIDATION` (see [`parse`](Self::parse)); returns `None`
+    /// when the guard is off
+    pub fn from_env() -> Option<Self> {
+        Some(Self::parse(&std::env::var("HFT_LIQUIDATION").ok()?))
+    }
+
+    fn level(&self, distance_pct: f64) -> Level {
+        if distance_pct < self.reduce_pct {
+            Level::Reduce
+        } else if distance_pct < self.warn_pct {
+            Level::Warn
+        } else {
+            Level::Safe
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    Safe,
+    Warn,
+    Reduce,
+}
+
+#[derive(Default)]
+struct State {
+    /// Level of each open position at the last check, by venue and symbol
+    levels: HashMap<(String, String), Level>,
+    reduced_at: HashMap<(String, String), Instant>,
+}
+
+/// Watches the distance between each venue position's mark price and its
+/// liquidation price, alerting as it shrinks and closing part of the
+/// position once it is too close. Reductions go through the order gateway
+/// like any other order.
+pub struct LiquidationGuard {
+    config: LiquidationConfig,
+    venues: VenueRegistry,
+    order_tx: mpsc::Sender<Order>,
+    /// Venues report positions under their own symbols
+    instruments: Arc<InstrumentMap>,
+    events: EventBus,
+    state: Mutex<State>,
+}
+
+impl LiquidationGuard {
+    pub fn new(config: LiquidationConfig, venues: VenueRegistry, order_tx: mpsc::Sender<Order>, events: EventBus) -> Self {
+        Self { config, venues, order_tx, instruments: Arc::default(), events, state: Mutex::default() }
+    }
+
+    pub fn with_instruments(mut self, instruments: Arc<InstrumentMap>) -> Self {
+        self.instruments = instruments;
+        self
+    }
+
+    /// The order reducing `risk` if it is too close to liquidation and was
+    /// not reduced within the cooldown, publishing an event when its level
+    /// worsens or it is reduced
+    fn assess(&self, venue: &str, risk: &PositionRisk, now: Instant) -> Option<Order> {
+        let distance = risk.liquidation_distance_pct()?;
+        LIQUIDATION_DISTANCE.with_label_values(&[venue, &risk.symbol]).set(distance);
+
+        let key = (venue.to_string(), risk.symbol.clone());
+        let level = self.config.level(distance);
+        let mut state = self.state.lock().unwrap();
+        let previous = state.levels.insert(key.clone(), level).unwrap_or(Level::Safe);
+
+        let reduce = level == Level::Reduce
+            && state.reduced_at.get(&key).is_none_or(|at| now.duration_since(*at) >= self.config.cooldown);
+        if reduce {
+            state.reduced_at.insert(key, now);
+        }
+        drop(state);
+
+        if !reduce && (level == Level::Safe || level <= previous) {
+            return None;
+        }
+        let detail = format!(
+            "mark {} is {:.2}% from liquidation at {} with {} open",
+            risk.mark_price, distance, risk.liquidation_price, risk.quantity
+        );
+        warn!(venue = venue, symbol = %risk.symbol, detail = %detail, reducing = reduce, "Position near liquidation");
+        self.events.publish(EngineEvent::LiquidationRisk {
+            venue: venue.to_string(),
+            symbol: risk.symbol.clone(),
+            distance_pct: distance,
+            reducing: reduce,
+            detail,
+        });
+
+        reduce.then(|| Order {
+            symbol: self.instruments.canonical(venue.into(), risk.symbol.as_str().into()),
+            side: if risk.quantity > 0.0 { OrderSide::Sell } else { OrderSide::Buy },
+            quantity: risk.quantity.abs() * self.config.reduce_fraction,
+            price: 0.0,
+            venue: venue.into(),
+            order_type: OrderType::Market,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        })
+    }
+
+    /// Check `venue`'s open positions, reducing those too close to
+    /// liquidation
+    pub async fn check(&self, venue: &dyn VenueAdapter) -> Result<(), HftError> {
+        let name = venue.name().await;
+        let risks = venue.position_risks().await?;
+        let now = Instant::now();
+        self.state.lock().unwrap().levels.retain(|(v, symbol), _| {
+            *v != name || risks.iter().any(|risk| &risk.symbol == symbol)
+        });
+
+        for risk in &risks {
+            let Some(order) = self.assess(&name, risk, now) else {
+                continue;
+            };
+            if let Err(e) = self.order_tx.try_send(order) {
+                error!(venue = %name, symbol = %risk.symbol, error = %e, "Failed to send order reducing position near liquidation");
+            }
+        }
+        Ok(())
+    }
+
+    /// Check every venue's positions every few seconds
+    pub async fn run(self: std::sync::Arc<Self>) {
+        let mut interval = tokio::time::interval(LIQUIDATION_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            for venue in self.venues.all() {
+                let result = self.check(&*venue).await;
+                let name = venue.name().await;
+                match result {
+                    Ok(()) => {}
+                    Err(HftError::Venue(VenueError::NotSupported(_))) => debug!(venue = %name, "Venue reports no position risk"),
+                    Err(e) => warn!(venue = %name, error = %e, "Failed to fetch position risk"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::mock_venue::{MockVenue, MockVenueConfig};
+
+    fn risk(mark_price: f64) -> PositionRisk {
+        PositionRisk { symbol: "BTCUSDT".to_string(), quantity: 2.0, mark_price, liquidation_price: 90.0 }
+    }
+
+    #[tokio::test]
+    async fn test_alerts_then_reduces_as_distance_shrinks() {
+        let config = LiquidationConfig::parse("warn:10, reduce:5, fraction:0.5, cooldown:60, fraction:2, x:1");
+        assert_eq!(config.reduce_fraction, 0.5);
+        let events = EventBus::new(16);
+        let mut rx = events.subscribe();
+        let venue = Arc::new(MockVenue::new("MOCK", MockVenueConfig { error_probability: 0.0, ..Default::default() }));
+        let (order_tx, mut order_rx) = mpsc::channel(8);
+        let mut instruments = InstrumentMap::new();
+        instruments.insert("BTC-PERP", "MOCK", "BTCUSDT");
+        let guard = LiquidationGuard::new(config, VenueRegistry::new(), order_tx, events)
+            .with_instruments(Arc::new(instruments));
+
+        // 18% away: nothing to do
+        venue.set_position_risks(vec![risk(110.0)]).await;
+        guard.check(&*venue).await.unwrap();
+        assert!(rx.try_recv().is_err());
+
+        // 8% away: alert once
+        venue.set_position_risks(vec![risk(98.0)]).await;
+        guard.check(&*venue).await.unwrap();
+        guard.check(&*venue).await.unwrap();
+        assert!(matches!(rx.try_recv(), Ok(EngineEvent::LiquidationRisk { reducing: false, .. })));
+        assert!(rx.try_recv().is_err());
+        assert!(order_rx.try_recv().is_err());
+
+        // 4% away: sell half, then wait out the cooldown
+        venue.set_position_risks(vec![risk(94.0)]).await;
+        guard.check(&*venue).await.unwrap();
+        guard.check(&*venue).await.unwrap();
+        assert!(matches!(rx.try_recv(), Ok(EngineEvent::LiquidationRisk { reducing: true, .. })));
+        // Sent to the gateway under the canonical symbol, never straight to
+        // the venue
+        let order = order_rx.try_recv().unwrap();
+        assert_eq!((order.symbol.as_str(), order.venue.as_str()), ("BTC-PERP", "MOCK"));
+        assert_eq!((order.side, order.quantity), (OrderSide::Sell, 1.0));
+        assert!(order_rx.try_recv().is_err());
+        assert!(venue.submitted_orders().await.is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use tracing::warn;
+
+/// Daily loss limits, expressed as positive amounts of quote currency
+#[derive(Debug, Clone)]
+pub struct LossLimits {
+    /// Maximum loss across all strategies for the trading day
+    pub daily_loss_limit: Option<f64>,
+    /// Maximum loss per strategy for the trading day
+    pub strategy_loss_limits: HashMap<String, f64>,
+    /// Send market orders to flatten positions when a limit is breached
+    pub flatten_on_breach: bool,
+    /// Minimum time the kill switch stays engaged after a breach
+    pub cooldown: Duration,
+}
+
+impl Default for LossLimits {
+    fn default() -> Self {
+        Self {
+            daily_loss_limit: None,
+            strategy_loss_limits: HashMap::new(),
+            flatten_on_breach: false,
+            cooldown: Duration::from_secs(300),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LossScope {
+    Portfolio,
+    Strategy(String),
+}
+
+impl fmt::Display for LossScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LossScope::Portfolio => write!(f, "portfolio"),
+            LossScope::Strategy(name) => write!(f, "strategy:{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LossBreach {
+    pub scope: LossScope,
+    pub pnl: f64,
+    pub limit: f64,
+}
+
+impl fmt::Display for LossBreach {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} daily PnL {:.2} breached loss limit {:.2}", self.scope, self.pnl, self.limit)
+    }
+}
+
+impl LossLimits {
+    /// Comma separated `daily:AMOUNT`, `strategy.NAME:AMOUNT`,
+    /// `cooldown:SECS` and `flatten` entries, e.g.
+    /// `daily:5000,strategy.mm:1000,flatten`; unset ones keep their
+    /// defaults
+    pub fn parse(spec: &str) -> Self {
+        let mut limits = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            if entry == "flatten" {
+                limits.flatten_on_breach = true;
+                continue;
+            }
+            let parsed = entry.split_once(':')
+                .and_then(|(key, value)| Some((key.trim(), value.trim().parse::<f64>().ok().filter(|v| *v > 0.0)?)));
+            match parsed {
+                Some(("daily", amount)) => limits.daily_loss_limit = Some(amount),
+                Some(("cooldown", secs)) => limits.cooldown = Duration::from_secs_f64(secs),
+                Some((key, amount)) if key.starts_with("strategy.") && key.len() > "strategy.".len() => {
+                    limits.strategy_loss_limits.insert(key["strategy.".len()..].to_string(), amount);
+                }
+                _ => warn!(entry = entry, "Ignoring malformed loss limit entry"),
+            }
+        }
+        limits
+    }
+
+    /// Read `HFT_LOSS_LIMITS` (see [`parse`](Self::parse)); returns `None`
+    /// when unset
+    pub fn from_env() -> Option<Self> {
+        Some(Self::parse(&std::env::var("HFT_LOSS_LIMITS").ok()?))
+    }
+
+    /// Return the first breached limit, checking the portfolio limit before
+    /// strategy limits
+    pub fn check(&self, total_pnl: f64, strategy_pnl: &HashMap<String, f64>) -> Option<LossBreach> {
+        if let Some(limit) = self.daily_loss_limit {
+            if total_pnl <= -limit {
+                return Some(LossBreach {
+                    scope: LossScope::Portfolio,
+                    pnl: total_pnl,
+                    limit,
+                });
+            }
+        }
+
+        let mut strategies: Vec<_> = self.strategy_loss_limits.iter().collect();
+        strategies.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (strategy, &limit) in strategies {
+            let pnl = strategy_pnl.get(strategy).copied().unwrap_or(0.0);
+            if pnl <= -limit {
+                return Some(LossBreach {
+                    scope: LossScope::Strategy(strategy.clone()),
+                    pnl,
+                    limit,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portfolio_limit_checked_first() {
+        let limits = LossLimits {
+            daily_loss_limit: Some(100.0),
+            strategy_loss_limits: HashMap::from([("mm".to_string(), 50.0)]),
+            ..Default::default()
+        };
+
+        let strategy_pnl = HashMap::from([("mm".to_string(), -120.0)]);
+        let breach = limits.check(-120.0, &strategy_pnl).unwrap();
+        assert_eq!(breach.scope, LossScope::Portfolio);
+
+        let breach = limits.check(-60.0, &HashMap::from([("mm".to_string(), -60.0)])).unwrap();
+        assert_eq!(breach.scope, LossScope::Strategy("mm".to_string()));
+
+        assert!(limits.check(-10.0, &HashMap::from([("mm".to_string(), -10.0)])).is_none());
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let limits = LossLimits::parse("daily:5000, strategy.mm:1000,cooldown:60,flatten,strategy.:5,daily:-1");
+        assert_eq!(limits.daily_loss_limit, Some(5000.0));
+        assert_eq!(limits.strategy_loss_limits, HashMap::from([("mm".to_string(), 1000.0)]));
+        assert_eq!(limits.cooldown, Duration::from_secs(60));
+        assert!(limits.flatten_on_breach);
+
+        let limits = LossLimits::parse("");
+        assert_eq!((limits.daily_loss_limit, limits.flatten_on_breach), (None, false));
+    }
+}
@@ -0,0 +1,1182 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use chrono::{NaiveDate, Utc};
+use tokio::sync::{mpsc, RwLock};
+use tracing::{info, warn, error};
+
+use crate::types::{Fill, OptionInstrument, Order, OrderSide, OrderType, Quote};
+use crate::error::{HftError, ExecutionError};
+use crate::events::{EngineEvent, EventBus};
+use crate::audit::{AuditEvent, AuditLog};
+use crate::report::ActivityTracker;
+use crate::fees::FeeModel;
+use crate::gateways::order::CancelRequest;
+use crate::signals::{GreeksFeed, PricingInputs};
+use crate::sink::{OrderEvent, SinkHandle};
+use crate::command::mode::{TradingMode, TradingState};
+use crate::metrics::{FILLED_QUANTITY, ORDER_FILLS, KILL_SWITCH_ENGAGED, STRATEGY_PNL, LOSS_LIMIT_BREACHES, EXPOSURE_NOTIONAL, FX_RATES, PORTFOLIO_GREEKS, COMMISSIONS};
+
+pub mod positions;
+pub mod loss;
+pub mod exposure;
+pub mod toggles;
+pub mod fx;
+pub mod sanity;
+pub mod financing;
+pub mod liquidation;
+pub mod outage;
+pub mod expiry;
+pub mod heatmap;
+pub mod greeks;
+pub mod stress;
+pub mod balances;
+
+pub use positions::{Position, PositionKey, PositionTracker};
+pub use loss::{LossBreach, LossLimits, LossScope};
+pub use toggles::{DisabledTrading, TradingToggles};
+pub use fx::FxConversion;
+pub use financing::{Carry, FinancingRates};
+pub use liquidation::{LiquidationConfig, LiquidationGuard};
+pub use outage::{OutageConfig, OutageGuard};
+pub use expiry::{Contract, ContractPhase, ExpiryConfig, ExpiryManager};
+pub use heatmap::{HeatMap, HeatMapCell};
+pub use greeks::{Greek, GreekBreach, GreekLimits, PortfolioGreeks, UnderlyingGreeks};
+pub use stress::{Scenario, Shock, StressConfig, StressReport, StressResult, StressTester};
+pub use balances::{BalanceConfig, BalanceMonitor, Balances};
+pub use sanity::{PriceDeviation, PriceSanity, PriceSanityConfig};
+use exposure::split_symbol;
+pub use exposure::{Exposure, ExposureBreach, ExposureGroup, ExposureLimit, ExposureLimits, ExposureScope};
+
+/// Who engaged the kill switch, which decides whether it lifts by itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaltOwner {
+    /// Daily loss limits; released once back inside every limit after the
+    /// cool-down
+    LossLimits,
+    /// Operators and other components; stays engaged until released
+    Operator,
+}
+
+struct Halt {
+    reason: String,
+    owner: HaltOwner,
+    engaged_at: Instant,
+}
+
+/// Global switch that blocks all new orders while engaged
+#[derive(Default)]
+pub struct KillSwitch {
+    halt: RwLock<Option<Halt>>,
+    events: Option<EventBus>,
+}
+
+impl KillSwitch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Engage until released by hand
+    pub async fn engage(&self, reason: &str) {
+        self.engage_as(HaltOwner::Operator, reason).await;
+    }
+
+    /// Engage on behalf of `owner`. An operator engaging a switch already
+    /// held by the loss limits takes it over, so it no longer lifts by
+    /// itself.
+    pub async fn engage_as(&self, owner: HaltOwner, reason: &str) {
+        let mut halt = self.halt.write().await;
+        if let Some(halt) = halt.as_mut() {
+            if owner == HaltOwner::Operator && halt.owner != owner {
+                warn!(reason = %reason, "Kill switch taken over by operator");
+                halt.owner = owner;
+                halt.reason = reason.to_string();
+            }
+            return;
+        }
+        warn!(reason = %reason, owner = ?owner, "Kill switch engaged");
+        *halt = Some(Halt {
+            reason: reason.to_string(),
+            owner,
+            engaged_at: Instant::now(),
+        });
+        KILL_SWITCH_ENGAGED.set(1.0);
+        if let Some(events) = &self.events {
+            events.publish(EngineEvent::KillSwitchEngaged { reason: reason.to_string() });
+        }
+    }
+
+    pub async fn release(&self) {
+        let mut halt = self.halt.write().await;
+        if halt.take().is_some() {
+            info!("Kill switch released");
+            KILL_SWITCH_ENGAGED.set(0.0);
+            if let Some(events) = &self.events {
+                events.publish(EngineEvent::KillSwitchReleased);
+            }
+        }
+    }
+
+    pub async fn is_engaged(&self) -> bool {
+        self.halt.read().await.is_some()
+    }
+
+    pub async fn reason(&self) -> Option<String> {
+        self.halt.read().await.as_ref().map(|h| h.reason.clone())
+    }
+
+    /// How long the switch has been engaged, if it is
+    pub async fn engaged_for(&self) -> Option<Duration> {
+        self.halt.read().await.as_ref().map(|h| h.engaged_at.elapsed())
+    }
+
+    pub async fn owner(&self) -> Option<HaltOwner> {
+        self.halt.read().await.as_ref().map(|h| h.owner)
+    }
+}
+
+/// PnL at the start of the trading day, used to derive daily PnL
+struct DailyBaseline {
+    date: NaiveDate,
+    total: f64,
+    strategies: HashMap<String, f64>,
+}
+
+/// Pre-trade checks and loss-limit enforcement
+pub struct RiskManager {
+    positions: RwLock<PositionTracker>,
+    kill_switch: KillSwitch,
+    toggles: Arc<TradingToggles>,
+    trading: TradingState,
+    loss_limits: LossLimits,
+    exposure_limits: ExposureLimits,
+    baseline: RwLock<DailyBaseline>,
+    events: Option<EventBus>,
+    /// Attached after construction since the manager is shared by then
+    audit: OnceLock<AuditLog>,
+    /// Drop-copy destinations mirroring every fill
+    drop_copy: OnceLock<Vec<SinkHandle>>,
+    /// PnL and exposures are in each symbol's quote currency until set
+    fx: OnceLock<FxConversion>,
+    /// Funding and borrow rates, with when they were last accrued
+    financing: OnceLock<(FinancingRates, std::sync::Mutex<Instant>)>,
+    /// Cross-venue price check of marketable orders, when set
+    sanity: OnceLock<PriceSanity>,
+    /// Dated contracts whose positions may only shrink near expiry, when set
+    expiries: OnceLock<ExpiryConfig>,
+    /// Caps on portfolio greeks, and betas to weight delta by, when set
+    greek_limits: OnceLock<GreekLimits>,
+    /// Prices options when aggregating portfolio greeks
+    greeks: Option<GreeksFeed>,
+    /// Portfolio greeks as of the latest fill, with the symbols whose marks
+    /// change them
+    portfolio: std::sync::RwLock<(PortfolioGreeks, HashSet<String>)>,
+    /// Scenarios the admin API and scheduled stress tests run
+    stress_scenarios: OnceLock<Vec<Scenario>>,
+    /// Venue balances as last polled
+    balances: Balances,
+    /// Leverage orders may take on the available balance, when set
+    max_leverage: OnceLock<f64>,
+    activity: ActivityTracker,
+    /// Shared with strategies and the order gateway
+    fees: FeeModel,
+    /// Where flattening orders and loss-limit cancels are sent
+    order_tx: Option<mpsc::Sender<Order>>,
+    cancel_tx: Option<mpsc::Sender<CancelRequest>>,
+}
+
+impl RiskManager {
+    pub fn new(loss_limits: LossLimits) -> Self {
+        Self {
+            positions: RwLock::new(PositionTracker::new()),
+            kill_switch: KillSwitch::new(),
+            toggles: Arc::new(TradingToggles::new()),
+            trading: TradingState::new(),
+            loss_limits,
+            exposure_limits: ExposureLimits::default(),
+            baseline: RwLock::new(DailyBaseline {
+                date: Utc::now().date_naive(),
+                total: 0.0,
+                strategies: HashMap::new(),
+            }),
+            events: None,
+            audit: OnceLock::new(),
+            drop_copy: OnceLock::new(),
+            fx: OnceLock::new(),
+            sanity: OnceLock::new(),
+            expiries: OnceLock::new(),
+            greek_limits: OnceLock::new(),
+            greeks: None,
+            portfolio: std::sync::RwLock::new(Default::default()),
+            stress_scenarios: OnceLock::new(),
+            financing: OnceLock::new(),
+            balances: Balances::default(),
+            max_leverage: OnceLock::new(),
+            activity: ActivityTracker::new(),
+            fees: FeeModel::default(),
+            order_tx: None,
+            cancel_tx: None,
+        }
+    }
+
+    pub fn with_exposure_limits(mut self, exposure_limits: ExposureLimits) -> Self {
+        self.exposure_limits = exposure_limits;
+        self
+    }
+
+    /// Count fills toward each venue's fee tier in `fees`
+    pub fn with_fee_model(mut self, fees: FeeModel) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Price options in portfolio greeks from `greeks`
+    pub fn with_greeks(mut self, greeks: GreeksFeed) -> Self {
+        self.greeks = Some(greeks);
+        self
+    }
+
+    /// Send flattening orders and cancels through the order gateway reading
+    /// these channels; without them a breach only halts trading
+    pub fn with_gateway(mut self, order_tx: mpsc::Sender<Order>, cancel_tx: mpsc::Sender<CancelRequest>) -> Self {
+        self.order_tx = Some(order_tx);
+        self.cancel_tx = Some(cancel_tx);
+        self
+    }
+
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.kill_switch.events = Some(events.clone());
+        self.trading.events = Some(events.clone());
+        self.events = Some(events);
+        self
+    }
+
+    /// Record fills and risk cancellations in `audit`; only the first call
+    /// takes effect
+    pub fn set_audit(&self, audit: AuditLog) {
+        let _ = self.audit.set(audit);
+    }
+
+    /// Mirror fills to drop-copy `sinks`; only the first call takes effect
+    pub fn set_drop_copy(&self, sinks: Vec<SinkHandle>) {
+        let _ = self.drop_copy.set(sinks);
+    }
+
+    /// Report PnL and exposures in `fx`'s reporting currency; only the first
+    /// call takes effect
+    pub fn set_fx_conversion(&self, fx: FxConversion) {
+        let _ = self.fx.set(fx);
+    }
+
+    /// Charge open positions funding and borrow costs at `rates` from now
+    /// on; only the first call takes effect
+    pub fn set_financing(&self, rates: FinancingRates) {
+        let _ = self.financing.set((rates, std::sync::Mutex::new(Instant::now())));
+    }
+
+    /// Accrue funding and borrow costs on open positions since the last
+    /// accrual
+    pub async fn accrue_financing(&self) {
+        let Some((rates, accrued_at)) = self.financing.get() else {
+            return;
+        };
+        let now = Instant::now();
+        let elapsed = now.duration_since(std::mem::replace(&mut *accrued_at.lock().unwrap(), now));
+        self.positions.write().await.accrue_financing(rates, elapsed);
+    }
+
+    /// Hold marketable orders whose price strays from the other venues;
+    /// only the first call takes effect
+    pub fn set_price_sanity(&self, sanity: PriceSanity) {
+        let _ = self.sanity.set(sanity);
+    }
+
+    /// Refuse orders growing positions in dated contracts near their
+    /// expiry; only the first call takes effect
+    pub fn set_expiries(&self, expiries: ExpiryConfig) {
+        let _ = self.expiries.set(expiries);
+    }
+
+    /// Refuse orders taking portfolio greeks past `limits`, and weight delta
+    /// by its betas; only the first call takes effect
+    pub fn set_greek_limits(&self, limits: GreekLimits) {
+        let _ = self.greek_limits.set(limits);
+    }
+
+    /// Stress test positions against `scenarios` on demand; only the first
+    /// call takes effect
+    pub fn set_stress_scenarios(&self, scenarios: Vec<Scenario>) {
+        let _ = self.stress_scenarios.set(scenarios);
+    }
+
+    pub fn stress_scenarios(&self) -> &[Scenario] {
+        self.stress_scenarios.get().map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Refuse orders growing a position whose margin at `leverage` exceeds
+    /// the venue's available balance; only the first call takes effect
+    pub fn set_max_leverage(&self, leverage: f64) {
+        let _ = self.max_leverage.set(leverage);
+    }
+
+    /// Venue balances, kept current by the balance monitor
+    pub fn balances(&self) -> &Balances {
+        &self.balances
+    }
+
+    /// `amount` in `symbol`'s quote currency, in the reporting currency
+    fn to_reporting(&self, positions: &PositionTracker, symbol: &str, amount: f64) -> f64 {
+        match self.fx.get() {
+            Some(fx) => fx.convert(symbol, amount, positions),
+            None => amount,
+        }
+    }
+
+    fn publish(&self, event: EngineEvent) {
+        if let Some(events) = &self.events {
+            events.publish(event);
+        }
+    }
+
+    pub fn kill_switch(&self) -> &KillSwitch {
+        &self.kill_switch
+    }
+
+    /// Operator pauses, shared with the strategy runner and admin API
+    pub fn toggles(&self) -> Arc<TradingToggles> {
+        Arc::clone(&self.toggles)
+    }
+
+    /// Engine trading mode, shared with the strategy runner, order gateway
+    /// and admin API
+    pub fn trading_state(&self) -> TradingState {
+        self.trading.clone()
+    }
+
+    /// Whether the trading mode allows `order`; in reduce-only mode it must
+    /// shrink the net position on its venue
+    pub async fn check_mode(&self, order: &Order) -> Result<(), HftError> {
+        let mode = self.trading.mode();
+        let increases = match mode {
+            TradingMode::ReduceOnly => self.grows_position(order, false).await,
+            _ => true,
+        };
+        if mode.permits(increases) {
+            return Ok(());
+        }
+        Err(ExecutionError::TradingHalted(match mode {
+            TradingMode::ReduceOnly => format!("reduce-only, order would grow the {} position", order.symbol),
+            _ => format!("trading mode is {}", mode),
+        }).into())
+    }
+
+    /// Whether `order` would grow the net position on its venue, or flip
+    /// it; with `own_strategy` only the position of the order's strategy
+    /// counts
+    async fn grows_position(&self, order: &Order, own_strategy: bool) -> bool {
+        let strategy = order.strategy.as_deref().unwrap_or_default();
+        let position: f64 = self.positions.read().await
+            .positions()
+            .filter(|(key, _)| key.venue == order.venue && key.symbol == order.symbol)
+            .filter(|(key, _)| !own_strategy || key.strategy == strategy)
+            .map(|(_, position)| position.quantity)
+            .sum();
+        let after = position + order.side.sign() * order.quantity;
+        after.abs() > position.abs() + f64::EPSILON || after * position < 0.0
+    }
+
+    /// The fees each venue charges at its current tier
+    pub fn fees(&self) -> &FeeModel {
+        &self.fees
+    }
+
+    pub fn loss_limits(&self) -> &LossLimits {
+        &self.loss_limits
+    }
+
+    pub async fn on_fill(&self, fill: &Fill) {
+        let strategy = if fill.strategy.is_empty() { "none" } else { fill.strategy.as_str() };
+        ORDER_FILLS.with_label_values(&[&fill.venue, strategy]).inc();
+        FILLED_QUANTITY.with_label_values(&[&fill.venue, strategy]).inc_by(fill.quantity);
+        if let Some(audit) = self.audit.get() {
+            audit.record(AuditEvent::Fill(fill.clone()));
+        }
+        for sink in self.drop_copy.get().into_iter().flatten() {
+            sink.send(OrderEvent::Fill(fill.clone()));
+        }
+        self.fees.on_fill(fill);
+        let mut positions = self.positions.write().await;
+        let commission = positions.apply_fill(fill);
+        if let Some(charged) = &fill.commission {
+            COMMISSIONS.with_label_values(&[&fill.venue, &charged.asset]).add(charged.amount);
+            if commission.is_none() {
+                warn!(venue = %fill.venue, symbol = %fill.symbol, asset = %charged.asset, "Commission asset has no price against the quote currency; left out of PnL");
+            }
+        }
+        self.activity.record_fill(fill, commission);
+        self.refresh_greeks(&positions);
+    }
+
+    /// Volume traded and order flow today and yesterday, for reports
+    pub fn activity(&self) -> &ActivityTracker {
+        &self.activity
+    }
+
+    /// `amount` in `symbol`'s quote currency, in the reporting currency
+    pub async fn in_reporting_currency(&self, symbol: &str, amount: f64) -> f64 {
+        self.to_reporting(&*self.positions.read().await, symbol, amount)
+    }
+
+    /// Each position with its PnL in the reporting currency, since it was
+    /// opened
+    pub async fn position_pnl(&self) -> Vec<(PositionKey, Position, f64)> {
+        let positions = self.positions.read().await;
+        positions.positions()
+            .map(|(key, position)| {
+                let pnl = self.to_reporting(&positions, &key.symbol, positions.position_pnl(key, position));
+                (key.clone(), position.clone(), pnl)
+            })
+            .collect()
+    }
+
+    /// Mark positions to the quote mid
+    pub async fn on_quote(&self, quote: &Quote) {
+        if quote.bid > 0.0 && quote.ask > 0.0 {
+            let mut positions = self.positions.write().await;
+            positions.mark(&quote.symbol, (quote.bid + quote.ask) / 2.0);
+            if self.portfolio.read().unwrap().1.contains(quote.symbol.as_str()) {
+                self.refresh_greeks(&positions);
+            }
+        }
+    }
+
+    pub async fn positions(&self) -> Vec<(PositionKey, Position)> {
+        self.positions.read().await
+            .positions()
+            .map(|(k, p)| (k.clone(), p.clone()))
+            .collect()
+    }
+
+    /// Replace tracked positions, e.g. from a snapshot
+    pub async fn restore_positions(&self, positions: Vec<(PositionKey, Position)>) {
+        let mut tracker = self.positions.write().await;
+        for (key, position) in positions {
+            tracker.restore(key, position);
+        }
+    }
+
+    /// Pre-trade check run before an order is handed to the order gateway.
+    ///
+    /// Orders marked to bypass the kill switch skip every check, but may
+    /// only shrink their strategy's position on their venue.
+    pub async fn check_order(&self, order: &Order) -> Result<(), HftError> {
+        if order.bypass_kill_switch {
+            if self.grows_position(order, true).await {
+                return Err(ExecutionError::TradingHalted(format!("kill switch bypass, order would grow the {} position", order.symbol)).into());
+            }
+            return Ok(());
+        }
+
+        if let Some(reason) = self.kill_switch.reason().await {
+            return Err(ExecutionError::TradingHalted(reason).into());
+        }
+
+        self.check_mode(order).await?;
+
+        if !self.toggles.is_symbol_enabled(&order.symbol) {
+            return Err(ExecutionError::TradingHalted(format!("{} is disabled", order.symbol)).into());
+        }
+
+        if let Some(contract) = self.expiries.get().and_then(|expiries| {
+            let closing = expiries.phase(&order.symbol, Utc::now()) != ContractPhase::Open;
+            expiries.contract(&order.symbol).filter(|_| closing)
+        }) {
+            if self.grows_position(order, false).await {
+                return Err(ExecutionError::TradingHalted(format!("{} expires at {}, positions may only be reduced", order.symbol, contract.expiry)).into());
+            }
+        }
+
+        if let Some(deviation) = self.sanity.get().and_then(|sanity| sanity.check(order)) {
+            warn!(venue = %order.venue, symbol = %order.symbol, deviation = %deviation, "Order held by price sanity check");
+            self.publish(EngineEvent::PriceDeviation {
+                venue: deviation.venue.clone(),
+                symbol: deviation.symbol.clone(),
+                detail: deviation.to_string(),
+            });
+            return Err(ExecutionError::PriceDeviation(deviation.to_string()).into());
+        }
+
+        if !self.exposure_limits.is_empty() {
+            // Assume the order fills in full and check the resulting exposure
+            let positions = self.positions.read().await;
+            let mut quantities = positions.symbol_quantities();
+            *quantities.entry(order.symbol.to_string()).or_insert(0.0) += order.side.sign() * order.quantity;
+
+            let notionals = self.notionals(&positions, &quantities, Some(order));
+            drop(positions);
+
+            let exposures = self.exposure_limits.aggregate(&notionals);
+            if let Some(breach) = self.exposure_limits.check(&exposures) {
+                warn!(symbol = %order.symbol, breach = %breach, "Order rejected by exposure limits");
+                self.publish(EngineEvent::RiskBreach {
+                    scope: breach.scope.to_string(),
+                    detail: breach.to_string(),
+                });
+                return Err(ExecutionError::RiskLimitExceeded(breach.to_string()).into());
+            }
+        }
+
+        if let Some(&leverage) = self.max_leverage.get() {
+            if let Some(detail) = self.margin_shortfall(order, leverage).await {
+                warn!(venue = %order.venue, symbol = %order.symbol, detail = %detail, "Order rejected for lack of capital");
+                self.publish(EngineEvent::RiskBreach {
+                    scope: format!("capital:{}", order.venue),
+                    detail: detail.clone(),
+                });
+                return Err(ExecutionError::RiskLimitExceeded(detail).into());
+            }
+        }
+
+        if let Some(limits) = self.greek_limits.get().filter(|limits| !limits.is_empty()) {
+            let positions = self.positions.read().await;
+            let mut quantities = positions.symbol_quantities();
+            *quantities.entry(order.symbol.to_string()).or_insert(0.0) += order.side.sign() * order.quantity;
+
+            let (greeks, _) = self.aggregate_greeks(&positions, &quantities, Some(order));
+            drop(positions);
+
+            if let Some(breach) = limits.check(&greeks) {
+                warn!(symbol = %order.symbol, breach = %breach, "Order rejected by greek limits");
+                self.publish(EngineEvent::RiskBreach {
+                    scope: breach.scope(),
+                    detail: breach.to_string(),
+                });
+                return Err(ExecutionError::RiskLimitExceeded(breach.to_string()).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Why `order` needs more margin at `leverage` than its venue has
+    /// available, if it grows a position. Balances in assets other than the
+    /// quote currency count at the mark of their pair with it; orders on
+    /// venues not polled yet pass.
+    async fn margin_shortfall(&self, order: &Order, leverage: f64) -> Option<String> {
+        let balances = self.balances.venue(&order.venue)?;
+        let (_, quote) = split_symbol(&order.symbol)?;
+        if !self.grows_position(order, false).await {
+            return None;
+        }
+        let positions = self.positions.read().await;
+        let price = positions.mark_price(&order.symbol).unwrap_or(order.price);
+        let margin = order.quantity * price / leverage;
+        let available: f64 = balances.iter()
+            .filter_map(|balance| positions.value_in(&balance.asset, balance.available, quote))
+            .sum();
+        (margin > available).then(|| format!(
+            "margin {:.2} {} at {}x leverage exceeds {:.2} available on {}",
+            margin, quote, leverage, available, order.venue,
+        ))
+    }
+
+    /// Signed notional per symbol in the reporting currency, valuing each
+    /// symbol at its mark price. The order price stands in for symbols that
+    /// have not been marked yet.
+    fn notionals(
+        &self,
+        positions: &PositionTracker,
+        quantities: &HashMap<String, f64>,
+        order: Option<&Order>,
+    ) -> HashMap<String, f64> {
+        quantities
+            .iter()
+            .map(|(symbol, &quantity)| {
+                let price = positions.mark_price(symbol)
+                    .or_else(|| order.filter(|o| &o.symbol == symbol && o.price > 0.0).map(|o| o.price))
+                    .unwrap_or(0.0);
+                (symbol.clone(), self.to_reporting(positions, symbol, quantity * price))
+            })
+            .collect()
+    }
+
+    /// Greeks of `quantities` by underlying, with the symbols whose prices
+    /// they depend on. Options count once their greeks can be priced, other
+    /// symbols as delta one in their base asset.
+    fn aggregate_greeks(
+        &self,
+        positions: &PositionTracker,
+        quantities: &HashMap<String, f64>,
+        order: Option<&Order>,
+    ) -> (PortfolioGreeks, HashSet<String>) {
+        let mut portfolio = PortfolioGreeks {
+            currency: self.fx.get().map(|fx| fx.reporting_currency.clone()),
+            ..Default::default()
+        };
+        let mut symbols = HashSet::new();
+        for (symbol, &quantity) in quantities.iter().filter(|(_, quantity)| **quantity != 0.0) {
+            symbols.insert(symbol.clone());
+            let (underlying, greeks) = if OptionInstrument::parse(symbol).is_some() {
+                let Some(feed) = &self.greeks else {
+                    continue;
+                };
+                let Some(underlying_symbol) = feed.underlying_symbol(symbol) else {
+                    continue;
+                };
+                symbols.insert(underlying_symbol.clone());
+                let (Some(option), Some(instrument)) = (feed.greeks(symbol), feed.instrument(symbol)) else {
+                    continue;
+                };
+                let delta = quantity * option.greeks.delta;
+                (instrument.underlying.to_uppercase(), UnderlyingGreeks {
+                    delta,
+                    delta_notional: self.to_reporting(positions, &underlying_symbol, delta * option.underlying_price),
+                    gamma: quantity * option.greeks.gamma,
+                    vega: quantity * option.greeks.vega,
+                    theta: quantity * option.greeks.theta,
+                })
+            } else {
+                let price = positions.mark_price(symbol)
+                    .or_else(|| order.filter(|o| &o.symbol == symbol && o.price > 0.0).map(|o| o.price))
+                    .unwrap_or(0.0);
+                (self.exposure_limits.asset_for(symbol), UnderlyingGreeks {
+                    delta: quantity,
+                    delta_notional: self.to_reporting(positions, symbol, quantity * price),
+                    ..Default::default()
+                })
+            };
+            *portfolio.underlyings.entry(underlying).or_default() += greeks;
+        }
+        portfolio.beta_weighted_delta = portfolio.underlyings.iter()
+            .map(|(underlying, greeks)| {
+                let beta = self.greek_limits.get().map(|limits| limits.beta(underlying)).unwrap_or(1.0);
+                greeks.delta_notional * beta
+            })
+            .sum();
+        (portfolio, symbols)
+    }
+
+    /// Recompute portfolio greeks from current positions and publish them
+    /// as gauges
+    fn refresh_greeks(&self, positions: &PositionTracker) {
+        let (greeks, symbols) = self.aggregate_greeks(positions, &positions.symbol_quantities(), None);
+        let mut portfolio = self.portfolio.write().unwrap();
+        for underlying in portfolio.0.underlyings.keys().filter(|u| !greeks.underlyings.contains_key(*u)) {
+            for greek in ["delta", "gamma", "vega", "theta"] {
+                PORTFOLIO_GREEKS.with_label_values(&[underlying, greek]).set(0.0);
+            }
+        }
+        for (underlying, g) in &greeks.underlyings {
+            PORTFOLIO_GREEKS.with_label_values(&[underlying, "delta"]).set(g.delta);
+            PORTFOLIO_GREEKS.with_label_values(&[underlying, "gamma"]).set(g.gamma);
+            PORTFOLIO_GREEKS.with_label_values(&[underlying, "vega"]).set(g.vega);
+            PORTFOLIO_GREEKS.with_label_values(&[underlying, "theta"]).set(g.theta);
+        }
+        *portfolio = (greeks, symbols);
+    }
+
+    /// Delta, gamma and vega per underlying and beta-weighted delta, as of
+    /// the latest fill or mark of a symbol they depend on
+    pub fn portfolio_greeks(&self) -> PortfolioGreeks {
+        self.portfolio.read().unwrap().0.clone()
+    }
+
+    /// Hypothetical PnL of current positions under each of `scenarios`, in
+    /// the reporting currency. Options are repriced with Black-Scholes at
+    /// the shocked underlying price and implied volatility, and options
+    /// whose greeks cannot be priced yet are left out; other symbols move
+    /// with their base asset's price.
+    pub async fn stress_test(&self, scenarios: &[Scenario]) -> StressReport {
+        let positions = self.positions.read().await;
+        let quantities = positions.symbol_quantities();
+        let results = scenarios.iter()
+            .map(|scenario| {
+                let mut underlyings = BTreeMap::new();
+                for (symbol, &quantity) in quantities.iter().filter(|(_, quantity)| **quantity != 0.0) {
+                    let (underlying, pnl) = if OptionInstrument::parse(symbol).is_some() {
+                        let Some(feed) = &self.greeks else {
+                            continue;
+                        };
+                        let (Some((inputs, iv)), Some(underlying_symbol), Some(instrument)) =
+                            (feed.pricing(symbol), feed.underlying_symbol(symbol), feed.instrument(symbol)) else {
+                            continue;
+                        };
+                        let underlying = instrument.underlying.to_uppercase();
+                        let shock = scenario.shock(&underlying).cloned().unwrap_or_default();
+                        let shocked = PricingInputs { underlying: inputs.underlying * (1.0 + shock.price), ..inputs };
+                        let change = shocked.price(iv * (1.0 + shock.vol)) - inputs.price(iv);
+                        (underlying, self.to_reporting(&positions, &underlying_symbol, quantity * change))
+                    } else {
+                        let underlying = self.exposure_limits.asset_for(symbol);
+                        let shock = scenario.shock(&underlying).map_or(0.0, |shock| shock.price);
+                        let mark = positions.mark_price(symbol).unwrap_or(0.0);
+                        (underlying, self.to_reporting(&positions, symbol, quantity * mark * shock))
+                    };
+                    *underlyings.entry(underlying).or_insert(0.0) += pnl;
+                }
+                StressResult { scenario: scenario.name.clone(), pnl: underlyings.values().sum(), underlyings }
+            })
+            .collect();
+        StressReport { currency: self.fx.get().map(|fx| fx.reporting_currency.clone()), results }
+    }
+
+    /// Current asset and group exposures
+    pub async fn exposures(&self) -> HashMap<ExposureScope, Exposure> {
+        let positions = self.positions.read().await;
+        let notionals = self.notionals(&positions, &positions.symbol_quantities(), None);
+        self.exposure_limits.aggregate(&notionals)
+    }
+
+    /// Exposure and PnL of every open position by symbol, venue and
+    /// strategy, in the reporting currency
+    pub async fn heat_map(&self) -> HeatMap {
+        let positions = self.positions.read().await;
+        let cells = positions.positions()
+            .filter(|(_, position)| !position.is_flat())
+            .map(|(key, position)| {
+                let mark = positions.mark_price(&key.symbol).unwrap_or(position.avg_price);
+                let delta = self.to_reporting(&positions, &key.symbol, position.quantity * mark);
+                HeatMapCell {
+                    symbol: key.symbol.clone(),
+                    venue: key.venue.clone(),
+                    strategy: key.strategy.clone(),
+                    quantity: position.quantity,
+                    notional: delta.abs(),
+                    delta,
+                    pnl: self.to_reporting(&positions, &key.symbol, positions.position_pnl(key, position)),
+                }
+            })
+            .collect();
+        HeatMap::new(self.fx.get().map(|fx| fx.reporting_currency.clone()), cells)
+    }
+
+    /// Signed net quantity per base asset, across strategies and venues
+    pub async fn net_by_asset(&self) -> HashMap<String, f64> {
+        let mut nets = HashMap::new();
+        for (symbol, quantity) in self.positions.read().await.symbol_quantities() {
+            *nets.entry(self.exposure_limits.asset_for(&symbol)).or_insert(0.0) += quantity;
+        }
+        nets
+    }
+
+    /// Publish current exposures as gauges
+    pub async fn update_exposure_gauges(&self) {
+        for (scope, exposure) in self.exposures().await {
+            let (kind, name) = match &scope {
+                ExposureScope::Asset(name) => ("asset", name),
+                ExposureScope::Group(name) => ("group", name),
+            };
+            EXPOSURE_NOTIONAL.with_label_values(&[kind, name, "gross"]).set(exposure.gross);
+            EXPOSURE_NOTIONAL.with_label_values(&[kind, name, "net"]).set(exposure.net);
+        }
+    }
+
+    /// Publish the FX rates used for conversion as gauges
+    pub async fn update_fx_gauges(&self) {
+        let Some(fx) = self.fx.get() else {
+            return;
+        };
+        let positions = self.positions.read().await;
+        for currency in fx.currencies() {
+            if let Some(rate) = fx.rate(&currency, &positions) {
+                FX_RATES.with_label_values(&[&currency, &fx.reporting_currency]).set(rate);
+            }
+        }
+    }
+
+    /// Portfolio and per-strategy PnL in the reporting currency
+    fn pnl(&self, positions: &PositionTracker) -> (f64, HashMap<String, f64>) {
+        let mut total = 0.0;
+        let mut strategies = HashMap::new();
+        for (key, position) in positions.positions() {
+            let pnl = self.to_reporting(positions, &key.symbol, positions.position_pnl(key, position));
+            total += pnl;
+            *strategies.entry(key.strategy.clone()).or_insert(0.0) += pnl;
+        }
+        (total, strategies)
+    }
+
+    /// Portfolio and per-strategy PnL since the start of the trading day
+    pub async fn daily_pnl(&self) -> (f64, HashMap<String, f64>) {
+        self.daily_pnl_on(Utc::now().date_naive()).await
+    }
+
+    async fn daily_pnl_on(&self, today: NaiveDate) -> (f64, HashMap<String, f64>) {
+        let positions = self.positions.read().await;
+        let (total, mut strategies) = self.pnl(&positions);
+        drop(positions);
+
+        let mut baseline = self.baseline.write().await;
+        if baseline.date != today {
+            // New trading day: losses are measured from here on
+            info!(date = %today, "Rolling daily PnL baseline");
+            baseline.date = today;
+            baseline.total = total;
+            baseline.strategies = strategies.clone();
+        }
+
+        for (strategy, pnl) in strategies.iter_mut() {
+            *pnl -= baseline.strategies.get(strategy).copied().unwrap_or(0.0);
+        }
+
+        (total - baseline.total, strategies)
+    }
+
+    /// Compare daily PnL against the configured loss limits
+    pub async fn check_loss_limits(&self) -> Option<LossBreach> {
+        let (total, strategies) = self.daily_pnl().await;
+
+        for (strategy, pnl) in &strategies {
+            STRATEGY_PNL.with_label_values(&[strategy]).set(*pnl);
+        }
+
+        self.loss_limits.check(total, &strategies)
+    }
+
+    /// Check loss limits and halt trading on a breach, cancelling open
+    /// orders and, if configured, flattening through the order gateway.
+    ///
+    /// Once engaged, the kill switch is only released after the cool-down has
+    /// elapsed and daily PnL is back inside every limit, which in practice means
+    /// the next trading day or a limit change. Halts engaged by operators are
+    /// left for them to release.
+    pub async fn enforce_loss_limits(&self) -> Option<LossBreach> {
+        let breach = self.check_loss_limits().await;
+
+        if let Some(elapsed) = self.kill_switch.engaged_for().await {
+            let owned = self.kill_switch.owner().await == Some(HaltOwner::LossLimits);
+            if owned && breach.is_none() && elapsed >= self.loss_limits.cooldown {
+                self.kill_switch.release().await;
+            }
+            return None;
+        }
+
+        let breach = breach?;
+
+        error!(breach = %breach, "Daily loss limit breached");
+        LOSS_LIMIT_BREACHES.with_label_values(&[&breach.scope.to_string()]).inc();
+        self.publish(EngineEvent::RiskBreach {
+            scope: breach.scope.to_string(),
+            detail: breach.to_string(),
+        });
+
+        self.kill_switch.engage_as(HaltOwner::LossLimits, &breach.to_string()).await;
+        self.cancel_all("loss_limit", &breach.to_string()).await;
+
+        if self.loss_limits.flatten_on_breach {
+            self.flatten(&breach.scope).await;
+        }
+
+        Some(breach)
+    }
+
+    /// Have the order gateway cancel every open order on every venue
+    pub async fn cancel_all(&self, kind: &'static str, reason: &str) {
+        let Some(cancel_tx) = &self.cancel_tx else {
+            warn!(reason = %reason, "No order gateway attached, open orders left in place");
+            return;
+        };
+        if cancel_tx.send(CancelRequest::all(None, kind, reason)).await.is_err() {
+            error!(reason = %reason, "Order gateway stopped, open orders left in place");
+        }
+    }
+
+    /// Market orders that would flatten every position within `scope`,
+    /// netted per strategy, venue and symbol so each fill closes its own
+    /// strategy's position
+    pub async fn flatten_orders(&self, scope: &LossScope) -> Vec<Order> {
+        let positions = self.positions.read().await;
+        let mut net: HashMap<(String, String, String), f64> = HashMap::new();
+
+        for (key, position) in positions.positions() {
+            let in_scope = match scope {
+                LossScope::Portfolio => true,
+                LossScope::Strategy(name) => &key.strategy == name,
+            };
+            if in_scope && !position.is_flat() {
+                *net.entry((key.strategy.clone(), key.venue.clone(), key.symbol.clone())).or_insert(0.0) += position.quantity;
+            }
+        }
+
+        let mut orders: Vec<Order> = net
+            .into_iter()
+            .filter(|(_, quantity)| quantity.abs() > f64::EPSILON)
+            .map(|((strategy, venue, symbol), quantity)| Order {
+                symbol: symbol.into(),
+                side: if quantity > 0.0 { OrderSide::Sell } else { OrderSide::Buy },
+                quantity: quantity.abs(),
+                price: 0.0,
+                venue: venue.into(),
+                order_type: OrderType::Market,
+                expire_after: None,
+                // Fills of orders without a strategy are booked under none
+                strategy: Some(strategy).filter(|s| !s.is_empty()),
+                bypass_kill_switch: true,
+            })
+            .collect();
+        orders.sort_by(|a, b| (&a.strategy, &a.venue, &a.symbol).cmp(&(&b.strategy, &b.venue, &b.symbol)));
+        orders
+    }
+
+    /// Send the [`flatten_orders`](Self::flatten_orders) for `scope`
+    /// through the order gateway, past the kill switch, returning them
+    pub async fn flatten(&self, scope: &LossScope) -> Vec<Order> {
+        let orders = self.flatten_orders(scope).await;
+        let Some(order_tx) = &self.order_tx else {
+            if !orders.is_empty() {
+                warn!(scope = %scope, "No order gateway attached, positions left open");
+            }
+            return orders;
+        };
+        for order in &orders {
+            info!(venue = %order.venue, symbol = %order.symbol, side = ?order.side, quantity = %order.quantity, "Flattening position");
+            if order_tx.send(order.clone()).await.is_err() {
+                error!(venue = %order.venue, symbol = %order.symbol, "Order gateway stopped, position left open");
+            }
+        }
+        orders
+    }
+
+    /// Periodically publish exposures and enforce loss limits until the task
+    /// is dropped
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            self.update_exposure_gauges().await;
+            self.update_fx_gauges().await;
+            self.accrue_financing().await;
+            self.enforce_loss_limits().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gateways::order::CancelTarget;
+
+    fn fill(strategy: &str, side: OrderSide, quantity: f64, price: f64) -> Fill {
+        Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "MOCK".into(),
+            strategy: strategy.to_string(),
+            side,
+            quantity,
+            price,
+            timestamp: 0,
+            commission: None,
+        }
+    }
+
+    fn quote(mid: f64) -> Quote {
+        Quote {
+            symbol: "BTCUSDT".into(),
+            bid: mid - 0.5,
+            ask: mid + 0.5,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "MOCK".into(),
+            timestamp: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_breach_halts_cancels_and_flattens() {
+        let (order_tx, mut order_rx) = mpsc::channel(8);
+        let (cancel_tx, mut cancel_rx) = mpsc::channel(8);
+        let risk = RiskManager::new(LossLimits {
+            daily_loss_limit: Some(100.0),
+            flatten_on_breach: true,
+            ..Default::default()
+        }).with_gateway(order_tx, cancel_tx);
+
+        risk.on_fill(&fill("mm", OrderSide::Buy, 2.0, 50000.0)).await;
+        risk.on_quote(&quote(49990.0)).await;
+        assert!(risk.enforce_loss_limits().await.is_none());
+
+        // 2 * -100 = -200 unrealized
+        risk.on_quote(&quote(49900.0)).await;
+        let breach = risk.enforce_loss_limits().await.unwrap();
+        assert_eq!(breach.scope, LossScope::Portfolio);
+        assert!(risk.kill_switch().is_engaged().await);
+
+        let cancel = cancel_rx.try_recv().unwrap();
+        assert_eq!((cancel.target, cancel.kind), (CancelTarget::All(None), "loss_limit"));
+
+        let order = order_rx.try_recv().unwrap();
+        assert!(order_rx.try_recv().is_err());
+        assert_eq!((order.side, order.quantity, order.bypass_kill_switch), (OrderSide::Sell, 2.0, true));
+
+        // The flattening order passes the engaged kill switch, but the same
+        // order without the bypass, or one growing the position, does not
+        assert!(risk.check_order(&order).await.is_ok());
+        let result = risk.check_order(&Order { bypass_kill_switch: false, ..order.clone() }).await;
+        assert!(matches!(result, Err(HftError::Execution(ExecutionError::TradingHalted(_)))));
+        let result = risk.check_order(&Order { side: OrderSide::Buy, ..order }).await;
+        assert!(matches!(result, Err(HftError::Execution(ExecutionError::TradingHalted(_)))));
+    }
+
+    #[tokio::test]
+    async fn test_strategy_breach_flattens_only_that_strategy() {
+        let risk = RiskManager::new(LossLimits {
+            strategy_loss_limits: HashMap::from([("losing".to_string(), 50.0)]),
+            flatten_on_breach: true,
+            ..Default::default()
+        });
+
+        risk.on_fill(&fill("losing", OrderSide::Sell, 1.0, 50000.0)).await;
+        risk.on_fill(&fill("other", OrderSide::Buy, 3.0, 50000.0)).await;
+        risk.on_quote(&quote(50100.0)).await;
+
+        let breach = risk.check_loss_limits().await.unwrap();
+        assert_eq!(breach.scope, LossScope::Strategy("losing".to_string()));
+
+        let orders = risk.flatten_orders(&breach.scope).await;
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].side, OrderSide::Buy);
+        assert_eq!(orders[0].quantity, 1.0);
+        assert_eq!(orders[0].strategy.as_deref(), Some("losing"));
+
+        // The buy shrinks the losing short even though the venue is net
+        // long, and its fill books against the losing strategy
+        risk.kill_switch().engage("operator").await;
+        assert!(risk.check_order(&orders[0]).await.is_ok());
+        risk.on_fill(&fill("losing", OrderSide::Buy, 1.0, 50100.0)).await;
+        assert!(risk.flatten_orders(&breach.scope).await.is_empty());
+        assert_eq!(risk.flatten_orders(&LossScope::Portfolio).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_kill_switch_released_after_cooldown_once_within_limits() {
+        let risk = RiskManager::new(LossLimits {
+            daily_loss_limit: Some(100.0),
+            cooldown: Duration::from_millis(0),
+            ..Default::default()
+        });
+
+        risk.on_fill(&fill("mm", OrderSide::Buy, 1.0, 50000.0)).await;
+        risk.on_quote(&quote(49800.0)).await;
+        assert!(risk.enforce_loss_limits().await.is_some());
+
+        // Still in breach, so the switch stays engaged
+        risk.enforce_loss_limits().await;
+        assert!(risk.kill_switch().is_engaged().await);
+
+        // Price recovers inside the limit
+        risk.on_quote(&quote(49950.0)).await;
+        risk.enforce_loss_limits().await;
+        assert!(!risk.kill_switch().is_engaged().await);
+
+        // Operator halts are never lifted by the loss limits
+        risk.kill_switch().engage("operator").await;
+        risk.enforce_loss_limits().await;
+        assert!(risk.kill_switch().is_engaged().await);
+
+        // Nor are loss limit halts an operator took over
+        risk.kill_switch().release().await;
+        risk.on_quote(&quote(49800.0)).await;
+        assert!(risk.enforce_loss_limits().await.is_some());
+        risk.kill_switch().engage("operator").await;
+        risk.on_quote(&quote(49950.0)).await;
+        risk.enforce_loss_limits().await;
+        assert_eq!(risk.kill_switch().owner().await, Some(HaltOwner::Operator));
+        assert_eq!(risk.kill_switch().reason().await.as_deref(), Some("operator"));
+    }
+
+    #[tokio::test]
+    async fn test_reduce_only_mode_refuses_growing_orders() {
+        let risk = RiskManager::new(LossLimits::default());
+        risk.on_fill(&fill("mm", OrderSide::Buy, 2.0, 50000.0)).await;
+        let order = |side, quantity| Order {
+            symbol: "BTCUSDT".into(),
+            side,
+            quantity,
+            price: 50000.0,
+            venue: "MOCK".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        };
+        // Nothing goes out before the engine is active
+        assert!(risk.check_order(&order(OrderSide::Sell, 1.0)).await.is_err());
+
+        risk.trading_state().transition(TradingMode::ReduceOnly, "test").unwrap_err();
+        risk.trading_state().transition(TradingMode::Active, "test").unwrap();
+        risk.trading_state().transition(TradingMode::ReduceOnly, "test").unwrap();
+        assert!(risk.check_order(&order(OrderSide::Sell, 1.0)).await.is_ok());
+        assert!(risk.check_order(&order(OrderSide::Sell, 2.0)).await.is_ok());
+        assert!(risk.check_order(&order(OrderSide::Buy, 0.1)).await.is_err());
+        assert!(risk.check_order(&order(OrderSide::Sell, 3.0)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exposure_limit_rejects_order() {
+        let risk = RiskManager::new(LossLimits::default())
+            .with_exposure_limits(ExposureLimits {
+                assets: HashMap::from([("BTC".to_string(), ExposureLimit {
+                    max_gross: None,
+                    max_net: Some(150_000.0),
+                })]),
+                ..Default::default()
+            });
+        risk.trading_state().transition(TradingMode::Active, "test").unwrap();
+
+        risk.on_fill(&fill("mm", OrderSide::Buy, 2.0, 50000.0)).await;
+        risk.on_quote(&quote(50000.0)).await;
+
+        let order = Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity: 2.0,
+            price: 50000.0,
+            venue: "MOCK".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        };
+        let result = risk.check_order(&order).await;
+        assert!(matches!(result, Err(HftError::Execution(ExecutionError::RiskLimitExceeded(_)))));
+
+        // Reducing the position is always inside the net limit
+        let order = Order { side: OrderSide::Sell, ..order };
+        assert!(risk.check_order(&order).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_daily_baseline_rolls() {
+        let risk = RiskManager::new(LossLimits::default());
+        risk.on_fill(&fill("mm", OrderSide::Buy, 1.0, 50000.0)).await;
+        risk.on_quote(&quote(49900.0)).await;
+
+        let today = Utc::now().date_naive();
+        let (total, _) = risk.daily_pnl_on(today).await;
+        assert_eq!(total, -100.0);
+
+        // Yesterday's losses no longer count against today's limit
+        let tomorrow = today.succ_opt().unwrap();
+        let (total, strategies) = risk.daily_pnl_on(tomorrow).await;
+        assert_eq!(total, 0.0);
+        assert_eq!(strategies.get("mm"), Some(&0.0));
+    }
+
+    #[tokio::test]
+    async fn test_pnl_aggregated_in_reporting_currency() {
+        let risk = RiskManager::new(LossLimits::default());
+        risk.set_fx_conversion(FxConversion::new("USDT").with_pair("EURUSDT", "EUR", "USDT"));
+
+        risk.on_fill(&fill("mm", OrderSide::Buy, 1.0, 50000.0)).await;
+        risk.on_fill(&Fill { symbol: "BTCEUR".into(), ..fill("mm", OrderSide::Buy, 1.0, 40000.0) }).await;
+        risk.on_quote(&quote(49900.0)).await;
+        risk.on_quote(&Quote { symbol: "BTCEUR".into(), ..quote(39900.0) }).await;
+        risk.on_quote(&Quote { symbol: "EURUSDT".into(), ..quote(1.5) }).await;
+
+        // -100 USDT plus -100 EUR at 1.5
+        let (total, strategies) = risk.daily_pnl_on(Utc::now().date_naive()).await;
+        assert_eq!(total, -250.0);
+        assert_eq!(strategies.get("mm"), Some(&-250.0));
+    }
+}
@@ -0,0 +1,292 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{error, info, warn};
+
+use crate::events::{EngineEvent, EventBus};
+use crate::metrics::OUTAGE_ORDERS;
+use crate::risk::{LossScope, RiskManager};
+use crate::types::Order;
+
+const DEFAULT_GRACE: Duration = Duration::from_secs(30);
+
+/// How often outages are checked against the grace period
+const OUTAGE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What to do about positions on a venue that stays disconnected
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutageConfig {
+    /// How long a venue may be down while positions are held there
+    pub grace: Duration,
+    /// Venues to offset stranded positions on, in order of preference;
+    /// without one the positions are flattened once the venue is back
+    pub hedge_venues: Vec<String>,
+}
+
+impl Default for OutageConfig {
+    fn default() -> Self {
+        Self { grace: DEFAULT_GRACE, hedge_venues: Vec::new() }
+    }
+}
+
+impl OutageConfig {
+    /// Comma separated `grace:SECS` and `hedge:VENUE;VENUE` entries, e.g.
+    /// `grace:30,hedge:KRAKEN;COINBASE`; unset ones keep their defaults
+    fn parse(spec: &str) -> Self {
+        let mut config = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once(':').map(|(key, value)| (key.trim(), value.trim())) {
+                Some(("grace", secs)) => match secs.parse::<f64>() {
+                    Ok(secs) if secs >= 0.0 => config.grace = Duration::from_secs_f64(secs),
+                    _ => warn!(entry = entry, "Ignoring malformed outage guard entry"),
+                },
+                Some(("hedge", venues)) => {
+                    config.hedge_venues = venues.split(';')
+                        .map(|v| v.trim().to_uppercase())
+                        .filter(|v| !v.is_empty())
+                        .collect();
+                }
+                _ => warn!(entry = entry, "Ignoring malformed outage guard entry"),
+            }
+        }
+        config
+    }
+
+    /// Read `HFT_OUTAGE_GUARD` (see [`parse`](Self::parse)); returns `None`
+    /// when the guard is off
+    pub fn from_env() -> Option<Self> {
+        Some(Self::parse(&std::env::var("HFT_OUTAGE_GUARD").ok()?))
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// When each disconnected venue went down
+    down: HashMap<String, Instant>,
+    /// Venues whose outage outlasted the grace period with positions held;
+    /// their positions are flattened when they are back
+    stranded: HashSet<String>,
+    /// Offsetting orders sent elsewhere per stranded venue, unwound once
+    /// it is back
+    hedges: HashMap<String, Vec<Order>>,
+}
+
+/// Protects positions on venues whose market data or order session stays
+/// down: past the grace period they are hedged on another venue, and once
+/// the venue reconnects they are flattened there and the hedges unwound
+pub struct OutageGuard {
+    config: OutageConfig,
+    risk: Arc<RiskManager>,
+    order_tx: mpsc::Sender<Order>,
+    events: EventBus,
+    state: Mutex<State>,
+}
+
+impl OutageGuard {
+    pub fn new(config: OutageConfig, risk: Arc<RiskManager>, order_tx: mpsc::Sender<Order>, events: EventBus) -> Self {
+        Self { config, risk, order_tx, events, state: Mutex::new(State::default()) }
+    }
+
+    fn alert(&self, venue: &str, action: &str, detail: String) {
+        self.events.publish(EngineEvent::VenueOutage {
+            venue: venue.to_string(),
+            action: action.to_string(),
+            detail,
+        });
+    }
+
+    fn send(&self, order: Order, action: &str) {
+        OUTAGE_ORDERS.with_label_values(&[&order.venue, action]).inc();
+        info!(venue = %order.venue, symbol = %order.symbol, side = ?order.side, quantity = order.quantity, action = action, "Sending outage order");
+        if let Err(e) = self.order_tx.try_send(order) {
+            error!(error = %e, action = action, "Failed to send outage order, position left open");
+        }
+    }
+
+    /// Orders that would flatten the positions held on `venue`
+    async fn stranded_positions(&self, venue: &str) -> Vec<Order> {
+        self.risk.flatten_orders(&LossScope::Portfolio).await
+            .into_iter()
+            .filter(|order| order.venue == venue)
+            .collect()
+    }
+
+    /// Follow a venue connectivity event
+    pub async fn on_event(&self, event: &EngineEvent) {
+        match event {
+            EngineEvent::VenueDisconnected { venue, .. } => {
+                self.state.lock().unwrap().down.entry(venue.clone()).or_insert_with(Instant::now);
+            }
+            EngineEvent::VenueConnected { venue } => {
+                let (stranded, hedges) = {
+                    let mut state = self.state.lock().unwrap();
+                    state.down.remove(venue);
+                    (state.stranded.remove(venue), state.hedges.remove(venue).unwrap_or_default())
+                };
+                if stranded {
+                    self.recover(venue, hedges).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Flatten what is still held on a reconnected venue and unwind the
+    /// hedges placed for it
+    async fn recover(&self, venue: &str, hedges: Vec<Order>) {
+        let flatten = self.stranded_positions(venue).await;
+        self.alert(venue, "flattening", format!(
+            "{} back; flattening {} positions and unwinding {} hedges",
+            venue, flatten.len(), hedges.len()
+        ));
+        for order in flatten {
+            self.send(order, "flatten");
+        }
+        for hedge in hedges {
+            let unwind = Order { side: hedge.side.opposite(), ..hedge };
+            self.send(unwind, "unwind");
+        }
+    }
+
+    /// Act on venues that have been down longer than the grace period while
+    /// positions are held there
+    pub async fn check(&self, now: Instant) {
+        let (overdue, down): (Vec<(String, Duration)>, HashSet<String>) = {
+            let state = self.state.lock().unwrap();
+            let overdue = state.down.iter()
+                .filter(|(venue, since)| !state.stranded.contains(*venue) && now.saturating_duration_since(**since) >= self.config.grace)
+                .map(|(venue, since)| (venue.clone(), now.saturating_duration_since(*since)))
+                .collect();
+            (overdue, state.down.keys().cloned().collect())
+        };
+
+        for (venue, elapsed) in overdue {
+            let positions = self.stranded_positions(&venue).await;
+            if positions.is_empty() {
+                continue;
+            }
+            self.state.lock().unwrap().stranded.insert(venue.clone());
+
+            let hedge_venue = self.config.hedge_venues.iter().find(|v| **v != venue && !down.contains(*v));
+            let Some(hedge_venue) = hedge_venue else {
+                self.alert(&venue, "awaiting_reconnect", format!(
+                    "{} down for {}s with {} positions and no hedge venue; flattening once it is back",
+                    venue, elapsed.as_secs(), positions.len()
+                ));
+                continue;
+            };
+
+            self.alert(&venue, "hedging", format!(
+                "{} down for {}s; hedging {} positions on {}",
+                venue, elapsed.as_secs(), positions.len(), hedge_venue
+            ));
+            let hedges: Vec<Order> = positions.into_iter()
+                .map(|order| Order { venue: hedge_venue.as_str().into(), ..order })
+                .collect();
+            for hedge in &hedges {
+                self.send(hedge.clone(), "hedge");
+            }
+            self.state.lock().unwrap().hedges.insert(venue, hedges);
+        }
+    }
+
+    /// Follow venue connectivity on the event bus, checking outages every
+    /// second
+    pub async fn run(self: Arc<Self>) {
+        let mut events = self.events.subscribe();
+        let mut interval = tokio::time::interval(OUTAGE_CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => self.on_event(&event).await,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(skipped = skipped, "Outage guard lagging the event bus");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = interval.tick() => self.check(Instant::now()).await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Fill, OrderSide, OrderType};
+
+    async fn long_on(risk: &RiskManager, venue: &str) {
+        risk.on_fill(&Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: venue.into(),
+            strategy: "mm".to_string(),
+            side: OrderSide::Buy,
+            quantity: 2.0,
+            price: 100.0,
+            timestamp: 0,
+            commission: None,
+        }).await;
+    }
+
+    fn disconnected(venue: &str) -> EngineEvent {
+        EngineEvent::VenueDisconnected { venue: venue.to_string(), reason: "test".to_string() }
+    }
+
+    #[tokio::test]
+    async fn test_hedges_then_flattens_on_reconnect() {
+        let config = OutageConfig::parse("grace:10, hedge:mock;backup, grace:x");
+        assert_eq!(config, OutageConfig { grace: Duration::from_secs(10), hedge_venues: vec!["MOCK".to_string(), "BACKUP".to_string()] });
+        let events = EventBus::new(16);
+        let mut alerts = events.subscribe();
+        let (order_tx, mut order_rx) = mpsc::channel(8);
+        let risk = Arc::new(RiskManager::new(crate::risk::LossLimits::default()));
+        long_on(&risk, "MOCK").await;
+        let guard = OutageGuard::new(config, Arc::clone(&risk), order_tx, events);
+
+        let start = Instant::now();
+        guard.on_event(&disconnected("MOCK")).await;
+        guard.check(start + Duration::from_secs(5)).await;
+        assert!(order_rx.try_recv().is_err());
+
+        // Past the grace period the position is offset on the backup venue,
+        // once
+        guard.check(start + Duration::from_secs(11)).await;
+        guard.check(start + Duration::from_secs(12)).await;
+        assert!(matches!(alerts.try_recv(), Ok(EngineEvent::VenueOutage { action, .. }) if action == "hedging"));
+        let hedge = order_rx.try_recv().unwrap();
+        assert_eq!((hedge.venue.as_str(), hedge.side, hedge.quantity, hedge.order_type), ("BACKUP", OrderSide::Sell, 2.0, OrderType::Market));
+        assert!(order_rx.try_recv().is_err());
+
+        guard.on_event(&EngineEvent::VenueConnected { venue: "MOCK".to_string() }).await;
+        assert!(matches!(alerts.try_recv(), Ok(EngineEvent::VenueOutage { action, .. }) if action == "flattening"));
+        let flatten = order_rx.try_recv().unwrap();
+        assert_eq!((flatten.venue.as_str(), flatten.side), ("MOCK", OrderSide::Sell));
+        let unwind = order_rx.try_recv().unwrap();
+        assert_eq!((unwind.venue.as_str(), unwind.side, unwind.quantity), ("BACKUP", OrderSide::Buy, 2.0));
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_reconnect_without_hedge_venue() {
+        let events = EventBus::new(16);
+        let mut alerts = events.subscribe();
+        let (order_tx, mut order_rx) = mpsc::channel(8);
+        let risk = Arc::new(RiskManager::new(crate::risk::LossLimits::default()));
+        long_on(&risk, "MOCK").await;
+        let guard = OutageGuard::new(OutageConfig::parse("grace:0"), risk, order_tx, events);
+
+        // Flat venues are left alone
+        guard.on_event(&disconnected("IDLE")).await;
+        guard.on_event(&disconnected("MOCK")).await;
+        guard.check(Instant::now()).await;
+        assert!(matches!(alerts.try_recv(), Ok(EngineEvent::VenueOutage { action, venue, .. }) if action == "awaiting_reconnect" && venue == "MOCK"));
+        assert!(alerts.try_recv().is_err());
+        assert!(order_rx.try_recv().is_err());
+
+        guard.on_event(&EngineEvent::VenueConnected { venue: "IDLE".to_string() }).await;
+        assert!(order_rx.try_recv().is_err());
+        guard.on_event(&EngineEvent::VenueConnected { venue: "MOCK".to_string() }).await;
+        assert_eq!(order_rx.try_recv().unwrap().side, OrderSide::Sell);
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Fill, OrderSide};
+use super::exposure::split_symbol;
+use super::financing::FinancingRates;
+
+/// Quantities below this are treated as flat
+const QUANTITY_EPSILON: f64 = 1e-12;
+
+/// Identifies a position held by a strategy on a venue
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PositionKey {
+    pub strategy: String,
+    pub venue: String,
+    pub symbol: String,
+}
+
+/// Net position with average entry price, realized PnL, financing and
+/// commissions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Position {
+    /// Signed quantity, positive when long
+    pub quantity: f64,
+    pub avg_price: f64,
+    pub realized_pnl: f64,
+    /// Funding and borrow PnL accrued while held, negative when paid
+    #[serde(default)]
+    pub financing: f64,
+    /// Commissions charged on its fills in the quote currency, negative
+    /// when rebated
+    #[serde(default)]
+    pub commission: f64,
+}
+
+impl Position {
+    pub fn apply_fill(&mut self, side: OrderSide, quantity: f64, price: f64) {
+        let signed = side.sign() * quantity;
+
+        if self.quantity.abs() < QUANTITY_EPSILON || self.quantity.signum() == signed.signum() {
+            // Opening or adding to the position
+            let new_quantity = self.quantity + signed;
+            self.avg_price = (self.avg_price * self.quantity.abs() + price * quantity) / new_quantity.abs();
+            self.quantity = new_quantity;
+            return;
+        }
+
+        // Reducing, closing or flipping the position
+        let closing = quantity.min(self.quantity.abs());
+        self.realized_pnl += closing * (price - self.avg_price) * self.quantity.signum();
+
+        let new_quantity = self.quantity + signed;
+        if new_quantity.abs() < QUANTITY_EPSILON {
+            self.quantity = 0.0;
+            self.avg_price = 0.0;
+        } else {
+            if new_quantity.signum() != self.quantity.signum() {
+                // Flipped through flat, the remainder was opened at this price
+                self.avg_price = price;
+            }
+            self.quantity = new_quantity;
+        }
+    }
+
+    pub fn unrealized_pnl(&self, mark: f64) -> f64 {
+        self.quantity * (mark - self.avg_price)
+    }
+
+    pub fn is_flat(&self) -> bool {
+        self.quantity.abs() < QUANTITY_EPSILON
+    }
+}
+
+/// Tracks positions from fills and marks them against the latest prices
+#[derive(Debug, Default)]
+pub struct PositionTracker {
+    positions: HashMap<PositionKey, Position>,
+    marks: HashMap<String, f64>,
+}
+
+impl PositionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply `fill`, returning the commission charged to the position in
+    /// the quote currency; `None` when the fill carries none or it cannot
+    /// be priced yet
+    pub fn apply_fill(&mut self, fill: &Fill) -> Option<f64> {
+        let key = PositionKey {
+            strategy: fill.strategy.clone(),
+            venue: fill.venue.to_string(),
+            symbol: fill.symbol.to_string(),
+        };
+        let commission = self.commission_in_quote(fill);
+
+        let position = self.positions.entry(key).or_default();
+        position.apply_fill(fill.side, fill.quantity, fill.price);
+        position.commission += commission.unwrap_or(0.0);
+        commission
+    }
+
+    /// `fill`'s commission in its symbol's quote currency, at the fill
+    /// price when charged in the base asset and otherwise as
+    /// [`value_in`](Self::value_in) has it
+    pub fn commission_in_quote(&self, fill: &Fill) -> Option<f64> {
+        let commission = fill.commission.as_ref()?;
+        let (base, quote) = split_symbol(&fill.symbol)?;
+        if commission.asset.eq_ignore_ascii_case(&base) {
+            Some(commission.amount * fill.price)
+        } else {
+            self.value_in(&commission.asset, commission.amount, quote)
+        }
+    }
+
+    /// `amount` of `asset` in `currency`: as is when they are the same, and
+    /// otherwise at the mark of the pair between them either way round
+    /// (e.g. `BNBUSDT`)
+    pub fn value_in(&self, asset: &str, amount: f64, currency: &str) -> Option<f64> {
+        let (asset, currency) = (asset.to_uppercase(), currency.to_uppercase());
+        if asset == currency {
+            return Some(amount);
+        }
+        self.mark_price(&format!("{}{}", asset, currency)).map(|mid| amount * mid)
+            .or_else(|| self.mark_price(&format!("{}{}", currency, asset)).map(|mid| amount / mid))
+    }
+
+    pub fn restore(&mut self, key: PositionKey, position: Position) {
+        self.positions.insert(key, position);
+    }
+
+    /// Update the mark price used for unrealized PnL
+    pub fn mark(&mut self, symbol: &str, price: f64) {
+        if price > 0.0 {
+            self.marks.insert(symbol.to_string(), price);
+        }
+    }
+
+    pub fn mark_price(&self, symbol: &str) -> Option<f64> {
+        self.marks.get(symbol).copied()
+    }
+
+    pub fn position(&self, key: &PositionKey) -> Option<&Position> {
+        self.positions.get(key)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (&PositionKey, &Position)> {
+        self.positions.iter()
+    }
+
+    /// Net quantity per symbol across strategies and venues
+    pub fn symbol_quantities(&self) -> HashMap<String, f64> {
+        let mut quantities = HashMap::new();
+        for (key, position) in &self.positions {
+            *quantities.entry(key.symbol.clone()).or_insert(0.0) += position.quantity;
+        }
+        quantities
+    }
+
+    /// Accrue `elapsed` of funding and borrow costs on every open position,
+    /// valued at its mark or, without one, its entry price
+    pub fn accrue_financing(&mut self, rates: &FinancingRates, elapsed: Duration) {
+        for (key, position) in self.positions.iter_mut().filter(|(_, p)| !p.is_flat()) {
+            let mark = self.marks.get(&key.symbol).copied().unwrap_or(position.avg_price);
+            position.financing += rates.accrual(&key.symbol, position, mark, elapsed);
+        }
+    }
+
+    /// Realized plus unrealized PnL and financing, less commissions, for a
+    /// single position.
+    ///
+    /// Positions without a mark fall back to their entry price, i.e. no
+    /// unrealized contribution.
+    pub fn position_pnl(&self, key: &PositionKey, position: &Position) -> f64 {
+        let mark = self.mark_price(&key.symbol).unwrap_or(position.avg_price);
+        position.realized_pnl + position.unrealized_pnl(mark) + position.financing - position.commission
+    }
+
+    /// Total PnL per strategy
+    pub fn strategy_pnl(&self) -> HashMap<String, f64> {
+        let mut pnl = HashMap::new();
+        for (key, position) in &self.positions {
+            *pnl.entry(key.strategy.clone()).or_insert(0.0) += self.position_pnl(key, position);
+        }
+        pnl
+    }
+
+    pub fn total_pnl(&self) -> f64 {
+        self.positions
+            .iter()
+            .map(|(key, position)| self.position_pnl(key, position))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::financing::Carry;
+
+    fn fill(side: OrderSide, quantity: f64, price: f64) -> Fill {
+        Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "MOCK".into(),
+            strategy: "mm".to_string(),
+            side,
+            quantity,
+            price,
+            timestamp: 0,
+            commission: None,
+        }
+    }
+
+    #[test]
+    fn test_position_average_price() {
+        let mut position = Position::default();
+        position.apply_fill(OrderSide::Buy, 1.0, 100.0);
+        position.apply_fill(OrderSide::Buy, 1.0, 110.0);
+
+        assert_eq!(position.quantity, 2.0);
+        assert_eq!(position.avg_price, 105.0);
+        assert_eq!(position.realized_pnl, 0.0);
+    }
+
+    #[test]
+    fn test_position_realized_pnl_on_close_and_flip() {
+        let mut position = Position::default();
+        position.apply_fill(OrderSide::Buy, 2.0, 100.0);
+        position.apply_fill(OrderSide::Sell, 3.0, 90.0);
+
+        // Closed 2 @ -10 each, then opened 1 short at 90
+        assert_eq!(position.realized_pnl, -20.0);
+        assert_eq!(position.quantity, -1.0);
+        assert_eq!(position.avg_price, 90.0);
+
+        position.apply_fill(OrderSide::Buy, 1.0, 80.0);
+        assert!(position.is_flat());
+        assert_eq!(position.realized_pnl, -10.0);
+    }
+
+    #[test]
+    fn test_tracker_marks_unrealized_pnl() {
+        let mut tracker = PositionTracker::new();
+        tracker.apply_fill(&fill(OrderSide::Buy, 2.0, 100.0));
+
+        // Without a mark the position contributes nothing
+        assert_eq!(tracker.total_pnl(), 0.0);
+
+        tracker.mark("BTCUSDT", 95.0);
+        assert_eq!(tracker.total_pnl(), -10.0);
+        assert_eq!(tracker.strategy_pnl().get("mm"), Some(&-10.0));
+    }
+
+    #[test]
+    fn test_financing_counts_toward_pnl() {
+        let mut tracker = PositionTracker::new();
+        tracker.apply_fill(&fill(OrderSide::Sell, 2.0, 100.0));
+        let rates = FinancingRates::new().with_carry("BTCUSDT", Carry::Borrow { annual_rate: 0.365 });
+
+        tracker.accrue_financing(&rates, Duration::from_secs(24 * 3600));
+        // 0.1% of the 200 short notional for a day
+        assert!((tracker.total_pnl() + 0.2).abs() < 1e-12);
+        tracker.apply_fill(&fill(OrderSide::Buy, 2.0, 100.0));
+        tracker.accrue_financing(&rates, Duration::from_secs(24 * 3600));
+        assert!((tracker.strategy_pnl()["mm"] + 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_commissions_in_quote_currency_count_toward_pnl() {
+        let charged = |amount: f64, asset: &str| Fill {
+            commission: Some(crate::types::Commission { amount, asset: asset.to_string() }),
+            ..fill(OrderSide::Buy, 1.0, 100.0)
+        };
+        let mut tracker = PositionTracker::new();
+        assert_eq!(tracker.apply_fill(&charged(0.05, "USDT")), Some(0.05));
+        assert_eq!(tracker.apply_fill(&charged(0.001, "BTC")), Some(0.1));
+        // Unpriced until BNBUSDT is marked
+        assert_eq!(tracker.apply_fill(&charged(0.01, "BNB")), None);
+        tracker.mark("BNBUSDT", 5.0);
+        assert_eq!(tracker.apply_fill(&charged(-0.01, "BNB")), Some(-0.05));
+
+        tracker.mark("BTCUSDT", 100.0);
+        assert!((tracker.total_pnl() + 0.1).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::types::{Order, OrderSide, OrderType, Quote, Symbol, VenueId};
+
+const DEFAULT_MAX_DEVIATION_BPS: f64 = 100.0;
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(5);
+
+/// How far a marketable order's price may stray from the other venues
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceSanityConfig {
+    /// Largest deviation from the other venues' mid, in basis points
+    pub max_deviation_bps: f64,
+    /// Per-symbol overrides of `max_deviation_bps`
+    pub symbols: HashMap<String, f64>,
+    /// Quotes older than this are left out of the reference price
+    pub max_age: Duration,
+}
+
+impl Default for PriceSanityConfig {
+    fn default() -> Self {
+        Self {
+            max_deviation_bps: DEFAULT_MAX_DEVIATION_BPS,
+            symbols: HashMap::new(),
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+}
+
+impl PriceSanityConfig {
+    /// Comma separated deviations in basis points, a bare number for every
+    /// symbol and `SYMBOL:BPS` for one, e.g. `100,BTCUSDT:50`
+    fn parse(spec: &str) -> Self {
+        let mut config = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = match entry.split_once(':') {
+                Some((symbol, bps)) => bps.trim().parse().ok().map(|bps| (Some(symbol.trim()), bps)),
+                None => entry.parse().ok().map(|bps| (None, bps)),
+            };
+            match parsed {
+                Some((Some(symbol), bps)) if bps > 0.0 && !symbol.is_empty() => {
+                    config.symbols.insert(symbol.to_string(), bps);
+                }
+                Some((None, bps)) if bps > 0.0 => config.max_deviation_bps = bps,
+                _ => warn!(entry = entry, "Ignoring malformed price sanity entry"),
+            }
+        }
+        config
+    }
+
+    /// Read `HFT_PRICE_SANITY`, e.g. `100,BTCUSDT:50`, and
+    /// `HFT_PRICE_SANITY_MAX_AGE_MS` (default 5000); returns `None` when
+    /// the check is off
+    pub fn from_env() -> Option<Self> {
+        let mut config = Self::parse(&std::env::var("HFT_PRICE_SANITY").ok()?);
+        if let Some(ms) = std::env::var("HFT_PRICE_SANITY_MAX_AGE_MS").ok().and_then(|ms| ms.parse().ok()) {
+            config.max_age = Duration::from_millis(ms);
+        }
+        Some(config)
+    }
+
+    fn max_deviation_bps(&self, symbol: &str) -> f64 {
+        self.symbols.get(symbol).copied().unwrap_or(self.max_deviation_bps)
+    }
+}
+
+/// An order held back because its price strayed from the other venues
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceDeviation {
+    pub venue: String,
+    pub symbol: String,
+    /// The price the order would trade at
+    pub price: f64,
+    /// Mid of the other venues' best bid and offer
+    pub reference: f64,
+    pub deviation_bps: f64,
+}
+
+impl std::fmt::Display for PriceDeviation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} price {} on {} is {:.1} bps from {} on other venues",
+            self.symbol, self.price, self.venue, self.deviation_bps, self.reference
+        )
+    }
+}
+
+struct VenueTop {
+    bid: f64,
+    ask: f64,
+    at: Instant,
+}
+
+/// Checks marketable orders against the consolidated top of book of the
+/// other venues quoting the symbol, so a bad feed on one venue is not
+/// traded through. Cloning shares the quotes.
+#[derive(Clone)]
+pub struct PriceSanity {
+    config: PriceSanityConfig,
+    /// Best bid and offer per symbol and venue
+    tops: Arc<RwLock<HashMap<Symbol, HashMap<VenueId, VenueTop>>>>,
+}
+
+impl PriceSanity {
+    pub fn new(config: PriceSanityConfig) -> Self {
+        Self { config, tops: Arc::default() }
+    }
+
+    pub fn on_quote(&self, quote: &Quote) {
+        let top = VenueTop { bid: quote.bid, ask: quote.ask, at: Instant::now() };
+        self.tops.write().unwrap()
+            .entry(quote.symbol)
+            .or_default()
+            .insert(quote.venue, top);
+    }
+
+    /// The deviation of `order`, if it is marketable on its venue and its
+    /// price strays from the other venues by more than allowed. Orders with
+    /// no fresh quote on another venue to compare with pass.
+    pub fn check(&self, order: &Order) -> Option<PriceDeviation> {
+        let tops = self.tops.read().unwrap();
+        let venues = tops.get(&order.symbol)?;
+        let now = Instant::now();
+        let fresh = |top: &&VenueTop| now.duration_since(top.at) <= self.config.max_age;
+
+        // The price the order takes liquidity at on its own venue
+        let own = venues.get(&order.venue).filter(fresh);
+        let touch = own.map(|top| match order.side {
+            OrderSide::Buy => top.ask,
+            OrderSide::Sell => top.bid,
+        }).filter(|price| *price > 0.0);
+        let price = match order.order_type {
+            OrderType::Market => touch.or((order.price > 0.0).then_some(order.price))?,
+            OrderType::Limit => {
+                let marketable = touch.is_some_and(|touch| match order.side {
+                    OrderSide::Buy => order.price >= touch,
+                    OrderSide::Sell => order.price <= touch,
+                });
+                if !marketable {
+                    return None;
+                }
+                order.price
+            }
+        };
+
+        let others = venues.iter()
+            .filter(|(venue, _)| **venue != order.venue)
+            .map(|(_, top)| top)
+            .filter(fresh);
+        let (mut bid, mut ask) = (f64::NAN, f64::NAN);
+        for top in others {
+            if top.bid > 0.0 {
+                bid = bid.max(top.bid);
+            }
+            if top.ask > 0.0 {
+                ask = ask.min(top.ask);
+            }
+        }
+        let reference = match (bid.is_nan(), ask.is_nan()) {
+            (false, false) => (bid + ask) / 2.0,
+            (false, true) => bid,
+            (true, false) => ask,
+            (true, true) => return None,
+        };
+
+        let deviation_bps = (price - reference).abs() / reference * 10_000.0;
+        (deviation_bps > self.config.max_deviation_bps(&order.symbol)).then(|| PriceDeviation {
+            venue: order.venue.to_string(),
+            symbol: order.symbol.to_string(),
+            price,
+            reference,
+            deviation_bps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(venue: &str, bid: f64, ask: f64) -> Quote {
+        Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: venue.into(),
+            timestamp: 0,
+        }
+    }
+
+    fn order(venue: &str, order_type: OrderType, price: f64) -> Order {
+        Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price,
+            venue: venue.into(),
+            order_type,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        }
+    }
+
+    #[test]
+    fn test_holds_orders_through_a_bad_feed() {
+        let sanity = PriceSanity::new(PriceSanityConfig::parse("100,ETHUSDT:20"));
+        sanity.on_quote(&quote("A", 49990.0, 50010.0));
+        sanity.on_quote(&quote("B", 49995.0, 50005.0));
+        // C's feed shows an offer 2% above the others
+        sanity.on_quote(&quote("C", 50990.0, 51000.0));
+
+        let deviation = sanity.check(&order("C", OrderType::Market, 0.0)).unwrap();
+        assert_eq!(deviation.reference, 50000.0);
+        assert!((deviation.deviation_bps - 200.0).abs() < 1e-9);
+        assert!(sanity.check(&order("A", OrderType::Market, 0.0)).is_none());
+
+        // Limit orders only when marketable on their venue
+        assert!(sanity.check(&order("C", OrderType::Limit, 51000.0)).is_some());
+        assert!(sanity.check(&order("C", OrderType::Limit, 50900.0)).is_none());
+        // Nothing to compare with
+        assert!(sanity.check(&Order { symbol: "ETHUSDT".into(), ..order("C", OrderType::Market, 3000.0) }).is_none());
+    }
+
+    #[test]
+    fn test_parse() {
+        let config = PriceSanityConfig::parse("50, BTCUSDT:25, ETHUSDT:x, -1");
+        assert_eq!(config.max_deviation_bps("SOLUSDT"), 50.0);
+        assert_eq!(config.max_deviation_bps("BTCUSDT"), 25.0);
+        assert_eq!(config.symbols.len(), 1);
+    }
+}
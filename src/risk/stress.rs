@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use warp::Filter;
+
+use super::RiskManager;
+use crate::metrics::STRESS_PNL;
+
+/// Relative moves of an underlying's price and implied volatility
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Shock {
+    /// `None` for every underlying without a shock of its own
+    #[serde(default)]
+    pub underlying: Option<String>,
+    /// e.g. -0.2 for a 20% fall
+    #[serde(default)]
+    pub price: f64,
+    /// e.g. 0.5 for implied volatilities half as high again
+    #[serde(default)]
+    pub vol: f64,
+}
+
+/// A named set of shocks applied together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    pub shocks: Vec<Shock>,
+}
+
+impl Scenario {
+    /// Shock to `underlying`: its own, or else the catch-all
+    pub fn shock(&self, underlying: &str) -> Option<&Shock> {
+        self.shocks.iter()
+            .find(|shock| shock.underlying.as_deref().is_some_and(|u| u.eq_ignore_ascii_case(underlying)))
+            .or_else(|| self.shocks.iter().find(|shock| shock.underlying.is_none()))
+    }
+}
+
+/// Hypothetical PnL of current positions under one scenario, in the
+/// reporting currency
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StressResult {
+    pub scenario: String,
+    pub pnl: f64,
+    /// PnL by underlying
+    pub underlyings: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StressReport {
+    /// Currency of PnL; each symbol's quote currency when there is no FX
+    /// conversion
+    pub currency: Option<String>,
+    pub results: Vec<StressResult>,
+}
+
+/// Scenarios to run on demand, and how often to run them on a schedule
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StressConfig {
+    pub scenarios: Vec<Scenario>,
+    /// Scenarios only run on demand when unset
+    pub interval: Option<Duration>,
+}
+
+/// `-20%` or `-0.2`
+fn parse_move(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let parsed = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().ok()? / 100.0,
+        None => value.parse::<f64>().ok()?,
+    };
+    (parsed.is_finite() && parsed > -1.0).then_some(parsed)
+}
+
+impl StressConfig {
+    pub fn with_scenario(mut self, scenario: Scenario) -> Self {
+        self.scenarios.push(scenario);
+        self
+    }
+
+    /// Semicolon separated `NAME:SHOCKS` scenarios, each shock
+    /// `UNDERLYING=MOVE` for price or `UNDERLYING.vol=MOVE` for implied
+    /// volatility, with `*` for every other underlying and moves as
+    /// percentages or fractions, e.g.
+    /// `btc_crash:BTC=-20%,BTC.vol=+50%;selloff:*=-10%,*.vol=+25%`
+    fn parse(spec: &str) -> Vec<Scenario> {
+        let mut scenarios = Vec::new();
+        for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((name, shocks)) = entry.split_once(':').filter(|(name, _)| !name.trim().is_empty()) else {
+                warn!(entry = entry, "Ignoring malformed stress scenario");
+                continue;
+            };
+            let mut scenario = Scenario { name: name.trim().to_string(), shocks: Vec::new() };
+            for shock in shocks.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let parsed = shock.split_once('=').and_then(|(key, value)| Some((key.trim(), parse_move(value)?)));
+                let (underlying, is_vol, value) = match parsed {
+                    Some((key, value)) => match key.strip_suffix(".vol") {
+                        Some(underlying) => (underlying, true, value),
+                        None => (key, false, value),
+                    },
+                    None => {
+                        warn!(scenario = %scenario.name, shock = shock, "Ignoring malformed stress shock");
+                        continue;
+                    }
+                };
+                if underlying.is_empty() {
+                    warn!(scenario = %scenario.name, shock = shock, "Ignoring malformed stress shock");
+                    continue;
+                }
+                let underlying = (underlying != "*").then(|| underlying.to_uppercase());
+                let index = match scenario.shocks.iter().position(|s| s.underlying == underlying) {
+                    Some(index) => index,
+                    None => {
+                        scenario.shocks.push(Shock { underlying, ..Default::default() });
+                        scenario.shocks.len() - 1
+                    }
+                };
+                if is_vol {
+                    scenario.shocks[index].vol = value;
+                } else {
+                    scenario.shocks[index].price = value;
+                }
+            }
+            scenarios.push(scenario);
+        }
+        scenarios
+    }
+
+    /// Read `HFT_STRESS_SCENARIOS` (see [`parse`](Self::parse)) and
+    /// `HFT_STRESS_INTERVAL_SECS`; returns `None` when no scenarios are set
+    pub fn from_env() -> Option<Self> {
+        let scenarios = Self::parse(&std::env::var("HFT_STRESS_SCENARIOS").ok()?);
+        let interval = std::env::var("HFT_STRESS_INTERVAL_SECS").ok()
+            .and_then(|secs| secs.parse().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs);
+        Some(Self { scenarios, interval })
+    }
+}
+
+/// Runs the configured scenarios on a schedule, publishing their PnL as
+/// gauges
+pub struct StressTester {
+    risk: Arc<RiskManager>,
+    interval: Duration,
+}
+
+impl StressTester {
+    pub fn new(risk: Arc<RiskManager>, interval: Duration) -> Self {
+        Self { risk, interval }
+    }
+
+    pub async fn run_once(&self) -> StressReport {
+        let report = self.risk.stress_test(self.risk.stress_scenarios()).await;
+        for result in &report.results {
+            STRESS_PNL.with_label_values(&[&result.scenario]).set(result.pnl);
+            info!(scenario = %result.scenario, pnl = result.pnl, "Stress scenario");
+        }
+        report
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            self.run_once().await;
+        }
+    }
+}
+
+/// - `GET /admin/stress` runs the configured scenarios against current
+///   positions
+/// - `POST /admin/stress` runs the list of [`Scenario`]s in the body instead
+pub fn routes(
+    risk: Arc<RiskManager>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let configured = {
+        let risk = Arc::clone(&risk);
+        warp::path!("admin" / "stress")
+            .and(warp::get())
+            .then(move || {
+                let risk = Arc::clone(&risk);
+                async move { warp::reply::json(&risk.stress_test(risk.stress_scenarios()).await) }
+            })
+    };
+    let ad_hoc = warp::path!("admin" / "stress")
+        .and(warp::post())
+        .and(warp::body::json())
+        .then(move |scenarios: Vec<Scenario>| {
+            let risk = Arc::clone(&risk);
+            async move { warp::reply::json(&risk.stress_test(&scenarios).await) }
+        });
+    configured.or(ad_hoc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::LossLimits;
+    use crate::signals::{GreeksConfig, GreeksFeed};
+    use crate::types::{Fill, OptionInstrument, OrderSide, Quote};
+
+    fn quote(symbol: &str, mid: f64, timestamp: u64) -> Quote {
+        Quote { symbol: symbol.into(), bid: mid, ask: mid, bid_size: 1.0, ask_size: 1.0, venue: "DERIBIT".into(), timestamp }
+    }
+
+    #[test]
+    fn test_parse_scenarios() {
+        let scenarios = StressConfig::parse("btc_crash:btc=-20%,BTC.vol=+50%;selloff:*=-0.1;bad;x:BTC=-150%,ETH=abc");
+        assert_eq!(scenarios.len(), 3);
+        assert_eq!(scenarios[0].shocks, vec![Shock { underlying: Some("BTC".to_string()), price: -0.2, vol: 0.5 }]);
+        assert_eq!(scenarios[1].shock("ETH").map(|s| s.price), Some(-0.1));
+        assert_eq!(scenarios[0].shock("ETH"), None);
+        assert!(scenarios[2].shocks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stress_pnl_of_linear_and_option_positions() {
+        let feed = GreeksFeed::new(GreeksConfig { premium_in_underlying: true, ..Default::default() });
+        let risk = Arc::new(RiskManager::new(LossLimits::default()).with_greeks(feed.clone()));
+        let option = "BTC-27MAR26-50000-P";
+        let now = OptionInstrument::parse(option).unwrap().expiry - 30 * 86_400_000;
+        for quote in [quote(option, 0.05, now), quote("BTC", 50_000.0, now), quote("BTCUSDT", 50_000.0, now), quote("ETHUSDT", 3_000.0, now)] {
+            feed.on_quote(&quote);
+            risk.on_quote(&quote).await;
+        }
+        for (symbol, side, quantity) in [("BTCUSDT", OrderSide::Buy, 2.0), ("ETHUSDT", OrderSide::Sell, 10.0), (option, OrderSide::Buy, 4.0)] {
+            risk.on_fill(&Fill {
+                order_id: "1".to_string(),
+                symbol: symbol.into(),
+                venue: "DERIBIT".into(),
+                strategy: "s".to_string(),
+                side,
+                quantity,
+                price: 1.0,
+                timestamp: 0,
+                commission: None,
+            }).await;
+        }
+
+        let scenarios = StressConfig::parse("crash:*=-20%;vol:BTC.vol=+50%");
+        let report = risk.stress_test(&scenarios).await;
+        let (crash, vol) = (&report.results[0], &report.results[1]);
+        assert_eq!(crash.underlyings["ETH"], 6_000.0);
+        // Puts gain in a crash, but less than the underlying falls
+        let btc = crash.underlyings["BTC"];
+        assert!(btc > -20_000.0 && btc < -20_000.0 + 4.0 * 10_000.0);
+        assert!((crash.pnl - btc - 6_000.0).abs() < 1e-6);
+        assert!(vol.pnl > 0.0);
+        assert!(!vol.underlyings.contains_key("ETH") || vol.underlyings["ETH"] == 0.0);
+
+        let response = warp::test::request().method("POST").path("/admin/stress")
+            .json(&scenarios[..1].to_vec())
+            .reply(&routes(Arc::clone(&risk))).await;
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["results"][0]["underlyings"]["ETH"], 6_000.0);
+
+        risk.set_stress_scenarios(scenarios);
+        let report = StressTester::new(Arc::clone(&risk), Duration::from_secs(60)).run_once().await;
+        assert_eq!(report.results.len(), 2);
+    }
+}
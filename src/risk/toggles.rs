@@ -0,0 +1,147 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, RwLock};
+use serde::Serialize;
+use tracing::info;
+use warp::Filter;
+
+use crate::metrics::TRADING_DISABLED;
+
+/// Symbols and strategies paused by an operator.
+///
+/// Paused strategies still receive market data, so they resume with
+/// current state; only their orders are withheld.
+#[derive(Debug, Default)]
+pub struct TradingToggles {
+    symbols: RwLock<BTreeSet<String>>,
+    strategies: RwLock<BTreeSet<String>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DisabledTrading {
+    pub symbols: Vec<String>,
+    pub strategies: Vec<String>,
+}
+
+fn set(target: &RwLock<BTreeSet<String>>, scope: &str, name: &str, disabled: bool) -> bool {
+    let mut names = target.write().unwrap();
+    let changed = if disabled {
+        names.insert(name.to_string())
+    } else {
+        names.remove(name)
+    };
+
+    if changed {
+        info!(scope = scope, name = name, disabled = disabled, "Trading toggle changed");
+        TRADING_DISABLED
+            .with_label_values(&[scope, name])
+            .set(if disabled { 1.0 } else { 0.0 });
+    }
+    changed
+}
+
+impl TradingToggles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether the toggle changed
+    pub fn disable_symbol(&self, symbol: &str) -> bool {
+        set(&self.symbols, "symbol", symbol, true)
+    }
+
+    pub fn enable_symbol(&self, symbol: &str) -> bool {
+        set(&self.symbols, "symbol", symbol, false)
+    }
+
+    pub fn disable_strategy(&self, strategy: &str) -> bool {
+        set(&self.strategies, "strategy", strategy, true)
+    }
+
+    pub fn enable_strategy(&self, strategy: &str) -> bool {
+        set(&self.strategies, "strategy", strategy, false)
+    }
+
+    pub fn is_symbol_enabled(&self, symbol: &str) -> bool {
+        !self.symbols.read().unwrap().contains(symbol)
+    }
+
+    pub fn is_strategy_enabled(&self, strategy: &str) -> bool {
+        !self.strategies.read().unwrap().contains(strategy)
+    }
+
+    pub fn disabled(&self) -> DisabledTrading {
+        DisabledTrading {
+            symbols: self.symbols.read().unwrap().iter().cloned().collect(),
+            strategies: self.strategies.read().unwrap().iter().cloned().collect(),
+        }
+    }
+}
+
+/// Admin endpoints:
+/// - `GET /admin/toggles` lists paused symbols and strategies
+/// - `POST /admin/toggles/{symbol|strategy}/{name}/{disable|enable}`
+pub fn routes(
+    toggles: Arc<TradingToggles>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let listed = Arc::clone(&toggles);
+    let list = warp::path!("admin" / "toggles")
+        .and(warp::get())
+        .map(move || warp::reply::json(&listed.disabled()));
+
+    let change = warp::path!("admin" / "toggles" / String / String / String)
+        .and(warp::post())
+        .and_then(move |scope: String, name: String, action: String| {
+            let toggles = Arc::clone(&toggles);
+            async move {
+                let disable = match action.as_str() {
+                    "disable" => true,
+                    "enable" => false,
+                    _ => return Err(warp::reject::not_found()),
+                };
+                match (scope.as_str(), disable) {
+                    ("symbol", true) => toggles.disable_symbol(&name),
+                    ("symbol", false) => toggles.enable_symbol(&name),
+                    ("strategy", true) => toggles.disable_strategy(&name),
+                    ("strategy", false) => toggles.enable_strategy(&name),
+                    _ => return Err(warp::reject::not_found()),
+                };
+                Ok(warp::reply::json(&toggles.disabled()))
+            }
+        });
+
+    list.or(change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_admin_routes() {
+        let toggles = Arc::new(TradingToggles::new());
+        let api = routes(Arc::clone(&toggles));
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/admin/toggles/symbol/BTCUSDT/disable")
+            .reply(&api)
+            .await;
+        assert_eq!(response.status(), 200);
+        assert!(!toggles.is_symbol_enabled("BTCUSDT"));
+        assert!(toggles.is_strategy_enabled("mm"));
+
+        toggles.disable_strategy("mm");
+        toggles.enable_symbol("BTCUSDT");
+        let response = warp::test::request().path("/admin/toggles").reply(&api).await;
+        let disabled: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(disabled["symbols"], serde_json::json!([]));
+        assert_eq!(disabled["strategies"], serde_json::json!(["mm"]));
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/admin/toggles/venue/X/disable")
+            .reply(&api)
+            .await;
+        assert_eq!(response.status(), 404);
+    }
+}
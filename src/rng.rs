@@ -0,0 +1,52 @@
+//! Seedable randomness for simulated venues, fault injection and
+//! backtests.
+//!
+//! Each component draws from its own generator, derived from one run seed
+//! and the component's name, so a run replayed with the same seed makes the
+//! same draws in every component however their tasks interleave.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tracing::info;
+
+pub type SimRng = StdRng;
+
+/// The seed in `HFT_SEED`, if set
+pub fn env_seed() -> Option<u64> {
+    std::env::var("HFT_SEED").ok().and_then(|seed| seed.parse().ok())
+}
+
+/// The seed in `HFT_SEED`, or a random one that is logged so the run can
+/// be repeated
+pub fn seed_from_env() -> u64 {
+    env_seed().unwrap_or_else(|| {
+        let seed = rand::random();
+        info!(seed, "Seeded randomly, set HFT_SEED to repeat this run");
+        seed
+    })
+}
+
+/// The generator for `component` in a run seeded with `seed`
+pub fn component_rng(seed: u64, component: &str) -> SimRng {
+    // FNV-1a, which unlike the std hasher is the same in every build
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in seed.to_le_bytes().into_iter().chain(component.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    StdRng::seed_from_u64(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_component_streams() {
+        let draws = |seed, component| component_rng(seed, component).random::<[u64; 4]>();
+        assert_eq!(draws(7, "quotes"), draws(7, "quotes"));
+        assert_ne!(draws(7, "quotes"), draws(7, "orders"));
+        assert_ne!(draws(7, "quotes"), draws(8, "quotes"));
+    }
+}
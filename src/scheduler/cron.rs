@@ -0,0 +1,113 @@
+use chrono::{DateTime, Datelike, Timelike, Utc};
+
+/// A five field cron expression, `MINUTE HOUR DAY MONTH WEEKDAY`, matched
+/// against UTC time. Fields take `*`, numbers, ranges `a-b`, steps `*/n`
+/// and `a-b/n`, and lists of those. Weekdays run from 0 (Sunday) to 6, with
+/// 7 also Sunday. As in cron, when both day and weekday are restricted a
+/// time matching either runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+/// Bits `min..=max` of a field, or `None` if it is malformed
+fn parse_field(field: &str, min: u32, max: u32) -> Option<u64> {
+    let mut bits = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0)?),
+            None => (item, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+                // `5/15` runs from 5 to the end of the range
+                None if item.contains('/') => (range.parse().ok()?, max),
+                None => {
+                    let value = range.parse().ok()?;
+                    (value, value)
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return None;
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Some(bits)
+}
+
+impl CronSchedule {
+    pub fn parse(spec: &str) -> Option<Self> {
+        let fields: Vec<&str> = spec.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return None;
+        };
+        let mut weekday_bits = parse_field(weekdays, 0, 7)?;
+        if weekday_bits & (1 << 7) != 0 {
+            weekday_bits = (weekday_bits | 1) & !(1 << 7);
+        }
+        Some(Self {
+            minutes: parse_field(minutes, 0, 59)?,
+            hours: parse_field(hours, 0, 23)?,
+            days: parse_field(days, 1, 31)?,
+            months: parse_field(months, 1, 12)?,
+            weekdays: weekday_bits,
+            any_day: days == "*",
+            any_weekday: weekdays == "*",
+        })
+    }
+
+    /// Whether the schedule fires in the minute containing `at`
+    pub fn matches(&self, at: DateTime<Utc>) -> bool {
+        let bit = |bits: u64, value: u32| bits & (1 << value) != 0;
+        let day = bit(self.days, at.day());
+        let weekday = bit(self.weekdays, at.weekday().num_days_from_sunday());
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        };
+        bit(self.minutes, at.minute()) && bit(self.hours, at.hour()) && bit(self.months, at.month()) && day_matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_and_match() {
+        let at = |d, h, m| Utc.with_ymd_and_hms(2026, 10, d, h, m, 0).unwrap();
+
+        // 2026-10-16 is a Friday, 2026-10-17 a Saturday
+        let eod = CronSchedule::parse("55 21 * * 1-5").unwrap();
+        assert!(eod.matches(at(16, 21, 55)));
+        assert!(!eod.matches(at(16, 21, 56)));
+        assert!(!eod.matches(at(17, 21, 55)));
+
+        let quarter_hourly = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert!(quarter_hourly.matches(at(17, 3, 45)));
+        assert!(!quarter_hourly.matches(at(17, 3, 50)));
+
+        // Day or weekday when both are restricted
+        let either = CronSchedule::parse("0 0 1 * 6,7").unwrap();
+        assert!(either.matches(at(1, 0, 0)));
+        assert!(either.matches(at(17, 0, 0)));
+        assert!(either.matches(at(18, 0, 0)));
+        assert!(!either.matches(at(16, 0, 0)));
+
+        for bad in ["", "* * * *", "60 * * * *", "* 24 * * *", "*/0 * * * *", "5-1 * * * *", "x * * * *"] {
+            assert_eq!(CronSchedule::parse(bad), None, "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use chrono::{DateTime, NaiveDate, Timelike, Utc};
+use tokio::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::audit;
+use crate::error::HftError;
+use crate::events::{EngineEvent, EventBus};
+use crate::metrics::{MetricsRegistry, TRADE_RECONCILE_BREAKS};
+use crate::report::{reconcile_trades, ReportConfig, Reporter};
+use crate::risk::{LossScope, RiskManager};
+use crate::venues::VenueRegistry;
+
+pub mod cron;
+pub mod timers;
+pub use cron::CronSchedule;
+pub use timers::{TimerId, TimerService};
+
+const DEFAULT_REPORT_DIR: &str = "reports";
+const DEFAULT_METRICS_DIR: &str = "state/metrics";
+
+/// Rotated copies of a log file kept by `rotate_logs`
+const ROTATED_LOGS_KEPT: usize = 7;
+
+/// Trade history compared by `reconcile_trades`, ending when it runs
+const RECONCILE_WINDOW: Duration = Duration::from_secs(86_400);
+
+/// Something the scheduler can run
+#[derive(Debug, Clone, PartialEq)]
+pub enum Job {
+    /// Cancel open orders and flatten every position with market orders
+    Flatten,
+    /// Write the daily PnL and activity report into a directory
+    PnlReport { dir: PathBuf },
+    /// Write the Prometheus metrics as text into a directory
+    MetricsSnapshot { dir: PathBuf },
+    /// Copy a log file aside with the date appended and truncate it, for
+    /// output redirected by a process supervisor
+    RotateLogs { path: PathBuf },
+    /// Compare the fills in an audit log with each venue's trade history
+    /// over the past day
+    ReconcileTrades { journal: PathBuf },
+}
+
+impl Job {
+    /// `NAME[:ARG]`, the argument being a directory or file path
+    fn parse(spec: &str) -> Option<Self> {
+        let (name, arg) = match spec.split_once(':') {
+            Some((name, arg)) => (name, Some(PathBuf::from(arg))),
+            None => (spec, None),
+        };
+        match name {
+            "flatten" => Some(Job::Flatten),
+            "pnl_report" => Some(Job::PnlReport { dir: arg.unwrap_or_else(|| DEFAULT_REPORT_DIR.into()) }),
+            "metrics_snapshot" => Some(Job::MetricsSnapshot { dir: arg.unwrap_or_else(|| DEFAULT_METRICS_DIR.into()) }),
+            "rotate_logs" => Some(Job::RotateLogs { path: arg? }),
+            "reconcile_trades" => Some(Job::ReconcileTrades { journal: arg? }),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Job::Flatten => "flatten",
+            Job::PnlReport { .. } => "pnl_report",
+            Job::MetricsSnapshot { .. } => "metrics_snapshot",
+            Job::RotateLogs { .. } => "rotate_logs",
+            Job::ReconcileTrades { .. } => "reconcile_trades",
+        }
+    }
+
+    /// Trading jobs are skipped on holidays; housekeeping runs every day
+    fn trading_days_only(&self) -> bool {
+        matches!(self, Job::Flatten | Job::PnlReport { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledJob {
+    pub schedule: CronSchedule,
+    pub job: Job,
+}
+
+/// Days trading jobs do not run on
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TradingCalendar {
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl TradingCalendar {
+    pub fn with_holiday(mut self, date: NaiveDate) -> Self {
+        self.holidays.insert(date);
+        self
+    }
+
+    /// Read `HFT_HOLIDAYS` as comma separated `YYYY-MM-DD` dates
+    pub fn from_env() -> Self {
+        Self::parse(&std::env::var("HFT_HOLIDAYS").unwrap_or_default())
+    }
+
+    fn parse(spec: &str) -> Self {
+        let mut calendar = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match NaiveDate::parse_from_str(entry, "%Y-%m-%d") {
+                Ok(date) => calendar = calendar.with_holiday(date),
+                Err(_) => warn!(entry = entry, "Ignoring malformed holiday"),
+            }
+        }
+        calendar
+    }
+
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        !self.holidays.contains(&date)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchedulerConfig {
+    pub jobs: Vec<ScheduledJob>,
+    pub calendar: TradingCalendar,
+}
+
+impl SchedulerConfig {
+    /// Read `HFT_SCHEDULE` as `;` separated `CRON JOB[:ARG]` entries, e.g.
+    /// `55 21 * * 1-5 flatten; 0 22 * * 1-5 pnl_report:reports; */15 * * * * metrics_snapshot`,
+    /// and holidays from [`TradingCalendar::from_env`]. Returns `None` when
+    /// no job is scheduled.
+    pub fn from_env() -> Option<Self> {
+        let config = Self::parse(&std::env::var("HFT_SCHEDULE").ok()?);
+        (!config.jobs.is_empty()).then(|| Self { calendar: TradingCalendar::from_env(), ..config })
+    }
+
+    fn parse(spec: &str) -> Self {
+        let mut config = Self::default();
+        for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.rsplit_once(char::is_whitespace).and_then(|(cron, job)| {
+                Some(ScheduledJob { schedule: CronSchedule::parse(cron)?, job: Job::parse(job)? })
+            });
+            match parsed {
+                Some(job) => config.jobs.push(job),
+                None => warn!(entry = entry, "Ignoring malformed scheduled job"),
+            }
+        }
+        config
+    }
+}
+
+/// Runs [`SchedulerConfig`] jobs when their schedule fires, publishing the
+/// outcome of each as [`EngineEvent::ScheduledJob`]
+pub struct Scheduler {
+    config: SchedulerConfig,
+    risk: Arc<RiskManager>,
+    reports: Reporter,
+    venues: VenueRegistry,
+    events: EventBus,
+    metrics: MetricsRegistry,
+}
+
+impl Scheduler {
+    pub fn new(config: SchedulerConfig, risk: Arc<RiskManager>, venues: VenueRegistry, events: EventBus) -> Self {
+        let reports = Reporter::new(ReportConfig::default(), Arc::clone(&risk));
+        Self { config, risk, reports, venues, events, metrics: MetricsRegistry::global() }
+    }
+
+    /// Write `pnl_report` reports with `reports`' fees and webhook
+    pub fn with_reporter(mut self, reports: Reporter) -> Self {
+        self.reports = reports;
+        self
+    }
+
+    /// Snapshot `metrics_snapshot` from `metrics` rather than the global
+    /// registry
+    pub fn with_metrics(mut self, metrics: MetricsRegistry) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Check the schedule at the start of every minute until the task is
+    /// dropped
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            let now = Utc::now();
+            let into_minute = Duration::from_secs(now.second() as u64) + Duration::from_nanos(now.nanosecond() as u64);
+            tokio::time::sleep(Duration::from_secs(60).saturating_sub(into_minute)).await;
+            self.run_due(Utc::now()).await;
+        }
+    }
+
+    /// Run every job scheduled for the minute of `at`, in configured order
+    pub async fn run_due(&self, at: DateTime<Utc>) -> Vec<&'static str> {
+        let mut ran = Vec::new();
+        for scheduled in &self.config.jobs {
+            if !scheduled.schedule.matches(at) {
+                continue;
+            }
+            if scheduled.job.trading_days_only() && !self.config.calendar.is_trading_day(at.date_naive()) {
+                info!(job = scheduled.job.name(), "Skipping scheduled job on a holiday");
+                continue;
+            }
+            let result = self.run_job(&scheduled.job, at).await;
+            match &result {
+                Ok(detail) => info!(job = scheduled.job.name(), detail = %detail, "Scheduled job finished"),
+                Err(e) => error!(job = scheduled.job.name(), error = %e, "Scheduled job failed"),
+            }
+            self.events.publish(EngineEvent::ScheduledJob {
+                job: scheduled.job.name().to_string(),
+                ok: result.is_ok(),
+                detail: result.unwrap_or_else(|e| e.to_string()),
+            });
+            ran.push(scheduled.job.name());
+        }
+        ran
+    }
+
+    /// Run `job` now, returning a summary of what it did
+    pub async fn run_job(&self, job: &Job, at: DateTime<Utc>) -> Result<String, HftError> {
+        match job {
+            Job::Flatten => {
+                // Both go through the order gateway, which sends nothing
+                // from a standby
+                self.risk.cancel_all("scheduled", "scheduled flatten").await;
+                let orders = self.risk.flatten(&LossScope::Portfolio).await.len();
+                Ok(format!("{} flattening orders sent", orders))
+            }
+            Job::PnlReport { dir } => self.reports.generate(dir, at).await,
+            Job::MetricsSnapshot { dir } => {
+                let buffer = self.metrics.encode()?;
+                let path = dir.join(format!("metrics-{}.prom", at.format("%Y%m%dT%H%M")));
+                write_file(&path, &buffer)?;
+                Ok(format!("wrote {}", path.display()))
+            }
+            Job::RotateLogs { path } => rotate(path, at.date_naive()),
+            Job::ReconcileTrades { journal } => {
+                let until = at.timestamp_millis().max(0) as u64;
+                let from = until.saturating_sub(RECONCILE_WINDOW.as_millis() as u64);
+                let fills: Vec<_> = audit::read_fills(journal)?.into_iter().map(|(_, fill)| fill).collect();
+                let held: Vec<(String, String)> = self.risk.position_pnl().await.into_iter()
+                    .filter(|(_, position, _)| !position.is_flat())
+                    .map(|(key, _, _)| (key.venue, key.symbol))
+                    .collect();
+                let reconciliation = reconcile_trades(&self.venues, &fills, &held, from, until).await;
+                for trade_break in &reconciliation.breaks {
+                    warn!(venue = %trade_break.venue, symbol = %trade_break.symbol, detail = %trade_break, "Trade break");
+                    TRADE_RECONCILE_BREAKS.with_label_values(&[&trade_break.venue, &trade_break.kind.to_string()]).inc();
+                    self.events.publish(EngineEvent::TradeBreak {
+                        venue: trade_break.venue.clone(),
+                        symbol: trade_break.symbol.clone(),
+                        kind: trade_break.kind.to_string(),
+                        detail: trade_break.to_string(),
+                    });
+                }
+                Ok(reconciliation.summary())
+            }
+        }
+    }
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> Result<(), HftError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Copy `path` to `path.DATE`, truncate it so writers holding it open in
+/// append mode carry on, and keep the newest rotated copies only
+fn rotate(path: &Path, date: NaiveDate) -> Result<String, HftError> {
+    let file_name = path.file_name()
+        .ok_or_else(|| HftError::Config(format!("Not a log file: {}", path.display())))?
+        .to_string_lossy()
+        .to_string();
+    let rotated = path.with_file_name(format!("{}.{}", file_name, date));
+    std::fs::copy(path, &rotated)?;
+    std::fs::OpenOptions::new().write(true).open(path)?.set_len(0)?;
+
+    let dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let prefix = format!("{}.", file_name);
+    let mut copies: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|p| p.file_name().is_some_and(|n| n.to_string_lossy().starts_with(&prefix)))
+        .collect();
+    // Dated suffixes sort oldest first
+    copies.sort();
+    let removed = copies.len().saturating_sub(ROTATED_LOGS_KEPT);
+    for old in &copies[..removed] {
+        std::fs::remove_file(old)?;
+    }
+    Ok(format!("rotated to {}, removed {} old copies", rotated.display(), removed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::mocks::mock_venue::{MockVenue, MockVenueConfig};
+    use crate::gateways::order::CancelTarget;
+    use crate::risk::LossLimits;
+    use crate::types::{Fill, OrderSide};
+
+    #[test]
+    fn test_parse_schedule() {
+        let config = SchedulerConfig::parse("55 21 * * 1-5 flatten; */15 * * * * metrics_snapshot:/tmp/m; 0 0 * * * rotate_logs; bogus");
+        assert_eq!(config.jobs.len(), 2);
+        assert_eq!(config.jobs[0].job, Job::Flatten);
+        assert_eq!(config.jobs[1].job, Job::MetricsSnapshot { dir: "/tmp/m".into() });
+
+        let calendar = TradingCalendar::parse("2026-12-25, 2027-13-01");
+        assert!(!calendar.is_trading_day(NaiveDate::from_ymd_opt(2026, 12, 25).unwrap()));
+        assert_eq!(calendar.holidays.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_eod_flatten_and_report_skip_holidays() {
+        let dir = std::env::temp_dir().join(format!("hft_scheduler_{}", std::process::id()));
+        let venue = Arc::new(MockVenue::new("SCHED", MockVenueConfig::default()));
+        let venues = VenueRegistry::from_venues(vec![venue.clone()]).await;
+        let (order_tx, mut order_rx) = tokio::sync::mpsc::channel(8);
+        let (cancel_tx, mut cancel_rx) = tokio::sync::mpsc::channel(8);
+        let risk = Arc::new(RiskManager::new(LossLimits::default()).with_gateway(order_tx, cancel_tx));
+        risk.on_fill(&Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "SCHED".into(),
+            strategy: "mm".to_string(),
+            side: OrderSide::Buy,
+            quantity: 2.0,
+            price: 50000.0,
+            timestamp: 1,
+            commission: None,
+        }).await;
+
+        let config = SchedulerConfig {
+            jobs: SchedulerConfig::parse(&format!("55 21 * * 1-5 flatten; 0 22 * * * pnl_report:{}", dir.display())).jobs,
+            calendar: TradingCalendar::default().with_holiday(NaiveDate::from_ymd_opt(2026, 12, 25).unwrap()),
+        };
+        let events = EventBus::default();
+        let mut job_events = events.subscribe();
+        let scheduler = Scheduler::new(config, risk, venues, events);
+
+        assert!(scheduler.run_due(Utc.with_ymd_and_hms(2026, 10, 16, 21, 54, 0).unwrap()).await.is_empty());
+        assert_eq!(scheduler.run_due(Utc.with_ymd_and_hms(2026, 10, 16, 21, 55, 0).unwrap()).await, vec!["flatten"]);
+        assert_eq!(cancel_rx.try_recv().unwrap().target, CancelTarget::All(None));
+        let flatten = order_rx.try_recv().unwrap();
+        assert!(order_rx.try_recv().is_err());
+        assert_eq!((flatten.side, flatten.quantity), (OrderSide::Sell, 2.0));
+        assert!(matches!(job_events.recv().await.unwrap(), EngineEvent::ScheduledJob { ok: true, .. }));
+
+        assert_eq!(scheduler.run_due(Utc.with_ymd_and_hms(2026, 10, 16, 22, 0, 0).unwrap()).await, vec!["pnl_report"]);
+        let report: serde_json::Value = serde_json::from_slice(&std::fs::read(dir.join("report-2026-10-16.json")).unwrap()).unwrap();
+        assert_eq!(report["activity"][0]["position"], 2.0);
+
+        // Christmas is a Friday in 2026
+        assert!(scheduler.run_due(Utc.with_ymd_and_hms(2026, 12, 25, 21, 55, 0).unwrap()).await.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_trades_publishes_breaks() {
+        let dir = std::env::temp_dir().join(format!("hft_reconcile_{}", std::process::id()));
+        let config = audit::AuditConfig { path: dir.join("audit.log"), syslog: false };
+        let at = Utc.with_ymd_and_hms(2026, 10, 16, 1, 0, 0).unwrap();
+        let fill = |order_id: &str, quantity: f64| Fill {
+            order_id: order_id.to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "SCHED".into(),
+            strategy: "mm".to_string(),
+            side: OrderSide::Buy,
+            quantity,
+            price: 50000.0,
+            timestamp: at.timestamp_millis() as u64 - 60_000,
+            commission: None,
+        };
+        let log = audit::AuditLog::open(&config).unwrap();
+        log.record(audit::AuditEvent::Fill(fill("1", 1.0)));
+        for _ in 0..100 {
+            if audit::read_fills(&config.path).is_ok_and(|fills| !fills.is_empty()) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let venue = Arc::new(MockVenue::new("SCHED", MockVenueConfig::default()));
+        venue.set_trades(vec![fill("1", 1.0), fill("2", 0.5)]).await;
+        let venues = VenueRegistry::from_venues(vec![venue]).await;
+        let events = EventBus::default();
+        let mut breaks = events.subscribe();
+        let scheduler = Scheduler::new(SchedulerConfig::default(), Arc::new(RiskManager::new(LossLimits::default())), venues, events);
+
+        let job = Job::parse(&format!("reconcile_trades:{}", config.path.display())).unwrap();
+        assert!(!job.trading_days_only());
+        let summary = scheduler.run_job(&job, at).await.unwrap();
+        assert_eq!(summary, "1 orders matched, 1 missing, 0 extra, 0 mismatched");
+        match breaks.recv().await.unwrap() {
+            EngineEvent::TradeBreak { symbol, kind, .. } => assert_eq!((symbol.as_str(), kind.as_str()), ("BTCUSDT", "missing")),
+            other => panic!("unexpected event {:?}", other),
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotate_keeps_newest_copies() {
+        let dir = std::env::temp_dir().join(format!("hft_rotate_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = dir.join("engine.log");
+        for day in 1..=9 {
+            std::fs::write(&log, format!("day {}\n", day)).unwrap();
+            rotate(&log, NaiveDate::from_ymd_opt(2026, 10, day).unwrap()).unwrap();
+        }
+        assert_eq!(std::fs::read(&log).unwrap().len(), 0);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), ROTATED_LOGS_KEPT + 1);
+        assert!(!dir.join("engine.log.2026-10-02").exists());
+        assert_eq!(std::fs::read_to_string(dir.join("engine.log.2026-10-09")).unwrap(), "day 9\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,296 @@
+//! Shared high-resolution timers for strategies and algos.
+//!
+//! One task drives every registered timer off a hashed timer wheel, so a
+//! requote every 50 ms and a TWAP slice every 5 s share a wake-up instead of
+//! each running its own `tokio::time` loop. Timers due within the same tick
+//! fire together, and repeating timers stay on their period's grid rather
+//! than drifting by the time their callbacks take.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tracing::debug;
+
+use crate::metrics::TIMER_LAG;
+
+/// Slots in a wheel; deadlines further out than one turn wait extra turns
+const WHEEL_SLOTS: usize = 1024;
+
+/// Resolution of the timer service; timers due within a tick coalesce
+pub const TIMER_TICK: Duration = Duration::from_millis(1);
+
+/// Hashed timer wheel. Scheduling and expiry are O(1) per entry regardless
+/// of how many are pending.
+pub(crate) struct TimerWheel<T> {
+    tick: Duration,
+    origin: Instant,
+    /// Last tick that has been expired
+    current: u64,
+    /// `(deadline tick, entry)` bucketed by deadline tick
+    slots: Vec<Vec<(u64, T)>>,
+}
+
+impl<T> TimerWheel<T> {
+    pub(crate) fn new(tick: Duration) -> Self {
+        Self {
+            tick,
+            origin: Instant::now(),
+            current: 0,
+            slots: (0..WHEEL_SLOTS).map(|_| Vec::new()).collect(),
+        }
+    }
+
+    fn tick_of(&self, at: Instant) -> u64 {
+        (at.saturating_duration_since(self.origin).as_nanos() / self.tick.as_nanos()) as u64
+    }
+
+    pub(crate) fn schedule(&mut self, entry: T, deadline: Instant) {
+        let tick = self.tick_of(deadline).max(self.current + 1);
+        self.slots[tick as usize % WHEEL_SLOTS].push((tick, entry));
+    }
+
+    /// Entries whose deadline tick is at or before `now`'s
+    pub(crate) fn advance(&mut self, now: Instant) -> Vec<T> {
+        let target = self.tick_of(now);
+        let mut due = Vec::new();
+        let steps = target.saturating_sub(self.current).min(WHEEL_SLOTS as u64);
+        for step in 1..=steps {
+            let slot = &mut self.slots[(self.current + step) as usize % WHEEL_SLOTS];
+            let mut i = 0;
+            while i < slot.len() {
+                if slot[i].0 <= target {
+                    due.push(slot.swap_remove(i).1);
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        self.current = self.current.max(target);
+        due
+    }
+
+    /// Start of the first tick `advance` has not expired
+    fn next_tick_at(&self) -> Instant {
+        self.origin + Duration::from_nanos((self.tick.as_nanos() as u64).saturating_mul(self.current + 1))
+    }
+}
+
+/// Identifies a registered timer, for cancelling it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+struct Timer {
+    name: String,
+    /// Repeats at this period when set
+    period: Option<Duration>,
+    deadline: Instant,
+    callback: Box<dyn FnMut() + Send>,
+}
+
+struct Timers {
+    wheel: TimerWheel<TimerId>,
+    timers: HashMap<TimerId, Timer>,
+    /// Timers whose callbacks are running, and whether they were cancelled
+    /// meanwhile
+    firing: HashMap<TimerId, bool>,
+    next_id: u64,
+}
+
+/// Timers strategies and algos register callbacks on, driven by one task.
+///
+/// Callbacks run on the timer task and should only hand work off, e.g. by
+/// sending on a channel; they may register and cancel timers.
+#[derive(Clone)]
+pub struct TimerService {
+    inner: Arc<Mutex<Timers>>,
+    wake: Arc<Notify>,
+}
+
+impl Default for TimerService {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Timers {
+                wheel: TimerWheel::new(TIMER_TICK),
+                timers: HashMap::new(),
+                firing: HashMap::new(),
+                next_id: 0,
+            })),
+            wake: Arc::new(Notify::new()),
+        }
+    }
+}
+
+impl TimerService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `callback` every `period`, first one period from now
+    pub fn every(&self, name: &str, period: Duration, callback: impl FnMut() + Send + 'static) -> TimerId {
+        let period = period.max(TIMER_TICK);
+        self.register(name, Some(period), period, Box::new(callback))
+    }
+
+    /// Call `callback` once after `delay`
+    pub fn after(&self, name: &str, delay: Duration, callback: impl FnOnce() + Send + 'static) -> TimerId {
+        let mut callback = Some(callback);
+        self.register(name, None, delay, Box::new(move || {
+            if let Some(callback) = callback.take() {
+                callback();
+            }
+        }))
+    }
+
+    fn register(&self, name: &str, period: Option<Duration>, delay: Duration, callback: Box<dyn FnMut() + Send>) -> TimerId {
+        let deadline = Instant::now() + delay;
+        let mut inner = self.inner.lock().unwrap();
+        let id = TimerId(inner.next_id);
+        inner.next_id += 1;
+        inner.wheel.schedule(id, deadline);
+        inner.timers.insert(id, Timer { name: name.to_string(), period, deadline, callback });
+        drop(inner);
+        self.wake.notify_one();
+        id
+    }
+
+    /// Stop a timer; false when it already fired once or was cancelled
+    pub fn cancel(&self, id: TimerId) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.timers.remove(&id).is_some() {
+            return true;
+        }
+        match inner.firing.get_mut(&id) {
+            Some(cancelled) => !std::mem::replace(cancelled, true),
+            None => false,
+        }
+    }
+
+    /// Timers waiting to fire
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().timers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// When the earliest timer comes due, no sooner than the wheel can
+    /// expire it
+    fn next_deadline(&self) -> Option<Instant> {
+        let inner = self.inner.lock().unwrap();
+        let earliest = inner.timers.values().map(|t| t.deadline).min()?;
+        Some(earliest.max(inner.wheel.next_tick_at()))
+    }
+
+    /// Run the callbacks of timers due at `now`, returning how many ran
+    pub fn fire_due(&self, now: Instant) -> usize {
+        let due: Vec<(TimerId, Timer)> = {
+            let mut inner = self.inner.lock().unwrap();
+            let ids = inner.wheel.advance(now);
+            let due: Vec<_> = ids.into_iter()
+                .filter_map(|id| inner.timers.remove(&id).map(|timer| (id, timer)))
+                .collect();
+            for (id, _) in &due {
+                inner.firing.insert(*id, false);
+            }
+            due
+        };
+
+        let mut fired = Vec::with_capacity(due.len());
+        for (id, mut timer) in due {
+            TIMER_LAG.with_label_values(&[&timer.name])
+                .observe(now.saturating_duration_since(timer.deadline).as_secs_f64());
+            (timer.callback)();
+            fired.push((id, timer));
+        }
+
+        let count = fired.len();
+        let mut inner = self.inner.lock().unwrap();
+        for (id, mut timer) in fired {
+            let cancelled = inner.firing.remove(&id).unwrap_or(false);
+            let Some(period) = timer.period.filter(|_| !cancelled) else { continue };
+            // Stay on the period's grid, skipping fires that were missed
+            let behind = now.saturating_duration_since(timer.deadline);
+            let periods = (behind.as_nanos() / period.as_nanos()) as u32 + 1;
+            if periods > 1 {
+                debug!(timer = %timer.name, missed = periods - 1, "Timer fell behind, missed fires skipped");
+            }
+            timer.deadline += period * periods;
+            inner.wheel.schedule(id, timer.deadline);
+            inner.timers.insert(id, timer);
+        }
+        count
+    }
+
+    /// Fire timers as they come due, sleeping until the next deadline
+    pub async fn run(self) {
+        loop {
+            match self.next_deadline() {
+                Some(deadline) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(deadline.into()) => {}
+                        // A timer was registered, maybe due sooner
+                        _ = self.wake.notified() => continue,
+                    }
+                    self.fire_due(Instant::now());
+                }
+                None => self.wake.notified().await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn counter() -> (Arc<AtomicUsize>, impl FnMut() + Send + 'static) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&count);
+        (count, move || { counted.fetch_add(1, Ordering::SeqCst); })
+    }
+
+    #[test]
+    fn test_repeating_timers_coalesce_and_cancel() {
+        let timers = TimerService::new();
+        let start = Instant::now();
+        let (requotes, requote) = counter();
+        let (slices, slice) = counter();
+        let (once, fire_once) = counter();
+        let requote = timers.every("requote", Duration::from_millis(50), requote);
+        timers.every("twap_slice", Duration::from_secs(5), slice);
+        timers.after("once", Duration::from_millis(50), fire_once);
+        assert_eq!(timers.len(), 3);
+
+        assert_eq!(timers.fire_due(start + Duration::from_millis(20)), 0);
+        // Both 50 ms timers fire in the same pass
+        assert_eq!(timers.fire_due(start + Duration::from_millis(60)), 2);
+        assert_eq!((requotes.load(Ordering::SeqCst), once.load(Ordering::SeqCst)), (1, 1));
+        assert_eq!(timers.len(), 2);
+
+        // Far behind: one fire, then back on the 50 ms grid
+        assert_eq!(timers.fire_due(start + Duration::from_millis(1020)), 1);
+        assert_eq!(timers.fire_due(start + Duration::from_millis(1040)), 0);
+        assert_eq!(timers.fire_due(start + Duration::from_millis(1060)), 1);
+        assert_eq!(requotes.load(Ordering::SeqCst), 3);
+
+        assert!(timers.cancel(requote));
+        assert!(!timers.cancel(requote));
+        assert_eq!(timers.fire_due(start + Duration::from_millis(5010)), 1);
+        assert_eq!((requotes.load(Ordering::SeqCst), slices.load(Ordering::SeqCst)), (3, 1));
+    }
+
+    #[tokio::test]
+    async fn test_service_task_fires_timers() {
+        let timers = TimerService::new();
+        tokio::spawn(timers.clone().run());
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        timers.every("requote", Duration::from_millis(5), move || { let _ = tx.send(()); });
+
+        for _ in 0..3 {
+            tokio::time::timeout(Duration::from_secs(1), rx.recv()).await.unwrap().unwrap();
+        }
+    }
+}
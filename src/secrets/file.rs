@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::error::HftError;
+
+const FORMAT_VERSION: u32 = 1;
+const KDF: &str = "pbkdf2-sha256";
+const DEFAULT_ITERATIONS: u32 = 600_000;
+
+/// On-disk form of an encrypted secrets file: a JSON object of secret names
+/// to values, sealed with AES-256-GCM under a key derived from a passphrase
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedFile {
+    version: u32,
+    kdf: String,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Key<Aes256Gcm> {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, &mut key);
+    key.into()
+}
+
+fn decode(field: &str, value: &str) -> Result<Vec<u8>, HftError> {
+    BASE64.decode(value).map_err(|e| HftError::Config(format!("Invalid {} in secrets file: {}", field, e)))
+}
+
+/// Encrypt `secrets` under `passphrase`
+pub fn seal(secrets: &BTreeMap<String, String>, passphrase: &str) -> Result<String, HftError> {
+    seal_with_iterations(secrets, passphrase, DEFAULT_ITERATIONS)
+}
+
+pub(super) fn seal_with_iterations(secrets: &BTreeMap<String, String>, passphrase: &str, iterations: u32) -> Result<String, HftError> {
+    let salt: [u8; 16] = rand::random();
+    let nonce: [u8; 12] = rand::random();
+    let plaintext = serde_json::to_vec(secrets)
+        .map_err(|e| HftError::Serialization(format!("Failed to serialize secrets: {}", e)))?;
+    let ciphertext = Aes256Gcm::new(&derive_key(passphrase, &salt, iterations))
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|_| HftError::Config("Failed to encrypt secrets".to_string()))?;
+
+    let sealed = SealedFile {
+        version: FORMAT_VERSION,
+        kdf: KDF.to_string(),
+        iterations,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+    serde_json::to_string_pretty(&sealed)
+        .map_err(|e| HftError::Serialization(format!("Failed to serialize secrets file: {}", e)))
+}
+
+/// Decrypt a sealed secrets file's contents
+pub fn open(sealed: &str, passphrase: &str) -> Result<BTreeMap<String, String>, HftError> {
+    let sealed: SealedFile = serde_json::from_str(sealed)
+        .map_err(|e| HftError::Config(format!("Invalid secrets file: {}", e)))?;
+    if sealed.version != FORMAT_VERSION || sealed.kdf != KDF {
+        return Err(HftError::Config(format!("Unsupported secrets file version {} ({})", sealed.version, sealed.kdf)));
+    }
+    let nonce = decode("nonce", &sealed.nonce)?;
+    if nonce.len() != 12 {
+        return Err(HftError::Config("Invalid nonce in secrets file".to_string()));
+    }
+
+    let key = derive_key(passphrase, &decode("salt", &sealed.salt)?, sealed.iterations);
+    let plaintext = Aes256Gcm::new(&key)
+        .decrypt(Nonce::from_slice(&nonce), decode("ciphertext", &sealed.ciphertext)?.as_slice())
+        .map_err(|_| HftError::Config("Failed to decrypt secrets file: wrong passphrase or corrupt file".to_string()))?;
+    serde_json::from_slice(&plaintext).map_err(|e| HftError::Config(format!("Invalid secrets: {}", e)))
+}
+
+/// Read and decrypt the secrets file at `path`
+pub fn load(path: &Path, passphrase: &str) -> Result<BTreeMap<String, String>, HftError> {
+    let sealed = std::fs::read_to_string(path)
+        .map_err(|e| HftError::Config(format!("Failed to read secrets file {}: {}", path.display(), e)))?;
+    open(&sealed, passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let secrets = BTreeMap::from([
+            ("BINANCE_API_KEY".to_string(), "key".to_string()),
+            ("BINANCE_API_SECRET".to_string(), "secret".to_string()),
+        ]);
+        let sealed = seal_with_iterations(&secrets, "correct horse", 1000).unwrap();
+        assert!(!sealed.contains("secret\""));
+
+        assert_eq!(open(&sealed, "correct horse").unwrap(), secrets);
+        assert!(open(&sealed, "wrong").is_err());
+
+        // Tampering is detected rather than decrypting to garbage
+        let mut file: SealedFile = serde_json::from_str(&sealed).unwrap();
+        let mut ciphertext = BASE64.decode(&file.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        file.ciphertext = BASE64.encode(ciphertext);
+        assert!(open(&serde_json::to_string(&file).unwrap(), "correct horse").is_err());
+    }
+}
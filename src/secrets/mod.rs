@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::error::HftError;
+
+pub mod file;
+pub mod vault;
+pub use vault::VaultConfig;
+
+/// The secrets file passphrase, read from the file at
+/// `HFT_SECRETS_PASSPHRASE_FILE` or else from `HFT_SECRETS_PASSPHRASE`
+pub fn passphrase_from_env() -> Option<String> {
+    match std::env::var("HFT_SECRETS_PASSPHRASE_FILE") {
+        Ok(path) => match std::fs::read_to_string(&path) {
+            Ok(passphrase) => Some(passphrase.trim_end_matches(['\r', '\n']).to_string()),
+            Err(e) => {
+                warn!(path = %path, error = %e, "Failed to read secrets passphrase file");
+                None
+            }
+        },
+        Err(_) => std::env::var("HFT_SECRETS_PASSPHRASE").ok(),
+    }
+}
+
+/// Where credentials come from: an encrypted secrets file, Vault, or both,
+/// with Vault taking precedence for names in both
+#[derive(Debug, Clone, Default)]
+pub struct SecretsConfig {
+    pub file: Option<PathBuf>,
+    pub passphrase: Option<String>,
+    pub vault: Option<VaultConfig>,
+}
+
+impl SecretsConfig {
+    /// Read `HFT_SECRETS_FILE` with the passphrase of
+    /// [`passphrase_from_env`], and the Vault settings of [`VaultConfig::from_env`]. Returns `None` when no
+    /// backend is configured.
+    pub fn from_env() -> Option<Self> {
+        let file = std::env::var("HFT_SECRETS_FILE").ok().filter(|p| !p.is_empty()).map(PathBuf::from);
+        let vault = VaultConfig::from_env();
+        if file.is_none() && vault.is_none() {
+            return None;
+        }
+        Some(Self { file, passphrase: passphrase_from_env(), vault })
+    }
+
+    /// Decrypt and fetch every configured backend
+    pub async fn load(&self) -> Result<Secrets, HftError> {
+        let mut values = BTreeMap::new();
+        if let Some(path) = &self.file {
+            let passphrase = self.passphrase.as_deref()
+                .ok_or_else(|| HftError::Config("HFT_SECRETS_FILE is set without a passphrase".to_string()))?;
+            values.extend(file::load(path, passphrase)?);
+            info!(path = %path.display(), count = values.len(), "Loaded secrets file");
+        }
+        if let Some(vault) = &self.vault {
+            let fetched = vault.fetch().await?;
+            info!(path = %vault.path, count = fetched.len(), "Loaded secrets from Vault");
+            values.extend(fetched);
+        }
+        Ok(Secrets { values: Arc::new(values), plain_env: false })
+    }
+}
+
+/// Credentials for venue constructors, resolved once at startup.
+///
+/// Values never appear in `Debug` output. Without a configured backend
+/// secrets are read from plain environment variables, for development only.
+#[derive(Clone, Default)]
+pub struct Secrets {
+    values: Arc<BTreeMap<String, String>>,
+    /// Fall back to the process environment
+    plain_env: bool,
+}
+
+impl fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Secrets")
+            .field("names", &self.values.keys().collect::<Vec<_>>())
+            .field("plain_env", &self.plain_env)
+            .finish()
+    }
+}
+
+impl Secrets {
+    /// No secrets; every lookup misses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load from the backends of [`SecretsConfig::from_env`], or read
+    /// plain environment variables when none is configured
+    pub async fn from_env() -> Result<Self, HftError> {
+        match SecretsConfig::from_env() {
+            Some(config) => config.load().await,
+            None => {
+                warn!("No secrets backend configured, reading credentials from plain environment variables");
+                Ok(Self { values: Arc::default(), plain_env: true })
+            }
+        }
+    }
+
+    pub fn with_secret(mut self, name: &str, value: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.values).insert(name.to_string(), value.into());
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        match self.values.get(name) {
+            Some(value) => Some(value.clone()),
+            None if self.plain_env => std::env::var(name).ok(),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_load_secrets_file() {
+        let dir = std::env::temp_dir().join(format!("hft-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("secrets.json");
+        let values = BTreeMap::from([("BINANCE_API_KEY".to_string(), "key".to_string())]);
+        std::fs::write(&path, file::seal_with_iterations(&values, "passphrase", 1000).unwrap()).unwrap();
+
+        let mut config = SecretsConfig { file: Some(path), passphrase: Some("passphrase".to_string()), vault: None };
+        let secrets = config.load().await.unwrap();
+        assert_eq!(secrets.get("BINANCE_API_KEY").as_deref(), Some("key"));
+        // Loaded secrets never fall back to the environment
+        assert_eq!(secrets.get("PATH"), None);
+        assert!(!format!("{:?}", secrets).contains("\"key\""));
+
+        config.passphrase = None;
+        assert!(config.load().await.is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use serde_json::Value;
+
+use crate::error::HftError;
+
+/// Where secrets are read from in HashiCorp Vault
+#[derive(Clone)]
+pub struct VaultConfig {
+    /// e.g. `https://vault.internal:8200`
+    pub addr: String,
+    pub token: String,
+    /// Path under `/v1/`, e.g. `secret/data/hft` for a KV v2 mount
+    pub path: String,
+    pub namespace: Option<String>,
+}
+
+impl std::fmt::Debug for VaultConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultConfig")
+            .field("addr", &self.addr)
+            .field("path", &self.path)
+            .field("namespace", &self.namespace)
+            .finish_non_exhaustive()
+    }
+}
+
+impl VaultConfig {
+    /// Read `VAULT_ADDR`, `VAULT_TOKEN`, `VAULT_NAMESPACE` and
+    /// `HFT_VAULT_PATH`; returns `None` unless the address and path are set
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var("HFT_VAULT_PATH").ok().filter(|p| !p.is_empty())?;
+        Some(Self {
+            addr: std::env::var("VAULT_ADDR").ok().filter(|a| !a.is_empty())?,
+            token: std::env::var("VAULT_TOKEN").unwrap_or_default(),
+            path,
+            namespace: std::env::var("VAULT_NAMESPACE").ok().filter(|n| !n.is_empty()),
+        })
+    }
+
+    /// Fetch the secrets at `path`, from a KV v2 or v1 mount
+    pub async fn fetch(&self) -> Result<BTreeMap<String, String>, HftError> {
+        let url = format!("{}/v1/{}", self.addr.trim_end_matches('/'), self.path.trim_start_matches('/'));
+        let mut request = reqwest::Client::new().get(&url).header("X-Vault-Token", &self.token);
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        let response = request.send().await
+            .map_err(|e| HftError::Config(format!("Vault request failed: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(HftError::Config(format!("Vault returned {} for {}", status, self.path)));
+        }
+        let body: Value = response.json().await
+            .map_err(|e| HftError::Config(format!("Invalid Vault response: {}", e)))?;
+        parse_secrets(&body)
+    }
+}
+
+fn parse_secrets(body: &Value) -> Result<BTreeMap<String, String>, HftError> {
+    let data = &body["data"];
+    // KV v2 nests the secret under `data.data`, next to `data.metadata`
+    let data = if data["metadata"].is_object() { &data["data"] } else { data };
+    let Some(data) = data.as_object() else {
+        return Err(HftError::Config("Vault response has no secret data".to_string()));
+    };
+    Ok(data.iter()
+        .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use warp::Filter;
+
+    #[tokio::test]
+    async fn test_fetch_kv_v2() {
+        let route = warp::path!("v1" / "secret" / "data" / "hft")
+            .and(warp::header::<String>("x-vault-token"))
+            .map(|token: String| {
+                if token != "s.token" {
+                    return warp::reply::with_status(warp::reply::json(&json!({ "errors": ["permission denied"] })), warp::http::StatusCode::FORBIDDEN);
+                }
+                warp::reply::with_status(warp::reply::json(&json!({
+                    "data": {
+                        "data": { "BINANCE_API_KEY": "key", "BINANCE_API_SECRET": "secret", "ignored": 1 },
+                        "metadata": { "version": 3 },
+                    }
+                })), warp::http::StatusCode::OK)
+            });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let mut config = VaultConfig {
+            addr: format!("http://{}", addr),
+            token: "s.token".to_string(),
+            path: "secret/data/hft".to_string(),
+            namespace: None,
+        };
+        let secrets = config.fetch().await.unwrap();
+        assert_eq!(secrets.len(), 2);
+        assert_eq!(secrets["BINANCE_API_SECRET"], "secret");
+        assert!(!format!("{:?}", config).contains("s.token"));
+
+        config.token = "wrong".to_string();
+        assert!(config.fetch().await.is_err());
+
+        // KV v1 returns the secret directly under `data`
+        let v1 = parse_secrets(&json!({ "data": { "BINANCE_API_KEY": "key" } })).unwrap();
+        assert_eq!(v1["BINANCE_API_KEY"], "key");
+    }
+}
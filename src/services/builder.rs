@@ -0,0 +1,431 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::time::Duration;
+
+use crate::book::{BookBuilder, BookChecksums, BookDelta, BookGauges, Compactor, OrderBook};
+use crate::events::EventBus;
+use crate::execution::{ExecutionEngine, FillRouter, OrderTracker};
+use crate::feed::FeedPublisher;
+use crate::gateways::{order::OrderGateway, quote::QuoteGateway, FailoverPolicies};
+use crate::health::{HealthRegistry, Probe};
+use crate::incident::IncidentLog;
+use crate::instruments::InstrumentMap;
+use crate::metrics::MetricsRegistry;
+use crate::report::{ReportConfig, Reporter};
+use crate::risk::{ExposureLimits, LossLimits, RiskManager, TradingToggles};
+use crate::scheduler::TimerService;
+use crate::secrets::Secrets;
+use crate::signals::Signals;
+use crate::strategy::{ParameterStore, Shadows, Strategy, StrategyPlugin};
+use crate::types::{Fill, Order, Quote, Symbol};
+use crate::venues::{LatencyInjection, ReconnectPolicies, Regions, VenueAdapter, VenueRegistry, VenueTransports};
+use super::{Services, Supervisor};
+
+const DEFAULT_QUOTE_CAPACITY: usize = 1000;
+const DEFAULT_ORDER_CAPACITY: usize = 1000;
+
+/// Strategies loaded at runtime waiting for the strategy runner
+const PLUGIN_CAPACITY: usize = 16;
+
+/// What a venue needs from the engine to be wired in: where to send its
+/// quotes, depth updates and fills, where to report connectivity, how to
+/// reconnect, how to reach the network and its credentials
+pub struct VenueContext {
+    pub quote_tx: mpsc::Sender<Quote>,
+    pub book_tx: mpsc::Sender<BookDelta>,
+    /// Fills on the engine's orders, from the venue's user data stream
+    pub fill_tx: mpsc::Sender<Fill>,
+    pub events: EventBus,
+    pub reconnect: ReconnectPolicies,
+    pub transports: VenueTransports,
+    pub secrets: Secrets,
+    /// Keeps venues' latest raw messages for incident dumps
+    pub incidents: IncidentLog,
+}
+
+type VenueFactory = Box<dyn FnOnce(&VenueContext) -> Arc<dyn VenueAdapter> + Send>;
+
+/// Assembles [`Services`] from the venues, strategies and channel sizes
+/// the caller chooses, for tests and embedders that do not want the
+/// environment-driven defaults of [`Services::new`]
+pub struct ServicesBuilder {
+    venues: Vec<VenueFactory>,
+    strategies: Vec<Box<dyn StrategyPlugin>>,
+    quote_capacity: usize,
+    order_capacity: usize,
+    loss_limits: LossLimits,
+    exposure_limits: ExposureLimits,
+    params: ParameterStore,
+    quote_dedup: bool,
+    book_gauges: bool,
+    reconnect: ReconnectPolicies,
+    transports: VenueTransports,
+    secrets: Secrets,
+    metrics: MetricsRegistry,
+    latency: LatencyInjection,
+    regions: Regions,
+    incidents: IncidentLog,
+}
+
+impl Default for ServicesBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServicesBuilder {
+    pub fn new() -> Self {
+        Self {
+            venues: Vec::new(),
+            strategies: Vec::new(),
+            quote_capacity: DEFAULT_QUOTE_CAPACITY,
+            order_capacity: DEFAULT_ORDER_CAPACITY,
+            loss_limits: LossLimits::default(),
+            exposure_limits: ExposureLimits::default(),
+            params: ParameterStore::new(),
+            quote_dedup: false,
+            book_gauges: false,
+            reconnect: ReconnectPolicies::default(),
+            transports: VenueTransports::default(),
+            secrets: Secrets::new(),
+            metrics: MetricsRegistry::global(),
+            latency: LatencyInjection::default(),
+            regions: Regions::default(),
+            incidents: IncidentLog::default(),
+        }
+    }
+
+    /// Register a venue. `venue` is called during [`build`](Self::build)
+    /// with the engine's quote channel and event bus.
+    pub fn with_venue<F>(mut self, venue: F) -> Self
+    where
+        F: FnOnce(&VenueContext) -> Arc<dyn VenueAdapter> + Send + 'static,
+    {
+        self.venues.push(Box::new(venue));
+        self
+    }
+
+    /// Load a strategy once the services are built
+    pub fn with_strategy(mut self, plugin: Box<dyn StrategyPlugin>) -> Self {
+        self.strategies.push(plugin);
+        self
+    }
+
+    /// Quotes buffered ahead of each venue's reader and between the readers
+    /// and the book builder
+    pub fn with_quote_capacity(mut self, capacity: usize) -> Self {
+        self.quote_capacity = capacity;
+        self
+    }
+
+    /// Orders buffered between strategies and the order gateway
+    pub fn with_order_capacity(mut self, capacity: usize) -> Self {
+        self.order_capacity = capacity;
+        self
+    }
+
+    pub fn with_loss_limits(mut self, limits: LossLimits) -> Self {
+        self.loss_limits = limits;
+        self
+    }
+
+    /// Gross and net notional caps every order is checked against before
+    /// the order gateway sends it
+    pub fn with_exposure_limits(mut self, limits: ExposureLimits) -> Self {
+        self.exposure_limits = limits;
+        self
+    }
+
+    pub fn with_strategy_params(mut self, params: ParameterStore) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Drop repeated quotes before they reach the book builder
+    pub fn with_quote_dedup(mut self) -> Self {
+        self.quote_dedup = true;
+        self
+    }
+
+    /// Export each book's best bid, best ask, spread and staleness as gauges
+    pub fn with_book_gauges(mut self) -> Self {
+        self.book_gauges = true;
+        self
+    }
+
+    /// Reconnect policies handed to venues through [`VenueContext`]
+    pub fn with_reconnect_policies(mut self, policies: ReconnectPolicies) -> Self {
+        self.reconnect = policies;
+        self
+    }
+
+    /// Proxies and CA certificates handed to venues through [`VenueContext`]
+    pub fn with_venue_transports(mut self, transports: VenueTransports) -> Self {
+        self.transports = transports;
+        self
+    }
+
+    /// Credentials handed to venues through [`VenueContext`]
+    pub fn with_secrets(mut self, secrets: Secrets) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// Registry the engine's metrics are exported from, the global
+    /// `prometheus` one by default; [`MetricsRegistry::isolated`] keeps
+    /// several engines in one process, or tests, apart
+    pub fn with_metrics_registry(mut self, metrics: MetricsRegistry) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Delay venues' quotes and orders as if the engine ran further from
+    /// them; none by default
+    pub fn with_latency_injection(mut self, latency: LatencyInjection) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Regions of the engine and its venues, exported as metrics and
+    /// preferred when failing over
+    pub fn with_regions(mut self, regions: Regions) -> Self {
+        self.regions = regions;
+        self
+    }
+
+    /// Ring buffers of venue messages and engine events for post-incident
+    /// dumps, kept under the defaults unless set
+    pub fn with_incident_log(mut self, incidents: IncidentLog) -> Self {
+        self.incidents = incidents;
+        self
+    }
+
+    pub async fn build(self) -> Services {
+        self.metrics.register_engine_metrics();
+        let (quote_tx, quote_rx) = mpsc::channel(self.quote_capacity);
+        let (book_tx, book_rx) = mpsc::channel(self.quote_capacity);
+        let (order_tx, order_rx) = mpsc::channel(self.order_capacity);
+        let (cancel_tx, cancel_rx) = mpsc::channel(self.order_capacity);
+        let (amend_tx, amend_rx) = mpsc::channel(self.order_capacity);
+        let (fill_tx, fill_rx) = mpsc::channel(self.order_capacity);
+        let (strategy_fill_tx, strategy_fill_rx) = mpsc::channel(self.order_capacity);
+        let books = Arc::new(RwLock::new(HashMap::new()));
+        let events = EventBus::default();
+        let health = HealthRegistry::new();
+        let orders = Arc::new(OrderTracker::new());
+        let feed = FeedPublisher::default();
+        let signals = Signals::default();
+        let shadows = Shadows::default();
+        let (plugin_tx, plugin_rx) = mpsc::channel(PLUGIN_CAPACITY);
+        let risk = Arc::new(RiskManager::new(self.loss_limits)
+            .with_exposure_limits(self.exposure_limits)
+            .with_event_bus(events.clone())
+            .with_fee_model(signals.fees.clone())
+            .with_greeks(signals.greeks.clone())
+            .with_gateway(order_tx.clone(), cancel_tx.clone()));
+        let reports = Reporter::new(ReportConfig::default(), Arc::clone(&risk));
+
+        let venues = VenueRegistry::new();
+
+        let mut quote_gateway = QuoteGateway::new(quote_tx)
+            .with_venues(venues.clone())
+            .with_reader_capacity(self.quote_capacity)
+            .with_heartbeat(health.register("quote_gateway", Probe::Readiness, Some(Duration::from_secs(30))));
+        if self.quote_dedup {
+            quote_gateway = quote_gateway.with_dedup();
+        }
+        let quote_gateway = Arc::new(quote_gateway);
+
+        // Each venue gets a quote channel of its own, read by its own task
+        for venue in self.venues {
+            let (venue_quote_tx, venue_quote_rx) = mpsc::channel(self.quote_capacity);
+            let context = VenueContext {
+                quote_tx: venue_quote_tx,
+                book_tx: book_tx.clone(),
+                fill_tx: fill_tx.clone(),
+                events: events.clone(),
+                reconnect: self.reconnect.clone(),
+                transports: self.transports.clone(),
+                secrets: self.secrets.clone(),
+                incidents: self.incidents.clone(),
+            };
+            let venue = venue(&context);
+            let name = venue.name().await;
+            health.register_venue(&name);
+            self.regions.publish(&name);
+            let (venue, venue_quote_rx) = self.latency.apply(venue, &name, venue_quote_rx);
+            quote_gateway.add_reader(&name, venue_quote_rx);
+            quote_gateway.add_venue(venue).await;
+        }
+
+        let mut services = Services {
+            quote_gateway,
+            order_gateway: Arc::new(Mutex::new(OrderGateway {
+                venues: venues.clone(),
+                order_rx,
+                cancel_rx: Some(cancel_rx),
+                amend_rx: Some(amend_rx),
+                events: events.clone(),
+                heartbeat: Some(health.register("order_gateway", Probe::Liveness, Some(Duration::from_secs(5)))),
+                orders: Arc::clone(&orders),
+                sinks: Vec::new(),
+                leadership: None,
+                audit: None,
+                failover: FailoverPolicies::default(),
+                down: HashMap::new(),
+                reconcile_soon: false,
+                queued: VecDeque::new(),
+                instruments: Arc::new(InstrumentMap::new()),
+                chaos: None,
+                fees: signals.fees.clone(),
+                risk: Some(Arc::clone(&risk)),
+                recent_orders: Default::default(),
+                regions: self.regions.clone(),
+            })),
+            book_builder: Arc::new(Mutex::new(BookBuilder {
+                books: Arc::clone(&books),
+                quote_rx,
+                heartbeat: Some(health.register("book_builder", Probe::Liveness, Some(Duration::from_secs(5)))),
+                feed: Some(feed.clone()),
+                stream: None,
+                gauges: self.book_gauges.then(BookGauges::default),
+                deltas: Some(book_rx),
+                checksums: BookChecksums::default(),
+                resyncing: HashSet::new(),
+                recorder: None,
+                compactor: Compactor::default(),
+                snapshots: signals.books.clone(),
+                risk: Some(Arc::clone(&risk)),
+            })),
+            strategy: Arc::new(Mutex::new(Strategy {
+                books: Arc::clone(&books),
+                order_tx: order_tx.clone(),
+                plugins: Vec::new(),
+                audit: None,
+                toggles: risk.toggles(),
+                signals: signals.clone(),
+                trading: Some(risk.trading_state()),
+                shadows: shadows.clone(),
+                timer_rx: None,
+                fill_rx: Some(strategy_fill_rx),
+                plugin_rx: Some(plugin_rx),
+            })),
+            plugin_tx,
+            shadows,
+            fill_router: Arc::new(Mutex::new(FillRouter {
+                fill_rx,
+                risk: Arc::clone(&risk),
+                orders: Arc::clone(&orders),
+                strategies: strategy_fill_tx,
+                sinks: Vec::new(),
+            })),
+            fill_tx,
+            cancel_tx,
+            amend_tx,
+            brackets: None,
+            legs: None,
+            trailing_stops: None,
+            execution: ExecutionEngine {
+                order_tx,
+                risk: Arc::clone(&risk),
+                audit: None,
+            },
+            risk,
+            events,
+            health,
+            books,
+            orders,
+            feed,
+            leadership: None,
+            signals,
+            params: self.params,
+            venues,
+            instruments: Arc::new(InstrumentMap::new()),
+            supervisor: Supervisor::default(),
+            restart_policies: HashMap::new(),
+            secrets: self.secrets,
+            audit: None,
+            scheduler: None,
+            quality: None,
+            liquidation: None,
+            outage: None,
+            expiries: None,
+            stress: None,
+            balances: None,
+            warmup: Duration::ZERO,
+            timers: TimerService::new(),
+            stream: None,
+            reports,
+            metrics: self.metrics,
+            metrics_backend: None,
+            regions: self.regions,
+            incidents: self.incidents,
+        };
+        for plugin in self.strategies {
+            services.add_strategy(plugin);
+        }
+        services
+    }
+}
+
+/// Shared handles to the engine's components, for inspecting and driving a
+/// running engine from tests and embedding code
+#[derive(Clone)]
+pub struct ServiceHandles {
+    /// Shared with both gateways; venues added here are subscribed to on
+    /// the next `subscribe` and routable immediately
+    pub venues: VenueRegistry,
+    pub books: Arc<RwLock<HashMap<Symbol, OrderBook>>>,
+    pub orders: Arc<OrderTracker>,
+    pub risk: Arc<RiskManager>,
+    pub toggles: Arc<TradingToggles>,
+    /// Feeds the order gateway directly; the gateway runs the same risk
+    /// checks as the execution engine, without auditing the request
+    pub order_tx: mpsc::Sender<Order>,
+    pub events: EventBus,
+    pub health: HealthRegistry,
+    pub feed: FeedPublisher,
+    pub signals: Signals,
+    pub params: ParameterStore,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::mock_venue::{MockVenue, MockVenueConfig};
+
+    struct Idle;
+
+    impl StrategyPlugin for Idle {
+        fn name(&self) -> &str {
+            "idle"
+        }
+
+        fn on_quote(&mut self, _quote: &Quote) -> Vec<Order> {
+            Vec::new()
+        }
+
+        fn on_fill(&mut self, _fill: &Fill) -> Vec<Order> {
+            Vec::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_builder_wires_injected_venues() {
+        let services = ServicesBuilder::new()
+            .with_venue(|ctx| Arc::new(MockVenue::new("BUILT", MockVenueConfig::default()).with_quote_sender(ctx.quote_tx.clone())))
+            .with_strategy(Box::new(Idle))
+            .with_order_capacity(16)
+            .build()
+            .await;
+
+        let handles = services.handles();
+        assert_eq!(handles.venues.len(), 1);
+        assert_eq!(handles.venues.names(), vec!["BUILT"]);
+        assert_eq!(services.quote_gateway.venues.len(), 1);
+        assert_eq!(handles.order_tx.max_capacity(), 16);
+        assert_eq!(services.strategy.lock().await.plugins[0].name(), "idle");
+        assert!(handles.health.report(Probe::Readiness).components.contains_key("venue:BUILT"));
+    }
+}
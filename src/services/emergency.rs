@@ -0,0 +1,235 @@
+//! Last-resort handling of a crash.
+//!
+//! A panic outside the supervised components, or a supervised component
+//! that panicked and will not be restarted, leaves the engine in a state
+//! nobody planned for. Once installed, either cancels every open order on
+//! every venue, dumps positions, open orders and the latest engine events,
+//! and exits with [`EMERGENCY_EXIT_CODE`] so the process supervisor can
+//! tell a crash from a clean shutdown.
+//!
+//! Cancels go straight to the venues rather than through the order gateway
+//! and risk checks, whose tasks may be what failed, and every step is
+//! bounded so a lock held by the failed task cannot stop the ones after it.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use chrono::Utc;
+use futures_util::future::join_all;
+use serde::Serialize;
+use tokio::time::timeout;
+use tracing::error;
+
+use crate::error::HftError;
+use crate::execution::{OpenOrder, OrderTracker};
+use crate::incident::{IncidentEntry, IncidentLog};
+use crate::risk::{Position, PositionKey, RiskManager};
+use crate::venues::VenueRegistry;
+
+/// Exit code after a crash was handled, `EX_SOFTWARE` from sysexits
+pub const EMERGENCY_EXIT_CODE: i32 = 70;
+
+/// Longest each cancel and each read of engine state may take
+const STEP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest the whole routine may take before the process exits anyway
+const EMERGENCY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Most recent engine events included in the state dump
+const DUMP_EVENTS: usize = 100;
+
+tokio::task_local! {
+    /// Set while a supervised component runs, whose panics the supervisor
+    /// handles
+    pub(crate) static SUPERVISED: &'static str;
+}
+
+static INSTALLED: OnceLock<Emergency> = OnceLock::new();
+static TRIGGERED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize)]
+struct PositionDump {
+    #[serde(flatten)]
+    key: PositionKey,
+    #[serde(flatten)]
+    position: Position,
+}
+
+/// Engine state at the time of a crash. Positions and open orders are
+/// `None` when they could not be read in time.
+#[derive(Serialize)]
+struct StateDump<'a> {
+    reason: &'a str,
+    dumped_at: i64,
+    /// Outcome of the cancel-all per venue
+    cancels: BTreeMap<String, String>,
+    positions: Option<Vec<PositionDump>>,
+    open_orders: Option<Vec<OpenOrder>>,
+    /// Oldest first
+    events: Vec<IncidentEntry>,
+}
+
+/// What the emergency routine needs from the running engine
+#[derive(Clone)]
+pub struct Emergency {
+    venues: VenueRegistry,
+    risk: Arc<RiskManager>,
+    orders: Arc<OrderTracker>,
+    incidents: IncidentLog,
+}
+
+impl Emergency {
+    pub fn new(venues: VenueRegistry, risk: Arc<RiskManager>, orders: Arc<OrderTracker>, incidents: IncidentLog) -> Self {
+        Self { venues, risk, orders, incidents }
+    }
+
+    /// Cancel every venue's open orders, returning each venue's outcome
+    async fn cancel_all(&self) -> BTreeMap<String, String> {
+        join_all(self.venues.all().into_iter().map(|venue| async move {
+            let outcome = match timeout(STEP_TIMEOUT, venue.cancel_all_orders()).await {
+                Ok(Ok(())) => "cancelled".to_string(),
+                Ok(Err(e)) => e.to_string(),
+                Err(_) => "timed out".to_string(),
+            };
+            (venue.name().await, outcome)
+        })).await.into_iter().collect()
+    }
+
+    /// Cancel every open order, then write the engine's state next to the
+    /// incident dumps, returning the dump's path
+    pub async fn handle(&self, reason: &str) -> Result<PathBuf, HftError> {
+        error!(reason, "Engine crashed, cancelling all orders");
+        let cancels = self.cancel_all().await;
+        let positions = timeout(STEP_TIMEOUT, self.risk.positions()).await.ok()
+            .map(|positions| positions.into_iter().map(|(key, position)| PositionDump { key, position }).collect());
+        let open_orders = timeout(STEP_TIMEOUT, self.orders.open_orders()).await.ok();
+        let mut events: Vec<IncidentEntry> = self.incidents.entries().into_values()
+            .flatten()
+            .filter(|entry| matches!(entry, IncidentEntry::Event { .. }))
+            .collect();
+        events.sort_by_key(IncidentEntry::at);
+        let events = events.split_off(events.len().saturating_sub(DUMP_EVENTS));
+
+        let dumped_at = Utc::now().timestamp_millis();
+        let dump = StateDump { reason, dumped_at, cancels, positions, open_orders, events };
+        std::fs::create_dir_all(self.incidents.dir())?;
+        let path = self.incidents.dir().join(format!("emergency-{}.json", dumped_at));
+        let json = serde_json::to_vec_pretty(&dump)
+            .map_err(|e| HftError::Config(format!("Failed to serialize engine state: {}", e)))?;
+        std::fs::write(&path, json)?;
+        error!(path = %path.display(), "Dumped engine state");
+        Ok(path)
+    }
+
+    /// Handle panics outside the supervised components and supervised
+    /// components that fail for good. Only the first call takes effect.
+    pub fn install(self) {
+        if INSTALLED.set(self).is_err() {
+            return;
+        }
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |panic| {
+            previous(panic);
+            // The supervisor restarts the component, or escalates when it
+            // will not
+            if SUPERVISED.try_with(|_| ()).is_err() {
+                escalate(&format!("panic: {}", panic));
+            }
+        }));
+    }
+}
+
+/// Run the installed emergency routine and exit with
+/// [`EMERGENCY_EXIT_CODE`]; returns at once when none is installed
+pub(crate) fn escalate(reason: &str) {
+    let Some(emergency) = INSTALLED.get() else {
+        return;
+    };
+    if TRIGGERED.swap(true, Ordering::SeqCst) {
+        // Another thread is already handling a crash and will exit
+        loop {
+            std::thread::park();
+        }
+    }
+
+    // The failing thread may be a runtime worker, so the routine gets a
+    // thread and runtime of its own
+    let emergency = emergency.clone();
+    let reason = reason.to_string();
+    let handler = std::thread::Builder::new().name("emergency".to_string()).spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => return error!(error = %e, "Failed to start the emergency runtime"),
+        };
+        match runtime.block_on(timeout(EMERGENCY_TIMEOUT, emergency.handle(&reason))) {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => error!(error = %e, "Failed to dump engine state"),
+            Err(_) => error!("Emergency handling timed out"),
+        }
+    });
+    if let Ok(handler) = handler {
+        let _ = handler.join();
+    }
+    std::process::exit(EMERGENCY_EXIT_CODE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::incident::IncidentConfig;
+    use crate::mocks::mock_venue::{MockVenue, MockVenueConfig};
+    use crate::events::EngineEvent;
+    use crate::risk::LossLimits;
+    use crate::types::{Fill, Order, OrderSide, OrderType};
+
+    #[tokio::test]
+    async fn test_emergency_cancels_and_dumps_state() {
+        let dir = std::env::temp_dir().join(format!("hft_emergency_{}", std::process::id()));
+        let incidents = IncidentLog::new(IncidentConfig { capacity: 10, dir: dir.clone() });
+        incidents.record_message("EMERGENCY", "raw frame");
+        incidents.record_event(&EngineEvent::KillSwitchEngaged { reason: "daily loss".to_string() });
+
+        let venue = Arc::new(MockVenue::new("EMERGENCY", MockVenueConfig::default()));
+        let venues = VenueRegistry::from_venues(vec![venue.clone()]).await;
+        let risk = Arc::new(RiskManager::new(LossLimits::default()));
+        risk.on_fill(&Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "EMERGENCY".into(),
+            strategy: "mm".to_string(),
+            side: OrderSide::Buy,
+            quantity: 0.5,
+            price: 50_000.0,
+            timestamp: 0,
+            commission: None,
+        }).await;
+        let orders = Arc::new(OrderTracker::new());
+        orders.insert("2".to_string(), Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Sell,
+            quantity: 0.5,
+            price: 51_000.0,
+            venue: "EMERGENCY".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: Some("mm".to_string()),
+            bypass_kill_switch: false,
+        }).await;
+
+        let path = Emergency::new(venues, risk, orders, incidents).handle("panic: test").await.unwrap();
+        assert_eq!(venue.cancel_all_count().await, 1);
+        let dump: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(dump["reason"], "panic: test");
+        assert_eq!(dump["cancels"]["EMERGENCY"], "cancelled");
+        assert_eq!(dump["positions"][0]["symbol"], "BTCUSDT");
+        assert_eq!(dump["positions"][0]["quantity"], 0.5);
+        assert_eq!(dump["open_orders"][0]["order_id"], "2");
+        // Raw messages stay in the incident dumps
+        assert_eq!(dump["events"].as_array().unwrap().len(), 1);
+        assert_eq!(dump["events"][0]["event"]["type"], "kill_switch_engaged");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
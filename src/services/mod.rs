@@ -1,76 +1,1906 @@
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::time::Duration;
 use std::collections::HashMap;
 
-use crate::gateways::{quote::QuoteGateway, order::OrderGateway};
-use crate::book::{BookBuilder, OrderBook};
-use crate::strategy::Strategy;
-use crate::execution::ExecutionEngine;
-use crate::venues::BinanceVenue;
+use crate::gateways::{quote::QuoteGateway, order::{AmendRequest, CancelRequest, OrderGateway}, ChaosConfig, DataQualityConfig, DataQualityMonitor, FailoverPolicies, QuoteBackpressure, QuoteStormConfig, QuoteStormGuard, SubscriptionChanges, SubscriptionSpec, SubscriptionStore};
+use crate::book::{BookBuilder, BookChecksums, BookGauges, BookLimits, Compactor, DepthRecorder, OrderBook};
+use crate::strategy::{ParameterStore, Shadows, Strategy, StrategyPlugin};
+use crate::execution::{BracketManager, ExecutionEngine, FillRouter, LegCoordinator, ManualOrders, OrderTracker, QuoteThrottle, QuoteThrottleConfig, TrailingStopConfig, TrailingStops};
+use crate::risk::{BalanceConfig, BalanceMonitor, ExpiryConfig, ExpiryManager, ExposureLimits, FinancingRates, FxConversion, GreekLimits, LiquidationConfig, LiquidationGuard, LossLimits, OutageConfig, OutageGuard, PriceSanity, PriceSanityConfig, RiskManager, StressConfig, StressTester, TradingToggles};
+use crate::events::{EngineEvent, EventBus};
+use crate::health::HealthRegistry;
+use crate::incident::{IncidentConfig, IncidentLog};
+use crate::metrics::{MetricsBackendConfig, MetricsExporter, MetricsRegistry};
+use crate::snapshot::{self, EngineSnapshot, SNAPSHOT_VERSION};
+use crate::feed::{FeedPublisher, StreamHub};
+use crate::fees::FeeSchedules;
+use crate::sink::SinkHandle;
+use crate::mirror::MirrorSource;
+use crate::failover::Leadership;
+use crate::audit::AuditLog;
+use crate::secrets::Secrets;
+use crate::types::{Fill, Quote, Symbol};
+use crate::scheduler::{Scheduler, SchedulerConfig, TimerService};
+use crate::report::{ReportConfig, Reporter};
+use crate::hedger::{HedgeConfig, Hedger};
+use crate::instruments::InstrumentMap;
+use crate::signals::{CandleConfig, GreeksConfig, Signals, ToxicityConfig};
+use crate::command::preflight::{self, PreflightConfig, PreflightReport};
+use crate::command::mode::{TradingMode, TradingState};
+use crate::error::{HftError, VenueError};
+use tracing::{error, info, warn};
+use crate::venues::{binance_ws, margin, BinanceVenue, FrameRecorder, FrameRecordingConfig, LatencyInjection, MarginConfig, ReconnectPolicies, Regions, VenueAdapter, VenueRegistry, VenueTransports};
 
+pub mod builder;
+pub mod emergency;
+pub mod supervisor;
+pub mod status;
+
+pub use builder::{ServiceHandles, ServicesBuilder, VenueContext};
+pub use emergency::{Emergency, EMERGENCY_EXIT_CODE};
+pub use status::{ComponentReport, EngineStatus, StatusSource};
+pub use supervisor::{RestartPolicy, TaskState};
+use supervisor::Supervisor;
+
+/// How often the risk task publishes exposures and checks loss limits
+const RISK_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Refusal to configure a component `start` has already handed to its task
+fn configured_after_start(component: &str) -> HftError {
+    HftError::Config(format!("{} configured after start", component))
+}
+
+/// Binance venue configured from `BINANCE_*` and `HFT_RECORD_FRAMES`, with
+/// its API key and secret from the secrets backend
+fn binance_from_env(ctx: &VenueContext) -> Arc<dyn VenueAdapter> {
+    let mut binance = BinanceVenue::new(
+        ctx.secrets.get("BINANCE_API_KEY").unwrap_or_default(),
+        ctx.secrets.get("BINANCE_API_SECRET").unwrap_or_default(),
+    )
+        .with_quote_sender(ctx.quote_tx.clone())
+        .with_fill_sender(ctx.fill_tx.clone())
+        .with_event_bus(ctx.events.clone())
+        .with_reconnect_policy(ctx.reconnect.for_venue("BINANCE_FUTURES").clone())
+        .with_transport(ctx.transports.for_venue("BINANCE_FUTURES"))
+        .with_incident_log(ctx.incidents.clone());
+    if std::env::var("BINANCE_WS_ORDER_ENTRY").is_ok_and(|v| v == "1" || v == "true") {
+        binance = binance.with_ws_order_entry(
+            std::env::var("BINANCE_WS_API_URL").unwrap_or_else(|_| binance_ws::WS_API_URL.to_string())
+        );
+    }
+    if let Some(config) = FrameRecordingConfig::from_env() {
+        match FrameRecorder::open(&config.path) {
+            Ok(recorder) => binance = binance.with_frame_recorder(recorder),
+            Err(e) => warn!(path = %config.path.display(), error = %e, "Frame recording disabled"),
+        }
+    }
+    Arc::new(binance)
+}
+
+// Components are held here until `start` hands them to their tasks
+#[allow(dead_code)]
 pub struct Services {
-    quote_gateway: QuoteGateway,
-    order_gateway: OrderGateway,
-    book_builder: BookBuilder,
-    strategy: Strategy,
+    quote_gateway: Arc<QuoteGateway>,
+    order_gateway: Arc<Mutex<OrderGateway>>,
+    book_builder: Arc<Mutex<BookBuilder>>,
+    strategy: Arc<Mutex<Strategy>>,
+    /// Shared with the strategy runner
+    shadows: Shadows,
+    /// Hands strategies loaded after start to the running strategy runner
+    plugin_tx: mpsc::Sender<Box<dyn StrategyPlugin>>,
+    fill_router: Arc<Mutex<FillRouter>>,
+    /// Handed to venues; kept so the fill router runs with no venue wired
+    fill_tx: mpsc::Sender<Fill>,
+    /// The order gateway's cancel queue
+    cancel_tx: mpsc::Sender<CancelRequest>,
+    /// The order gateway's amend queue
+    amend_tx: mpsc::Sender<AmendRequest>,
+    /// Set by `brackets`, watching stops once started
+    brackets: Option<Arc<BracketManager>>,
+    /// Set by `legs`
+    legs: Option<Arc<LegCoordinator>>,
+    /// Set by `trailing_stops`, following quotes once started
+    trailing_stops: Option<TrailingStops>,
     execution: ExecutionEngine,
+    risk: Arc<RiskManager>,
+    events: EventBus,
+    health: HealthRegistry,
+    books: Arc<RwLock<HashMap<Symbol, OrderBook>>>,
+    orders: Arc<OrderTracker>,
+    feed: FeedPublisher,
+    leadership: Option<Leadership>,
+    signals: Signals,
+    params: ParameterStore,
+    /// Shared with both gateways
+    venues: VenueRegistry,
+    instruments: Arc<InstrumentMap>,
+    supervisor: Supervisor,
+    restart_policies: HashMap<&'static str, RestartPolicy>,
+    secrets: Secrets,
+    audit: Option<AuditLog>,
+    /// Built when started, with the report settings of the time
+    scheduler: Option<SchedulerConfig>,
+    quality: Option<Arc<DataQualityMonitor>>,
+    liquidation: Option<LiquidationConfig>,
+    outage: Option<OutageConfig>,
+    expiries: Option<ExpiryConfig>,
+    stress: Option<StressConfig>,
+    balances: Option<BalanceConfig>,
+    /// Time in warm-up between `start` and active trading
+    warmup: Duration,
+    timers: TimerService,
+    stream: Option<StreamHub>,
+    reports: Reporter,
+    metrics: MetricsRegistry,
+    /// Connected when started
+    metrics_backend: Option<MetricsBackendConfig>,
+    /// Shared with the venues
+    incidents: IncidentLog,
+    regions: Regions,
 }
 
 impl Services {
-    pub async fn new() -> Self {
-        let (quote_tx, quote_rx) = mpsc::channel(1000);
-        let (order_tx, order_rx) = mpsc::channel(1000);
-        let books = Arc::new(RwLock::new(HashMap::new()));
-
-        let binance = Arc::new(BinanceVenue::new(
-            std::env::var("BINANCE_API_KEY").unwrap_or_default(),
-            std::env::var("BINANCE_API_SECRET").unwrap_or_default(),
-        ));
-
-        Self {
-            quote_gateway: QuoteGateway::new(quote_tx),
-            order_gateway: OrderGateway {
-                venues: vec![],
-                order_rx,
-            },
-            book_builder: BookBuilder {
-                books: Arc::clone(&books),
-                quote_rx,
-            },
-            strategy: Strategy {
-                books: Arc::clone(&books),
-                order_tx: order_tx.clone(),
-            },
-            execution: ExecutionEngine {
-                order_tx,
-            },
+    /// Services trading on Binance with options from the environment and
+    /// credentials from the configured secrets backend. Fails when the
+    /// secrets cannot be loaded.
+    pub async fn new() -> Result<Self, HftError> {
+        Ok(ServicesBuilder::new()
+            .with_reconnect_policies(ReconnectPolicies::from_env().unwrap_or_default())
+            .with_venue_transports(VenueTransports::from_env().unwrap_or_default())
+            .with_secrets(Secrets::from_env().await?)
+            .with_latency_injection(LatencyInjection::from_env().unwrap_or_default())
+            .with_regions(Regions::from_env().unwrap_or_default())
+            .with_incident_log(IncidentLog::new(IncidentConfig::from_env()))
+            .with_loss_limits(LossLimits::from_env().unwrap_or_default())
+            .with_exposure_limits(ExposureLimits::from_env().unwrap_or_default())
+            .with_venue(binance_from_env)
+            .build()
+            .await)
+    }
+
+    /// Handles to the shared components
+    pub fn handles(&self) -> ServiceHandles {
+        ServiceHandles {
+            venues: self.venues.clone(),
+            books: Arc::clone(&self.books),
+            orders: Arc::clone(&self.orders),
+            risk: Arc::clone(&self.risk),
+            toggles: self.risk.toggles(),
+            order_tx: self.execution.order_tx.clone(),
+            events: self.events.clone(),
+            health: self.health.clone(),
+            feed: self.feed.clone(),
+            signals: self.signals.clone(),
+            params: self.params.clone(),
+        }
+    }
+
+    /// The quote gateway while it can still be configured
+    fn quote_gateway_mut(&mut self) -> Result<&mut QuoteGateway, HftError> {
+        Arc::get_mut(&mut self.quote_gateway)
+            .ok_or_else(|| configured_after_start("quote gateway"))
+    }
+
+    /// The order gateway while it can still be configured
+    fn order_gateway_mut(&mut self) -> Result<&mut OrderGateway, HftError> {
+        Arc::get_mut(&mut self.order_gateway)
+            .map(Mutex::get_mut)
+            .ok_or_else(|| configured_after_start("order gateway"))
+    }
+
+    /// The strategy runner while it can still be configured
+    fn strategy_mut(&mut self) -> Result<&mut Strategy, HftError> {
+        Arc::get_mut(&mut self.strategy)
+            .map(Mutex::get_mut)
+            .ok_or_else(|| configured_after_start("strategy runner"))
+    }
+
+    /// The fill router while it can still be configured
+    fn fill_router_mut(&mut self) -> Result<&mut FillRouter, HftError> {
+        Arc::get_mut(&mut self.fill_router)
+            .map(Mutex::get_mut)
+            .ok_or_else(|| configured_after_start("fill router"))
+    }
+
+    /// The book builder while it can still be configured
+    fn book_builder_mut(&mut self) -> Result<&mut BookBuilder, HftError> {
+        Arc::get_mut(&mut self.book_builder)
+            .map(Mutex::get_mut)
+            .ok_or_else(|| configured_after_start("book builder"))
+    }
+
+    /// How `start` supervises `component`: `book_builder`,
+    /// `order_gateway`, `risk`, `timers`, `fills` and `strategy` always
+    /// run, and `brackets`, `trailing_stops`, `scheduler`, `data_quality`,
+    /// `stream_pnl`, `liquidation`, `outage`, `expiry`, `stress`,
+    /// `balances` and `metrics_export` when configured. Components without
+    /// a policy keep [`RestartPolicy::default`].
+    pub fn with_restart_policy(mut self, component: &'static str, policy: RestartPolicy) -> Self {
+        self.restart_policies.insert(component, policy);
+        self
+    }
+
+    /// Publish order lifecycle events to a downstream sink, alongside any
+    /// added before
+    pub fn with_order_sink(mut self, sink: SinkHandle) -> Result<Self, HftError> {
+        self.order_gateway_mut()?.sinks.push(sink);
+        Ok(self)
+    }
+
+    /// Translate canonical instrument IDs to and from venue symbols at the
+    /// gateways
+    pub fn with_instruments(mut self, instruments: InstrumentMap) -> Result<Self, HftError> {
+        let instruments = Arc::new(instruments);
+        self.order_gateway_mut()?.instruments = Arc::clone(&instruments);
+        self.instruments = Arc::clone(&instruments);
+        self.quote_gateway_mut()?.instruments = instruments;
+        Ok(self)
+    }
+
+    /// Drop repeated quotes before they reach the book builder
+    pub fn with_quote_dedup(mut self) -> Result<Self, HftError> {
+        self.quote_gateway_mut()?.last_quotes = Some(Default::default());
+        Ok(self)
+    }
+
+    /// Export each book's best bid, best ask, spread and staleness as gauges
+    pub fn with_book_gauges(mut self) -> Result<Self, HftError> {
+        self.book_builder_mut()?.gauges = Some(BookGauges::default());
+        Ok(self)
+    }
+
+    /// Verify venues' depth updates against their checksums
+    pub fn with_book_checksums(mut self, checksums: BookChecksums) -> Result<Self, HftError> {
+        self.book_builder_mut()?.checksums = checksums;
+        Ok(self)
+    }
+
+    /// Cap the books' depth to `limits`
+    pub fn with_book_limits(mut self, limits: BookLimits) -> Result<Self, HftError> {
+        self.book_builder_mut()?.compactor = Compactor::new(limits);
+        Ok(self)
+    }
+
+    /// Record every quote and depth update the books take, with periodic
+    /// snapshots, for exact replay
+    pub fn with_depth_recorder(mut self, recorder: DepthRecorder) -> Result<Self, HftError> {
+        self.book_builder_mut()?.recorder = Some(recorder);
+        Ok(self)
+    }
+
+    /// What the order gateway does with orders for a venue that is down
+    pub fn with_venue_failover(mut self, policies: FailoverPolicies) -> Result<Self, HftError> {
+        self.order_gateway_mut()?.failover = policies;
+        Ok(self)
+    }
+
+    /// Persist the subscribed symbols so [`resume_subscriptions`](Self::resume_subscriptions)
+    /// can restore them after a restart
+    pub fn with_subscription_store(mut self, store: SubscriptionStore) -> Result<Self, HftError> {
+        self.quote_gateway_mut()?.store = Some(store);
+        Ok(self)
+    }
+
+    /// Inject latency and drops into the quote and order gateways
+    pub fn with_chaos(mut self, chaos: ChaosConfig) -> Result<Self, HftError> {
+        self.order_gateway_mut()?.chaos = Some(chaos.clone());
+        self.quote_gateway_mut()?.chaos = Some(chaos);
+        Ok(self)
+    }
+
+    /// Hold marketable orders whose price deviates from the other venues'
+    /// consolidated book, alerting instead of trading through a bad feed
+    pub fn with_price_sanity(mut self, config: PriceSanityConfig) -> Result<Self, HftError> {
+        let sanity = PriceSanity::new(config);
+        self.risk.set_price_sanity(sanity.clone());
+        self.quote_gateway_mut()?.sanity = Some(sanity);
+        Ok(self)
+    }
+
+    /// Check incoming quotes for data quality issues, alerting on them and
+    /// scoring each venue
+    pub fn with_data_quality(mut self, config: DataQualityConfig) -> Result<Self, HftError> {
+        let quality = Arc::new(DataQualityMonitor::new(config, self.events.clone()));
+        self.quote_gateway_mut()?.quality = Some(Arc::clone(&quality));
+        self.quality = Some(quality);
+        Ok(self)
+    }
+
+    /// Hold back or drop quotes rather than waiting when the book builder
+    /// falls behind
+    pub fn with_quote_backpressure(mut self, backpressure: QuoteBackpressure) -> Result<Self, HftError> {
+        self.quote_gateway_mut()?.backpressure = backpressure;
+        Ok(self)
+    }
+
+    /// Conflate a venue's quotes while it streams them far faster than usual,
+    /// alerting as storms start and end
+    pub fn with_quote_storm_guard(mut self, config: QuoteStormConfig) -> Result<Self, HftError> {
+        let guard = QuoteStormGuard::new(config).with_events(self.events.clone());
+        self.quote_gateway_mut()?.storm = Some(guard);
+        Ok(self)
+    }
+
+    /// Watch venue positions' distance to liquidation, alerting as it
+    /// shrinks and partly closing positions that get too close
+    pub fn with_liquidation_guard(mut self, config: LiquidationConfig) -> Self {
+        self.liquidation = Some(config);
+        self
+    }
+
+    /// Hedge positions on venues that stay disconnected past the grace
+    /// period, and flatten them once the venue is back
+    pub fn with_outage_guard(mut self, config: OutageConfig) -> Self {
+        self.outage = Some(config);
+        self
+    }
+
+    /// Stop positions in dated contracts growing near expiry, roll them
+    /// into the next contract and delist the contract once expired
+    pub fn with_contract_expiries(mut self, config: ExpiryConfig) -> Self {
+        self.risk.set_expiries(config.clone());
+        self.expiries = Some(config);
+        self
+    }
+
+    /// Push metrics to a statsd or OTLP backend as well as serving them for
+    /// scraping
+    pub fn with_metrics_backend(mut self, config: MetricsBackendConfig) -> Self {
+        self.metrics_backend = Some(config);
+        self
+    }
+
+    /// Stream book updates, order events and strategy PnL changes to
+    /// `stream`'s WebSocket clients
+    pub fn with_stream(mut self, stream: StreamHub) -> Result<Self, HftError> {
+        self.book_builder_mut()?.stream = Some(stream.clone());
+        self.stream = Some(stream.clone());
+        self.with_order_sink(SinkHandle::spawn(Arc::new(stream), 8192))
+    }
+
+    /// Outbound market data feed for downstream consumers
+    pub fn feed(&self) -> FeedPublisher {
+        self.feed.clone()
+    }
+
+    /// Run as one half of a hot/standby pair: orders are only sent while
+    /// `leadership` holds the leader lock
+    pub fn with_leadership(mut self, leadership: Leadership) -> Result<Self, HftError> {
+        self.order_gateway_mut()?.leadership = Some(leadership.clone());
+        self.leadership = Some(leadership);
+        Ok(self)
+    }
+
+    /// Record order requests, risk decisions, acks, cancels and fills for
+    /// compliance
+    pub fn with_audit(mut self, audit: AuditLog) -> Result<Self, HftError> {
+        self.strategy_mut()?.audit = Some(audit.clone());
+        self.execution.audit = Some(audit.clone());
+        self.order_gateway_mut()?.audit = Some(audit.clone());
+        self.risk.set_audit(audit.clone());
+        self.audit = Some(audit);
+        Ok(self)
+    }
+
+    /// Mirror every execution report, fills included, to drop-copy `sinks`
+    /// for compliance, apart from the audit log and other order sinks
+    pub fn with_drop_copy(mut self, sinks: Vec<SinkHandle>) -> Result<Self, HftError> {
+        self.order_gateway_mut()?.sinks.extend(sinks.iter().cloned());
+        self.risk.set_drop_copy(sinks);
+        Ok(self)
+    }
+
+    /// Run scheduled jobs such as the end of day flatten as the
+    /// `scheduler` component
+    pub fn with_scheduler(mut self, config: SchedulerConfig) -> Self {
+        self.scheduler = Some(config);
+        self
+    }
+
+    /// Fee rates, output directory and webhook of daily reports, both
+    /// scheduled and requested through the admin API
+    pub fn with_reports(mut self, config: ReportConfig) -> Self {
+        self.reports = Reporter::new(config, Arc::clone(&self.risk));
+        self
+    }
+
+    /// Builds daily PnL and activity reports on demand
+    pub fn reporter(&self) -> Reporter {
+        self.reports.clone()
+    }
+
+    pub fn audit(&self) -> Option<AuditLog> {
+        self.audit.clone()
+    }
+
+    /// Operator order entry through the engine's risk checks and audit log;
+    /// take it after `with_audit`
+    pub fn manual_orders(&self) -> ManualOrders {
+        ManualOrders {
+            execution: self.execution.clone(),
+            orders: Arc::clone(&self.orders),
+            venues: self.venues.clone(),
+            instruments: Arc::clone(&self.instruments),
+            audit: self.audit.clone(),
+        }
+    }
+
+    /// Credentials the venues were built with, for other components that
+    /// authenticate
+    pub fn secrets(&self) -> Secrets {
+        self.secrets.clone()
+    }
+
+    /// Aggregate PnL and exposures across quote currencies in one reporting
+    /// currency
+    pub fn with_fx_conversion(self, fx: FxConversion) -> Self {
+        self.risk.set_fx_conversion(fx);
+        self
+    }
+
+    /// Charge open positions perpetual funding and margin borrow costs in
+    /// their PnL
+    pub fn with_financing(self, rates: FinancingRates) -> Self {
+        self.risk.set_financing(rates);
+        self
+    }
+
+    /// Hedger offsetting net inventory on the configured hedge venues
+    pub fn hedger(&self, config: HedgeConfig) -> Hedger {
+        Hedger::new(
+            config,
+            Arc::clone(&self.risk),
+            Arc::clone(&self.books),
+            self.execution.order_tx.clone(),
+        )
+    }
+
+    /// Trailing stops behind designated positions, closing them through
+    /// the order gateway. Created on the first call, before `start`, which
+    /// follows quotes on the feed; later calls ignore `config`.
+    pub fn trailing_stops(&mut self, config: TrailingStopConfig) -> TrailingStops {
+        if let Some(stops) = &self.trailing_stops {
+            return stops.clone();
+        }
+        let stops = TrailingStops::new(config, self.execution.order_tx.clone());
+        let stops = match &self.audit {
+            Some(audit) => stops.with_audit(audit.clone()),
+            None => stops,
+        };
+        self.trailing_stops = Some(stops.clone());
+        stops
+    }
+
+    /// Bracket orders placed and cancelled through the order gateway, which
+    /// reports their acks back, with fills from the fill router. Created on
+    /// the first call, before `start`, which watches the stops on the feed.
+    pub fn brackets(&mut self) -> Result<Arc<BracketManager>, HftError> {
+        if let Some(brackets) = &self.brackets {
+            return Ok(Arc::clone(brackets));
+        }
+        let brackets = Arc::new(BracketManager::new(self.execution.order_tx.clone(), self.cancel_tx.clone(), Arc::clone(&self.orders)));
+        // One queue for acks and fills keeps an entry's ack ahead of its fills
+        let sink = SinkHandle::spawn(brackets.clone(), 1024);
+        self.order_gateway_mut()?.sinks.push(sink.clone());
+        self.fill_router_mut()?.sinks.push(sink);
+        self.brackets = Some(Arc::clone(&brackets));
+        Ok(brackets)
+    }
+
+    /// Multi-leg orders sent through the order gateway, which reports each
+    /// leg's ack or rejection back, with fills from the fill router to
+    /// unwind legged ones. Created on the first call, before `start`.
+    pub fn legs(&mut self) -> Result<Arc<LegCoordinator>, HftError> {
+        if let Some(legs) = &self.legs {
+            return Ok(Arc::clone(legs));
+        }
+        let legs = Arc::new(LegCoordinator::new(self.execution.order_tx.clone()));
+        // One queue for acks and fills keeps a leg's ack ahead of its fills
+        let sink = SinkHandle::spawn(legs.clone(), 1024);
+        self.order_gateway_mut()?.sinks.push(sink.clone());
+        self.fill_router_mut()?.sinks.push(sink);
+        self.legs = Some(Arc::clone(&legs));
+        Ok(legs)
+    }
+
+    /// Route strategy orders through a quote throttle, which amends the
+    /// quotes resting on the venues through the order gateway and follows
+    /// their acks and fills. Call before `start`; the returned throttle
+    /// must be run for strategy orders to reach the gateway.
+    pub fn quote_throttle(&mut self, config: QuoteThrottleConfig) -> Result<QuoteThrottle, HftError> {
+        let (throttle_tx, throttle_rx) = mpsc::channel(1000);
+        let order_tx = std::mem::replace(&mut self.strategy_mut()?.order_tx, throttle_tx);
+        let throttle = QuoteThrottle::new(config, throttle_rx, order_tx, self.amend_tx.clone(), Arc::clone(&self.orders));
+        // One queue for acks and fills keeps a quote's ack ahead of its fills
+        let sink = SinkHandle::spawn(throttle.resting_quotes(), 1024);
+        self.order_gateway_mut()?.sinks.push(sink.clone());
+        self.fill_router_mut()?.sinks.push(sink);
+        Ok(throttle)
+    }
+
+    /// Monitor order-flow toxicity, withholding strategy quotes while it is
+    /// high
+    pub fn with_toxicity(self, config: ToxicityConfig) -> Self {
+        self.signals.toxicity.configure(config);
+        self
+    }
+
+    /// Solve option quotes for implied volatility and greeks, priced off
+    /// the underlyings `config` names
+    pub fn with_greeks(self, config: GreeksConfig) -> Self {
+        self.signals.greeks.configure(config);
+        self
+    }
+
+    /// Run `config`'s stress scenarios against current positions on demand
+    /// through the admin API, and on its schedule when it has one
+    pub fn with_stress_tests(mut self, config: StressConfig) -> Self {
+        self.risk.set_stress_scenarios(config.scenarios.clone());
+        self.stress = Some(config);
+        self
+    }
+
+    /// Poll venue balances and publish deposits, withdrawals and funding,
+    /// refusing orders the available balance cannot margin when `config`
+    /// caps leverage
+    pub fn with_balance_monitor(mut self, config: BalanceConfig) -> Self {
+        if let Some(leverage) = config.max_leverage {
+            self.risk.set_max_leverage(leverage);
+        }
+        self.balances = Some(config);
+        self
+    }
+
+    /// Cap net delta, gamma and vega per underlying and beta-weighted
+    /// delta across the portfolio
+    pub fn with_greek_limits(self, limits: GreekLimits) -> Self {
+        self.risk.set_greek_limits(limits);
+        self
+    }
+
+    /// Charge each venue's maker and taker fees by volume tier in reports,
+    /// failover routing and fee-aware strategies
+    pub fn with_fee_schedules(self, schedules: FeeSchedules) -> Self {
+        self.signals.fees.configure(schedules);
+        self
+    }
+
+    /// Strategy parameters that can be changed while strategies run
+    pub fn with_strategy_params(mut self, params: ParameterStore) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn strategy_params(&self) -> ParameterStore {
+        self.params.clone()
+    }
+
+    /// Engine-derived market data shared with strategies
+    pub fn signals(&self) -> Signals {
+        self.signals.clone()
+    }
+
+    /// Load recent candle history from each venue so strategies start with
+    /// warm indicators. Venues without candle history build them from
+    /// trades only.
+    pub async fn warm_up_candles(&self, config: &CandleConfig) {
+        for venue in self.venues.all() {
+            let venue_name = venue.name().await;
+            for (symbol, interval, limit) in &config.series {
+                let venue_symbol = self.instruments.venue_symbol(venue_name.as_str().into(), symbol.into());
+                match self.signals.candles.warm_up(&*venue, symbol, &venue_symbol, *interval, *limit).await {
+                    Ok(loaded) => info!(venue = %venue_name, symbol = %symbol, interval = ?interval, candles = loaded, "Candles warmed up"),
+                    Err(HftError::Venue(VenueError::NotSupported(_))) => {
+                        info!(venue = %venue_name, symbol = %symbol, interval = ?interval, "No candle history, building from trades");
+                    }
+                    Err(e) => warn!(venue = %venue_name, symbol = %symbol, error = %e, "Failed to fetch candle history"),
+                }
+            }
+        }
+    }
+
+    /// Keep the engine in warm-up for `warmup` after `start`, seeing market
+    /// data without sending orders
+    pub fn with_warmup(mut self, warmup: Duration) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// Shared timers for strategies and algos, running once started
+    pub fn timers(&self) -> TimerService {
+        self.timers.clone()
+    }
+
+    /// Engine trading mode
+    pub fn trading_state(&self) -> TradingState {
+        self.risk.trading_state()
+    }
+
+    /// Operator pauses per symbol and strategy
+    pub fn toggles(&self) -> Arc<TradingToggles> {
+        self.risk.toggles()
+    }
+
+    pub fn leadership(&self) -> Option<Leadership> {
+        self.leadership.clone()
+    }
+
+    /// Load a strategy into the strategy runner, hot-swapping any running
+    /// strategy with the same name
+    pub fn add_strategy(&mut self, plugin: Box<dyn StrategyPlugin>) {
+        let name = plugin.name().to_string();
+        let Some(strategy) = Arc::get_mut(&mut self.strategy) else {
+            // The running runner swaps it in between callbacks
+            if self.plugin_tx.try_send(plugin).is_err() {
+                warn!(strategy = %name, "Strategy runner busy, strategy not loaded");
+            }
+            return;
+        };
+        if strategy.get_mut().add_plugin(plugin).is_some() {
+            info!(strategy = %name, "Strategy replaced");
+        } else {
+            info!(strategy = %name, "Strategy loaded");
+        }
+    }
+
+    /// Load a strategy in shadow: it sees live market data, but its orders
+    /// fill in a simulation and are compared with the live strategy named
+    /// `live`
+    pub fn add_shadow_strategy(&mut self, plugin: Box<dyn StrategyPlugin>, live: Option<String>) -> Result<(), HftError> {
+        let name = plugin.name().to_string();
+        if self.strategy_mut()?.add_shadow(plugin, live) {
+            info!(strategy = %name, "Shadow strategy replaced");
+        } else {
+            info!(strategy = %name, "Shadow strategy loaded");
+        }
+        Ok(())
+    }
+
+    /// Recent venue messages and engine events, for dumping after an
+    /// incident
+    pub fn incidents(&self) -> IncidentLog {
+        self.incidents.clone()
+    }
+
+    /// Cancels every order and dumps engine state if the engine crashes,
+    /// once installed
+    pub fn emergency(&self) -> Emergency {
+        Emergency::new(self.venues.clone(), Arc::clone(&self.risk), Arc::clone(&self.orders), self.incidents.clone())
+    }
+
+    /// Shadow strategies' results, for the admin API
+    pub fn shadows(&self) -> Shadows {
+        self.shadows.clone()
+    }
+
+    /// Register a venue while the engine runs: it is tracked for
+    /// connectivity, subscribed to the symbols the other venues stream, and
+    /// routable by the order gateway and the risk manager
+    pub async fn add_venue(&self, venue: Arc<dyn VenueAdapter>) {
+        let name = venue.name().await;
+        self.health.register_venue(&name);
+        self.regions.publish(&name);
+        self.quote_gateway.add_venue(venue).await;
+    }
+
+    /// A quote channel for a venue added with [`add_venue`](Self::add_venue),
+    /// read by a task of its own like the venues the engine was built with
+    pub fn venue_quote_sender(&self, venue: &str) -> mpsc::Sender<Quote> {
+        self.quote_gateway.venue_sender(venue)
+    }
+
+    /// The fill channel for a venue added with [`add_venue`](Self::add_venue)
+    pub fn fill_sender(&self) -> mpsc::Sender<Fill> {
+        self.fill_tx.clone()
+    }
+
+    /// Amends of resting orders, sent to the running order gateway
+    pub fn amend_sender(&self) -> mpsc::Sender<AmendRequest> {
+        self.amend_tx.clone()
+    }
+
+    /// Discard `venue`'s quotes until resumed, without affecting other
+    /// venues. Returns false for an unknown venue.
+    pub fn pause_venue_quotes(&self, venue: &str) -> bool {
+        self.quote_gateway.pause_venue(venue)
+    }
+
+    pub fn resume_venue_quotes(&self, venue: &str) -> bool {
+        self.quote_gateway.resume_venue(venue)
+    }
+
+    /// Subscribe every venue to `symbols`, returning the ones not already
+    /// subscribed
+    pub async fn subscribe(&self, symbols: Vec<String>) -> Result<Vec<String>, HftError> {
+        self.quote_gateway.add_symbols(symbols).await
+    }
+
+    /// Stop forwarding quotes for `symbols`, returning the ones that were
+    /// subscribed
+    pub async fn unsubscribe(&self, symbols: &[String]) -> Result<Vec<String>, HftError> {
+        self.quote_gateway.remove_symbols(symbols).await
+    }
+
+    /// Subscribed canonical symbols, sorted
+    pub async fn subscriptions(&self) -> Vec<String> {
+        self.quote_gateway.symbols().await
+    }
+
+    /// Subscribe each venue to exactly the symbols `spec` lists for it
+    pub async fn apply_subscriptions(&self, spec: &SubscriptionSpec) -> Result<SubscriptionChanges, HftError> {
+        self.quote_gateway.apply(spec).await
+    }
+
+    /// Subscribed symbols per venue
+    pub async fn subscription_spec(&self) -> SubscriptionSpec {
+        self.quote_gateway.spec().await
+    }
+
+    /// Apply the subscriptions saved in the subscription store, or
+    /// `initial` when none were saved
+    pub async fn resume_subscriptions(&self, initial: &SubscriptionSpec) -> Result<SubscriptionChanges, HftError> {
+        let saved = match &self.quote_gateway.store {
+            Some(store) => store.load()?,
+            None => SubscriptionSpec::new(),
+        };
+        let spec = if saved.is_empty() { initial } else { &saved };
+        if spec.is_empty() {
+            return Ok(SubscriptionChanges::default());
+        }
+        self.apply_subscriptions(spec).await
+    }
+
+    /// Positions and open orders for external state mirrors
+    pub fn mirror_source(&self) -> MirrorSource {
+        MirrorSource {
+            risk: Arc::clone(&self.risk),
+            orders: Arc::clone(&self.orders),
+        }
+    }
+
+    /// Capture books, open orders, positions and subscriptions
+    pub async fn snapshot(&self) -> EngineSnapshot {
+        let books = self.books.read().await
+            .values()
+            .map(OrderBook::snapshot)
+            .collect();
+
+        EngineSnapshot {
+            version: SNAPSHOT_VERSION,
+            taken_at: chrono::Utc::now().timestamp_millis() as u64,
+            books,
+            open_orders: self.orders.open_orders().await,
+            positions: self.risk.positions().await,
+            subscriptions: self.quote_gateway.get_subscriptions().await,
+        }
+    }
+
+    /// Reload state from a snapshot and reconcile open orders with the venues.
+    ///
+    /// Orders the venue no longer reports are dropped; venues that cannot list
+    /// open orders keep the snapshot's view. Subscriptions are re-established
+    /// last so fresh quotes overwrite the restored books.
+    pub async fn restore(&self, snapshot: EngineSnapshot) -> Result<(), HftError> {
+        info!(
+            taken_at = snapshot.taken_at,
+            books = snapshot.books.len(),
+            open_orders = snapshot.open_orders.len(),
+            positions = snapshot.positions.len(),
+            "Restoring engine state from snapshot"
+        );
+
+        {
+            let mut books = self.books.write().await;
+            for book in &snapshot.books {
+                books.insert(book.symbol.as_str().into(), OrderBook::from_snapshot(book));
+            }
+        }
+
+        self.risk.restore_positions(snapshot.positions).await;
+
+        for open in snapshot.open_orders.iter().cloned() {
+            self.orders.restore(open).await;
+        }
+
+        for venue in self.venues.all() {
+            let venue_name = venue.name().await;
+            let known: Vec<String> = snapshot.open_orders
+                .iter()
+                .filter(|o| o.order.venue == venue_name)
+                .map(|o| o.order_id.clone())
+                .collect();
+
+            match venue.open_orders().await {
+                Ok(open) => {
+                    let result = snapshot::reconcile(&known, &open);
+                    for order_id in &result.closed {
+                        self.orders.remove(order_id).await;
+                    }
+                    if !result.unknown.is_empty() {
+                        warn!(venue = %venue_name, orders = ?result.unknown, "Venue has open orders missing from snapshot");
+                    }
+                    info!(
+                        venue = %venue_name,
+                        confirmed = result.confirmed.len(),
+                        closed = result.closed.len(),
+                        unknown = result.unknown.len(),
+                        "Reconciled open orders"
+                    );
+                }
+                Err(HftError::Venue(VenueError::NotSupported(_))) => {
+                    warn!(venue = %venue_name, "Venue cannot list open orders, keeping snapshot orders");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut subscriptions = SubscriptionSpec::new();
+        for (venue, symbols) in snapshot.subscriptions {
+            subscriptions = subscriptions.with_venue(&venue, symbols);
+        }
+        if !subscriptions.is_empty() {
+            self.quote_gateway.apply(&subscriptions).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Set each venue's configured leverage and margin mode per symbol and
+    /// read them back, alerting on any symbol left with other settings
+    pub async fn configure_margin(&self, config: &MarginConfig) {
+        for venue in self.venues.all() {
+            let venue_name = venue.name().await;
+            for (symbol, settings) in config.symbols(&venue_name) {
+                let venue_symbol = self.instruments.venue_symbol(venue_name.as_str().into(), symbol.into());
+                match margin::apply(&*venue, &venue_symbol, *settings).await {
+                    Ok(()) => info!(venue = %venue_name, symbol = %symbol, settings = %settings, "Margin settings verified"),
+                    Err(e) => {
+                        error!(venue = %venue_name, symbol = %symbol, settings = %settings, error = %e, "Failed to apply margin settings");
+                        self.events.publish(EngineEvent::MarginMismatch {
+                            venue: venue_name.clone(),
+                            symbol: symbol.clone(),
+                            detail: format!("wanted {}: {}", settings, e),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check every venue is ready to trade the configured symbols
+    pub async fn preflight(&self, config: &PreflightConfig) -> PreflightReport {
+        preflight::run(&self.venues.all(), &self.instruments, config).await
+    }
+
+    /// Component health shared with the `/healthz` and `/readyz` endpoints
+    pub fn health(&self) -> HealthRegistry {
+        self.health.clone()
+    }
+
+    /// Registry the engine's metrics are exported from
+    pub fn metrics(&self) -> MetricsRegistry {
+        self.metrics.clone()
+    }
+
+    /// Reads component status while the engine runs, for the admin API
+    pub fn status_source(&self) -> StatusSource {
+        StatusSource {
+            tasks: self.supervisor.counters(),
+            health: self.health.clone(),
+            toggles: self.risk.toggles(),
+            trading: self.risk.trading_state(),
+            quotes: self.quote_gateway.quote_tx.downgrade(),
+            orders: self.execution.order_tx.downgrade(),
         }
     }
 
+    pub fn status(&self) -> EngineStatus {
+        self.status_source().status()
+    }
+
+    /// Engine event bus shared by all components
+    pub fn events(&self) -> EventBus {
+        self.events.clone()
+    }
+
+    /// Spawn the book builder, order gateway, risk checks, timers, fill
+    /// router and strategy runner as supervised tasks. Calling it again while they run does nothing.
     pub async fn start(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Starting services...");
+        if self.supervisor.is_started() {
+            info!("Services already started");
+            return Ok(());
+        }
+        let policy = |component| self.restart_policies.get(component).copied().unwrap_or_default();
+        let (book_policy, order_policy, risk_policy) = (policy("book_builder"), policy("order_gateway"), policy("risk"));
+
+        // Each venue's quotes are normalized by a task of its own
+        self.quote_gateway.start_readers();
 
-        // Start quote gateway
-        println!("Starting quote gateway...");
-        // Add your quote gateway start logic
+        // A restarted component picks up where it left off: the task holds
+        // the component's lock, which a panic releases
+        let book_builder = Arc::clone(&self.book_builder);
+        self.supervisor.spawn("book_builder", book_policy, move || {
+            let book_builder = Arc::clone(&book_builder);
+            async move { book_builder.lock().await.run().await }
+        });
 
-        // Start order gateway
-        println!("Starting order gateway...");
-        // Add your order gateway start logic
+        let order_gateway = Arc::clone(&self.order_gateway);
+        self.supervisor.spawn("order_gateway", order_policy, move || {
+            let order_gateway = Arc::clone(&order_gateway);
+            async move { order_gateway.lock().await.run().await }
+        });
 
-        // Start book builder
-        println!("Starting book builder...");
-        // Add your book builder start logic
+        let risk = Arc::clone(&self.risk);
+        self.supervisor.spawn("risk", risk_policy, move || Arc::clone(&risk).run(RISK_CHECK_INTERVAL));
 
-        // Start strategy
-        println!("Starting strategy...");
-        // Add your strategy start logic
+        let timers = self.timers.clone();
+        self.supervisor.spawn("timers", policy("timers"), move || timers.clone().run());
 
-        // Start execution engine
-        println!("Starting execution engine...");
-        // Add your execution engine start logic
+        // Timers are registered once; a restarted runner keeps reading them
+        {
+            let mut strategy = self.strategy.lock().await;
+            if strategy.timer_rx.is_none() {
+                let (_, fired) = strategy.schedule_timers(&self.timers);
+                strategy.timer_rx = Some(fired);
+            }
+        }
+        let fill_router = Arc::clone(&self.fill_router);
+        self.supervisor.spawn("fills", policy("fills"), move || {
+            let fill_router = Arc::clone(&fill_router);
+            async move { fill_router.lock().await.run().await }
+        });
+
+        let (strategy, feed) = (Arc::clone(&self.strategy), self.feed.clone());
+        self.supervisor.spawn("strategy", policy("strategy"), move || {
+            let (strategy, frames) = (Arc::clone(&strategy), feed.subscribe());
+            async move { strategy.lock().await.run(frames).await }
+        });
+
+        if let Some(brackets) = &self.brackets {
+            let (brackets, feed) = (Arc::clone(brackets), self.feed.clone());
+            self.supervisor.spawn("brackets", policy("brackets"), move || Arc::clone(&brackets).run(feed.clone()));
+        }
+        if let Some(stops) = &self.trailing_stops {
+            let (stops, feed) = (stops.clone(), self.feed.clone());
+            self.supervisor.spawn("trailing_stops", policy("trailing_stops"), move || stops.clone().run(feed.clone()));
+        }
+
+        if let Some(config) = &self.scheduler {
+            let scheduler = Arc::new(Scheduler::new(config.clone(), Arc::clone(&self.risk), self.venues.clone(), self.events.clone())
+                .with_reporter(self.reports.clone())
+                .with_metrics(self.metrics.clone()));
+            self.supervisor.spawn("scheduler", policy("scheduler"), move || Arc::clone(&scheduler).run());
+        }
+
+        if let Some(quality) = &self.quality {
+            let quality = Arc::clone(quality);
+            self.supervisor.spawn("data_quality", policy("data_quality"), move || Arc::clone(&quality).run());
+        }
+
+        if let Some(stream) = &self.stream {
+            let (stream, risk) = (stream.clone(), Arc::clone(&self.risk));
+            self.supervisor.spawn("stream_pnl", policy("stream_pnl"), move || stream.clone().track_pnl(Arc::clone(&risk)));
+        }
+
+        if let Some(config) = &self.liquidation {
+            let guard = Arc::new(LiquidationGuard::new(config.clone(), self.venues.clone(), self.execution.order_tx.clone(), self.events.clone())
+                .with_instruments(Arc::clone(&self.instruments)));
+            self.supervisor.spawn("liquidation", policy("liquidation"), move || Arc::clone(&guard).run());
+        }
+
+        if let Some(config) = &self.outage {
+            let guard = Arc::new(OutageGuard::new(config.clone(), Arc::clone(&self.risk), self.execution.order_tx.clone(), self.events.clone()));
+            self.supervisor.spawn("outage", policy("outage"), move || Arc::clone(&guard).run());
+        }
 
-        println!("All services started successfully");
+        if let Some(config) = &self.expiries {
+            let manager = Arc::new(ExpiryManager::new(
+                config.clone(),
+                Arc::clone(&self.risk),
+                self.execution.order_tx.clone(),
+                Arc::clone(&self.quote_gateway),
+                Arc::clone(&self.books),
+                self.events.clone(),
+            ));
+            self.supervisor.spawn("expiry", policy("expiry"), move || Arc::clone(&manager).run());
+        }
+
+        if let Some(interval) = self.stress.as_ref().and_then(|config| config.interval) {
+            let tester = Arc::new(StressTester::new(Arc::clone(&self.risk), interval));
+            self.supervisor.spawn("stress", policy("stress"), move || Arc::clone(&tester).run());
+        }
+
+        if let Some(config) = &self.balances {
+            let monitor = Arc::new(BalanceMonitor::new(Arc::clone(&self.risk), self.venues.clone(), self.events.clone(), config.interval));
+            self.supervisor.spawn("balances", policy("balances"), move || Arc::clone(&monitor).run());
+        }
+
+        if let Some(config) = &self.metrics_backend {
+            match config.connect().await {
+                Ok(Some(sink)) => {
+                    let exporter = Arc::new(MetricsExporter::new(sink, self.metrics.clone(), config.flush_interval));
+                    self.supervisor.spawn("metrics_export", policy("metrics_export"), move || Arc::clone(&exporter).run());
+                }
+                Ok(None) => {}
+                Err(e) => warn!(error = %e, "Metrics export disabled"),
+            }
+        }
+
+        // Warm up once, on the first start; later modes are the operator's
+        let trading = self.risk.trading_state();
+        if trading.transition_from(TradingMode::Init, TradingMode::Warmup, "services started") {
+            if self.warmup.is_zero() {
+                trading.transition_from(TradingMode::Warmup, TradingMode::Active, "no warm-up");
+            } else {
+                let warmup = self.warmup;
+                tokio::spawn(async move {
+                    tokio::time::sleep(warmup).await;
+                    trading.transition_from(TradingMode::Warmup, TradingMode::Active, "warm-up complete");
+                });
+            }
+        }
+
+        info!(components = ?self.supervisor.components(), "Services started");
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Stop the component tasks; `start` runs them again
+    pub async fn stop(&mut self) {
+        self.supervisor.stop().await;
+        info!("Services stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::{Bracket, LegOutPolicy, ProtectedPosition, TrailDistance};
+    use crate::hedger::HedgeTarget;
+    use crate::risk::{ExposureLimit, HaltOwner};
+    use crate::venues::PositionRisk;
+    use crate::mocks::mock_venue::{MockVenue, MockVenueConfig};
+    use crate::types::{Order, OrderSide, OrderType};
+
+    #[tokio::test]
+    async fn test_start_runs_components_once() {
+        let venue = Arc::new(MockVenue::new("SUPERVISED", MockVenueConfig {
+            error_probability: 0.0,
+            latency_ms: 1,
+            ..MockVenueConfig::default()
+        }));
+        let injected = Arc::clone(&venue);
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |_| injected)
+            .build()
+            .await;
+
+        assert!(!services.status().started);
+        services.start().await.unwrap();
+        services.start().await.unwrap();
+        assert_eq!(services.supervisor.components(), vec!["book_builder", "order_gateway", "risk", "timers", "fills", "strategy"]);
+        // Components handed to their tasks can no longer be configured
+        assert!(matches!(services.brackets(), Err(HftError::Config(_))));
+
+        let status = services.status();
+        assert!(status.started);
+        assert_eq!(status.venues.get("SUPERVISED"), Some(&false));
+        let json = serde_json::to_value(&status).unwrap();
+        let gateway = json["components"].as_array().unwrap().iter().find(|c| c["name"] == "order_gateway").unwrap();
+        assert_eq!(gateway["state"], "running");
+        assert_eq!(gateway["queue_depth"], 0);
+
+        services.handles().order_tx.send(Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price: 50000.0,
+            venue: "SUPERVISED".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        }).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while venue.submitted_orders().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.expect("order gateway did not route the order");
+
+        services.stop().await;
+        assert!(!services.supervisor.is_started());
+        let status = services.status();
+        assert!(!status.started);
+        assert!(status.components.iter().all(|c| c.state == TaskState::Stopped));
+    }
+
+    /// Bids one tick under the first quote it sees
+    struct Bidder {
+        sent: bool,
+    }
+
+    impl StrategyPlugin for Bidder {
+        fn name(&self) -> &str {
+            "bidder"
+        }
+
+        fn on_quote(&mut self, quote: &Quote) -> Vec<Order> {
+            if std::mem::replace(&mut self.sent, true) {
+                return Vec::new();
+            }
+            vec![Order {
+                symbol: quote.symbol,
+                side: OrderSide::Buy,
+                quantity: 1.0,
+                price: quote.bid - 0.5,
+                venue: quote.venue,
+                order_type: OrderType::Limit,
+                expire_after: None,
+                strategy: None,
+                bypass_kill_switch: false,
+            }]
+        }
+
+        fn on_fill(&mut self, _fill: &Fill) -> Vec<Order> {
+            Vec::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_strategy_orders_reach_the_venue() {
+        let venue = Arc::new(MockVenue::new("STRATEGY", MockVenueConfig {
+            error_probability: 0.0,
+            latency_ms: 1,
+            ..MockVenueConfig::default()
+        }));
+        let injected = Arc::clone(&venue);
+        let (context_tx, context_rx) = std::sync::mpsc::channel();
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |ctx| {
+                context_tx.send(ctx.quote_tx.clone()).unwrap();
+                injected
+            })
+            .build()
+            .await;
+        let quote_tx = context_rx.recv().unwrap();
+        services.start().await.unwrap();
+        // Loaded into the running runner
+        services.add_strategy(Box::new(Bidder { sent: false }));
+
+        let quote = Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 100.0,
+            ask: 101.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "STRATEGY".into(),
+            timestamp: 1,
+        };
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while venue.submitted_orders().await.is_empty() {
+                quote_tx.send(quote.clone()).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("strategy order did not reach the venue");
+
+        let sent = venue.submitted_orders().await;
+        assert_eq!((sent.len(), sent[0].price, sent[0].strategy.as_deref()), (1, 99.5, Some("bidder")));
+        services.stop().await;
+    }
+
+    /// Bids one tick under every quote it sees
+    struct Repricer;
+
+    impl StrategyPlugin for Repricer {
+        fn name(&self) -> &str {
+            "repricer"
+        }
+
+        fn on_quote(&mut self, quote: &Quote) -> Vec<Order> {
+            vec![Order {
+                symbol: quote.symbol,
+                side: OrderSide::Buy,
+                quantity: 1.0,
+                price: quote.bid - 0.5,
+                venue: quote.venue,
+                order_type: OrderType::Limit,
+                expire_after: None,
+                strategy: None,
+                bypass_kill_switch: false,
+            }]
+        }
+
+        fn on_fill(&mut self, _fill: &Fill) -> Vec<Order> {
+            Vec::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quote_throttle_amends_the_resting_quote() {
+        let venue = Arc::new(MockVenue::new("THROTTLE", MockVenueConfig {
+            error_probability: 0.0,
+            latency_ms: 1,
+            ..MockVenueConfig::default()
+        }));
+        let injected = Arc::clone(&venue);
+        let (context_tx, context_rx) = std::sync::mpsc::channel();
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |ctx| {
+                context_tx.send(ctx.quote_tx.clone()).unwrap();
+                injected
+            })
+            .build()
+            .await;
+        let quote_tx = context_rx.recv().unwrap();
+        let throttle = services.quote_throttle(QuoteThrottleConfig::default()).unwrap();
+        tokio::spawn(throttle.run());
+        services.add_strategy(Box::new(Repricer));
+        services.start().await.unwrap();
+
+        // Each reprice replaces the resting bid rather than adding one
+        for bid in [100.0, 101.0, 102.0] {
+            let quote = Quote {
+                symbol: "BTCUSDT".into(),
+                bid,
+                ask: bid + 1.0,
+                bid_size: 1.0,
+                ask_size: 1.0,
+                venue: "THROTTLE".into(),
+                timestamp: 1,
+            };
+            tokio::time::timeout(Duration::from_secs(2), async {
+                loop {
+                    let open = services.orders.open_orders().await;
+                    if open.len() == 1 && open[0].order.price == bid - 0.5 {
+                        break;
+                    }
+                    quote_tx.send(quote.clone()).await.unwrap();
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+            }).await.expect("quote was not repriced");
+            assert_eq!(venue.open_order_ids().await.len(), 1);
+        }
+
+        // Once cancelled the quote is placed again
+        let order_id = venue.open_order_ids().await.remove(0);
+        services.cancel_tx.send(CancelRequest::order(order_id, "manual", "test")).await.unwrap();
+        let quote = Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 102.0,
+            ask: 103.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "THROTTLE".into(),
+            timestamp: 2,
+        };
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while services.orders.open_orders().await.len() != 1 || venue.open_order_ids().await.len() != 1 {
+                quote_tx.send(quote.clone()).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        }).await.expect("cancelled quote was not placed again");
+        services.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_venue_fills_and_quotes_engage_the_kill_switch() {
+        let (context_tx, context_rx) = std::sync::mpsc::channel();
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |ctx| {
+                context_tx.send((ctx.quote_tx.clone(), ctx.fill_tx.clone())).unwrap();
+                Arc::new(MockVenue::new("FILLS", MockVenueConfig::default()))
+            })
+            .with_loss_limits(LossLimits::parse("daily:100"))
+            .build()
+            .await;
+        let (quote_tx, fill_tx) = context_rx.recv().unwrap();
+        services.start().await.unwrap();
+        let risk = services.handles().risk;
+
+        fill_tx.send(Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "FILLS".into(),
+            strategy: String::new(),
+            side: OrderSide::Buy,
+            quantity: 10.0,
+            price: 100.0,
+            timestamp: 1,
+            commission: None,
+        }).await.unwrap();
+        // Marked at 80 by the book builder, the position is 200 down
+        let quote = Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 80.0,
+            ask: 81.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "FILLS".into(),
+            timestamp: 2,
+        };
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !risk.kill_switch().is_engaged().await {
+                quote_tx.send(quote.clone()).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        }).await.expect("loss limit breach did not engage the kill switch");
+
+        let positions = risk.positions().await;
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].1.quantity, 10.0);
+        services.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_fills_are_hedged_through_the_order_gateway() {
+        let venue = Arc::new(MockVenue::new("HEDGE", MockVenueConfig {
+            error_probability: 0.0,
+            latency_ms: 1,
+            ..MockVenueConfig::default()
+        }));
+        let injected = Arc::clone(&venue);
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |_| injected)
+            .build()
+            .await;
+        let mut hedger = services.hedger(HedgeConfig {
+            assets: HashMap::from([("BTC".to_string(), HedgeTarget {
+                venue: "HEDGE".to_string(),
+                symbol: "BTCUSDT".to_string(),
+                band: 0.5,
+                passive_timeout: Duration::from_secs(5),
+            })]),
+            ..HedgeConfig::default()
+        });
+        services.start().await.unwrap();
+
+        services.fill_sender().send(Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "HEDGE".into(),
+            strategy: "maker".to_string(),
+            side: OrderSide::Buy,
+            quantity: 2.0,
+            price: 50000.0,
+            timestamp: 1,
+            commission: None,
+        }).await.unwrap();
+        let risk = services.handles().risk;
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while risk.positions().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.expect("fill was not booked");
+
+        hedger.tick().await;
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while venue.submitted_orders().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.expect("hedge did not reach the venue");
+        let sent = venue.submitted_orders().await;
+        assert_eq!((sent[0].side, sent[0].quantity, sent[0].strategy.as_deref()), (OrderSide::Sell, 2.0, Some("hedger")));
+
+        // With trading halted the gateway holds the next hedge back
+        risk.kill_switch().engage("test").await;
+        hedger.tick().await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(venue.submitted_orders().await.len(), 1);
+        services.stop().await;
+    }
+
+    fn bracket_entry(venue: &str) -> Bracket {
+        Bracket {
+            entry: Order {
+                symbol: "BTCUSDT".into(),
+                side: OrderSide::Buy,
+                quantity: 1.0,
+                price: 100.0,
+                venue: venue.into(),
+                order_type: OrderType::Limit,
+                expire_after: None,
+                strategy: None,
+                bypass_kill_switch: false,
+            },
+            take_profit: 110.0,
+            stop_loss: 95.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_brackets_go_through_the_order_gateway() {
+        let venue = Arc::new(MockVenue::new("BRACKETS", MockVenueConfig {
+            error_probability: 0.0,
+            latency_ms: 1,
+            ..MockVenueConfig::default()
+        }));
+        let injected = Arc::clone(&venue);
+        let (context_tx, context_rx) = std::sync::mpsc::channel();
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |ctx| {
+                context_tx.send(ctx.quote_tx.clone()).unwrap();
+                injected
+            })
+            .build()
+            .await;
+        let quote_tx = context_rx.recv().unwrap();
+        let brackets = services.brackets().unwrap();
+        services.start().await.unwrap();
+        assert!(services.supervisor.components().contains(&"brackets"));
+
+        brackets.place(bracket_entry("BRACKETS")).unwrap();
+        let wait_for = |count: usize| {
+            let venue = Arc::clone(&venue);
+            tokio::time::timeout(Duration::from_secs(2), async move {
+                while venue.submitted_orders().await.len() < count {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            })
+        };
+        wait_for(1).await.expect("entry did not reach the venue");
+        let entry_id = venue.open_order_ids().await.remove(0);
+        // The ack comes back through the gateway's order events
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while services.orders.get(&entry_id).await.is_none() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.unwrap();
+
+        // The venue's fill reaches the brackets through the fill router
+        services.fill_sender().send(Fill {
+            order_id: entry_id,
+            symbol: "BTCUSDT".into(),
+            venue: "BRACKETS".into(),
+            strategy: String::new(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price: 100.0,
+            timestamp: 1,
+            commission: None,
+        }).await.unwrap();
+        wait_for(2).await.expect("take-profit did not reach the venue");
+        assert_eq!(venue.submitted_orders().await[1].price, 110.0);
+        let take_profit_id = venue.open_order_ids().await.pop().unwrap();
+
+        // The stop fires on the feed: the take-profit is cancelled through
+        // the gateway and the position closed at market
+        let quote = Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 94.0,
+            ask: 94.5,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "BRACKETS".into(),
+            timestamp: 2,
+        };
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while venue.submitted_orders().await.len() < 3 {
+                quote_tx.send(quote.clone()).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("stop did not reach the venue");
+        let stop = venue.submitted_orders().await.remove(2);
+        assert_eq!((stop.order_type, stop.side, stop.quantity), (OrderType::Market, OrderSide::Sell, 1.0));
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while services.orders.get(&take_profit_id).await.is_some() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.expect("take-profit was not cancelled");
+        assert!(!venue.open_order_ids().await.contains(&take_profit_id));
+
+        services.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_bracket_entries_are_risk_checked() {
+        let venue = Arc::new(MockVenue::new("BRACKETS", MockVenueConfig {
+            error_probability: 0.0,
+            latency_ms: 1,
+            ..MockVenueConfig::default()
+        }));
+        let injected = Arc::clone(&venue);
+        // The entry's 100 notional is over the BTC limit
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |_| injected)
+            .with_exposure_limits(ExposureLimits {
+                assets: HashMap::from([("BTC".to_string(), ExposureLimit { max_gross: Some(50.0), max_net: None })]),
+                ..ExposureLimits::default()
+            })
+            .build()
+            .await;
+        let brackets = services.brackets().unwrap();
+        let mut events = services.events().subscribe();
+        services.start().await.unwrap();
+
+        brackets.place(bracket_entry("BRACKETS")).unwrap();
+        let rejected = tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                if let Ok(EngineEvent::OrderRejected { reason, .. }) = events.recv().await {
+                    return reason;
+                }
+            }
+        }).await.expect("entry was not rejected");
+        assert!(rejected.contains("BTC"), "{}", rejected);
+        assert!(venue.submitted_orders().await.is_empty());
+        services.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_amends_go_through_the_running_order_gateway() {
+        let venue = Arc::new(MockVenue::new("AMEND", MockVenueConfig {
+            error_probability: 0.0,
+            latency_ms: 1,
+            ..MockVenueConfig::default()
+        }));
+        let injected = Arc::clone(&venue);
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |_| injected)
+            .with_exposure_limits(ExposureLimits {
+                assets: HashMap::from([("BTC".to_string(), ExposureLimit { max_gross: Some(250.0), max_net: None })]),
+                ..ExposureLimits::default()
+            })
+            .build()
+            .await;
+        services.start().await.unwrap();
+
+        services.handles().order_tx.send(Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price: 100.0,
+            venue: "AMEND".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        }).await.unwrap();
+        let order_id = tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                if let Some(open) = services.orders.open_orders().await.pop() {
+                    return open.order_id;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.expect("order was not tracked");
+
+        // The mock venue replaces the order while the gateway runs
+        let (request, reply) = AmendRequest::new(order_id.clone(), 99.0, 2.0).with_reply();
+        services.amend_sender().send(request).await.unwrap();
+        let new_order_id = reply.await.unwrap().unwrap();
+        assert_ne!(new_order_id, order_id);
+        assert_eq!(venue.open_order_ids().await, vec![new_order_id.clone()]);
+        assert_eq!(services.orders.get(&new_order_id).await.unwrap().order.quantity, 2.0);
+
+        // 5 at 99 is over the BTC limit, so the order is left as it was
+        let (request, reply) = AmendRequest::new(new_order_id.clone(), 99.0, 5.0).with_reply();
+        services.amend_sender().send(request).await.unwrap();
+        let result = reply.await.unwrap();
+        assert!(result.is_err(), "{:?}", result);
+        assert_eq!(venue.open_order_ids().await, vec![new_order_id.clone()]);
+        assert_eq!(venue.submitted_orders().await.len(), 2);
+        assert_eq!(services.orders.get(&new_order_id).await.unwrap().order.quantity, 2.0);
+
+        services.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_trailing_stops_close_through_the_order_gateway() {
+        let venue = Arc::new(MockVenue::new("TRAIL", MockVenueConfig {
+            error_probability: 0.0,
+            latency_ms: 1,
+            ..MockVenueConfig::default()
+        }));
+        let injected = Arc::clone(&venue);
+        let (context_tx, context_rx) = std::sync::mpsc::channel();
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |ctx| {
+                context_tx.send(ctx.quote_tx.clone()).unwrap();
+                injected
+            })
+            .build()
+            .await;
+        let quote_tx = context_rx.recv().unwrap();
+        let stops = services.trailing_stops(TrailingStopConfig::default().with_strategy("breakout", TrailDistance::Price(2.0)));
+        services.start().await.unwrap();
+        assert!(services.supervisor.components().contains(&"trailing_stops"));
+
+        let id = stops.designate(ProtectedPosition {
+            venue: "TRAIL".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            strategy: "breakout".to_string(),
+            side: OrderSide::Buy,
+            quantity: 0.5,
+            entry_price: 100.0,
+        }).unwrap();
+        let quote = |bid: f64, timestamp: u64| Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask: bid + 0.5,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "TRAIL".into(),
+            timestamp,
+        };
+        // A new high on the feed lifts the stop
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while stops.stop_price(id) != Some(103.0) {
+                quote_tx.send(quote(105.0, 1)).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("stop did not follow the feed");
+        assert!(venue.submitted_orders().await.is_empty());
+
+        // Falling back to it closes the long at market on the venue
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while venue.submitted_orders().await.is_empty() {
+                quote_tx.send(quote(102.5, 2)).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("stop did not reach the venue");
+        let close = venue.submitted_orders().await.remove(0);
+        assert_eq!((close.order_type, close.side, close.quantity), (OrderType::Market, OrderSide::Sell, 0.5));
+        assert_eq!(close.strategy.as_deref(), Some("breakout"));
+        assert!(stops.stop_price(id).is_none());
+        services.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_leg_refused_by_risk_unwinds_the_package() {
+        let venue = Arc::new(MockVenue::new("LEGS", MockVenueConfig {
+            error_probability: 0.0,
+            latency_ms: 1,
+            ..MockVenueConfig::default()
+        }));
+        let injected = Arc::clone(&venue);
+        // The ETH leg's 3000 notional is over its limit, the BTC leg is not
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |_| injected)
+            .with_exposure_limits(ExposureLimits {
+                assets: HashMap::from([("ETH".to_string(), ExposureLimit { max_gross: Some(1000.0), max_net: None })]),
+                ..ExposureLimits::default()
+            })
+            .build()
+            .await;
+        let legs = services.legs().unwrap();
+        services.start().await.unwrap();
+
+        let leg = |symbol: &str, side, price| Order {
+            symbol: symbol.into(),
+            side,
+            quantity: 1.0,
+            price,
+            venue: "LEGS".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: Some("spread".to_string()),
+            bypass_kill_switch: false,
+        };
+        legs.execute(vec![leg("BTCUSDT", OrderSide::Buy, 100.0), leg("ETHUSDT", OrderSide::Sell, 3000.0)], LegOutPolicy::Hedge).unwrap();
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while venue.open_order_ids().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.expect("BTC leg did not reach the venue");
+        let btc_leg = venue.open_order_ids().await.remove(0);
+        // Both legs reported on, so the package has legged
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(venue.submitted_orders().await.len(), 1);
+
+        // The live leg's fill is unwound through the gateway
+        services.fill_sender().send(Fill {
+            order_id: btc_leg,
+            symbol: "BTCUSDT".into(),
+            venue: "LEGS".into(),
+            strategy: String::new(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price: 100.0,
+            timestamp: 1,
+            commission: None,
+        }).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while venue.submitted_orders().await.len() < 2 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.expect("legged fill was not unwound");
+        let unwind = venue.submitted_orders().await.remove(1);
+        assert_eq!((unwind.symbol.as_str(), unwind.side, unwind.quantity, unwind.order_type), ("BTCUSDT", OrderSide::Sell, 1.0, OrderType::Market));
+        services.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_multileg_packages_go_through_the_order_gateway() {
+        let venue = Arc::new(MockVenue::new("LEGS", MockVenueConfig {
+            error_probability: 0.0,
+            latency_ms: 1,
+            ..MockVenueConfig::default()
+        }));
+        let injected = Arc::clone(&venue);
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |_| injected)
+            .build()
+            .await;
+        let legs = services.legs().unwrap();
+        services.start().await.unwrap();
+
+        let leg = |symbol: &str, side, price| Order {
+            symbol: symbol.into(),
+            side,
+            quantity: 1.0,
+            price,
+            venue: "LEGS".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: Some("spread".to_string()),
+            bypass_kill_switch: false,
+        };
+        legs.execute(vec![leg("BTCUSDT", OrderSide::Buy, 100.0), leg("ETHUSDT", OrderSide::Sell, 3000.0)], LegOutPolicy::Hedge).unwrap();
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while venue.open_order_ids().await.len() < 2 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.expect("legs did not reach the venue");
+        // Both legs are tracked from the gateway's acks
+        for id in venue.open_order_ids().await {
+            tokio::time::timeout(Duration::from_secs(2), async {
+                while services.orders.get(&id).await.is_none() {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            }).await.expect("leg was not tracked");
+            assert_eq!(services.orders.get(&id).await.unwrap().order.strategy.as_deref(), Some("spread"));
+        }
+
+        // A halted engine holds the next package back at the gateway
+        services.handles().risk.kill_switch().engage("operator").await;
+        legs.execute(vec![leg("BTCUSDT", OrderSide::Buy, 100.0), leg("ETHUSDT", OrderSide::Sell, 3000.0)], LegOutPolicy::Hedge).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(venue.submitted_orders().await.len(), 2);
+        services.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_liquidation_reductions_go_through_the_order_gateway() {
+        let venue = Arc::new(MockVenue::new("LIQ", MockVenueConfig {
+            error_probability: 0.0,
+            latency_ms: 1,
+            ..MockVenueConfig::default()
+        }));
+        // 4.3% from liquidation, inside the default 5% reduce threshold
+        venue.set_position_risks(vec![PositionRisk { symbol: "BTCUSDT".to_string(), quantity: 2.0, mark_price: 94.0, liquidation_price: 90.0 }]).await;
+        let injected = Arc::clone(&venue);
+        let mut instruments = InstrumentMap::new();
+        instruments.insert("BTC-PERP", "LIQ", "BTCUSDT");
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |_| injected)
+            .build()
+            .await
+            .with_instruments(instruments)
+            .unwrap()
+            .with_liquidation_guard(LiquidationConfig { reduce_fraction: 0.5, ..LiquidationConfig::default() });
+        let mut events = services.events().subscribe();
+        services.start().await.unwrap();
+        assert!(services.supervisor.components().contains(&"liquidation"));
+
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while venue.submitted_orders().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.expect("reduction did not reach the venue");
+        // Sent as the canonical instrument, mapped back to the venue's
+        // symbol by the gateway
+        let order = venue.submitted_orders().await.remove(0);
+        assert_eq!((order.symbol.as_str(), order.side, order.quantity, order.order_type), ("BTCUSDT", OrderSide::Sell, 1.0, OrderType::Market));
+        let reducing = tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                if let Ok(EngineEvent::LiquidationRisk { symbol, reducing, .. }) = events.recv().await {
+                    return (symbol, reducing);
+                }
+            }
+        }).await.expect("no liquidation event");
+        assert_eq!(reducing, ("BTCUSDT".to_string(), true));
+        services.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_risk_loop_releases_only_loss_limit_halts() {
+        let (context_tx, context_rx) = std::sync::mpsc::channel();
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |ctx| {
+                context_tx.send(ctx.quote_tx.clone()).unwrap();
+                Arc::new(MockVenue::new("HALTS", MockVenueConfig::default()))
+            })
+            .with_loss_limits(LossLimits::parse("daily:100,cooldown:0.01"))
+            .build()
+            .await;
+        let quote_tx = context_rx.recv().unwrap();
+        services.start().await.unwrap();
+        let risk = services.handles().risk;
+
+        services.fill_sender().send(Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "HALTS".into(),
+            strategy: String::new(),
+            side: OrderSide::Buy,
+            quantity: 10.0,
+            price: 100.0,
+            timestamp: 1,
+            commission: None,
+        }).await.unwrap();
+        let quote = |bid: f64| Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask: bid + 1.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "HALTS".into(),
+            timestamp: 2,
+        };
+        // 200 down: the loss limits halt trading
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !risk.kill_switch().is_engaged().await {
+                quote_tx.send(quote(80.0)).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        }).await.expect("loss limit breach did not engage the kill switch");
+        assert_eq!(risk.kill_switch().owner().await, Some(HaltOwner::LossLimits));
+
+        // Back within the limit, the risk loop lifts its own halt
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while risk.kill_switch().is_engaged().await {
+                quote_tx.send(quote(95.0)).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        }).await.expect("loss limit halt was not released");
+
+        // but never an operator's
+        risk.kill_switch().engage("operator").await;
+        for _ in 0..25 {
+            quote_tx.send(quote(95.0)).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(risk.kill_switch().owner().await, Some(HaltOwner::Operator));
+        services.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_added_venue_is_subscribed_and_routable() {
+        let config = MockVenueConfig { error_probability: 0.0, latency_ms: 1, ..MockVenueConfig::default() };
+        let (quote_tx, _quote_rx) = mpsc::channel(1000);
+        let first = Arc::new(MockVenue::new("FIRST", config.clone()).with_quote_sender(quote_tx.clone()));
+        let injected = Arc::clone(&first);
+        let mut services = ServicesBuilder::new()
+            .with_venue(move |_| injected)
+            .build()
+            .await;
+        services.start().await.unwrap();
+        services.quote_gateway.subscribe(vec!["BTCUSDT".to_string()]).await.unwrap();
+
+        let added = Arc::new(MockVenue::new("ADDED", config).with_quote_sender(quote_tx));
+        services.add_venue(added.clone()).await;
+        assert_eq!(services.handles().venues.names(), vec!["FIRST", "ADDED"]);
+        assert_eq!(services.quote_gateway.subscriptions.read().await.get("ADDED"), Some(&vec!["BTCUSDT".to_string()]));
+
+        services.handles().order_tx.send(Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Sell,
+            quantity: 1.0,
+            price: 50000.0,
+            venue: "ADDED".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        }).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while added.submitted_orders().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.expect("order gateway did not route to the added venue");
+
+        services.stop().await;
+        first.stop().await;
+        added.stop().await;
+    }
+
+}
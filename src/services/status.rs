@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use warp::Filter;
+
+use crate::health::{HealthRegistry, Probe};
+use crate::command::mode::{TradingMode, TradingState};
+use crate::risk::TradingToggles;
+use crate::types::{Order, Quote};
+use super::supervisor::{TaskState, TaskTable};
+
+/// How one supervised component is doing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentReport {
+    pub name: String,
+    pub state: TaskState,
+    pub restarts: u32,
+    /// Times the task panicked, including panics it was restarted after
+    pub panics: u32,
+    /// Since the component last reported a heartbeat, when it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_activity_ms_ago: Option<u64>,
+    /// Messages waiting on the component's input channel, when it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_depth: Option<usize>,
+}
+
+/// Engine state for the CLI and `GET /admin/status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineStatus {
+    /// Whether `Services::start` has spawned the components
+    pub started: bool,
+    #[serde(default)]
+    pub mode: TradingMode,
+    pub components: Vec<ComponentReport>,
+    /// Venue connectivity by venue name
+    pub venues: BTreeMap<String, bool>,
+    pub paused_symbols: Vec<String>,
+    pub paused_strategies: Vec<String>,
+}
+
+impl fmt::Display for EngineStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Trading system {}, mode {}", if self.started { "running" } else { "not started" }, self.mode)?;
+        for c in &self.components {
+            write!(f, "\n  {}: {:?}, {} restarts, {} panics", c.name, c.state, c.restarts, c.panics)?;
+            if let Some(ms) = c.last_activity_ms_ago {
+                write!(f, ", active {}ms ago", ms)?;
+            }
+            if let Some(depth) = c.queue_depth {
+                write!(f, ", {} queued", depth)?;
+            }
+        }
+        for (venue, connected) in &self.venues {
+            write!(f, "\n  venue {}: {}", venue, if *connected { "connected" } else { "disconnected" })?;
+        }
+        if !self.paused_symbols.is_empty() {
+            write!(f, "\n  paused symbols: {}", self.paused_symbols.join(", "))?;
+        }
+        if !self.paused_strategies.is_empty() {
+            write!(f, "\n  paused strategies: {}", self.paused_strategies.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Messages waiting in a channel, without keeping the channel open
+fn queue_depth<T>(tx: &mpsc::WeakSender<T>) -> Option<usize> {
+    tx.upgrade().map(|tx| tx.max_capacity() - tx.capacity())
+}
+
+/// Builds [`EngineStatus`] from state the components share, so it can be
+/// read while they run
+#[derive(Clone)]
+pub struct StatusSource {
+    pub(crate) tasks: TaskTable,
+    pub(crate) health: HealthRegistry,
+    pub(crate) toggles: Arc<TradingToggles>,
+    pub(crate) trading: TradingState,
+    /// Input of the book builder
+    pub(crate) quotes: mpsc::WeakSender<Quote>,
+    /// Input of the order gateway
+    pub(crate) orders: mpsc::WeakSender<Order>,
+}
+
+impl StatusSource {
+    pub fn status(&self) -> EngineStatus {
+        let health = self.health.report(Probe::Readiness);
+        let tasks = self.tasks.read().unwrap();
+        let components = tasks.iter()
+            .map(|(name, counters)| ComponentReport {
+                name: name.to_string(),
+                state: counters.state(),
+                restarts: counters.restarts(),
+                panics: counters.panics(),
+                last_activity_ms_ago: health.components.get(*name).map(|c| c.last_beat_ms_ago),
+                queue_depth: match *name {
+                    "book_builder" => queue_depth(&self.quotes),
+                    "order_gateway" => queue_depth(&self.orders),
+                    _ => None,
+                },
+            })
+            .collect();
+        let venues = health.components.iter()
+            .filter_map(|(name, c)| Some((name.strip_prefix("venue:")?.to_string(), c.healthy)))
+            .collect();
+        let disabled = self.toggles.disabled();
+
+        EngineStatus {
+            started: tasks.values().any(|c| c.state() != TaskState::Stopped),
+            mode: self.trading.mode(),
+            components,
+            venues,
+            paused_symbols: disabled.symbols,
+            paused_strategies: disabled.strategies,
+        }
+    }
+}
+
+/// `GET /admin/status`
+pub fn routes(
+    source: StatusSource,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "status")
+        .and(warp::get())
+        .map(move || warp::reply::json(&source.status()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::supervisor::TaskCounters;
+
+    #[tokio::test]
+    async fn test_status_reports_components_venues_and_pauses() {
+        let tasks = TaskTable::default();
+        for name in ["book_builder", "order_gateway", "strategy"] {
+            tasks.write().unwrap().insert(name, Arc::<TaskCounters>::default());
+        }
+        let health = HealthRegistry::new();
+        health.register("strategy", Probe::Liveness, None);
+        health.register_venue("BINANCE").set_healthy();
+        health.register_venue("MOCK");
+        let toggles = Arc::new(TradingToggles::new());
+        toggles.disable_symbol("ETHUSDT");
+        toggles.disable_strategy("mm");
+        let trading = TradingState::new();
+        trading.transition(TradingMode::Halted, "test").unwrap();
+        let (quote_tx, _quote_rx) = mpsc::channel(8);
+        let (order_tx, _order_rx) = mpsc::channel::<Order>(8);
+        for timestamp in 0..2 {
+            quote_tx.try_send(Quote {
+                symbol: "BTCUSDT".into(),
+                bid: 100.0,
+                ask: 101.0,
+                bid_size: 1.0,
+                ask_size: 1.0,
+                venue: "MOCK".into(),
+                timestamp,
+            }).unwrap();
+        }
+        let source = StatusSource { tasks, health, toggles, trading, quotes: quote_tx.downgrade(), orders: order_tx.downgrade() };
+
+        let status = source.status();
+        assert!(status.started);
+        assert_eq!(status.mode, TradingMode::Halted);
+        let component = |name: &str| status.components.iter().find(|c| c.name == name).unwrap().clone();
+        assert_eq!((component("book_builder").state, component("book_builder").queue_depth), (TaskState::Running, Some(2)));
+        assert_eq!(component("order_gateway").queue_depth, Some(0));
+        assert!(component("strategy").last_activity_ms_ago.is_some());
+        assert!(component("strategy").queue_depth.is_none());
+        assert_eq!(status.venues, BTreeMap::from([("BINANCE".to_string(), true), ("MOCK".to_string(), false)]));
+        assert_eq!((status.paused_symbols.as_slice(), status.paused_strategies.as_slice()), (&["ETHUSDT".to_string()][..], &["mm".to_string()][..]));
+
+        let text = status.to_string();
+        assert!(text.starts_with("Trading system running, mode halted"), "{}", text);
+        assert!(text.contains("book_builder: Running, 0 restarts, 0 panics, 2 queued"), "{}", text);
+        assert!(text.contains("venue MOCK: disconnected"), "{}", text);
+        assert!(text.contains("paused symbols: ETHUSDT"), "{}", text);
+
+        // A closed input has no depth to report
+        drop(quote_tx);
+        let response = warp::test::request().path("/admin/status").reply(&routes(source)).await;
+        let served: EngineStatus = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(served.mode, TradingMode::Halted);
+        assert_eq!(served.components.len(), 3);
+        assert!(served.components.iter().find(|c| c.name == "book_builder").unwrap().queue_depth.is_none());
+        assert_eq!(served.venues, status.venues);
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use std::sync::{Arc, RwLock};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::metrics::COMPONENT_RESTARTS;
+use super::emergency::{self, SUPERVISED};
+
+/// What the supervisor does when a component task ends
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RestartPolicy {
+    /// Leave the component stopped
+    Never,
+    /// Restart after a panic, up to `max_restarts` times; a clean exit is
+    /// final
+    OnPanic { max_restarts: u32, backoff: Duration },
+    /// Restart whenever the task ends, panicked or not
+    Always { backoff: Duration },
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy::OnPanic { max_restarts: 5, backoff: Duration::from_secs(1) }
+    }
+}
+
+impl RestartPolicy {
+    /// The delay before the next run, or `None` to leave the component
+    /// stopped
+    fn restart_after(&self, panicked: bool, restarts: u32) -> Option<Duration> {
+        match *self {
+            RestartPolicy::Never => None,
+            RestartPolicy::OnPanic { max_restarts, backoff } => (panicked && restarts < max_restarts).then_some(backoff),
+            RestartPolicy::Always { backoff } => Some(backoff),
+        }
+    }
+}
+
+/// Lifecycle of a supervised component task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Running,
+    /// Ended and waiting out the restart backoff
+    Restarting,
+    /// Returned on its own and was not restarted
+    Exited,
+    /// Panicked and was not restarted
+    Failed,
+    /// Stopped by the supervisor
+    Stopped,
+}
+
+impl TaskState {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => TaskState::Running,
+            1 => TaskState::Restarting,
+            2 => TaskState::Exited,
+            3 => TaskState::Failed,
+            _ => TaskState::Stopped,
+        }
+    }
+}
+
+/// Counters a supervised task updates as it runs
+#[derive(Debug, Default)]
+pub(crate) struct TaskCounters {
+    state: AtomicU8,
+    restarts: AtomicU32,
+    panics: AtomicU32,
+}
+
+impl TaskCounters {
+    fn set(&self, state: TaskState) {
+        self.state.store(state as u8, Ordering::Relaxed);
+    }
+
+    pub(crate) fn state(&self) -> TaskState {
+        TaskState::from_u8(self.state.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn restarts(&self) -> u32 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn panics(&self) -> u32 {
+        self.panics.load(Ordering::Relaxed)
+    }
+}
+
+/// Counters of every supervised task by component name, shared with status
+/// reporting
+pub(crate) type TaskTable = Arc<RwLock<BTreeMap<&'static str, Arc<TaskCounters>>>>;
+
+/// Runs component tasks and restarts them according to their policy
+pub(crate) struct Supervisor {
+    tasks: Vec<(&'static str, JoinHandle<()>)>,
+    counters: TaskTable,
+    shutdown: watch::Sender<bool>,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self { tasks: Vec::new(), counters: TaskTable::default(), shutdown: watch::channel(false).0 }
+    }
+}
+
+impl Supervisor {
+    /// Whether any component has been started
+    pub(crate) fn is_started(&self) -> bool {
+        !self.tasks.is_empty()
+    }
+
+    /// Names of the supervised components, in start order
+    pub(crate) fn components(&self) -> Vec<&'static str> {
+        self.tasks.iter().map(|(name, _)| *name).collect()
+    }
+
+    pub(crate) fn counters(&self) -> TaskTable {
+        Arc::clone(&self.counters)
+    }
+
+    /// Run `run` as component `name`, calling it again for each restart
+    pub(crate) fn spawn<F, Fut>(&mut self, name: &'static str, policy: RestartPolicy, run: F)
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut shutdown = self.shutdown.subscribe();
+        let counters = Arc::new(TaskCounters::default());
+        self.counters.write().unwrap().insert(name, Arc::clone(&counters));
+        let handle = tokio::spawn(async move {
+            let mut restarts = 0;
+            loop {
+                counters.set(TaskState::Running);
+                info!(component = name, "Component started");
+                let mut task = tokio::spawn(SUPERVISED.scope(name, run()));
+                let outcome = tokio::select! {
+                    outcome = &mut task => outcome,
+                    _ = shutdown.changed() => {
+                        task.abort();
+                        counters.set(TaskState::Stopped);
+                        return;
+                    }
+                };
+
+                let panicked = match outcome {
+                    Ok(()) => false,
+                    Err(e) if e.is_panic() => true,
+                    // Aborted from outside the supervisor
+                    Err(_) => {
+                        counters.set(TaskState::Stopped);
+                        return;
+                    }
+                };
+                if panicked {
+                    counters.panics.fetch_add(1, Ordering::Relaxed);
+                }
+                let Some(backoff) = policy.restart_after(panicked, restarts) else {
+                    if panicked {
+                        error!(component = name, restarts, "Component panicked, leaving it stopped");
+                        counters.set(TaskState::Failed);
+                        emergency::escalate(&format!("component {} failed", name));
+                    } else {
+                        info!(component = name, "Component exited");
+                        counters.set(TaskState::Exited);
+                    }
+                    return;
+                };
+
+                restarts += 1;
+                counters.set(TaskState::Restarting);
+                counters.restarts.fetch_add(1, Ordering::Relaxed);
+                COMPONENT_RESTARTS.with_label_values(&[name]).inc();
+                warn!(component = name, panicked, restarts, backoff = ?backoff, "Restarting component");
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = shutdown.changed() => {
+                        counters.set(TaskState::Stopped);
+                        return;
+                    }
+                }
+            }
+        });
+        self.tasks.push((name, handle));
+    }
+
+    /// Stop every component and wait for the supervising tasks to finish
+    pub(crate) async fn stop(&mut self) {
+        let _ = self.shutdown.send(true);
+        for (name, handle) in self.tasks.drain(..) {
+            if let Err(e) = handle.await {
+                error!(component = name, error = %e, "Supervisor task failed");
+            }
+        }
+        self.shutdown = watch::channel(false).0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    async fn runs_with(policy: RestartPolicy, panics: usize) -> (usize, TaskState) {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let mut supervisor = Supervisor::default();
+        let counter = Arc::clone(&runs);
+        supervisor.spawn("flaky", policy, move || {
+            let counter = Arc::clone(&counter);
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) < panics {
+                    panic!("flaky component");
+                }
+            }
+        });
+        let (_, handle) = supervisor.tasks.pop().unwrap();
+        handle.await.unwrap();
+        let state = supervisor.counters.read().unwrap()["flaky"].state();
+        (runs.load(Ordering::SeqCst), state)
+    }
+
+    #[tokio::test]
+    async fn test_restart_policies() {
+        let backoff = Duration::from_millis(1);
+        assert_eq!(runs_with(RestartPolicy::Never, 5).await, (1, TaskState::Failed));
+        // Restarted until it exits cleanly
+        assert_eq!(runs_with(RestartPolicy::OnPanic { max_restarts: 5, backoff }, 2).await, (3, TaskState::Exited));
+        // Gives up after the restart budget
+        assert_eq!(runs_with(RestartPolicy::OnPanic { max_restarts: 2, backoff }, 5).await, (3, TaskState::Failed));
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::error::HftError;
+use crate::types::{Candle, Trade};
+use crate::venues::VenueAdapter;
+
+/// Parse an interval such as `15s`, `1m`, `4h` or `1d`
+pub fn parse_interval(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let (count, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit())?);
+    let count: u64 = count.parse().ok().filter(|&c| c > 0)?;
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return None,
+    };
+    Some(Duration::from_secs(count * secs))
+}
+
+/// A venue's candles of one interval for one symbol
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SeriesKey {
+    venue: String,
+    symbol: String,
+    interval: Duration,
+}
+
+#[derive(Debug)]
+struct Series {
+    /// Candles kept, oldest dropped first
+    capacity: usize,
+    candles: VecDeque<Candle>,
+}
+
+impl Series {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            candles: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, candle: Candle) {
+        self.candles.push_back(candle);
+        while self.candles.len() > self.capacity {
+            self.candles.pop_front();
+        }
+    }
+
+    fn apply_trade(&mut self, interval_ms: u64, price: f64, quantity: f64, timestamp: u64) {
+        let open_time = timestamp - timestamp % interval_ms;
+        match self.candles.back_mut() {
+            Some(last) if last.open_time == open_time => {
+                last.high = last.high.max(price);
+                last.low = last.low.min(price);
+                last.close = price;
+                last.volume += quantity;
+            }
+            // Late trades for a candle already closed are ignored
+            Some(last) if last.open_time > open_time => {}
+            _ => self.push(Candle {
+                open_time,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume: quantity,
+            }),
+        }
+    }
+
+    /// Replace history with `fetched`, keeping live candles newer than it
+    fn seed(&mut self, fetched: Vec<Candle>) {
+        let Some(last_fetched) = fetched.last().map(|c| c.open_time) else {
+            return;
+        };
+        let live: Vec<Candle> = self.candles.drain(..).filter(|c| c.open_time > last_fetched).collect();
+        for candle in fetched.into_iter().chain(live) {
+            self.push(candle);
+        }
+    }
+}
+
+/// OHLCV candles per venue, symbol and interval, seeded from venue history
+/// and kept current from the live trade stream. Clones share the cache.
+#[derive(Debug, Clone, Default)]
+pub struct CandleCache {
+    series: Arc<RwLock<HashMap<SeriesKey, Series>>>,
+}
+
+impl CandleCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn key(venue: &str, symbol: &str, interval: Duration) -> SeriesKey {
+        SeriesKey {
+            venue: venue.to_string(),
+            symbol: symbol.to_string(),
+            interval,
+        }
+    }
+
+    /// Build `interval` candles for `symbol` from trades on `venue`,
+    /// keeping the latest `capacity`
+    pub fn track(&self, venue: &str, symbol: &str, interval: Duration, capacity: usize) {
+        self.series.write().unwrap()
+            .entry(Self::key(venue, symbol, interval))
+            .or_insert_with(|| Series::new(capacity));
+    }
+
+    /// Load historical candles, oldest first, into a tracked series
+    pub fn seed(&self, venue: &str, symbol: &str, interval: Duration, candles: Vec<Candle>) {
+        let mut series = self.series.write().unwrap();
+        let capacity = candles.len();
+        series
+            .entry(Self::key(venue, symbol, interval))
+            .or_insert_with(|| Series::new(capacity))
+            .seed(candles);
+    }
+
+    /// Fetch the last `limit` candles from `venue` and keep building them
+    /// from trades. Returns how many candles were loaded; venues without
+    /// candle history start empty.
+    pub async fn warm_up(
+        &self,
+        venue: &dyn VenueAdapter,
+        symbol: &str,
+        venue_symbol: &str,
+        interval: Duration,
+        limit: usize,
+    ) -> Result<usize, HftError> {
+        let venue_name = venue.name().await;
+        self.track(&venue_name, symbol, interval, limit);
+
+        let candles = venue.fetch_candles(venue_symbol, interval, limit).await?;
+        let loaded = candles.len();
+        self.seed(&venue_name, symbol, interval, candles);
+        Ok(loaded)
+    }
+
+    /// Fold a trade into every tracked series for its venue and symbol
+    pub fn on_trade(&self, trade: &Trade) {
+        if trade.price <= 0.0 {
+            warn!(venue = %trade.venue, symbol = %trade.symbol, price = trade.price, "Ignoring trade with invalid price");
+            return;
+        }
+
+        let mut series = self.series.write().unwrap();
+        for (key, series) in series.iter_mut() {
+            if key.venue == trade.venue && key.symbol == trade.symbol {
+                let interval_ms = key.interval.as_millis().max(1) as u64;
+                series.apply_trade(interval_ms, trade.price, trade.quantity, trade.timestamp);
+            }
+        }
+    }
+
+    /// Candles for a series, oldest first. The last one is still forming.
+    pub fn candles(&self, venue: &str, symbol: &str, interval: Duration) -> Vec<Candle> {
+        self.series.read().unwrap()
+            .get(&Self::key(venue, symbol, interval))
+            .map(|s| s.candles.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Latest candle of a series, possibly still forming
+    pub fn latest(&self, venue: &str, symbol: &str, interval: Duration) -> Option<Candle> {
+        self.series.read().unwrap()
+            .get(&Self::key(venue, symbol, interval))
+            .and_then(|s| s.candles.back().cloned())
+    }
+}
+
+/// Candle series to warm up at startup
+#[derive(Debug, Clone, Default)]
+pub struct CandleConfig {
+    /// (symbol, interval, candles of history)
+    pub series: Vec<(String, Duration, usize)>,
+}
+
+impl CandleConfig {
+    /// Read `HFT_CANDLES` as comma separated `SYMBOL:INTERVAL:COUNT` entries,
+    /// e.g. `BTCUSDT:1m:500,ETHUSDT:5m:200`
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var("HFT_CANDLES").ok()?;
+
+        let mut config = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = match entry.split(':').collect::<Vec<_>>()[..] {
+                [symbol, interval, count] if !symbol.is_empty() => parse_interval(interval)
+                    .zip(count.parse().ok())
+                    .map(|(interval, count)| (symbol.to_string(), interval, count)),
+                _ => None,
+            };
+            match parsed {
+                Some(series) => config.series.push(series),
+                None => warn!(entry = entry, "Ignoring malformed candle series"),
+            }
+        }
+        Some(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderSide;
+
+    fn trade(price: f64, quantity: f64, timestamp: u64) -> Trade {
+        Trade {
+            symbol: "BTCUSDT".into(),
+            venue: "MOCK".into(),
+            price,
+            quantity,
+            side: OrderSide::Buy,
+            timestamp,
+        }
+    }
+
+    fn candle(open_time: u64, close: f64) -> Candle {
+        Candle { open_time, open: close, high: close, low: close, close, volume: 1.0 }
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("15s"), Some(Duration::from_secs(15)));
+        assert_eq!(parse_interval("4h"), Some(Duration::from_secs(4 * 3600)));
+        assert_eq!(parse_interval("0m"), None);
+        assert_eq!(parse_interval("1w"), None);
+        assert_eq!(parse_interval("m"), None);
+    }
+
+    #[test]
+    fn test_candles_built_from_trades_after_history() {
+        let cache = CandleCache::new();
+        let minute = Duration::from_secs(60);
+        cache.track("MOCK", "BTCUSDT", minute, 3);
+
+        // A trade before warm-up finishes, inside the last fetched candle
+        cache.on_trade(&trade(99.0, 1.0, 120_500));
+        cache.seed("MOCK", "BTCUSDT", minute, vec![candle(0, 100.0), candle(60_000, 101.0), candle(120_000, 102.0)]);
+        assert_eq!(cache.candles("MOCK", "BTCUSDT", minute).len(), 3);
+
+        cache.on_trade(&trade(103.0, 1.0, 180_000));
+        cache.on_trade(&trade(105.0, 2.0, 200_000));
+        cache.on_trade(&trade(104.0, 0.5, 239_999));
+        // Late trade for a closed candle
+        cache.on_trade(&trade(1.0, 1.0, 130_000));
+
+        let candles = cache.candles("MOCK", "BTCUSDT", minute);
+        assert_eq!(candles.len(), 3);
+        assert_eq!(candles[0].open_time, 60_000);
+        assert_eq!(candles[1], candle(120_000, 102.0));
+        assert_eq!(candles[2], Candle { open_time: 180_000, open: 103.0, high: 105.0, low: 103.0, close: 104.0, volume: 3.5 });
+
+        // Untracked series are not built
+        assert!(cache.candles("OTHER", "BTCUSDT", minute).is_empty());
+    }
+}
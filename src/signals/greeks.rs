@@ -0,0 +1,352 @@
+//! Implied volatility and greeks of option quotes.
+//!
+//! Options are priced with Black-Scholes on the latest mid of their
+//! underlying, and each option's implied volatility is solved from its own
+//! mid. Symbols that parse as options (see [`OptionInstrument::parse`])
+//! are tracked as options; the rest of the quote stream updates underlying
+//! prices.
+
+use std::collections::HashMap;
+use std::f64::consts::{PI, SQRT_2};
+use std::sync::{Arc, RwLock};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::types::{OptionInstrument, OptionKind, Quote, Symbol};
+
+/// Implied volatility search range, as annualized fractions
+const MIN_VOL: f64 = 1e-4;
+const MAX_VOL: f64 = 10.0;
+const VOL_TOLERANCE: f64 = 1e-8;
+
+fn norm_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * PI).sqrt()
+}
+
+/// Abramowitz and Stegun 7.1.26, accurate to about 1e-7
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    if x < 0.0 { -y } else { y }
+}
+
+fn norm_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / SQRT_2))
+}
+
+/// An option's price sensitivities, in the underlying's quote currency
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct Greeks {
+    /// Change in price per unit change in the underlying
+    pub delta: f64,
+    /// Change in delta per unit change in the underlying
+    pub gamma: f64,
+    /// Change in price per volatility point (0.01)
+    pub vega: f64,
+    /// Change in price per calendar day
+    pub theta: f64,
+}
+
+/// Inputs to Black-Scholes besides volatility
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PricingInputs {
+    pub kind: OptionKind,
+    pub underlying: f64,
+    pub strike: f64,
+    /// Years to expiry
+    pub years: f64,
+    /// Continuously compounded risk-free rate
+    pub rate: f64,
+}
+
+impl PricingInputs {
+    fn d1_d2(&self, vol: f64) -> (f64, f64) {
+        let sd = vol * self.years.sqrt();
+        let d1 = ((self.underlying / self.strike).ln() + (self.rate + 0.5 * vol * vol) * self.years) / sd;
+        (d1, d1 - sd)
+    }
+
+    fn discount(&self) -> f64 {
+        (-self.rate * self.years).exp()
+    }
+
+    /// Black-Scholes price at `vol`, the intrinsic value at expiry
+    pub fn price(&self, vol: f64) -> f64 {
+        let (s, k) = (self.underlying, self.strike);
+        if self.years <= 0.0 || vol <= 0.0 {
+            return match self.kind {
+                OptionKind::Call => (s - k * self.discount()).max(0.0),
+                OptionKind::Put => (k * self.discount() - s).max(0.0),
+            };
+        }
+        let (d1, d2) = self.d1_d2(vol);
+        match self.kind {
+            OptionKind::Call => s * norm_cdf(d1) - k * self.discount() * norm_cdf(d2),
+            OptionKind::Put => k * self.discount() * norm_cdf(-d2) - s * norm_cdf(-d1),
+        }
+    }
+
+    /// Greeks at `vol`; `None` at or past expiry, where they are undefined
+    pub fn greeks(&self, vol: f64) -> Option<Greeks> {
+        if self.years <= 0.0 || vol <= 0.0 {
+            return None;
+        }
+        let (s, k, t) = (self.underlying, self.strike, self.years);
+        let (d1, d2) = self.d1_d2(vol);
+        let carry = self.rate * k * self.discount();
+        let decay = -s * norm_pdf(d1) * vol / (2.0 * t.sqrt());
+        let (delta, theta) = match self.kind {
+            OptionKind::Call => (norm_cdf(d1), decay - carry * norm_cdf(d2)),
+            OptionKind::Put => (norm_cdf(d1) - 1.0, decay + carry * norm_cdf(-d2)),
+        };
+        Some(Greeks {
+            delta,
+            gamma: norm_pdf(d1) / (s * vol * t.sqrt()),
+            vega: s * norm_pdf(d1) * t.sqrt() / 100.0,
+            theta: theta / 365.0,
+        })
+    }
+
+    /// The volatility at which the option is worth `price`, or `None` when
+    /// no volatility in range prices it there, e.g. below intrinsic value
+    pub fn implied_vol(&self, price: f64) -> Option<f64> {
+        if self.years <= 0.0 || !(self.price(MIN_VOL)..=self.price(MAX_VOL)).contains(&price) {
+            return None;
+        }
+        // Price rises with volatility, so bisect
+        let (mut low, mut high) = (MIN_VOL, MAX_VOL);
+        while high - low > VOL_TOLERANCE {
+            let mid = 0.5 * (low + high);
+            if self.price(mid) < price {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Some(0.5 * (low + high))
+    }
+}
+
+/// Where the greeks feed gets underlying prices and how options are quoted
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GreeksConfig {
+    /// Symbol whose mid prices each underlying, e.g. `BTC` to
+    /// `BTC-PERPETUAL`; an underlying without one is priced from quotes of
+    /// its own name
+    pub underlyings: HashMap<String, String>,
+    pub rate: f64,
+    /// Option prices are quoted in units of the underlying, as on Deribit,
+    /// rather than in its quote currency
+    pub premium_in_underlying: bool,
+}
+
+impl GreeksConfig {
+    /// Comma separated `UNDERLYING=SYMBOL`, `rate:RATE` and
+    /// `premium:underlying|quote` entries, e.g.
+    /// `BTC=BTC-PERPETUAL,ETH=ETH-PERPETUAL,rate:0.03,premium:underlying`
+    fn parse(spec: &str) -> Self {
+        let mut config = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            if let Some((underlying, symbol)) = entry.split_once('=') {
+                let (underlying, symbol) = (underlying.trim(), symbol.trim());
+                if !underlying.is_empty() && !symbol.is_empty() {
+                    config.underlyings.insert(underlying.to_string(), symbol.to_string());
+                    continue;
+                }
+            }
+            match entry.split_once(':').map(|(key, value)| (key.trim(), value.trim())) {
+                Some(("rate", rate)) => match rate.parse::<f64>() {
+                    Ok(rate) if rate.is_finite() => config.rate = rate,
+                    _ => warn!(entry = entry, "Ignoring malformed greeks entry"),
+                },
+                Some(("premium", "underlying")) => config.premium_in_underlying = true,
+                Some(("premium", "quote")) => config.premium_in_underlying = false,
+                _ => warn!(entry = entry, "Ignoring malformed greeks entry"),
+            }
+        }
+        config
+    }
+
+    /// Read `HFT_GREEKS` (see [`parse`](Self::parse)); returns `None` when
+    /// unset
+    pub fn from_env() -> Option<Self> {
+        Some(Self::parse(&std::env::var("HFT_GREEKS").ok()?))
+    }
+
+    pub fn underlying_symbol<'a>(&'a self, underlying: &'a str) -> &'a str {
+        self.underlyings.get(underlying).map(String::as_str).unwrap_or(underlying)
+    }
+}
+
+/// An option's implied volatility and greeks as of its latest quote
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OptionGreeks {
+    pub symbol: String,
+    pub underlying_price: f64,
+    /// Mid price in the underlying's quote currency
+    pub price: f64,
+    /// Annualized implied volatility, as a fraction
+    pub iv: f64,
+    pub greeks: Greeks,
+    /// Of the option quote the greeks were taken from
+    pub timestamp: u64,
+}
+
+#[derive(Debug)]
+struct TrackedOption {
+    instrument: OptionInstrument,
+    mid: f64,
+    timestamp: u64,
+}
+
+#[derive(Debug, Default)]
+struct Feed {
+    config: GreeksConfig,
+    options: HashMap<Symbol, TrackedOption>,
+    /// Latest mid by symbol, for underlyings
+    prices: HashMap<Symbol, f64>,
+}
+
+/// Greeks of every option seen in the quote stream, on demand. Clones share
+/// the same state.
+#[derive(Debug, Clone, Default)]
+pub struct GreeksFeed {
+    inner: Arc<RwLock<Feed>>,
+}
+
+impl GreeksFeed {
+    pub fn new(config: GreeksConfig) -> Self {
+        let feed = Self::default();
+        feed.configure(config);
+        feed
+    }
+
+    /// Replace the configuration, keeping the prices seen so far
+    pub fn configure(&self, config: GreeksConfig) {
+        self.inner.write().unwrap().config = config;
+    }
+
+    pub fn on_quote(&self, quote: &Quote) {
+        if quote.bid <= 0.0 || quote.ask < quote.bid {
+            return;
+        }
+        let mid = (quote.bid + quote.ask) / 2.0;
+        let mut inner = self.inner.write().unwrap();
+        if let Some(option) = inner.options.get_mut(&quote.symbol) {
+            option.mid = mid;
+            option.timestamp = quote.timestamp;
+        } else if let Some(instrument) = OptionInstrument::parse(&quote.symbol) {
+            inner.options.insert(quote.symbol, TrackedOption { instrument, mid, timestamp: quote.timestamp });
+        } else {
+            inner.prices.insert(quote.symbol, mid);
+        }
+    }
+
+    /// Greeks of `symbol` from its latest quote and its underlying's, when
+    /// both have been seen and the option is priced within the volatility
+    /// range
+    pub fn greeks(&self, symbol: &str) -> Option<OptionGreeks> {
+        let (inputs, price, iv, timestamp) = self.solve(symbol)?;
+        Some(OptionGreeks {
+            symbol: symbol.to_string(),
+            underlying_price: inputs.underlying,
+            price,
+            iv,
+            greeks: inputs.greeks(iv)?,
+            timestamp,
+        })
+    }
+
+    /// Pricing inputs of `symbol` and its implied volatility, to reprice it
+    /// under other conditions
+    pub fn pricing(&self, symbol: &str) -> Option<(PricingInputs, f64)> {
+        self.solve(symbol).map(|(inputs, _, iv, _)| (inputs, iv))
+    }
+
+    /// Pricing inputs, mid in the quote currency, implied volatility and
+    /// quote time of `symbol`
+    fn solve(&self, symbol: &str) -> Option<(PricingInputs, f64, f64, u64)> {
+        let inner = self.inner.read().unwrap();
+        let option = inner.options.get(symbol)?;
+        let underlying = *inner.prices.get(inner.config.underlying_symbol(&option.instrument.underlying))?;
+        let price = if inner.config.premium_in_underlying { option.mid * underlying } else { option.mid };
+        let inputs = PricingInputs {
+            kind: option.instrument.kind,
+            underlying,
+            strike: option.instrument.strike,
+            years: option.instrument.years_to_expiry(option.timestamp),
+            rate: inner.config.rate,
+        };
+        let iv = inputs.implied_vol(price)?;
+        Some((inputs, price, iv, option.timestamp))
+    }
+
+    /// Options seen in the quote stream, sorted
+    pub fn options(&self) -> Vec<String> {
+        let mut options: Vec<String> = self.inner.read().unwrap().options.keys().map(|s| s.to_string()).collect();
+        options.sort();
+        options
+    }
+
+    /// Symbol whose quotes price the underlying of option `symbol`, when it
+    /// has been quoted
+    pub fn underlying_symbol(&self, symbol: &str) -> Option<String> {
+        let inner = self.inner.read().unwrap();
+        let option = inner.options.get(symbol)?;
+        Some(inner.config.underlying_symbol(&option.instrument.underlying).to_string())
+    }
+
+    /// The option `symbol` names, when it has been quoted
+    pub fn instrument(&self, symbol: &str) -> Option<OptionInstrument> {
+        self.inner.read().unwrap().options.get(symbol).map(|o| o.instrument.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(kind: OptionKind) -> PricingInputs {
+        PricingInputs { kind, underlying: 100.0, strike: 100.0, years: 1.0, rate: 0.05 }
+    }
+
+    #[test]
+    fn test_black_scholes_and_implied_vol() {
+        // Hull's textbook values for S = K = 100, r = 5%, sigma = 20%, T = 1
+        let (call, put) = (inputs(OptionKind::Call), inputs(OptionKind::Put));
+        assert!((call.price(0.2) - 10.4506).abs() < 1e-3);
+        assert!((put.price(0.2) - 5.5735).abs() < 1e-3);
+
+        let greeks = call.greeks(0.2).unwrap();
+        assert!((greeks.delta - 0.6368).abs() < 1e-3);
+        assert!((greeks.gamma - 0.018_76).abs() < 1e-4);
+        assert!((greeks.vega - 0.3752).abs() < 1e-3);
+        assert!((greeks.theta * 365.0 + 6.414).abs() < 1e-2);
+        assert!((put.greeks(0.2).unwrap().delta + 0.3632).abs() < 1e-3);
+
+        assert!((call.implied_vol(10.4506).unwrap() - 0.2).abs() < 1e-4);
+        // Below intrinsic value
+        assert!(PricingInputs { strike: 50.0, ..call }.implied_vol(40.0).is_none());
+    }
+
+    #[test]
+    fn test_feed_prices_options_off_underlying() {
+        let feed = GreeksFeed::new(GreeksConfig::parse("BTC=BTC-PERPETUAL, rate:0, premium:underlying, bogus"));
+        let quote = |symbol: &str, bid: f64, ask: f64, timestamp: u64| Quote {
+            symbol: symbol.into(), bid, ask, bid_size: 1.0, ask_size: 1.0, venue: "DERIBIT".into(), timestamp,
+        };
+        let option = OptionInstrument::parse("BTC-27MAR26-50000-C").unwrap();
+        let now = option.expiry - 30 * 86_400_000;
+
+        feed.on_quote(&quote("BTC-27MAR26-50000-C", 0.05, 0.06, now));
+        assert!(feed.greeks("BTC-27MAR26-50000-C").is_none());
+        feed.on_quote(&quote("BTC-PERPETUAL", 49_990.0, 50_010.0, now));
+
+        let greeks = feed.greeks("BTC-27MAR26-50000-C").unwrap();
+        assert_eq!(greeks.price, 0.055 * 50_000.0);
+        assert!(greeks.iv > 0.3 && greeks.iv < 0.7, "{}", greeks.iv);
+        assert!((greeks.greeks.delta - 0.5).abs() < 0.1);
+        assert_eq!(feed.options(), vec!["BTC-27MAR26-50000-C".to_string()]);
+    }
+}
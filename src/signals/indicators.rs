@@ -0,0 +1,390 @@
+//! Incremental indicators for strategies. Each keeps a fixed amount of
+//! state allocated at construction, so updates never allocate.
+
+use std::time::Duration;
+
+use crate::types::{Candle, Quote, Trade};
+
+/// Fixed-capacity ring buffer; pushing onto a full ring drops the oldest
+#[derive(Debug, Clone)]
+pub(super) struct Ring<T> {
+    items: Vec<T>,
+    capacity: usize,
+    /// Index of the oldest item
+    head: usize,
+    len: usize,
+}
+
+impl<T: Copy> Ring<T> {
+    pub(super) fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            items: Vec::with_capacity(capacity),
+            capacity,
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Push `item`, returning the item it displaced when full
+    pub(super) fn push(&mut self, item: T) -> Option<T> {
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+            self.len += 1;
+            return None;
+        }
+        if self.len < self.capacity {
+            self.items[(self.head + self.len) % self.capacity] = item;
+            self.len += 1;
+            return None;
+        }
+        let evicted = std::mem::replace(&mut self.items[self.head], item);
+        self.head = (self.head + 1) % self.capacity;
+        Some(evicted)
+    }
+
+    pub(super) fn front(&self) -> Option<T> {
+        (self.len > 0).then(|| self.items[self.head])
+    }
+
+    pub(super) fn pop_front(&mut self) -> Option<T> {
+        let item = self.front()?;
+        self.head = (self.head + 1) % self.capacity;
+        self.len -= 1;
+        Some(item)
+    }
+
+    pub(super) fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.len).map(move |i| self.items[(self.head + i) % self.capacity])
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Exponential moving average
+#[derive(Debug, Clone)]
+pub struct Ema {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    /// EMA with the conventional `2 / (period + 1)` smoothing
+    pub fn new(period: usize) -> Self {
+        Self::with_alpha(2.0 / (period.max(1) as f64 + 1.0))
+    }
+
+    pub fn with_alpha(alpha: f64) -> Self {
+        Self { alpha: alpha.clamp(0.0, 1.0), value: None }
+    }
+
+    /// Fold in a sample; the first sample seeds the average
+    pub fn update(&mut self, x: f64) -> f64 {
+        let value = match self.value {
+            Some(v) => v + self.alpha * (x - v),
+            None => x,
+        };
+        self.value = Some(value);
+        value
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+/// Volume-weighted average trade price over a trailing time window.
+///
+/// Holds at most `capacity` trades; in bursts beyond that the oldest trades
+/// leave the window early.
+#[derive(Debug, Clone)]
+pub struct RollingVwap {
+    window: u64,
+    /// (timestamp, price * quantity, quantity)
+    trades: Ring<(u64, f64, f64)>,
+    notional: f64,
+    volume: f64,
+}
+
+impl RollingVwap {
+    pub fn new(window: Duration, capacity: usize) -> Self {
+        Self {
+            window: window.as_millis() as u64,
+            trades: Ring::new(capacity),
+            notional: 0.0,
+            volume: 0.0,
+        }
+    }
+
+    fn remove(&mut self, (_, notional, volume): (u64, f64, f64)) {
+        self.notional -= notional;
+        self.volume -= volume;
+    }
+
+    /// Drop trades older than the window ending at `now` (milliseconds)
+    pub fn expire(&mut self, now: u64) {
+        while let Some(oldest) = self.trades.front() {
+            if oldest.0 + self.window > now {
+                break;
+            }
+            self.trades.pop_front();
+            self.remove(oldest);
+        }
+        if self.trades.len() == 0 {
+            // Reset accumulated rounding error
+            self.notional = 0.0;
+            self.volume = 0.0;
+        }
+    }
+
+    pub fn update(&mut self, price: f64, quantity: f64, timestamp: u64) {
+        self.expire(timestamp);
+        if let Some(evicted) = self.trades.push((timestamp, price * quantity, quantity)) {
+            self.remove(evicted);
+        }
+        self.notional += price * quantity;
+        self.volume += quantity;
+    }
+
+    pub fn on_trade(&mut self, trade: &Trade) {
+        self.update(trade.price, trade.quantity, trade.timestamp);
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        (self.volume > 0.0).then(|| self.notional / self.volume)
+    }
+}
+
+/// Time-weighted average price over a trailing time window, each sample
+/// weighted by how long it stood.
+///
+/// Holds at most `capacity` samples; beyond that the oldest leave the
+/// window early.
+#[derive(Debug, Clone)]
+pub struct RollingTwap {
+    window: u64,
+    /// (timestamp, price)
+    samples: Ring<(u64, f64)>,
+}
+
+impl RollingTwap {
+    pub fn new(window: Duration, capacity: usize) -> Self {
+        Self {
+            window: window.as_millis() as u64,
+            samples: Ring::new(capacity),
+        }
+    }
+
+    pub fn update(&mut self, price: f64, timestamp: u64) {
+        // Keep the last sample at or before the window start, it still
+        // stands at the start of the window
+        let start = timestamp.saturating_sub(self.window);
+        while self.samples.len() > 1 && self.samples.iter().nth(1).is_some_and(|(t, _)| t <= start) {
+            self.samples.pop_front();
+        }
+        self.samples.push((timestamp, price));
+    }
+
+    /// Track the quote mid
+    pub fn on_quote(&mut self, quote: &Quote) {
+        if quote.bid > 0.0 && quote.ask > 0.0 {
+            self.update((quote.bid + quote.ask) / 2.0, quote.timestamp);
+        }
+    }
+
+    /// TWAP over the window ending at `now` (milliseconds)
+    pub fn value(&self, now: u64) -> Option<f64> {
+        let start = now.saturating_sub(self.window);
+        let mut weighted = 0.0;
+        let mut elapsed = 0u64;
+        let mut samples = self.samples.iter().peekable();
+        while let Some((t, price)) = samples.next() {
+            let from = t.max(start);
+            let until = samples.peek().map_or(now, |(next, _)| *next).min(now);
+            if until > from {
+                weighted += price * (until - from) as f64;
+                elapsed += until - from;
+            }
+        }
+
+        match elapsed {
+            0 => self.samples.iter().last().map(|(_, price)| price),
+            elapsed => Some(weighted / elapsed as f64),
+        }
+    }
+}
+
+/// Standard deviation of the last `period` samples
+#[derive(Debug, Clone)]
+pub struct RollingStdDev {
+    samples: Ring<f64>,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl RollingStdDev {
+    pub fn new(period: usize) -> Self {
+        Self {
+            samples: Ring::new(period.max(2)),
+            sum: 0.0,
+            sum_sq: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, x: f64) {
+        if let Some(evicted) = self.samples.push(x) {
+            self.sum -= evicted;
+            self.sum_sq -= evicted * evicted;
+        }
+        self.sum += x;
+        self.sum_sq += x * x;
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        let n = self.samples.len();
+        (n > 0).then(|| self.sum / n as f64)
+    }
+
+    /// Sample standard deviation, once there are two samples
+    pub fn value(&self) -> Option<f64> {
+        let n = self.samples.len() as f64;
+        if n < 2.0 {
+            return None;
+        }
+        let variance = (self.sum_sq - self.sum * self.sum / n) / (n - 1.0);
+        Some(variance.max(0.0).sqrt())
+    }
+}
+
+/// Average true range with Wilder smoothing, fed with closed candles
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+    prev_close: Option<f64>,
+    /// True ranges summed until `period` have been seen
+    seed_sum: f64,
+    seen: usize,
+    value: Option<f64>,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            prev_close: None,
+            seed_sum: 0.0,
+            seen: 0,
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, high: f64, low: f64, close: f64) -> Option<f64> {
+        let true_range = match self.prev_close {
+            Some(prev) => (high - low).max((high - prev).abs()).max((low - prev).abs()),
+            None => high - low,
+        };
+        self.prev_close = Some(close);
+
+        let n = self.period as f64;
+        self.value = match self.value {
+            Some(atr) => Some((atr * (n - 1.0) + true_range) / n),
+            None => {
+                self.seed_sum += true_range;
+                self.seen += 1;
+                (self.seen == self.period).then(|| self.seed_sum / n)
+            }
+        };
+        self.value
+    }
+
+    pub fn on_candle(&mut self, candle: &Candle) -> Option<f64> {
+        self.update(candle.high, candle.low, candle.close)
+    }
+
+    /// `None` until `period` candles have been seen
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_ema() {
+        let mut ema = Ema::new(3);
+        assert_eq!(ema.value(), None);
+        assert_eq!(ema.update(10.0), 10.0);
+        assert_eq!(ema.update(20.0), 15.0);
+        assert_eq!(ema.update(15.0), 15.0);
+    }
+
+    #[test]
+    fn test_rolling_vwap_expires_old_trades() {
+        let mut vwap = RollingVwap::new(Duration::from_millis(1000), 16);
+        vwap.update(100.0, 1.0, 0);
+        vwap.update(110.0, 3.0, 500);
+        assert!(close(vwap.value().unwrap(), 107.5));
+
+        // The first trade leaves the window
+        vwap.update(120.0, 1.0, 1000);
+        assert!(close(vwap.value().unwrap(), 112.5));
+
+        vwap.expire(5000);
+        assert_eq!(vwap.value(), None);
+
+        // A full ring evicts the oldest trade early
+        let mut vwap = RollingVwap::new(Duration::from_secs(60), 2);
+        vwap.update(100.0, 1.0, 0);
+        vwap.update(200.0, 1.0, 1);
+        vwap.update(300.0, 1.0, 2);
+        assert!(close(vwap.value().unwrap(), 250.0));
+    }
+
+    #[test]
+    fn test_rolling_twap_weights_by_time() {
+        let mut twap = RollingTwap::new(Duration::from_millis(1000), 16);
+        assert_eq!(twap.value(0), None);
+        twap.update(100.0, 0);
+        assert_eq!(twap.value(0), Some(100.0));
+
+        twap.update(200.0, 750);
+        // 100 for 750ms, 200 for 250ms
+        assert!(close(twap.value(1000).unwrap(), 125.0));
+
+        // 100 still stands at the start of the window
+        twap.update(200.0, 1250);
+        assert!(close(twap.value(1500).unwrap(), 175.0));
+    }
+
+    #[test]
+    fn test_rolling_std_dev() {
+        let mut std_dev = RollingStdDev::new(3);
+        std_dev.update(1.0);
+        assert_eq!(std_dev.value(), None);
+        std_dev.update(2.0);
+        std_dev.update(3.0);
+        assert!(close(std_dev.value().unwrap(), 1.0));
+
+        // 1.0 drops out, window is 2, 3, 7
+        std_dev.update(7.0);
+        assert!(close(std_dev.mean().unwrap(), 4.0));
+        assert!(close(std_dev.value().unwrap(), 7.0_f64.sqrt()));
+    }
+
+    #[test]
+    fn test_atr_wilder_smoothing() {
+        let mut atr = Atr::new(2);
+        assert_eq!(atr.update(11.0, 9.0, 10.0), None);
+        // Gap up: true range runs from the previous close
+        assert_eq!(atr.update(14.0, 12.0, 13.0), Some(3.0));
+        assert_eq!(atr.update(14.0, 13.0, 13.5), Some(2.0));
+    }
+}
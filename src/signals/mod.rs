@@ -0,0 +1,29 @@
+pub mod candles;
+pub mod greeks;
+pub mod indicators;
+pub mod order_flow;
+pub mod queue;
+pub mod toxicity;
+
+use crate::book::BookSnapshots;
+use crate::fees::FeeModel;
+
+pub use candles::{CandleCache, CandleConfig};
+pub use greeks::{Greeks, GreeksConfig, GreeksFeed, OptionGreeks, PricingInputs};
+pub use indicators::{Atr, Ema, RollingStdDev, RollingTwap, RollingVwap};
+pub use order_flow::{depth_imbalance, OrderFlow, OrderFlowImbalance};
+pub use queue::{QueueEstimator, QueuePosition};
+pub use toxicity::{ToxicityConfig, ToxicityLevel, ToxicityMonitor};
+
+/// Market data derived by the engine and shared with strategies, with
+/// the fees venues charge and lock-free snapshots of the books
+#[derive(Debug, Clone, Default)]
+pub struct Signals {
+    pub candles: CandleCache,
+    pub order_flow: OrderFlow,
+    pub queue: QueueEstimator,
+    pub toxicity: ToxicityMonitor,
+    pub greeks: GreeksFeed,
+    pub fees: FeeModel,
+    pub books: BookSnapshots,
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::book::OrderBook;
+use crate::types::{Quote, Symbol, VenueId};
+use super::indicators::Ring;
+
+/// Trailing window order-flow imbalance is summed over by default
+pub const DEFAULT_OFI_WINDOW: Duration = Duration::from_secs(1);
+
+/// Top-of-book events kept per window; older events leave early in bursts
+const OFI_CAPACITY: usize = 4096;
+
+/// Order-flow imbalance (Cont, Kukanov and Stoikov) from top-of-book
+/// changes, summed over a trailing time window.
+///
+/// Each quote contributes the size added to the bid minus the size added to
+/// the ask, counting a level that moved away as fully removed. Positive
+/// values mean net buying pressure.
+#[derive(Debug, Clone)]
+pub struct OrderFlowImbalance {
+    window: u64,
+    /// (timestamp, contribution)
+    events: Ring<(u64, f64)>,
+    /// Previous (bid, bid size, ask, ask size)
+    last: Option<(f64, f64, f64, f64)>,
+    sum: f64,
+    abs_sum: f64,
+}
+
+impl OrderFlowImbalance {
+    pub fn new(window: Duration, capacity: usize) -> Self {
+        Self {
+            window: window.as_millis() as u64,
+            events: Ring::new(capacity),
+            last: None,
+            sum: 0.0,
+            abs_sum: 0.0,
+        }
+    }
+
+    fn remove(&mut self, (_, e): (u64, f64)) {
+        self.sum -= e;
+        self.abs_sum -= e.abs();
+    }
+
+    pub fn update(&mut self, bid: f64, bid_size: f64, ask: f64, ask_size: f64, timestamp: u64) {
+        let start = timestamp.saturating_sub(self.window);
+        while let Some(oldest) = self.events.front() {
+            if oldest.0 > start {
+                break;
+            }
+            self.events.pop_front();
+            self.remove(oldest);
+        }
+
+        let Some((prev_bid, prev_bid_size, prev_ask, prev_ask_size)) = self.last.replace((bid, bid_size, ask, ask_size)) else {
+            return;
+        };
+
+        let mut e = 0.0;
+        if bid >= prev_bid {
+            e += bid_size;
+        }
+        if bid <= prev_bid {
+            e -= prev_bid_size;
+        }
+        if ask <= prev_ask {
+            e -= ask_size;
+        }
+        if ask >= prev_ask {
+            e += prev_ask_size;
+        }
+
+        if let Some(evicted) = self.events.push((timestamp, e)) {
+            self.remove(evicted);
+        }
+        self.sum += e;
+        self.abs_sum += e.abs();
+    }
+
+    pub fn on_quote(&mut self, quote: &Quote) {
+        if quote.bid > 0.0 && quote.ask > 0.0 {
+            self.update(quote.bid, quote.bid_size, quote.ask, quote.ask_size, quote.timestamp);
+        }
+    }
+
+    /// Net size added to the bid over the window, in base units
+    pub fn value(&self) -> f64 {
+        self.sum
+    }
+
+    /// Imbalance scaled to [-1, 1] by the total size that changed
+    pub fn normalized(&self) -> Option<f64> {
+        (self.abs_sum > f64::EPSILON).then(|| (self.sum / self.abs_sum).clamp(-1.0, 1.0))
+    }
+}
+
+/// Resting size imbalance over the best `levels` of `book`, in [-1, 1].
+/// Positive when the bid side is deeper.
+pub fn depth_imbalance(book: &OrderBook, levels: usize) -> Option<f64> {
+    let (bids, asks) = book.depth(levels);
+    let total = bids + asks;
+    (total > 0.0).then(|| (bids - asks) / total)
+}
+
+/// Order-flow imbalance per venue and symbol, kept current from quotes.
+/// Clones share the same state.
+#[derive(Debug, Clone)]
+pub struct OrderFlow {
+    window: Duration,
+    series: Arc<RwLock<HashMap<(VenueId, Symbol), OrderFlowImbalance>>>,
+}
+
+impl Default for OrderFlow {
+    fn default() -> Self {
+        Self::new(DEFAULT_OFI_WINDOW)
+    }
+}
+
+impl OrderFlow {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            series: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn on_quote(&self, quote: &Quote) {
+        let mut series = self.series.write().unwrap();
+        let key = (quote.venue, quote.symbol);
+        series
+            .entry(key)
+            .or_insert_with(|| OrderFlowImbalance::new(self.window, OFI_CAPACITY))
+            .on_quote(quote);
+    }
+
+    /// Raw order-flow imbalance over the window
+    pub fn ofi(&self, venue: &str, symbol: &str) -> Option<f64> {
+        self.series.read().unwrap()
+            .get(&(venue.into(), symbol.into()))
+            .map(OrderFlowImbalance::value)
+    }
+
+    /// Order-flow imbalance scaled to [-1, 1]
+    pub fn normalized_ofi(&self, venue: &str, symbol: &str) -> Option<f64> {
+        self.series.read().unwrap()
+            .get(&(venue.into(), symbol.into()))
+            .and_then(OrderFlowImbalance::normalized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_flow_imbalance() {
+        let mut ofi = OrderFlowImbalance::new(Duration::from_millis(1000), 16);
+        ofi.update(100.0, 5.0, 101.0, 5.0, 0);
+        assert_eq!(ofi.value(), 0.0);
+        assert_eq!(ofi.normalized(), None);
+
+        // Bid size grows at the same price
+        ofi.update(100.0, 8.0, 101.0, 5.0, 100);
+        assert_eq!(ofi.value(), 3.0);
+
+        // Ask lifted: the level moves up, so its size counts as removed
+        ofi.update(100.0, 8.0, 101.5, 2.0, 200);
+        assert_eq!(ofi.value(), 8.0);
+        assert_eq!(ofi.normalized(), Some(1.0));
+
+        // Bid drops a level
+        ofi.update(99.5, 4.0, 101.5, 2.0, 300);
+        assert_eq!(ofi.value(), 0.0);
+
+        // The first two events leave the window
+        ofi.update(99.5, 4.0, 101.5, 2.0, 1200);
+        assert_eq!(ofi.value(), -8.0);
+    }
+
+    #[test]
+    fn test_depth_imbalance() {
+        let mut book = OrderBook::new("BTCUSDT".into());
+        assert_eq!(depth_imbalance(&book, 5), None);
+
+        for (bid, ask, size) in [(100.0, 101.0, 3.0), (99.0, 102.0, 1.0)] {
+            book.update(&Quote {
+                symbol: "BTCUSDT".into(),
+                bid,
+                ask,
+                bid_size: size,
+                ask_size: 1.0,
+                venue: "MOCK".into(),
+                timestamp: 0,
+            });
+        }
+        assert_eq!(depth_imbalance(&book, 1), Some(0.5));
+        assert_eq!(depth_imbalance(&book, 2), Some(2.0 / 6.0));
+    }
+}
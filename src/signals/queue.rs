@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::feed::BookUpdate;
+use crate::types::{Order, OrderSide, Trade};
+
+/// Estimated place of a resting order in its price level's queue
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueuePosition {
+    /// Size resting ahead of the order
+    pub ahead: f64,
+    /// Total displayed size at the level, including the order
+    pub level_size: f64,
+}
+
+impl QueuePosition {
+    /// Share of the level ahead of the order; 0 at the front
+    pub fn fraction_ahead(&self) -> f64 {
+        if self.level_size > 0.0 {
+            (self.ahead / self.level_size).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    venue: String,
+    symbol: String,
+    side: OrderSide,
+    price: f64,
+    quantity: f64,
+    ahead: f64,
+    level_size: f64,
+}
+
+impl RestingOrder {
+    fn at_level(&self, venue: &str, symbol: &str, side: OrderSide, price: f64) -> bool {
+        self.side == side && self.price == price && self.venue == venue && self.symbol == symbol
+    }
+}
+
+/// Estimates where our resting orders sit in their level's queue from L2
+/// diffs and trades.
+///
+/// An order joins behind everything displayed at its price. Trades at the
+/// price consume the queue from the front. Other size decreases are treated
+/// as cancellations spread evenly over the rest of the level, so only the
+/// share ahead of us moves us forward; increases join behind us. Clones
+/// share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct QueueEstimator {
+    orders: Arc<RwLock<HashMap<String, RestingOrder>>>,
+}
+
+impl QueueEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking `order` resting on its venue, with `displayed` size
+    /// already at its price before it joined
+    pub fn track(&self, order_id: &str, order: &Order, displayed: f64) {
+        self.orders.write().unwrap().insert(order_id.to_string(), RestingOrder {
+            venue: order.venue.to_string(),
+            symbol: order.symbol.to_string(),
+            side: order.side,
+            price: order.price,
+            quantity: order.quantity,
+            ahead: displayed.max(0.0),
+            level_size: displayed.max(0.0) + order.quantity,
+        });
+    }
+
+    /// Stop tracking an order once it is filled, cancelled or repriced
+    pub fn remove(&self, order_id: &str) {
+        self.orders.write().unwrap().remove(order_id);
+    }
+
+    /// Reduce the remaining quantity after a partial fill
+    pub fn on_fill(&self, order_id: &str, quantity: f64) {
+        if let Some(order) = self.orders.write().unwrap().get_mut(order_id) {
+            order.quantity = (order.quantity - quantity).max(0.0);
+            order.level_size = (order.level_size - quantity).max(order.ahead);
+        }
+    }
+
+    /// Trades at our price eat into the queue ahead; a trade through our
+    /// price means everything ahead has gone
+    pub fn on_trade(&self, trade: &Trade) {
+        // An aggressive sell takes liquidity from resting buys
+        let resting_side = trade.side.opposite();
+        for order in self.orders.write().unwrap().values_mut() {
+            if order.side != resting_side || order.venue != trade.venue || order.symbol != trade.symbol {
+                continue;
+            }
+            let through = match resting_side {
+                OrderSide::Buy => trade.price < order.price,
+                OrderSide::Sell => trade.price > order.price,
+            };
+            if through {
+                order.level_size -= order.ahead;
+                order.ahead = 0.0;
+            } else if trade.price == order.price {
+                let consumed = trade.quantity.min(order.ahead);
+                order.ahead -= consumed;
+                order.level_size -= consumed;
+            }
+        }
+    }
+
+    /// Apply a change to a price level's displayed size. Call after
+    /// `on_trade` for the trades that caused it, so they are not counted as
+    /// cancellations too.
+    pub fn on_book_update(&self, update: &BookUpdate) {
+        for order in self.orders.write().unwrap().values_mut() {
+            if !order.at_level(&update.venue, &update.symbol, update.side, update.price) {
+                continue;
+            }
+
+            let size = update.size.max(0.0);
+            if size >= order.level_size {
+                // New size joins the back of the queue
+                order.level_size = size;
+                continue;
+            }
+
+            let others = order.level_size - order.quantity;
+            let cancelled = order.level_size - size;
+            if others > 0.0 {
+                order.ahead -= cancelled * order.ahead / others;
+            }
+            order.ahead = order.ahead.clamp(0.0, size);
+            order.level_size = size.max(order.ahead + order.quantity.min(size));
+        }
+    }
+
+    pub fn position(&self, order_id: &str) -> Option<QueuePosition> {
+        self.orders.read().unwrap().get(order_id).map(|o| QueuePosition {
+            ahead: o.ahead,
+            level_size: o.level_size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderType;
+
+    fn trade(side: OrderSide, price: f64, quantity: f64) -> Trade {
+        Trade {
+            symbol: "BTCUSDT".into(),
+            venue: "MOCK".into(),
+            price,
+            quantity,
+            side,
+            timestamp: 0,
+        }
+    }
+
+    fn level(price: f64, size: f64) -> BookUpdate {
+        BookUpdate {
+            symbol: "BTCUSDT".to_string(),
+            venue: "MOCK".to_string(),
+            side: OrderSide::Buy,
+            price,
+            size,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_queue_advances_on_trades_and_cancels() {
+        let queue = QueueEstimator::new();
+        let order = Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity: 2.0,
+            price: 100.0,
+            venue: "MOCK".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        };
+        queue.track("1", &order, 10.0);
+        assert_eq!(queue.position("1"), Some(QueuePosition { ahead: 10.0, level_size: 12.0 }));
+
+        // Others join behind us
+        queue.on_book_update(&level(100.0, 16.0));
+        assert_eq!(queue.position("1").unwrap().ahead, 10.0);
+
+        // A sell of 4 at our price takes from the front
+        queue.on_trade(&trade(OrderSide::Sell, 100.0, 4.0));
+        queue.on_book_update(&level(100.0, 12.0));
+        assert_eq!(queue.position("1"), Some(QueuePosition { ahead: 6.0, level_size: 12.0 }));
+
+        // 5 cancelled out of the 10 others: 6 of them are ahead of us
+        queue.on_book_update(&level(100.0, 7.0));
+        let position = queue.position("1").unwrap();
+        assert_eq!(position.ahead, 3.0);
+        assert_eq!(position.level_size, 7.0);
+
+        // Buys and other levels do not move us
+        queue.on_trade(&trade(OrderSide::Buy, 100.0, 1.0));
+        queue.on_book_update(&level(99.0, 0.0));
+        assert_eq!(queue.position("1").unwrap().ahead, 3.0);
+
+        // A sell through our price clears the queue ahead
+        queue.on_trade(&trade(OrderSide::Sell, 99.0, 5.0));
+        assert_eq!(queue.position("1").unwrap().fraction_ahead(), 0.0);
+
+        queue.remove("1");
+        assert_eq!(queue.position("1"), None);
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::metrics::{labels, TOXICITY_VPIN};
+use crate::types::{OrderSide, Quote, Symbol, Trade, VenueId};
+use super::indicators::Ring;
+
+const DEFAULT_VPIN_BUCKETS: usize = 50;
+const DEFAULT_WIDEN_VPIN: f64 = 0.5;
+const DEFAULT_PULL_VPIN: f64 = 0.8;
+const DEFAULT_WIDEN_FACTOR: f64 = 2.0;
+const DEFAULT_BURST_TRADES: usize = 20;
+const DEFAULT_BURST_WINDOW: Duration = Duration::from_millis(100);
+
+/// Volume-synchronized probability of informed trading.
+///
+/// Aggressor volume is split into buckets of equal volume; VPIN is the mean
+/// absolute buy/sell imbalance over the last `buckets` buckets, as a share
+/// of bucket volume. Trades spanning a bucket boundary are split across it.
+#[derive(Debug, Clone)]
+pub struct Vpin {
+    bucket_volume: f64,
+    buckets: usize,
+    /// Absolute imbalance of each completed bucket
+    imbalances: Ring<f64>,
+    imbalance_sum: f64,
+    buy: f64,
+    sell: f64,
+}
+
+impl Vpin {
+    pub fn new(bucket_volume: f64, buckets: usize) -> Self {
+        let buckets = buckets.max(1);
+        Self {
+            bucket_volume,
+            buckets,
+            imbalances: Ring::new(buckets),
+            imbalance_sum: 0.0,
+            buy: 0.0,
+            sell: 0.0,
+        }
+    }
+
+    /// Add aggressor volume; returns true when at least one bucket closed
+    pub fn update(&mut self, side: OrderSide, quantity: f64) -> bool {
+        if self.bucket_volume <= 0.0 {
+            return false;
+        }
+
+        let mut remaining = quantity.max(0.0);
+        let mut closed = false;
+        while remaining > 0.0 {
+            let take = remaining.min(self.bucket_volume - self.buy - self.sell);
+            match side {
+                OrderSide::Buy => self.buy += take,
+                OrderSide::Sell => self.sell += take,
+            }
+            remaining -= take;
+
+            if self.buy + self.sell >= self.bucket_volume * (1.0 - 1e-9) {
+                let imbalance = (self.buy - self.sell).abs();
+                if let Some(evicted) = self.imbalances.push(imbalance) {
+                    self.imbalance_sum -= evicted;
+                }
+                self.imbalance_sum += imbalance;
+                self.buy = 0.0;
+                self.sell = 0.0;
+                closed = true;
+            }
+        }
+        closed
+    }
+
+    pub fn on_trade(&mut self, trade: &Trade) -> bool {
+        self.update(trade.side, trade.quantity)
+    }
+
+    /// VPIN in [0, 1], once `buckets` buckets have closed
+    pub fn value(&self) -> Option<f64> {
+        (self.imbalances.len() == self.buckets)
+            .then(|| (self.imbalance_sum / (self.buckets as f64 * self.bucket_volume)).clamp(0.0, 1.0))
+    }
+}
+
+/// Flags bursts of at least `trades` trades within `window`. A burst is
+/// held for one further window after the last trade that triggered it.
+#[derive(Debug, Clone)]
+pub struct TradeBurst {
+    window: u64,
+    /// Timestamps of the last `trades` trades
+    times: Ring<u64>,
+    trades: usize,
+    until: u64,
+}
+
+impl TradeBurst {
+    pub fn new(trades: usize, window: Duration) -> Self {
+        let trades = trades.max(2);
+        Self {
+            window: window.as_millis() as u64,
+            times: Ring::new(trades),
+            trades,
+            until: 0,
+        }
+    }
+
+    pub fn update(&mut self, timestamp: u64) {
+        self.times.push(timestamp);
+        if self.times.len() == self.trades
+            && self.times.front().is_some_and(|first| timestamp.saturating_sub(first) <= self.window)
+        {
+            self.until = timestamp + self.window;
+        }
+    }
+
+    /// Whether a burst is in progress at `now` (milliseconds)
+    pub fn is_bursting(&self, now: u64) -> bool {
+        now < self.until
+    }
+}
+
+/// How strategies should quote a symbol given its current toxicity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToxicityLevel {
+    Normal,
+    /// Quote wider by the configured factor
+    Elevated,
+    /// Pull quotes until flow calms down
+    Toxic,
+}
+
+#[derive(Debug, Clone)]
+pub struct ToxicityConfig {
+    /// VPIN bucket volume per symbol, in base units; other symbols are not
+    /// monitored
+    pub bucket_volume: HashMap<String, f64>,
+    /// Buckets VPIN is averaged over
+    pub buckets: usize,
+    /// VPIN from which quotes are widened
+    pub widen_vpin: f64,
+    /// VPIN from which quotes are pulled
+    pub pull_vpin: f64,
+    /// Spread multiplier while toxicity is elevated
+    pub widen_factor: f64,
+    /// Trades within `burst_window` that count as a burst; quotes are
+    /// pulled during a burst
+    pub burst_trades: usize,
+    pub burst_window: Duration,
+}
+
+impl Default for ToxicityConfig {
+    fn default() -> Self {
+        Self {
+            bucket_volume: HashMap::new(),
+            buckets: DEFAULT_VPIN_BUCKETS,
+            widen_vpin: DEFAULT_WIDEN_VPIN,
+            pull_vpin: DEFAULT_PULL_VPIN,
+            widen_factor: DEFAULT_WIDEN_FACTOR,
+            burst_trades: DEFAULT_BURST_TRADES,
+            burst_window: DEFAULT_BURST_WINDOW,
+        }
+    }
+}
+
+impl ToxicityConfig {
+    /// Read `HFT_TOXICITY` as comma separated `SYMBOL:BUCKET_VOLUME` entries,
+    /// e.g. `BTCUSDT:5,ETHUSDT:80`, with `HFT_VPIN_BUCKETS`,
+    /// `HFT_VPIN_WIDEN`, `HFT_VPIN_PULL`, `HFT_TOXICITY_WIDEN_FACTOR`,
+    /// `HFT_TRADE_BURST_TRADES` and `HFT_TRADE_BURST_WINDOW_MS` overriding
+    /// the defaults
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var("HFT_TOXICITY").ok()?;
+
+        let mut config = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry
+                .split_once(':')
+                .and_then(|(symbol, volume)| Some((symbol.trim(), volume.trim().parse::<f64>().ok()?)))
+                .filter(|(symbol, volume)| !symbol.is_empty() && *volume > 0.0);
+            match parsed {
+                Some((symbol, volume)) => {
+                    config.bucket_volume.insert(symbol.to_string(), volume);
+                }
+                None => warn!(entry = entry, "Ignoring malformed toxicity entry"),
+            }
+        }
+
+        fn var<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|s| s.parse().ok())
+        }
+        config.buckets = var("HFT_VPIN_BUCKETS").unwrap_or(config.buckets);
+        config.widen_vpin = var("HFT_VPIN_WIDEN").unwrap_or(config.widen_vpin);
+        config.pull_vpin = var("HFT_VPIN_PULL").unwrap_or(config.pull_vpin);
+        config.widen_factor = var("HFT_TOXICITY_WIDEN_FACTOR").unwrap_or(config.widen_factor);
+        config.burst_trades = var("HFT_TRADE_BURST_TRADES").unwrap_or(config.burst_trades);
+        if let Some(ms) = var("HFT_TRADE_BURST_WINDOW_MS") {
+            config.burst_window = Duration::from_millis(ms);
+        }
+        Some(config)
+    }
+}
+
+#[derive(Debug)]
+struct Toxicity {
+    vpin: Vpin,
+    burst: TradeBurst,
+    /// Latest market data timestamp seen for the symbol
+    now: u64,
+}
+
+#[derive(Debug, Default)]
+struct Monitor {
+    config: ToxicityConfig,
+    series: HashMap<(VenueId, Symbol), Toxicity>,
+}
+
+/// VPIN and trade bursts per venue and symbol, built from the trade
+/// stream. Clones share the same state, so a configuration applied after
+/// strategies have attached still reaches them.
+#[derive(Debug, Clone, Default)]
+pub struct ToxicityMonitor {
+    inner: Arc<RwLock<Monitor>>,
+}
+
+impl ToxicityMonitor {
+    pub fn new(config: ToxicityConfig) -> Self {
+        let monitor = Self::default();
+        monitor.configure(config);
+        monitor
+    }
+
+    /// Replace the configuration, discarding accumulated state
+    pub fn configure(&self, config: ToxicityConfig) {
+        let mut inner = self.inner.write().unwrap();
+        inner.config = config;
+        inner.series.clear();
+    }
+
+    pub fn on_trade(&self, trade: &Trade) {
+        let mut inner = self.inner.write().unwrap();
+        let Monitor { config, series } = &mut *inner;
+        let Some(&bucket_volume) = config.bucket_volume.get(trade.symbol.as_str()) else {
+            return;
+        };
+
+        let toxicity = series
+            .entry((trade.venue, trade.symbol))
+            .or_insert_with(|| Toxicity {
+                vpin: Vpin::new(bucket_volume, config.buckets),
+                burst: TradeBurst::new(config.burst_trades, config.burst_window),
+                now: 0,
+            });
+        toxicity.now = toxicity.now.max(trade.timestamp);
+        toxicity.burst.update(trade.timestamp);
+        if toxicity.vpin.on_trade(trade) {
+            if let Some(vpin) = toxicity.vpin.value() {
+                TOXICITY_VPIN.with_label_values(&[&trade.venue, labels::symbol("toxicity_vpin", &trade.symbol)]).set(vpin);
+            }
+        }
+    }
+
+    /// Advance the symbol's clock so bursts end without further trades
+    pub fn on_quote(&self, quote: &Quote) {
+        let mut inner = self.inner.write().unwrap();
+        if let Some(toxicity) = inner.series.get_mut(&(quote.venue, quote.symbol)) {
+            toxicity.now = toxicity.now.max(quote.timestamp);
+        }
+    }
+
+    pub fn vpin(&self, venue: &str, symbol: &str) -> Option<f64> {
+        self.inner.read().unwrap()
+            .series
+            .get(&(venue.into(), symbol.into()))
+            .and_then(|t| t.vpin.value())
+    }
+
+    pub fn level(&self, venue: &str, symbol: &str) -> ToxicityLevel {
+        let inner = self.inner.read().unwrap();
+        let Some(toxicity) = inner.series.get(&(venue.into(), symbol.into())) else {
+            return ToxicityLevel::Normal;
+        };
+        if toxicity.burst.is_bursting(toxicity.now) {
+            return ToxicityLevel::Toxic;
+        }
+        match toxicity.vpin.value() {
+            Some(vpin) if vpin >= inner.config.pull_vpin => ToxicityLevel::Toxic,
+            Some(vpin) if vpin >= inner.config.widen_vpin => ToxicityLevel::Elevated,
+            _ => ToxicityLevel::Normal,
+        }
+    }
+
+    /// Factor strategies should scale their quoted spread by
+    pub fn spread_multiplier(&self, venue: &str, symbol: &str) -> f64 {
+        match self.level(venue, symbol) {
+            ToxicityLevel::Normal => 1.0,
+            _ => self.inner.read().unwrap().config.widen_factor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(side: OrderSide, quantity: f64, timestamp: u64) -> Trade {
+        Trade {
+            symbol: "BTCUSDT".into(),
+            venue: "MOCK".into(),
+            price: 100.0,
+            quantity,
+            side,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_vpin_buckets_split_trades() {
+        let mut vpin = Vpin::new(10.0, 2);
+        assert!(!vpin.update(OrderSide::Buy, 6.0));
+        // 4 closes the first bucket, 4 goes into the second
+        assert!(vpin.update(OrderSide::Sell, 8.0));
+        assert_eq!(vpin.value(), None);
+
+        assert!(vpin.update(OrderSide::Sell, 6.0));
+        // Buckets of 6/4 and 0/10
+        assert_eq!(vpin.value(), Some(12.0 / 20.0));
+
+        // One-sided flow
+        vpin.update(OrderSide::Buy, 20.0);
+        assert_eq!(vpin.value(), Some(1.0));
+    }
+
+    #[test]
+    fn test_trade_burst_expires() {
+        let mut burst = TradeBurst::new(3, Duration::from_millis(100));
+        burst.update(0);
+        burst.update(50);
+        burst.update(200);
+        assert!(!burst.is_bursting(200));
+
+        burst.update(220);
+        burst.update(250);
+        assert!(burst.is_bursting(250));
+        assert!(burst.is_bursting(349));
+        assert!(!burst.is_bursting(350));
+    }
+
+    #[test]
+    fn test_toxicity_levels() {
+        let monitor = ToxicityMonitor::new(ToxicityConfig {
+            bucket_volume: HashMap::from([("BTCUSDT".to_string(), 10.0)]),
+            buckets: 2,
+            burst_trades: 5,
+            ..Default::default()
+        });
+        assert_eq!(monitor.level("MOCK", "BTCUSDT"), ToxicityLevel::Normal);
+
+        // Balanced flow
+        for (i, side) in [OrderSide::Buy, OrderSide::Sell, OrderSide::Buy, OrderSide::Sell].into_iter().enumerate() {
+            monitor.on_trade(&trade(side, 5.0, i as u64 * 1000));
+        }
+        assert_eq!(monitor.vpin("MOCK", "BTCUSDT"), Some(0.0));
+        assert_eq!(monitor.spread_multiplier("MOCK", "BTCUSDT"), 1.0);
+
+        // Buckets of 8/2 and 8/2
+        for (i, side) in [OrderSide::Buy, OrderSide::Sell, OrderSide::Buy, OrderSide::Sell].into_iter().enumerate() {
+            monitor.on_trade(&trade(side, if side == OrderSide::Buy { 8.0 } else { 2.0 }, 10_000 + i as u64 * 1000));
+        }
+        assert_eq!(monitor.level("MOCK", "BTCUSDT"), ToxicityLevel::Elevated);
+        assert_eq!(monitor.spread_multiplier("MOCK", "BTCUSDT"), DEFAULT_WIDEN_FACTOR);
+
+        monitor.on_trade(&trade(OrderSide::Buy, 20.0, 20_000));
+        assert_eq!(monitor.level("MOCK", "BTCUSDT"), ToxicityLevel::Toxic);
+
+        // Unmonitored symbols stay normal
+        let mut other = trade(OrderSide::Buy, 100.0, 0);
+        other.symbol = "ETHUSDT".into();
+        monitor.on_trade(&other);
+        assert_eq!(monitor.level("MOCK", "ETHUSDT"), ToxicityLevel::Normal);
+    }
+
+    #[test]
+    fn test_trade_burst_pulls_quotes_until_quiet() {
+        let monitor = ToxicityMonitor::new(ToxicityConfig {
+            bucket_volume: HashMap::from([("BTCUSDT".to_string(), 1000.0)]),
+            burst_trades: 3,
+            ..Default::default()
+        });
+        for ts in [0, 10, 20] {
+            monitor.on_trade(&trade(OrderSide::Buy, 1.0, ts));
+        }
+        assert_eq!(monitor.level("MOCK", "BTCUSDT"), ToxicityLevel::Toxic);
+
+        monitor.on_quote(&Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 99.0,
+            ask: 101.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "MOCK".into(),
+            timestamp: 500,
+        });
+        assert_eq!(monitor.level("MOCK", "BTCUSDT"), ToxicityLevel::Normal);
+    }
+}
@@ -0,0 +1,479 @@
+//! Drop copy of executions for compliance.
+//!
+//! Every order event is rendered as a FIX 4.4 ExecutionReport (`35=8`) and
+//! mirrored to a FIX drop-copy session, to write-once files, or both. Each
+//! destination is a separate [`EventSink`](super::EventSink) with its own
+//! queue, independent of the audit log and any other sink.
+
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+use tracing::{info, warn};
+
+use crate::error::HftError;
+use crate::execution::OrderStatus;
+use crate::types::{OrderSide, OrderType};
+use super::{EventSink, OrderEvent};
+
+const SOH: char = '\x01';
+
+const DEFAULT_SENDER: &str = "HFT";
+const DEFAULT_TARGET: &str = "DROPCOPY";
+
+/// FIX session settings for a drop-copy acceptor
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixSessionConfig {
+    /// `host:port` of the acceptor
+    pub addr: String,
+    pub sender: String,
+    pub target: String,
+}
+
+/// Where executions are mirrored
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DropCopyConfig {
+    /// Directory of write-once drop-copy files
+    pub dir: Option<PathBuf>,
+    pub fix: Option<FixSessionConfig>,
+}
+
+impl DropCopyConfig {
+    /// Read `HFT_DROP_COPY_DIR` and `HFT_DROP_COPY_FIX` (`host:port`), with
+    /// `HFT_DROP_COPY_SENDER` and `HFT_DROP_COPY_TARGET` as the CompIDs
+    /// (default `HFT` and `DROPCOPY`); returns `None` when neither
+    /// destination is set
+    pub fn from_env() -> Option<Self> {
+        let comp_id = |name: &str, default: &str| std::env::var(name).unwrap_or_else(|_| default.to_string());
+        let config = Self {
+            dir: std::env::var("HFT_DROP_COPY_DIR").ok().map(PathBuf::from),
+            fix: std::env::var("HFT_DROP_COPY_FIX").ok().map(|addr| FixSessionConfig {
+                addr,
+                sender: comp_id("HFT_DROP_COPY_SENDER", DEFAULT_SENDER),
+                target: comp_id("HFT_DROP_COPY_TARGET", DEFAULT_TARGET),
+            }),
+        };
+        (config.dir.is_some() || config.fix.is_some()).then_some(config)
+    }
+
+    /// CompIDs stamped on drop-copy files, from the FIX session when there
+    /// is one
+    fn comp_ids(&self) -> (String, String) {
+        match &self.fix {
+            Some(fix) => (fix.sender.clone(), fix.target.clone()),
+            None => (DEFAULT_SENDER.to_string(), DEFAULT_TARGET.to_string()),
+        }
+    }
+
+    /// A sink for each configured destination
+    pub fn open(&self) -> Result<Vec<Arc<dyn EventSink>>, HftError> {
+        let mut sinks: Vec<Arc<dyn EventSink>> = Vec::new();
+        if let Some(dir) = &self.dir {
+            let (sender, target) = self.comp_ids();
+            sinks.push(Arc::new(FileDropCopy::open(dir, sender, target)?));
+        }
+        if let Some(fix) = &self.fix {
+            sinks.push(Arc::new(FixDropCopy::new(fix.clone())));
+        }
+        Ok(sinks)
+    }
+}
+
+fn fix_time(timestamp_ms: u64) -> String {
+    Utc.timestamp_millis_opt(timestamp_ms as i64)
+        .single()
+        .unwrap_or_default()
+        .format("%Y%m%d-%H:%M:%S%.3f")
+        .to_string()
+}
+
+fn side(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "1",
+        OrderSide::Sell => "2",
+    }
+}
+
+fn ord_type(order_type: OrderType) -> &'static str {
+    match order_type {
+        OrderType::Market => "1",
+        OrderType::Limit => "2",
+    }
+}
+
+/// ExecType (150) and OrdStatus (39) of a status change
+fn status_codes(status: OrderStatus) -> (&'static str, &'static str) {
+    match status {
+        OrderStatus::New => ("I", "0"),
+        OrderStatus::PartiallyFilled => ("I", "1"),
+        OrderStatus::Filled => ("I", "2"),
+        OrderStatus::Cancelled => ("4", "4"),
+        OrderStatus::Rejected => ("8", "8"),
+    }
+}
+
+/// Body fields of the ExecutionReport for `event`. CumQty and LeavesQty
+/// are only included where the event determines them.
+fn execution_report(event: &OrderEvent, exec_id: &str) -> Vec<(u32, String)> {
+    let mut fields = vec![(17, exec_id.to_string())];
+    match event {
+        OrderEvent::Submitted { order_id, order, timestamp } => fields.extend([
+            (37, order_id.clone()),
+            (11, order_id.clone()),
+            (150, "0".to_string()),
+            (39, "0".to_string()),
+            (55, order.symbol.to_string()),
+            (54, side(order.side).to_string()),
+            (40, ord_type(order.order_type).to_string()),
+            (38, order.quantity.to_string()),
+            (44, order.price.to_string()),
+            (14, "0".to_string()),
+            (151, order.quantity.to_string()),
+            (6, "0".to_string()),
+            (100, order.venue.to_string()),
+            (60, fix_time(*timestamp)),
+        ]),
+        OrderEvent::Rejected { order, reason, timestamp } => fields.extend([
+            (37, "NONE".to_string()),
+            (150, "8".to_string()),
+            (39, "8".to_string()),
+            (55, order.symbol.to_string()),
+            (54, side(order.side).to_string()),
+            (40, ord_type(order.order_type).to_string()),
+            (38, order.quantity.to_string()),
+            (44, order.price.to_string()),
+            (14, "0".to_string()),
+            (151, "0".to_string()),
+            (6, "0".to_string()),
+            (100, order.venue.to_string()),
+            (58, reason.clone()),
+            (60, fix_time(*timestamp)),
+        ]),
+        OrderEvent::StatusChanged { order_id, status, timestamp } => {
+            let (exec_type, ord_status) = status_codes(*status);
+            fields.extend([
+                (37, order_id.clone()),
+                (150, exec_type.to_string()),
+                (39, ord_status.to_string()),
+            ]);
+            if status.is_terminal() && *status != OrderStatus::Filled {
+                fields.push((151, "0".to_string()));
+            }
+            fields.push((60, fix_time(*timestamp)));
+        }
+        OrderEvent::Amended { order_id, price, quantity, timestamp } => fields.extend([
+            (37, order_id.clone()),
+            (150, "5".to_string()),
+            (39, "0".to_string()),
+            (38, quantity.to_string()),
+            (44, price.to_string()),
+            (60, fix_time(*timestamp)),
+        ]),
+        OrderEvent::Fill(fill) => {
+            fields.extend([
+                (37, fill.order_id.clone()),
+                (150, "F".to_string()),
+                (39, "1".to_string()),
+                (55, fill.symbol.to_string()),
+                (54, side(fill.side).to_string()),
+                (32, fill.quantity.to_string()),
+                (31, fill.price.to_string()),
+                (100, fill.venue.to_string()),
+            ]);
+            if let Some(commission) = &fill.commission {
+                // CommType 3 is an absolute amount, in CommCurrency
+                fields.extend([
+                    (12, commission.amount.to_string()),
+                    (13, "3".to_string()),
+                    (479, commission.asset.clone()),
+                ]);
+            }
+            fields.push((60, fix_time(fill.timestamp)));
+        }
+    }
+    fields
+}
+
+/// A complete FIX 4.4 message with BodyLength and CheckSum
+fn encode(msg_type: &str, seq: u64, sender: &str, target: &str, sent_at: DateTime<Utc>, fields: &[(u32, String)]) -> String {
+    let mut body = format!(
+        "35={msg_type}{SOH}49={sender}{SOH}56={target}{SOH}34={seq}{SOH}52={}{SOH}",
+        sent_at.format("%Y%m%d-%H:%M:%S%.3f"),
+    );
+    for (tag, value) in fields {
+        let _ = write!(body, "{tag}={value}{SOH}");
+    }
+    let mut message = format!("8=FIX.4.4{SOH}9={}{SOH}{body}", body.len());
+    let checksum = message.bytes().map(u32::from).sum::<u32>() % 256;
+    let _ = write!(message, "10={checksum:03}{SOH}");
+    message
+}
+
+struct DropFile {
+    file: File,
+    path: PathBuf,
+    date: NaiveDate,
+    seq: u64,
+}
+
+/// Appends ExecutionReports to one file per UTC day and engine run, one
+/// message per line. Files are created new, never reopened, and made
+/// read-only once the day rolls over.
+pub struct FileDropCopy {
+    dir: PathBuf,
+    sender: String,
+    target: String,
+    /// Distinguishes this run's files from earlier runs on the same day
+    started: u64,
+    current: Mutex<Option<DropFile>>,
+}
+
+impl FileDropCopy {
+    pub fn open(dir: &Path, sender: String, target: String) -> Result<Self, HftError> {
+        std::fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            sender,
+            target,
+            started: Utc::now().timestamp_millis() as u64,
+            current: Mutex::new(None),
+        })
+    }
+
+    fn roll(&self, date: NaiveDate) -> std::io::Result<DropFile> {
+        let path = self.dir.join(format!("dropcopy-{}-{}.fix", date.format("%Y%m%d"), self.started));
+        let file = OpenOptions::new().create_new(true).append(true).open(&path)?;
+        info!(path = %path.display(), "Opened drop-copy file");
+        Ok(DropFile { file, path, date, seq: 0 })
+    }
+
+    fn write(&self, events: &[OrderEvent], now: DateTime<Utc>) -> std::io::Result<()> {
+        let mut current = self.current.lock().unwrap();
+        if current.as_ref().is_some_and(|file| file.date != now.date_naive()) {
+            if let Some(done) = current.take() {
+                seal(&done.path);
+            }
+        }
+        let file = match current.as_mut() {
+            Some(file) => file,
+            None => current.insert(self.roll(now.date_naive())?),
+        };
+
+        let mut out = String::new();
+        for event in events {
+            file.seq += 1;
+            let exec_id = format!("{}-{}-{}", file.date.format("%Y%m%d"), self.started, file.seq);
+            let report = execution_report(event, &exec_id);
+            out.push_str(&encode("8", file.seq, &self.sender, &self.target, now, &report));
+            out.push('\n');
+        }
+        file.file.write_all(out.as_bytes())?;
+        file.file.sync_data()
+    }
+}
+
+/// Make a finished drop-copy file read-only
+fn seal(path: &Path) {
+    let result = std::fs::metadata(path).and_then(|metadata| {
+        let mut permissions = metadata.permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(path, permissions)
+    });
+    if let Err(e) = result {
+        warn!(path = %path.display(), error = %e, "Failed to make drop-copy file read-only");
+    }
+}
+
+#[async_trait]
+impl EventSink for FileDropCopy {
+    fn name(&self) -> &str {
+        "dropcopy_file"
+    }
+
+    async fn publish(&self, events: &[OrderEvent]) -> Result<(), HftError> {
+        self.write(events, Utc::now())
+            .map_err(|e| HftError::Sink(format!("Failed to write drop copy: {}", e)))
+    }
+}
+
+struct FixConnection {
+    writer: OwnedWriteHalf,
+    seq: u64,
+}
+
+/// Sends ExecutionReports over a FIX 4.4 initiator session. Each
+/// connection logs on with sequence numbers reset and no heartbeats; a
+/// failed write drops the connection, and the retried batch logs on again.
+pub struct FixDropCopy {
+    config: FixSessionConfig,
+    connection: tokio::sync::Mutex<Option<FixConnection>>,
+}
+
+impl FixDropCopy {
+    pub fn new(config: FixSessionConfig) -> Self {
+        Self { config, connection: tokio::sync::Mutex::new(None) }
+    }
+
+    async fn connect(&self) -> Result<FixConnection, HftError> {
+        let stream = TcpStream::connect(&self.config.addr).await
+            .map_err(|e| HftError::Sink(format!("Failed to connect to drop-copy acceptor {}: {}", self.config.addr, e)))?;
+        let (mut reader, writer) = stream.into_split();
+        // Nothing the acceptor sends needs an answer without heartbeats
+        tokio::spawn(async move {
+            let mut buf = [0u8; 4096];
+            while matches!(reader.read(&mut buf).await, Ok(n) if n > 0) {}
+        });
+
+        let mut connection = FixConnection { writer, seq: 0 };
+        let logon = [(98, "0".to_string()), (108, "0".to_string()), (141, "Y".to_string())];
+        self.send(&mut connection, "A", &logon).await?;
+        info!(addr = %self.config.addr, "Drop-copy session logged on");
+        Ok(connection)
+    }
+
+    async fn send(&self, connection: &mut FixConnection, msg_type: &str, fields: &[(u32, String)]) -> Result<(), HftError> {
+        connection.seq += 1;
+        let message = encode(msg_type, connection.seq, &self.config.sender, &self.config.target, Utc::now(), fields);
+        connection.writer.write_all(message.as_bytes()).await
+            .map_err(|e| HftError::Sink(format!("Failed to send to drop-copy acceptor {}: {}", self.config.addr, e)))
+    }
+}
+
+#[async_trait]
+impl EventSink for FixDropCopy {
+    fn name(&self) -> &str {
+        "dropcopy_fix"
+    }
+
+    async fn publish(&self, events: &[OrderEvent]) -> Result<(), HftError> {
+        let mut guard = self.connection.lock().await;
+        let connection = match guard.as_mut() {
+            Some(connection) => connection,
+            None => guard.insert(self.connect().await?),
+        };
+        for event in events {
+            let exec_id = format!("{}-{}", self.config.sender, Utc::now().timestamp_nanos_opt().unwrap_or_default());
+            if let Err(e) = self.send(connection, "8", &execution_report(event, &exec_id)).await {
+                *guard = None;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncBufReadExt;
+    use tokio::net::TcpListener;
+    use crate::types::{Commission, Fill, Order};
+
+    fn submitted() -> OrderEvent {
+        let order = Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity: 0.5,
+            price: 50000.0,
+            venue: "BINANCE".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        };
+        OrderEvent::Submitted { order_id: "42".to_string(), order, timestamp: 1_700_000_000_000 }
+    }
+
+    fn fill() -> OrderEvent {
+        OrderEvent::Fill(Fill {
+            order_id: "42".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "BINANCE".into(),
+            strategy: "mm".to_string(),
+            side: OrderSide::Buy,
+            quantity: 0.2,
+            price: 49999.5,
+            timestamp: 1_700_000_000_500,
+            commission: Some(Commission { amount: 3.5, asset: "USDT".to_string() }),
+        })
+    }
+
+    fn tag<'a>(message: &'a str, tag: &str) -> Option<&'a str> {
+        message.split(SOH).find_map(|field| field.strip_prefix(tag)?.strip_prefix('='))
+    }
+
+    #[test]
+    fn test_execution_report_encoding() {
+        let sent_at = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let message = encode("8", 7, "HFT", "DC", sent_at, &execution_report(&fill(), "X1"));
+        assert!(message.starts_with("8=FIX.4.4\x019="));
+        assert_eq!(tag(&message, "35"), Some("8"));
+        assert_eq!(tag(&message, "34"), Some("7"));
+        assert_eq!(tag(&message, "150"), Some("F"));
+        assert_eq!((tag(&message, "32"), tag(&message, "31")), (Some("0.2"), Some("49999.5")));
+        assert_eq!((tag(&message, "12"), tag(&message, "479")), (Some("3.5"), Some("USDT")));
+        assert_eq!(tag(&message, "60"), Some("20231114-22:13:20.500"));
+
+        let body_start = message.find("35=").unwrap();
+        let body_end = message.rfind("10=").unwrap();
+        assert_eq!(tag(&message, "9"), Some((body_end - body_start).to_string().as_str()));
+        let checksum = message[..body_end].bytes().map(u32::from).sum::<u32>() % 256;
+        assert_eq!(tag(&message, "10"), Some(format!("{:03}", checksum).as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_files_are_written_once_and_sealed_on_rollover() {
+        let dir = std::env::temp_dir().join(format!("hft-dropcopy-{}", std::process::id()));
+        let sink = FileDropCopy::open(&dir, "HFT".to_string(), "DC".to_string()).unwrap();
+        let day = Utc.with_ymd_and_hms(2026, 3, 2, 23, 59, 0).unwrap();
+        sink.write(&[submitted(), fill()], day).unwrap();
+        sink.write(&[fill()], day + chrono::Duration::minutes(2)).unwrap();
+
+        let first = dir.join(format!("dropcopy-20260302-{}.fix", sink.started));
+        let lines: Vec<String> = std::fs::read_to_string(&first).unwrap().lines().map(String::from).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!((tag(&lines[0], "150"), tag(&lines[1], "34")), (Some("0"), Some("2")));
+        assert!(std::fs::metadata(&first).unwrap().permissions().readonly());
+        let second = dir.join(format!("dropcopy-20260303-{}.fix", sink.started));
+        assert_eq!(tag(std::fs::read_to_string(&second).unwrap().trim_end(), "34"), Some("1"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fix_session_logs_on_then_sends_reports() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let sink = FixDropCopy::new(FixSessionConfig {
+            addr: listener.local_addr().unwrap().to_string(),
+            sender: "HFT".to_string(),
+            target: "DC".to_string(),
+        });
+        let acceptor = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = tokio::io::BufReader::new(stream);
+            let mut messages = Vec::new();
+            while messages.len() < 3 {
+                let mut message = Vec::new();
+                // Each message ends with the CheckSum field's SOH
+                while !String::from_utf8_lossy(&message).contains("\x0110=") || message.last() != Some(&1) {
+                    reader.read_until(1, &mut message).await.unwrap();
+                }
+                messages.push(String::from_utf8(message).unwrap());
+            }
+            messages
+        });
+
+        sink.publish(&[submitted(), fill()]).await.unwrap();
+        let messages = acceptor.await.unwrap();
+        assert_eq!(tag(&messages[0], "35"), Some("A"));
+        assert_eq!(tag(&messages[0], "141"), Some("Y"));
+        assert_eq!((tag(&messages[1], "35"), tag(&messages[1], "34")), (Some("8"), Some("2")));
+        assert_eq!(tag(&messages[2], "150"), Some("F"));
+        assert_eq!(tag(&messages[2], "56"), Some("DC"));
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+use async_trait::async_trait;
+use rskafka::client::partition::{Compression, PartitionClient, UnknownTopicHandling};
+use rskafka::client::ClientBuilder;
+use rskafka::record::Record;
+
+use crate::error::HftError;
+use super::{EventSink, OrderEvent};
+
+const DEFAULT_TOPIC: &str = "hft.orders";
+
+/// Connection settings for a Kafka-compatible broker (Kafka, Redpanda)
+#[derive(Debug, Clone)]
+pub struct KafkaConfig {
+    pub brokers: Vec<String>,
+    pub topic: String,
+    pub partition: i32,
+}
+
+impl KafkaConfig {
+    /// Read `HFT_KAFKA_BROKERS` (comma separated) and `HFT_KAFKA_TOPIC`;
+    /// returns `None` when no brokers are configured
+    pub fn from_env() -> Option<Self> {
+        let brokers: Vec<String> = std::env::var("HFT_KAFKA_BROKERS").ok()?
+            .split(',')
+            .map(|b| b.trim().to_string())
+            .filter(|b| !b.is_empty())
+            .collect();
+        if brokers.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            brokers,
+            topic: std::env::var("HFT_KAFKA_TOPIC").unwrap_or_else(|_| DEFAULT_TOPIC.to_string()),
+            partition: 0,
+        })
+    }
+}
+
+/// Publishes order events as JSON records keyed by symbol
+pub struct KafkaSink {
+    client: PartitionClient,
+    topic: String,
+}
+
+impl KafkaSink {
+    pub async fn connect(config: &KafkaConfig) -> Result<Self, HftError> {
+        let client = ClientBuilder::new(config.brokers.clone())
+            .build()
+            .await
+            .map_err(|e| HftError::Sink(format!("Failed to connect to Kafka: {}", e)))?;
+
+        let partition = client
+            .partition_client(config.topic.clone(), config.partition, UnknownTopicHandling::Retry)
+            .await
+            .map_err(|e| HftError::Sink(format!("Failed to open topic {}: {}", config.topic, e)))?;
+
+        Ok(Self { client: partition, topic: config.topic.clone() })
+    }
+}
+
+#[async_trait]
+impl EventSink for KafkaSink {
+    fn name(&self) -> &str {
+        "kafka"
+    }
+
+    async fn publish(&self, events: &[OrderEvent]) -> Result<(), HftError> {
+        let now = chrono::Utc::now();
+        let records = events
+            .iter()
+            .map(|event| {
+                let value = serde_json::to_vec(event)
+                    .map_err(|e| HftError::Serialization(format!("Failed to serialize order event: {}", e)))?;
+                Ok(Record {
+                    key: Some(event.key().as_bytes().to_vec()),
+                    value: Some(value),
+                    headers: BTreeMap::new(),
+                    timestamp: now,
+                })
+            })
+            .collect::<Result<Vec<_>, HftError>>()?;
+
+        self.client
+            .produce(records, Compression::NoCompression)
+            .await
+            .map_err(|e| HftError::Sink(format!("Failed to produce to {}: {}", self.topic, e)))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+use tracing::{error, warn};
+
+use crate::error::HftError;
+use crate::execution::OrderStatus;
+use crate::metrics::SINK_DROPPED_EVENTS;
+use crate::types::{Fill, Order};
+
+pub mod dropcopy;
+
+#[cfg(feature = "kafka")]
+pub mod kafka;
+
+/// Largest number of events handed to a sink in one call
+const MAX_BATCH: usize = 256;
+
+/// Attempts per batch before it is dropped
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Order lifecycle and fill records published to downstream systems
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OrderEvent {
+    Submitted { order_id: String, order: Order, timestamp: u64 },
+    Rejected { order: Order, reason: String, timestamp: u64 },
+    StatusChanged { order_id: String, status: OrderStatus, timestamp: u64 },
+    Amended { order_id: String, price: f64, quantity: f64, timestamp: u64 },
+    Fill(Fill),
+}
+
+impl OrderEvent {
+    /// Partitioning key: events for the same symbol stay ordered
+    pub fn key(&self) -> &str {
+        match self {
+            OrderEvent::Submitted { order, .. } | OrderEvent::Rejected { order, .. } => &order.symbol,
+            OrderEvent::StatusChanged { order_id, .. } | OrderEvent::Amended { order_id, .. } => order_id,
+            OrderEvent::Fill(fill) => &fill.symbol,
+        }
+    }
+}
+
+/// Destination for order events, e.g. a message broker topic
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    fn name(&self) -> &str;
+    async fn publish(&self, events: &[OrderEvent]) -> Result<(), HftError>;
+}
+
+/// Non-blocking handle used by the trading path to emit order events.
+///
+/// Events are queued and written by a background task; when the queue is
+/// full they are dropped and counted rather than delaying order flow.
+#[derive(Clone)]
+pub struct SinkHandle {
+    tx: mpsc::Sender<OrderEvent>,
+    name: String,
+}
+
+impl SinkHandle {
+    /// Start the background writer for `sink` with room for `capacity`
+    /// queued events
+    pub fn spawn(sink: Arc<dyn EventSink>, capacity: usize) -> Self {
+        let (tx, rx) = mpsc::channel(capacity);
+        let name = sink.name().to_string();
+        tokio::spawn(run_sink(sink, rx));
+        Self { tx, name }
+    }
+
+    pub fn send(&self, event: OrderEvent) {
+        if self.tx.try_send(event).is_err() {
+            SINK_DROPPED_EVENTS.with_label_values(&[&self.name]).inc();
+        }
+    }
+}
+
+async fn run_sink(sink: Arc<dyn EventSink>, mut rx: mpsc::Receiver<OrderEvent>) {
+    let mut batch = Vec::with_capacity(MAX_BATCH);
+
+    while rx.recv_many(&mut batch, MAX_BATCH).await > 0 {
+        let mut attempt = 1;
+        while let Err(e) = sink.publish(&batch).await {
+            if attempt >= MAX_ATTEMPTS {
+                error!(sink = sink.name(), events = batch.len(), error = %e, "Dropping order events after repeated failures");
+                SINK_DROPPED_EVENTS.with_label_values(&[sink.name()]).inc_by(batch.len() as f64);
+                break;
+            }
+            warn!(sink = sink.name(), attempt = attempt, error = %e, "Failed to publish order events, retrying");
+            tokio::time::sleep(Duration::from_millis(100 * 2u64.pow(attempt))).await;
+            attempt += 1;
+        }
+        batch.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::Mutex;
+    use crate::types::{OrderSide, OrderType};
+
+    #[derive(Default)]
+    struct RecordingSink {
+        events: Mutex<Vec<OrderEvent>>,
+    }
+
+    #[async_trait]
+    impl EventSink for RecordingSink {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn publish(&self, events: &[OrderEvent]) -> Result<(), HftError> {
+            self.events.lock().await.extend_from_slice(events);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_events_reach_sink_in_order() {
+        let sink = Arc::new(RecordingSink::default());
+        let handle = SinkHandle::spawn(sink.clone(), 16);
+
+        let order = Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price: 50000.0,
+            venue: "MOCK".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        };
+        handle.send(OrderEvent::Submitted { order_id: "1".to_string(), order, timestamp: 1 });
+        handle.send(OrderEvent::StatusChanged { order_id: "1".to_string(), status: OrderStatus::Filled, timestamp: 2 });
+
+        for _ in 0..100 {
+            if sink.events.lock().await.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let events = sink.events.lock().await;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].key(), "BTCUSDT");
+        let json = serde_json::to_value(&events[1]).unwrap();
+        assert_eq!(json["type"], "status_changed");
+        assert_eq!(json["status"], "Filled");
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+use crate::book::BookSnapshot;
+use crate::execution::OpenOrder;
+use crate::risk::{Position, PositionKey};
+use crate::error::HftError;
+
+/// Bumped whenever the snapshot layout changes incompatibly
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Engine state persisted across restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineSnapshot {
+    pub version: u32,
+    /// Milliseconds since the Unix epoch
+    pub taken_at: u64,
+    pub books: Vec<BookSnapshot>,
+    pub open_orders: Vec<OpenOrder>,
+    pub positions: Vec<(PositionKey, Position)>,
+    /// Subscribed symbols per venue
+    pub subscriptions: HashMap<String, Vec<String>>,
+}
+
+impl EngineSnapshot {
+    /// Write the snapshot atomically: a crash mid-write leaves the previous
+    /// snapshot in place
+    pub fn save(&self, path: &Path) -> Result<(), HftError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_vec_pretty(self)
+            .map_err(|e| HftError::Serialization(format!("Failed to serialize snapshot: {}", e)))?;
+
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)?;
+
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, HftError> {
+        let json = std::fs::read(path)?;
+        let snapshot: Self = serde_json::from_slice(&json)
+            .map_err(|e| HftError::Config(format!("Invalid snapshot {}: {}", path.display(), e)))?;
+
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(HftError::Config(format!(
+                "Snapshot version {} is not supported (expected {})",
+                snapshot.version, SNAPSHOT_VERSION
+            )));
+        }
+
+        Ok(snapshot)
+    }
+}
+
+/// Human-readable summary, as printed by `hft_engine snapshot`
+impl fmt::Display for EngineSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Snapshot v{} taken at {}", self.version, self.taken_at)?;
+        writeln!(f, "{} books", self.books.len())?;
+        for book in &self.books {
+            let level = |levels: &[(f64, f64)]| levels.first().map_or("-".to_string(), |(p, q)| format!("{}x{}", q, p));
+            writeln!(f, "  {}: bid {} ask {}", book.symbol, level(&book.bids), level(&book.asks))?;
+        }
+        writeln!(f, "{} open orders", self.open_orders.len())?;
+        for open in &self.open_orders {
+            writeln!(
+                f,
+                "  {} {} {:?} {}@{} on {} ({:?}, {} filled)",
+                open.order_id, open.order.symbol, open.order.side, open.order.quantity, open.order.price,
+                open.order.venue, open.status, open.filled_quantity,
+            )?;
+        }
+        writeln!(f, "{} positions", self.positions.len())?;
+        for (key, position) in &self.positions {
+            writeln!(
+                f,
+                "  {} {} {}: {} @ {} realized {}",
+                key.strategy, key.venue, key.symbol, position.quantity, position.avg_price, position.realized_pnl,
+            )?;
+        }
+        let mut venues: Vec<_> = self.subscriptions.iter().collect();
+        venues.sort();
+        write!(f, "Subscriptions:")?;
+        for (venue, symbols) in venues {
+            write!(f, " {} [{}]", venue, symbols.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of comparing snapshot orders with what a venue reports open
+#[derive(Debug, Default, PartialEq)]
+pub struct Reconciliation {
+    /// In the snapshot and still open on the venue
+    pub confirmed: Vec<String>,
+    /// In the snapshot but no longer open on the venue
+    pub closed: Vec<String>,
+    /// Open on the venue but unknown to the snapshot
+    pub unknown: Vec<String>,
+}
+
+pub fn reconcile(snapshot_orders: &[String], venue_orders: &[String]) -> Reconciliation {
+    let venue: HashSet<&String> = venue_orders.iter().collect();
+    let known: HashSet<&String> = snapshot_orders.iter().collect();
+
+    let mut result = Reconciliation::default();
+    for id in snapshot_orders {
+        if venue.contains(id) {
+            result.confirmed.push(id.clone());
+        } else {
+            result.closed.push(id.clone());
+        }
+    }
+    result.unknown = venue_orders
+        .iter()
+        .filter(|id| !known.contains(id))
+        .cloned()
+        .collect();
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconcile() {
+        let snapshot = vec!["a".to_string(), "b".to_string()];
+        let venue = vec!["b".to_string(), "c".to_string()];
+
+        let result = reconcile(&snapshot, &venue);
+        assert_eq!(result.confirmed, vec!["b".to_string()]);
+        assert_eq!(result.closed, vec!["a".to_string()]);
+        assert_eq!(result.unknown, vec!["c".to_string()]);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("hft_snapshot_{}", std::process::id()));
+        let path = dir.join("state.json");
+
+        let snapshot = EngineSnapshot {
+            version: SNAPSHOT_VERSION,
+            taken_at: 1,
+            books: vec![BookSnapshot {
+                symbol: "BTCUSDT".to_string(),
+                bids: vec![(50000.0, 1.0)],
+                asks: vec![(50001.0, 2.0)],
+            }],
+            open_orders: Vec::new(),
+            positions: vec![(
+                PositionKey {
+                    strategy: "mm".to_string(),
+                    venue: "MOCK".to_string(),
+                    symbol: "BTCUSDT".to_string(),
+                },
+                Position { quantity: 1.5, avg_price: 50000.0, realized_pnl: 12.0, financing: -0.5, commission: 1.25 },
+            )],
+            subscriptions: HashMap::from([("MOCK".to_string(), vec!["BTCUSDT".to_string()])]),
+        };
+
+        snapshot.save(&path).unwrap();
+        let loaded = EngineSnapshot::load(&path).unwrap();
+        assert_eq!(loaded.books[0].asks, vec![(50001.0, 2.0)]);
+        assert_eq!(loaded.positions[0].1.quantity, 1.5);
+        assert_eq!(loaded.subscriptions["MOCK"], vec!["BTCUSDT".to_string()]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
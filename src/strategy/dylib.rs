@@ -0,0 +1,258 @@
+//! Strategies loaded from shared libraries.
+//!
+//! A plugin is a `cdylib` exporting
+//! `hft_strategy_factory() -> *const StrategyFactory`. Payloads are JSON
+//! encodings of the engine types, as for WASM strategies. The library is
+//! copied before it is opened, so a rebuilt plugin can be loaded again with
+//! [`DylibStrategy::load`] and swapped in while the engine keeps running.
+//!
+//! Plugins run in-process with full access to the host, so only load them
+//! on trusted hosts. The plugin state must be safe to move between threads.
+
+use std::ffi::{c_char, c_void, CStr};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use libloading::{Library, Symbol};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::error::HftError;
+use crate::types::{Fill, Order, Quote};
+use super::StrategyPlugin;
+
+pub const ABI_VERSION: u32 = 1;
+
+const FACTORY_SYMBOL: &[u8] = b"hft_strategy_factory";
+
+/// Called by a plugin once per order; `ctx` is the value the host passed in
+pub type SubmitOrderFn = unsafe extern "C" fn(ctx: *mut c_void, order: *const u8, len: usize);
+
+/// Callback receiving a JSON payload and the order submission hook
+pub type EventFn = unsafe extern "C" fn(
+    state: *mut c_void,
+    payload: *const u8,
+    len: usize,
+    submit: SubmitOrderFn,
+    ctx: *mut c_void,
+);
+
+/// Entry points a plugin exposes to the host
+#[repr(C)]
+pub struct StrategyFactory {
+    /// Must equal [`ABI_VERSION`]
+    pub abi_version: u32,
+    /// NUL-terminated, valid for the lifetime of the library
+    pub name: *const c_char,
+    pub create: unsafe extern "C" fn() -> *mut c_void,
+    pub destroy: unsafe extern "C" fn(state: *mut c_void),
+    pub on_quote: EventFn,
+    pub on_fill: EventFn,
+}
+
+unsafe extern "C" fn collect_order(ctx: *mut c_void, order: *const u8, len: usize) {
+    let orders = &mut *(ctx as *mut Vec<Result<Order, String>>);
+    let bytes = std::slice::from_raw_parts(order, len);
+    orders.push(serde_json::from_slice(bytes).map_err(|e| e.to_string()));
+}
+
+static LOAD_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+pub struct DylibStrategy {
+    name: String,
+    factory: *const StrategyFactory,
+    state: *mut c_void,
+    /// Closed after the state is destroyed; factory and state point into it
+    library: Option<(Library, PathBuf)>,
+}
+
+// The plugin contract requires state that can move between threads, and
+// callbacks only run through `&mut self`
+unsafe impl Send for DylibStrategy {}
+unsafe impl Sync for DylibStrategy {}
+
+impl DylibStrategy {
+    pub fn load(path: &Path) -> Result<Self, HftError> {
+        // Open a private copy so the original can be replaced by a rebuild
+        let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let copy = std::env::temp_dir().join(format!(
+            "hft-plugin-{}-{}-{}{}",
+            stem,
+            std::process::id(),
+            LOAD_COUNTER.fetch_add(1, Ordering::Relaxed),
+            path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default(),
+        ));
+        std::fs::copy(path, &copy)?;
+
+        let result = Self::open(path, &copy);
+        if result.is_err() {
+            let _ = std::fs::remove_file(&copy);
+        }
+        result
+    }
+
+    fn open(path: &Path, copy: &Path) -> Result<Self, HftError> {
+        let library = unsafe { Library::new(copy) }
+            .map_err(|e| HftError::Config(format!("Failed to load {}: {}", path.display(), e)))?;
+        let factory = unsafe {
+            let entry: Symbol<unsafe extern "C" fn() -> *const StrategyFactory> = library
+                .get(FACTORY_SYMBOL)
+                .map_err(|e| HftError::Config(format!("{} is not a strategy plugin: {}", path.display(), e)))?;
+            entry()
+        };
+
+        let mut strategy = unsafe { Self::from_factory(factory)? };
+        strategy.library = Some((library, copy.to_path_buf()));
+        Ok(strategy)
+    }
+
+    /// # Safety
+    /// `factory` must point to a valid factory that outlives the strategy
+    unsafe fn from_factory(factory: *const StrategyFactory) -> Result<Self, HftError> {
+        let f = factory.as_ref()
+            .ok_or_else(|| HftError::Config("Strategy plugin returned no factory".to_string()))?;
+        if f.abi_version != ABI_VERSION {
+            return Err(HftError::Config(format!(
+                "Strategy plugin targets ABI {} (host is {})",
+                f.abi_version, ABI_VERSION
+            )));
+        }
+
+        let name = if f.name.is_null() {
+            "dylib".to_string()
+        } else {
+            CStr::from_ptr(f.name).to_string_lossy().into_owned()
+        };
+
+        Ok(Self { name, factory, state: (f.create)(), library: None })
+    }
+
+    fn dispatch<T: Serialize>(&mut self, callback: EventFn, payload: &T) -> Vec<Order> {
+        let bytes = match serde_json::to_vec(payload) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(strategy = %self.name, error = %e, "Failed to encode strategy payload");
+                return Vec::new();
+            }
+        };
+
+        let mut submitted: Vec<Result<Order, String>> = Vec::new();
+        unsafe {
+            callback(
+                self.state,
+                bytes.as_ptr(),
+                bytes.len(),
+                collect_order,
+                &mut submitted as *mut _ as *mut c_void,
+            );
+        }
+
+        submitted
+            .into_iter()
+            .filter_map(|order| {
+                order.map_err(|e| warn!(strategy = %self.name, error = %e, "Invalid order from strategy")).ok()
+            })
+            .collect()
+    }
+}
+
+impl StrategyPlugin for DylibStrategy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_quote(&mut self, quote: &Quote) -> Vec<Order> {
+        let callback = unsafe { (*self.factory).on_quote };
+        self.dispatch(callback, quote)
+    }
+
+    fn on_fill(&mut self, fill: &Fill) -> Vec<Order> {
+        let callback = unsafe { (*self.factory).on_fill };
+        self.dispatch(callback, fill)
+    }
+}
+
+impl Drop for DylibStrategy {
+    fn drop(&mut self) {
+        unsafe { ((*self.factory).destroy)(self.state) };
+        if let Some((library, copy)) = self.library.take() {
+            drop(library);
+            let _ = std::fs::remove_file(copy);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderSide, OrderType};
+
+    // An in-process plugin that counts quotes and buys on every second one
+
+    unsafe extern "C" fn create() -> *mut c_void {
+        Box::into_raw(Box::new(0u64)) as *mut c_void
+    }
+
+    unsafe extern "C" fn destroy(state: *mut c_void) {
+        drop(Box::from_raw(state as *mut u64));
+    }
+
+    unsafe extern "C" fn on_quote(state: *mut c_void, payload: *const u8, len: usize, submit: SubmitOrderFn, ctx: *mut c_void) {
+        let count = &mut *(state as *mut u64);
+        *count += 1;
+        if count.is_multiple_of(2) {
+            let quote: Quote = serde_json::from_slice(std::slice::from_raw_parts(payload, len)).unwrap();
+            let order = serde_json::to_vec(&Order {
+                symbol: quote.symbol,
+                side: OrderSide::Buy,
+                quantity: 1.0,
+                price: quote.bid,
+                venue: quote.venue,
+                order_type: OrderType::Limit,
+                expire_after: None,
+                strategy: None,
+                bypass_kill_switch: false,
+            }).unwrap();
+            submit(ctx, order.as_ptr(), order.len());
+        }
+    }
+
+    unsafe extern "C" fn on_fill(_: *mut c_void, _: *const u8, _: usize, _: SubmitOrderFn, _: *mut c_void) {}
+
+    fn factory(abi_version: u32) -> StrategyFactory {
+        StrategyFactory {
+            abi_version,
+            name: c"every_other".as_ptr(),
+            create,
+            destroy,
+            on_quote,
+            on_fill,
+        }
+    }
+
+    #[test]
+    fn test_factory_round_trip() {
+        let factory = Box::leak(Box::new(factory(ABI_VERSION)));
+        let mut strategy = unsafe { DylibStrategy::from_factory(factory).unwrap() };
+        assert_eq!(strategy.name(), "every_other");
+
+        let quote = Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 50000.0,
+            ask: 50001.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "MOCK".into(),
+            timestamp: 1,
+        };
+        assert!(strategy.on_quote(&quote).is_empty());
+        let orders = strategy.on_quote(&quote);
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].price, 50000.0);
+    }
+
+    #[test]
+    fn test_abi_mismatch_is_rejected() {
+        let factory = Box::leak(Box::new(factory(ABI_VERSION + 1)));
+        assert!(unsafe { DylibStrategy::from_factory(factory) }.is_err());
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::fees::FeeModel;
+use crate::signals::{RollingStdDev, Signals, ToxicityMonitor};
+use crate::types::{Fill, Order, OrderSide, OrderType, Quote, Symbol, VenueId};
+use super::params::ParameterStore;
+use super::StrategyPlugin;
+
+/// One rung of the quote ladder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuoteLevel {
+    /// Distance beyond the inside quote, in basis points of mid
+    pub offset_bps: f64,
+    pub size: f64,
+}
+
+/// Spread and skew model of the reference market maker. Missing fields
+/// keep their defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarketMakerParams {
+    /// Quoted spread around mid before adjustments, in basis points
+    pub base_spread_bps: f64,
+    /// Quote shift per unit of inventory, in basis points; long inventory
+    /// lowers both quotes
+    pub skew_bps_per_unit: f64,
+    /// Cap on the inventory shift, in basis points
+    pub max_skew_bps: f64,
+    /// Spread added per basis point of mid volatility
+    pub vol_multiplier: f64,
+    /// Quotes the mid volatility is measured over
+    pub vol_window: usize,
+    /// Quote ladder per side, innermost first
+    pub levels: Vec<QuoteLevel>,
+}
+
+impl Default for MarketMakerParams {
+    fn default() -> Self {
+        Self {
+            base_spread_bps: 10.0,
+            skew_bps_per_unit: 0.0,
+            max_skew_bps: 50.0,
+            vol_multiplier: 0.0,
+            vol_window: 100,
+            levels: vec![QuoteLevel { offset_bps: 0.0, size: 1.0 }],
+        }
+    }
+}
+
+impl MarketMakerParams {
+    /// Shift applied to both quotes for `position`, in basis points
+    pub fn skew_bps(&self, position: f64) -> f64 {
+        let max = self.max_skew_bps.abs();
+        (-position * self.skew_bps_per_unit).clamp(-max, max)
+    }
+
+    /// Half the quoted spread given mid volatility in basis points
+    pub fn half_spread_bps(&self, volatility_bps: f64) -> f64 {
+        (self.base_spread_bps + self.vol_multiplier * volatility_bps) / 2.0
+    }
+}
+
+/// Mid volatility per venue and symbol
+#[derive(Debug)]
+struct MidVolatility {
+    last_mid: f64,
+    returns_bps: RollingStdDev,
+}
+
+/// Reference market-making strategy: quotes a ladder on both sides of every
+/// symbol it sees, widened by mid volatility and order-flow toxicity and
+/// skewed against its inventory.
+///
+/// Parameters are read from the parameter store under the strategy's name
+/// and picked up on the next quote after they change.
+pub struct MarketMaker {
+    name: String,
+    store: ParameterStore,
+    params: MarketMakerParams,
+    version: u64,
+    /// Net position per symbol from this strategy's fills
+    positions: HashMap<String, f64>,
+    volatility: HashMap<(VenueId, Symbol), MidVolatility>,
+    toxicity: Option<ToxicityMonitor>,
+    /// Widens quotes by the maker fee so the quoted spread is net of fees
+    fees: Option<FeeModel>,
+}
+
+impl MarketMaker {
+    pub fn new(name: &str, store: ParameterStore) -> Self {
+        let mut mm = Self {
+            name: name.to_string(),
+            store,
+            params: MarketMakerParams::default(),
+            version: 0,
+            positions: HashMap::new(),
+            volatility: HashMap::new(),
+            toxicity: None,
+            fees: None,
+        };
+        mm.reload();
+        mm
+    }
+
+    pub fn params(&self) -> &MarketMakerParams {
+        &self.params
+    }
+
+    pub fn position(&self, symbol: &str) -> f64 {
+        self.positions.get(symbol).copied().unwrap_or(0.0)
+    }
+
+    /// Pick up parameters changed in the store. Invalid parameters are
+    /// logged and the current ones kept.
+    fn reload(&mut self) {
+        if self.store.version(&self.name) == self.version {
+            return;
+        }
+        match self.store.typed::<MarketMakerParams>(&self.name) {
+            Some((version, Ok(params))) => {
+                info!(strategy = %self.name, version = version, "Market maker parameters updated");
+                if params.vol_window != self.params.vol_window {
+                    self.volatility.clear();
+                }
+                self.params = params;
+                self.version = version;
+            }
+            Some((version, Err(e))) => {
+                warn!(strategy = %self.name, version = version, error = %e, "Ignoring invalid market maker parameters");
+                self.version = version;
+            }
+            None => {}
+        }
+    }
+
+    /// Fold in the new mid and return the current volatility in basis points
+    fn update_volatility(&mut self, quote: &Quote, mid: f64) -> f64 {
+        let window = self.params.vol_window;
+        let vol = self.volatility
+            .entry((quote.venue, quote.symbol))
+            .or_insert_with(|| MidVolatility {
+                last_mid: mid,
+                returns_bps: RollingStdDev::new(window),
+            });
+        if vol.last_mid != mid {
+            vol.returns_bps.update((mid / vol.last_mid - 1.0) * 10_000.0);
+            vol.last_mid = mid;
+        }
+        vol.returns_bps.value().unwrap_or(0.0)
+    }
+
+    fn order(&self, quote: &Quote, side: OrderSide, price: f64, quantity: f64) -> Order {
+        Order {
+            symbol: quote.symbol,
+            side,
+            quantity,
+            price,
+            venue: quote.venue,
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        }
+    }
+}
+
+impl StrategyPlugin for MarketMaker {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_quote(&mut self, quote: &Quote) -> Vec<Order> {
+        self.reload();
+        if quote.bid <= 0.0 || quote.ask <= 0.0 {
+            return Vec::new();
+        }
+
+        let mid = (quote.bid + quote.ask) / 2.0;
+        let volatility = self.update_volatility(quote, mid);
+        let toxicity = self.toxicity
+            .as_ref()
+            .map_or(1.0, |t| t.spread_multiplier(&quote.venue, &quote.symbol));
+        let maker_bps = self.fees.as_ref().map_or(0.0, |fees| fees.fees(&quote.venue).maker_bps);
+        let half_spread = self.params.half_spread_bps(volatility) * toxicity + maker_bps;
+        let skew = self.params.skew_bps(self.position(&quote.symbol));
+
+        let mut orders = Vec::with_capacity(self.params.levels.len() * 2);
+        for level in self.params.levels.iter().filter(|l| l.size > 0.0) {
+            let bid = mid * (1.0 + (skew - half_spread - level.offset_bps) / 10_000.0);
+            let ask = mid * (1.0 + (skew + half_spread + level.offset_bps) / 10_000.0);
+            orders.push(self.order(quote, OrderSide::Buy, bid, level.size));
+            orders.push(self.order(quote, OrderSide::Sell, ask, level.size));
+        }
+        orders
+    }
+
+    fn on_fill(&mut self, fill: &Fill) -> Vec<Order> {
+        if fill.strategy == self.name {
+            let signed = match fill.side {
+                OrderSide::Buy => fill.quantity,
+                OrderSide::Sell => -fill.quantity,
+            };
+            *self.positions.entry(fill.symbol.to_string()).or_insert(0.0) += signed;
+        }
+        Vec::new()
+    }
+
+    fn attach_signals(&mut self, signals: &Signals) {
+        self.toxicity = Some(signals.toxicity.clone());
+        self.fees = Some(signals.fees.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fees::{FeeSchedule, FeeSchedules, Fees};
+
+    fn quote(bid: f64, ask: f64) -> Quote {
+        Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "MOCK".into(),
+            timestamp: 0,
+        }
+    }
+
+    fn prices(orders: &[Order]) -> Vec<(OrderSide, f64, f64)> {
+        orders.iter().map(|o| (o.side, (o.price * 1e6).round() / 1e6, o.quantity)).collect()
+    }
+
+    #[test]
+    fn test_ladder_skews_against_inventory() {
+        let store = ParameterStore::new();
+        store.set("mm", serde_json::json!({
+            "base_spread_bps": 20.0,
+            "skew_bps_per_unit": 4.0,
+            "max_skew_bps": 6.0,
+            "levels": [{ "offset_bps": 0.0, "size": 1.0 }, { "offset_bps": 10.0, "size": 2.0 }],
+        }));
+        let mut mm = MarketMaker::new("mm", store);
+
+        let orders = mm.on_quote(&quote(9990.0, 10010.0));
+        assert_eq!(prices(&orders), vec![
+            (OrderSide::Buy, 9990.0, 1.0),
+            (OrderSide::Sell, 10010.0, 1.0),
+            (OrderSide::Buy, 9980.0, 2.0),
+            (OrderSide::Sell, 10020.0, 2.0),
+        ]);
+
+        // Long 2 units: the 8bp shift is capped at 6bp
+        mm.on_fill(&Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "MOCK".into(),
+            strategy: "mm".to_string(),
+            side: OrderSide::Buy,
+            quantity: 2.0,
+            price: 9990.0,
+            timestamp: 0,
+            commission: None,
+        });
+        let orders = mm.on_quote(&quote(9990.0, 10010.0));
+        assert_eq!(prices(&orders)[..2], [(OrderSide::Buy, 9984.0, 1.0), (OrderSide::Sell, 10004.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_parameters_hot_reload() {
+        let store = ParameterStore::new();
+        let mut mm = MarketMaker::new("mm", store.clone());
+        assert_eq!(mm.params(), &MarketMakerParams::default());
+        assert_eq!(prices(&mm.on_quote(&quote(9999.0, 10001.0)))[0].1, 9995.0);
+
+        store.set("mm", serde_json::json!({ "base_spread_bps": 2.0 }));
+        assert_eq!(prices(&mm.on_quote(&quote(9999.0, 10001.0)))[0].1, 9999.0);
+
+        // A bad update keeps the running parameters
+        store.set("mm", serde_json::json!({ "base_spread_bps": "wide" }));
+        assert_eq!(prices(&mm.on_quote(&quote(9999.0, 10001.0)))[0].1, 9999.0);
+        assert_eq!(mm.params().base_spread_bps, 2.0);
+    }
+
+    #[test]
+    fn test_volatility_widens_spread() {
+        let store = ParameterStore::new();
+        store.set("mm", serde_json::json!({ "base_spread_bps": 0.0, "vol_multiplier": 2.0, "vol_window": 2 }));
+        let mut mm = MarketMaker::new("mm", store);
+
+        mm.on_quote(&quote(9999.0, 10001.0));
+        mm.on_quote(&quote(10009.0, 10011.0));
+        // Returns of about +10bp and -10bp: a standard deviation of ~14.1bp
+        let orders = mm.on_quote(&quote(9999.0, 10001.0));
+        let half_spread_bps = (orders[1].price / 10_000.0 - 1.0) * 10_000.0;
+        assert!((half_spread_bps - 2.0 * 200f64.sqrt() / 2.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_spread_is_net_of_maker_fees() {
+        let store = ParameterStore::new();
+        store.set("mm", serde_json::json!({ "base_spread_bps": 10.0 }));
+        let mut mm = MarketMaker::new("mm", store);
+        let signals = Signals::default();
+        signals.fees.configure(FeeSchedules::new().with_venue("MOCK", FeeSchedule::new(Fees { maker_bps: 2.0, taker_bps: 5.0 })));
+        mm.attach_signals(&signals);
+
+        // 5bp half spread plus the 2bp maker fee on each side
+        let orders = prices(&mm.on_quote(&quote(9999.0, 10001.0)));
+        assert_eq!(orders[0].1, 9993.0);
+        assert_eq!(orders[1].1, 10007.0);
+    }
+}
@@ -1,10 +1,551 @@
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, RwLock};
 use std::collections::HashMap;
+use tracing::{debug, info, warn};
 use crate::book::OrderBook;
-use crate::types::Order;
+use crate::types::{Fill, Order, OrderType, Quote, Symbol, Trade};
+use crate::audit::{AuditEvent, AuditLog};
+use crate::risk::TradingToggles;
+use crate::metrics::{labels, TOXIC_QUOTES_WITHHELD};
+use crate::signals::{Signals, ToxicityLevel};
+use crate::feed::{wire, BookUpdate, FeedMessage};
+use crate::command::mode::TradingState;
+use crate::scheduler::{TimerId, TimerService};
 
+pub mod market_maker;
+pub mod params;
+pub mod shadow;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "plugins-dylib")]
+pub mod dylib;
+
+pub use market_maker::{MarketMaker, MarketMakerParams, QuoteLevel};
+pub use params::ParameterStore;
+pub use shadow::{ShadowReport, ShadowResults, ShadowSpec, Shadows};
+
+/// Trading logic loaded into the engine.
+///
+/// Callbacks return the orders the strategy wants to send; the host routes
+/// them, so a plugin never touches engine internals directly.
+pub trait StrategyPlugin: Send + Sync {
+    fn name(&self) -> &str;
+    fn on_quote(&mut self, quote: &Quote) -> Vec<Order>;
+    fn on_fill(&mut self, fill: &Fill) -> Vec<Order>;
+
+    /// Called once when the plugin is loaded, with engine-derived market
+    /// data such as candles that the plugin may keep a handle to
+    fn attach_signals(&mut self, signals: &Signals) {
+        let _ = signals;
+    }
+
+    /// Repeating timers as `(name, period)`, e.g. a requote every 50 ms,
+    /// run by the engine's shared timer service
+    fn timers(&self) -> Vec<(String, Duration)> {
+        Vec::new()
+    }
+
+    /// Called when one of the plugin's `timers` fires
+    fn on_timer(&mut self, timer: &str) -> Vec<Order> {
+        let _ = timer;
+        Vec::new()
+    }
+}
+
+/// Fired timers waiting for the strategy runner before some are dropped
+const TIMER_QUEUE: usize = 1024;
+
+/// A plugin timer that fired
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategyTimer {
+    pub plugin: String,
+    pub timer: String,
+}
+
+// Fields are consumed once a concrete strategy is plugged in
+#[allow(dead_code)]
 pub struct Strategy {
-    pub(crate) books: Arc<RwLock<HashMap<String, OrderBook>>>,
+    pub(crate) books: Arc<RwLock<HashMap<Symbol, OrderBook>>>,
     pub(crate) order_tx: mpsc::Sender<Order>,
-}
\ No newline at end of file
+    pub(crate) plugins: Vec<Box<dyn StrategyPlugin>>,
+    pub(crate) audit: Option<AuditLog>,
+    pub(crate) toggles: Arc<TradingToggles>,
+    pub(crate) signals: Signals,
+    /// Orders are withheld while the mode accepts none; unrestricted when
+    /// unset
+    pub(crate) trading: Option<TradingState>,
+    /// Strategies whose orders are simulated rather than sent
+    pub(crate) shadows: Shadows,
+    /// Plugin timers fired since the last `run`, once scheduled
+    pub(crate) timer_rx: Option<mpsc::Receiver<StrategyTimer>>,
+    /// Live fills, once the engine forwards them
+    pub(crate) fill_rx: Option<mpsc::Receiver<Fill>>,
+    /// Plugins loaded while `run` holds the runner
+    pub(crate) plugin_rx: Option<mpsc::Receiver<Box<dyn StrategyPlugin>>>,
+}
+
+enum Input {
+    Frame(Arc<Vec<u8>>),
+    Timer(StrategyTimer),
+    Fill(Fill),
+    Plugin(Box<dyn StrategyPlugin>),
+}
+
+/// The next message on `rx`, or never when there is no channel
+async fn recv<T>(rx: &mut Option<mpsc::Receiver<T>>) -> Option<T> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+impl Strategy {
+    /// Add a plugin, replacing any loaded plugin with the same name.
+    ///
+    /// Returns the replaced plugin so the caller decides when to drop it.
+    pub fn add_plugin(&mut self, mut plugin: Box<dyn StrategyPlugin>) -> Option<Box<dyn StrategyPlugin>> {
+        plugin.attach_signals(&self.signals);
+        match self.plugins.iter().position(|p| p.name() == plugin.name()) {
+            Some(i) => Some(std::mem::replace(&mut self.plugins[i], plugin)),
+            None => {
+                self.plugins.push(plugin);
+                None
+            }
+        }
+    }
+
+    /// Run `plugin` in shadow, compared with the live strategy named
+    /// `live`, replacing any shadow with the same name. Returns whether one
+    /// was replaced.
+    pub fn add_shadow(&mut self, plugin: Box<dyn StrategyPlugin>, live: Option<String>) -> bool {
+        self.shadows.add(plugin, live)
+    }
+
+    pub fn on_quote(&mut self, quote: &Quote) {
+        self.signals.order_flow.on_quote(quote);
+        self.signals.toxicity.on_quote(quote);
+        self.signals.greeks.on_quote(quote);
+        for i in 0..self.plugins.len() {
+            let orders = self.plugins[i].on_quote(quote);
+            self.send_orders(i, orders);
+        }
+        self.shadows.on_quote(quote);
+    }
+
+    /// Keep candles, queue position and toxicity estimates current with
+    /// the trade stream
+    pub fn on_trade(&mut self, trade: &Trade) {
+        self.signals.candles.on_trade(trade);
+        self.signals.queue.on_trade(trade);
+        self.signals.toxicity.on_trade(trade);
+        self.shadows.on_trade(trade);
+    }
+
+    /// Follow L2 level changes for queue position estimates
+    pub fn on_book_update(&mut self, update: &BookUpdate) {
+        self.signals.queue.on_book_update(update);
+        self.shadows.on_book_update(update);
+    }
+
+    /// Register every plugin's timers on `timers`. Fired timers arrive on
+    /// the returned channel for `on_timer`; a fire is dropped while the
+    /// channel is full, as the next one follows a period later.
+    pub fn schedule_timers(&self, timers: &TimerService) -> (Vec<TimerId>, mpsc::Receiver<StrategyTimer>) {
+        let (tx, rx) = mpsc::channel(TIMER_QUEUE);
+        let ids = self.plugins.iter()
+            .flat_map(|plugin| plugin.timers().into_iter().map(move |(timer, period)| (plugin.name().to_string(), timer, period)))
+            .chain(self.shadows.timers())
+            .map(|(plugin, timer, period)| {
+                let tx = tx.clone();
+                let fired = StrategyTimer { plugin, timer };
+                timers.every(&format!("{}.{}", fired.plugin, fired.timer), period, move || {
+                    if tx.try_send(fired.clone()).is_err() {
+                        debug!(strategy = %fired.plugin, timer = %fired.timer, "Strategy behind, timer fire dropped");
+                    }
+                })
+            })
+            .collect();
+        (ids, rx)
+    }
+
+    pub fn on_timer(&mut self, fired: &StrategyTimer) {
+        if let Some(i) = self.plugins.iter().position(|p| p.name() == fired.plugin) {
+            let orders = self.plugins[i].on_timer(&fired.timer);
+            self.send_orders(i, orders);
+        } else {
+            self.shadows.on_timer(&fired.plugin, &fired.timer);
+        }
+    }
+
+    /// Pass a live fill to every plugin. Shadow strategies only see their
+    /// own simulated fills, and track their live strategy's.
+    pub fn on_fill(&mut self, fill: &Fill) {
+        self.shadows.on_live_fill(fill);
+        for i in 0..self.plugins.len() {
+            let orders = self.plugins[i].on_fill(fill);
+            self.send_orders(i, orders);
+        }
+    }
+
+    /// Drive the plugins from the market data `feed`, their timers and
+    /// live fills until the feed closes
+    pub async fn run(&mut self, mut feed: broadcast::Receiver<Arc<Vec<u8>>>) {
+        loop {
+            let (timers, fills, plugins) = (&mut self.timer_rx, &mut self.fill_rx, &mut self.plugin_rx);
+            let next = tokio::select! {
+                frame = feed.recv() => match frame {
+                    Ok(frame) => Input::Frame(frame),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(skipped = skipped, "Strategies behind the market data feed, messages skipped");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                Some(fired) = recv(timers) => Input::Timer(fired),
+                Some(fill) = recv(fills) => Input::Fill(fill),
+                Some(plugin) = recv(plugins) => Input::Plugin(plugin),
+            };
+
+            match next {
+                Input::Frame(frame) => match wire::decode(&frame) {
+                    Ok(Some((FeedMessage::Quote(quote), _))) => self.on_quote(&quote),
+                    Ok(Some((FeedMessage::Trade(trade), _))) => self.on_trade(&trade),
+                    Ok(Some((FeedMessage::BookUpdate(update), _))) => self.on_book_update(&update),
+                    Ok(None) => warn!("Truncated market data frame"),
+                    Err(e) => warn!(error = %e, "Undecodable market data frame"),
+                },
+                Input::Timer(fired) => self.on_timer(&fired),
+                Input::Fill(fill) => self.on_fill(&fill),
+                Input::Plugin(plugin) => {
+                    let name = plugin.name().to_string();
+                    // Dropped here, between callbacks
+                    if self.add_plugin(plugin).is_some() {
+                        info!(strategy = %name, "Strategy replaced");
+                    } else {
+                        info!(strategy = %name, "Strategy loaded");
+                    }
+                }
+            }
+        }
+    }
+
+    fn send_orders(&self, plugin: usize, orders: Vec<Order>) {
+        let name = self.plugins[plugin].name();
+        if let Some(mode) = self.trading.as_ref().map(TradingState::mode).filter(|mode| !mode.accepts_orders()) {
+            if !orders.is_empty() {
+                debug!(strategy = name, mode = %mode, orders = orders.len(), "Trading mode accepts no orders, orders withheld");
+            }
+            return;
+        }
+        if !orders.is_empty() && !self.toggles.is_strategy_enabled(name) {
+            debug!(strategy = name, orders = orders.len(), "Strategy paused, orders withheld");
+            return;
+        }
+
+        for mut order in orders {
+            order.strategy = Some(name.to_string());
+            if !self.toggles.is_symbol_enabled(&order.symbol) {
+                debug!(strategy = name, symbol = %order.symbol, "Symbol paused, order withheld");
+                continue;
+            }
+            // Pull quotes into toxic flow; market orders still go out to
+            // let strategies reduce risk
+            if order.order_type == OrderType::Limit
+                && self.signals.toxicity.level(&order.venue, &order.symbol) == ToxicityLevel::Toxic
+            {
+                debug!(strategy = name, symbol = %order.symbol, "Order flow toxic, quote withheld");
+                TOXIC_QUOTES_WITHHELD.with_label_values(&[name, labels::symbol("toxic_quotes_withheld", &order.symbol)]).inc();
+                continue;
+            }
+            if let Some(audit) = &self.audit {
+                audit.record(AuditEvent::OrderRequest {
+                    strategy: Some(name.to_string()),
+                    order: order.clone(),
+                });
+            }
+            self.shadows.on_live_order(&order);
+            if let Err(e) = self.order_tx.try_send(order) {
+                warn!(strategy = name, error = %e, "Dropping strategy order");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::ToxicityConfig;
+    use crate::types::OrderSide;
+
+    /// Buys one unit at the bid on every quote
+    struct Joiner;
+
+    impl StrategyPlugin for Joiner {
+        fn name(&self) -> &str {
+            "joiner"
+        }
+
+        fn on_quote(&mut self, quote: &Quote) -> Vec<Order> {
+            vec![Order {
+                symbol: quote.symbol,
+                side: OrderSide::Buy,
+                quantity: 1.0,
+                price: quote.bid,
+                venue: quote.venue,
+                order_type: OrderType::Limit,
+                expire_after: None,
+                strategy: None,
+                bypass_kill_switch: false,
+            }]
+        }
+
+        fn on_fill(&mut self, _fill: &Fill) -> Vec<Order> {
+            Vec::new()
+        }
+    }
+
+    /// Sells one unit at the bid on every quote
+    struct Taker;
+
+    impl StrategyPlugin for Taker {
+        fn name(&self) -> &str {
+            "taker"
+        }
+
+        fn on_quote(&mut self, quote: &Quote) -> Vec<Order> {
+            vec![Order {
+                symbol: quote.symbol,
+                side: OrderSide::Sell,
+                quantity: 1.0,
+                price: quote.bid,
+                venue: quote.venue,
+                order_type: OrderType::Limit,
+                expire_after: None,
+                strategy: None,
+                bypass_kill_switch: false,
+            }]
+        }
+
+        fn on_fill(&mut self, _fill: &Fill) -> Vec<Order> {
+            Vec::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shadow_orders_simulated_beside_live_results() {
+        let (order_tx, mut order_rx) = mpsc::channel(8);
+        let mut strategy = Strategy {
+            books: Arc::new(RwLock::new(HashMap::new())),
+            order_tx,
+            plugins: Vec::new(),
+            audit: None,
+            toggles: Arc::new(TradingToggles::new()),
+            signals: Signals::default(),
+            trading: None,
+            shadows: Shadows::default(),
+            timer_rx: None,
+            fill_rx: None,
+            plugin_rx: None,
+        };
+        strategy.add_plugin(Box::new(Joiner));
+        assert!(!strategy.add_shadow(Box::new(Taker), Some("joiner".to_string())));
+
+        let quote = |bid: f64, timestamp: u64| Quote {
+            symbol: "BTCUSDT".into(),
+            bid,
+            ask: bid + 1.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "MOCK".into(),
+            timestamp,
+        };
+        strategy.on_quote(&quote(100.0, 1));
+        // Only the live strategy's order is sent
+        assert_eq!(order_rx.try_recv().unwrap().strategy.as_deref(), Some("joiner"));
+        assert!(order_rx.try_recv().is_err());
+        strategy.on_fill(&Fill {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".into(),
+            venue: "MOCK".into(),
+            strategy: "joiner".to_string(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price: 100.0,
+            timestamp: 1,
+            commission: None,
+        });
+        strategy.on_quote(&quote(90.0, 2));
+
+        let reports = strategy.shadows.reports();
+        let report = &reports[0];
+        // Short 2 from 95 and long 1 from 100, both marked at 90.5
+        assert_eq!(report.shadow, ShadowResults { orders: 2, fills: 2, notional: 190.0, pnl: 9.0 });
+        assert_eq!(report.actual, Some(ShadowResults { orders: 2, fills: 1, notional: 100.0, pnl: -9.5 }));
+        assert_eq!(report.recent_fills.len(), 2);
+        assert!(report.recent_orders.iter().all(|order| order.strategy.as_deref() == Some("taker")));
+    }
+
+    #[tokio::test]
+    async fn test_plugin_orders_are_routed() {
+        let (order_tx, mut order_rx) = mpsc::channel(8);
+        let mut strategy = Strategy {
+            books: Arc::new(RwLock::new(HashMap::new())),
+            order_tx,
+            plugins: Vec::new(),
+            audit: None,
+            toggles: Arc::new(TradingToggles::new()),
+            signals: Signals::default(),
+            trading: None,
+            shadows: Shadows::default(),
+            timer_rx: None,
+            fill_rx: None,
+            plugin_rx: None,
+        };
+        strategy.add_plugin(Box::new(Joiner));
+
+        let quote = Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 50000.0,
+            ask: 50001.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "MOCK".into(),
+            timestamp: 1,
+        };
+        strategy.on_quote(&quote);
+
+        let order = order_rx.try_recv().unwrap();
+        assert_eq!(order.price, 50000.0);
+        assert!(order_rx.try_recv().is_err());
+
+        // Paused strategies keep receiving quotes but send nothing
+        strategy.toggles.disable_strategy("joiner");
+        strategy.on_quote(&quote);
+        assert!(order_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_quotes_pulled_during_trade_burst() {
+        let (order_tx, mut order_rx) = mpsc::channel(8);
+        let signals = Signals::default();
+        signals.toxicity.configure(ToxicityConfig {
+            bucket_volume: HashMap::from([("BTCUSDT".to_string(), 100.0)]),
+            burst_trades: 2,
+            burst_window: Duration::from_millis(100),
+            ..Default::default()
+        });
+        let mut strategy = Strategy {
+            books: Arc::new(RwLock::new(HashMap::new())),
+            order_tx,
+            plugins: Vec::new(),
+            audit: None,
+            toggles: Arc::new(TradingToggles::new()),
+            signals,
+            trading: None,
+            shadows: Shadows::default(),
+            timer_rx: None,
+            fill_rx: None,
+            plugin_rx: None,
+        };
+        strategy.add_plugin(Box::new(Joiner));
+
+        for timestamp in [0, 10] {
+            strategy.on_trade(&Trade {
+                symbol: "BTCUSDT".into(),
+                venue: "MOCK".into(),
+                price: 50000.0,
+                quantity: 1.0,
+                side: OrderSide::Sell,
+                timestamp,
+            });
+        }
+
+        let mut quote = Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 50000.0,
+            ask: 50001.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "MOCK".into(),
+            timestamp: 50,
+        };
+        strategy.on_quote(&quote);
+        assert!(order_rx.try_recv().is_err());
+
+        // Quoting resumes once the burst has passed
+        quote.timestamp = 200;
+        strategy.on_quote(&quote);
+        assert!(order_rx.try_recv().is_ok());
+    }
+
+    /// Refreshes a bid on its requote timer
+    struct Requoter;
+
+    impl StrategyPlugin for Requoter {
+        fn name(&self) -> &str {
+            "requoter"
+        }
+
+        fn on_quote(&mut self, _quote: &Quote) -> Vec<Order> {
+            Vec::new()
+        }
+
+        fn on_fill(&mut self, _fill: &Fill) -> Vec<Order> {
+            Vec::new()
+        }
+
+        fn timers(&self) -> Vec<(String, Duration)> {
+            vec![("requote".to_string(), Duration::from_millis(50))]
+        }
+
+        fn on_timer(&mut self, timer: &str) -> Vec<Order> {
+            assert_eq!(timer, "requote");
+            vec![Order {
+                symbol: "BTCUSDT".into(),
+                side: OrderSide::Buy,
+                quantity: 1.0,
+                price: 49999.0,
+                venue: "MOCK".into(),
+                order_type: OrderType::Limit,
+                expire_after: Some(100),
+                strategy: None,
+                bypass_kill_switch: false,
+            }]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plugin_timers_route_orders() {
+        let (order_tx, mut order_rx) = mpsc::channel(8);
+        let mut strategy = Strategy {
+            books: Arc::new(RwLock::new(HashMap::new())),
+            order_tx,
+            plugins: Vec::new(),
+            audit: None,
+            toggles: Arc::new(TradingToggles::new()),
+            signals: Signals::default(),
+            trading: None,
+            shadows: Shadows::default(),
+            timer_rx: None,
+            fill_rx: None,
+            plugin_rx: None,
+        };
+        strategy.add_plugin(Box::new(Joiner));
+        strategy.add_plugin(Box::new(Requoter));
+
+        let timers = TimerService::new();
+        let start = std::time::Instant::now();
+        let (ids, mut fired) = strategy.schedule_timers(&timers);
+        assert_eq!(ids.len(), 1);
+
+        timers.fire_due(start + Duration::from_millis(60));
+        let timer = fired.try_recv().unwrap();
+        assert_eq!(timer, StrategyTimer { plugin: "requoter".to_string(), timer: "requote".to_string() });
+        strategy.on_timer(&timer);
+
+        let order = order_rx.try_recv().unwrap();
+        assert_eq!(order.strategy.as_deref(), Some("requoter"));
+        assert!(order_rx.try_recv().is_err());
+    }
+}
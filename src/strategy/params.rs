@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use warp::Filter;
+
+use crate::error::HftError;
+
+#[derive(Debug, Default)]
+struct Entry {
+    version: u64,
+    params: Value,
+}
+
+/// Strategy parameters by strategy name, changeable while strategies run.
+///
+/// Each strategy's parameters are a JSON object carrying a version that
+/// bumps on every change, so strategies can cheaply check for updates on
+/// their hot path. Clones share the same store.
+#[derive(Debug, Clone, Default)]
+pub struct ParameterStore {
+    entries: Arc<RwLock<HashMap<String, Entry>>>,
+}
+
+impl ParameterStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a JSON file mapping strategy names to their parameters
+    pub fn load(path: &Path) -> Result<Self, HftError> {
+        let json = std::fs::read(path)?;
+        let strategies: HashMap<String, Value> = serde_json::from_slice(&json)
+            .map_err(|e| HftError::Config(format!("Invalid strategy parameters {}: {}", path.display(), e)))?;
+
+        let store = Self::new();
+        for (strategy, params) in strategies {
+            store.set(&strategy, params);
+        }
+        Ok(store)
+    }
+
+    /// Replace a strategy's parameters; returns the new version
+    pub fn set(&self, strategy: &str, params: Value) -> u64 {
+        let mut entries = self.entries.write().unwrap();
+        let entry = entries.entry(strategy.to_string()).or_default();
+        entry.version += 1;
+        entry.params = params;
+        entry.version
+    }
+
+    /// Current version of a strategy's parameters, 0 when none are set
+    pub fn version(&self, strategy: &str) -> u64 {
+        self.entries.read().unwrap().get(strategy).map_or(0, |e| e.version)
+    }
+
+    pub fn get(&self, strategy: &str) -> Option<Value> {
+        self.entries.read().unwrap().get(strategy).map(|e| e.params.clone())
+    }
+
+    /// Version and parameters of `strategy`, deserialized as `T`
+    pub fn typed<T: DeserializeOwned>(&self, strategy: &str) -> Option<(u64, Result<T, serde_json::Error>)> {
+        let entries = self.entries.read().unwrap();
+        let entry = entries.get(strategy)?;
+        Some((entry.version, T::deserialize(&entry.params)))
+    }
+
+    fn all(&self) -> HashMap<String, Value> {
+        self.entries.read().unwrap()
+            .iter()
+            .map(|(name, e)| (name.clone(), e.params.clone()))
+            .collect()
+    }
+}
+
+/// Admin endpoints:
+/// - `GET /admin/params` lists parameters by strategy
+/// - `PUT /admin/params/{strategy}` replaces a strategy's parameters with
+///   the JSON body
+pub fn routes(
+    store: ParameterStore,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let listed = store.clone();
+    let list = warp::path!("admin" / "params")
+        .and(warp::get())
+        .map(move || warp::reply::json(&listed.all()));
+
+    let change = warp::path!("admin" / "params" / String)
+        .and(warp::put())
+        .and(warp::body::json())
+        .map(move |strategy: String, params: Value| {
+            let version = store.set(&strategy, params);
+            warp::reply::json(&serde_json::json!({ "strategy": strategy, "version": version }))
+        });
+
+    list.or(change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_params_updated_over_admin_route() {
+        let store = ParameterStore::new();
+        assert_eq!(store.version("mm"), 0);
+        store.set("mm", serde_json::json!({ "base_spread_bps": 10.0 }));
+        let api = routes(store.clone());
+
+        let response = warp::test::request()
+            .method("PUT")
+            .path("/admin/params/mm")
+            .json(&serde_json::json!({ "base_spread_bps": 4.0 }))
+            .reply(&api)
+            .await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(store.version("mm"), 2);
+        assert_eq!(store.get("mm").unwrap()["base_spread_bps"], 4.0);
+
+        let response = warp::test::request().path("/admin/params").reply(&api).await;
+        let params: Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(params["mm"]["base_spread_bps"], 4.0);
+    }
+}
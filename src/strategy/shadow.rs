@@ -0,0 +1,317 @@
+//! Strategies run in shadow for validation before promotion.
+//!
+//! A shadow strategy sees the same quotes, trades, book updates and timers
+//! as live strategies, but its orders never leave the engine: they fill in
+//! a [`Backtest`] stepped with the live quotes, by default against the top
+//! of book. Paired with the live strategy it would replace, the live
+//! strategy's sent orders and fills from the same moment on are tracked
+//! alongside and marked at the same prices, so the two compare like for
+//! like.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::Utc;
+use serde::Serialize;
+use tracing::warn;
+use warp::Filter;
+
+use crate::backtest::Backtest;
+use crate::feed::BookUpdate;
+use crate::risk::Position;
+use crate::signals::Signals;
+use crate::types::{Fill, Order, Quote, Trade};
+use super::StrategyPlugin;
+
+/// Recent orders and fills kept per shadow strategy
+const JOURNAL_LEN: usize = 50;
+
+/// A shadow strategy to run and the live strategy it is compared with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowSpec {
+    pub name: String,
+    pub live: Option<String>,
+}
+
+impl ShadowSpec {
+    /// Market makers to shadow from `HFT_SHADOW_MARKET_MAKERS`, comma
+    /// separated `NAME[:LIVE]`
+    pub fn from_env() -> Vec<Self> {
+        std::env::var("HFT_SHADOW_MARKET_MAKERS").map(|spec| Self::parse(&spec)).unwrap_or_default()
+    }
+
+    fn parse(spec: &str) -> Vec<Self> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let (name, live) = match entry.split_once(':') {
+                    Some((name, live)) => (name.trim(), Some(live.trim())),
+                    None => (entry, None),
+                };
+                if name.is_empty() || live == Some("") {
+                    warn!(entry, "Ignoring malformed HFT_SHADOW_MARKET_MAKERS entry");
+                    return None;
+                }
+                Some(Self { name: name.to_string(), live: live.map(str::to_string) })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Journal {
+    orders: VecDeque<Order>,
+    fills: VecDeque<Fill>,
+}
+
+impl Journal {
+    fn push<T>(queue: &mut VecDeque<T>, item: T) {
+        if queue.len() == JOURNAL_LEN {
+            queue.pop_front();
+        }
+        queue.push_back(item);
+    }
+}
+
+/// Journals the orders a shadow plugin would have sent and the fills it
+/// was told of
+struct Recorder {
+    plugin: Box<dyn StrategyPlugin>,
+    journal: Arc<Mutex<Journal>>,
+}
+
+impl Recorder {
+    fn record(&self, mut orders: Vec<Order>) -> Vec<Order> {
+        let mut journal = self.journal.lock().unwrap();
+        for order in &mut orders {
+            order.strategy = Some(self.plugin.name().to_string());
+            Journal::push(&mut journal.orders, order.clone());
+        }
+        orders
+    }
+}
+
+impl StrategyPlugin for Recorder {
+    fn name(&self) -> &str {
+        self.plugin.name()
+    }
+
+    fn on_quote(&mut self, quote: &Quote) -> Vec<Order> {
+        let orders = self.plugin.on_quote(quote);
+        self.record(orders)
+    }
+
+    fn on_fill(&mut self, fill: &Fill) -> Vec<Order> {
+        Journal::push(&mut self.journal.lock().unwrap().fills, fill.clone());
+        let orders = self.plugin.on_fill(fill);
+        self.record(orders)
+    }
+
+    fn attach_signals(&mut self, signals: &Signals) {
+        self.plugin.attach_signals(signals);
+    }
+
+    fn timers(&self) -> Vec<(String, Duration)> {
+        self.plugin.timers()
+    }
+
+    fn on_timer(&mut self, timer: &str) -> Vec<Order> {
+        let orders = self.plugin.on_timer(timer);
+        self.record(orders)
+    }
+}
+
+/// Orders, fills and PnL before fees, from when the shadow was added
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ShadowResults {
+    pub orders: usize,
+    pub fills: usize,
+    /// Filled quantity times price, summed over all fills
+    pub notional: f64,
+    /// Realized plus unrealized, marked at the latest mid
+    pub pnl: f64,
+}
+
+/// A shadow strategy's results next to its live strategy's
+#[derive(Debug, Clone, Serialize)]
+pub struct ShadowReport {
+    pub strategy: String,
+    pub live: Option<String>,
+    /// When the shadow was added, in milliseconds since the epoch
+    pub since: i64,
+    pub shadow: ShadowResults,
+    /// Fees the fill model charged the shadow, in the quote currency
+    pub shadow_fees: f64,
+    /// The live strategy's results over the same time
+    pub actual: Option<ShadowResults>,
+    /// Most recent last
+    pub recent_orders: Vec<Order>,
+    pub recent_fills: Vec<Fill>,
+}
+
+struct ShadowStrategy {
+    name: String,
+    live: Option<String>,
+    since: i64,
+    engine: Backtest,
+    journal: Arc<Mutex<Journal>>,
+    /// The live strategy's results and positions by venue and symbol
+    actual: ShadowResults,
+    live_positions: BTreeMap<(String, String), Position>,
+}
+
+impl ShadowStrategy {
+    fn report(&self) -> ShadowReport {
+        let report = self.engine.report();
+        let mark = |venue: &str, symbol: &str, position: &Position| {
+            report.marks.get(&(venue.to_string(), symbol.to_string())).copied().unwrap_or(position.avg_price)
+        };
+        let actual = self.live.as_ref().map(|_| ShadowResults {
+            pnl: self.live_positions.iter()
+                .map(|((venue, symbol), position)| position.realized_pnl + position.unrealized_pnl(mark(venue, symbol, position)))
+                .sum(),
+            ..self.actual.clone()
+        });
+        let journal = self.journal.lock().unwrap();
+        ShadowReport {
+            strategy: self.name.clone(),
+            live: self.live.clone(),
+            since: self.since,
+            shadow: ShadowResults {
+                orders: report.orders,
+                fills: report.fills,
+                notional: report.notional,
+                pnl: report.total_pnl(),
+            },
+            shadow_fees: report.fees,
+            actual,
+            recent_orders: journal.orders.iter().cloned().collect(),
+            recent_fills: journal.fills.iter().cloned().collect(),
+        }
+    }
+}
+
+/// The shadow strategies loaded into the strategy runner, shared with the
+/// admin API
+#[derive(Clone, Default)]
+pub struct Shadows {
+    strategies: Arc<Mutex<Vec<ShadowStrategy>>>,
+}
+
+impl Shadows {
+    /// Run `plugin` in shadow, compared with the live strategy named
+    /// `live`, replacing any shadow with the same name. Returns whether one
+    /// was replaced.
+    pub fn add(&self, plugin: Box<dyn StrategyPlugin>, live: Option<String>) -> bool {
+        let name = plugin.name().to_string();
+        let journal = Arc::new(Mutex::new(Journal::default()));
+        let recorder = Recorder { plugin, journal: Arc::clone(&journal) };
+        let shadow = ShadowStrategy {
+            name: name.clone(),
+            live,
+            since: Utc::now().timestamp_millis(),
+            engine: Backtest::new().with_strategy(Box::new(recorder)),
+            journal,
+            actual: ShadowResults::default(),
+            live_positions: BTreeMap::new(),
+        };
+        let mut strategies = self.strategies.lock().unwrap();
+        match strategies.iter().position(|s| s.name == name) {
+            Some(i) => {
+                strategies[i] = shadow;
+                true
+            }
+            None => {
+                strategies.push(shadow);
+                false
+            }
+        }
+    }
+
+    pub fn on_quote(&self, quote: &Quote) {
+        for shadow in self.strategies.lock().unwrap().iter_mut() {
+            shadow.engine.on_quote(quote.clone());
+        }
+    }
+
+    pub fn on_trade(&self, trade: &Trade) {
+        for shadow in self.strategies.lock().unwrap().iter_mut() {
+            shadow.engine.on_trade(trade);
+        }
+    }
+
+    pub fn on_book_update(&self, update: &BookUpdate) {
+        for shadow in self.strategies.lock().unwrap().iter_mut() {
+            shadow.engine.on_book_update(update);
+        }
+    }
+
+    /// Fire a shadow strategy's timer; false when no shadow is named
+    /// `plugin`
+    pub fn on_timer(&self, plugin: &str, timer: &str) -> bool {
+        let mut strategies = self.strategies.lock().unwrap();
+        match strategies.iter_mut().find(|shadow| shadow.name == plugin) {
+            Some(shadow) => {
+                shadow.engine.on_timer(plugin, timer);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Every shadow strategy's timers as `(plugin, timer, period)`
+    pub fn timers(&self) -> Vec<(String, String, Duration)> {
+        self.strategies.lock().unwrap().iter().flat_map(|shadow| shadow.engine.timers()).collect()
+    }
+
+    /// A live strategy sent `order`
+    pub fn on_live_order(&self, order: &Order) {
+        self.each_paired(order.strategy_label(), |shadow| shadow.actual.orders += 1);
+    }
+
+    /// A live strategy's order filled
+    pub fn on_live_fill(&self, fill: &Fill) {
+        self.each_paired(&fill.strategy, |shadow| {
+            shadow.actual.fills += 1;
+            shadow.actual.notional += fill.quantity * fill.price;
+            shadow.live_positions
+                .entry((fill.venue.to_string(), fill.symbol.to_string()))
+                .or_default()
+                .apply_fill(fill.side, fill.quantity, fill.price);
+        });
+    }
+
+    fn each_paired(&self, live: &str, f: impl FnMut(&mut ShadowStrategy)) {
+        self.strategies.lock().unwrap().iter_mut()
+            .filter(|shadow| shadow.live.as_deref() == Some(live))
+            .for_each(f);
+    }
+
+    pub fn reports(&self) -> Vec<ShadowReport> {
+        self.strategies.lock().unwrap().iter().map(ShadowStrategy::report).collect()
+    }
+}
+
+/// `GET /admin/shadows` for each shadow strategy's results next to its
+/// live strategy's
+pub fn routes(
+    shadows: Shadows,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("admin" / "shadows")
+        .and(warp::get())
+        .map(move || warp::reply::json(&shadows.reports()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shadow_specs() {
+        assert_eq!(ShadowSpec::parse("mm_v2:market_maker, mm_wide,:mm,mm_bad:"), vec![
+            ShadowSpec { name: "mm_v2".to_string(), live: Some("market_maker".to_string()) },
+            ShadowSpec { name: "mm_wide".to_string(), live: None },
+        ]);
+    }
+}
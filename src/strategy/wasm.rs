@@ -0,0 +1,242 @@
+//! Strategies compiled to WebAssembly.
+//!
+//! Host ABI, version 1. Payloads are JSON encodings of the engine types.
+//!
+//! The guest exports:
+//! - `memory`
+//! - `hft_abi_version() -> i32`, returning [`ABI_VERSION`]
+//! - `alloc(len: i32) -> i32`, a buffer the host writes the next payload to;
+//!   the guest may reuse it once the callback returns
+//! - `on_quote(ptr: i32, len: i32)` with a `Quote`
+//! - `on_fill(ptr: i32, len: i32)` with a `Fill`
+//!
+//! The host provides, in module `hft`:
+//! - `submit_order(ptr: i32, len: i32)` with an `Order`
+//! - `log(ptr: i32, len: i32)` with a UTF-8 message
+//!
+//! Each callback runs with a fixed fuel budget, so a runaway guest traps
+//! instead of stalling the engine.
+
+use std::path::Path;
+use serde::Serialize;
+use tracing::{info, warn};
+use wasmtime::{Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, Store, TypedFunc};
+
+use crate::error::HftError;
+use crate::types::{Fill, Order, Quote};
+use super::StrategyPlugin;
+
+pub const ABI_VERSION: i32 = 1;
+
+/// Instructions a guest may execute per callback
+const DEFAULT_FUEL: u64 = 10_000_000;
+
+#[derive(Default)]
+struct HostState {
+    name: String,
+    orders: Vec<Order>,
+}
+
+fn guest_bytes(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> Option<Vec<u8>> {
+    let Some(Extern::Memory(memory)) = caller.get_export("memory") else {
+        return None;
+    };
+    let start = ptr as u32 as usize;
+    memory.data(&*caller).get(start..start + len as u32 as usize).map(<[u8]>::to_vec)
+}
+
+fn wasm_error(context: &str, e: wasmtime::Error) -> HftError {
+    HftError::Config(format!("{}: {:#}", context, e))
+}
+
+pub struct WasmStrategy {
+    name: String,
+    store: Store<HostState>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    on_quote: TypedFunc<(i32, i32), ()>,
+    on_fill: TypedFunc<(i32, i32), ()>,
+    fuel: u64,
+}
+
+impl WasmStrategy {
+    /// Load a `.wasm` (or `.wat`) module; the strategy is named after the file
+    pub fn load(path: &Path) -> Result<Self, HftError> {
+        let name = path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "wasm".to_string());
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| wasm_error("Failed to start WASM engine", e))?;
+        let module = Module::from_file(&engine, path)
+            .map_err(|e| wasm_error(&format!("Failed to load {}", path.display()), e))?;
+
+        Self::instantiate(name, &engine, &module)
+    }
+
+    fn instantiate(name: String, engine: &Engine, module: &Module) -> Result<Self, HftError> {
+        let mut linker = Linker::new(engine);
+        linker
+            .func_wrap("hft", "submit_order", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                let Some(bytes) = guest_bytes(&mut caller, ptr, len) else {
+                    warn!(strategy = %caller.data().name, "submit_order outside guest memory");
+                    return;
+                };
+                match serde_json::from_slice::<Order>(&bytes) {
+                    Ok(order) => caller.data_mut().orders.push(order),
+                    Err(e) => warn!(strategy = %caller.data().name, error = %e, "Invalid order from strategy"),
+                }
+            })
+            .map_err(|e| wasm_error("Failed to link submit_order", e))?;
+        linker
+            .func_wrap("hft", "log", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                if let Some(bytes) = guest_bytes(&mut caller, ptr, len) {
+                    info!(strategy = %caller.data().name, "{}", String::from_utf8_lossy(&bytes));
+                }
+            })
+            .map_err(|e| wasm_error("Failed to link log", e))?;
+
+        let mut store = Store::new(engine, HostState { name: name.clone(), orders: Vec::new() });
+        store.set_fuel(DEFAULT_FUEL).map_err(|e| wasm_error("Failed to set fuel", e))?;
+        let instance: Instance = linker.instantiate(&mut store, module)
+            .map_err(|e| wasm_error(&format!("Failed to instantiate {}", name), e))?;
+
+        let version = instance.get_typed_func::<(), i32>(&mut store, "hft_abi_version")
+            .and_then(|f| f.call(&mut store, ()))
+            .map_err(|e| wasm_error(&format!("{} does not export hft_abi_version", name), e))?;
+        if version != ABI_VERSION {
+            return Err(HftError::Config(format!(
+                "{} targets strategy ABI {} (host is {})",
+                name, version, ABI_VERSION
+            )));
+        }
+
+        let memory = instance.get_memory(&mut store, "memory")
+            .ok_or_else(|| HftError::Config(format!("{} does not export memory", name)))?;
+        let export = |store: &mut Store<HostState>, func: &str| {
+            instance.get_typed_func::<(i32, i32), ()>(store, func)
+                .map_err(|e| wasm_error(&format!("{} does not export {}", name, func), e))
+        };
+        let on_quote = export(&mut store, "on_quote")?;
+        let on_fill = export(&mut store, "on_fill")?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")
+            .map_err(|e| wasm_error(&format!("{} does not export alloc", name), e))?;
+
+        Ok(Self { name, store, memory, alloc, on_quote, on_fill, fuel: DEFAULT_FUEL })
+    }
+
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    fn call<T: Serialize>(&mut self, func: TypedFunc<(i32, i32), ()>, payload: &T) -> Result<Vec<Order>, HftError> {
+        let bytes = serde_json::to_vec(payload)
+            .map_err(|e| HftError::Serialization(format!("Failed to encode strategy payload: {}", e)))?;
+        let len = bytes.len() as i32;
+
+        self.store.set_fuel(self.fuel).map_err(|e| wasm_error("Failed to set fuel", e))?;
+        let ptr = self.alloc.call(&mut self.store, len).map_err(|e| wasm_error("alloc trapped", e))?;
+        self.memory.write(&mut self.store, ptr as u32 as usize, &bytes)
+            .map_err(|e| HftError::Config(format!("alloc returned an invalid buffer: {}", e)))?;
+
+        let result = func.call(&mut self.store, (ptr, len));
+        let orders = std::mem::take(&mut self.store.data_mut().orders);
+        result.map_err(|e| wasm_error("Strategy trapped", e))?;
+        Ok(orders)
+    }
+
+    fn dispatch<T: Serialize>(&mut self, func: TypedFunc<(i32, i32), ()>, payload: &T) -> Vec<Order> {
+        self.call(func, payload).unwrap_or_else(|e| {
+            warn!(strategy = %self.name, error = %e, "WASM strategy callback failed");
+            Vec::new()
+        })
+    }
+}
+
+impl StrategyPlugin for WasmStrategy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_quote(&mut self, quote: &Quote) -> Vec<Order> {
+        let func = self.on_quote.clone();
+        self.dispatch(func, quote)
+    }
+
+    fn on_fill(&mut self, fill: &Fill) -> Vec<Order> {
+        let func = self.on_fill.clone();
+        self.dispatch(func, fill)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderSide, OrderType};
+
+    fn guest(on_quote_body: &str) -> WasmStrategy {
+        let order = serde_json::to_string(&Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price: 50000.0,
+            venue: "MOCK".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        }).unwrap();
+
+        let wat = format!(r#"
+            (module
+              (import "hft" "submit_order" (func $submit (param i32 i32)))
+              (memory (export "memory") 1)
+              (data (i32.const 0) "{data}")
+              (func (export "hft_abi_version") (result i32) i32.const 1)
+              (func (export "alloc") (param i32) (result i32) i32.const 4096)
+              (func (export "on_quote") (param i32 i32) {body})
+              (func (export "on_fill") (param i32 i32)))
+            "#,
+            data = order.replace('"', "\\22"),
+            body = on_quote_body.replace("LEN", &order.len().to_string()),
+        );
+
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).unwrap();
+        let module = Module::new(&engine, wat).unwrap();
+        WasmStrategy::instantiate("test".to_string(), &engine, &module).unwrap()
+    }
+
+    fn quote() -> Quote {
+        Quote {
+            symbol: "BTCUSDT".into(),
+            bid: 50000.0,
+            ask: 50001.0,
+            bid_size: 1.0,
+            ask_size: 1.0,
+            venue: "MOCK".into(),
+            timestamp: 1,
+        }
+    }
+
+    #[test]
+    fn test_guest_submits_order() {
+        let mut strategy = guest("i32.const 0 i32.const LEN call $submit");
+        let orders = strategy.on_quote(&quote());
+
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].symbol, "BTCUSDT");
+        assert_eq!(orders[0].side, OrderSide::Buy);
+    }
+
+    #[test]
+    fn test_runaway_guest_runs_out_of_fuel() {
+        let mut strategy = guest("(loop br 0)").with_fuel(10_000);
+        assert!(strategy.on_quote(&quote()).is_empty());
+
+        // The instance stays usable after a trap
+        assert!(strategy.on_quote(&quote()).is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+//! Terminal dashboard for operators without Grafana.
+//!
+//! Shows each symbol's best bid and offer, open orders, positions with
+//! their PnL, venue connectivity and the alerts raised from the event bus,
+//! redrawn a few times a second until `q`, `Esc` or `Ctrl+C`.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+use chrono::{TimeZone, Utc};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Row, Table};
+use ratatui::Frame;
+use tokio::sync::broadcast;
+
+use crate::alerts::{Alert, AlertConfig, AlertManager, Severity};
+use crate::execution::OpenOrder;
+use crate::health::Probe;
+use crate::risk::{Position, PositionKey};
+use crate::services::ServiceHandles;
+
+/// How often the dashboard is redrawn
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Alerts kept on screen, newest first
+const MAX_ALERTS: usize = 50;
+
+/// A price level as price and size
+type Level = (f64, f64);
+
+/// What one redraw shows
+#[derive(Debug, Default)]
+struct View {
+    /// Symbol with best bid and offer, if quoted
+    quotes: Vec<(String, Option<Level>, Option<Level>)>,
+    orders: Vec<OpenOrder>,
+    positions: Vec<(PositionKey, Position, f64)>,
+    /// Venue, whether connected and why not
+    venues: Vec<(String, bool, Option<String>)>,
+    alerts: VecDeque<Alert>,
+}
+
+impl View {
+    async fn refresh(&mut self, handles: &ServiceHandles) {
+        self.quotes = handles.books.read().await
+            .iter()
+            .map(|(symbol, book)| (symbol.to_string(), book.best_bid(), book.best_ask()))
+            .collect();
+        self.quotes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        self.orders = handles.orders.open_orders().await;
+        self.orders.sort_by_key(|open| std::cmp::Reverse(open.created_at));
+
+        self.positions = handles.risk.position_pnl().await
+            .into_iter()
+            .filter(|(_, position, _)| !position.is_flat())
+            .collect();
+        self.positions.sort_by(|a, b| a.0.cmp(&b.0));
+
+        self.venues = handles.health.report(Probe::Readiness).components
+            .into_iter()
+            .filter_map(|(name, status)| Some((name.strip_prefix("venue:")?.to_string(), status.healthy, status.detail)))
+            .collect();
+    }
+
+    /// Add the alerts raised by `events` since the last redraw
+    fn take_alerts(&mut self, events: &mut broadcast::Receiver<crate::events::EngineEvent>, classifier: &mut AlertManager) {
+        loop {
+            match events.try_recv() {
+                Ok(event) => {
+                    if let Some(alert) = classifier.classify(&event) {
+                        self.alerts.push_front(alert);
+                        self.alerts.truncate(MAX_ALERTS);
+                    }
+                }
+                Err(broadcast::error::TryRecvError::Lagged(_)) => {}
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+fn price(level: Option<Level>) -> String {
+    level.map(|(price, size)| format!("{} x {}", price, size)).unwrap_or_else(|| "-".to_string())
+}
+
+fn header(cells: &[&'static str]) -> Row<'static> {
+    Row::new(cells.to_vec()).style(Style::default().add_modifier(Modifier::BOLD))
+}
+
+fn render(frame: &mut Frame, view: &View) {
+    let [top, middle, bottom] = Layout::vertical([
+        Constraint::Percentage(30),
+        Constraint::Percentage(40),
+        Constraint::Percentage(30),
+    ]).areas(frame.area());
+    let [quotes_area, venues_area] = Layout::horizontal([Constraint::Percentage(70), Constraint::Percentage(30)]).areas(top);
+    let [orders_area, positions_area] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(middle);
+
+    let quotes = view.quotes.iter().map(|(symbol, bid, ask)| {
+        Row::new(vec![symbol.clone(), price(*bid), price(*ask)])
+    });
+    frame.render_widget(
+        Table::new(quotes, [Constraint::Percentage(30), Constraint::Percentage(35), Constraint::Percentage(35)])
+            .header(header(&["Symbol", "Bid", "Ask"]))
+            .block(Block::bordered().title("Best bid/offer")),
+        quotes_area,
+    );
+
+    let venues = view.venues.iter().map(|(venue, connected, detail)| {
+        let (status, color) = match connected {
+            true => ("connected".to_string(), Color::Green),
+            false => (detail.clone().unwrap_or_else(|| "down".to_string()), Color::Red),
+        };
+        Row::new(vec![venue.clone(), status]).style(Style::default().fg(color))
+    });
+    frame.render_widget(
+        Table::new(venues, [Constraint::Percentage(50), Constraint::Percentage(50)])
+            .header(header(&["Venue", "Status"]))
+            .block(Block::bordered().title("Venues")),
+        venues_area,
+    );
+
+    let orders = view.orders.iter().map(|open| {
+        Row::new(vec![
+            open.order_id.clone(),
+            open.order.venue.to_string(),
+            open.order.symbol.to_string(),
+            format!("{:?}", open.order.side),
+            format!("{} @ {}", open.order.quantity, open.order.price),
+            format!("{:?}", open.status),
+        ])
+    });
+    frame.render_widget(
+        Table::new(orders, [Constraint::Ratio(1, 6); 6])
+            .header(header(&["ID", "Venue", "Symbol", "Side", "Qty @ Price", "Status"]))
+            .block(Block::bordered().title(format!("Open orders ({})", view.orders.len()))),
+        orders_area,
+    );
+
+    let total: f64 = view.positions.iter().map(|(_, _, pnl)| pnl).sum();
+    let positions = view.positions.iter().map(|(key, position, pnl)| {
+        let color = if *pnl < 0.0 { Color::Red } else { Color::Green };
+        Row::new(vec![
+            key.strategy.clone(),
+            key.venue.clone(),
+            key.symbol.clone(),
+            format!("{}", position.quantity),
+            format!("{:.2}", position.avg_price),
+            format!("{:.2}", pnl),
+        ]).style(Style::default().fg(color))
+    });
+    frame.render_widget(
+        Table::new(positions, [Constraint::Ratio(1, 6); 6])
+            .header(header(&["Strategy", "Venue", "Symbol", "Qty", "Avg", "PnL"]))
+            .block(Block::bordered().title(format!("Positions (PnL {:.2})", total))),
+        positions_area,
+    );
+
+    let alerts = view.alerts.iter().map(|alert| {
+        let color = match alert.severity {
+            Severity::Critical => Color::Red,
+            Severity::Warning => Color::Yellow,
+            Severity::Info => Color::Reset,
+        };
+        let time = Utc.timestamp_millis_opt(alert.timestamp as i64)
+            .single()
+            .map(|at| at.format("%H:%M:%S").to_string())
+            .unwrap_or_default();
+        Row::new(vec![time, alert.severity.to_string(), alert.title.clone(), alert.message.clone()])
+            .style(Style::default().fg(color))
+    });
+    frame.render_widget(
+        Table::new(alerts, [Constraint::Length(8), Constraint::Length(8), Constraint::Percentage(40), Constraint::Percentage(60)])
+            .header(header(&["Time", "Severity", "Alert", "Detail"]))
+            .block(Block::bordered().title("Alerts (q to quit)")),
+        bottom,
+    );
+}
+
+/// Whether a pending key press asks to quit, without blocking
+fn quit_requested() -> std::io::Result<bool> {
+    while event::poll(Duration::ZERO)? {
+        if let Event::Key(key) = event::read()? {
+            let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if key.kind == KeyEventKind::Press && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Take over the terminal and show the dashboard until the operator quits
+pub async fn run(handles: ServiceHandles) -> std::io::Result<()> {
+    let mut events = handles.events.subscribe();
+    let mut classifier = AlertManager::new(AlertConfig::default());
+    let mut view = View::default();
+    let mut terminal = ratatui::init();
+    let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+
+    let result = loop {
+        interval.tick().await;
+        view.take_alerts(&mut events, &mut classifier);
+        view.refresh(&handles).await;
+        if let Err(e) = terminal.draw(|frame| render(frame, &view)) {
+            break Err(e);
+        }
+        match quit_requested() {
+            Ok(false) => {}
+            Ok(true) => break Ok(()),
+            Err(e) => break Err(e),
+        }
+    };
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use crate::events::EngineEvent;
+    use crate::execution::OrderStatus;
+    use crate::types::{Order, OrderSide, OrderType};
+
+    #[test]
+    fn test_render_shows_each_panel() {
+        let (tx, mut events) = broadcast::channel(8);
+        tx.send(EngineEvent::KillSwitchEngaged { reason: "daily loss".to_string() }).unwrap();
+        let mut view = View {
+            quotes: vec![("BTCUSDT".to_string(), Some((50000.0, 1.0)), None)],
+            orders: vec![OpenOrder {
+                order_id: "42".to_string(),
+                order: Order {
+                    symbol: "BTCUSDT".into(),
+                    side: OrderSide::Buy,
+                    quantity: 1.0,
+                    price: 49990.0,
+                    venue: "MOCK".into(),
+                    order_type: OrderType::Limit,
+                    expire_after: None,
+                    strategy: None,
+                    bypass_kill_switch: false,
+                },
+                status: OrderStatus::New,
+                filled_quantity: 0.0,
+                created_at: 0,
+            }],
+            positions: vec![(
+                PositionKey { strategy: "mm".to_string(), venue: "MOCK".to_string(), symbol: "BTCUSDT".to_string() },
+                Position { quantity: 2.0, avg_price: 100.0, ..Position::default() },
+                -12.5,
+            )],
+            venues: vec![("MOCK".to_string(), false, Some("not connected".to_string()))],
+            alerts: VecDeque::new(),
+        };
+        view.take_alerts(&mut events, &mut AlertManager::new(AlertConfig::default()));
+
+        let mut terminal = Terminal::new(TestBackend::new(160, 40)).unwrap();
+        terminal.draw(|frame| render(frame, &view)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        for expected in ["50000 x 1", "not connected", "49990", "PnL -12.50", "Kill switch"] {
+            assert!(screen.contains(expected), "{} missing from dashboard", expected);
+        }
+    }
+}
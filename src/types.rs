@@ -1,34 +1,312 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashSet;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{OnceLock, RwLock};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The process-wide copy of `s`. Venue and symbol names are a small set, so
+/// each is allocated once and kept for the life of the process.
+fn intern(s: &str) -> &'static str {
+    static INTERNED: OnceLock<RwLock<HashSet<&'static str>>> = OnceLock::new();
+    let interned = INTERNED.get_or_init(Default::default);
+    if let Some(existing) = interned.read().unwrap().get(s) {
+        return existing;
+    }
+    let mut interned = interned.write().unwrap();
+    if let Some(existing) = interned.get(s) {
+        return existing;
+    }
+    let leaked: &'static str = Box::leak(s.to_owned().into_boxed_str());
+    interned.insert(leaked);
+    leaked
+}
+
+/// An interned name that is `Copy` and compares by pointer rather than by
+/// its characters. It hashes like its `str`, so maps keyed by it can be
+/// looked up with a plain `&str`.
+macro_rules! interned_id {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy)]
+        pub struct $name(&'static str);
+
+        impl $name {
+            pub fn new(name: &str) -> Self {
+                Self(intern(name))
+            }
+
+            pub fn as_str(&self) -> &'static str {
+                self.0
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new("")
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                std::ptr::eq(self.0, other.0)
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.hash(state);
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.cmp(other.0)
+            }
+        }
+
+        impl std::ops::Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                self.0
+            }
+        }
+
+        impl std::borrow::Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(self.0)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Debug::fmt(self.0, f)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(name: &str) -> Self {
+                Self::new(name)
+            }
+        }
+
+        impl From<&String> for $name {
+            fn from(name: &String) -> Self {
+                Self::new(name)
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(name: String) -> Self {
+                Self::new(&name)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> Self {
+                id.0.to_string()
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<$name> for str {
+            fn eq(&self, other: &$name) -> bool {
+                self == other.0
+            }
+        }
+
+        impl PartialEq<$name> for &str {
+            fn eq(&self, other: &$name) -> bool {
+                *self == other.0
+            }
+        }
+
+        impl PartialEq<$name> for String {
+            fn eq(&self, other: &$name) -> bool {
+                self == other.0
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Ok(Self::new(&String::deserialize(deserializer)?))
+            }
+        }
+    };
+}
+
+interned_id! {
+    /// A venue's name, e.g. `BINANCE`
+    VenueId
+}
+
+interned_id! {
+    /// An instrument's symbol, canonical or as a venue calls it
+    Symbol
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Quote {
-    pub symbol: String,
+    pub symbol: Symbol,
     pub bid: f64,
     pub ask: f64,
     pub bid_size: f64,
     pub ask_size: f64,
-    pub venue: String,
+    pub venue: VenueId,
+    pub timestamp: u64,
+}
+
+/// A public trade printed on a venue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    pub symbol: Symbol,
+    pub venue: VenueId,
+    pub price: f64,
+    pub quantity: f64,
+    /// Aggressor side
+    pub side: OrderSide,
     pub timestamp: u64,
 }
 
+/// OHLCV bar for one interval, opened at `open_time` (milliseconds)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    pub open_time: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
-    pub symbol: String,
+    pub symbol: Symbol,
     pub side: OrderSide,
     pub quantity: f64,
     pub price: f64,
-    pub venue: String,
+    pub venue: VenueId,
     pub order_type: OrderType,
+    /// Milliseconds a resting order may live before it is cancelled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expire_after: Option<u64>,
+    /// Strategy that placed the order, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    /// Sent while the kill switch is engaged, as orders flattening
+    /// positions are; refused if it would grow a position
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bypass_kill_switch: bool,
+}
+
+impl Order {
+    /// Strategy name to label metrics with
+    pub fn strategy_label(&self) -> &str {
+        self.strategy.as_deref().unwrap_or("none")
+    }
+
+    /// Whether the order gateway's report on `reported` is about this
+    /// order; the venue may differ when the gateway rerouted it
+    pub fn is_reported_as(&self, reported: &Order) -> bool {
+        self.symbol == reported.symbol
+            && self.side == reported.side
+            && self.quantity == reported.quantity
+            && self.price == reported.price
+            && self.order_type == reported.order_type
+            && self.strategy == reported.strategy
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub order_id: String,
+    pub symbol: Symbol,
+    pub venue: VenueId,
+    pub strategy: String,
+    pub side: OrderSide,
+    pub quantity: f64,
+    pub price: f64,
+    pub timestamp: u64,
+    /// Commission the venue charged, when its execution report says
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commission: Option<Commission>,
+}
+
+/// Commission on a fill in the asset the venue charged it in; negative
+/// amounts are rebates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Commission {
+    pub amount: f64,
+    pub asset: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum OrderSide {
     Buy,
     Sell,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl OrderSide {
+    /// +1 for buys, -1 for sells
+    pub fn sign(&self) -> f64 {
+        match self {
+            OrderSide::Buy => 1.0,
+            OrderSide::Sell => -1.0,
+        }
+    }
+
+    pub fn opposite(&self) -> OrderSide {
+        match self {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderType {
     Market,
     Limit,
@@ -41,4 +319,91 @@ impl fmt::Display for OrderType {
             OrderType::Limit => write!(f, "limit"),
         }
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OptionKind {
+    Call,
+    Put,
+}
+
+/// A European option on `underlying`, e.g. Deribit's `BTC-27MAR26-50000-C`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionInstrument {
+    /// Currency or index the option is written on, e.g. `BTC`
+    pub underlying: String,
+    pub strike: f64,
+    /// Milliseconds since the Unix epoch
+    pub expiry: u64,
+    pub kind: OptionKind,
+}
+
+/// Hour of day, UTC, Deribit options expire at
+const DERIBIT_EXPIRY_HOUR: u32 = 8;
+
+impl OptionInstrument {
+    /// Parse a Deribit option name, `UNDERLYING-DMMMYY-STRIKE-C|P`, with `d`
+    /// standing for a decimal point in the strike. Returns `None` for
+    /// anything else, so any symbol can be tried.
+    pub fn parse(symbol: &str) -> Option<Self> {
+        let mut parts = symbol.split('-');
+        let (underlying, date, strike, kind) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() || underlying.is_empty() {
+            return None;
+        }
+        let kind = match kind {
+            "C" => OptionKind::Call,
+            "P" => OptionKind::Put,
+            _ => return None,
+        };
+        let strike: f64 = strike.replace('d', ".").parse().ok().filter(|s: &f64| *s > 0.0)?;
+        let expiry = chrono::NaiveDate::parse_from_str(date, "%d%b%y").ok()?
+            .and_hms_opt(DERIBIT_EXPIRY_HOUR, 0, 0)?
+            .and_utc()
+            .timestamp_millis();
+        Some(Self { underlying: underlying.to_string(), strike, expiry: u64::try_from(expiry).ok()?, kind })
+    }
+
+    /// Years from `now` (milliseconds) to expiry, counting 365 days a year
+    pub fn years_to_expiry(&self, now: u64) -> f64 {
+        self.expiry.saturating_sub(now) as f64 / (365.0 * 24.0 * 3600.0 * 1000.0)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_interned_ids() {
+        let venue = VenueId::new("BINANCE");
+        assert_eq!(venue, VenueId::from("BINANCE".to_string()));
+        assert!(std::ptr::eq(venue.as_str(), VenueId::new("BINANCE").as_str()));
+        assert_ne!(venue, VenueId::new("KRAKEN"));
+        assert_eq!(venue, "BINANCE");
+        assert_eq!(venue.to_string(), "BINANCE");
+
+        // Maps keyed by ids are looked up with plain strings
+        let books = HashMap::from([(Symbol::new("BTCUSDT"), 1)]);
+        assert_eq!(books.get("BTCUSDT"), Some(&1));
+
+        let quote: Quote = serde_json::from_str(r#"{"symbol":"BTCUSDT","bid":1.0,"ask":2.0,"bid_size":1.0,"ask_size":1.0,"venue":"BINANCE","timestamp":0}"#).unwrap();
+        assert_eq!((quote.symbol, quote.venue), (Symbol::new("BTCUSDT"), venue));
+        assert!(serde_json::to_string(&quote).unwrap().contains(r#""venue":"BINANCE""#));
+    }
+
+    #[test]
+    fn test_parse_option_instrument() {
+        let option = OptionInstrument::parse("BTC-27MAR26-50000-C").unwrap();
+        assert_eq!(option.underlying, "BTC");
+        assert_eq!((option.strike, option.kind), (50000.0, OptionKind::Call));
+        assert_eq!(option.expiry, 1774598400000);
+        assert!((option.years_to_expiry(option.expiry - 365 * 86_400_000) - 1.0).abs() < 1e-12);
+        assert_eq!(option.years_to_expiry(option.expiry + 1), 0.0);
+
+        assert_eq!(OptionInstrument::parse("XRP_USDC-5APR26-0d625-P").map(|o| (o.strike, o.kind)), Some((0.625, OptionKind::Put)));
+        for symbol in ["BTCUSDT", "BTC-PERPETUAL", "BTC-27MAR26", "BTC-27MAR26-50000-X", "BTC-32MAR26-50000-C"] {
+            assert!(OptionInstrument::parse(symbol).is_none(), "{}", symbol);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
+/// Funds held in one asset on a venue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetBalance {
+    pub asset: String,
+    pub total: f64,
+    /// Free for new orders and withdrawals
+    pub available: f64,
+}
+
+/// Why a balance changed outside of trading
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferKind {
+    Deposit,
+    Withdrawal,
+    /// Perpetual funding paid or received
+    Funding,
+    /// Anything else the venue credits or debits, e.g. insurance fund
+    /// clearances
+    Other,
+}
+
+impl fmt::Display for TransferKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransferKind::Deposit => write!(f, "deposit"),
+            TransferKind::Withdrawal => write!(f, "withdrawal"),
+            TransferKind::Funding => write!(f, "funding"),
+            TransferKind::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// A balance change that did not come from a fill
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transfer {
+    /// The venue's id for the change, unique per venue
+    pub id: String,
+    pub asset: String,
+    /// Positive when credited
+    pub amount: f64,
+    pub kind: TransferKind,
+    pub timestamp: u64,
+}
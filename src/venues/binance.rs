@@ -1,18 +1,51 @@
-use crate::error::{HftError, VenueError, ErrorExt};
-use crate::types::{Order, Quote, OrderSide, OrderType};
-use crate::venues::VenueAdapter;
+use crate::error::{HftError, VenueError};
+use std::time::Duration;
+use crate::types::{Candle, Commission, Fill, Order, OrderSide, OrderType, Quote};
+use crate::venues::{AssetBalance, InstrumentRules, MarginMode, MarginSettings, PositionRisk, ReconnectPolicy, Reconnector, Transfer, TransferKind, VenueAdapter, VenueCapabilities, VenueTransport};
+use crate::events::{EngineEvent, EventBus};
+use crate::incident::IncidentLog;
+use crate::metrics::{ORDER_ENTRY_REQUESTS, VENUE_CONNECTIONS};
+use crate::venues::binance_ws::WsTradingSession;
+use crate::venues::frames::FrameRecorder;
+use crate::venues::inbound::InboundMetrics;
 use async_trait::async_trait;
-use futures_util::StreamExt;
+use futures_util::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
 use serde::Deserialize;
+use sha2::Sha256;
 use tokio_tungstenite::{
-    connect_async,
-    tungstenite::http::Request,
+    tungstenite::client::IntoClientRequest,
+    tungstenite::http::HeaderValue,
+    tungstenite::Message,
+    MaybeTlsStream, WebSocketStream,
 };
-use tokio::sync::mpsc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
 use tracing::{info, warn, error, debug, trace};
 
-const RECONNECT_DELAY_MS: u64 = 5000;
-const MAX_RECONNECT_ATTEMPTS: usize = 5;
+/// Milliseconds a signed request stays valid after its timestamp
+const RECV_WINDOW_MS: u64 = 5000;
+
+/// Binance error codes for orders that are filled, cancelled or never existed
+const UNKNOWN_ORDER_CODES: [i64; 2] = [-2011, -2013];
+
+/// Most streams Binance allows on one market data connection
+const MAX_STREAMS_PER_CONNECTION: usize = 200;
+
+/// Most klines Binance returns per request
+const MAX_KLINES: usize = 1500;
+
+/// Listen keys expire an hour after they were last kept alive
+const LISTEN_KEY_KEEPALIVE: Duration = Duration::from_secs(30 * 60);
+
+/// Kline intervals Binance accepts, by length in seconds
+const KLINE_INTERVALS: [(u64, &str); 12] = [
+    (60, "1m"), (180, "3m"), (300, "5m"), (900, "15m"), (1800, "30m"), (3600, "1h"),
+    (7200, "2h"), (14400, "4h"), (21600, "6h"), (28800, "8h"), (43200, "12h"), (86400, "1d"),
+];
 
 #[derive(Debug)]
 pub struct BinanceVenue {
@@ -20,7 +53,53 @@ pub struct BinanceVenue {
     api_key: String,
     api_secret: String,
     rest_url: String,
+    http: reqwest::Client,
+    /// Preferred over REST for order entry when set
+    ws_trading: Option<WsTradingSession>,
     quote_tx: Option<mpsc::Sender<Quote>>,
+    /// Fills from the user data stream, which is only opened when set
+    fill_tx: Option<mpsc::Sender<Fill>>,
+    /// Reads the user data stream, opened with the first subscription
+    user_stream: Mutex<Option<JoinHandle<()>>>,
+    events: Option<EventBus>,
+    /// Tap recording raw market data frames
+    recorder: Option<FrameRecorder>,
+    /// Keeps the latest raw frames for incident dumps
+    incidents: Option<IncidentLog>,
+    market_streams: Arc<Mutex<Vec<MarketStream>>>,
+    /// Streams per market data connection before another is opened
+    max_streams: usize,
+    /// Numbers market data connections for their metric labels
+    next_connection: AtomicUsize,
+    reconnect: ReconnectPolicy,
+    transport: VenueTransport,
+}
+
+type WsWrite = futures_util::stream::SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+type WsRead = futures_util::stream::SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>;
+
+/// A market data connection and the streams it carries, so streams can be
+/// dropped without touching the others
+#[derive(Debug)]
+struct MarketStream {
+    /// Stream names, e.g. `btcusdt@bookTicker`; a reconnect subscribes to
+    /// the ones left
+    streams: Arc<std::sync::Mutex<Vec<String>>>,
+    /// `None` while reconnecting
+    write: Arc<Mutex<Option<WsWrite>>>,
+    /// Reads the connection and reconnects it when it drops
+    task: JoinHandle<()>,
+}
+
+fn stream_name(symbol: &str) -> String {
+    format!("{}@bookTicker", symbol.to_lowercase())
+}
+
+/// Whether `text` is the response to a `SUBSCRIBE`/`UNSUBSCRIBE` request
+/// rather than market data
+fn is_stream_response(text: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(text)
+        .is_ok_and(|v| v.get("id").is_some() && v.get("result").is_some())
 }
 
 #[derive(Debug, Deserialize)]
@@ -39,6 +118,150 @@ struct BinanceBookTicker {
     time: u64,
 }
 
+#[derive(Debug, Deserialize)]
+pub(crate) struct BinanceApiError {
+    code: i64,
+    msg: String,
+}
+
+/// Map a Binance error payload to an engine error
+pub(crate) fn api_error(status: u16, err: BinanceApiError) -> HftError {
+    if UNKNOWN_ORDER_CODES.contains(&err.code) {
+        VenueError::UnknownOrder(err.msg).into()
+    } else if status == 401 {
+        VenueError::AuthenticationFailed(err.msg).into()
+    } else if status == 429 || status == 418 {
+        VenueError::RateLimitExceeded.into()
+    } else {
+        VenueError::OrderSubmissionFailed(format!("{} ({})", err.msg, err.code)).into()
+    }
+}
+
+/// Hex HMAC-SHA256 of the query string, as Binance expects in `signature`
+pub(crate) fn sign(secret: &str, query: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(query.as_bytes());
+    mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceOrderResponse {
+    order_id: u64,
+}
+
+/// An entry of `GET /fapi/v1/openOrders`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceOpenOrder {
+    symbol: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceServerTime {
+    server_time: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceUserTrade {
+    symbol: String,
+    order_id: u64,
+    side: String,
+    price: String,
+    qty: String,
+    commission: String,
+    commission_asset: String,
+    time: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceBalance {
+    asset: String,
+    balance: String,
+    available_balance: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceIncome {
+    income_type: String,
+    income: String,
+    asset: String,
+    time: u64,
+    tran_id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinancePositionRisk {
+    symbol: String,
+    position_amt: String,
+    mark_price: String,
+    liquidation_price: String,
+    leverage: String,
+    margin_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceExchangeInfo {
+    symbols: Vec<BinanceSymbolInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceSymbolInfo {
+    symbol: String,
+    status: String,
+}
+
+/// A kline row: `[open time, open, high, low, close, volume, ...]` with
+/// prices and volume as strings
+fn parse_kline(row: &[serde_json::Value]) -> Option<Candle> {
+    let number = |i: usize| row.get(i)?.as_str()?.parse::<f64>().ok();
+    Some(Candle {
+        open_time: row.first()?.as_u64()?,
+        open: number(1)?,
+        high: number(2)?,
+        low: number(3)?,
+        close: number(4)?,
+        volume: number(5)?,
+    })
+}
+
+/// Parse a `bookTicker` frame into a quote, rejecting non-positive prices
+/// and sizes. Used by the live stream and by frame replay.
+pub fn parse_book_ticker(text: &str) -> Result<Quote, VenueError> {
+    let ticker: BinanceBookTicker = serde_json::from_str(text)
+        .map_err(|e| VenueError::ParseError(format!("Not a bookTicker frame: {}", e)))?;
+    let field = |name: &str, value: &str| {
+        value.parse::<f64>()
+            .ok()
+            .filter(|v| *v > 0.0)
+            .ok_or_else(|| VenueError::ParseError(format!("Invalid {} {:?} for {}", name, value, ticker.symbol)))
+    };
+
+    Ok(Quote {
+        bid: field("bid price", &ticker.best_bid_price)?,
+        ask: field("ask price", &ticker.best_ask_price)?,
+        bid_size: field("bid size", &ticker.best_bid_quantity)?,
+        ask_size: field("ask size", &ticker.best_ask_quantity)?,
+        symbol: ticker.symbol.into(),
+        venue: "BINANCE_FUTURES".into(),
+        // Exchange transaction time in milliseconds
+        timestamp: ticker.time,
+    })
+}
+
+fn side_param(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "BUY",
+        OrderSide::Sell => "SELL",
+    }
+}
+
 impl BinanceVenue {
     pub fn new(api_key: String, api_secret: String) -> Self {
         Self {
@@ -46,159 +269,544 @@ impl BinanceVenue {
             rest_url: "https://fapi.binance.com/fapi".to_string(),
             api_key,
             api_secret,
+            http: reqwest::Client::new(),
+            ws_trading: None,
             quote_tx: None,
+            fill_tx: None,
+            user_stream: Mutex::new(None),
+            events: None,
+            recorder: None,
+            incidents: None,
+            market_streams: Arc::new(Mutex::new(Vec::new())),
+            max_streams: MAX_STREAMS_PER_CONNECTION,
+            next_connection: AtomicUsize::new(0),
+            reconnect: ReconnectPolicy::default(),
+            transport: VenueTransport::default(),
         }
     }
 
+    /// Open another market data connection past `max` streams instead of
+    /// Binance's limit of 200
+    pub fn with_max_streams_per_connection(mut self, max: usize) -> Self {
+        self.max_streams = max.max(1);
+        self
+    }
+
     pub fn with_quote_sender(mut self, quote_tx: mpsc::Sender<Quote>) -> Self {
         self.quote_tx = Some(quote_tx);
         self
     }
 
-    async fn connect_websocket(&self, symbols: Vec<String>) -> Result<(), HftError> {
-        let streams: Vec<String> = symbols
+    /// Send fills on the account's orders from the user data stream
+    pub fn with_fill_sender(mut self, fill_tx: mpsc::Sender<Fill>) -> Self {
+        self.fill_tx = Some(fill_tx);
+        self
+    }
+
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// How market data connections are retried when they fail or drop
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// Route REST and WebSocket connections through a proxy or trust extra
+    /// CA certificates
+    pub fn with_transport(mut self, transport: VenueTransport) -> Self {
+        self.http = transport.http_client_or_default();
+        self.ws_trading = self.ws_trading.map(|session| session.with_transport(transport.clone()));
+        self.transport = transport;
+        self
+    }
+
+    /// Stream market data from another endpoint, e.g. the testnet
+    pub fn with_ws_url(mut self, ws_url: impl Into<String>) -> Self {
+        self.ws_url = ws_url.into();
+        self
+    }
+
+    /// Record every raw market data frame, for replay in regression tests
+    pub fn with_frame_recorder(mut self, recorder: FrameRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Keep the latest raw market data frames in `incidents`
+    pub fn with_incident_log(mut self, incidents: IncidentLog) -> Self {
+        self.incidents = Some(incidents);
+        self
+    }
+
+    /// Point REST calls at another endpoint, e.g. the testnet
+    pub fn with_rest_url(mut self, rest_url: impl Into<String>) -> Self {
+        self.rest_url = rest_url.into();
+        self
+    }
+
+    /// Send orders, amends and cancels over the WebSocket API at `url`,
+    /// falling back to REST whenever the session is down
+    pub fn with_ws_order_entry(mut self, url: impl Into<String>) -> Self {
+        self.ws_trading = Some(WsTradingSession::new(url.into(), self.api_key.clone(), self.api_secret.clone())
+            .with_transport(self.transport.clone()));
+        self
+    }
+
+    /// Try the WebSocket session first. Only requests that never reached
+    /// the venue are retried over REST, so an order is never sent twice.
+    async fn ws_request(&self, method: &str, params: &[(&str, String)]) -> Option<Result<serde_json::Value, HftError>> {
+        let session = self.ws_trading.as_ref()?;
+        match session.request(method, params).await {
+            Err(HftError::Venue(VenueError::ConnectionFailed(reason))) => {
+                warn!(method = method, reason = %reason, "WS trading session unavailable, falling back to REST");
+                None
+            }
+            result => {
+                ORDER_ENTRY_REQUESTS.with_label_values(&["BINANCE_FUTURES", "ws"]).inc();
+                Some(result)
+            }
+        }
+    }
+
+    /// Send a signed (USER_DATA/TRADE) REST request and decode the response
+    async fn signed_request<T: serde::de::DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<T, HftError> {
+        let mut query: String = params
             .iter()
-            .map(|s| format!("{}@bookTicker", s.to_lowercase()))
-            .collect();
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+        if !query.is_empty() {
+            query.push('&');
+        }
+        query.push_str(&format!(
+            "recvWindow={}&timestamp={}",
+            RECV_WINDOW_MS,
+            chrono::Utc::now().timestamp_millis()
+        ));
+        let signature = sign(&self.api_secret, &query);
+
+        let url = format!("{}{}?{}&signature={}", self.rest_url, path, query, signature);
+        let response = self.http
+            .request(method, url)
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| match e.is_connect() {
+                true => VenueError::ConnectionFailed(format!("Binance REST request failed: {}", e.without_url())),
+                // The request may have gone out before the failure
+                false => VenueError::NoResponse(format!("Binance REST request failed: {}", e.without_url())),
+            })?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| VenueError::NoResponse(format!("Failed to read Binance response: {}", e.without_url())))?;
+
+        ORDER_ENTRY_REQUESTS.with_label_values(&["BINANCE_FUTURES", "rest"]).inc();
+        if status.is_success() {
+            return serde_json::from_str(&body)
+                .map_err(|e| VenueError::ParseError(format!("Unexpected Binance response: {}", e)).into());
+        }
+
+        match serde_json::from_str::<BinanceApiError>(&body) {
+            Ok(err) => Err(api_error(status.as_u16(), err)),
+            Err(_) if status == reqwest::StatusCode::TOO_MANY_REQUESTS => Err(VenueError::RateLimitExceeded.into()),
+            Err(_) => Err(VenueError::OrderSubmissionFailed(format!("HTTP {}: {}", status, body)).into()),
+        }
+    }
+
+    /// Send an unsigned (MARKET_DATA) REST GET and decode the response
+    async fn public_request<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, HftError> {
+        let response = self.http
+            .get(format!("{}{}", self.rest_url, path))
+            .send()
+            .await
+            .map_err(|e| match e.is_connect() {
+                true => VenueError::ConnectionFailed(format!("Binance REST request failed: {}", e.without_url())),
+                // The request may have gone out before the failure
+                false => VenueError::NoResponse(format!("Binance REST request failed: {}", e.without_url())),
+            })?;
 
-        let ws_url = format!("{}/{}", self.ws_url, streams.join("/"));
-        info!(url = %ws_url, "Connecting to Binance WebSocket");
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| VenueError::NoResponse(format!("Failed to read Binance response: {}", e.without_url())))?;
+        if !status.is_success() {
+            return match serde_json::from_str::<BinanceApiError>(&body) {
+                Ok(err) => Err(api_error(status.as_u16(), err)),
+                Err(_) => Err(VenueError::ConnectionFailed(format!("HTTP {}: {}", status, body)).into()),
+            };
+        }
 
-        // Create a request instead of using URL directly
-        let request = Request::builder()
-            .uri(ws_url)
-            .header("User-Agent", "Mozilla/5.0")
-            .body(())
-            .map_err(|e| VenueError::ConnectionFailed(format!("Failed to build request: {}", e)))?;
+        serde_json::from_str(&body)
+            .map_err(|e| VenueError::ParseError(format!("Unexpected Binance response: {}", e)).into())
+    }
 
+    /// Open a market data connection carrying `streams`
+    async fn connect_websocket(&self, streams: Vec<String>) -> Result<MarketStream, HftError> {
         let quote_tx = match &self.quote_tx {
             Some(tx) => tx.clone(),
             None => return Err(VenueError::ConnectionFailed("Quote sender not configured".to_string()).into()),
         };
 
-        self.ws_connect_with_retry(request, quote_tx, MAX_RECONNECT_ATTEMPTS).await?;
+        let mut reconnector = Reconnector::new("BINANCE_FUTURES", self.reconnect.clone());
+        let (write, read) = reconnector.connect(|| connect_market_data(&self.transport, &self.ws_url, &streams)).await?;
+        report_connected(&self.events);
+
+        let connection = format!("market-{}", self.next_connection.fetch_add(1, Ordering::Relaxed));
+        let streams = Arc::new(std::sync::Mutex::new(streams));
+        let write = Arc::new(Mutex::new(Some(write)));
+        let task = tokio::spawn(run_market_stream(MarketStreamTask {
+            ws_url: self.ws_url.clone(),
+            transport: self.transport.clone(),
+            streams: Arc::clone(&streams),
+            write: Arc::clone(&write),
+            quote_tx,
+            events: self.events.clone(),
+            recorder: self.recorder.clone(),
+            incidents: self.incidents.clone(),
+            inbound: InboundMetrics::new("BINANCE_FUTURES", &connection),
+            reconnector,
+        }, read));
+        Ok(MarketStream { streams, write, task })
+    }
+
+    /// Open the user data stream if fills are wanted and it is not open yet
+    async fn open_user_stream(&self) -> Result<(), HftError> {
+        let Some(fill_tx) = &self.fill_tx else { return Ok(()) };
+        let mut user_stream = self.user_stream.lock().await;
+        if user_stream.is_some() {
+            return Ok(());
+        }
 
+        let mut task = UserStreamTask {
+            http: self.http.clone(),
+            rest_url: self.rest_url.clone(),
+            ws_url: self.ws_url.clone(),
+            api_key: self.api_key.clone(),
+            transport: self.transport.clone(),
+            fill_tx: fill_tx.clone(),
+            reconnector: Reconnector::new("BINANCE_FUTURES", self.reconnect.clone()),
+        };
+        let mut reconnector = task.reconnector.clone();
+        let connection = reconnector.connect(|| task.connect()).await?;
+        task.reconnector = reconnector;
+        *user_stream = Some(tokio::spawn(run_user_stream(task, connection)));
+        info!("Binance user data stream opened");
         Ok(())
     }
 
-    async fn ws_connect_with_retry(
-        &self,
-        request: Request<()>,
-        quote_tx: mpsc::Sender<Quote>,
-        max_attempts: usize
-    ) -> Result<(), HftError> {
-        let mut attempts = 0;
-
-        loop {
-            attempts += 1;
-            // Fixed: Use clone() and handle the connect_async result separately
-            let request_copy = request.clone();
-            match connect_async(request_copy).await {
-                Ok((ws_stream, _)) => {
-                    info!("WebSocket connected successfully");
-                    let (_write, read) = ws_stream.split();
-
-                    self.process_websocket_messages(read, quote_tx.clone()).await;
-                    return Ok(());
-                }
-                Err(e) => {
-                    error!(error = ?e, "WebSocket connection error");
-                    if attempts >= max_attempts {
-                        return Err(VenueError::ConnectionFailed(
-                            format!("Failed after {} attempts: {}", attempts, e)
-                        ).into());
-                    }
+    /// Close every market data connection without reporting a disconnect
+    async fn close_market_streams(&self) {
+        for connection in self.market_streams.lock().await.drain(..) {
+            connection.task.abort();
+            if let Some(mut write) = connection.write.lock().await.take() {
+                let _ = write.close().await;
+            }
+        }
+        VENUE_CONNECTIONS.with_label_values(&["BINANCE_FUTURES"]).set(0.0);
+    }
+}
 
-                    warn!(
-                        attempt = attempts,
-                        max_attempts = max_attempts,
-                        delay_ms = RECONNECT_DELAY_MS,
-                        "Retrying connection"
-                    );
-                    tokio::time::sleep(tokio::time::Duration::from_millis(RECONNECT_DELAY_MS)).await;
-                }
+/// Open a combined-stream connection for `streams`
+async fn connect_market_data(transport: &VenueTransport, ws_url: &str, streams: &[String]) -> Result<(WsWrite, WsRead), HftError> {
+    let ws_url = format!("{}/{}", ws_url, streams.join("/"));
+    info!(url = %ws_url, "Connecting to Binance WebSocket");
+
+    // Start from the URL so the handshake headers are filled in
+    let mut request = ws_url
+        .into_client_request()
+        .map_err(|e| VenueError::ConnectionFailed(format!("Failed to build request: {}", e)))?;
+    request.headers_mut().insert("User-Agent", HeaderValue::from_static("Mozilla/5.0"));
+
+    let ws_stream = transport.connect_ws(request).await?;
+    info!("WebSocket connected successfully");
+    Ok(ws_stream.split())
+}
+
+fn report_connected(events: &Option<EventBus>) {
+    VENUE_CONNECTIONS.with_label_values(&["BINANCE_FUTURES"]).set(1.0);
+    if let Some(events) = events {
+        events.publish(EngineEvent::VenueConnected { venue: "BINANCE_FUTURES".to_string() });
+    }
+}
+
+/// What the task behind a [`MarketStream`] needs to read and reconnect it
+struct MarketStreamTask {
+    ws_url: String,
+    transport: VenueTransport,
+    streams: Arc<std::sync::Mutex<Vec<String>>>,
+    write: Arc<Mutex<Option<WsWrite>>>,
+    quote_tx: mpsc::Sender<Quote>,
+    events: Option<EventBus>,
+    recorder: Option<FrameRecorder>,
+    incidents: Option<IncidentLog>,
+    /// Labelled by connection, kept across reconnects
+    inbound: InboundMetrics,
+    reconnector: Reconnector,
+}
+
+/// Forward quotes until the connection drops, then reconnect to the
+/// remaining streams as the reconnect policy allows
+async fn run_market_stream(mut task: MarketStreamTask, mut read: WsRead) {
+    loop {
+        read_market_data(&mut read, &task).await;
+
+        error!("WebSocket stream ended unexpectedly");
+        task.write.lock().await.take();
+        VENUE_CONNECTIONS.with_label_values(&["BINANCE_FUTURES"]).set(0.0);
+        if let Some(events) = &task.events {
+            events.publish(EngineEvent::VenueDisconnected {
+                venue: "BINANCE_FUTURES".to_string(),
+                reason: "WebSocket stream ended".to_string(),
+            });
+        }
+
+        let streams = task.streams.lock().unwrap().clone();
+        match task.reconnector.connect(|| connect_market_data(&task.transport, &task.ws_url, &streams)).await {
+            Ok((write, reconnected)) => {
+                *task.write.lock().await = Some(write);
+                read = reconnected;
+                report_connected(&task.events);
+            }
+            Err(e) => {
+                error!(streams = ?streams, error = ?e, "Market data connection lost");
+                return;
             }
         }
     }
+}
 
-    async fn process_websocket_messages(
-        &self,
-        mut read: futures_util::stream::SplitStream<
-            tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>
-        >,
-        quote_tx: mpsc::Sender<Quote>,
-    ) {
-        tokio::spawn(async move {
-            while let Some(message) = read.next().await {
-                match message {
-                    Ok(msg) => {
-                        trace!(message = %msg.to_string(), "Received WebSocket message");
-
-                        match serde_json::from_str::<BinanceBookTicker>(&msg.to_string()) {
-                            Ok(ticker) => {
-                                // Use ? operator with Result to propagate errors
-                                let bid = ticker.best_bid_price.parse::<f64>()
-                                    .map_err(|e| VenueError::ParseError(format!("Invalid bid price: {}", e)))
-                                    .unwrap_or(0.0);
-
-                                let ask = ticker.best_ask_price.parse::<f64>()
-                                    .map_err(|e| VenueError::ParseError(format!("Invalid ask price: {}", e)))
-                                    .unwrap_or(0.0);
-
-                                let bid_size = ticker.best_bid_quantity.parse::<f64>()
-                                    .map_err(|e| VenueError::ParseError(format!("Invalid bid size: {}", e)))
-                                    .unwrap_or(0.0);
-
-                                let ask_size = ticker.best_ask_quantity.parse::<f64>()
-                                    .map_err(|e| VenueError::ParseError(format!("Invalid ask size: {}", e)))
-                                    .unwrap_or(0.0);
-
-                                // Validate data before creating Quote
-                                if bid <= 0.0 || ask <= 0.0 || bid_size <= 0.0 || ask_size <= 0.0 {
-                                    warn!(
-                                        symbol = %ticker.symbol,
-                                        bid = bid,
-                                        ask = ask,
-                                        bid_size = bid_size,
-                                        ask_size = ask_size,
-                                        "Invalid quote data received"
-                                    );
-                                    continue;
-                                }
-
-                                let quote = Quote {
-                                    symbol: ticker.symbol,
-                                    bid,
-                                    ask,
-                                    bid_size,
-                                    ask_size,
-                                    venue: "BINANCE_FUTURES".to_string(),
-                                    timestamp: std::time::SystemTime::now()
-                                        .duration_since(std::time::UNIX_EPOCH)
-                                        .unwrap_or_else(|_| std::time::Duration::from_secs(0))
-                                        .as_millis() as u64,
-                                };
-
-                                debug!(
-                                    symbol = %quote.symbol,
-                                    bid = %quote.bid,
-                                    ask = %quote.ask,
-                                    "Processed quote"
-                                );
-
-                                if let Err(e) = quote_tx.send(quote).await {
-                                    error!(error = %e, "Failed to send quote to channel");
-                                }
-                            }
-                            Err(e) => warn!(error = %e, "Failed to parse message"),
+async fn read_market_data(read: &mut WsRead, task: &MarketStreamTask) {
+    while let Some(message) = read.next().await {
+        match message {
+            Ok(msg) => {
+                task.inbound.on_message(msg.len());
+                let text = msg.to_string();
+                trace!(message = %text, "Received WebSocket message");
+                if msg.is_text() {
+                    if let Some(recorder) = &task.recorder {
+                        recorder.record("BINANCE_FUTURES", &text);
+                    }
+                    if let Some(incidents) = &task.incidents {
+                        incidents.record_message("BINANCE_FUTURES", &text);
+                    }
+                }
+
+                match parse_book_ticker(&text) {
+                    Ok(quote) => {
+                        debug!(
+                            symbol = %quote.symbol,
+                            bid = %quote.bid,
+                            ask = %quote.ask,
+                            "Processed quote"
+                        );
+
+                        if let Err(e) = task.quote_tx.send(quote).await {
+                            error!(error = %e, "Failed to send quote to channel");
                         }
                     }
-                    Err(e) => error!(error = %e, "WebSocket error"),
+                    Err(_) if is_stream_response(&text) => trace!(message = %text, "Stream change acknowledged"),
+                    Err(e) => {
+                        task.inbound.on_parse_failure();
+                        warn!(error = %e, "Failed to parse message");
+                    }
+                }
+            }
+            Err(e) => error!(error = %e, "WebSocket error"),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceListenKey {
+    listen_key: String,
+}
+
+/// A user data stream event; only `ORDER_TRADE_UPDATE` carries `o`
+#[derive(Debug, Deserialize)]
+struct BinanceUserEvent {
+    #[serde(rename = "e")]
+    event: String,
+    #[serde(rename = "o")]
+    order: Option<BinanceOrderUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceOrderUpdate {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "S")]
+    side: String,
+    /// Execution type, `TRADE` for a fill
+    #[serde(rename = "x")]
+    execution_type: String,
+    #[serde(rename = "i")]
+    order_id: u64,
+    #[serde(rename = "l")]
+    last_quantity: String,
+    #[serde(rename = "L")]
+    last_price: String,
+    #[serde(rename = "n")]
+    commission: Option<String>,
+    #[serde(rename = "N")]
+    commission_asset: Option<String>,
+    #[serde(rename = "T")]
+    time: u64,
+}
+
+/// What a user data stream frame means for the engine
+#[derive(Debug)]
+enum UserEvent {
+    Fill(Fill),
+    /// The listen key expired and the stream must be reopened with a new one
+    Expired,
+    Other,
+}
+
+/// Parse a user data stream frame, turning `ORDER_TRADE_UPDATE` trades into
+/// fills
+fn parse_user_event(text: &str) -> Result<UserEvent, VenueError> {
+    let event: BinanceUserEvent = serde_json::from_str(text)
+        .map_err(|e| VenueError::ParseError(format!("Not a user data event: {}", e)))?;
+    let order = match (event.event.as_str(), event.order) {
+        ("listenKeyExpired", _) => return Ok(UserEvent::Expired),
+        ("ORDER_TRADE_UPDATE", Some(order)) if order.execution_type == "TRADE" => order,
+        _ => return Ok(UserEvent::Other),
+    };
+    let number = |name: &str, value: &str| value.parse::<f64>()
+        .map_err(|_| VenueError::ParseError(format!("Invalid {} {:?} for order {}", name, value, order.order_id)));
+
+    let commission = match (&order.commission, order.commission_asset) {
+        (Some(amount), Some(asset)) => Some(Commission { amount: number("commission", amount)?, asset }),
+        _ => None,
+    };
+    Ok(UserEvent::Fill(Fill {
+        order_id: order.order_id.to_string(),
+        symbol: order.symbol.as_str().into(),
+        venue: "BINANCE_FUTURES".into(),
+        strategy: String::new(),
+        side: if order.side == "BUY" { OrderSide::Buy } else { OrderSide::Sell },
+        quantity: number("quantity", &order.last_quantity)?,
+        price: number("price", &order.last_price)?,
+        timestamp: order.time,
+        commission,
+    }))
+}
+
+/// What the task reading the user data stream needs to keep its listen key
+/// alive and reopen the stream
+struct UserStreamTask {
+    http: reqwest::Client,
+    rest_url: String,
+    ws_url: String,
+    api_key: String,
+    transport: VenueTransport,
+    fill_tx: mpsc::Sender<Fill>,
+    reconnector: Reconnector,
+}
+
+impl UserStreamTask {
+    /// `POST /fapi/v1/listenKey` for a new listen key, `PUT` to keep the
+    /// current one alive; both take the API key but no signature
+    async fn listen_key(&self, method: reqwest::Method) -> Result<String, HftError> {
+        let response = self.http
+            .request(method, format!("{}/v1/listenKey", self.rest_url))
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| VenueError::ConnectionFailed(format!("Binance listen key request failed: {}", e.without_url())))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| VenueError::ConnectionFailed(format!("Failed to read Binance response: {}", e.without_url())))?;
+        if !status.is_success() {
+            return match serde_json::from_str::<BinanceApiError>(&body) {
+                Ok(err) => Err(api_error(status.as_u16(), err)),
+                Err(_) => Err(VenueError::ConnectionFailed(format!("HTTP {}: {}", status, body)).into()),
+            };
+        }
+        let key: BinanceListenKey = serde_json::from_str(&body)
+            .map_err(|e| VenueError::ParseError(format!("Unexpected listen key response: {}", e)))?;
+        Ok(key.listen_key)
+    }
+
+    /// Get a new listen key and connect to its stream
+    async fn connect(&self) -> Result<WsRead, HftError> {
+        let listen_key = self.listen_key(reqwest::Method::POST).await?;
+        let (_, read) = connect_market_data(&self.transport, &self.ws_url, std::slice::from_ref(&listen_key)).await?;
+        Ok(read)
+    }
+}
+
+/// Forward fills until the stream drops or its listen key expires, then
+/// reopen it as the reconnect policy allows
+async fn run_user_stream(mut task: UserStreamTask, mut read: WsRead) {
+    loop {
+        read_user_data(&mut read, &task).await;
+
+        warn!("Binance user data stream ended, reopening");
+        let mut reconnector = task.reconnector.clone();
+        let reconnected = reconnector.connect(|| task.connect()).await;
+        task.reconnector = reconnector;
+        match reconnected {
+            Ok(reconnected) => read = reconnected,
+            Err(e) => {
+                error!(error = ?e, "Binance user data stream lost, fills are no longer received");
+                return;
+            }
+        }
+    }
+}
+
+/// Read fills until the connection drops or the listen key expires,
+/// keeping the listen key alive meanwhile
+async fn read_user_data(read: &mut WsRead, task: &UserStreamTask) {
+    let mut keepalive = tokio::time::interval_at(tokio::time::Instant::now() + LISTEN_KEY_KEEPALIVE, LISTEN_KEY_KEEPALIVE);
+    loop {
+        let message = tokio::select! {
+            message = read.next() => message,
+            _ = keepalive.tick() => {
+                if let Err(e) = task.listen_key(reqwest::Method::PUT).await {
+                    warn!(error = %e, "Failed to keep the Binance listen key alive");
                 }
+                continue;
             }
+        };
+        let text = match message {
+            Some(Ok(msg)) if msg.is_text() => msg.to_string(),
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => {
+                error!(error = %e, "User data stream error");
+                continue;
+            }
+            None => return,
+        };
+        trace!(message = %text, "Received user data event");
 
-            error!("WebSocket stream ended unexpectedly");
-        });
+        match parse_user_event(&text) {
+            Ok(UserEvent::Fill(fill)) => {
+                debug!(order_id = %fill.order_id, symbol = %fill.symbol, quantity = %fill.quantity, price = %fill.price, "Received fill");
+                if let Err(e) = task.fill_tx.send(fill).await {
+                    error!(error = %e, "Failed to send fill to channel");
+                }
+            }
+            Ok(UserEvent::Expired) => return,
+            Ok(UserEvent::Other) => {}
+            Err(e) => warn!(error = %e, "Failed to parse user data event"),
+        }
     }
 }
 
@@ -208,12 +816,108 @@ impl VenueAdapter for BinanceVenue {
         "BINANCE_FUTURES".to_string()
     }
 
+    fn capabilities(&self) -> VenueCapabilities {
+        VenueCapabilities {
+            amend: true,
+            ws_trading: self.ws_trading.is_some(),
+            cancel_all: true,
+            // 300 orders per 10 seconds on USD-M futures
+            max_orders_per_sec: Some(30),
+            instrument_rules: InstrumentRules::VenueMetadata,
+            ..VenueCapabilities::default()
+        }
+    }
+
+    /// Add the symbols' streams not already carried: `SUBSCRIBE` on
+    /// connections with room for more, then new connections for the rest
     async fn subscribe_quotes(&self, symbols: Vec<String>) -> Result<(), HftError> {
         if symbols.is_empty() {
             return Err(VenueError::SubscriptionFailed("Empty symbol list".to_string()).into());
         }
+        self.open_user_stream().await?;
 
-        self.connect_websocket(symbols).await
+        let mut connections = self.market_streams.lock().await;
+        let mut wanted: Vec<String> = Vec::new();
+        for stream in symbols.iter().map(|s| stream_name(s)) {
+            let carried = connections.iter().any(|c| c.streams.lock().unwrap().contains(&stream));
+            if !carried && !wanted.contains(&stream) {
+                wanted.push(stream);
+            }
+        }
+
+        let mut failed = Vec::new();
+        for connection in connections.iter() {
+            if wanted.is_empty() {
+                break;
+            }
+            let added: Vec<String> = {
+                let mut streams = connection.streams.lock().unwrap();
+                let room = self.max_streams.saturating_sub(streams.len()).min(wanted.len());
+                let added: Vec<String> = wanted.drain(..room).collect();
+                streams.extend(added.iter().cloned());
+                added
+            };
+            // A connection that is reconnecting subscribes to them when back
+            if let (false, Some(write)) = (added.is_empty(), connection.write.lock().await.as_mut()) {
+                let request = serde_json::json!({ "method": "SUBSCRIBE", "params": added, "id": 1 });
+                if let Err(e) = write.send(Message::text(request.to_string())).await {
+                    failed.push(format!("{:?}: {}", added, e));
+                }
+            }
+        }
+        for streams in wanted.chunks(self.max_streams) {
+            connections.push(self.connect_websocket(streams.to_vec()).await?);
+        }
+        info!(symbols = ?symbols, connections = connections.len(), "Subscribed to Binance streams");
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(VenueError::SubscriptionFailed(format!("Failed to subscribe {}", failed.join(", "))).into())
+        }
+    }
+
+    /// Send `UNSUBSCRIBE` for the symbols' streams, closing connections left
+    /// with no streams
+    async fn unsubscribe_quotes(&self, symbols: Vec<String>) -> Result<(), HftError> {
+        let unwanted: Vec<String> = symbols.iter().map(|s| stream_name(s)).collect();
+        let mut connections = self.market_streams.lock().await;
+        let mut failed = Vec::new();
+        for connection in std::mem::take(&mut *connections) {
+            let (dropped, remaining): (Vec<String>, Vec<String>) = {
+                let mut streams = connection.streams.lock().unwrap();
+                let (dropped, remaining) = streams.drain(..).partition(|s| unwanted.contains(s));
+                streams.clone_from(&remaining);
+                (dropped, remaining)
+            };
+            let mut write = connection.write.lock().await;
+            if remaining.is_empty() {
+                connection.task.abort();
+                if let Some(mut write) = write.take() {
+                    let _ = write.close().await;
+                }
+                continue;
+            }
+            // A connection that is reconnecting subscribes to what is left
+            if let (false, Some(write)) = (dropped.is_empty(), write.as_mut()) {
+                let request = serde_json::json!({ "method": "UNSUBSCRIBE", "params": dropped, "id": 1 });
+                if let Err(e) = write.send(Message::text(request.to_string())).await {
+                    failed.push(format!("{:?}: {}", dropped, e));
+                }
+            }
+            drop(write);
+            connections.push(connection);
+        }
+        if connections.is_empty() {
+            VENUE_CONNECTIONS.with_label_values(&["BINANCE_FUTURES"]).set(0.0);
+        }
+        info!(symbols = ?symbols, connections = connections.len(), "Unsubscribed from Binance streams");
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(VenueError::SubscriptionFailed(format!("Failed to unsubscribe {}", failed.join(", "))).into())
+        }
     }
 
     async fn submit_order(&self, order: Order) -> Result<String, HftError> {
@@ -224,13 +928,30 @@ impl VenueAdapter for BinanceVenue {
             ).into());
         }
 
-        if order.price <= 0.0 && matches!(order.order_type, crate::types::OrderType::Limit) {
+        if order.price <= 0.0 && matches!(order.order_type, OrderType::Limit) {
             return Err(VenueError::OrderSubmissionFailed(
                 format!("Invalid price for limit order: {}", order.price)
             ).into());
         }
 
-        // TODO: Implement actual order submission with proper error handling
+        let mut params = vec![
+            ("symbol", order.symbol.to_string()),
+            ("side", side_param(order.side).to_string()),
+            ("quantity", order.quantity.to_string()),
+        ];
+        match order.order_type {
+            OrderType::Limit => params.extend([
+                ("type", "LIMIT".to_string()),
+                ("timeInForce", "GTC".to_string()),
+                ("price", order.price.to_string()),
+            ]),
+            OrderType::Market => params.push(("type", "MARKET".to_string())),
+        }
+        let response: BinanceOrderResponse = match self.ws_request("order.place", &params).await {
+            Some(result) => serde_json::from_value(result?)
+                .map_err(|e| VenueError::ParseError(format!("Unexpected order.place response: {}", e)))?,
+            None => self.signed_request(reqwest::Method::POST, "/v1/order", &params).await?,
+        };
 
         info!(
             symbol = %order.symbol,
@@ -238,10 +959,230 @@ impl VenueAdapter for BinanceVenue {
             quantity = %order.quantity,
             price = %order.price,
             order_type = ?order.order_type,
+            order_id = response.order_id,
             "Order submitted to Binance"
         );
+        Ok(response.order_id.to_string())
+    }
+
+    async fn cancel_order(&self, order_id: &str, symbol: &str) -> Result<(), HftError> {
+        let params = [("symbol", symbol.to_string()), ("orderId", order_id.to_string())];
+        match self.ws_request("order.cancel", &params).await {
+            Some(result) => {
+                result?;
+            }
+            None => {
+                let _: BinanceOrderResponse = self.signed_request(reqwest::Method::DELETE, "/v1/order", &params).await?;
+            }
+        }
+
+        info!(symbol = %symbol, order_id = %order_id, "Order cancelled on Binance");
+        Ok(())
+    }
+
+    /// Modify a resting limit order with `PUT /fapi/v1/order`; Binance keeps
+    /// the order ID
+    async fn amend_order(&self, order_id: &str, order: &Order) -> Result<String, HftError> {
+        if order.order_type != OrderType::Limit || order.quantity <= 0.0 || order.price <= 0.0 {
+            return Err(VenueError::OrderSubmissionFailed(format!(
+                "Invalid amend: {:?} {} @ {}", order.order_type, order.quantity, order.price
+            )).into());
+        }
+
+        let params = [
+            ("symbol", order.symbol.to_string()),
+            ("orderId", order_id.to_string()),
+            ("side", side_param(order.side).to_string()),
+            ("quantity", order.quantity.to_string()),
+            ("price", order.price.to_string()),
+        ];
+        let response: BinanceOrderResponse = match self.ws_request("order.modify", &params).await {
+            Some(result) => serde_json::from_value(result?)
+                .map_err(|e| VenueError::ParseError(format!("Unexpected order.modify response: {}", e)))?,
+            None => self.signed_request(reqwest::Method::PUT, "/v1/order", &params).await?,
+        };
+
+        info!(symbol = %order.symbol, order_id = %order_id, price = %order.price, quantity = %order.quantity, "Order amended on Binance");
+        Ok(response.order_id.to_string())
+    }
+
+    /// Public `GET /fapi/v1/klines`; the last candle is still forming
+    async fn fetch_candles(&self, symbol: &str, interval: Duration, limit: usize) -> Result<Vec<Candle>, HftError> {
+        let Some((_, interval_name)) = KLINE_INTERVALS.iter().find(|(secs, _)| Duration::from_secs(*secs) == interval) else {
+            return Err(VenueError::NotSupported(format!("{:?} klines", interval)).into());
+        };
+
+        let rows: Vec<Vec<serde_json::Value>> = self.public_request(&format!(
+            "/v1/klines?symbol={}&interval={}&limit={}",
+            symbol, interval_name, limit.clamp(1, MAX_KLINES)
+        )).await?;
+        rows.iter()
+            .map(|row| parse_kline(row).ok_or_else(|| VenueError::ParseError(format!("Invalid kline: {:?}", row)).into()))
+            .collect()
+    }
+
+    /// Signed `GET /fapi/v1/userTrades`, which covers at most seven days
+    /// and 1000 trades per request
+    async fn trade_history(&self, symbol: &str, since: u64, until: u64) -> Result<Vec<Fill>, HftError> {
+        let params = [
+            ("symbol", symbol.to_string()),
+            ("startTime", since.to_string()),
+            ("endTime", until.to_string()),
+            ("limit", "1000".to_string()),
+        ];
+        let trades: Vec<BinanceUserTrade> = self.signed_request(reqwest::Method::GET, "/v1/userTrades", &params).await?;
+        let number = |s: &str| s.parse::<f64>().map_err(|_| VenueError::ParseError(format!("Invalid trade number {:?}", s)));
+        trades.into_iter()
+            .map(|trade| Ok(Fill {
+                order_id: trade.order_id.to_string(),
+                symbol: trade.symbol.as_str().into(),
+                venue: "BINANCE_FUTURES".into(),
+                strategy: String::new(),
+                side: if trade.side == "BUY" { OrderSide::Buy } else { OrderSide::Sell },
+                quantity: number(&trade.qty)?,
+                price: number(&trade.price)?,
+                timestamp: trade.time,
+                commission: Some(Commission { amount: number(&trade.commission)?, asset: trade.commission_asset }),
+            }))
+            .collect()
+    }
+
+    /// Public `GET /fapi/v1/time`
+    async fn server_time(&self) -> Result<u64, HftError> {
+        let time: BinanceServerTime = self.public_request("/v1/time").await?;
+        Ok(time.server_time)
+    }
+
+    /// Signed `GET /fapi/v2/balance`, which needs no trading permission
+    async fn verify_credentials(&self) -> Result<(), HftError> {
+        if self.api_key.is_empty() || self.api_secret.is_empty() {
+            return Err(VenueError::AuthenticationFailed("API key or secret not set".to_string()).into());
+        }
+        let _: serde_json::Value = self.signed_request(reqwest::Method::GET, "/v2/balance", &[]).await?;
+        Ok(())
+    }
+
+    /// Public `GET /fapi/v1/exchangeInfo`, keeping symbols with status
+    /// `TRADING`
+    async fn tradable_symbols(&self) -> Result<Vec<String>, HftError> {
+        let info: BinanceExchangeInfo = self.public_request("/v1/exchangeInfo").await?;
+        Ok(info.symbols.into_iter().filter(|s| s.status == "TRADING").map(|s| s.symbol).collect())
+    }
+
+    /// Signed `POST /fapi/v1/leverage`
+    async fn set_leverage(&self, symbol: &str, leverage: u32) -> Result<(), HftError> {
+        let params = [("symbol", symbol.to_string()), ("leverage", leverage.to_string())];
+        let _: serde_json::Value = self.signed_request(reqwest::Method::POST, "/v1/leverage", &params).await?;
+        Ok(())
+    }
 
-        Ok("mock_order_id".to_string())
+    /// Signed `POST /fapi/v1/marginType`; Binance rejects the change while
+    /// the symbol has open orders or a position
+    async fn set_margin_mode(&self, symbol: &str, mode: MarginMode) -> Result<(), HftError> {
+        let margin_type = match mode {
+            MarginMode::Isolated => "ISOLATED",
+            MarginMode::Cross => "CROSSED",
+        };
+        let params = [("symbol", symbol.to_string()), ("marginType", margin_type.to_string())];
+        let _: serde_json::Value = self.signed_request(reqwest::Method::POST, "/v1/marginType", &params).await?;
+        Ok(())
+    }
+
+    /// Signed `GET /fapi/v2/positionRisk` for the symbol
+    async fn margin_settings(&self, symbol: &str) -> Result<MarginSettings, HftError> {
+        let risks: Vec<BinancePositionRisk> = self
+            .signed_request(reqwest::Method::GET, "/v2/positionRisk", &[("symbol", symbol.to_string())])
+            .await?;
+        let risk = risks.into_iter().next()
+            .ok_or_else(|| VenueError::OrderSubmissionFailed(format!("No position risk for {}", symbol)))?;
+        let leverage = risk.leverage.parse()
+            .map_err(|_| VenueError::OrderSubmissionFailed(format!("Invalid leverage {:?}", risk.leverage)))?;
+        let mode = match risk.margin_type.as_str() {
+            "isolated" => MarginMode::Isolated,
+            "cross" => MarginMode::Cross,
+            other => return Err(VenueError::OrderSubmissionFailed(format!("Unknown margin type {:?}", other)).into()),
+        };
+        Ok(MarginSettings { leverage, mode })
+    }
+
+    /// Signed `GET /fapi/v2/positionRisk`, keeping open positions
+    async fn position_risks(&self) -> Result<Vec<PositionRisk>, HftError> {
+        let risks: Vec<BinancePositionRisk> = self.signed_request(reqwest::Method::GET, "/v2/positionRisk", &[]).await?;
+        let price = |s: &str| s.parse::<f64>().unwrap_or(0.0);
+        Ok(risks.into_iter()
+            .map(|risk| PositionRisk {
+                quantity: price(&risk.position_amt),
+                mark_price: price(&risk.mark_price),
+                liquidation_price: price(&risk.liquidation_price),
+                symbol: risk.symbol,
+            })
+            .filter(|risk| risk.quantity != 0.0)
+            .collect())
+    }
+
+    /// Signed `GET /fapi/v2/balance`
+    async fn balances(&self) -> Result<Vec<AssetBalance>, HftError> {
+        let balances: Vec<BinanceBalance> = self.signed_request(reqwest::Method::GET, "/v2/balance", &[]).await?;
+        let amount = |s: &str| s.parse::<f64>().map_err(|_| VenueError::ParseError(format!("Invalid balance {:?}", s)));
+        balances.into_iter()
+            .map(|balance| Ok(AssetBalance {
+                total: amount(&balance.balance)?,
+                available: amount(&balance.available_balance)?,
+                asset: balance.asset,
+            }))
+            .collect()
+    }
+
+    /// Signed `GET /fapi/v1/income`, leaving out realized PnL and
+    /// commissions, which fills already account for
+    async fn transfers(&self, since: u64) -> Result<Vec<Transfer>, HftError> {
+        let params = [("startTime", since.to_string()), ("limit", "1000".to_string())];
+        let incomes: Vec<BinanceIncome> = self.signed_request(reqwest::Method::GET, "/v1/income", &params).await?;
+        let mut transfers = Vec::new();
+        for income in incomes {
+            let amount = income.income.parse::<f64>()
+                .map_err(|_| VenueError::ParseError(format!("Invalid income {:?}", income.income)))?;
+            let kind = match income.income_type.as_str() {
+                "REALIZED_PNL" | "COMMISSION" => continue,
+                "TRANSFER" if amount >= 0.0 => TransferKind::Deposit,
+                "TRANSFER" => TransferKind::Withdrawal,
+                "FUNDING_FEE" => TransferKind::Funding,
+                _ => TransferKind::Other,
+            };
+            transfers.push(Transfer { id: income.tran_id.to_string(), asset: income.asset, amount, kind, timestamp: income.time });
+        }
+        Ok(transfers)
+    }
+
+    /// `DELETE /fapi/v1/allOpenOrders` for each symbol with open orders,
+    /// as Binance only cancels one symbol at a time
+    async fn cancel_all_orders(&self) -> Result<(), HftError> {
+        let open: Vec<BinanceOpenOrder> = self.signed_request(reqwest::Method::GET, "/v1/openOrders", &[]).await?;
+        let mut symbols: Vec<String> = open.into_iter().map(|order| order.symbol).collect();
+        symbols.sort();
+        symbols.dedup();
+
+        let mut failed = Vec::new();
+        for symbol in &symbols {
+            let params = [("symbol", symbol.clone())];
+            if let Err(e) = self.signed_request::<serde_json::Value>(reqwest::Method::DELETE, "/v1/allOpenOrders", &params).await {
+                error!(symbol = %symbol, error = %e, "Failed to cancel open orders on Binance");
+                failed.push(format!("{}: {}", symbol, e));
+            }
+        }
+        if !failed.is_empty() {
+            return Err(VenueError::OrderSubmissionFailed(format!("Cancel all failed for {}", failed.join(", "))).into());
+        }
+        info!(symbols = ?symbols, "Cancelled all open orders on Binance");
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<(), HftError> {
+        self.close_market_streams().await;
+        if let Some(task) = self.user_stream.lock().await.take() {
+            task.abort();
+        }
+        Ok(())
     }
 }
 
@@ -263,12 +1204,15 @@ async fn test_binance_invalid_order_quantity() {
     );
 
     let order = Order {
-        symbol: "BTCUSDT".to_string(),
+        symbol: "BTCUSDT".into(),
         side: OrderSide::Buy,
         quantity: -1.0, // Invalid quantity
         price: 50000.0,
-        venue: "BINANCE".to_string(),
+        venue: "BINANCE".into(),
         order_type: OrderType::Limit,
+        expire_after: None,
+        strategy: None,
+        bypass_kill_switch: false,
     };
 
     let result = venue.submit_order(order).await;
@@ -289,12 +1233,15 @@ async fn test_binance_invalid_limit_price() {
     );
 
     let order = Order {
-        symbol: "BTCUSDT".to_string(),
+        symbol: "BTCUSDT".into(),
         side: OrderSide::Buy,
         quantity: 1.0,
         price: 0.0, // Invalid price for limit order
-        venue: "BINANCE".to_string(),
+        venue: "BINANCE".into(),
         order_type: OrderType::Limit,
+        expire_after: None,
+        strategy: None,
+        bypass_kill_switch: false,
     };
 
     let result = venue.submit_order(order).await;
@@ -310,22 +1257,58 @@ async fn test_binance_invalid_limit_price() {
 #[tokio::test]
 async fn test_market_order_zero_price() {
     // Market orders can have a zero price
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
     let venue = BinanceVenue::new(
         "fake_api_key".to_string(),
         "fake_api_secret".to_string(),
-    );
+    ).with_rest_url(exchange.rest_url());
 
     let order = Order {
-        symbol: "BTCUSDT".to_string(),
+        symbol: "BTCUSDT".into(),
         side: OrderSide::Buy,
         quantity: 1.0,
         price: 0.0, // Valid for market orders
-        venue: "BINANCE".to_string(),
+        venue: "BINANCE".into(),
         order_type: OrderType::Market,
+        expire_after: None,
+        strategy: None,
+        bypass_kill_switch: false,
     };
 
     let result = venue.submit_order(order).await;
     assert!(result.is_ok());
+    let requests = exchange.requests();
+    assert_eq!(requests[0].params["type"], "MARKET");
+    assert!(!requests[0].params.contains_key("price"));
+}
+
+#[test]
+fn test_request_signature() {
+    // Example from the Binance API documentation
+    let venue = BinanceVenue::new(
+        "fake_api_key".to_string(),
+        "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j".to_string(),
+    );
+
+    assert_eq!(
+        sign(&venue.api_secret, "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559"),
+        "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71"
+    );
+}
+
+#[test]
+fn test_parse_kline() {
+    let rows: Vec<Vec<serde_json::Value>> = serde_json::from_str(r#"[
+        [1499040000000, "0.01634790", "0.80000000", "0.01575800", "0.01577100", "148976.11427815",
+         1499644799999, "2434.19055334", 308, "1756.87402397", "28.46694368", "0"]
+    ]"#).unwrap();
+
+    let candle = parse_kline(&rows[0]).unwrap();
+    assert_eq!(candle.open_time, 1499040000000);
+    assert_eq!(candle.high, 0.8);
+    assert_eq!(candle.close, 0.015771);
+    assert_eq!(candle.volume, 148976.11427815);
+    assert!(parse_kline(&rows[0][..3]).is_none());
 }
 
 #[tokio::test]
@@ -342,26 +1325,410 @@ async fn test_venue_with_quote_sender() {
     // The actual connection would be tested in an integration test with proper mocking.
 
     assert_eq!(venue.name().await, "BINANCE_FUTURES");
+}
+
+#[tokio::test]
+async fn test_quotes_streamed_from_fake_exchange() {
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    let (tx, mut rx) = mpsc::channel::<Quote>(100);
+    let events = EventBus::default();
+    let mut venue_events = events.subscribe();
+    let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+        .with_ws_url(exchange.ws_url())
+        .with_quote_sender(tx)
+        .with_event_bus(events)
+        .with_reconnect_policy(ReconnectPolicy::default().with_backoff(Duration::from_millis(10), Duration::from_millis(50)));
+
+    venue.subscribe_quotes(vec!["BTCUSDT".to_string()]).await.unwrap();
+    exchange.wait_for_connections(1).await;
+    assert_eq!(exchange.streams(), vec!["btcusdt@bookTicker"]);
+    assert!(matches!(venue_events.recv().await.unwrap(), EngineEvent::VenueConnected { .. }));
+
+    // Garbage, invalid prices and unsubscribed symbols never become quotes
+    exchange.send_raw("not json");
+    exchange.push_book_ticker("BTCUSDT", 0.0, 1.0, 50001.0, 2.0, 1);
+    exchange.push_book_ticker("ETHUSDT", 3000.0, 1.0, 3001.0, 2.0, 2);
+    exchange.push_book_ticker("BTCUSDT", 50000.0, 1.5, 50001.0, 2.0, 3);
+
+    let quote = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+    assert_eq!(quote.symbol, "BTCUSDT");
+    assert_eq!((quote.bid, quote.bid_size, quote.ask, quote.ask_size), (50000.0, 1.5, 50001.0, 2.0));
+    assert_eq!(quote.timestamp, 3);
+
+    // The stream dropping is reported, and the venue reconnects by itself
+    exchange.drop_connections();
+    let event = tokio::time::timeout(Duration::from_secs(5), venue_events.recv()).await.unwrap().unwrap();
+    assert!(matches!(event, EngineEvent::VenueDisconnected { .. }));
+    let event = tokio::time::timeout(Duration::from_secs(5), venue_events.recv()).await.unwrap().unwrap();
+    assert!(matches!(event, EngineEvent::VenueConnected { .. }));
+    exchange.wait_for_connections(1).await;
+    assert_eq!(exchange.connects(), 2);
+    assert_eq!(exchange.streams(), vec!["btcusdt@bookTicker"]);
+    exchange.push_book_ticker("BTCUSDT", 50010.0, 1.0, 50011.0, 1.0, 4);
+    let quote = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+    assert_eq!(quote.bid, 50010.0);
+}
+
+#[tokio::test]
+async fn test_unsubscribe_quotes_on_fake_exchange() {
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    let (tx, mut rx) = mpsc::channel::<Quote>(100);
+    let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+        .with_ws_url(exchange.ws_url())
+        .with_quote_sender(tx);
+
+    venue.subscribe_quotes(vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]).await.unwrap();
+    exchange.wait_for_connections(1).await;
+
+    // One stream is dropped from the live connection
+    venue.unsubscribe_quotes(vec!["ETHUSDT".to_string()]).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while exchange.streams() != vec!["btcusdt@bookTicker"] {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }).await.unwrap();
+    exchange.push_book_ticker("ETHUSDT", 3000.0, 1.0, 3001.0, 2.0, 1);
+    exchange.push_book_ticker("BTCUSDT", 50000.0, 1.0, 50001.0, 2.0, 2);
+    let quote = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+    assert_eq!(quote.symbol, "BTCUSDT");
 
-    // Testing that submit_order still works with the quote sender configured
+    // Dropping the last stream closes the connection
+    venue.unsubscribe_quotes(vec!["BTCUSDT".to_string()]).await.unwrap();
+    exchange.wait_for_connections(0).await;
+    exchange.push_book_ticker("BTCUSDT", 50000.0, 1.0, 50001.0, 2.0, 3);
+    assert!(tokio::time::timeout(Duration::from_millis(100), rx.recv()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_streams_spread_over_connections_and_added_live() {
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    let (tx, mut rx) = mpsc::channel::<Quote>(100);
+    let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+        .with_ws_url(exchange.ws_url())
+        .with_quote_sender(tx)
+        .with_max_streams_per_connection(2);
+    let symbols = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    venue.subscribe_quotes(symbols(&["BTCUSDT", "ETHUSDT", "SOLUSDT", "BTCUSDT"])).await.unwrap();
+    exchange.wait_for_connections(2).await;
+    assert_eq!(exchange.streams(), vec!["solusdt@bookTicker"]);
+
+    // Room on the second connection takes a new symbol without reconnecting;
+    // symbols already streamed are left alone
+    venue.subscribe_quotes(symbols(&["ETHUSDT", "XRPUSDT"])).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while exchange.streams() != vec!["solusdt@bookTicker", "xrpusdt@bookTicker"] {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }).await.unwrap();
+    assert_eq!(exchange.connects(), 2);
+    exchange.push_book_ticker("XRPUSDT", 0.5, 100.0, 0.51, 100.0, 1);
+    let quote = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+    assert_eq!(quote.symbol, "XRPUSDT");
+
+    // Once every connection is full, another is opened
+    venue.subscribe_quotes(symbols(&["ADAUSDT"])).await.unwrap();
+    exchange.wait_for_connections(3).await;
+    assert_eq!(exchange.streams(), vec!["adausdt@bookTicker"]);
+}
+
+#[tokio::test]
+async fn test_connections_through_proxies() {
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    let proxy = crate::mocks::fake_proxy::FakeProxy::start().await;
+    let exchange_addr = exchange.rest_url().trim_start_matches("http://").trim_end_matches("/fapi").to_string();
+
+    for scheme in ["http", "socks5h"] {
+        let (tx, mut rx) = mpsc::channel::<Quote>(100);
+        let transport = VenueTransport::default().with_proxy(crate::venues::ProxyConfig::parse(&proxy.url(scheme)).unwrap());
+        let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+            .with_ws_url(exchange.ws_url())
+            .with_rest_url(exchange.rest_url())
+            .with_quote_sender(tx)
+            .with_transport(transport);
+
+        venue.subscribe_quotes(vec!["BTCUSDT".to_string()]).await.unwrap();
+        exchange.wait_for_connections(1).await;
+        exchange.push_book_ticker("BTCUSDT", 50000.0, 1.0, 50001.0, 2.0, 1);
+        let quote = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+        assert_eq!(quote.bid, 50000.0);
+
+        venue.cancel_order("17", "BTCUSDT").await.unwrap();
+        venue.stop().await.unwrap();
+        exchange.wait_for_connections(0).await;
+        // Both the stream and the REST call went through the proxy
+        assert_eq!(proxy.targets(), vec![exchange_addr.clone(); if scheme == "http" { 2 } else { 4 }]);
+    }
+}
+
+#[tokio::test]
+async fn test_rest_order_endpoints_on_fake_exchange() {
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+        .with_rest_url(exchange.rest_url());
+
+    venue.cancel_order("17", "BTCUSDT").await.unwrap();
     let order = Order {
-        symbol: "BTCUSDT".to_string(),
+        symbol: "BTCUSDT".into(),
+        side: OrderSide::Sell,
+        quantity: 0.5,
+        price: 50100.0,
+        venue: "BINANCE_FUTURES".into(),
+        order_type: OrderType::Limit,
+        expire_after: None,
+        strategy: None,
+        bypass_kill_switch: false,
+    };
+    assert_eq!(venue.amend_order("17", &order).await.unwrap(), "17");
+
+    let requests = exchange.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].method, "DELETE /fapi/v1/order");
+    assert_eq!(requests[0].api_key.as_deref(), Some("key"));
+    assert!(requests[0].params.contains_key("signature"));
+    assert_eq!(requests[1].method, "PUT /fapi/v1/order");
+    assert_eq!(requests[1].params["side"], "SELL");
+    assert_eq!(requests[1].params["price"], "50100");
+
+    exchange.reject_orders(400, -2011, "Unknown order sent.");
+    let result = venue.cancel_order("17", "BTCUSDT").await;
+    assert!(matches!(result, Err(HftError::Venue(VenueError::UnknownOrder(_)))));
+    exchange.reject_orders(401, -2015, "Invalid API-key, IP, or permissions for action.");
+    let result = venue.amend_order("17", &order).await;
+    assert!(matches!(result, Err(HftError::Venue(VenueError::AuthenticationFailed(_)))));
+}
+
+#[tokio::test]
+async fn test_ws_order_entry_on_fake_exchange() {
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+        .with_rest_url(exchange.rest_url())
+        .with_ws_order_entry(exchange.ws_api_url());
+
+    let order = Order {
+        symbol: "BTCUSDT".into(),
         side: OrderSide::Buy,
         quantity: 1.0,
         price: 50000.0,
-        venue: "BINANCE".to_string(),
+        venue: "BINANCE_FUTURES".into(),
         order_type: OrderType::Limit,
+        expire_after: None,
+        strategy: None,
+        bypass_kill_switch: false,
     };
+    assert_eq!(venue.submit_order(order.clone()).await.unwrap(), "1");
+    assert_eq!(venue.submit_order(order).await.unwrap(), "2");
+
+    let requests = exchange.requests();
+    assert_eq!(requests[0].method, "order.place");
+    assert_eq!(requests[0].params["type"], "LIMIT");
+    assert_eq!(requests[0].api_key.as_deref(), Some("key"));
+}
 
+#[tokio::test]
+async fn test_orders_fall_back_to_rest_without_ws_session() {
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    // Nothing listens on the WS API address
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let dead_url = format!("ws://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+        .with_rest_url(exchange.rest_url())
+        .with_ws_order_entry(dead_url);
+
+    let order = Order {
+        symbol: "BTCUSDT".into(),
+        side: OrderSide::Buy,
+        quantity: 1.0,
+        price: 50000.0,
+        venue: "BINANCE_FUTURES".into(),
+        order_type: OrderType::Limit,
+        expire_after: None,
+        strategy: None,
+        bypass_kill_switch: false,
+    };
+    assert_eq!(venue.submit_order(order.clone()).await.unwrap(), "1");
+    let requests = exchange.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "POST /fapi/v1/order");
+    assert_eq!(requests[0].params["type"], "LIMIT");
+    assert_eq!(requests[0].params["timeInForce"], "GTC");
+    assert!(requests[0].params.contains_key("signature"));
+
+    // Rejections surface instead of a made-up order ID
+    exchange.reject_orders(400, -2019, "Margin is insufficient.");
     let result = venue.submit_order(order).await;
-    assert!(result.is_ok());
+    assert!(matches!(result, Err(HftError::Venue(VenueError::OrderSubmissionFailed(msg))) if msg.contains("-2019")));
+}
+
+#[tokio::test]
+async fn test_cancel_all_orders_per_symbol_on_fake_exchange() {
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+        .with_rest_url(exchange.rest_url());
+    exchange.set_open_orders(vec![
+        serde_json::json!({ "orderId": 1, "symbol": "BTCUSDT" }),
+        serde_json::json!({ "orderId": 2, "symbol": "ETHUSDT" }),
+        serde_json::json!({ "orderId": 3, "symbol": "BTCUSDT" }),
+    ]);
+
+    venue.cancel_all_orders().await.unwrap();
+    let requests = exchange.requests();
+    let cancels: Vec<&str> = requests.iter()
+        .filter(|r| r.method == "DELETE /fapi/v1/allOpenOrders")
+        .map(|r| r.params["symbol"].as_str())
+        .collect();
+    assert_eq!(cancels, ["BTCUSDT", "ETHUSDT"]);
+    assert!(requests.iter().all(|r| r.params.contains_key("signature")));
+
+    // Nothing open, nothing to cancel
+    venue.cancel_all_orders().await.unwrap();
+    assert_eq!(exchange.requests().len(), requests.len() + 1);
+}
+
+#[tokio::test]
+async fn test_candles_from_fake_exchange() {
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    exchange.set_klines(vec![serde_json::json!([60_000, "1.0", "2.0", "0.5", "1.5", "10.0", 119_999])]);
+    let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+        .with_rest_url(exchange.rest_url());
+
+    let candles = venue.fetch_candles("BTCUSDT", Duration::from_secs(60), 10).await.unwrap();
+    assert_eq!(candles.len(), 1);
+    assert_eq!(candles[0].open_time, 60_000);
+    assert_eq!(candles[0].close, 1.5);
+}
+
+#[tokio::test]
+async fn test_trade_history_from_fake_exchange() {
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    exchange.set_user_trades(vec![
+        serde_json::json!({ "symbol": "BTCUSDT", "id": 1, "orderId": 17, "side": "SELL", "price": "50100.5", "qty": "0.25", "commission": "5.01005", "commissionAsset": "USDT", "time": 1_000 }),
+        serde_json::json!({ "symbol": "ETHUSDT", "id": 2, "orderId": 18, "side": "BUY", "price": "3000", "qty": "1", "commission": "0.0001", "commissionAsset": "BNB", "time": 2_000 }),
+    ]);
+    let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+        .with_rest_url(exchange.rest_url());
+
+    let fills = venue.trade_history("BTCUSDT", 0, 5_000).await.unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!((fills[0].order_id.as_str(), fills[0].side, fills[0].quantity, fills[0].price), ("17", OrderSide::Sell, 0.25, 50100.5));
+    assert_eq!(fills[0].commission, Some(Commission { amount: 5.01005, asset: "USDT".to_string() }));
+    let requests = exchange.requests();
+    assert_eq!(requests[0].method, "GET /fapi/v1/userTrades");
+    assert_eq!(requests[0].params["endTime"], "5000");
 }
 
-// In a real test suite, you would add tests for:
-// - WebSocket connection and reconnection
-// - Quote parsing from WebSocket messages
-// - Order submission via REST API
-// - Error handling for network issues
-//
-// These would require mocking the WebSocket and HTTP responses,
-// which is beyond the scope of this implementation.
\ No newline at end of file
+#[tokio::test]
+async fn test_fills_streamed_from_user_data_stream() {
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    let (quote_tx, _quote_rx) = mpsc::channel::<Quote>(100);
+    let (fill_tx, mut fill_rx) = mpsc::channel::<Fill>(100);
+    let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+        .with_ws_url(exchange.ws_url())
+        .with_rest_url(exchange.rest_url())
+        .with_quote_sender(quote_tx)
+        .with_fill_sender(fill_tx);
+
+    // The user data stream opens with the first subscription only
+    venue.subscribe_quotes(vec!["BTCUSDT".to_string()]).await.unwrap();
+    venue.subscribe_quotes(vec!["ETHUSDT".to_string()]).await.unwrap();
+    exchange.wait_for_connections(2).await;
+    let requests = exchange.requests();
+    assert_eq!(requests.iter().filter(|r| r.method == "POST /fapi/v1/listenKey").count(), 1);
+    assert_eq!(requests[0].api_key.as_deref(), Some("key"));
+
+    // Order updates other than trades are not fills
+    exchange.send_raw(r#"{"e":"ORDER_TRADE_UPDATE","T":1,"o":{"s":"BTCUSDT","S":"BUY","x":"NEW","i":7,"l":"0","L":"0","T":1}}"#);
+    exchange.push_order_trade("BTCUSDT", 7, "BUY", 0.5, 50000.0, 2);
+    let fill = tokio::time::timeout(Duration::from_secs(5), fill_rx.recv()).await.unwrap().unwrap();
+    assert_eq!((fill.order_id.as_str(), fill.side, fill.quantity, fill.price, fill.timestamp), ("7", OrderSide::Buy, 0.5, 50000.0, 2));
+    assert_eq!(fill.venue, "BINANCE_FUTURES");
+    assert_eq!(fill.commission, Some(Commission { amount: 0.01, asset: "USDT".to_string() }));
+
+    // An expired listen key is replaced and the stream reopened
+    exchange.send_raw(r#"{"e":"listenKeyExpired","E":3}"#);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while exchange.connects() < 3 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }).await.unwrap();
+    exchange.wait_for_connections(2).await;
+    assert_eq!(exchange.requests().iter().filter(|r| r.method == "POST /fapi/v1/listenKey").count(), 2);
+    exchange.push_order_trade("BTCUSDT", 8, "SELL", 0.5, 50010.0, 4);
+    let fill = tokio::time::timeout(Duration::from_secs(5), fill_rx.recv()).await.unwrap().unwrap();
+    assert_eq!((fill.order_id.as_str(), fill.side), ("8", OrderSide::Sell));
+}
+
+#[tokio::test]
+async fn test_balances_and_transfers_from_fake_exchange() {
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    exchange.set_balances(vec![
+        serde_json::json!({ "asset": "USDT", "balance": "1200.5", "availableBalance": "800.25" }),
+    ]);
+    exchange.set_incomes(vec![
+        serde_json::json!({ "incomeType": "TRANSFER", "income": "-300", "asset": "USDT", "time": 1_000, "tranId": 7 }),
+        serde_json::json!({ "incomeType": "COMMISSION", "income": "-0.5", "asset": "USDT", "time": 2_000, "tranId": 8 }),
+        serde_json::json!({ "incomeType": "FUNDING_FEE", "income": "0.12", "asset": "USDT", "time": 3_000, "tranId": 9 }),
+    ]);
+    let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+        .with_rest_url(exchange.rest_url());
+
+    let balances = venue.balances().await.unwrap();
+    assert_eq!(balances, vec![AssetBalance { asset: "USDT".to_string(), total: 1200.5, available: 800.25 }]);
+    // Commissions come with fills, not as transfers
+    let transfers = venue.transfers(500).await.unwrap();
+    assert_eq!(transfers.iter().map(|t| (t.id.as_str(), t.kind, t.amount)).collect::<Vec<_>>(),
+        vec![("7", TransferKind::Withdrawal, -300.0), ("9", TransferKind::Funding, 0.12)]);
+    assert_eq!(venue.transfers(2_500).await.unwrap().len(), 1);
+    assert!(exchange.requests().iter().any(|r| r.method == "GET /fapi/v1/income" && r.params["startTime"] == "500"));
+}
+
+#[tokio::test]
+async fn test_recorded_frames_replay_through_parser() {
+    let dir = std::env::temp_dir().join(format!("hft_binance_frames_{}", std::process::id()));
+    let path = dir.join("frames.jsonl");
+    let _ = std::fs::remove_file(&path);
+
+    let exchange = crate::mocks::fake_exchange::FakeExchange::start().await;
+    let (tx, mut rx) = mpsc::channel::<Quote>(100);
+    let venue = BinanceVenue::new("key".to_string(), "secret".to_string())
+        .with_ws_url(exchange.ws_url())
+        .with_quote_sender(tx)
+        .with_frame_recorder(crate::venues::FrameRecorder::open(&path).unwrap());
+    venue.subscribe_quotes(vec!["BTCUSDT".to_string()]).await.unwrap();
+    exchange.wait_for_connections(1).await;
+
+    exchange.push_book_ticker("BTCUSDT", 50000.0, 1.0, 50001.0, 2.0, 1);
+    exchange.send_raw(r#"{"result":null,"id":1}"#);
+    exchange.push_book_ticker("BTCUSDT", 50002.0, 1.0, 50003.0, 2.0, 2);
+    let mut live = Vec::new();
+    for _ in 0..2 {
+        live.push(tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap());
+    }
+
+    let mut frames = Vec::new();
+    for _ in 0..100 {
+        frames = crate::venues::frames::read_frames(&path).unwrap();
+        if frames.len() == 3 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[1].text, r#"{"result":null,"id":1}"#);
+
+    // Replaying the capture gives the same quotes as the live stream
+    let replayed = crate::venues::frames::replay(&frames, "BINANCE_FUTURES", parse_book_ticker);
+    assert_eq!(replayed.parsed, live);
+    assert_eq!(replayed.rejected.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![1]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_parse_book_ticker_rejects_invalid_levels() {
+    let frame = r#"{"e":"bookTicker","s":"BTCUSDT","b":"50000.1","B":"0","a":"50000.2","A":"3","T":7}"#;
+    assert!(matches!(parse_book_ticker(frame), Err(VenueError::ParseError(_))));
+    let quote = parse_book_ticker(&frame.replace(r#""B":"0""#, r#""B":"1.5""#)).unwrap();
+    assert_eq!((quote.bid, quote.bid_size, quote.timestamp), (50000.1, 1.5, 7));
+}
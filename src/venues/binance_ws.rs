@@ -0,0 +1,229 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+use tracing::{debug, info, warn};
+
+use crate::error::{HftError, VenueError};
+use super::binance::{api_error, sign, BinanceApiError};
+use super::inbound::InboundMetrics;
+use super::transport::VenueTransport;
+
+/// Binance Futures WebSocket API endpoint for order entry
+pub const WS_API_URL: &str = "wss://ws-fapi.binance.com/ws-fapi/v1";
+
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+type Pending = Arc<Mutex<HashMap<String, oneshot::Sender<Result<Value, HftError>>>>>;
+
+#[derive(Debug, Deserialize)]
+struct WsResponse {
+    id: Option<String>,
+    status: u16,
+    result: Option<Value>,
+    error: Option<BinanceApiError>,
+}
+
+struct Connection {
+    tx: mpsc::UnboundedSender<Message>,
+    pending: Pending,
+    alive: Arc<AtomicBool>,
+}
+
+/// Signed request/response session on the Binance WebSocket API.
+///
+/// The connection is opened on first use and reopened on the next request
+/// after it drops. Responses are matched to requests by `id`. A request
+/// that could not be sent fails with `ConnectionFailed` and is safe to
+/// retry over REST; one that was sent but got no response fails with
+/// `WebSocketError`, since the venue may have acted on it.
+pub(crate) struct WsTradingSession {
+    url: String,
+    api_key: String,
+    api_secret: String,
+    timeout: Duration,
+    transport: VenueTransport,
+    connection: tokio::sync::Mutex<Option<Connection>>,
+    next_id: AtomicU64,
+}
+
+impl std::fmt::Debug for WsTradingSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WsTradingSession").field("url", &self.url).finish_non_exhaustive()
+    }
+}
+
+impl WsTradingSession {
+    pub(crate) fn new(url: String, api_key: String, api_secret: String) -> Self {
+        Self {
+            url,
+            api_key,
+            api_secret,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            transport: VenueTransport::default(),
+            connection: tokio::sync::Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Connect through a proxy or with extra CA certificates
+    pub(crate) fn with_transport(mut self, transport: VenueTransport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    async fn connect(&self) -> Result<Connection, HftError> {
+        let request = self.url.as_str()
+            .into_client_request()
+            .map_err(|e| VenueError::ConnectionFailed(format!("Failed to build request: {}", e)))?;
+        let ws_stream = self.transport.connect_ws(request).await?;
+        info!(url = %self.url, "Binance WS trading session connected");
+
+        let (mut write, mut read) = ws_stream.split();
+        let (tx, mut rx) = mpsc::unbounded_channel::<Message>();
+        let pending: Pending = Arc::new(Mutex::new(HashMap::new()));
+        let alive = Arc::new(AtomicBool::new(true));
+
+        tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                if write.send(message).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let reader_pending = Arc::clone(&pending);
+        let reader_alive = Arc::clone(&alive);
+        let reader_tx = tx.clone();
+        let inbound = InboundMetrics::new("BINANCE_FUTURES", "orders");
+        tokio::spawn(async move {
+            while let Some(Ok(message)) = read.next().await {
+                inbound.on_message(message.len());
+                match message {
+                    Message::Text(text) => {
+                        let response = match serde_json::from_str::<WsResponse>(&text) {
+                            Ok(response) => response,
+                            Err(e) => {
+                                inbound.on_parse_failure();
+                                warn!(error = %e, "Unparseable Binance WS API message");
+                                continue;
+                            }
+                        };
+                        let Some(waiter) = response.id.and_then(|id| reader_pending.lock().unwrap().remove(&id)) else {
+                            continue;
+                        };
+                        let result = match (response.result, response.error) {
+                            (_, Some(err)) => Err(api_error(response.status, err)),
+                            (Some(result), None) => Ok(result),
+                            (None, None) => Err(VenueError::ParseError("Empty Binance WS API response".to_string()).into()),
+                        };
+                        let _ = waiter.send(result);
+                    }
+                    Message::Ping(payload) => {
+                        let _ = reader_tx.send(Message::Pong(payload));
+                    }
+                    Message::Close(_) => break,
+                    _ => {}
+                }
+            }
+
+            warn!("Binance WS trading session closed");
+            reader_alive.store(false, Ordering::Relaxed);
+            for (_, waiter) in reader_pending.lock().unwrap().drain() {
+                let _ = waiter.send(Err(VenueError::WebSocketError("WS trading session closed before response".to_string()).into()));
+            }
+        });
+
+        Ok(Connection { tx, pending, alive })
+    }
+
+    /// Send a signed request and wait for its response
+    pub(crate) async fn request(&self, method: &str, params: &[(&str, String)]) -> Result<Value, HftError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed).to_string();
+
+        // Binance signs the parameters sorted by name
+        let mut signed: BTreeMap<&str, String> = params.iter().cloned().collect();
+        signed.insert("apiKey", self.api_key.clone());
+        signed.insert("timestamp", chrono::Utc::now().timestamp_millis().to_string());
+        let query = signed.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+        signed.insert("signature", sign(&self.api_secret, &query));
+
+        let payload = serde_json::json!({ "id": id, "method": method, "params": signed }).to_string();
+
+        let (waiter_tx, waiter_rx) = oneshot::channel();
+        let pending = {
+            let mut connection = self.connection.lock().await;
+            if !connection.as_ref().is_some_and(|c| c.alive.load(Ordering::Relaxed)) {
+                *connection = Some(self.connect().await?);
+            }
+            let connection = connection.as_ref().expect("connected above");
+            connection.pending.lock().unwrap().insert(id.clone(), waiter_tx);
+            if connection.tx.send(Message::text(payload)).is_err() {
+                connection.alive.store(false, Ordering::Relaxed);
+                connection.pending.lock().unwrap().remove(&id);
+                return Err(VenueError::ConnectionFailed("WS trading session closed".to_string()).into());
+            }
+            Arc::clone(&connection.pending)
+        };
+
+        debug!(id = %id, method = method, "Sent Binance WS API request");
+        match tokio::time::timeout(self.timeout, waiter_rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(VenueError::WebSocketError("WS trading session closed before response".to_string()).into()),
+            Err(_) => {
+                pending.lock().unwrap().remove(&id);
+                Err(VenueError::WebSocketError(format!("{} timed out after {:?}", method, self.timeout)).into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    /// WS API stand-in that accepts one order, then drops the connection
+    /// on the next request
+    async fn fake_ws_api() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+            let Some(Ok(Message::Text(text))) = ws.next().await else { return };
+            let request: Value = serde_json::from_str(&text).unwrap();
+            assert!(request["params"]["signature"].is_string());
+            let response = serde_json::json!({
+                "id": request["id"],
+                "status": 200,
+                "result": { "orderId": 42, "symbol": request["params"]["symbol"] },
+            });
+            ws.send(Message::text(response.to_string())).await.unwrap();
+            let _ = ws.next().await;
+        });
+        format!("ws://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_request_response_and_session_loss() {
+        let session = WsTradingSession::new(fake_ws_api().await, "key".to_string(), "secret".to_string());
+
+        let result = session.request("order.place", &[("symbol", "BTCUSDT".to_string())]).await.unwrap();
+        assert_eq!(result["orderId"], 42);
+
+        // The server hangs up with the request in flight, so its outcome is unknown
+        let result = session.request("order.place", &[("symbol", "BTCUSDT".to_string())]).await;
+        assert!(matches!(result, Err(HftError::Venue(VenueError::WebSocketError(_)))));
+
+        // Nothing is listening any more, so the request never leaves
+        let result = session.request("order.place", &[("symbol", "BTCUSDT".to_string())]).await;
+        assert!(matches!(result, Err(HftError::Venue(VenueError::ConnectionFailed(_)))));
+    }
+}
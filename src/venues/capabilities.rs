@@ -0,0 +1,71 @@
+use std::fmt;
+use serde::Serialize;
+
+/// Where a venue's tick and lot size constraints come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstrumentRules {
+    /// Not known to the engine; orders are sent as given and the venue
+    /// rejects ones that do not fit
+    #[default]
+    Unknown,
+    /// Published by the venue in its instrument metadata
+    VenueMetadata,
+    /// Fixed in the adapter
+    Static,
+}
+
+/// What a venue supports, so the gateways, execution and risk checks can
+/// adapt to it rather than assume Binance. The default supports nothing
+/// beyond plain orders and cancels.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct VenueCapabilities {
+    /// Orders are modified in place; elsewhere an amend is a cancel and a
+    /// new order
+    pub amend: bool,
+    /// Several orders can be placed in one request
+    pub batch_orders: bool,
+    /// Limit orders can be made post-only
+    pub post_only: bool,
+    /// Orders can be sent over a WebSocket session rather than REST
+    pub ws_trading: bool,
+    /// Every open order can be cancelled in one request
+    pub cancel_all: bool,
+    /// Orders per second the venue accepts before rate limiting
+    pub max_orders_per_sec: Option<u32>,
+    pub instrument_rules: InstrumentRules,
+}
+
+impl fmt::Display for VenueCapabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let features = [
+            ("amend", self.amend),
+            ("batch_orders", self.batch_orders),
+            ("post_only", self.post_only),
+            ("ws_trading", self.ws_trading),
+            ("cancel_all", self.cancel_all),
+        ];
+        let supported: Vec<&str> = features.iter().filter(|(_, on)| *on).map(|(name, _)| *name).collect();
+        if supported.is_empty() {
+            write!(f, "plain orders only")?;
+        } else {
+            write!(f, "{}", supported.join(", "))?;
+        }
+        if let Some(rate) = self.max_orders_per_sec {
+            write!(f, "; {} orders/s", rate)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_summarizes_support() {
+        assert_eq!(VenueCapabilities::default().to_string(), "plain orders only");
+        let capabilities = VenueCapabilities { amend: true, cancel_all: true, max_orders_per_sec: Some(30), ..VenueCapabilities::default() };
+        assert_eq!(capabilities.to_string(), "amend, cancel_all; 30 orders/s");
+    }
+}
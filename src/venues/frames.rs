@@ -0,0 +1,163 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::error;
+
+use crate::error::{HftError, VenueError};
+
+/// A raw WebSocket text frame exactly as the venue sent it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// Milliseconds since the Unix epoch when the frame was read
+    pub received_at: u64,
+    pub venue: String,
+    pub text: String,
+}
+
+/// Records raw venue frames to a file, one JSON frame per line.
+///
+/// Frames are written by a dedicated thread so the read loop never waits on
+/// disk. Clones share the same file.
+#[derive(Debug, Clone)]
+pub struct FrameRecorder {
+    tx: mpsc::UnboundedSender<RecordedFrame>,
+}
+
+/// Append what is sent on the returned channel to `path` as JSON lines,
+/// from a thread named `name` that exits once every sender is dropped
+pub(crate) fn spawn_writer<T: Serialize + Send + 'static>(path: &Path, name: &str) -> Result<mpsc::UnboundedSender<T>, HftError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<T>();
+    std::thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            while let Some(record) = rx.blocking_recv() {
+                let mut out = String::new();
+                for record in std::iter::once(record).chain(std::iter::from_fn(|| rx.try_recv().ok())) {
+                    if let Ok(line) = serde_json::to_string(&record) {
+                        out.push_str(&line);
+                        out.push('\n');
+                    }
+                }
+                if let Err(e) = file.write_all(out.as_bytes()) {
+                    error!(error = %e, "Failed to write recording");
+                }
+            }
+        })?;
+    Ok(tx)
+}
+
+/// Read a recording of JSON lines, skipping blank lines
+pub(crate) fn read_lines<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, HftError> {
+    BufReader::new(File::open(path)?)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.as_ref().is_ok_and(|l| l.trim().is_empty()))
+        .map(|(i, line)| {
+            serde_json::from_str(&line?)
+                .map_err(|e| HftError::Config(format!("Invalid recording {} at line {}: {}", path.display(), i + 1, e)))
+        })
+        .collect()
+}
+
+impl FrameRecorder {
+    /// Append to the recording at `path`, creating it if needed
+    pub fn open(path: &Path) -> Result<Self, HftError> {
+        Ok(Self { tx: spawn_writer(path, "frame-recorder")? })
+    }
+
+    pub fn record(&self, venue: &str, text: &str) {
+        let _ = self.tx.send(RecordedFrame {
+            received_at: chrono::Utc::now().timestamp_millis() as u64,
+            venue: venue.to_string(),
+            text: text.to_string(),
+        });
+    }
+}
+
+/// Read a recording made by [`FrameRecorder`], oldest frame first
+pub fn read_frames(path: &Path) -> Result<Vec<RecordedFrame>, HftError> {
+    read_lines(path)
+}
+
+/// Outcome of feeding a recording back through a venue's parser
+#[derive(Debug)]
+pub struct Replay<T> {
+    /// Messages parsed, in frame order
+    pub parsed: Vec<T>,
+    /// Frames the parser rejected, by index into the recording
+    pub rejected: Vec<(usize, VenueError)>,
+}
+
+/// Feed recorded frames through `parse`. Frames from other venues are
+/// skipped.
+pub fn replay<T>(frames: &[RecordedFrame], venue: &str, parse: impl Fn(&str) -> Result<T, VenueError>) -> Replay<T> {
+    let mut replay = Replay { parsed: Vec::new(), rejected: Vec::new() };
+    for (i, frame) in frames.iter().enumerate().filter(|(_, f)| f.venue == venue) {
+        match parse(&frame.text) {
+            Ok(message) => replay.parsed.push(message),
+            Err(e) => replay.rejected.push((i, e)),
+        }
+    }
+    replay
+}
+
+/// Where raw frames are recorded
+#[derive(Debug, Clone)]
+pub struct FrameRecordingConfig {
+    pub path: PathBuf,
+}
+
+impl FrameRecordingConfig {
+    /// Read `HFT_RECORD_FRAMES`; returns `None` when recording is off
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            path: PathBuf::from(std::env::var("HFT_RECORD_FRAMES").ok()?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("hft_frames_{}", std::process::id()));
+        let path = dir.join("frames.jsonl");
+        let _ = std::fs::remove_file(&path);
+
+        let recorder = FrameRecorder::open(&path).unwrap();
+        recorder.record("A", "1");
+        recorder.record("B", "2");
+        recorder.record("A", "x");
+        drop(recorder);
+
+        // The writer thread drains the channel after the last handle drops
+        let mut frames = Vec::new();
+        for _ in 0..100 {
+            frames = read_frames(&path).unwrap();
+            if frames.len() == 3 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(frames.iter().map(|f| f.text.as_str()).collect::<Vec<_>>(), ["1", "2", "x"]);
+
+        let replayed = replay(&frames, "A", |text| {
+            text.parse::<u32>().map_err(|e| VenueError::ParseError(e.to_string()))
+        });
+        assert_eq!(replayed.parsed, vec![1]);
+        assert_eq!(replayed.rejected.len(), 1);
+        assert_eq!(replayed.rejected[0].0, 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
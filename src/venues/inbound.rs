@@ -0,0 +1,54 @@
+use prometheus::{Counter, Histogram};
+
+use crate::metrics::{VENUE_MESSAGES, VENUE_MESSAGE_BYTES, VENUE_PARSE_FAILURES};
+
+/// Counts what arrives on one venue connection, so a feed going quiet or
+/// turning to garbage shows up before the books it drives go stale
+#[derive(Debug, Clone)]
+pub(crate) struct InboundMetrics {
+    messages: Counter,
+    parse_failures: Counter,
+    bytes: Histogram,
+}
+
+impl InboundMetrics {
+    /// Metrics for `connection` on `venue`; the connection label should stay
+    /// the same across reconnects
+    pub(crate) fn new(venue: &str, connection: &str) -> Self {
+        Self {
+            messages: VENUE_MESSAGES.with_label_values(&[venue, connection]),
+            parse_failures: VENUE_PARSE_FAILURES.with_label_values(&[venue, connection]),
+            bytes: VENUE_MESSAGE_BYTES.with_label_values(&[venue, connection]),
+        }
+    }
+
+    /// A message of `len` bytes arrived
+    pub(crate) fn on_message(&self, len: usize) {
+        self.messages.inc();
+        self.bytes.observe(len as f64);
+    }
+
+    pub(crate) fn on_parse_failure(&self) {
+        self.parse_failures.inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inbound_metrics_per_connection() {
+        let metrics = InboundMetrics::new("INBOUND_TEST", "market-0");
+        metrics.on_message(100);
+        metrics.on_message(3000);
+        metrics.on_parse_failure();
+        InboundMetrics::new("INBOUND_TEST", "market-0").on_message(10);
+
+        assert_eq!(VENUE_MESSAGES.with_label_values(&["INBOUND_TEST", "market-0"]).get(), 3.0);
+        assert_eq!(VENUE_PARSE_FAILURES.with_label_values(&["INBOUND_TEST", "market-0"]).get(), 1.0);
+        let bytes = VENUE_MESSAGE_BYTES.with_label_values(&["INBOUND_TEST", "market-0"]);
+        assert_eq!((bytes.get_sample_count(), bytes.get_sample_sum()), (3, 3110.0));
+        assert_eq!(VENUE_MESSAGES.with_label_values(&["INBOUND_TEST", "market-1"]).get(), 0.0);
+    }
+}
@@ -0,0 +1,305 @@
+//! Simulated latency for evaluating the engine from another location.
+//!
+//! Wrapping a venue delays its quotes on their way in and its order
+//! requests on their way out by a fixed amount plus random jitter, so a
+//! strategy can be run live, typically in shadow, as it would behave with a
+//! longer path to the exchange, without moving the deployment.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use async_trait::async_trait;
+use rand::Rng;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use crate::error::HftError;
+use crate::rng::{component_rng, SimRng};
+use crate::types::{Candle, Order, Quote};
+use crate::venues::{MarginMode, MarginSettings, PositionRisk, VenueAdapter, VenueCapabilities};
+
+/// A fixed delay plus up to `jitter` more, drawn uniformly
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Delay {
+    pub fixed: Duration,
+    pub jitter: Duration,
+}
+
+impl Delay {
+    /// `MS` or `MS+JITTER_MS`
+    fn parse(spec: &str) -> Option<Self> {
+        let (fixed, jitter) = spec.split_once('+').unwrap_or((spec, "0"));
+        let millis = |ms: &str| ms.trim().parse::<f64>().ok().filter(|ms| *ms >= 0.0).map(|ms| Duration::from_secs_f64(ms / 1000.0));
+        Some(Self { fixed: millis(fixed)?, jitter: millis(jitter)? })
+    }
+
+    fn sample(&self, rng: &Mutex<SimRng>) -> Duration {
+        if self.jitter.is_zero() {
+            return self.fixed;
+        }
+        self.fixed + self.jitter.mul_f64(rng.lock().unwrap().random())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.fixed.is_zero() && self.jitter.is_zero()
+    }
+}
+
+/// Delays injected into one venue's traffic
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct VenueLatency {
+    /// Added to each quote before the engine sees it
+    pub quotes: Delay,
+    /// Added to each order request before it reaches the venue
+    pub orders: Delay,
+}
+
+/// Injected latency by venue name, `*` applying to venues without their own
+#[derive(Debug, Clone, Default)]
+pub struct LatencyInjection {
+    venues: HashMap<String, VenueLatency>,
+    /// Seed for the jitter, each venue drawing from its own streams
+    seed: u64,
+}
+
+impl LatencyInjection {
+    pub fn with_venue(mut self, venue: &str, latency: VenueLatency) -> Self {
+        self.venues.insert(venue.to_uppercase(), latency);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Comma separated `VENUE=QUOTES/ORDERS` entries, each delay in
+    /// milliseconds with optional `+JITTER`, e.g.
+    /// `BINANCE_FUTURES=5+2/8+3,*=1/1`
+    fn parse(spec: &str) -> Self {
+        let mut config = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=').and_then(|(venue, delays)| {
+                let (quotes, orders) = delays.split_once('/')?;
+                Some((venue.trim(), VenueLatency { quotes: Delay::parse(quotes)?, orders: Delay::parse(orders)? }))
+            });
+            match parsed {
+                Some((venue, latency)) if !venue.is_empty() => config = config.with_venue(venue, latency),
+                _ => warn!(entry = entry, "Ignoring malformed latency injection entry"),
+            }
+        }
+        config
+    }
+
+    /// Read `HFT_INJECT_LATENCY` (see [`parse`](Self::parse)), seeded from
+    /// `HFT_SEED`; returns `None` when unset
+    pub fn from_env() -> Option<Self> {
+        let config = Self::parse(&std::env::var("HFT_INJECT_LATENCY").ok()?);
+        Some(config.with_seed(crate::rng::seed_from_env()))
+    }
+
+    pub fn for_venue(&self, venue: &str) -> Option<VenueLatency> {
+        self.venues.get(&venue.to_uppercase()).or_else(|| self.venues.get("*")).copied()
+    }
+
+    fn rng(&self, venue: &str, stream: &str) -> Mutex<SimRng> {
+        Mutex::new(component_rng(self.seed, &format!("latency:{}:{}", venue, stream)))
+    }
+
+    /// `venue` with its configured latency injected: the adapter wrapped so
+    /// order requests are delayed, and `quotes` relayed with delay into the
+    /// returned receiver. Venues without latency configured are returned
+    /// as they are.
+    pub fn apply(&self, venue: Arc<dyn VenueAdapter>, name: &str, quotes: mpsc::Receiver<Quote>) -> (Arc<dyn VenueAdapter>, mpsc::Receiver<Quote>) {
+        let Some(latency) = self.for_venue(name) else {
+            return (venue, quotes);
+        };
+        info!(venue = %name, quotes = ?latency.quotes, orders = ?latency.orders, "Injecting latency");
+        let quotes = if latency.quotes.is_zero() {
+            quotes
+        } else {
+            delay_quotes(quotes, latency.quotes, self.rng(name, "quotes"))
+        };
+        let venue: Arc<dyn VenueAdapter> = if latency.orders.is_zero() {
+            venue
+        } else {
+            Arc::new(DelayedVenue { inner: venue, orders: latency.orders, rng: self.rng(name, "orders") })
+        };
+        (venue, quotes)
+    }
+}
+
+/// Relay `quotes` into a new channel, each one `delay` after it arrived.
+/// Quotes keep their order, so one drawing less jitter than the quote
+/// before it waits for that one.
+fn delay_quotes(mut quotes: mpsc::Receiver<Quote>, delay: Delay, rng: Mutex<SimRng>) -> mpsc::Receiver<Quote> {
+    let (tx, rx) = mpsc::channel(quotes.max_capacity());
+    let (due_tx, mut due_rx) = mpsc::channel::<(Instant, Quote)>(quotes.max_capacity());
+    tokio::spawn(async move {
+        let mut last = Instant::now();
+        while let Some(quote) = quotes.recv().await {
+            last = last.max(Instant::now() + delay.sample(&rng));
+            if due_tx.send((last, quote)).await.is_err() {
+                break;
+            }
+        }
+    });
+    tokio::spawn(async move {
+        while let Some((due, quote)) = due_rx.recv().await {
+            tokio::time::sleep_until(due).await;
+            if tx.send(quote).await.is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// A venue whose order requests are held back before being sent
+struct DelayedVenue {
+    inner: Arc<dyn VenueAdapter>,
+    orders: Delay,
+    rng: Mutex<SimRng>,
+}
+
+impl DelayedVenue {
+    async fn delay(&self) {
+        tokio::time::sleep(self.orders.sample(&self.rng)).await;
+    }
+}
+
+#[async_trait]
+impl VenueAdapter for DelayedVenue {
+    async fn name(&self) -> String {
+        self.inner.name().await
+    }
+
+    fn capabilities(&self) -> VenueCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn subscribe_quotes(&self, symbols: Vec<String>) -> Result<(), HftError> {
+        self.inner.subscribe_quotes(symbols).await
+    }
+
+    async fn unsubscribe_quotes(&self, symbols: Vec<String>) -> Result<(), HftError> {
+        self.inner.unsubscribe_quotes(symbols).await
+    }
+
+    async fn submit_order(&self, order: Order) -> Result<String, HftError> {
+        self.delay().await;
+        self.inner.submit_order(order).await
+    }
+
+    async fn cancel_order(&self, order_id: &str, symbol: &str) -> Result<(), HftError> {
+        self.delay().await;
+        self.inner.cancel_order(order_id, symbol).await
+    }
+
+    async fn amend_order(&self, order_id: &str, order: &Order) -> Result<String, HftError> {
+        self.delay().await;
+        self.inner.amend_order(order_id, order).await
+    }
+
+    async fn cancel_all_orders(&self) -> Result<(), HftError> {
+        self.delay().await;
+        self.inner.cancel_all_orders().await
+    }
+
+    async fn open_orders(&self) -> Result<Vec<String>, HftError> {
+        self.inner.open_orders().await
+    }
+
+    async fn fetch_candles(&self, symbol: &str, interval: Duration, limit: usize) -> Result<Vec<Candle>, HftError> {
+        self.inner.fetch_candles(symbol, interval, limit).await
+    }
+
+    async fn server_time(&self) -> Result<u64, HftError> {
+        self.inner.server_time().await
+    }
+
+    async fn verify_credentials(&self) -> Result<(), HftError> {
+        self.inner.verify_credentials().await
+    }
+
+    async fn tradable_symbols(&self) -> Result<Vec<String>, HftError> {
+        self.inner.tradable_symbols().await
+    }
+
+    async fn set_leverage(&self, symbol: &str, leverage: u32) -> Result<(), HftError> {
+        self.inner.set_leverage(symbol, leverage).await
+    }
+
+    async fn set_margin_mode(&self, symbol: &str, mode: MarginMode) -> Result<(), HftError> {
+        self.inner.set_margin_mode(symbol, mode).await
+    }
+
+    async fn margin_settings(&self, symbol: &str) -> Result<MarginSettings, HftError> {
+        self.inner.margin_settings(symbol).await
+    }
+
+    async fn position_risks(&self) -> Result<Vec<PositionRisk>, HftError> {
+        self.inner.position_risks().await
+    }
+
+    async fn stop(&self) -> Result<(), HftError> {
+        self.inner.stop().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::mock_venue::{MockVenue, MockVenueConfig};
+    use crate::types::{OrderSide, OrderType};
+
+    #[test]
+    fn test_parse_config() {
+        let config = LatencyInjection::parse("binance_futures=5+2/8, *=1/0, bad=5, =1/1");
+        assert_eq!(config.for_venue("BINANCE_FUTURES"), Some(VenueLatency {
+            quotes: Delay { fixed: Duration::from_millis(5), jitter: Duration::from_millis(2) },
+            orders: Delay { fixed: Duration::from_millis(8), jitter: Duration::ZERO },
+        }));
+        assert_eq!(config.for_venue("OTHER").unwrap().quotes.fixed, Duration::from_millis(1));
+        assert!(LatencyInjection::default().for_venue("OTHER").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_quotes_and_orders_delayed_in_order() {
+        let latency = VenueLatency {
+            quotes: Delay { fixed: Duration::from_millis(20), jitter: Duration::from_millis(10) },
+            orders: Delay { fixed: Duration::from_millis(10), jitter: Duration::ZERO },
+        };
+        let injection = LatencyInjection::default().with_venue("MOCK", latency).with_seed(7);
+        let venue: Arc<dyn VenueAdapter> = Arc::new(MockVenue::new("MOCK", MockVenueConfig { latency_ms: 0, error_probability: 0.0, disconnect_probability: 0.0, ..MockVenueConfig::default() }));
+        let (quote_tx, quote_rx) = mpsc::channel(16);
+        let (venue, mut quote_rx) = injection.apply(venue, "MOCK", quote_rx);
+
+        let start = Instant::now();
+        for bid in 0..5 {
+            let quote = Quote { symbol: "BTCUSDT".into(), bid: bid as f64, ask: 1.0, bid_size: 1.0, ask_size: 1.0, venue: "MOCK".into(), timestamp: 0 };
+            quote_tx.send(quote).await.unwrap();
+        }
+        for bid in 0..5 {
+            assert_eq!(quote_rx.recv().await.unwrap().bid, bid as f64);
+        }
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        let start = Instant::now();
+        let order = Order {
+            symbol: "BTCUSDT".into(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            price: 50000.0,
+            venue: "MOCK".into(),
+            order_type: OrderType::Limit,
+            expire_after: None,
+            strategy: None,
+            bypass_kill_switch: false,
+        };
+        venue.submit_order(order).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        assert_eq!(venue.name().await, "MOCK");
+    }
+}
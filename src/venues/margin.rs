@@ -0,0 +1,182 @@
+//! Per-symbol leverage and margin mode.
+//!
+//! Leverage and margin mode live on the venue account, where a manual
+//! change or a previous deployment can leave them different from what risk
+//! limits assume. [`MarginConfig`] lists the settings each symbol should
+//! have; [`apply`] sets them at startup and reads them back to verify.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::error::{HftError, VenueError};
+use super::VenueAdapter;
+
+/// How collateral backs a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarginMode {
+    /// Each position has its own margin, limiting losses to it
+    Isolated,
+    /// Positions share the account's margin
+    Cross,
+}
+
+impl fmt::Display for MarginMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarginMode::Isolated => write!(f, "isolated"),
+            MarginMode::Cross => write!(f, "cross"),
+        }
+    }
+}
+
+/// Leverage and margin mode of a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarginSettings {
+    pub leverage: u32,
+    pub mode: MarginMode,
+}
+
+impl fmt::Display for MarginSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x {}", self.leverage, self.mode)
+    }
+}
+
+impl MarginSettings {
+    /// Parse `<leverage>x:<isolated|cross>`, e.g. `10x:isolated`
+    fn parse(s: &str) -> Option<Self> {
+        let (leverage, mode) = s.split_once(':')?;
+        let leverage = leverage.trim().trim_end_matches('x').parse().ok().filter(|l| *l > 0)?;
+        let mode = match mode.trim() {
+            "isolated" => MarginMode::Isolated,
+            "cross" => MarginMode::Cross,
+            _ => return None,
+        };
+        Some(Self { leverage, mode })
+    }
+}
+
+/// An open position as the venue margins it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionRisk {
+    pub symbol: String,
+    /// Signed size, negative when short
+    pub quantity: f64,
+    pub mark_price: f64,
+    /// Mark price at which the venue liquidates the position; zero when it
+    /// cannot be liquidated, e.g. fully collateralized
+    pub liquidation_price: f64,
+}
+
+impl PositionRisk {
+    /// How far the mark price is from liquidation, as a percentage of it;
+    /// `None` when the position cannot be liquidated
+    pub fn liquidation_distance_pct(&self) -> Option<f64> {
+        (self.liquidation_price > 0.0 && self.mark_price > 0.0)
+            .then(|| (self.mark_price - self.liquidation_price).abs() / self.mark_price * 100.0)
+    }
+}
+
+/// Settings each venue's symbols should have
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MarginConfig {
+    venues: BTreeMap<String, BTreeMap<String, MarginSettings>>,
+}
+
+impl MarginConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_symbol(mut self, venue: &str, symbol: &str, settings: MarginSettings) -> Self {
+        self.venues.entry(venue.to_string()).or_default().insert(symbol.to_string(), settings);
+        self
+    }
+
+    /// Symbols configured on `venue` with their settings
+    pub fn symbols(&self, venue: &str) -> impl Iterator<Item = (&String, &MarginSettings)> {
+        self.venues.get(venue).into_iter().flatten()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.venues.is_empty()
+    }
+
+    /// Comma separated `VENUE:SYMBOL=<leverage>x:<isolated|cross>` entries,
+    /// e.g. `BINANCE_FUTURES:BTCUSDT=5x:isolated,BINANCE_FUTURES:ETHUSDT=3x:cross`
+    fn parse(spec: &str) -> Self {
+        let mut config = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=').and_then(|(target, settings)| {
+                let (venue, symbol) = target.split_once(':')?;
+                let (venue, symbol) = (venue.trim(), symbol.trim());
+                (!venue.is_empty() && !symbol.is_empty()).then_some((venue, symbol, MarginSettings::parse(settings)?))
+            });
+            match parsed {
+                Some((venue, symbol, settings)) => config = config.with_symbol(venue, symbol, settings),
+                None => warn!(entry = entry, "Ignoring malformed margin setting"),
+            }
+        }
+        config
+    }
+
+    /// Read `HFT_MARGIN` (see [`parse`](Self::parse))
+    pub fn from_env() -> Option<Self> {
+        Some(Self::parse(&std::env::var("HFT_MARGIN").ok()?))
+    }
+}
+
+/// Set `symbol`'s margin mode and leverage on `venue`, then read them back;
+/// fails if either could not be set or the venue reports other settings
+pub async fn apply(venue: &dyn VenueAdapter, symbol: &str, wanted: MarginSettings) -> Result<(), HftError> {
+    let current = venue.margin_settings(symbol).await?;
+    if current == wanted {
+        return Ok(());
+    }
+    // Venues refuse to set the margin mode a symbol already has
+    if current.mode != wanted.mode {
+        venue.set_margin_mode(symbol, wanted.mode).await?;
+    }
+    if current.leverage != wanted.leverage {
+        venue.set_leverage(symbol, wanted.leverage).await?;
+    }
+
+    let applied = venue.margin_settings(symbol).await?;
+    if applied != wanted {
+        return Err(VenueError::OrderSubmissionFailed(format!(
+            "{} margin is {} after setting {}", symbol, applied, wanted
+        )).into());
+    }
+    info!(venue = %venue.name().await, symbol = symbol, from = %current, to = %wanted, "Margin settings changed");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::mock_venue::{MockVenue, MockVenueConfig};
+
+    #[test]
+    fn test_parse() {
+        let config = MarginConfig::parse("BINANCE_FUTURES:BTCUSDT=5x:isolated, BINANCE_FUTURES:ETHUSDT=3:cross, X:Y=0x:cross, BAD=1x:cross");
+        let symbols: Vec<_> = config.symbols("BINANCE_FUTURES").map(|(s, m)| (s.as_str(), m.to_string())).collect();
+        assert_eq!(symbols, vec![("BTCUSDT", "5x isolated".to_string()), ("ETHUSDT", "3x cross".to_string())]);
+        assert_eq!(config.symbols("X").count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_apply_sets_and_verifies() {
+        let venue = MockVenue::new("MARGIN", MockVenueConfig::default());
+        let wanted = MarginSettings { leverage: 5, mode: MarginMode::Isolated };
+        apply(&venue, "BTCUSDT", wanted).await.unwrap();
+        assert_eq!(venue.margin_settings("BTCUSDT").await.unwrap(), wanted);
+
+        // A venue capping leverage fails verification
+        venue.cap_leverage(3).await;
+        let err = apply(&venue, "BTCUSDT", MarginSettings { leverage: 10, mode: MarginMode::Isolated }).await.unwrap_err();
+        assert!(err.to_string().contains("3x isolated"));
+    }
+}
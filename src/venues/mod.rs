@@ -1,21 +1,142 @@
+use std::time::Duration;
 use async_trait::async_trait;
-use crate::types::{Order, Quote};
-use crate::error::HftError;
+use crate::types::{Candle, Fill, Order};
+use crate::error::{HftError, VenueError};
 
+pub mod account;
 pub mod binance;
+pub mod binance_ws;
+pub mod capabilities;
+pub mod frames;
+pub mod inbound;
+pub mod latency;
+pub mod margin;
+pub mod reconnect;
+pub mod region;
+pub mod registry;
+pub mod transport;
+pub use account::{AssetBalance, Transfer, TransferKind};
 pub use binance::BinanceVenue;
+pub use capabilities::{InstrumentRules, VenueCapabilities};
+pub use frames::{FrameRecorder, FrameRecordingConfig, RecordedFrame};
+pub use latency::{Delay, LatencyInjection, VenueLatency};
+pub use margin::{MarginConfig, MarginMode, MarginSettings, PositionRisk};
+pub use reconnect::{ReconnectPolicies, ReconnectPolicy, Reconnector};
+pub use region::Regions;
+pub use registry::VenueRegistry;
+pub use transport::{ProxyConfig, VenueTransport, VenueTransports};
 
 #[async_trait]
 pub trait VenueAdapter: Send + Sync {
     /// Get the venue name
     async fn name(&self) -> String;
 
+    /// What the venue supports, for components to adapt to
+    fn capabilities(&self) -> VenueCapabilities {
+        VenueCapabilities::default()
+    }
+
     /// Subscribe to quotes for the given symbols
     async fn subscribe_quotes(&self, symbols: Vec<String>) -> Result<(), HftError>;
 
+    /// Stop streaming quotes for the given symbols, leaving the rest
+    /// subscribed
+    async fn unsubscribe_quotes(&self, symbols: Vec<String>) -> Result<(), HftError> {
+        let _ = symbols;
+        Err(VenueError::NotSupported("unsubscribe_quotes".to_string()).into())
+    }
+
     /// Submit an order to the venue
     async fn submit_order(&self, order: Order) -> Result<String, HftError>;
     
+    /// Cancel a single open order
+    async fn cancel_order(&self, order_id: &str, symbol: &str) -> Result<(), HftError> {
+        let _ = (order_id, symbol);
+        Err(VenueError::NotSupported("cancel_order".to_string()).into())
+    }
+
+    /// Change the price and quantity of an open order in place, returning
+    /// the venue order ID it is known by afterwards. `order` carries the
+    /// new price and quantity.
+    async fn amend_order(&self, order_id: &str, order: &Order) -> Result<String, HftError> {
+        let _ = (order_id, order);
+        Err(VenueError::NotSupported("amend_order".to_string()).into())
+    }
+
+    /// Cancel every open order on the venue
+    async fn cancel_all_orders(&self) -> Result<(), HftError> {
+        Err(VenueError::NotSupported("cancel_all_orders".to_string()).into())
+    }
+
+    /// Venue order IDs of all orders currently open on the venue
+    async fn open_orders(&self) -> Result<Vec<String>, HftError> {
+        Err(VenueError::NotSupported("open_orders".to_string()).into())
+    }
+
+    /// Our executions in `symbol` between `since` and `until`, in
+    /// milliseconds since the Unix epoch, oldest first
+    async fn trade_history(&self, symbol: &str, since: u64, until: u64) -> Result<Vec<Fill>, HftError> {
+        let _ = (symbol, since, until);
+        Err(VenueError::NotSupported("trade_history".to_string()).into())
+    }
+
+    /// The latest `limit` candles of `interval` for `symbol`, oldest first
+    async fn fetch_candles(&self, symbol: &str, interval: Duration, limit: usize) -> Result<Vec<Candle>, HftError> {
+        let _ = (symbol, interval, limit);
+        Err(VenueError::NotSupported("fetch_candles".to_string()).into())
+    }
+
+    /// The venue's clock, in milliseconds since the Unix epoch
+    async fn server_time(&self) -> Result<u64, HftError> {
+        Err(VenueError::NotSupported("server_time".to_string()).into())
+    }
+
+    /// Make an authenticated read-only request, failing if the API keys are
+    /// rejected
+    async fn verify_credentials(&self) -> Result<(), HftError> {
+        Err(VenueError::NotSupported("verify_credentials".to_string()).into())
+    }
+
+    /// Symbols currently open for trading, as the venue names them
+    async fn tradable_symbols(&self) -> Result<Vec<String>, HftError> {
+        Err(VenueError::NotSupported("tradable_symbols".to_string()).into())
+    }
+
+    /// Set the leverage of new and open positions in `symbol`
+    async fn set_leverage(&self, symbol: &str, leverage: u32) -> Result<(), HftError> {
+        let _ = (symbol, leverage);
+        Err(VenueError::NotSupported("set_leverage".to_string()).into())
+    }
+
+    /// Switch `symbol` between isolated and cross margin
+    async fn set_margin_mode(&self, symbol: &str, mode: MarginMode) -> Result<(), HftError> {
+        let _ = (symbol, mode);
+        Err(VenueError::NotSupported("set_margin_mode".to_string()).into())
+    }
+
+    /// The leverage and margin mode `symbol` currently has on the venue
+    async fn margin_settings(&self, symbol: &str) -> Result<MarginSettings, HftError> {
+        let _ = symbol;
+        Err(VenueError::NotSupported("margin_settings".to_string()).into())
+    }
+
+    /// Open positions with their mark and liquidation prices
+    async fn position_risks(&self) -> Result<Vec<PositionRisk>, HftError> {
+        Err(VenueError::NotSupported("position_risks".to_string()).into())
+    }
+
+    /// Account balances per asset
+    async fn balances(&self) -> Result<Vec<AssetBalance>, HftError> {
+        Err(VenueError::NotSupported("balances".to_string()).into())
+    }
+
+    /// Deposits, withdrawals, funding and other balance changes outside of
+    /// trading since `since`, in milliseconds since the Unix epoch
+    async fn transfers(&self, since: u64) -> Result<Vec<Transfer>, HftError> {
+        let _ = since;
+        Err(VenueError::NotSupported("transfers".to_string()).into())
+    }
+
     /// Stop any background tasks or connections
     async fn stop(&self) -> Result<(), HftError> {
         // Default implementation does nothing
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::future::Future;
+use tokio::time::{Duration, Instant};
+use tracing::{error, warn};
+
+use crate::error::{HftError, VenueError};
+use crate::metrics::VENUE_RECONNECTS;
+
+/// How a venue adapter retries a failed or dropped connection: exponential
+/// backoff with jitter, up to `max_attempts` in a row. A connection that
+/// stays up for `stable_after` resets the count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    /// Fraction of each backoff randomized either way, so venues do not
+    /// reconnect in lockstep
+    pub jitter: f64,
+    /// Attempts in a row before giving up, including the first connect
+    pub max_attempts: u32,
+    pub stable_after: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: 5,
+            stable_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_stable_after(mut self, stable_after: Duration) -> Self {
+        self.stable_after = stable_after;
+        self
+    }
+
+    /// `ATTEMPTS:INITIAL_MS:MAX_MS:STABLE_MS`, trailing fields optional
+    fn parse(spec: &str) -> Option<Self> {
+        let mut fields = spec.split(':').map(str::trim);
+        let mut policy = Self::default().with_max_attempts(fields.next()?.parse().ok()?);
+        let mut millis = || -> Option<Option<Duration>> {
+            match fields.next() {
+                Some(ms) => Some(Some(Duration::from_millis(ms.parse().ok()?))),
+                None => Some(None),
+            }
+        };
+        if let Some(initial) = millis()? {
+            policy.initial_backoff = initial;
+        }
+        if let Some(max) = millis()? {
+            policy.max_backoff = max;
+        }
+        if let Some(stable) = millis()? {
+            policy.stable_after = stable;
+        }
+        (policy.max_attempts > 0 && policy.initial_backoff <= policy.max_backoff).then_some(policy)
+    }
+
+    /// Delay before retry number `retry` (from 1), with jitter
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let base = self.initial_backoff.as_secs_f64() * self.multiplier.powi(retry.saturating_sub(1) as i32);
+        let base = base.min(self.max_backoff.as_secs_f64());
+        let spread = if self.jitter > 0.0 {
+            rand::random_range(-self.jitter..=self.jitter)
+        } else {
+            0.0
+        };
+        Duration::from_secs_f64((base * (1.0 + spread)).max(0.0))
+    }
+}
+
+/// Reconnect policies by venue name
+#[derive(Debug, Clone, Default)]
+pub struct ReconnectPolicies {
+    default: ReconnectPolicy,
+    venues: HashMap<String, ReconnectPolicy>,
+}
+
+impl ReconnectPolicies {
+    /// Read `HFT_VENUE_RECONNECT` as comma separated `VENUE=POLICY` entries,
+    /// with `*` for the default and `POLICY` as
+    /// `ATTEMPTS:INITIAL_MS:MAX_MS:STABLE_MS`, e.g.
+    /// `*=5:500:30000,BINANCE_FUTURES=10:250:60000:120000`
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var("HFT_VENUE_RECONNECT").ok()?;
+        Some(Self::parse(&spec))
+    }
+
+    fn parse(spec: &str) -> Self {
+        let mut policies = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once('=').and_then(|(venue, policy)| Some((venue.trim(), ReconnectPolicy::parse(policy)?))) {
+                Some(("*", policy)) => policies.default = policy,
+                Some((venue, policy)) => {
+                    policies.venues.insert(venue.to_uppercase(), policy);
+                }
+                None => warn!(entry = entry, "Ignoring malformed reconnect policy"),
+            }
+        }
+        policies
+    }
+
+    pub fn for_venue(&self, venue: &str) -> &ReconnectPolicy {
+        self.venues.get(venue).unwrap_or(&self.default)
+    }
+}
+
+/// Applies a [`ReconnectPolicy`] across the life of one connection,
+/// counting retries in `hft_venue_reconnects_total`
+#[derive(Debug, Clone)]
+pub struct Reconnector {
+    policy: ReconnectPolicy,
+    venue: String,
+    /// Connection attempts since the connection was last stable
+    attempts: u32,
+    connected_at: Option<Instant>,
+}
+
+impl Reconnector {
+    pub fn new(venue: &str, policy: ReconnectPolicy) -> Self {
+        Self { policy, venue: venue.to_string(), attempts: 0, connected_at: None }
+    }
+
+    /// Call `connect` until it succeeds or the policy gives up, backing off
+    /// between attempts. Connections that drop before they are stable count
+    /// as attempts, so a flapping connection is given up on too.
+    pub async fn connect<T, F, Fut>(&mut self, mut connect: F) -> Result<T, HftError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, HftError>>,
+    {
+        if self.connected_at.take().is_some_and(|at| at.elapsed() >= self.policy.stable_after) {
+            self.attempts = 0;
+        }
+
+        let mut last_error = None;
+        loop {
+            if self.attempts >= self.policy.max_attempts {
+                error!(venue = %self.venue, attempts = self.attempts, "Giving up connecting");
+                return Err(last_error.unwrap_or_else(|| VenueError::ConnectionFailed(
+                    format!("Connection dropped {} times without stabilizing", self.attempts)
+                ).into()));
+            }
+            if self.attempts > 0 {
+                let delay = self.policy.backoff(self.attempts);
+                warn!(venue = %self.venue, attempt = self.attempts + 1, delay = ?delay, "Reconnecting");
+                VENUE_RECONNECTS.with_label_values(&[&self.venue]).inc();
+                tokio::time::sleep(delay).await;
+            }
+
+            self.attempts += 1;
+            match connect().await {
+                Ok(connection) => {
+                    self.connected_at = Some(Instant::now());
+                    return Ok(connection);
+                }
+                Err(e) => {
+                    warn!(venue = %self.venue, attempt = self.attempts, error = ?e, "Connection attempt failed");
+                    last_error = Some(e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_and_parse() {
+        let policy = ReconnectPolicy::default()
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(350))
+            .with_jitter(0.0);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+
+        let jittered = policy.clone().with_jitter(0.5);
+        assert!((50..=150).contains(&jittered.backoff(1).as_millis()));
+
+        let policies = ReconnectPolicies::parse("*=3:100:1000, binance_futures=10:250:60000:5000, KRAKEN=0, bogus");
+        assert_eq!(policies.for_venue("OTHER").max_attempts, 3);
+        let binance = policies.for_venue("BINANCE_FUTURES");
+        assert_eq!((binance.max_attempts, binance.initial_backoff, binance.stable_after), (10, Duration::from_millis(250), Duration::from_secs(5)));
+        assert_eq!(policies.for_venue("KRAKEN").max_attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_reconnector_gives_up_unless_stable() {
+        let policy = ReconnectPolicy::default()
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(5))
+            .with_max_attempts(3)
+            .with_stable_after(Duration::from_secs(10));
+
+        let mut reconnector = Reconnector::new("RECONNECT_TEST", policy.clone());
+        let mut calls = 0;
+        let result: Result<(), HftError> = reconnector.connect(|| {
+            calls += 1;
+            async { Err(VenueError::ConnectionFailed("refused".to_string()).into()) }
+        }).await;
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+        assert_eq!(VENUE_RECONNECTS.with_label_values(&["RECONNECT_TEST"]).get(), 2.0);
+
+        // Drops right after connecting use up the attempts too
+        let mut flapping = Reconnector::new("RECONNECT_FLAP", policy);
+        for _ in 0..3 {
+            flapping.connect(|| async { Ok(()) }).await.unwrap();
+        }
+        assert!(flapping.connect(|| async { Ok(()) }).await.is_err());
+
+        // A stable connection starts over with the full budget
+        flapping.connected_at = Instant::now().checked_sub(Duration::from_secs(11));
+        flapping.connect(|| async { Ok(()) }).await.unwrap();
+        assert_eq!(flapping.attempts, 1);
+    }
+}
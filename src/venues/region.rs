@@ -0,0 +1,109 @@
+//! Where the engine and each venue run, for deployments spanning regions.
+//!
+//! The regions are exported as info gauges, to be joined onto other series
+//! by venue rather than added as a label to each of them, and orders
+//! failing over to another venue go to one in the engine's own region when
+//! the candidates cost the same.
+
+use std::collections::HashMap;
+use tracing::warn;
+
+use crate::metrics::{ENGINE_REGION, VENUE_REGION};
+
+/// Region or colocation site of the engine and of each venue, as free-form
+/// names such as `ap-northeast-1` or `LD4`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Regions {
+    local: Option<String>,
+    venues: HashMap<String, String>,
+}
+
+impl Regions {
+    /// The region this engine runs in
+    pub fn with_local(mut self, region: &str) -> Self {
+        self.local = Some(region.to_string());
+        self
+    }
+
+    pub fn with_venue(mut self, venue: &str, region: &str) -> Self {
+        self.venues.insert(venue.to_uppercase(), region.to_string());
+        self
+    }
+
+    /// Comma separated `VENUE=REGION` entries, e.g.
+    /// `BINANCE_FUTURES=ap-northeast-1,BITSTAMP=eu-west-1`
+    fn parse(spec: &str) -> Self {
+        let mut regions = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once('=').map(|(venue, region)| (venue.trim(), region.trim())) {
+                Some((venue, region)) if !venue.is_empty() && !region.is_empty() => regions = regions.with_venue(venue, region),
+                _ => warn!(entry = entry, "Ignoring malformed venue region"),
+            }
+        }
+        regions
+    }
+
+    /// Read the engine's region from `HFT_REGION` and the venues' from
+    /// `HFT_VENUE_REGIONS` (see [`parse`](Self::parse)); returns `None`
+    /// when neither is set
+    pub fn from_env() -> Option<Self> {
+        let local = std::env::var("HFT_REGION").ok().filter(|r| !r.trim().is_empty());
+        let venues = std::env::var("HFT_VENUE_REGIONS").ok();
+        if local.is_none() && venues.is_none() {
+            return None;
+        }
+        let regions = Self::parse(venues.as_deref().unwrap_or(""));
+        Some(match local {
+            Some(local) => regions.with_local(local.trim()),
+            None => regions,
+        })
+    }
+
+    pub fn local(&self) -> Option<&str> {
+        self.local.as_deref()
+    }
+
+    pub fn region(&self, venue: &str) -> Option<&str> {
+        self.venues.get(&venue.to_uppercase()).map(String::as_str)
+    }
+
+    /// Whether `venue` is known to run in the engine's region
+    pub fn is_local(&self, venue: &str) -> bool {
+        self.local.is_some() && self.local() == self.region(venue)
+    }
+
+    /// Move venues in the engine's region to the front, keeping the order
+    /// otherwise, so a first-listed tie-break picks them
+    pub fn prefer_local(&self, venues: &mut [String]) {
+        venues.sort_by_key(|venue| !self.is_local(venue));
+    }
+
+    /// Export the engine's region and `venue`'s, when known
+    pub fn publish(&self, venue: &str) {
+        if let Some(local) = self.local() {
+            ENGINE_REGION.with_label_values(&[local]).set(1.0);
+        }
+        if let Some(region) = self.region(venue) {
+            VENUE_REGION.with_label_values(&[venue, region]).set(1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_prefer_local() {
+        let regions = Regions::parse("binance_futures=ap-northeast-1, BITSTAMP=eu-west-1, KRAKEN=eu-west-1, bad, =x")
+            .with_local("eu-west-1");
+        assert_eq!(regions.region("BINANCE_FUTURES"), Some("ap-northeast-1"));
+        assert!(regions.is_local("KRAKEN"));
+        assert!(!regions.is_local("OTHER"));
+        assert!(!Regions::default().is_local("OTHER"));
+
+        let mut venues = vec!["BINANCE_FUTURES".to_string(), "OTHER".to_string(), "KRAKEN".to_string(), "BITSTAMP".to_string()];
+        regions.prefer_local(&mut venues);
+        assert_eq!(venues, ["KRAKEN", "BITSTAMP", "BINANCE_FUTURES", "OTHER"]);
+    }
+}
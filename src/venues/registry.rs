@@ -0,0 +1,70 @@
+use std::sync::{Arc, RwLock};
+
+use super::VenueAdapter;
+
+/// A registered venue under the name it reported when added
+type Entry = (String, Arc<dyn VenueAdapter>);
+
+/// The venues the engine trades on, shared by the quote and order gateways
+/// so a venue added at runtime can be both subscribed to and routed to.
+///
+/// Clones share the same set. Names are cached on registration so lookups
+/// on the order path do not await the adapter.
+#[derive(Clone, Default)]
+pub struct VenueRegistry {
+    venues: Arc<RwLock<Vec<Entry>>>,
+}
+
+impl VenueRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn from_venues(venues: Vec<Arc<dyn VenueAdapter>>) -> Self {
+        let registry = Self::new();
+        for venue in venues {
+            registry.add(venue).await;
+        }
+        registry
+    }
+
+    /// Register `venue`, returning any venue it replaced under the same name
+    pub async fn add(&self, venue: Arc<dyn VenueAdapter>) -> Option<Arc<dyn VenueAdapter>> {
+        let name = venue.name().await;
+        let mut venues = self.venues.write().unwrap();
+        match venues.iter_mut().find(|(n, _)| *n == name) {
+            Some(entry) => Some(std::mem::replace(&mut entry.1, venue)),
+            None => {
+                venues.push((name, venue));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&self, name: &str) -> Option<Arc<dyn VenueAdapter>> {
+        let mut venues = self.venues.write().unwrap();
+        let index = venues.iter().position(|(n, _)| n == name)?;
+        Some(venues.remove(index).1)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn VenueAdapter>> {
+        self.venues.read().unwrap().iter().find(|(n, _)| n == name).map(|(_, v)| Arc::clone(v))
+    }
+
+    /// Every registered venue, in registration order
+    pub fn all(&self) -> Vec<Arc<dyn VenueAdapter>> {
+        self.venues.read().unwrap().iter().map(|(_, v)| Arc::clone(v)).collect()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.venues.read().unwrap().iter().map(|(n, _)| n.clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.venues.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}